@@ -1,4 +1,5 @@
 use async_trait::async_trait;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -39,6 +40,9 @@ pub struct AgentContext {
     /// User input/query
     pub input: String,
 
+    /// Images and other content sent along with `input`
+    pub input_parts: Vec<crate::ContentPart>,
+
     /// Conversation history
     pub messages: Vec<Message>,
 
@@ -50,6 +54,16 @@ pub struct AgentContext {
 
     /// Execution metadata
     pub metadata: ExecutionMetadata,
+
+    /// Agent-to-agent delegations leading to this run (0 for a direct run)
+    pub delegation_depth: usize,
+
+    /// Headers sent with this run's model requests, over `ModelConfig.headers`
+    pub request_headers: HashMap<String, String>,
+
+    /// Conversation session (e.g. a user or channel id) whose history the
+    /// run continues; the executor's own session when unset
+    pub session_id: Option<String>,
 }
 
 /// Message in conversation history
@@ -59,6 +73,12 @@ pub struct Message {
     pub content: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_calls: Option<Vec<crate::ToolCall>>,
+    /// Id of the tool call a `Tool` message answers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+    /// Content following `content`, e.g. images for vision models
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parts: Vec<crate::ContentPart>,
 }
 
 /// Message role
@@ -82,7 +102,7 @@ pub struct ToolResult {
 }
 
 /// Execution metadata
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ExecutionMetadata {
     /// Tokens used (input)
     pub input_tokens: usize,
@@ -94,6 +114,12 @@ pub struct ExecutionMetadata {
     pub tool_calls: usize,
     /// Model used
     pub model: Option<String>,
+    /// Estimated cost (USD), when the model has a known price
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+    /// Trace id of the run, shared by its log lines
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_id: Option<String>,
 }
 
 impl AgentContext {
@@ -101,22 +127,69 @@ impl AgentContext {
     pub fn new(input: impl Into<String>) -> Self {
         Self {
             input: input.into(),
+            input_parts: Vec::new(),
             messages: Vec::new(),
             state: HashMap::new(),
             tool_results: Vec::new(),
             metadata: ExecutionMetadata::default(),
+            delegation_depth: 0,
+            request_headers: HashMap::new(),
+            session_id: None,
         }
     }
 
+    /// Send `part` (e.g. an image) to the model along with the input
+    pub fn with_input_part(mut self, part: crate::ContentPart) -> Self {
+        self.input_parts.push(part);
+        self
+    }
+
+    /// Send header `name: value` with this run's model requests
+    pub fn with_request_header(
+        mut self,
+        name: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        self.request_headers.insert(name.into(), value.into());
+        self
+    }
+
+    /// Continue the conversation of session `session_id`
+    pub fn with_session_id(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self
+    }
+
     /// Add a message to history
     pub fn add_message(&mut self, role: MessageRole, content: impl Into<String>) {
+        self.add_message_with_parts(role, content, vec![]);
+    }
+
+    /// Add a message with further content parts to history
+    pub fn add_message_with_parts(
+        &mut self,
+        role: MessageRole,
+        content: impl Into<String>,
+        parts: Vec<crate::ContentPart>,
+    ) {
         self.messages.push(Message {
             role,
             content: content.into(),
             tool_calls: None,
+            tool_call_id: None,
+            parts,
         });
     }
 
+    /// Add a user message showing an image to history
+    pub fn add_image_message(&mut self, text: impl Into<String>, image: crate::ImageUrl) {
+        self.add_message_with_parts(
+            MessageRole::User,
+            text,
+            vec![crate::ContentPart::ImageUrl { image_url: image }],
+        );
+    }
+
     /// Get state value
     pub fn get_state<T: serde::de::DeserializeOwned>(&self, key: &str) -> Option<T> {
         self.state
@@ -130,6 +203,52 @@ impl AgentContext {
         self.state.insert(key.into(), json_value);
         Ok(())
     }
+
+    /// Capture the conversation so it can be resumed later, possibly in another process
+    pub fn to_snapshot(&self) -> ContextSnapshot {
+        ContextSnapshot {
+            messages: self.messages.clone(),
+            state: self.state.clone(),
+            tool_results: self.tool_results.clone(),
+            metadata: self.metadata.clone(),
+        }
+    }
+
+    /// Resume a conversation from a snapshot
+    ///
+    /// The input is left empty; set it to the next user query before executing.
+    pub fn from_snapshot(snapshot: ContextSnapshot) -> Self {
+        Self {
+            input: String::new(),
+            input_parts: Vec::new(),
+            messages: snapshot.messages,
+            state: snapshot.state,
+            tool_results: snapshot.tool_results,
+            metadata: snapshot.metadata,
+            delegation_depth: 0,
+            request_headers: HashMap::new(),
+            session_id: None,
+        }
+    }
+}
+
+/// Portable, serializable copy of an agent conversation
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContextSnapshot {
+    /// Conversation history
+    pub messages: Vec<Message>,
+
+    /// Session state/variables
+    #[serde(default)]
+    pub state: HashMap<String, serde_json::Value>,
+
+    /// Tool execution results
+    #[serde(default)]
+    pub tool_results: Vec<ToolResult>,
+
+    /// Accumulated execution metadata (token counts carry across resumes)
+    #[serde(default)]
+    pub metadata: ExecutionMetadata,
 }
 
 /// Agent metadata
@@ -154,7 +273,7 @@ pub struct AgentMetadata {
 
 /// Agent configuration
 /// Supports both flat format and Kubernetes-style format
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(from = "AgentConfigInput")]
 pub struct AgentConfig {
     /// Agent name
@@ -179,6 +298,16 @@ pub struct AgentConfig {
     #[serde(default = "default_max_iterations")]
     pub max_iterations: usize,
 
+    /// Iteration at which a warning is raised that the run is nearing
+    /// `max_iterations` (e.g. 8 of 10); execution carries on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warn_iteration_threshold: Option<usize>,
+
+    /// Total tool calls allowed per run; the run stops with the content
+    /// produced so far once the model asks for more
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tool_calls: Option<usize>,
+
     /// Temperature (0.0-1.0)
     #[serde(default = "default_temperature")]
     pub temperature: f32,
@@ -187,24 +316,149 @@ pub struct AgentConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_tokens: Option<usize>,
 
+    /// Context window of the model in tokens, when it differs from the
+    /// size known for the model (see `context_window_for_model`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_window: Option<usize>,
+
+    /// Sequences that end generation when the model produces them, e.g.
+    /// a delimiter between parts of a structured answer
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stop_sequences: Vec<String>,
+
+    /// Sampling seed for reproducible runs, on providers that support it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+
+    /// JSON schema the final response must match (structured output)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_schema: Option<serde_json::Value>,
+
+    /// Cancel the rest of a parallel tool batch once one tool fails
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub fail_fast: bool,
+
+    /// Concurrency permits a tool holds while it runs (default 1), so
+    /// expensive tools limit the total load of a parallel batch
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub tool_weights: HashMap<String, u32>,
+
+    /// Read-only tools whose repeated calls with the same arguments share
+    /// one execution
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cacheable_tools: Vec<String>,
+
+    /// Retries of model calls failing with transient errors
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_retry_policy: Option<ModelRetryPolicy>,
+
+    /// How long Ollama keeps the model loaded after each request
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_alive: Option<KeepAlive>,
+
+    /// Load an Ollama model when the agent is loaded rather than on its
+    /// first request
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub preload: bool,
+
+    /// What to do when the model rejects the conversation as too long
+    #[serde(default, skip_serializing_if = "ContextOverflowPolicy::is_default")]
+    pub on_context_overflow: ContextOverflowPolicy,
+
+    /// What to do when the model ends its turn without content or tool calls
+    #[serde(default, skip_serializing_if = "EmptyResponsePolicy::is_default")]
+    pub on_empty_response: EmptyResponsePolicy,
+
+    /// Fail to load when an MCP server can't be reached, instead of loading
+    /// without its tools
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub require_all_tools: bool,
+
+    /// Answer requests identical to a recent one from a response cache
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_cache: Option<FeatureToggle<PromptCacheConfig>>,
+
+    /// Let the agent hand sub-tasks to other agents of its runtime with the
+    /// `delegate_to_agent` tool
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delegation: Option<FeatureToggle<DelegationConfig>>,
+
     /// Custom configuration
     #[serde(flatten)]
     pub extra: HashMap<String, serde_json::Value>,
 }
 
+impl Default for AgentConfig {
+    /// Config with no name or model and every other field at its YAML default
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            system_prompt: None,
+            model: String::new(),
+            tools: Vec::new(),
+            memory: None,
+            max_iterations: default_max_iterations(),
+            warn_iteration_threshold: None,
+            max_tool_calls: None,
+            temperature: default_temperature(),
+            max_tokens: None,
+            context_window: None,
+            stop_sequences: Vec::new(),
+            seed: None,
+            response_schema: None,
+            fail_fast: false,
+            tool_weights: HashMap::new(),
+            cacheable_tools: Vec::new(),
+            model_retry_policy: None,
+            keep_alive: None,
+            preload: false,
+            on_context_overflow: ContextOverflowPolicy::default(),
+            on_empty_response: EmptyResponsePolicy::default(),
+            require_all_tools: false,
+            prompt_cache: None,
+            delegation: None,
+            extra: HashMap::new(),
+        }
+    }
+}
+
+impl AgentConfig {
+    /// Context window of the agent's model in tokens: `context_window` if
+    /// set, otherwise the size known for `model`
+    pub fn context_window(&self) -> usize {
+        self.context_window
+            .unwrap_or_else(|| crate::context_window_for_model(&self.model))
+    }
+
+    /// Parse an agent config from YAML, expanding `${VAR}` and
+    /// `${VAR:-default}` references from the environment first
+    pub fn from_yaml(yaml: &str) -> AofResult<Self> {
+        let yaml = crate::interpolate_env(yaml)?;
+        Ok(serde_yaml::from_str(&yaml)?)
+    }
+
+    /// JSON Schema for agent config files, for editor validation and
+    /// autocompletion (e.g. YAML language server)
+    pub fn json_schema() -> serde_json::Value {
+        schemars::schema_for!(AgentConfig).to_value()
+    }
+}
+
 /// Internal type for flexible config parsing
 /// Supports both flat format and Kubernetes-style format
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 #[serde(untagged)]
 enum AgentConfigInput {
     /// Flat format (original) - try this first since it has required fields
+    #[schemars(description = "Flat format with every field at the top level")]
     Flat(FlatAgentConfig),
     /// Kubernetes-style format with apiVersion, kind, metadata, spec
     Kubernetes(KubernetesConfig),
 }
 
 /// Kubernetes-style config wrapper
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[schemars(rename = "KubernetesAgentConfig")]
 struct KubernetesConfig {
     #[serde(rename = "apiVersion")]
     api_version: String,  // Required for K8s format
@@ -213,8 +467,10 @@ struct KubernetesConfig {
     spec: AgentSpec,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 struct KubernetesMetadata {
+    /// Agent name
+    #[schemars(length(min = 1))]
     name: String,
     #[serde(default)]
     labels: HashMap<String, String>,
@@ -222,37 +478,147 @@ struct KubernetesMetadata {
     annotations: HashMap<String, String>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 struct AgentSpec {
+    /// Model to use (e.g. `claude-3-5-sonnet-20241022` or `ollama:llama3`)
+    #[schemars(length(min = 1))]
     model: String,
+    /// System prompt (also accepts "system_prompt")
     #[serde(alias = "system_prompt")]
     instructions: Option<String>,
+    /// Tools available to the agent
     #[serde(default)]
     tools: Vec<String>,
+    /// Memory backend
     memory: Option<String>,
+    /// Max iterations
     #[serde(default = "default_max_iterations")]
+    #[schemars(range(min = 1))]
     max_iterations: usize,
+    /// Iteration at which to warn that the run nears `max_iterations`
+    #[schemars(range(min = 1))]
+    warn_iteration_threshold: Option<usize>,
+    /// Total tool calls allowed per run
+    max_tool_calls: Option<usize>,
+    /// Sampling temperature
     #[serde(default = "default_temperature")]
+    #[schemars(range(min = 0.0, max = 2.0), extend("default" = 0.7))]
     temperature: f32,
+    /// Max tokens per response
     max_tokens: Option<usize>,
+    /// Context window of the model in tokens
+    #[schemars(range(min = 1))]
+    context_window: Option<usize>,
+    /// Sequences that end generation when the model produces them
+    #[serde(default)]
+    stop_sequences: Vec<String>,
+    /// Sampling seed for reproducible runs
+    seed: Option<u64>,
+    /// JSON schema the final response must match
+    response_schema: Option<serde_json::Value>,
+    /// Cancel the rest of a parallel tool batch once one tool fails
+    #[serde(default)]
+    fail_fast: bool,
+    /// Concurrency permits each tool holds while it runs (default 1)
+    #[serde(default)]
+    tool_weights: HashMap<String, u32>,
+    /// Read-only tools whose identical calls share one execution
+    #[serde(default)]
+    cacheable_tools: Vec<String>,
+    /// Retries of model calls failing with transient errors
+    model_retry_policy: Option<ModelRetryPolicy>,
+    /// How long Ollama keeps the model loaded after each request
+    keep_alive: Option<KeepAlive>,
+    /// Load an Ollama model when the agent is loaded
+    #[serde(default)]
+    preload: bool,
+    /// What to do when the model rejects the conversation as too long
+    #[serde(default)]
+    on_context_overflow: ContextOverflowPolicy,
+    /// What to do when the model ends its turn without content or tool calls
+    #[serde(default)]
+    on_empty_response: EmptyResponsePolicy,
+    /// Fail to load when an MCP server can't be reached
+    #[serde(default)]
+    require_all_tools: bool,
+    /// Response cache for repeated identical requests (`true` or settings)
+    prompt_cache: Option<FeatureToggle<PromptCacheConfig>>,
+    /// Delegation to other agents (`true` or settings)
+    delegation: Option<FeatureToggle<DelegationConfig>>,
     #[serde(flatten)]
     extra: HashMap<String, serde_json::Value>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 struct FlatAgentConfig {
+    /// Agent name
+    #[schemars(length(min = 1))]
     name: String,
+    /// System prompt (also accepts "instructions")
     #[serde(alias = "instructions")]
     system_prompt: Option<String>,
+    /// Model to use (e.g. `claude-3-5-sonnet-20241022` or `ollama:llama3`)
+    #[schemars(length(min = 1))]
     model: String,
+    /// Tools available to the agent
     #[serde(default)]
     tools: Vec<String>,
+    /// Memory backend
     memory: Option<String>,
+    /// Max iterations
     #[serde(default = "default_max_iterations")]
+    #[schemars(range(min = 1))]
     max_iterations: usize,
+    /// Iteration at which to warn that the run nears `max_iterations`
+    #[schemars(range(min = 1))]
+    warn_iteration_threshold: Option<usize>,
+    /// Total tool calls allowed per run
+    max_tool_calls: Option<usize>,
+    /// Sampling temperature
     #[serde(default = "default_temperature")]
+    #[schemars(range(min = 0.0, max = 2.0), extend("default" = 0.7))]
     temperature: f32,
+    /// Max tokens per response
     max_tokens: Option<usize>,
+    /// Context window of the model in tokens
+    #[schemars(range(min = 1))]
+    context_window: Option<usize>,
+    /// Sequences that end generation when the model produces them
+    #[serde(default)]
+    stop_sequences: Vec<String>,
+    /// Sampling seed for reproducible runs
+    seed: Option<u64>,
+    /// JSON schema the final response must match
+    response_schema: Option<serde_json::Value>,
+    /// Cancel the rest of a parallel tool batch once one tool fails
+    #[serde(default)]
+    fail_fast: bool,
+    /// Concurrency permits each tool holds while it runs (default 1)
+    #[serde(default)]
+    tool_weights: HashMap<String, u32>,
+    /// Read-only tools whose identical calls share one execution
+    #[serde(default)]
+    cacheable_tools: Vec<String>,
+    /// Retries of model calls failing with transient errors
+    model_retry_policy: Option<ModelRetryPolicy>,
+    /// How long Ollama keeps the model loaded after each request
+    keep_alive: Option<KeepAlive>,
+    /// Load an Ollama model when the agent is loaded
+    #[serde(default)]
+    preload: bool,
+    /// What to do when the model rejects the conversation as too long
+    #[serde(default)]
+    on_context_overflow: ContextOverflowPolicy,
+    /// What to do when the model ends its turn without content or tool calls
+    #[serde(default)]
+    on_empty_response: EmptyResponsePolicy,
+    /// Fail to load when an MCP server can't be reached
+    #[serde(default)]
+    require_all_tools: bool,
+    /// Response cache for repeated identical requests (`true` or settings)
+    prompt_cache: Option<FeatureToggle<PromptCacheConfig>>,
+    /// Delegation to other agents (`true` or settings)
+    delegation: Option<FeatureToggle<DelegationConfig>>,
     #[serde(flatten)]
     extra: HashMap<String, serde_json::Value>,
 }
@@ -267,8 +633,25 @@ impl From<AgentConfigInput> for AgentConfig {
                 tools: flat.tools,
                 memory: flat.memory,
                 max_iterations: flat.max_iterations,
+                warn_iteration_threshold: flat.warn_iteration_threshold,
+                max_tool_calls: flat.max_tool_calls,
                 temperature: flat.temperature,
                 max_tokens: flat.max_tokens,
+                context_window: flat.context_window,
+                stop_sequences: flat.stop_sequences,
+                seed: flat.seed,
+                response_schema: flat.response_schema,
+                fail_fast: flat.fail_fast,
+                tool_weights: flat.tool_weights,
+                cacheable_tools: flat.cacheable_tools,
+                model_retry_policy: flat.model_retry_policy,
+                keep_alive: flat.keep_alive,
+                preload: flat.preload,
+                on_context_overflow: flat.on_context_overflow,
+                on_empty_response: flat.on_empty_response,
+                require_all_tools: flat.require_all_tools,
+                prompt_cache: flat.prompt_cache,
+                delegation: flat.delegation,
                 extra: flat.extra,
             },
             AgentConfigInput::Kubernetes(k8s) => {
@@ -279,8 +662,25 @@ impl From<AgentConfigInput> for AgentConfig {
                     tools: k8s.spec.tools,
                     memory: k8s.spec.memory,
                     max_iterations: k8s.spec.max_iterations,
+                    warn_iteration_threshold: k8s.spec.warn_iteration_threshold,
+                    max_tool_calls: k8s.spec.max_tool_calls,
                     temperature: k8s.spec.temperature,
                     max_tokens: k8s.spec.max_tokens,
+                    context_window: k8s.spec.context_window,
+                    stop_sequences: k8s.spec.stop_sequences,
+                    seed: k8s.spec.seed,
+                    response_schema: k8s.spec.response_schema,
+                    fail_fast: k8s.spec.fail_fast,
+                    tool_weights: k8s.spec.tool_weights,
+                    cacheable_tools: k8s.spec.cacheable_tools,
+                    model_retry_policy: k8s.spec.model_retry_policy,
+                    keep_alive: k8s.spec.keep_alive,
+                    preload: k8s.spec.preload,
+                    on_context_overflow: k8s.spec.on_context_overflow,
+                    on_empty_response: k8s.spec.on_empty_response,
+                    require_all_tools: k8s.spec.require_all_tools,
+                    prompt_cache: k8s.spec.prompt_cache,
+                    delegation: k8s.spec.delegation,
                     extra: k8s.spec.extra,
                 }
             }
@@ -288,6 +688,146 @@ impl From<AgentConfigInput> for AgentConfig {
     }
 }
 
+/// Retry settings for model calls failing with transient errors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct ModelRetryPolicy {
+    /// Attempts in total, the first call included
+    #[schemars(range(min = 1))]
+    pub max_attempts: u32,
+
+    /// First retry delay when the error carries no `Retry-After`; doubles
+    /// with each further attempt
+    pub initial_backoff_ms: u64,
+}
+
+impl Default for ModelRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff_ms: 1000,
+        }
+    }
+}
+
+impl ModelRetryPolicy {
+    /// Delay before retry `attempt` (1-based) after `error`
+    pub fn backoff(&self, error: &crate::AofError, attempt: u32) -> std::time::Duration {
+        match error {
+            crate::AofError::RateLimited {
+                retry_after: Some(retry_after),
+            } => *retry_after,
+            _ => std::time::Duration::from_millis(
+                self.initial_backoff_ms
+                    .saturating_mul(2_u64.saturating_pow(attempt - 1)),
+            ),
+        }
+    }
+}
+
+/// How long Ollama keeps a model loaded after a request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum KeepAlive {
+    /// Seconds; a negative value keeps the model loaded indefinitely
+    Seconds(i64),
+    /// Duration such as `30m` or `1h30m`
+    Duration(String),
+}
+
+/// Agent feature switched on with `true` or configured with an object
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum FeatureToggle<T> {
+    /// `true` enables the feature with its default settings
+    Enabled(bool),
+    /// Enables the feature with these settings
+    Config(T),
+}
+
+impl<T: Clone + Default> FeatureToggle<T> {
+    /// Settings in effect, or `None` when the feature is switched off
+    pub fn settings(&self) -> Option<T> {
+        match self {
+            Self::Enabled(false) => None,
+            Self::Enabled(true) => Some(T::default()),
+            Self::Config(config) => Some(config.clone()),
+        }
+    }
+}
+
+/// Response cache settings (`prompt_cache`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct PromptCacheConfig {
+    /// Responses kept; the least recently used is evicted beyond this
+    #[schemars(range(min = 1))]
+    pub max_entries: usize,
+
+    /// Seconds a response stays usable after it was stored
+    pub ttl_secs: u64,
+}
+
+impl Default for PromptCacheConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: 128,
+            ttl_secs: 300,
+        }
+    }
+}
+
+/// Delegation settings (`delegation`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct DelegationConfig {
+    /// Nested delegations allowed below the first agent
+    pub max_depth: usize,
+}
+
+impl Default for DelegationConfig {
+    fn default() -> Self {
+        Self { max_depth: 3 }
+    }
+}
+
+/// What the executor does when the model rejects the conversation as too long
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextOverflowPolicy {
+    /// Fail the run
+    #[default]
+    Error,
+    /// Drop the oldest half of the conversation and retry the iteration
+    Prune,
+    /// Like `Prune`, replacing the dropped messages with a summary written
+    /// by the agent's model (or the executor's history summarizer, if set)
+    Summarize,
+}
+
+impl ContextOverflowPolicy {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// What the executor does when the model ends its turn without content or tool calls
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EmptyResponsePolicy {
+    /// Fail the run with a model error
+    #[default]
+    Error,
+    /// Ask the model once more, failing if the answer is still empty
+    Retry,
+}
+
+impl EmptyResponsePolicy {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
 fn default_max_iterations() -> usize {
     10
 }
@@ -325,6 +865,24 @@ mod tests {
         assert_eq!(ctx.messages[1].content, "assistant response");
     }
 
+    #[test]
+    fn test_agent_context_snapshot_round_trip() {
+        let mut ctx = AgentContext::new("first");
+        ctx.add_message(MessageRole::User, "first");
+        ctx.add_message(MessageRole::Assistant, "reply");
+        ctx.set_state("ticket", "INC-42").unwrap();
+        ctx.metadata.input_tokens = 120;
+
+        let json = serde_json::to_string(&ctx.to_snapshot()).unwrap();
+        let restored = AgentContext::from_snapshot(serde_json::from_str(&json).unwrap());
+
+        assert!(restored.input.is_empty());
+        assert_eq!(restored.messages.len(), 2);
+        assert_eq!(restored.messages[1].content, "reply");
+        assert_eq!(restored.get_state::<String>("ticket"), Some("INC-42".to_string()));
+        assert_eq!(restored.metadata.input_tokens, 120);
+    }
+
     #[test]
     fn test_agent_context_state() {
         let mut ctx = AgentContext::new("test");
@@ -354,6 +912,28 @@ mod tests {
         assert_eq!(deserialized, MessageRole::Assistant);
     }
 
+    #[test]
+    fn test_agent_config_json_schema() {
+        let schema = AgentConfig::json_schema();
+
+        // Either the flat or the Kubernetes-style format
+        assert_eq!(schema["anyOf"].as_array().unwrap().len(), 2);
+
+        let flat = &schema["$defs"]["FlatAgentConfig"];
+        let fields = ["name", "model", "system_prompt", "tools", "temperature"];
+        for field in fields {
+            assert!(flat["properties"].get(field).is_some(), "missing {}", field);
+        }
+        assert_eq!(flat["required"], serde_json::json!(["name", "model"]));
+        assert_eq!(flat["properties"]["temperature"]["maximum"], 2.0);
+        assert_eq!(flat["properties"]["temperature"]["minimum"], 0.0);
+        assert_eq!(flat["properties"]["temperature"]["default"], 0.7);
+        assert_eq!(flat["properties"]["max_iterations"]["minimum"], 1);
+
+        let spec = &schema["$defs"]["AgentSpec"]["properties"];
+        assert_eq!(spec["temperature"]["maximum"], 2.0);
+    }
+
     #[test]
     fn test_agent_config_defaults() {
         let yaml = r#"
@@ -368,6 +948,7 @@ mod tests {
         assert_eq!(config.temperature, 0.7); // default
         assert!(config.tools.is_empty());
         assert!(config.system_prompt.is_none());
+        assert_eq!(config.context_window(), 200_000); // known for the model
     }
 
     #[test]
@@ -382,6 +963,7 @@ mod tests {
             max_iterations: 20
             temperature: 0.5
             max_tokens: 4096
+            context_window: 32000
         "#;
         let config: AgentConfig = serde_yaml::from_str(yaml).unwrap();
 
@@ -392,6 +974,58 @@ mod tests {
         assert_eq!(config.max_iterations, 20);
         assert_eq!(config.temperature, 0.5);
         assert_eq!(config.max_tokens, Some(4096));
+        assert_eq!(config.context_window(), 32_000);
+    }
+
+    #[test]
+    fn test_agent_config_feature_toggles() {
+        let config: AgentConfig = serde_yaml::from_str(
+            "name: router\nmodel: gpt-4o\nprompt_cache: true\ndelegation:\n  max_depth: 1",
+        )
+        .unwrap();
+        assert_eq!(
+            config.prompt_cache.unwrap().settings(),
+            Some(PromptCacheConfig::default())
+        );
+        assert_eq!(
+            config.delegation.unwrap().settings(),
+            Some(DelegationConfig { max_depth: 1 })
+        );
+        assert!(config.extra.is_empty());
+
+        let config: AgentConfig =
+            serde_yaml::from_str("name: router\nmodel: gpt-4o\nprompt_cache: false").unwrap();
+        assert_eq!(config.prompt_cache.unwrap().settings(), None);
+        assert!(config.delegation.is_none());
+
+        // Misspelled settings are rejected rather than ignored
+        assert!(serde_yaml::from_str::<AgentConfig>(
+            "name: router\nmodel: gpt-4o\nprompt_cache:\n  max_entrys: 10"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_agent_config_from_yaml_interpolates_env() {
+        std::env::set_var("AOF_TEST_AGENT_PROMPT", "You are on call.");
+        std::env::remove_var("AOF_TEST_AGENT_UNSET");
+
+        let yaml = r#"
+name: env-agent
+model: ${AOF_TEST_AGENT_UNSET:-ollama:llama3}
+system_prompt: "${AOF_TEST_AGENT_PROMPT}"
+endpoint: ${AOF_TEST_AGENT_UNSET:-http://localhost:11434}
+"#;
+        let config = AgentConfig::from_yaml(yaml).unwrap();
+        assert_eq!(config.model, "ollama:llama3");
+        assert_eq!(config.system_prompt.as_deref(), Some("You are on call."));
+        assert_eq!(config.extra["endpoint"], "http://localhost:11434");
+
+        let err = AgentConfig::from_yaml("name: a\nmodel: ${AOF_TEST_AGENT_UNSET}\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Configuration error: unset env var AOF_TEST_AGENT_UNSET referenced in config"
+        );
     }
 
     #[test]
@@ -1,3 +1,4 @@
+use std::time::Duration;
 use thiserror::Error;
 
 /// Main error type for AOF framework
@@ -9,6 +10,15 @@ pub enum AofError {
     #[error("Model error: {0}")]
     Model(String),
 
+    #[error("Rate limited{}", format_retry_after(.retry_after))]
+    RateLimited { retry_after: Option<Duration> },
+
+    #[error("HTTP {status}: {message}")]
+    Http { status: u16, message: String },
+
+    #[error("Context window exceeded: {0}")]
+    ContextOverflow(String),
+
     #[error("Tool execution error: {0}")]
     Tool(String),
 
@@ -76,6 +86,41 @@ impl AofError {
     pub fn config(msg: impl Into<String>) -> Self {
         Self::Config(msg.into())
     }
+
+    /// Create a rate-limit error
+    pub fn rate_limited(retry_after: Option<Duration>) -> Self {
+        Self::RateLimited { retry_after }
+    }
+
+    /// Create a context-overflow error (the prompt is too long for the model)
+    pub fn context_overflow(msg: impl Into<String>) -> Self {
+        Self::ContextOverflow(msg.into())
+    }
+
+    /// Create an HTTP status error
+    pub fn http(status: u16, message: impl Into<String>) -> Self {
+        Self::Http {
+            status,
+            message: message.into(),
+        }
+    }
+
+    /// Whether retrying the same request may succeed
+    ///
+    /// True for rate limits, timeouts, I/O failures and HTTP 408/429/5xx.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::RateLimited { .. } | Self::Timeout(_) | Self::Io(_) => true,
+            Self::Http { status, .. } => matches!(status, 408 | 429 | 500..=599),
+            _ => false,
+        }
+    }
+}
+
+fn format_retry_after(retry_after: &Option<Duration>) -> String {
+    retry_after
+        .map(|d| format!(" (retry after {}s)", d.as_secs()))
+        .unwrap_or_default()
 }
 
 #[cfg(test)]
@@ -109,6 +154,17 @@ mod tests {
         assert!(config_err.to_string().contains("Configuration error"));
     }
 
+    #[test]
+    fn test_structured_http_errors() {
+        let err = AofError::rate_limited(Some(Duration::from_secs(7)));
+        assert_eq!(err.to_string(), "Rate limited (retry after 7s)");
+        assert!(err.is_transient());
+
+        assert!(AofError::http(503, "overloaded").is_transient());
+        assert!(!AofError::http(401, "bad key").is_transient());
+        assert!(!AofError::model("timeout").is_transient());
+    }
+
     #[test]
     fn test_error_from_io() {
         let io_err = std::io::Error::new(std::io::ErrorKind::NotFound, "file not found");
@@ -5,6 +5,7 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::{RwLock, RwLockReadGuard};
 use chrono::Utc;
 
 /// Error record for tracking and learning
@@ -102,7 +103,15 @@ impl ErrorRecord {
 }
 
 /// Local Error Knowledge Base (RAG system)
+///
+/// Internally synchronized, so one knowledge base can be shared (e.g. behind
+/// an `Arc`) by every agent executor of a runtime.
 pub struct ErrorKnowledgeBase {
+    inner: RwLock<KnowledgeBaseStore>,
+}
+
+#[derive(Default)]
+struct KnowledgeBaseStore {
     /// Store of error records
     errors: HashMap<String, ErrorRecord>,
 
@@ -117,49 +126,55 @@ impl ErrorKnowledgeBase {
     /// Create a new knowledge base
     pub fn new() -> Self {
         Self {
-            errors: HashMap::new(),
-            error_index: HashMap::new(),
-            tag_index: HashMap::new(),
+            inner: RwLock::new(KnowledgeBaseStore::default()),
         }
     }
 
+    fn read(&self) -> RwLockReadGuard<'_, KnowledgeBaseStore> {
+        self.inner.read().unwrap_or_else(|e| e.into_inner())
+    }
+
     /// Record an error
-    pub fn record(&mut self, error: ErrorRecord) {
+    pub fn record(&self, error: ErrorRecord) {
+        let mut store = self.inner.write().unwrap_or_else(|e| e.into_inner());
         let error_id = error.id.clone();
         let error_type = error.error_type.clone();
 
         // Increment occurrence count if error already exists
-        if let Some(existing) = self.errors.get_mut(&error_id) {
+        if let Some(existing) = store.errors.get_mut(&error_id) {
             existing.occurrence_count += 1;
             return;
         }
 
         // Index by error type
-        self.error_index
+        store
+            .error_index
             .entry(error_type)
             .or_insert_with(Vec::new)
             .push(error_id.clone());
 
         // Index by tags
         for tag in &error.tags {
-            self.tag_index
+            store
+                .tag_index
                 .entry(tag.clone())
                 .or_insert_with(Vec::new)
                 .push(error_id.clone());
         }
 
         // Store the error
-        self.errors.insert(error_id, error);
+        store.errors.insert(error_id, error);
     }
 
     /// Find similar errors (for learning from past mistakes)
     pub fn find_similar(&self, error_type: &str, keywords: &[&str]) -> Vec<ErrorRecord> {
+        let store = self.read();
         let mut matches = vec![];
 
         // Get errors of the same type
-        if let Some(error_ids) = self.error_index.get(error_type) {
+        if let Some(error_ids) = store.error_index.get(error_type) {
             for error_id in error_ids {
-                if let Some(error) = self.errors.get(error_id) {
+                if let Some(error) = store.errors.get(error_id) {
                     // Check if any keywords match
                     let matches_keywords = keywords.iter().any(|kw| {
                         error.message.contains(kw)
@@ -181,19 +196,44 @@ impl ErrorKnowledgeBase {
 
     /// Get errors by tag
     pub fn find_by_tag(&self, tag: &str) -> Vec<ErrorRecord> {
-        if let Some(error_ids) = self.tag_index.get(tag) {
+        let store = self.read();
+        if let Some(error_ids) = store.tag_index.get(tag) {
             error_ids
                 .iter()
-                .filter_map(|id| self.errors.get(id).cloned())
+                .filter_map(|id| store.errors.get(id).cloned())
                 .collect()
         } else {
             vec![]
         }
     }
 
+    /// Total occurrences per tag starting with `prefix`, most frequent first
+    ///
+    /// With the executor's `tool:<name>` tags, `occurrences_by_tag("tool:")`
+    /// answers "which tools fail most".
+    pub fn occurrences_by_tag(&self, prefix: &str) -> Vec<(String, usize)> {
+        let store = self.read();
+        let mut counts: Vec<(String, usize)> = store
+            .tag_index
+            .iter()
+            .filter(|(tag, _)| tag.starts_with(prefix))
+            .map(|(tag, error_ids)| {
+                let occurrences = error_ids
+                    .iter()
+                    .filter_map(|id| store.errors.get(id))
+                    .map(|e| e.occurrence_count)
+                    .sum();
+                (tag.clone(), occurrences)
+            })
+            .collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        counts
+    }
+
     /// Get all unresolved errors
     pub fn unresolved(&self) -> Vec<ErrorRecord> {
-        self.errors
+        self.read()
+            .errors
             .values()
             .filter(|e| !e.resolved)
             .cloned()
@@ -202,22 +242,23 @@ impl ErrorKnowledgeBase {
 
     /// Get most frequent errors
     pub fn most_frequent(&self, limit: usize) -> Vec<ErrorRecord> {
-        let mut errors: Vec<_> = self.errors.values().cloned().collect();
+        let mut errors: Vec<_> = self.read().errors.values().cloned().collect();
         errors.sort_by(|a, b| b.occurrence_count.cmp(&a.occurrence_count));
         errors.into_iter().take(limit).collect()
     }
 
     /// Export to JSON for documentation/learning
     pub fn export_json(&self) -> serde_json::Result<String> {
-        serde_json::to_string_pretty(&self.errors)
+        serde_json::to_string_pretty(&self.read().errors)
     }
 
     /// Get statistics about errors
     pub fn stats(&self) -> ErrorStats {
-        let total_errors = self.errors.len();
-        let total_occurrences = self.errors.values().map(|e| e.occurrence_count).sum();
-        let unresolved_count = self.errors.values().filter(|e| !e.resolved).count();
-        let with_solutions = self.errors.values().filter(|e| e.solution.is_some()).count();
+        let store = self.read();
+        let total_errors = store.errors.len();
+        let total_occurrences = store.errors.values().map(|e| e.occurrence_count).sum();
+        let unresolved_count = store.errors.values().filter(|e| !e.resolved).count();
+        let with_solutions = store.errors.values().filter(|e| e.solution.is_some()).count();
 
         ErrorStats {
             total_unique_errors: total_errors,
@@ -264,7 +305,7 @@ mod tests {
 
     #[test]
     fn test_knowledge_base_recording() {
-        let mut kb = ErrorKnowledgeBase::new();
+        let kb = ErrorKnowledgeBase::new();
 
         let error1 = ErrorRecord::new("MCP", "Client not initialized", "context1")
             .with_tag("initialization");
@@ -274,12 +315,12 @@ mod tests {
         kb.record(error1);
         kb.record(error2);
 
-        assert_eq!(kb.errors.len(), 2);
+        assert_eq!(kb.stats().total_unique_errors, 2);
     }
 
     #[test]
     fn test_find_by_tag() {
-        let mut kb = ErrorKnowledgeBase::new();
+        let kb = ErrorKnowledgeBase::new();
 
         kb.record(ErrorRecord::new("MCP", "Error 1", "ctx").with_tag("init"));
         kb.record(ErrorRecord::new("MCP", "Error 2", "ctx").with_tag("execution"));
@@ -291,7 +332,7 @@ mod tests {
 
     #[test]
     fn test_find_similar() {
-        let mut kb = ErrorKnowledgeBase::new();
+        let kb = ErrorKnowledgeBase::new();
 
         kb.record(
             ErrorRecord::new("MCP", "Client not initialized", "runtime").with_tag("init")
@@ -304,4 +345,20 @@ mod tests {
         assert_eq!(similar.len(), 1);
         assert!(similar[0].message.contains("initialized"));
     }
+
+    #[test]
+    fn test_occurrences_by_tag() {
+        let kb = ErrorKnowledgeBase::new();
+
+        kb.record(ErrorRecord::new("Tool", "timeout", "ctx").with_tag("tool:kubectl"));
+        kb.record(ErrorRecord::new("Tool", "timeout", "ctx").with_tag("tool:kubectl"));
+        kb.record(ErrorRecord::new("Tool", "denied", "ctx").with_tag("tool:kubectl"));
+        kb.record(ErrorRecord::new("Tool", "not found", "ctx").with_tag("tool:shell"));
+        kb.record(ErrorRecord::new("Model", "overloaded", "ctx").with_tag("agent:ops"));
+
+        assert_eq!(
+            kb.occurrences_by_tag("tool:"),
+            vec![("tool:kubectl".to_string(), 3), ("tool:shell".to_string(), 1)]
+        );
+    }
 }
@@ -0,0 +1,137 @@
+//! Environment variable interpolation for configuration files
+//!
+//! Expands `${VAR}` and `${VAR:-default}` in raw config text before it is
+//! deserialized, so secrets and environment-specific values can stay out of
+//! the YAML. `$${VAR}` escapes the expansion and yields a literal `${VAR}`.
+
+use crate::{AofError, AofResult};
+
+/// Expand environment variable references using the process environment
+pub fn interpolate_env(input: &str) -> AofResult<String> {
+    interpolate_with(input, |name| std::env::var(name).ok())
+}
+
+/// Expand variable references, resolving names through `lookup`
+///
+/// A reference without a default whose variable is unset is an error; the
+/// default is used when the variable is unset or empty. Text after `${` that
+/// is not a valid variable name is left untouched.
+pub fn interpolate_with<F>(input: &str, lookup: F) -> AofResult<String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(start) = rest.find("${") {
+        // `$${` is an escaped, literal `${`
+        if rest[..start].ends_with('$') {
+            output.push_str(&rest[..start - 1]);
+            output.push_str("${");
+            rest = &rest[start + 2..];
+            continue;
+        }
+
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+
+        let Some(end) = after.find('}') else {
+            output.push_str(&rest[start..]);
+            return Ok(output);
+        };
+
+        let expr = &after[..end];
+        let (name, default) = match expr.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (expr, None),
+        };
+
+        if !is_var_name(name) {
+            output.push_str("${");
+            rest = after;
+            continue;
+        }
+
+        // As in the shell, `:-` also replaces a variable that is set but empty
+        let value = match default {
+            Some(default) => lookup(name)
+                .filter(|value| !value.is_empty())
+                .or_else(|| Some(default.to_string())),
+            None => lookup(name),
+        };
+
+        match value {
+            Some(value) => output.push_str(&value),
+            None => {
+                return Err(AofError::config(format!(
+                    "unset env var {} referenced in config",
+                    name
+                )))
+            }
+        }
+
+        rest = &after[end + 1..];
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
+fn is_var_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "REGION" => Some("eu-west-1".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_interpolates_variables() {
+        let output =
+            interpolate_with("endpoint: https://${REGION}.example.com/${REGION}", lookup).unwrap();
+        assert_eq!(output, "endpoint: https://eu-west-1.example.com/eu-west-1");
+    }
+
+    #[test]
+    fn test_default_used_when_unset() {
+        let output = interpolate_with(
+            "model: ${MODEL:-ollama:llama3}\nregion: ${REGION:-us-east-1}\nempty: '${EMPTY:-x}' '${EMPTY}'",
+            lookup,
+        )
+        .unwrap();
+        assert_eq!(
+            output,
+            "model: ollama:llama3\nregion: eu-west-1\nempty: 'x' ''"
+        );
+    }
+
+    #[test]
+    fn test_unset_variable_is_an_error() {
+        let err = interpolate_with("api_key: ${API_KEY}", lookup).unwrap_err();
+        assert!(matches!(err, AofError::Config(_)));
+        assert_eq!(
+            err.to_string(),
+            "Configuration error: unset env var API_KEY referenced in config"
+        );
+    }
+
+    #[test]
+    fn test_literal_text_is_preserved() {
+        let input = "prompt: run `echo $${HOME}` or ${not a var} and ${unterminated";
+        let output = interpolate_with(input, lookup).unwrap();
+        assert_eq!(
+            output,
+            "prompt: run `echo ${HOME}` or ${not a var} and ${unterminated"
+        );
+    }
+}
@@ -6,31 +6,43 @@
 pub mod agent;
 pub mod error;
 pub mod error_tracker;
+pub mod interpolate;
+pub mod logging;
 pub mod memory;
 pub mod model;
+pub mod pricing;
 pub mod tool;
+pub mod validation;
+pub mod workflow;
 
 // Re-export core types
 pub use agent::{
-    Agent, AgentConfig, AgentContext, AgentMetadata, ExecutionMetadata, Message, MessageRole,
-    ToolResult as AgentToolResult,
+    Agent, AgentConfig, AgentContext, AgentMetadata, ContextOverflowPolicy, ContextSnapshot,
+    DelegationConfig, EmptyResponsePolicy, ExecutionMetadata, FeatureToggle, KeepAlive, Message,
+    MessageRole, ModelRetryPolicy, PromptCacheConfig, ToolResult as AgentToolResult,
 };
 pub use error::{AofError, AofResult};
 pub use error_tracker::{ErrorKnowledgeBase, ErrorRecord, ErrorStats};
+pub use interpolate::interpolate_env;
+pub use logging::{init_tracing, LogFormat};
 pub use memory::{Memory, MemoryBackend, MemoryEntry, MemoryQuery};
 pub use model::{
-    Model, ModelConfig, ModelProvider, ModelRequest, ModelResponse, RequestMessage, StopReason,
-    StreamChunk, ToolDefinition as ModelToolDefinition, Usage,
+    context_window_for_model, ContentPart, ImageUrl, Model, ModelConfig, ModelProvider,
+    ModelRequest, ModelResponse, RequestMessage, StopReason, StreamChunk,
+    ToolDefinition as ModelToolDefinition, Usage,
 };
+pub use pricing::{ModelPrice, Pricing};
 pub use tool::{
     Tool, ToolCall, ToolConfig, ToolDefinition, ToolExecutor, ToolInput, ToolResult, ToolType,
 };
+pub use validation::{ConfigError, Validate};
+pub use workflow::{render_step_input, OnError, WorkflowConfig, WorkflowStep};
 
 /// Version information
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
-/// Default context window size (tokens)
-pub const DEFAULT_CONTEXT_WINDOW: usize = 100_000;
+/// Context window size (tokens) assumed for models of unknown size
+pub const DEFAULT_CONTEXT_WINDOW: usize = 128_000;
 
 /// Maximum parallel tool calls
 pub const MAX_PARALLEL_TOOLS: usize = 10;
@@ -0,0 +1,115 @@
+//! Tracing subscriber setup shared by the AOF binaries
+//!
+//! Logs are human-readable by default. Setting `AOF_LOG_FORMAT=json` switches
+//! to one JSON object per line for log shippers such as Loki or ELK: event
+//! fields are flattened into the object, `span` holds the innermost span's
+//! fields and `spans` the whole span stack, so the `agent`, `trace_id`,
+//! `tool` and `task_id` recorded by the runtime's spans appear on every event.
+
+use tracing::Subscriber;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
+/// Environment variable selecting the log format
+pub const LOG_FORMAT_ENV: &str = "AOF_LOG_FORMAT";
+
+/// Output format for log lines
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable lines
+    #[default]
+    Text,
+
+    /// One JSON object per line
+    Json,
+}
+
+impl LogFormat {
+    /// Read the format from `AOF_LOG_FORMAT`, defaulting to text
+    pub fn from_env() -> Self {
+        match std::env::var(LOG_FORMAT_ENV) {
+            Ok(value) if value.eq_ignore_ascii_case("json") => Self::Json,
+            _ => Self::Text,
+        }
+    }
+}
+
+/// Formatting layer writing `format` lines to `writer`
+pub fn fmt_layer<S, W>(format: LogFormat, writer: W) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    match format {
+        LogFormat::Text => tracing_subscriber::fmt::layer().with_writer(writer).boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_span_list(true)
+            .with_writer(writer)
+            .boxed(),
+    }
+}
+
+/// Install the global subscriber, logging to stdout in the `AOF_LOG_FORMAT` format
+///
+/// `default_filter` applies when `RUST_LOG` is unset.
+pub fn init_tracing(default_filter: &str) {
+    tracing_subscriber::registry()
+        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| default_filter.into()))
+        .with(fmt_layer(LogFormat::from_env(), std::io::stdout))
+        .init();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct CapturedOutput(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for CapturedOutput {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_json_lines_carry_span_fields() {
+        let output = CapturedOutput::default();
+        let writer = output.clone();
+        let subscriber =
+            tracing_subscriber::registry().with(fmt_layer(LogFormat::Json, move || writer.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let task = tracing::info_span!("task", task_id = "task-1");
+            let _task = task.enter();
+            let tool = tracing::info_span!("tool", agent = "ops", tool = "kubectl");
+            let _tool = tool.enter();
+            tracing::info!(attempts = 1, "Tool execution metrics");
+        });
+
+        let bytes = output.0.lock().unwrap().clone();
+        let line = String::from_utf8(bytes).unwrap();
+        let json: serde_json::Value = serde_json::from_str(line.trim()).unwrap();
+
+        assert_eq!(json["level"], "INFO");
+        assert_eq!(json["message"], "Tool execution metrics");
+        assert_eq!(json["attempts"], 1);
+        assert_eq!(json["span"]["agent"], "ops");
+        assert_eq!(json["span"]["tool"], "kubectl");
+        assert_eq!(json["spans"][0]["task_id"], "task-1");
+        assert!(json["timestamp"].is_string());
+    }
+}
@@ -25,6 +25,26 @@ pub trait MemoryBackend: Send + Sync {
     /// Clear all entries
     async fn clear(&self) -> AofResult<()>;
 
+    /// Store several entries at once
+    ///
+    /// Backends with a network round trip per call should override this to
+    /// send the whole batch together.
+    async fn store_many(&self, entries: Vec<(String, MemoryEntry)>) -> AofResult<()> {
+        for (key, entry) in entries {
+            self.store(&key, entry).await?;
+        }
+        Ok(())
+    }
+
+    /// Retrieve several entries at once, in the order of `keys`
+    async fn retrieve_many(&self, keys: &[String]) -> AofResult<Vec<Option<MemoryEntry>>> {
+        let mut entries = Vec::with_capacity(keys.len());
+        for key in keys {
+            entries.push(self.retrieve(key).await?);
+        }
+        Ok(entries)
+    }
+
     /// Search entries by metadata
     async fn search(&self, query: &MemoryQuery) -> AofResult<Vec<MemoryEntry>> {
         // Default implementation: filter in-memory
@@ -60,6 +80,26 @@ pub trait Memory: Send + Sync {
 
     /// Clear all memory
     async fn clear(&self) -> AofResult<()>;
+
+    /// Store several values at once
+    async fn store_many(&self, values: Vec<(String, serde_json::Value)>) -> AofResult<()> {
+        for (key, value) in values {
+            self.store(&key, value).await?;
+        }
+        Ok(())
+    }
+
+    /// Retrieve several values at once, in the order of `keys`
+    async fn retrieve_many<T: serde::de::DeserializeOwned + Send>(
+        &self,
+        keys: &[String],
+    ) -> AofResult<Vec<Option<T>>> {
+        let mut values = Vec::with_capacity(keys.len());
+        for key in keys {
+            values.push(self.retrieve(key).await?);
+        }
+        Ok(values)
+    }
 }
 
 /// Memory entry with metadata
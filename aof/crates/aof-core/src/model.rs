@@ -47,6 +47,103 @@ pub enum ModelProvider {
     Custom,
 }
 
+impl ModelProvider {
+    /// Parse a provider name as used in `provider:model` strings
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "anthropic" => Some(Self::Anthropic),
+            "openai" => Some(Self::OpenAI),
+            "google" => Some(Self::Google),
+            "groq" => Some(Self::Groq),
+            "bedrock" => Some(Self::Bedrock),
+            "azure" => Some(Self::Azure),
+            "ollama" => Some(Self::Ollama),
+            _ => None,
+        }
+    }
+
+    /// Lowercase provider name, matching the serialized form
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Anthropic => "anthropic",
+            Self::OpenAI => "openai",
+            Self::Google => "google",
+            Self::Groq => "groq",
+            Self::Bedrock => "bedrock",
+            Self::Azure => "azure",
+            Self::Ollama => "ollama",
+            Self::Custom => "custom",
+        }
+    }
+
+    /// Infer the provider serving a model id
+    ///
+    /// Honours an explicit `provider:model` or `provider/model` prefix, then
+    /// falls back to well-known model families. Returns `None` for models it
+    /// does not recognise so callers can pick their own default.
+    pub fn infer_from_model(model: &str) -> Option<Self> {
+        let model = model.trim().to_lowercase();
+
+        if let Some(provider) = model
+            .split_once([':', '/'])
+            .and_then(|(prefix, _)| Self::from_name(prefix))
+        {
+            return Some(provider);
+        }
+
+        // Groq ids overlap with Ollama's llama/gemma families, so check first
+        if Self::is_groq_model(&model) {
+            return Some(Self::Groq);
+        }
+
+        const FAMILIES: &[(&str, ModelProvider)] = &[
+            ("claude", ModelProvider::Anthropic),
+            ("gpt", ModelProvider::OpenAI),
+            ("chatgpt", ModelProvider::OpenAI),
+            ("o1", ModelProvider::OpenAI),
+            ("o3", ModelProvider::OpenAI),
+            ("o4", ModelProvider::OpenAI),
+            ("gemini", ModelProvider::Google),
+            ("llama", ModelProvider::Ollama),
+            ("codellama", ModelProvider::Ollama),
+            ("mistral", ModelProvider::Ollama),
+            ("phi", ModelProvider::Ollama),
+            ("qwen", ModelProvider::Ollama),
+            ("gemma", ModelProvider::Ollama),
+            ("deepseek", ModelProvider::Ollama),
+        ];
+
+        FAMILIES
+            .iter()
+            .find(|(prefix, _)| model.starts_with(prefix))
+            .map(|(_, provider)| *provider)
+    }
+
+    /// Whether a bare model id is one Groq hosts
+    ///
+    /// Groq ids use dashes and a size or context suffix
+    /// (`llama-3.3-70b-versatile`, `llama3-70b-8192`, `mixtral-8x7b-32768`),
+    /// unlike Ollama tags such as `llama3.1:8b`.
+    pub fn is_groq_model(model: &str) -> bool {
+        let model = model.to_lowercase();
+        if model.contains(':') {
+            return false;
+        }
+
+        let context_suffix = model
+            .rsplit_once('-')
+            .is_some_and(|(_, suffix)| suffix.len() >= 4 && suffix.chars().all(|c| c.is_ascii_digit()));
+
+        model.contains("groq")
+            || model.starts_with("mixtral")
+            || model.ends_with("-versatile")
+            || model.ends_with("-instant")
+            || model.ends_with("-specdec")
+            || (model.starts_with("gemma") && model.ends_with("-it"))
+            || ((model.starts_with("llama") || model.starts_with("gemma")) && context_suffix)
+    }
+}
+
 /// Model configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelConfig {
@@ -94,7 +191,7 @@ fn default_timeout() -> u64 {
 }
 
 /// Model request
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ModelRequest {
     /// Messages in conversation
     pub messages: Vec<RequestMessage>,
@@ -115,28 +212,112 @@ pub struct ModelRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_tokens: Option<usize>,
 
+    /// Sequences that end generation when the model produces them.
+    /// Anthropic and Bedrock report the stop as `StopReason::StopSequence`;
+    /// OpenAI, Gemini and Ollama don't tell it apart from `EndTurn`.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub stop_sequences: Vec<String>,
+
+    /// Sampling seed for reproducible output (OpenAI and Ollama; other
+    /// providers ignore it)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+
     /// Stream response
     #[serde(default)]
     pub stream: bool,
 
+    /// JSON schema the response content must match
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_schema: Option<serde_json::Value>,
+
+    /// HTTP headers for this request only, overriding `ModelConfig.headers`
+    /// of the same name (e.g. tenant or budget headers for an LLM gateway)
+    #[serde(skip_serializing_if = "HashMap::is_empty", default)]
+    pub request_headers: HashMap<String, String>,
+
     /// Extra parameters
     #[serde(flatten)]
     pub extra: HashMap<String, serde_json::Value>,
 }
 
+impl ModelRequest {
+    /// Whether any message carries an image
+    pub fn has_images(&self) -> bool {
+        self.messages.iter().any(|m| {
+            m.parts
+                .iter()
+                .any(|part| matches!(part, ContentPart::ImageUrl { .. }))
+        })
+    }
+}
+
 /// Message in request
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct RequestMessage {
     pub role: MessageRole,
     pub content: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_calls: Option<Vec<crate::ToolCall>>,
+    /// Id of the tool call a `Tool` message answers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+    /// Content following `content`, e.g. images for vision models
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parts: Vec<crate::ContentPart>,
+}
+
+/// Part of a multimodal message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentPart {
+    /// Text
+    Text { text: String },
+
+    /// Image, by URL or inline as a base64 `data:` URL
+    ImageUrl { image_url: ImageUrl },
+}
+
+impl ContentPart {
+    /// Image the provider fetches from `url`
+    pub fn image_url(url: impl Into<String>) -> Self {
+        Self::ImageUrl {
+            image_url: ImageUrl {
+                url: url.into(),
+                detail: None,
+            },
+        }
+    }
+
+    /// Inline image of `media_type` (e.g. `image/png`) from base64 `data`
+    pub fn image_base64(media_type: &str, data: &str) -> Self {
+        Self::image_url(format!("data:{};base64,{}", media_type, data))
+    }
+}
+
+/// Image in a message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImageUrl {
+    /// `https://` URL, or `data:<media type>;base64,<data>` for inline images
+    pub url: String,
+
+    /// Resolution hint (`low`, `high` or `auto`); only OpenAI uses it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl ImageUrl {
+    /// Media type and base64 data of an inline image
+    pub fn as_base64(&self) -> Option<(&str, &str)> {
+        self.url.strip_prefix("data:")?.split_once(";base64,")
+    }
 }
 
 /// Message role
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum MessageRole {
+    #[default]
     User,
     Assistant,
     System,
@@ -167,6 +348,10 @@ pub struct ModelResponse {
     /// Usage statistics
     pub usage: Usage,
 
+    /// Provider's explanation of the stop, such as a refusal message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_detail: Option<String>,
+
     /// Provider-specific metadata
     #[serde(flatten)]
     pub metadata: HashMap<String, serde_json::Value>,
@@ -195,17 +380,131 @@ pub struct Usage {
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum StreamChunk {
     ContentDelta { delta: String },
+    /// Reasoning text from models that expose it, separate from the answer
+    ThinkingDelta { delta: String },
     ToolCall { tool_call: crate::ToolCall },
-    Done { usage: Usage, stop_reason: StopReason },
+    Done {
+        usage: Usage,
+        stop_reason: StopReason,
+        /// Provider's explanation of the stop, as in `ModelResponse::stop_detail`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        stop_detail: Option<String>,
+    },
 }
 
 /// Reference-counted model
 pub type ModelRef = Arc<dyn Model>;
 
+/// Context window size (tokens) for a model, accepting `provider:model` names
+///
+/// Unknown models fall back to `DEFAULT_CONTEXT_WINDOW`.
+pub fn context_window_for_model(model: &str) -> usize {
+    let name = match model.split_once(':') {
+        Some((provider, rest)) if ModelProvider::from_name(provider).is_some() => rest,
+        _ => model,
+    }
+    .to_lowercase();
+
+    // Most specific prefixes first
+    const WINDOWS: &[(&str, usize)] = &[
+        ("claude", 200_000),
+        ("gpt-4.1", 1_047_576),
+        ("gpt-4o", 128_000),
+        ("gpt-4-turbo", 128_000),
+        ("gpt-4-32k", 32_768),
+        ("gpt-4", 8_192),
+        ("gpt-3.5-turbo", 16_385),
+        ("o1", 200_000),
+        ("o3", 200_000),
+        ("o4", 200_000),
+        ("gemini-1.5-pro", 2_097_152),
+        ("gemini", 1_048_576),
+        ("llama-3.1", 128_000),
+        ("llama-3.2", 128_000),
+        ("llama-3.3", 128_000),
+        ("llama3.1", 128_000),
+        ("llama3.2", 128_000),
+        ("llama3.3", 128_000),
+        ("llama3", 8_192),
+        ("mixtral", 32_768),
+        ("mistral", 32_768),
+    ];
+
+    WINDOWS
+        .iter()
+        .find(|(prefix, _)| name.starts_with(prefix))
+        .map(|(_, window)| *window)
+        .unwrap_or(crate::DEFAULT_CONTEXT_WINDOW)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_is_groq_model() {
+        assert!(ModelProvider::is_groq_model("llama-3.3-70b-versatile"));
+        assert!(ModelProvider::is_groq_model("llama-3.1-8b-instant"));
+        assert!(ModelProvider::is_groq_model("llama3-70b-8192"));
+        assert!(ModelProvider::is_groq_model("mixtral-8x7b-32768"));
+        assert!(ModelProvider::is_groq_model("gemma2-9b-it"));
+        assert!(!ModelProvider::is_groq_model("llama3.1:8b"));
+        assert!(!ModelProvider::is_groq_model("llama3"));
+        assert!(!ModelProvider::is_groq_model("gpt-4o"));
+        assert!(!ModelProvider::is_groq_model("claude-3-5-sonnet-20241022"));
+    }
+
+    #[test]
+    fn test_infer_from_model() {
+        let cases = [
+            ("claude-3-5-sonnet-20241022", Some(ModelProvider::Anthropic)),
+            ("anthropic:claude-3-haiku", Some(ModelProvider::Anthropic)),
+            ("gpt-4o", Some(ModelProvider::OpenAI)),
+            ("o1-preview", Some(ModelProvider::OpenAI)),
+            ("o3-mini", Some(ModelProvider::OpenAI)),
+            ("openai:gpt-4", Some(ModelProvider::OpenAI)),
+            ("gemini-2.0-flash", Some(ModelProvider::Google)),
+            ("google/gemini-1.5-pro", Some(ModelProvider::Google)),
+            ("llama3.1:8b", Some(ModelProvider::Ollama)),
+            ("mistral:7b", Some(ModelProvider::Ollama)),
+            ("phi3", Some(ModelProvider::Ollama)),
+            ("llama-3.3-70b-versatile", Some(ModelProvider::Groq)),
+            ("mixtral-8x7b-32768", Some(ModelProvider::Groq)),
+            ("groq:llama3-8b-8192", Some(ModelProvider::Groq)),
+            ("bedrock:anthropic.claude-3-sonnet", Some(ModelProvider::Bedrock)),
+            ("acme-large", None),
+            ("", None),
+        ];
+
+        for (model, expected) in cases {
+            assert_eq!(ModelProvider::infer_from_model(model), expected, "model: {}", model);
+        }
+    }
+
+    #[test]
+    fn test_context_window_for_model() {
+        assert_eq!(
+            context_window_for_model("claude-3-5-sonnet-20241022"),
+            200_000
+        );
+        assert_eq!(
+            context_window_for_model("anthropic:claude-sonnet-4"),
+            200_000
+        );
+        assert_eq!(context_window_for_model("openai:gpt-4o-mini"), 128_000);
+        assert_eq!(context_window_for_model("openai:gpt-4-turbo"), 128_000);
+        assert_eq!(context_window_for_model("openai:gpt-4"), 8_192);
+        assert_eq!(
+            context_window_for_model("google:gemini-2.5-flash"),
+            1_048_576
+        );
+        assert_eq!(context_window_for_model("google:gemini-1.5-pro"), 2_097_152);
+        assert_eq!(context_window_for_model("ollama:llama3.1:8b"), 128_000);
+        assert_eq!(context_window_for_model("llama3.1:8b"), 128_000);
+        assert_eq!(context_window_for_model("ollama:llama3"), 8_192);
+        assert_eq!(context_window_for_model("custom-model"), 128_000);
+    }
+
     #[test]
     fn test_model_provider_serialization() {
         let provider = ModelProvider::Anthropic;
@@ -280,19 +579,15 @@ mod tests {
     #[test]
     fn test_model_request() {
         let request = ModelRequest {
-            messages: vec![
-                RequestMessage {
-                    role: MessageRole::User,
-                    content: "Hello".to_string(),
-                    tool_calls: None,
-                },
-            ],
+            messages: vec![RequestMessage {
+                role: MessageRole::User,
+                content: "Hello".to_string(),
+                ..Default::default()
+            }],
             system: Some("You are a helpful assistant.".to_string()),
-            tools: vec![],
             temperature: Some(0.5),
             max_tokens: Some(1000),
-            stream: false,
-            extra: HashMap::new(),
+            ..Default::default()
         };
 
         assert_eq!(request.messages.len(), 1);
@@ -343,6 +638,7 @@ mod tests {
                 output_tokens: 20,
             },
             stop_reason: StopReason::EndTurn,
+            stop_detail: None,
         };
 
         let json = serde_json::to_string(&chunk).unwrap();
@@ -360,6 +656,7 @@ mod tests {
                 input_tokens: 5,
                 output_tokens: 3,
             },
+            stop_detail: None,
             metadata: HashMap::new(),
         };
 
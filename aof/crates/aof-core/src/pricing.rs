@@ -0,0 +1,190 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::{AofError, AofResult, ModelProvider, Usage};
+
+/// Price of a model in USD per 1M tokens
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    /// USD per 1M input (prompt) tokens
+    pub input_per_million: f64,
+
+    /// USD per 1M output (completion) tokens
+    pub output_per_million: f64,
+}
+
+impl ModelPrice {
+    pub const fn new(input_per_million: f64, output_per_million: f64) -> Self {
+        Self {
+            input_per_million,
+            output_per_million,
+        }
+    }
+}
+
+/// Built-in list prices (USD per 1M tokens)
+const DEFAULT_PRICES: &[(&str, ModelPrice)] = &[
+    // Anthropic
+    ("claude-opus-4", ModelPrice::new(15.0, 75.0)),
+    ("claude-sonnet-4", ModelPrice::new(3.0, 15.0)),
+    ("claude-haiku-4", ModelPrice::new(1.0, 5.0)),
+    ("claude-3-7-sonnet", ModelPrice::new(3.0, 15.0)),
+    ("claude-3-5-sonnet", ModelPrice::new(3.0, 15.0)),
+    ("claude-3-5-haiku", ModelPrice::new(0.8, 4.0)),
+    ("claude-3-opus", ModelPrice::new(15.0, 75.0)),
+    ("claude-3-haiku", ModelPrice::new(0.25, 1.25)),
+    // OpenAI
+    ("gpt-4.1", ModelPrice::new(2.0, 8.0)),
+    ("gpt-4.1-mini", ModelPrice::new(0.4, 1.6)),
+    ("gpt-4.1-nano", ModelPrice::new(0.1, 0.4)),
+    ("gpt-4o", ModelPrice::new(2.5, 10.0)),
+    ("gpt-4o-mini", ModelPrice::new(0.15, 0.6)),
+    ("gpt-4-turbo", ModelPrice::new(10.0, 30.0)),
+    ("gpt-4", ModelPrice::new(30.0, 60.0)),
+    ("gpt-3.5-turbo", ModelPrice::new(0.5, 1.5)),
+    ("o1", ModelPrice::new(15.0, 60.0)),
+    ("o1-mini", ModelPrice::new(1.1, 4.4)),
+    ("o3", ModelPrice::new(2.0, 8.0)),
+    ("o3-mini", ModelPrice::new(1.1, 4.4)),
+    ("o4-mini", ModelPrice::new(1.1, 4.4)),
+    // Google
+    ("gemini-2.5-pro", ModelPrice::new(1.25, 10.0)),
+    ("gemini-2.5-flash", ModelPrice::new(0.3, 2.5)),
+    ("gemini-2.0-flash", ModelPrice::new(0.1, 0.4)),
+    ("gemini-1.5-pro", ModelPrice::new(1.25, 5.0)),
+    ("gemini-1.5-flash", ModelPrice::new(0.075, 0.3)),
+    // Groq
+    ("llama-3.3-70b-versatile", ModelPrice::new(0.59, 0.79)),
+    ("llama-3.1-8b-instant", ModelPrice::new(0.05, 0.08)),
+];
+
+/// Per-model token prices used to estimate run cost
+///
+/// Model ids match on the longest known prefix, so dated ids such as
+/// `claude-3-5-sonnet-20241022` use the `claude-3-5-sonnet` price. A
+/// `provider:` prefix is ignored. Local models (Ollama) have no price.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Pricing {
+    prices: HashMap<String, ModelPrice>,
+}
+
+impl Default for Pricing {
+    fn default() -> Self {
+        Self {
+            prices: DEFAULT_PRICES
+                .iter()
+                .map(|(model, price)| (model.to_string(), *price))
+                .collect(),
+        }
+    }
+}
+
+impl Pricing {
+    /// Empty pricing table
+    pub fn empty() -> Self {
+        Self {
+            prices: HashMap::new(),
+        }
+    }
+
+    /// Default prices overridden by a JSON file
+    ///
+    /// The file maps model ids to prices:
+    /// `{"gpt-4o": {"input_per_million": 2.5, "output_per_million": 10.0}}`
+    pub fn from_file(path: impl AsRef<Path>) -> AofResult<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+        let overrides: Pricing = serde_json::from_str(&content).map_err(|e| {
+            AofError::config(format!(
+                "Invalid pricing file {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+
+        let mut pricing = Self::default();
+        pricing.prices.extend(overrides.prices);
+        Ok(pricing)
+    }
+
+    /// Set (or replace) the price for a model id prefix
+    pub fn with_price(mut self, model: impl Into<String>, price: ModelPrice) -> Self {
+        self.prices.insert(model.into(), price);
+        self
+    }
+
+    /// Price for a model id, if known
+    pub fn price_for(&self, model: &str) -> Option<ModelPrice> {
+        let model = match model.split_once(':') {
+            Some((provider, rest)) if ModelProvider::from_name(provider).is_some() => rest,
+            _ => model,
+        };
+        let model = model.to_lowercase();
+
+        self.prices
+            .iter()
+            .filter(|(prefix, _)| model.starts_with(prefix.to_lowercase().as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, price)| *price)
+    }
+}
+
+impl Usage {
+    /// Estimated cost in USD, or `None` when the model has no known price
+    pub fn cost(&self, model: &str, pricing: &Pricing) -> Option<f64> {
+        let price = pricing.price_for(model)?;
+        Some(
+            (self.input_tokens as f64 * price.input_per_million
+                + self.output_tokens as f64 * price.output_per_million)
+                / 1_000_000.0,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_cost() {
+        let usage = Usage {
+            input_tokens: 200_000,
+            output_tokens: 50_000,
+        };
+        let pricing = Pricing::default();
+
+        // 0.2M * $3 + 0.05M * $15
+        let cost = usage.cost("claude-3-5-sonnet-20241022", &pricing).unwrap();
+        assert!((cost - 1.35).abs() < 1e-9);
+
+        // Longest prefix wins and provider prefixes are ignored
+        let cost = usage.cost("openai:gpt-4o-mini", &pricing).unwrap();
+        assert!((cost - 0.06).abs() < 1e-9);
+
+        assert_eq!(usage.cost("llama3.1:8b", &pricing), None);
+    }
+
+    #[test]
+    fn test_pricing_overrides_from_file() {
+        let path = std::env::temp_dir().join(format!("aof-pricing-{}.json", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"{"gpt-4o": {"input_per_million": 1.0, "output_per_million": 2.0},
+                "llama3": {"input_per_million": 0.0, "output_per_million": 0.0}}"#,
+        )
+        .unwrap();
+
+        let pricing = Pricing::from_file(&path).unwrap();
+        assert_eq!(pricing.price_for("gpt-4o"), Some(ModelPrice::new(1.0, 2.0)));
+        assert_eq!(pricing.price_for("llama3.1:8b"), Some(ModelPrice::new(0.0, 0.0)));
+        // Defaults are kept for models not in the file
+        assert_eq!(
+            pricing.price_for("claude-3-haiku-20240307"),
+            Some(ModelPrice::new(0.25, 1.25))
+        );
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
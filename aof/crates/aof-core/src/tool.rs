@@ -43,6 +43,14 @@ pub trait ToolExecutor: Send + Sync {
 
     /// Get specific tool
     fn get_tool(&self, name: &str) -> Option<Arc<dyn Tool>>;
+
+    /// Whether the services behind the tools (e.g. MCP servers) are reachable
+    ///
+    /// Must be cheap, as health endpoints call it; executors without remote
+    /// connections keep the default.
+    fn health_check(&self) -> AofResult<()> {
+        Ok(())
+    }
 }
 
 /// Tool configuration
@@ -140,6 +148,26 @@ pub struct ToolResult {
 
     /// Execution time (ms)
     pub execution_time_ms: u64,
+
+    /// Attempts made, including the final one (1 when not retried)
+    #[serde(default = "default_attempts")]
+    pub attempts: u32,
+}
+
+fn default_attempts() -> u32 {
+    1
+}
+
+impl Default for ToolResult {
+    fn default() -> Self {
+        Self {
+            success: false,
+            data: serde_json::Value::Null,
+            error: None,
+            execution_time_ms: 0,
+            attempts: default_attempts(),
+        }
+    }
 }
 
 impl ToolResult {
@@ -148,18 +176,15 @@ impl ToolResult {
         Self {
             success: true,
             data,
-            error: None,
-            execution_time_ms: 0,
+            ..Default::default()
         }
     }
 
     /// Create error result
     pub fn error(msg: impl Into<String>) -> Self {
         Self {
-            success: false,
-            data: serde_json::Value::Null,
             error: Some(msg.into()),
-            execution_time_ms: 0,
+            ..Default::default()
         }
     }
 
@@ -168,6 +193,11 @@ impl ToolResult {
         self.execution_time_ms = ms;
         self
     }
+
+    /// Whether the tool needed more than one attempt
+    pub fn retried(&self) -> bool {
+        self.attempts > 1
+    }
 }
 
 /// Tool call (from model)
@@ -0,0 +1,352 @@
+//! Semantic validation of parsed configuration
+//!
+//! Parsing only checks that a config has the right shape; [`Validate`]
+//! catches values that parse fine but cannot work at runtime, such as
+//! `max_iterations: 0` or an out-of-range temperature.
+
+use std::fmt;
+
+use crate::{AgentConfig, AofError, KeepAlive, ModelProvider};
+
+/// Providers the runtime knows how to construct
+pub const KNOWN_PROVIDERS: &[&str] = &[
+    "anthropic",
+    "openai",
+    "google",
+    "groq",
+    "bedrock",
+    "azure",
+    "ollama",
+];
+
+/// A single semantic problem found in a configuration
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError {
+    /// Offending field (e.g. `temperature`, `tools[1]`)
+    pub field: String,
+
+    /// What is wrong with it
+    pub message: String,
+}
+
+impl ConfigError {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// Aggregates every problem into a single `AofError::Config`
+impl From<Vec<ConfigError>> for AofError {
+    fn from(errors: Vec<ConfigError>) -> Self {
+        let details = errors
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("; ");
+        AofError::config(format!("{} validation error(s): {}", errors.len(), details))
+    }
+}
+
+/// Configuration that can check its own values
+pub trait Validate {
+    /// Check the configuration, reporting every problem at once
+    fn validate(&self) -> Result<(), Vec<ConfigError>>;
+}
+
+impl Validate for AgentConfig {
+    fn validate(&self) -> Result<(), Vec<ConfigError>> {
+        let mut errors = Vec::new();
+
+        if self.name.trim().is_empty() {
+            errors.push(ConfigError::new("name", "must not be empty"));
+        }
+
+        if self.model.trim().is_empty() {
+            errors.push(ConfigError::new("model", "must not be empty"));
+        } else if let Err(message) = infer_provider(self) {
+            errors.push(ConfigError::new("model", message));
+        }
+
+        if !(0.0..=2.0).contains(&self.temperature) {
+            errors.push(ConfigError::new(
+                "temperature",
+                format!("must be between 0.0 and 2.0 (got {})", self.temperature),
+            ));
+        }
+
+        if self.max_iterations < 1 {
+            errors.push(ConfigError::new("max_iterations", "must be at least 1"));
+        }
+
+        if let Some(threshold) = self.warn_iteration_threshold {
+            if threshold < 1 || threshold > self.max_iterations {
+                errors.push(ConfigError::new(
+                    "warn_iteration_threshold",
+                    format!(
+                        "must be between 1 and max_iterations ({}) (got {})",
+                        self.max_iterations, threshold
+                    ),
+                ));
+            }
+        }
+
+        for (index, tool) in self.tools.iter().enumerate() {
+            if tool.trim().is_empty() {
+                errors.push(ConfigError::new(
+                    format!("tools[{}]", index),
+                    "tool name must not be empty",
+                ));
+            }
+        }
+
+        let mut weights: Vec<_> = self.tool_weights.iter().collect();
+        weights.sort();
+        for (tool, weight) in weights {
+            if *weight < 1 {
+                errors.push(ConfigError::new(
+                    format!("tool_weights.{}", tool),
+                    "must be at least 1",
+                ));
+            }
+        }
+
+        if let Some(policy) = &self.model_retry_policy {
+            if policy.max_attempts < 1 {
+                errors.push(ConfigError::new(
+                    "model_retry_policy.max_attempts",
+                    "must be at least 1",
+                ));
+            }
+        }
+
+        if let Some(KeepAlive::Duration(duration)) = &self.keep_alive {
+            if !is_go_duration(duration) {
+                errors.push(ConfigError::new(
+                    "keep_alive",
+                    format!(
+                        "must be seconds or a duration such as '30m' or '1h30m' (got '{}')",
+                        duration
+                    ),
+                ));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Whether `value` is a duration Ollama accepts for `keep_alive`
+/// (Go syntax: `-1`, `0`, `30s`, `1.5h`, `1h30m`)
+fn is_go_duration(value: &str) -> bool {
+    let value = value.strip_prefix('-').unwrap_or(value);
+    if value == "0" || value.parse::<u64>().is_ok() {
+        return true;
+    }
+
+    let mut rest = value;
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        if rest[..number_len].parse::<f64>().is_err() {
+            return false;
+        }
+        rest = &rest[number_len..];
+
+        let Some(unit) = ["ns", "us", "µs", "ms", "s", "m", "h"]
+            .into_iter()
+            .filter(|unit| rest.starts_with(unit))
+            .max_by_key(|unit| unit.len())
+        else {
+            return false;
+        };
+        rest = &rest[unit.len()..];
+    }
+    !value.is_empty()
+}
+
+/// Work out which provider will serve the configured model
+///
+/// Uses an explicit `provider` field when present, otherwise infers it from
+/// the model id (`provider:model` prefix or a well-known model family).
+pub fn infer_provider(config: &AgentConfig) -> Result<String, String> {
+    if let Some(provider) = config.extra.get("provider").and_then(|p| p.as_str()) {
+        let provider = provider.to_lowercase();
+        return if KNOWN_PROVIDERS.contains(&provider.as_str()) {
+            Ok(provider)
+        } else {
+            Err(format!(
+                "unknown provider '{}' (expected one of: {})",
+                provider,
+                KNOWN_PROVIDERS.join(", ")
+            ))
+        };
+    }
+
+    if let Some(provider) = ModelProvider::infer_from_model(&config.model) {
+        return Ok(provider.name().to_string());
+    }
+
+    match config.model.split_once(':') {
+        Some((provider, _)) => Err(format!(
+            "unknown provider '{}' in '{}' (expected one of: {})",
+            provider.to_lowercase(),
+            config.model,
+            KNOWN_PROVIDERS.join(", ")
+        )),
+        None => Err(format!(
+            "cannot infer provider for '{}'; use the '<provider>:<model>' form",
+            config.model
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ContextOverflowPolicy, EmptyResponsePolicy};
+
+    fn parse(yaml: &str) -> AgentConfig {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_valid_config() {
+        let config = parse(
+            r#"
+name: echo-agent
+model: ollama:echo
+max_iterations: 2
+temperature: 0.0
+system_prompt: "Repeat the user's request."
+"#,
+        );
+
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_invalid_config_reports_every_problem() {
+        let config = parse(
+            r#"
+name: ""
+model: ""
+max_iterations: 0
+temperature: 3.0
+"#,
+        );
+
+        let errors = config.validate().unwrap_err();
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, ["name", "model", "temperature", "max_iterations"]);
+
+        let err = AofError::from(errors);
+        assert!(matches!(err, AofError::Config(_)));
+        let message = err.to_string();
+        assert!(message.contains("4 validation error(s)"));
+        assert!(message.contains("max_iterations: must be at least 1"));
+        assert!(message.contains("temperature: must be between 0.0 and 2.0 (got 3)"));
+    }
+
+    #[test]
+    fn test_warn_threshold_beyond_max_iterations() {
+        let config = parse(
+            r#"
+name: agent
+model: ollama:echo
+max_iterations: 5
+warn_iteration_threshold: 8
+"#,
+        );
+
+        let errors = config.validate().unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "warn_iteration_threshold");
+        assert_eq!(
+            errors[0].message,
+            "must be between 1 and max_iterations (5) (got 8)"
+        );
+    }
+
+    #[test]
+    fn test_typed_options() {
+        let config = parse(
+            r#"
+name: agent
+model: ollama:llama3
+fail_fast: true
+tool_weights:
+  run_build: 0
+cacheable_tools: [kubectl_get]
+model_retry_policy:
+  max_attempts: 0
+keep_alive: 30 minutes
+on_context_overflow: prune
+on_empty_response: retry
+require_all_tools: true
+"#,
+        );
+        assert!(config.fail_fast);
+        assert_eq!(config.cacheable_tools, ["kubectl_get"]);
+        assert_eq!(config.on_context_overflow, ContextOverflowPolicy::Prune);
+        assert_eq!(config.on_empty_response, EmptyResponsePolicy::Retry);
+        assert!(config.require_all_tools);
+        assert!(config.extra.is_empty());
+
+        let errors = config.validate().unwrap_err();
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            [
+                "tool_weights.run_build",
+                "model_retry_policy.max_attempts",
+                "keep_alive"
+            ]
+        );
+
+        for keep_alive in ["keep_alive: 30m", "keep_alive: 1h30m", "keep_alive: -1"] {
+            let config = parse(&format!(
+                "name: agent\nmodel: ollama:llama3\n{}",
+                keep_alive
+            ));
+            assert!(config.validate().is_ok(), "{}", keep_alive);
+        }
+
+        // A misspelled value no longer falls back to the default
+        assert!(serde_yaml::from_str::<AgentConfig>(
+            "name: agent\nmodel: ollama:llama3\non_context_overflow: sumarize"
+        )
+        .is_err());
+        assert!(serde_yaml::from_str::<AgentConfig>(
+            "name: agent\nmodel: ollama:llama3\non_empty_response: retyr"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_unknown_provider() {
+        let config = parse(
+            r#"
+name: agent
+model: acme:model-1
+"#,
+        );
+
+        let errors = config.validate().unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.starts_with("unknown provider 'acme'"));
+    }
+}
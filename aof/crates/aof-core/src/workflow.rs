@@ -0,0 +1,231 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{AofError, AofResult};
+
+/// Workflow configuration
+///
+/// A workflow is an ordered list of agent steps. Each step's input is a
+/// template that may reference the workflow input (`{{ input }}`) and the
+/// output of any earlier step (`{{ steps.N.output }}`, zero-based).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowConfig {
+    /// Workflow name
+    pub name: String,
+
+    /// Workflow description
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Steps executed in order
+    pub steps: Vec<WorkflowStep>,
+}
+
+/// A single agent step in a workflow
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowStep {
+    /// Step name (defaults to the step index)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Path to the agent configuration file, relative to the workflow file
+    pub agent: String,
+
+    /// Input template for the agent
+    #[serde(default = "default_step_input")]
+    pub input: String,
+
+    /// What to do when the step fails
+    #[serde(default)]
+    pub on_error: OnError,
+}
+
+/// Step failure policy
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OnError {
+    /// Stop the workflow at the failed step
+    #[default]
+    Abort,
+
+    /// Record the failure and continue with the next step
+    Continue,
+}
+
+fn default_step_input() -> String {
+    "{{ input }}".to_string()
+}
+
+impl WorkflowConfig {
+    /// Check the workflow for structural problems before running it
+    pub fn validate(&self) -> AofResult<()> {
+        if self.name.is_empty() {
+            return Err(AofError::config("Workflow name cannot be empty"));
+        }
+
+        if self.steps.is_empty() {
+            return Err(AofError::config(format!(
+                "Workflow '{}' has no steps",
+                self.name
+            )));
+        }
+
+        for (index, step) in self.steps.iter().enumerate() {
+            if step.agent.is_empty() {
+                return Err(AofError::config(format!(
+                    "Step {} has no agent configured",
+                    index
+                )));
+            }
+
+            for reference in step_references(&step.input)? {
+                if reference >= index {
+                    return Err(AofError::config(format!(
+                        "Step {} references output of step {}, which has not run yet",
+                        index, reference
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl WorkflowStep {
+    /// Display name for the step at `index`
+    pub fn display_name(&self, index: usize) -> String {
+        self.name.clone().unwrap_or_else(|| format!("step-{}", index))
+    }
+}
+
+/// Render a step input template
+///
+/// Replaces `{{ input }}` with the workflow input and `{{ steps.N.output }}`
+/// with the output of step `N`. Unknown placeholders are left untouched.
+pub fn render_step_input(template: &str, input: &str, outputs: &[String]) -> AofResult<String> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start..].find("}}") else {
+            break;
+        };
+        let end = start + len + 2;
+
+        rendered.push_str(&rest[..start]);
+        match parse_placeholder(&rest[start + 2..end - 2])? {
+            Some(Placeholder::Input) => rendered.push_str(input),
+            Some(Placeholder::StepOutput(index)) => {
+                let output = outputs.get(index).ok_or_else(|| {
+                    AofError::config(format!("No output available for step {}", index))
+                })?;
+                rendered.push_str(output);
+            }
+            None => rendered.push_str(&rest[start..end]),
+        }
+        rest = &rest[end..];
+    }
+
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+enum Placeholder {
+    Input,
+    StepOutput(usize),
+}
+
+fn parse_placeholder(expr: &str) -> AofResult<Option<Placeholder>> {
+    let expr = expr.trim();
+    if expr == "input" {
+        return Ok(Some(Placeholder::Input));
+    }
+
+    let parts: Vec<&str> = expr.split('.').collect();
+    match parts.as_slice() {
+        ["steps", index, "output"] => index
+            .parse()
+            .map(|index| Some(Placeholder::StepOutput(index)))
+            .map_err(|_| AofError::config(format!("Invalid step index in '{{{{ {} }}}}'", expr))),
+        _ => Ok(None),
+    }
+}
+
+/// Step indices referenced by a template
+fn step_references(template: &str) -> AofResult<Vec<usize>> {
+    let mut references = Vec::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start..].find("}}") else {
+            break;
+        };
+        if let Some(Placeholder::StepOutput(index)) =
+            parse_placeholder(&rest[start + 2..start + len])?
+        {
+            references.push(index);
+        }
+        rest = &rest[start + len + 2..];
+    }
+
+    Ok(references)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workflow_deserialization() {
+        let yaml = r#"
+name: triage
+steps:
+  - name: collect
+    agent: collector.yaml
+  - agent: summarizer.yaml
+    input: "Summarize: {{ steps.0.output }}"
+    on_error: continue
+"#;
+
+        let workflow: WorkflowConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(workflow.steps.len(), 2);
+        assert_eq!(workflow.steps[0].input, "{{ input }}");
+        assert_eq!(workflow.steps[0].on_error, OnError::Abort);
+        assert_eq!(workflow.steps[1].on_error, OnError::Continue);
+        assert_eq!(workflow.steps[1].display_name(1), "step-1");
+        assert!(workflow.validate().is_ok());
+    }
+
+    #[test]
+    fn test_render_step_input() {
+        let outputs = vec!["disk is full".to_string()];
+        let rendered = render_step_input(
+            "Input: {{input}} / Previous: {{ steps.0.output }} / {{ other }}",
+            "check node-1",
+            &outputs,
+        )
+        .unwrap();
+
+        assert_eq!(
+            rendered,
+            "Input: check node-1 / Previous: disk is full / {{ other }}"
+        );
+        assert!(render_step_input("{{ steps.1.output }}", "", &outputs).is_err());
+    }
+
+    #[test]
+    fn test_validate_rejects_forward_reference() {
+        let workflow = WorkflowConfig {
+            name: "bad".to_string(),
+            description: None,
+            steps: vec![WorkflowStep {
+                name: None,
+                agent: "agent.yaml".to_string(),
+                input: "{{ steps.0.output }}".to_string(),
+                on_error: OnError::Abort,
+            }],
+        };
+
+        assert!(workflow.validate().is_err());
+    }
+}
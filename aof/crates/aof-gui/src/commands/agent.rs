@@ -1,10 +1,11 @@
 // Agent Commands - Tauri handlers for agent operations with aof-runtime integration
 
-use aof_core::{AgentConfig, AgentContext, ExecutionMetadata, MessageRole, ModelConfig, ModelProvider};
+use aof_core::{AgentConfig, AgentContext, AofError, ExecutionMetadata, MessageRole, ModelConfig, ModelProvider, Validate};
 use aof_llm::ProviderFactory;
-use aof_runtime::{AgentExecutor, Task, TaskStatus};
+use aof_runtime::{AgentExecutor, StreamEvent, Task, TaskStatus};
 use serde::{Deserialize, Serialize};
 use tauri::{Emitter, State};
+use tokio::sync::mpsc;
 use uuid::Uuid;
 
 use crate::state::AppState;
@@ -107,8 +108,11 @@ pub async fn agent_run(
     window: tauri::Window,
 ) -> Result<AgentRunResponse, String> {
     // Parse the YAML configuration
-    let config: AgentConfig = serde_yaml::from_str(&request.config_yaml)
+    let config = AgentConfig::from_yaml(&request.config_yaml)
         .map_err(|e| format!("Failed to parse agent config: {}", e))?;
+    config
+        .validate()
+        .map_err(|errors| AofError::from(errors).to_string())?;
 
     let agent_id = Uuid::new_v4().to_string();
     let agent_name = config.name.clone();
@@ -156,21 +160,14 @@ pub async fn agent_run(
     }
 
     // Determine provider from model name and get appropriate API key
-    let (provider, api_key_var) = if config.model.starts_with("gemini") || config.model.starts_with("google/") {
-        (ModelProvider::Google, "GOOGLE_API_KEY")
-    } else if config.model.starts_with("claude") || config.model.starts_with("anthropic") {
-        (ModelProvider::Anthropic, "ANTHROPIC_API_KEY")
-    } else if config.model.starts_with("gpt") || config.model.starts_with("openai") || config.model.starts_with("o1") || config.model.starts_with("o3") {
-        (ModelProvider::OpenAI, "OPENAI_API_KEY")
-    } else if config.model.starts_with("llama") && !config.model.contains("groq") {
-        (ModelProvider::Ollama, "OLLAMA_HOST")
-    } else if config.model.starts_with("mistral") || config.model.starts_with("codellama") || config.model.starts_with("phi") {
-        (ModelProvider::Ollama, "OLLAMA_HOST")
-    } else if config.model.contains("groq") || config.model.contains("mixtral") {
-        (ModelProvider::Groq, "GROQ_API_KEY")
-    } else {
-        // Default to trying Google for unknown models
-        (ModelProvider::Google, "GOOGLE_API_KEY")
+    // Default to trying Google for unknown models
+    let provider = ModelProvider::infer_from_model(&config.model).unwrap_or(ModelProvider::Google);
+    let api_key_var = match provider {
+        ModelProvider::Anthropic => "ANTHROPIC_API_KEY",
+        ModelProvider::OpenAI => "OPENAI_API_KEY",
+        ModelProvider::Groq => "GROQ_API_KEY",
+        ModelProvider::Ollama => "OLLAMA_HOST",
+        _ => "GOOGLE_API_KEY",
     };
 
     // Get API key - first try database, then fall back to environment variable
@@ -186,30 +183,20 @@ pub async fn agent_run(
     let api_key = if provider == ModelProvider::Ollama {
         std::env::var("OLLAMA_HOST").unwrap_or_else(|_| "http://localhost:11434".to_string())
     } else {
-        // Try to get from database first
-        let db_key = if let Ok(db) = state.get_db().await {
-            tracing::info!("Checking database for {} API key...", provider_name);
-            match sqlx::query_scalar::<_, String>("SELECT api_key FROM provider_api_keys WHERE provider = ?")
-                .bind(provider_name)
-                .fetch_optional(&db)
-                .await
-            {
-                Ok(Some(key)) => {
-                    tracing::info!("✓ Found API key in database for {} (length: {})", provider_name, key.len());
-                    Some(key)
-                }
-                Ok(None) => {
-                    tracing::warn!("No API key found in database for {}", provider_name);
-                    None
-                }
-                Err(e) => {
-                    tracing::error!("Database query failed for {} API key: {}", provider_name, e);
-                    None
-                }
+        // Try the saved keys first (cached after the first lookup)
+        let db_key = match state.provider_api_key(provider_name).await {
+            Ok(Some(key)) => {
+                tracing::info!("✓ Found saved API key for {} (length: {})", provider_name, key.len());
+                Some(key)
+            }
+            Ok(None) => {
+                tracing::warn!("No API key found in database for {}", provider_name);
+                None
+            }
+            Err(e) => {
+                tracing::error!("Failed to load saved API key for {}: {}", provider_name, e);
+                None
             }
-        } else {
-            tracing::warn!("Could not get database connection");
-            None
         };
 
         // Fall back to environment variable if not in database
@@ -267,6 +254,9 @@ pub async fn agent_run(
     // Submit task to orchestrator
     let _handle = state.orchestrator.submit_task(task);
 
+    // Register before spawning so the agent can be stopped right away
+    let cancel_rx = state.register_cancellation(&agent_id).await;
+
     // Spawn background task for agent execution
     let state_clone = state.inner().clone();
     let window_clone = window.clone();
@@ -275,15 +265,17 @@ pub async fn agent_run(
 
     tokio::spawn(async move {
         execute_agent_with_runtime(
-            agent_id_clone,
+            agent_id_clone.clone(),
             config,
             input,
             api_key,
             provider,
-            state_clone,
+            cancel_rx,
+            state_clone.clone(),
             window_clone,
         )
         .await;
+        state_clone.unregister_cancellation(&agent_id_clone).await;
     });
 
     Ok(AgentRunResponse {
@@ -300,6 +292,7 @@ async fn execute_agent_with_runtime(
     input: String,
     api_key: String,
     provider: ModelProvider,
+    cancel_rx: tokio::sync::oneshot::Receiver<()>,
     state: AppState,
     window: tauri::Window,
 ) {
@@ -474,6 +467,7 @@ async fn execute_agent_with_runtime(
                             data: result,
                             error: None,
                             execution_time_ms,
+                            ..Default::default()
                         })
                     }
 
@@ -534,9 +528,9 @@ async fn execute_agent_with_runtime(
     tracing::info!("System prompt: {:?}", config.system_prompt.as_ref().map(|s| s.chars().take(100).collect::<String>()));
     tracing::info!("Tools: {:?}", config.tools);
 
-    // Execute agent
+    // Execute agent, forwarding stream events to the frontend as they happen
     let start_time = std::time::Instant::now();
-    tracing::info!("Starting executor.execute()...");
+    tracing::info!("Starting executor.execute_streaming()...");
 
     let _ = window.emit(
         "agent-output",
@@ -547,17 +541,36 @@ async fn execute_agent_with_runtime(
         }),
     );
 
-    let result = executor.execute(&mut ctx).await;
+    let (stream_tx, mut stream_rx) = mpsc::channel(100);
+    let forward_events = async {
+        while let Some(event) = stream_rx.recv().await {
+            if let Some((name, payload)) = stream_event_to_json(&agent_id, &event) {
+                let _ = window.emit(name, payload);
+            }
+        }
+    };
+    let (result, ()) = tokio::join!(
+        executor.execute_streaming_cancellable(&mut ctx, stream_tx, cancel_rx),
+        forward_events
+    );
+
+    // A run that ends after its cancellation was taken by `agent_stop` was stopped
+    let still_registered = state.unregister_cancellation(&agent_id).await;
+    if result.is_err() && !still_registered {
+        tracing::info!("Agent {} stopped after {}ms", agent_id, start_time.elapsed().as_millis());
+        handle_execution_stopped(&agent_id, &state, &window).await;
+        return;
+    }
 
     let elapsed = start_time.elapsed().as_millis();
 
     // Detailed logging based on result
     match &result {
         Ok(output) => {
-            tracing::info!("executor.execute() SUCCESS in {}ms, output length: {}", elapsed, output.len());
+            tracing::info!("executor.execute_streaming() SUCCESS in {}ms, output length: {}", elapsed, output.len());
         }
         Err(e) => {
-            tracing::error!("executor.execute() FAILED in {}ms: {:?}", elapsed, e);
+            tracing::error!("executor.execute_streaming() FAILED in {}ms: {:?}", elapsed, e);
             // Also emit the error to the frontend immediately
             let _ = window.emit(
                 "agent-output",
@@ -570,40 +583,11 @@ async fn execute_agent_with_runtime(
         }
     }
 
-    let _ = window.emit(
-        "agent-output",
-        serde_json::json!({
-            "agent_id": agent_id,
-            "content": format!("LLM API call completed in {}ms", elapsed),
-            "timestamp": chrono::Utc::now().to_rfc3339(),
-        }),
-    );
-
     let execution_time = elapsed as u64;
 
     // Handle execution result
     match result {
         Ok(output) => {
-            // Stream output chunks to frontend
-            let output_lines: Vec<&str> = output.lines().collect();
-            for (i, line) in output_lines.iter().enumerate() {
-                if !line.trim().is_empty() {
-                    let _ = window.emit(
-                        "agent-output",
-                        serde_json::json!({
-                            "agent_id": agent_id,
-                            "content": line,
-                            "timestamp": chrono::Utc::now().to_rfc3339(),
-                        }),
-                    );
-                }
-
-                // Add small delay for natural streaming effect
-                if i < output_lines.len() - 1 {
-                    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
-                }
-            }
-
             // Update final state
             {
                 let mut agents = state.agents.write().await;
@@ -633,6 +617,96 @@ async fn execute_agent_with_runtime(
     }
 }
 
+/// Map a runtime stream event to the Tauri event the frontend listens for
+///
+/// Text deltas go to `agent-stream`, tool calls and iterations to the
+/// `agent-output` log, and `Done` to `agent-metrics`. Completion and errors
+/// are emitted once the run returns, with the full execution metadata, so
+/// `Done` and `Error` produce no `agent-completed`/`agent-error` here.
+fn stream_event_to_json(agent_id: &str, event: &StreamEvent) -> Option<(&'static str, serde_json::Value)> {
+    let log = |content: String, extra: serde_json::Value| {
+        let mut payload = serde_json::json!({
+            "agent_id": agent_id,
+            "content": content,
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+        });
+        if let (Some(payload), serde_json::Value::Object(extra)) = (payload.as_object_mut(), extra) {
+            payload.extend(extra);
+        }
+        ("agent-output", payload)
+    };
+
+    match event {
+        StreamEvent::TextDelta { delta, .. } => Some((
+            "agent-stream",
+            serde_json::json!({
+                "agent_id": agent_id,
+                "content": delta,
+            }),
+        )),
+        StreamEvent::ToolCallStart { tool_name, tool_id, arguments } => Some(log(
+            format!("Calling tool {}", tool_name),
+            serde_json::json!({
+                "tool_name": tool_name,
+                "tool_id": tool_id,
+                "arguments": arguments,
+            }),
+        )),
+        StreamEvent::ToolCallComplete { tool_name, tool_id, success, execution_time_ms, error, .. } => {
+            let content = match error {
+                Some(error) if !success => format!("Tool {} failed: {}", tool_name, error),
+                _ => format!("Tool {} completed in {}ms", tool_name, execution_time_ms),
+            };
+            Some(log(
+                content,
+                serde_json::json!({
+                    "tool_name": tool_name,
+                    "tool_id": tool_id,
+                    "success": success,
+                    "execution_time_ms": execution_time_ms,
+                }),
+            ))
+        }
+        StreamEvent::Thinking { content } => Some(log(format!("Thinking: {}", content), serde_json::Value::Null)),
+        StreamEvent::Warning { message } => Some(log(format!("Warning: {}", message), serde_json::Value::Null)),
+        StreamEvent::IterationStart { iteration, max_iterations } => Some(log(
+            format!("Iteration {}/{}", iteration, max_iterations),
+            serde_json::Value::Null,
+        )),
+        StreamEvent::Done { input_tokens, output_tokens, .. } => Some((
+            "agent-metrics",
+            serde_json::json!({
+                "agent_id": agent_id,
+                "input_tokens": input_tokens,
+                "output_tokens": output_tokens,
+            }),
+        )),
+        StreamEvent::IterationComplete { .. } | StreamEvent::Error { .. } => None,
+    }
+}
+
+/// Mark an agent stopped once its execution has actually ended
+async fn handle_execution_stopped(agent_id: &str, state: &AppState, window: &tauri::Window) {
+    {
+        let mut agents = state.agents.write().await;
+        if let Some(runtime) = agents.get_mut(agent_id) {
+            runtime.status = AgentStatus::Stopped;
+            runtime.finished_at = Some(chrono::Utc::now());
+            runtime.output.push(format!(
+                "[{}] Agent stopped by user",
+                chrono::Utc::now().format("%H:%M:%S")
+            ));
+        }
+    }
+
+    let _ = window.emit(
+        "agent-stopped",
+        serde_json::json!({
+            "agent_id": agent_id,
+        }),
+    );
+}
+
 /// Handle execution errors with proper state updates and events
 async fn handle_execution_error(
     agent_id: &str,
@@ -671,29 +745,16 @@ async fn handle_execution_error(
 pub async fn agent_stop(
     agent_id: String,
     state: State<'_, AppState>,
-    window: tauri::Window,
 ) -> Result<(), String> {
-    // Try to cancel the task in orchestrator
-    let _ = state.orchestrator.cancel_task(&agent_id).await;
-
-    // Update agent state
-    let mut agents = state.agents.write().await;
+    let agents = state.agents.read().await;
 
-    if let Some(runtime) = agents.get_mut(&agent_id) {
+    if let Some(runtime) = agents.get(&agent_id) {
         if runtime.status == AgentStatus::Running || runtime.status == AgentStatus::Pending {
-            runtime.status = AgentStatus::Stopped;
-            runtime.finished_at = Some(chrono::Utc::now());
-            runtime.output.push(format!(
-                "[{}] Agent stopped by user",
-                chrono::Utc::now().format("%H:%M:%S")
-            ));
-
-            let _ = window.emit(
-                "agent-stopped",
-                serde_json::json!({
-                    "agent_id": agent_id,
-                }),
-            );
+            // The execution task marks the agent stopped once it has ended
+            if !state.cancel_agent(&agent_id).await {
+                return Err(format!("Agent {} is already stopping", agent_id));
+            }
+            let _ = state.orchestrator.cancel_task(&agent_id).await;
 
             Ok(())
         } else {
@@ -788,3 +849,70 @@ pub async fn agent_orchestrator_stats(
         "available_permits": stats.available_permits,
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_delta_maps_to_stream_chunk() {
+        let event = StreamEvent::TextDelta {
+            delta: "Hello".to_string(),
+            timestamp: None,
+        };
+
+        let (name, payload) = stream_event_to_json("agent-1", &event).unwrap();
+        assert_eq!(name, "agent-stream");
+        assert_eq!(payload, serde_json::json!({ "agent_id": "agent-1", "content": "Hello" }));
+    }
+
+    #[test]
+    fn test_tool_calls_map_to_output_log() {
+        let start = StreamEvent::ToolCallStart {
+            tool_name: "kubectl".to_string(),
+            tool_id: "call_1".to_string(),
+            arguments: serde_json::json!({ "args": ["get", "pods"] }),
+        };
+        let (name, payload) = stream_event_to_json("agent-1", &start).unwrap();
+        assert_eq!(name, "agent-output");
+        assert_eq!(payload["content"], "Calling tool kubectl");
+        assert_eq!(payload["tool_id"], "call_1");
+        assert_eq!(payload["arguments"]["args"][1], "pods");
+
+        let failed = StreamEvent::ToolCallComplete {
+            tool_name: "kubectl".to_string(),
+            tool_id: "call_1".to_string(),
+            success: false,
+            execution_time_ms: 12,
+            attempts: 1,
+            error: Some("connection refused".to_string()),
+        };
+        let (name, payload) = stream_event_to_json("agent-1", &failed).unwrap();
+        assert_eq!(name, "agent-output");
+        assert_eq!(payload["content"], "Tool kubectl failed: connection refused");
+        assert_eq!(payload["success"], false);
+    }
+
+    #[test]
+    fn test_done_maps_to_metrics_and_error_is_skipped() {
+        let done = StreamEvent::Done {
+            content: "All pods healthy".to_string(),
+            total_iterations: 2,
+            execution_time_ms: 1500,
+            input_tokens: 120,
+            output_tokens: 30,
+            trace_id: "trace-1".to_string(),
+        };
+        let (name, payload) = stream_event_to_json("agent-1", &done).unwrap();
+        assert_eq!(name, "agent-metrics");
+        assert_eq!(
+            payload,
+            serde_json::json!({ "agent_id": "agent-1", "input_tokens": 120, "output_tokens": 30 })
+        );
+
+        let error = StreamEvent::Error {
+            message: "boom".to_string(),
+        };
+        assert!(stream_event_to_json("agent-1", &error).is_none());
+    }
+}
@@ -1,6 +1,6 @@
 // Config Commands - Tauri handlers for configuration management
 
-use aof_core::AgentConfig;
+use aof_core::{AgentConfig, Validate};
 use serde::{Deserialize, Serialize};
 use tauri::State;
 
@@ -53,34 +53,17 @@ pub async fn config_validate(yaml_content: String) -> Result<ValidationResult, S
     let mut warnings = Vec::new();
 
     // Try to parse YAML
-    let config: Result<AgentConfig, _> = serde_yaml::from_str(&yaml_content);
+    let config = AgentConfig::from_yaml(&yaml_content);
 
     match config {
         Ok(cfg) => {
-            // Validate required fields
-            if cfg.name.is_empty() {
-                errors.push(ValidationError {
-                    field: "name".to_string(),
-                    message: "Agent name is required".to_string(),
+            // Semantic checks shared with the CLI and runtime
+            if let Err(config_errors) = cfg.validate() {
+                errors.extend(config_errors.into_iter().map(|e| ValidationError {
+                    field: e.field,
+                    message: e.message,
                     line: None,
-                });
-            }
-
-            if cfg.model.is_empty() {
-                errors.push(ValidationError {
-                    field: "model".to_string(),
-                    message: "Model is required".to_string(),
-                    line: None,
-                });
-            }
-
-            // Validate temperature range
-            if cfg.temperature < 0.0 || cfg.temperature > 2.0 {
-                errors.push(ValidationError {
-                    field: "temperature".to_string(),
-                    message: "Temperature must be between 0.0 and 2.0".to_string(),
-                    line: None,
-                });
+                }));
             }
 
             // Warnings for best practices
@@ -143,7 +126,7 @@ pub async fn config_validate(yaml_content: String) -> Result<ValidationResult, S
 
             errors.push(ValidationError {
                 field: "yaml".to_string(),
-                message: format!("YAML parse error: {}", error_msg),
+                message: error_msg,
                 line,
             });
 
@@ -1,6 +1,7 @@
 // Database Commands - Tauri handlers for database persistence operations
 
 use crate::db::{DbMcpServer, DbProviderApiKey};
+use crate::secrets::SecretCipher;
 use crate::state::AppState;
 use serde::{Deserialize, Serialize};
 use sqlx::Row;
@@ -204,18 +205,20 @@ pub async fn db_save_provider_api_key(
     tracing::info!("Saving API key for provider: {}", request.provider);
 
     let db = state.get_db().await?;
+    let cipher = state.get_cipher().await?;
     let api_key = DbProviderApiKey::new(
         request.provider.clone(),
         request.api_key,
         request.base_url,
         request.default_model,
     );
+    let encrypted_key = cipher.encrypt(&api_key.api_key)?;
 
     sqlx::query(
         "INSERT OR REPLACE INTO provider_api_keys (provider, api_key, base_url, default_model, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?)"
     )
     .bind(&api_key.provider)
-    .bind(&api_key.api_key)
+    .bind(&encrypted_key)
     .bind(&api_key.base_url)
     .bind(&api_key.default_model)
     .bind(&api_key.created_at)
@@ -224,6 +227,8 @@ pub async fn db_save_provider_api_key(
     .await
     .map_err(|e| format!("Failed to save API key: {}", e))?;
 
+    state.invalidate_api_keys().await;
+
     tracing::info!("✓ Saved API key for provider: {}", api_key.provider);
     Ok(api_key.into())
 }
@@ -232,6 +237,7 @@ pub async fn db_save_provider_api_key(
 #[tauri::command]
 pub async fn db_load_provider_api_keys(state: State<'_, AppState>) -> Result<Vec<ProviderApiKeyResponse>, String> {
     let db = state.get_db().await?;
+    let cipher = state.get_cipher().await?;
 
     let rows = sqlx::query("SELECT * FROM provider_api_keys ORDER BY provider")
         .fetch_all(&db)
@@ -240,17 +246,8 @@ pub async fn db_load_provider_api_keys(state: State<'_, AppState>) -> Result<Vec
 
     let keys: Vec<ProviderApiKeyResponse> = rows
         .into_iter()
-        .map(|row| {
-            DbProviderApiKey {
-                provider: row.get("provider"),
-                api_key: row.get("api_key"),
-                base_url: row.get("base_url"),
-                default_model: row.get("default_model"),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-            }.into()
-        })
-        .collect();
+        .map(|row| provider_api_key_from_row(&row, &cipher).map(ProviderApiKeyResponse::from))
+        .collect::<Result<_, _>>()?;
 
     tracing::debug!("Loaded {} provider API keys from database", keys.len());
     Ok(keys)
@@ -267,6 +264,8 @@ pub async fn db_delete_provider_api_key(provider: String, state: State<'_, AppSt
         .await
         .map_err(|e| format!("Failed to delete API key: {}", e))?;
 
+    state.invalidate_api_keys().await;
+
     tracing::info!("✓ Deleted API key for provider: {}", provider);
     Ok(())
 }
@@ -277,15 +276,7 @@ pub async fn db_get_provider_api_key(
     provider: String,
     state: State<'_, AppState>,
 ) -> Result<Option<String>, String> {
-    let db = state.get_db().await?;
-
-    let row = sqlx::query("SELECT api_key FROM provider_api_keys WHERE provider = ?")
-        .bind(&provider)
-        .fetch_optional(&db)
-        .await
-        .map_err(|e| format!("Failed to get API key: {}", e))?;
-
-    Ok(row.map(|r| r.get("api_key")))
+    state.provider_api_key(&provider).await
 }
 
 /// Get provider configuration (returns masked key and config)
@@ -295,6 +286,7 @@ pub async fn db_get_provider_config(
     state: State<'_, AppState>,
 ) -> Result<Option<ProviderApiKeyResponse>, String> {
     let db = state.get_db().await?;
+    let cipher = state.get_cipher().await?;
 
     let row = sqlx::query("SELECT * FROM provider_api_keys WHERE provider = ?")
         .bind(&provider)
@@ -302,14 +294,22 @@ pub async fn db_get_provider_config(
         .await
         .map_err(|e| format!("Failed to get provider config: {}", e))?;
 
-    Ok(row.map(|row| {
-        DbProviderApiKey {
-            provider: row.get("provider"),
-            api_key: row.get("api_key"),
-            base_url: row.get("base_url"),
-            default_model: row.get("default_model"),
-            created_at: row.get("created_at"),
-            updated_at: row.get("updated_at"),
-        }.into()
-    }))
+    row.map(|row| provider_api_key_from_row(&row, &cipher).map(ProviderApiKeyResponse::from))
+        .transpose()
+}
+
+/// Build a provider API key from a `provider_api_keys` row, decrypting the key
+fn provider_api_key_from_row(
+    row: &sqlx::sqlite::SqliteRow,
+    cipher: &SecretCipher,
+) -> Result<DbProviderApiKey, String> {
+    let stored: String = row.get("api_key");
+    Ok(DbProviderApiKey {
+        provider: row.get("provider"),
+        api_key: cipher.decrypt(&stored)?,
+        base_url: row.get("base_url"),
+        default_model: row.get("default_model"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    })
 }
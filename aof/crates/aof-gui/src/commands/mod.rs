@@ -8,6 +8,7 @@ pub mod integrations;
 pub mod mcp;
 pub mod memory;
 pub mod monitoring;
+pub mod provider;
 pub mod settings;
 
 use serde::{Deserialize, Serialize};
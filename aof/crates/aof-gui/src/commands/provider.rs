@@ -0,0 +1,546 @@
+// Provider API helpers - live calls used by the settings screen
+
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+/// Anthropic API version sent with every request
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Cheapest Anthropic model, used for the 1-token connection test
+const ANTHROPIC_TEST_MODEL: &str = "claude-3-5-haiku-20241022";
+
+/// How long a provider call may take before it is reported as failed
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Outcome of a provider connection test
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConnectionTestResult {
+    pub ok: bool,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+}
+
+impl ConnectionTestResult {
+    fn failed(latency_ms: u64, error: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            latency_ms,
+            error: Some(error.into()),
+        }
+    }
+}
+
+/// A model offered by a provider's catalog
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelInfo {
+    pub id: String,
+    /// Context window in tokens, where known
+    pub context_window: Option<u32>,
+    /// Whether the model accepts tool definitions, where known
+    pub supports_tools: Option<bool>,
+}
+
+impl ModelInfo {
+    fn new(
+        id: impl Into<String>,
+        context_window: Option<u32>,
+        supports_tools: Option<bool>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            context_window,
+            supports_tools,
+        }
+    }
+}
+
+/// Default API root for a provider, or None if the provider is unknown
+pub fn default_base_url(provider: &str) -> Option<&'static str> {
+    match provider {
+        "openai" => Some("https://api.openai.com"),
+        "anthropic" => Some("https://api.anthropic.com"),
+        "google" => Some("https://generativelanguage.googleapis.com"),
+        "groq" => Some("https://api.groq.com"),
+        "ollama" => Some("http://localhost:11434"),
+        _ => None,
+    }
+}
+
+/// Environment variable holding a provider's API key
+pub fn api_key_env_var(provider: &str) -> Option<&'static str> {
+    match provider {
+        "openai" => Some("OPENAI_API_KEY"),
+        "anthropic" => Some("ANTHROPIC_API_KEY"),
+        "google" => Some("GOOGLE_API_KEY"),
+        "groq" => Some("GROQ_API_KEY"),
+        _ => None,
+    }
+}
+
+/// HTTP client for provider calls
+pub fn http_client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+/// Check credentials with the cheapest real request each provider offers
+///
+/// `base_url` is the API root (scheme and host, without `/v1`); the
+/// provider's public endpoint is used when it is None. Fails only for an
+/// unknown provider; a rejected key or unreachable server is reported in the
+/// returned result.
+pub async fn test_connection(
+    client: &reqwest::Client,
+    provider: &str,
+    api_key: &str,
+    base_url: Option<&str>,
+) -> Result<ConnectionTestResult, String> {
+    let base = resolve_base_url(provider, base_url)?;
+
+    if provider != "ollama" && api_key.is_empty() {
+        return Ok(ConnectionTestResult::failed(0, "API key is required"));
+    }
+
+    let request = match provider {
+        "openai" => client
+            .get(format!("{}/v1/models", base))
+            .bearer_auth(api_key),
+        "groq" => client
+            .get(format!("{}/openai/v1/models", base))
+            .bearer_auth(api_key),
+        "anthropic" => client
+            .post(format!("{}/v1/messages", base))
+            .header("x-api-key", api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .json(&serde_json::json!({
+                "model": ANTHROPIC_TEST_MODEL,
+                "max_tokens": 1,
+                "messages": [{"role": "user", "content": "ping"}],
+            })),
+        "google" => client
+            .get(format!("{}/v1beta/models", base))
+            .query(&[("key", api_key)]),
+        "ollama" => client.get(format!("{}/api/tags", base)),
+        _ => return Err(format!("Unknown provider: {}", provider)),
+    };
+
+    let started = Instant::now();
+    let response = request.send().await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    let response = match response {
+        Ok(response) => response,
+        Err(e) => {
+            return Ok(ConnectionTestResult::failed(
+                latency_ms,
+                format!("connection failed: {}", e),
+            ))
+        }
+    };
+
+    let status = response.status();
+    if status.is_success() {
+        return Ok(ConnectionTestResult {
+            ok: true,
+            latency_ms,
+            error: None,
+        });
+    }
+
+    let body = response.text().await.unwrap_or_default();
+    Ok(ConnectionTestResult::failed(
+        latency_ms,
+        describe_failure(status, &body),
+    ))
+}
+
+/// List the chat models a provider currently offers
+///
+/// Queries the provider's catalog endpoint; Anthropic's documented model
+/// list is used as is. Embedding, audio and other non-chat models are left
+/// out.
+pub async fn list_models(
+    client: &reqwest::Client,
+    provider: &str,
+    api_key: &str,
+    base_url: Option<&str>,
+) -> Result<Vec<ModelInfo>, String> {
+    let base = resolve_base_url(provider, base_url)?;
+
+    if provider == "anthropic" {
+        return Ok(documented_models(provider).unwrap_or_default());
+    }
+    if provider != "ollama" && api_key.is_empty() {
+        return Err(format!("API key is required to list {} models", provider));
+    }
+
+    let request = match provider {
+        "openai" => client
+            .get(format!("{}/v1/models", base))
+            .bearer_auth(api_key),
+        "groq" => client
+            .get(format!("{}/openai/v1/models", base))
+            .bearer_auth(api_key),
+        "google" => client
+            .get(format!("{}/v1beta/models", base))
+            .query(&[("key", api_key), ("pageSize", "1000")]),
+        "ollama" => client.get(format!("{}/api/tags", base)),
+        _ => return Err(format!("Unknown provider: {}", provider)),
+    };
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("connection failed: {}", e))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(describe_failure(status, &body));
+    }
+    let catalog: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Invalid model catalog from {}: {}", provider, e))?;
+
+    let mut models = match provider {
+        "openai" => parse_openai_models(&catalog),
+        "groq" => parse_groq_models(&catalog),
+        "google" => parse_google_models(&catalog),
+        _ => parse_ollama_models(&catalog),
+    };
+    models.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(models)
+}
+
+/// Models listed in provider docs, used when the catalog can't be queried
+pub fn documented_models(provider: &str) -> Option<Vec<ModelInfo>> {
+    let models = match provider {
+        "anthropic" => vec![
+            ModelInfo::new("claude-3-5-sonnet-20241022", Some(200_000), Some(true)),
+            ModelInfo::new("claude-3-5-haiku-20241022", Some(200_000), Some(true)),
+            ModelInfo::new("claude-3-opus-20240229", Some(200_000), Some(true)),
+            ModelInfo::new("claude-3-sonnet-20240229", Some(200_000), Some(true)),
+            ModelInfo::new("claude-3-haiku-20240307", Some(200_000), Some(true)),
+        ],
+        "google" => vec![
+            ModelInfo::new("gemini-2.0-flash", Some(1_048_576), Some(true)),
+            ModelInfo::new("gemini-1.5-pro", Some(2_097_152), Some(true)),
+            ModelInfo::new("gemini-1.5-flash", Some(1_048_576), Some(true)),
+        ],
+        "openai" => [
+            "gpt-4o",
+            "gpt-4o-mini",
+            "gpt-4-turbo",
+            "gpt-4",
+            "gpt-3.5-turbo",
+        ]
+        .into_iter()
+        .map(openai_model)
+        .collect(),
+        "groq" => vec![
+            ModelInfo::new("llama-3.1-70b-versatile", Some(131_072), Some(true)),
+            ModelInfo::new("llama-3.1-8b-instant", Some(131_072), Some(true)),
+            ModelInfo::new("mixtral-8x7b-32768", Some(32_768), Some(true)),
+        ],
+        "ollama" => ["llama3", "llama2", "mistral", "codellama", "phi"]
+            .into_iter()
+            .map(|id| ModelInfo::new(id, None, None))
+            .collect(),
+        _ => return None,
+    };
+    Some(models)
+}
+
+/// `{"data": [{"id": ...}]}`, keeping GPT and o-series chat models
+fn parse_openai_models(catalog: &serde_json::Value) -> Vec<ModelInfo> {
+    const NON_CHAT: &[&str] = &[
+        "instruct",
+        "audio",
+        "realtime",
+        "transcribe",
+        "tts",
+        "search",
+        "image",
+    ];
+
+    catalog_ids(catalog, "data", "id")
+        .filter(|id| {
+            ["gpt-", "chatgpt-", "o1", "o3", "o4"]
+                .iter()
+                .any(|prefix| id.starts_with(prefix))
+        })
+        .filter(|id| !NON_CHAT.iter().any(|kind| id.contains(kind)))
+        .map(openai_model)
+        .collect()
+}
+
+fn openai_model(id: &str) -> ModelInfo {
+    let context_window = if id.starts_with("gpt-4.1") {
+        Some(1_047_576)
+    } else if id.starts_with("gpt-4o")
+        || id.starts_with("gpt-4-turbo")
+        || id.starts_with("chatgpt-")
+    {
+        Some(128_000)
+    } else if id.starts_with("gpt-4") {
+        Some(8_192)
+    } else if id.starts_with("gpt-3.5-turbo") {
+        Some(16_385)
+    } else if id.starts_with('o') {
+        Some(200_000)
+    } else {
+        None
+    };
+    ModelInfo::new(id, context_window, Some(true))
+}
+
+/// Groq's OpenAI-compatible catalog, which includes `context_window`
+fn parse_groq_models(catalog: &serde_json::Value) -> Vec<ModelInfo> {
+    const NON_CHAT: &[&str] = &["whisper", "tts", "guard"];
+
+    catalog_entries(catalog, "data")
+        .filter_map(|model| {
+            let id = model.get("id")?.as_str()?;
+            if NON_CHAT.iter().any(|kind| id.contains(kind)) {
+                return None;
+            }
+            let context_window = model
+                .get("context_window")
+                .and_then(|c| c.as_u64())
+                .map(|c| c as u32);
+            Some(ModelInfo::new(id, context_window, None))
+        })
+        .collect()
+}
+
+/// `{"models": [{"name": "models/...", "supportedGenerationMethods": [...]}]}`
+fn parse_google_models(catalog: &serde_json::Value) -> Vec<ModelInfo> {
+    catalog_entries(catalog, "models")
+        .filter_map(|model| {
+            let generates = model
+                .get("supportedGenerationMethods")?
+                .as_array()?
+                .iter()
+                .any(|method| method == "generateContent");
+            if !generates {
+                return None;
+            }
+            let name = model.get("name")?.as_str()?;
+            let id = name.strip_prefix("models/").unwrap_or(name);
+            let context_window = model
+                .get("inputTokenLimit")
+                .and_then(|c| c.as_u64())
+                .map(|c| c as u32);
+            Some(ModelInfo::new(
+                id,
+                context_window,
+                Some(id.starts_with("gemini")),
+            ))
+        })
+        .collect()
+}
+
+/// `{"models": [{"name": "llama3:latest"}]}`, without embedding models
+fn parse_ollama_models(catalog: &serde_json::Value) -> Vec<ModelInfo> {
+    catalog_ids(catalog, "models", "name")
+        .filter(|name| !name.contains("embed"))
+        .map(|name| ModelInfo::new(name, None, None))
+        .collect()
+}
+
+fn catalog_entries<'a>(
+    catalog: &'a serde_json::Value,
+    list: &str,
+) -> impl Iterator<Item = &'a serde_json::Value> {
+    catalog
+        .get(list)
+        .and_then(|entries| entries.as_array())
+        .into_iter()
+        .flatten()
+}
+
+fn catalog_ids<'a>(
+    catalog: &'a serde_json::Value,
+    list: &str,
+    field: &'a str,
+) -> impl Iterator<Item = &'a str> {
+    catalog_entries(catalog, list).filter_map(move |entry| entry.get(field)?.as_str())
+}
+
+/// API root to call, trimmed of any trailing slash
+fn resolve_base_url<'a>(provider: &str, base_url: Option<&'a str>) -> Result<&'a str, String> {
+    base_url
+        .or_else(|| default_base_url(provider))
+        .map(|base| base.trim_end_matches('/'))
+        .ok_or_else(|| format!("Unknown provider: {}", provider))
+}
+
+/// Turn an error response into a message for the settings screen
+fn describe_failure(status: reqwest::StatusCode, body: &str) -> String {
+    let message = provider_error_message(body);
+    let summary = match status.as_u16() {
+        401 | 403 => "invalid key".to_string(),
+        _ => format!("HTTP {}", status.as_u16()),
+    };
+
+    match message {
+        Some(message) => format!("{}: {}", summary, message),
+        None => summary,
+    }
+}
+
+/// Extract the provider's own error message from a response body
+///
+/// OpenAI, Groq, Anthropic and Google nest it as `error.message`; Ollama
+/// returns `error` as a string.
+fn provider_error_message(body: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(body).ok()?;
+    let error = value.get("error")?;
+    error
+        .get("message")
+        .and_then(|m| m.as_str())
+        .or_else(|| error.as_str())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::routing::{get, post};
+    use axum::{Json, Router};
+
+    /// Serve `app` on a local port and return its base URL
+    async fn serve(app: Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_connection_succeeds_with_valid_key() {
+        async fn models(headers: HeaderMap) -> (StatusCode, Json<serde_json::Value>) {
+            if headers["authorization"] == "Bearer sk-valid" {
+                (
+                    StatusCode::OK,
+                    Json(serde_json::json!({"object": "list", "data": []})),
+                )
+            } else {
+                (StatusCode::UNAUTHORIZED, Json(serde_json::json!({})))
+            }
+        }
+
+        let base = serve(Router::new().route("/v1/models", get(models))).await;
+        let client = http_client().unwrap();
+
+        let result = test_connection(&client, "openai", "sk-valid", Some(&base))
+            .await
+            .unwrap();
+        assert!(result.ok, "{:?}", result.error);
+        assert_eq!(result.error, None);
+    }
+
+    #[tokio::test]
+    async fn test_connection_reports_invalid_key() {
+        async fn messages() -> (StatusCode, Json<serde_json::Value>) {
+            (
+                StatusCode::UNAUTHORIZED,
+                Json(serde_json::json!({
+                    "type": "error",
+                    "error": {"type": "authentication_error", "message": "invalid x-api-key"}
+                })),
+            )
+        }
+
+        let base = serve(Router::new().route("/v1/messages", post(messages))).await;
+        let client = http_client().unwrap();
+
+        let result = test_connection(&client, "anthropic", "sk-ant-wrong", Some(&base))
+            .await
+            .unwrap();
+        assert!(!result.ok);
+        assert_eq!(
+            result.error.as_deref(),
+            Some("invalid key: invalid x-api-key")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_connection_reports_unreachable_server() {
+        // Bind and release a port so nothing is listening on it
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        let client = http_client().unwrap();
+        let result = test_connection(&client, "ollama", "", Some(&base))
+            .await
+            .unwrap();
+        assert!(!result.ok);
+        assert!(result.error.unwrap().starts_with("connection failed"));
+    }
+
+    #[tokio::test]
+    async fn test_list_openai_models_keeps_chat_models() {
+        async fn models() -> Json<serde_json::Value> {
+            Json(serde_json::json!({
+                "object": "list",
+                "data": [
+                    {"id": "gpt-4o", "object": "model"},
+                    {"id": "text-embedding-3-small", "object": "model"},
+                    {"id": "gpt-4o-mini", "object": "model"},
+                    {"id": "whisper-1", "object": "model"},
+                    {"id": "gpt-4o-realtime-preview", "object": "model"},
+                    {"id": "o1", "object": "model"}
+                ]
+            }))
+        }
+
+        let base = serve(Router::new().route("/v1/models", get(models))).await;
+        let client = http_client().unwrap();
+
+        let models = list_models(&client, "openai", "sk-valid", Some(&base))
+            .await
+            .unwrap();
+        assert_eq!(
+            models,
+            vec![
+                ModelInfo::new("gpt-4o", Some(128_000), Some(true)),
+                ModelInfo::new("gpt-4o-mini", Some(128_000), Some(true)),
+                ModelInfo::new("o1", Some(200_000), Some(true)),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_ollama_models_from_tags() {
+        async fn tags() -> Json<serde_json::Value> {
+            Json(serde_json::json!({
+                "models": [
+                    {"name": "llama3:latest", "size": 4661224676u64},
+                    {"name": "nomic-embed-text:latest", "size": 274302450},
+                    {"name": "mistral:7b", "size": 4113301824u64}
+                ]
+            }))
+        }
+
+        let base = serve(Router::new().route("/api/tags", get(tags))).await;
+        let client = http_client().unwrap();
+
+        let models = list_models(&client, "ollama", "", Some(&base))
+            .await
+            .unwrap();
+        let ids: Vec<&str> = models.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, ["llama3:latest", "mistral:7b"]);
+    }
+
+    #[tokio::test]
+    async fn test_connection_rejects_unknown_provider() {
+        let client = http_client().unwrap();
+        assert!(test_connection(&client, "acme", "key", None).await.is_err());
+    }
+}
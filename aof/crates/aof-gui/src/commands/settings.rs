@@ -1,10 +1,16 @@
 // Settings Command Handlers for Tauri
 
+use std::time::{Duration, Instant};
+
 use serde::{Deserialize, Serialize};
 use tauri::State;
 
+use crate::commands::provider::{self, ConnectionTestResult, ModelInfo};
 use crate::state::AppState;
 
+/// How long a provider's model catalog is reused before it is fetched again
+const MODEL_CATALOG_TTL: Duration = Duration::from_secs(300);
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProviderConfig {
     pub provider: String,
@@ -78,7 +84,7 @@ pub async fn settings_get(state: State<'_, AppState>) -> Result<AppSettings, Str
     let mut providers = AppSettings::default().providers;
 
     // Load saved API keys from database
-    if let Ok(db) = state.get_db().await {
+    if let (Ok(db), Ok(cipher)) = (state.get_db().await, state.get_cipher().await) {
         for provider in &mut providers {
             let row = sqlx::query("SELECT api_key, base_url, default_model FROM provider_api_keys WHERE provider = ?")
                 .bind(&provider.provider)
@@ -87,7 +93,16 @@ pub async fn settings_get(state: State<'_, AppState>) -> Result<AppSettings, Str
 
             if let Ok(Some(row)) = row {
                 use sqlx::Row;
-                provider.api_key = row.get::<Option<String>, _>("api_key");
+                provider.api_key = match row.get::<Option<String>, _>("api_key") {
+                    Some(stored) => match cipher.decrypt(&stored) {
+                        Ok(api_key) => Some(api_key),
+                        Err(e) => {
+                            tracing::warn!("Failed to decrypt API key for {}: {}", provider.provider, e);
+                            None
+                        }
+                    },
+                    None => None,
+                };
                 if let Some(base_url) = row.get::<Option<String>, _>("base_url") {
                     provider.base_url = Some(base_url);
                 }
@@ -130,19 +145,21 @@ pub async fn settings_update(
         app_settings.log_level = settings.log_level.clone();
     }
 
-    // Persist provider API keys to database
+    // Persist provider API keys to database, encrypted
     let db = state.get_db().await?;
+    let cipher = state.get_cipher().await?;
     for provider in &settings.providers {
         if let Some(api_key) = &provider.api_key {
             if !api_key.is_empty() {
                 tracing::info!("Saving API key for provider: {} to database", provider.provider);
 
+                let encrypted_key = cipher.encrypt(api_key)?;
                 let now = chrono::Utc::now().to_rfc3339();
                 sqlx::query(
                     "INSERT OR REPLACE INTO provider_api_keys (provider, api_key, base_url, default_model, created_at, updated_at) VALUES (?, ?, ?, ?, COALESCE((SELECT created_at FROM provider_api_keys WHERE provider = ?), ?), ?)"
                 )
                 .bind(&provider.provider)
-                .bind(api_key)
+                .bind(&encrypted_key)
                 .bind(&provider.base_url)
                 .bind(&provider.default_model)
                 .bind(&provider.provider)
@@ -157,95 +174,75 @@ pub async fn settings_update(
         }
     }
 
+    // New keys or endpoints can change what each provider offers
+    state.invalidate_api_keys().await;
+    state.model_catalogs.write().await.clear();
+
     tracing::info!("Settings updated successfully");
     Ok(())
 }
 
 /// Test provider connection
+///
+/// Makes a cheap authenticated request to the provider so a wrong key or
+/// unreachable server is caught here rather than on the first agent run.
 #[tauri::command]
 pub async fn provider_test_connection(
     provider: String,
     api_key: String,
     base_url: Option<String>,
-) -> Result<String, String> {
+) -> Result<ConnectionTestResult, String> {
     tracing::info!("Testing connection for provider: {}", provider);
 
-    match provider.as_str() {
-        "google" => {
-            // TODO: Actually test the connection
-            if api_key.is_empty() {
-                return Err("API key is required for Google".to_string());
-            }
-            Ok("Successfully configured Google API".to_string())
-        }
-        "anthropic" => {
-            // TODO: Actually test the connection once provider is refactored
-            if api_key.is_empty() {
-                return Err("API key is required for Anthropic".to_string());
-            }
-            Ok("Successfully configured Anthropic API".to_string())
-        }
-        "openai" => {
-            // TODO: Actually test the connection
-            if api_key.is_empty() {
-                return Err("API key is required for OpenAI".to_string());
-            }
-            Ok("Successfully configured OpenAI API".to_string())
-        }
-        "ollama" => {
-            // Ollama runs locally
-            let base = base_url.unwrap_or_else(|| "http://localhost:11434".to_string());
-            Ok(format!("Ollama configured at {}", base))
-        }
-        "groq" => {
-            // Groq uses OpenAI-compatible API
-            if api_key.is_empty() {
-                return Err("API key is required for Groq".to_string());
-            }
-            Ok("Successfully configured Groq API".to_string())
-        }
-        _ => Err(format!("Unknown provider: {}", provider)),
+    let client = provider::http_client()?;
+    let result = provider::test_connection(&client, &provider, &api_key, base_url.as_deref()).await?;
+
+    match &result.error {
+        None => tracing::info!("✓ {} connection OK ({} ms)", provider, result.latency_ms),
+        Some(error) => tracing::warn!("{} connection failed: {}", provider, error),
     }
+    Ok(result)
 }
 
 /// Get available models for a provider
+///
+/// Fetched from the provider's catalog and reused for `MODEL_CATALOG_TTL`;
+/// falls back to the documented models when the catalog can't be reached.
 #[tauri::command]
-pub async fn provider_list_models(provider: String) -> Result<Vec<String>, String> {
-    match provider.as_str() {
-        "google" => Ok(vec![
-            "gemini-2.0-flash".to_string(),
-            "gemini-1.5-pro".to_string(),
-            "gemini-1.5-flash".to_string(),
-            "gemini-1.0-pro".to_string(),
-        ]),
-        "anthropic" => Ok(vec![
-            "claude-3-5-sonnet-20241022".to_string(),
-            "claude-3-5-haiku-20241022".to_string(),
-            "claude-3-opus-20240229".to_string(),
-            "claude-3-sonnet-20240229".to_string(),
-            "claude-3-haiku-20240307".to_string(),
-        ]),
-        "openai" => Ok(vec![
-            "gpt-4o".to_string(),
-            "gpt-4o-mini".to_string(),
-            "gpt-4-turbo".to_string(),
-            "gpt-4".to_string(),
-            "gpt-3.5-turbo".to_string(),
-        ]),
-        "ollama" => Ok(vec![
-            "llama2".to_string(),
-            "llama3".to_string(),
-            "mistral".to_string(),
-            "codellama".to_string(),
-            "phi".to_string(),
-        ]),
-        "groq" => Ok(vec![
-            "llama-3.1-70b-versatile".to_string(),
-            "llama-3.1-8b-instant".to_string(),
-            "mixtral-8x7b-32768".to_string(),
-            "gemma-7b-it".to_string(),
-        ]),
-        _ => Err(format!("Unknown provider: {}", provider)),
+pub async fn provider_list_models(
+    provider: String,
+    base_url: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<ModelInfo>, String> {
+    if let Some((fetched_at, models)) = state.model_catalogs.read().await.get(&provider) {
+        if fetched_at.elapsed() < MODEL_CATALOG_TTL {
+            return Ok(models.clone());
+        }
+    }
+
+    let api_key = match state.provider_api_key(&provider).await {
+        Ok(Some(api_key)) => api_key,
+        _ => provider::api_key_env_var(&provider)
+            .and_then(|var| std::env::var(var).ok())
+            .unwrap_or_default(),
+    };
+
+    let client = provider::http_client()?;
+    match provider::list_models(&client, &provider, &api_key, base_url.as_deref()).await {
+        Ok(models) => {
+            tracing::debug!("Fetched {} models for {}", models.len(), provider);
+            state
+                .model_catalogs
+                .write()
+                .await
+                .insert(provider, (Instant::now(), models.clone()));
+            Ok(models)
+        }
+        Err(e) => {
+            tracing::warn!("Failed to fetch {} model catalog: {}", provider, e);
+            provider::documented_models(&provider)
+                .ok_or_else(|| format!("Unknown provider: {}", provider))
+        }
     }
 }
 
@@ -263,9 +260,14 @@ pub async fn settings_reset(state: State<'_, AppState>) -> Result<AppSettings, S
 }
 
 /// Export settings to JSON
+///
+/// API keys are left out so the export can be shared safely.
 #[tauri::command]
 pub async fn settings_export(state: State<'_, AppState>) -> Result<String, String> {
-    let settings = settings_get(state).await?;
+    let mut settings = settings_get(state).await?;
+    for provider in &mut settings.providers {
+        provider.api_key = None;
+    }
     serde_json::to_string_pretty(&settings)
         .map_err(|e| format!("Failed to serialize settings: {}", e))
 }
@@ -3,22 +3,18 @@
 
 pub mod commands;
 pub mod db;
+pub mod secrets;
 pub mod state;
 
 use state::AppState;
 use tauri::Manager;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 /// Run the Tauri application
 pub fn run() {
     // Initialize logging
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "aof_gui=debug,aof_core=debug,aof_mcp=debug,aof_runtime=warn,aof_llm=warn".into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    aof_core::init_tracing(
+        "aof_gui=debug,aof_core=debug,aof_mcp=debug,aof_runtime=warn,aof_llm=warn",
+    );
 
     tracing::info!("Starting AOF Desktop v{}", env!("CARGO_PKG_VERSION"));
     tracing::info!("AOF Core v{}", aof_core::VERSION);
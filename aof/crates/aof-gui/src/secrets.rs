@@ -0,0 +1,163 @@
+// Encryption at rest for provider API keys
+//
+// Keys are sealed with AES-256-GCM under an app-managed key that lives in a
+// file next to the database, so a copied or leaked aof.db alone doesn't expose
+// them.
+
+use std::path::Path;
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+
+/// Prefix marking a value produced by `SecretCipher::encrypt`
+const ENCRYPTED_PREFIX: &str = "enc:v1:";
+
+/// AES-GCM nonce length in bytes
+const NONCE_LEN: usize = 12;
+
+/// AES-256 key length in bytes
+const KEY_LEN: usize = 32;
+
+/// Encrypts and decrypts secrets stored in the database
+#[derive(Clone)]
+pub struct SecretCipher {
+    cipher: Aes256Gcm,
+}
+
+impl SecretCipher {
+    /// Create a cipher from a raw 32-byte key
+    pub fn new(key: &[u8]) -> Result<Self, String> {
+        if key.len() != KEY_LEN {
+            return Err(format!(
+                "Encryption key must be {} bytes, got {}",
+                KEY_LEN,
+                key.len()
+            ));
+        }
+        Ok(Self {
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)),
+        })
+    }
+
+    /// Load the key stored at `path`, generating and saving one on first use
+    pub fn load_or_create(path: &Path) -> Result<Self, String> {
+        if path.exists() {
+            let key =
+                std::fs::read(path).map_err(|e| format!("Failed to read encryption key: {}", e))?;
+            return Self::new(&key);
+        }
+
+        let key = Aes256Gcm::generate_key(OsRng);
+        write_key_file(path, &key)?;
+        tracing::info!("Generated encryption key at {:?}", path);
+        Self::new(&key)
+    }
+
+    /// Encrypt `plaintext` into a printable value safe to store
+    pub fn encrypt(&self, plaintext: &str) -> Result<String, String> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| "Failed to encrypt secret".to_string())?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        Ok(format!("{}{}", ENCRYPTED_PREFIX, BASE64.encode(sealed)))
+    }
+
+    /// Whether `stored` was produced by `encrypt`
+    pub fn is_encrypted(stored: &str) -> bool {
+        stored.starts_with(ENCRYPTED_PREFIX)
+    }
+
+    /// Decrypt a value produced by `encrypt`
+    ///
+    /// Values without the encryption prefix were stored before encryption was
+    /// introduced and are returned unchanged; `AppState::init_db` encrypts
+    /// them at startup.
+    pub fn decrypt(&self, stored: &str) -> Result<String, String> {
+        let Some(encoded) = stored.strip_prefix(ENCRYPTED_PREFIX) else {
+            return Ok(stored.to_string());
+        };
+
+        let sealed = BASE64
+            .decode(encoded)
+            .map_err(|e| format!("Malformed encrypted secret: {}", e))?;
+        if sealed.len() < NONCE_LEN {
+            return Err("Malformed encrypted secret: too short".to_string());
+        }
+
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let plaintext = self
+            .cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| "Failed to decrypt secret (wrong key or corrupted data)".to_string())?;
+
+        String::from_utf8(plaintext).map_err(|e| format!("Decrypted secret is not UTF-8: {}", e))
+    }
+}
+
+/// Write the key readable by the current user only
+fn write_key_file(path: &Path, key: &[u8]) -> Result<(), String> {
+    use std::io::Write;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create key directory: {}", e))?;
+    }
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options
+        .open(path)
+        .map_err(|e| format!("Failed to create encryption key: {}", e))?;
+    file.write_all(key)
+        .map_err(|e| format!("Failed to write encryption key: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_key_path() -> std::path::PathBuf {
+        std::env::temp_dir()
+            .join(format!("aof-gui-test-{}", uuid::Uuid::new_v4()))
+            .join("secret.key")
+    }
+
+    #[test]
+    fn test_encrypted_key_round_trips() {
+        let path = temp_key_path();
+        let cipher = SecretCipher::load_or_create(&path).unwrap();
+
+        let sealed = cipher.encrypt("sk-test-1234567890").unwrap();
+        assert!(sealed.starts_with(ENCRYPTED_PREFIX));
+        assert!(!sealed.contains("sk-test"));
+        assert_ne!(sealed, cipher.encrypt("sk-test-1234567890").unwrap());
+
+        // A cipher reloaded from the same key file can read it back
+        let reloaded = SecretCipher::load_or_create(&path).unwrap();
+        assert_eq!(reloaded.decrypt(&sealed).unwrap(), "sk-test-1234567890");
+
+        // Another key cannot
+        let other = SecretCipher::new(&[7u8; KEY_LEN]).unwrap();
+        assert!(other.decrypt(&sealed).is_err());
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_plaintext_values_pass_through() {
+        let cipher = SecretCipher::new(&[1u8; KEY_LEN]).unwrap();
+        assert_eq!(cipher.decrypt("sk-legacy").unwrap(), "sk-legacy");
+    }
+}
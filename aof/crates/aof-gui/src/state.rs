@@ -3,12 +3,15 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::Instant;
+use tokio::sync::{oneshot, RwLock};
 
 use aof_runtime::RuntimeOrchestrator;
 use crate::commands::agent::AgentRuntime;
 use crate::commands::config::ConfigMetadata;
 use crate::commands::mcp::{McpConnection, McpServerConfig};
+use crate::commands::provider::ModelInfo;
+use crate::secrets::SecretCipher;
 
 /// Main application state
 #[derive(Clone)]
@@ -16,6 +19,9 @@ pub struct AppState {
     /// Active agent runtimes
     pub agents: Arc<RwLock<HashMap<String, AgentRuntime>>>,
 
+    /// Cancellation senders for running agents (agent id -> sender)
+    pub cancellations: Arc<RwLock<HashMap<String, oneshot::Sender<()>>>>,
+
     /// Saved configurations (id -> (metadata, yaml_content))
     pub configs: Arc<RwLock<HashMap<String, (ConfigMetadata, String)>>>,
 
@@ -33,21 +39,67 @@ pub struct AppState {
 
     /// SQLite database pool
     pub db: Arc<RwLock<Option<sqlx::SqlitePool>>>,
+
+    /// Cipher for secrets stored in the database
+    pub cipher: Arc<RwLock<Option<SecretCipher>>>,
+
+    /// Decrypted provider API keys (provider -> key), loaded on first use
+    pub api_keys: Arc<RwLock<Option<HashMap<String, String>>>>,
+
+    /// Model catalogs fetched from providers (provider -> (fetched at, models))
+    pub model_catalogs: Arc<RwLock<HashMap<String, (Instant, Vec<ModelInfo>)>>>,
 }
 
 impl AppState {
     pub fn new() -> Self {
         Self {
             agents: Arc::new(RwLock::new(HashMap::new())),
+            cancellations: Arc::new(RwLock::new(HashMap::new())),
             configs: Arc::new(RwLock::new(HashMap::new())),
             mcp_connections: Arc::new(RwLock::new(HashMap::new())),
             mcp_server_configs: Arc::new(RwLock::new(HashMap::new())),
             settings: Arc::new(RwLock::new(AppSettings::default())),
             orchestrator: Arc::new(RuntimeOrchestrator::with_max_concurrent(5)),
             db: Arc::new(RwLock::new(None)),
+            cipher: Arc::new(RwLock::new(None)),
+            api_keys: Arc::new(RwLock::new(None)),
+            model_catalogs: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Register a running agent, returning the receiver its execution
+    /// should be cancelled by
+    pub async fn register_cancellation(&self, agent_id: &str) -> oneshot::Receiver<()> {
+        let (cancel_tx, cancel_rx) = oneshot::channel();
+        self.cancellations
+            .write()
+            .await
+            .insert(agent_id.to_string(), cancel_tx);
+        cancel_rx
+    }
+
+    /// Cancel a running agent's execution
+    ///
+    /// Returns false if the agent is not registered (never started, already
+    /// finished or already cancelled).
+    pub async fn cancel_agent(&self, agent_id: &str) -> bool {
+        match self.cancellations.write().await.remove(agent_id) {
+            Some(cancel_tx) => {
+                let _ = cancel_tx.send(());
+                true
+            }
+            None => false,
         }
     }
 
+    /// Unregister an agent whose execution has ended
+    ///
+    /// Returns false if the agent was cancelled first, which tells a failed
+    /// run apart from one that was stopped.
+    pub async fn unregister_cancellation(&self, agent_id: &str) -> bool {
+        self.cancellations.write().await.remove(agent_id).is_some()
+    }
+
     /// Initialize the database pool
     pub async fn init_db(&self, db_path: PathBuf) -> Result<(), String> {
         use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
@@ -87,6 +139,12 @@ impl AppState {
 
         tracing::info!("✓ Migrations completed");
 
+        // The key that encrypts stored API keys lives next to the database
+        let key_path = db_path.with_file_name("secret.key");
+        let cipher = SecretCipher::load_or_create(&key_path)?;
+        encrypt_plaintext_api_keys(&pool, &cipher).await?;
+        *self.cipher.write().await = Some(cipher);
+
         // Store the pool
         let mut db_guard = self.db.write().await;
         *db_guard = Some(pool);
@@ -100,6 +158,86 @@ impl AppState {
         let db_guard = self.db.read().await;
         db_guard.clone().ok_or_else(|| "Database not initialized".to_string())
     }
+
+    /// Get the cipher for stored secrets
+    pub async fn get_cipher(&self) -> Result<SecretCipher, String> {
+        let cipher_guard = self.cipher.read().await;
+        cipher_guard.clone().ok_or_else(|| "Database not initialized".to_string())
+    }
+
+    /// Get the saved API key for a provider
+    ///
+    /// Keys are read from the database and decrypted once, then served from
+    /// memory until `invalidate_api_keys` is called.
+    pub async fn provider_api_key(&self, provider: &str) -> Result<Option<String>, String> {
+        if let Some(keys) = self.api_keys.read().await.as_ref() {
+            return Ok(keys.get(provider).cloned());
+        }
+
+        let mut cache = self.api_keys.write().await;
+        if cache.is_none() {
+            *cache = Some(self.load_api_keys().await?);
+        }
+        Ok(cache.as_ref().and_then(|keys| keys.get(provider).cloned()))
+    }
+
+    /// Drop cached API keys so the next lookup reloads them
+    pub async fn invalidate_api_keys(&self) {
+        *self.api_keys.write().await = None;
+    }
+
+    async fn load_api_keys(&self) -> Result<HashMap<String, String>, String> {
+        let db = self.get_db().await?;
+        let cipher = self.get_cipher().await?;
+
+        let rows: Vec<(String, String)> =
+            sqlx::query_as("SELECT provider, api_key FROM provider_api_keys")
+                .fetch_all(&db)
+                .await
+                .map_err(|e| format!("Failed to load API keys: {}", e))?;
+
+        let mut keys = HashMap::new();
+        for (provider, stored) in rows {
+            match cipher.decrypt(&stored) {
+                Ok(key) => {
+                    keys.insert(provider, key);
+                }
+                Err(e) => tracing::warn!("Skipping API key for {}: {}", provider, e),
+            }
+        }
+
+        tracing::debug!("Loaded {} provider API keys from database", keys.len());
+        Ok(keys)
+    }
+}
+
+/// Encrypt API keys saved before encryption at rest was introduced
+async fn encrypt_plaintext_api_keys(
+    pool: &sqlx::SqlitePool,
+    cipher: &SecretCipher,
+) -> Result<(), String> {
+    let rows: Vec<(String, String)> =
+        sqlx::query_as("SELECT provider, api_key FROM provider_api_keys")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| format!("Failed to load API keys: {}", e))?;
+
+    for (provider, stored) in rows {
+        if SecretCipher::is_encrypted(&stored) {
+            continue;
+        }
+
+        sqlx::query("UPDATE provider_api_keys SET api_key = ? WHERE provider = ?")
+            .bind(cipher.encrypt(&stored)?)
+            .bind(&provider)
+            .execute(pool)
+            .await
+            .map_err(|e| format!("Failed to encrypt API key for {}: {}", provider, e))?;
+
+        tracing::info!("Encrypted stored API key for {}", provider);
+    }
+
+    Ok(())
 }
 
 impl Default for AppState {
@@ -138,3 +276,146 @@ impl Default for AppSettings {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aof_core::{AgentConfig, AgentContext, AofResult, Model, ModelConfig, ModelProvider, ModelRequest, ModelResponse, StreamChunk};
+    use aof_runtime::AgentExecutor;
+    use async_trait::async_trait;
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+
+    /// Model whose calls never return, standing in for a long LLM request
+    struct StalledModel {
+        config: ModelConfig,
+    }
+
+    #[async_trait]
+    impl Model for StalledModel {
+        async fn generate(&self, _request: &ModelRequest) -> AofResult<ModelResponse> {
+            futures::future::pending().await
+        }
+
+        async fn generate_stream(
+            &self,
+            _request: &ModelRequest,
+        ) -> AofResult<std::pin::Pin<Box<dyn futures::Stream<Item = AofResult<StreamChunk>> + Send>>> {
+            futures::future::pending().await
+        }
+
+        fn config(&self) -> &ModelConfig {
+            &self.config
+        }
+
+        fn provider(&self) -> ModelProvider {
+            ModelProvider::Custom
+        }
+    }
+
+    async fn state_with_temp_db() -> (AppState, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("aof-gui-test-{}", uuid::Uuid::new_v4()));
+        let state = AppState::new();
+        state.init_db(dir.join("aof.db")).await.unwrap();
+        (state, dir)
+    }
+
+    async fn save_key(state: &AppState, provider: &str, stored: &str) {
+        let now = chrono::Utc::now().to_rfc3339();
+        sqlx::query("INSERT OR REPLACE INTO provider_api_keys (provider, api_key, created_at, updated_at) VALUES (?, ?, ?, ?)")
+            .bind(provider)
+            .bind(stored)
+            .bind(&now)
+            .bind(&now)
+            .execute(&state.get_db().await.unwrap())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_provider_api_key_is_cached() {
+        let (state, dir) = state_with_temp_db().await;
+        let encrypted = state.get_cipher().await.unwrap().encrypt("sk-openai").unwrap();
+        save_key(&state, "openai", &encrypted).await;
+
+        assert_eq!(state.provider_api_key("openai").await.unwrap().as_deref(), Some("sk-openai"));
+
+        // Later lookups are served from memory without touching the database
+        sqlx::query("DELETE FROM provider_api_keys")
+            .execute(&state.get_db().await.unwrap())
+            .await
+            .unwrap();
+        assert_eq!(state.provider_api_key("openai").await.unwrap().as_deref(), Some("sk-openai"));
+        assert_eq!(state.provider_api_key("groq").await.unwrap(), None);
+
+        state.invalidate_api_keys().await;
+        assert_eq!(state.provider_api_key("openai").await.unwrap(), None);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_init_db_encrypts_plaintext_api_keys() {
+        let (state, dir) = state_with_temp_db().await;
+        save_key(&state, "anthropic", "sk-ant-plaintext").await;
+
+        // Reopening the database encrypts keys saved in plaintext
+        let reopened = AppState::new();
+        reopened.init_db(dir.join("aof.db")).await.unwrap();
+
+        let stored: String = sqlx::query_scalar("SELECT api_key FROM provider_api_keys WHERE provider = 'anthropic'")
+            .fetch_one(&reopened.get_db().await.unwrap())
+            .await
+            .unwrap();
+        assert!(SecretCipher::is_encrypted(&stored));
+        assert_eq!(
+            reopened.provider_api_key("anthropic").await.unwrap().as_deref(),
+            Some("sk-ant-plaintext")
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_cancel_agent_aborts_execution() {
+        let state = AppState::new();
+        let config: AgentConfig = serde_yaml::from_str("name: stalled\nmodel: mock-model").unwrap();
+        let model = Box::new(StalledModel {
+            config: ModelConfig {
+                model: "mock-model".to_string(),
+                provider: ModelProvider::Custom,
+                api_key: None,
+                endpoint: None,
+                temperature: 0.7,
+                max_tokens: None,
+                timeout_secs: 60,
+                headers: HashMap::new(),
+                extra: HashMap::new(),
+            },
+        });
+        let executor = AgentExecutor::new(config, model, None, None);
+
+        let cancel_rx = state.register_cancellation("agent-1").await;
+        let (stream_tx, mut stream_rx) = mpsc::channel(100);
+        let run = tokio::spawn(async move {
+            let mut ctx = AgentContext::new("hello");
+            executor
+                .execute_streaming_cancellable(&mut ctx, stream_tx, cancel_rx)
+                .await
+        });
+
+        // Wait until the model call is in flight, then stop the agent
+        stream_rx.recv().await.unwrap();
+        assert!(state.cancel_agent("agent-1").await);
+
+        let result = tokio::time::timeout(Duration::from_secs(1), run)
+            .await
+            .expect("cancellation should abort the execution")
+            .unwrap();
+        assert!(result.is_err());
+
+        // Cancelled agents are already unregistered
+        assert!(!state.cancel_agent("agent-1").await);
+        assert!(!state.unregister_cancellation("agent-1").await);
+    }
+}
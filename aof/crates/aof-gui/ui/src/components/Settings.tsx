@@ -24,6 +24,18 @@ interface AppSettings {
   providers: ProviderConfig[];
 }
 
+interface ModelInfo {
+  id: string;
+  context_window?: number;
+  supports_tools?: boolean;
+}
+
+interface ConnectionTestResult {
+  ok: boolean;
+  latency_ms: number;
+  error?: string;
+}
+
 interface ConnectionStatus {
   provider: string;
   status: 'idle' | 'testing' | 'success' | 'error';
@@ -56,10 +68,11 @@ export function Settings() {
 
       // Load models for each provider
       for (const provider of data.providers) {
-        const providerModels = await invoke<string[]>('provider_list_models', {
-          provider: provider.provider
+        const providerModels = await invoke<ModelInfo[]>('provider_list_models', {
+          provider: provider.provider,
+          baseUrl: provider.base_url
         });
-        setModels(prev => ({ ...prev, [provider.provider]: providerModels }));
+        setModels(prev => ({ ...prev, [provider.provider]: providerModels.map(m => m.id) }));
       }
     } catch (error) {
       console.error('Failed to load settings:', error);
@@ -106,14 +119,16 @@ export function Settings() {
     ));
 
     try {
-      const message = await invoke<string>('provider_test_connection', {
+      const result = await invoke<ConnectionTestResult>('provider_test_connection', {
         provider,
         apiKey,
         baseUrl
       });
 
       setConnectionStatuses(prev => prev.map(s =>
-        s.provider === provider ? { provider, status: 'success', message } : s
+        s.provider === provider ? (result.ok
+          ? { provider, status: 'success', message: `Connected (${result.latency_ms} ms)` }
+          : { provider, status: 'error', message: result.error ?? 'Connection failed' }) : s
       ));
     } catch (error) {
       setConnectionStatuses(prev => prev.map(s =>
@@ -0,0 +1,312 @@
+// Load balancing one logical model over several keys or endpoints
+
+use aof_core::{
+    AofError, AofResult, Model, ModelConfig, ModelProvider, ModelRequest, ModelResponse,
+    StreamChunk,
+};
+use async_trait::async_trait;
+use futures::Stream;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// How long a rate-limited backend is skipped when the error carries no `Retry-After`
+pub const DEFAULT_RATE_LIMIT_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Backend model with its share of the calls
+struct Backend {
+    model: Box<dyn Model>,
+    weight: u32,
+}
+
+/// Selection state of one backend
+#[derive(Default)]
+struct BackendState {
+    /// Smooth weighted round-robin counter
+    current: i64,
+    /// Skipped until then after a rate limit
+    cooling_until: Option<Instant>,
+}
+
+/// Model spreading calls over several backends serving the same model
+///
+/// Each backend is typically the same model behind another API key or
+/// regional endpoint. Calls are distributed by smooth weighted round-robin,
+/// so a backend with weight 3 gets three calls for every one of a backend
+/// with weight 1, interleaved rather than in bursts. A backend answering
+/// with `RateLimited` is skipped for the `Retry-After` delay (or the
+/// cooldown) and the call moves on to the next one; any other error is
+/// returned as is, since this spreads load rather than failing over.
+pub struct LoadBalancedModel {
+    backends: Vec<Backend>,
+    state: Mutex<Vec<BackendState>>,
+    cooldown: Duration,
+}
+
+impl LoadBalancedModel {
+    /// Balance over `(model, weight)` pairs
+    ///
+    /// Fails without backends or when a weight is zero.
+    pub fn new(backends: Vec<(Box<dyn Model>, u32)>) -> AofResult<Self> {
+        if backends.is_empty() {
+            return Err(AofError::config("Load balancing needs at least one model"));
+        }
+        if backends.iter().any(|(_, weight)| *weight == 0) {
+            return Err(AofError::config(
+                "Load-balanced model weights must be positive",
+            ));
+        }
+
+        let state = backends.iter().map(|_| BackendState::default()).collect();
+        Ok(Self {
+            backends: backends
+                .into_iter()
+                .map(|(model, weight)| Backend { model, weight })
+                .collect(),
+            state: Mutex::new(state),
+            cooldown: DEFAULT_RATE_LIMIT_COOLDOWN,
+        })
+    }
+
+    /// Skip rate-limited backends for `cooldown` when they send no `Retry-After`
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Index of the backend for the next call
+    ///
+    /// With every backend cooling down, fails with `RateLimited` until the
+    /// first one is available again.
+    fn next_backend(&self) -> AofResult<usize> {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        let available: Vec<usize> = (0..self.backends.len())
+            .filter(|&i| state[i].cooling_until.map_or(true, |until| until <= now))
+            .collect();
+        if available.is_empty() {
+            let retry_after = state
+                .iter()
+                .filter_map(|backend| backend.cooling_until)
+                .min()
+                .map(|until| until - now);
+            return Err(AofError::rate_limited(retry_after));
+        }
+
+        // Smooth weighted round-robin over the available backends
+        let mut total = 0;
+        for &i in &available {
+            let weight = i64::from(self.backends[i].weight);
+            state[i].current += weight;
+            total += weight;
+        }
+        let chosen = available
+            .into_iter()
+            .max_by_key(|&i| (state[i].current, std::cmp::Reverse(i)))
+            .expect("at least one backend is available");
+        state[chosen].current -= total;
+        state[chosen].cooling_until = None;
+        Ok(chosen)
+    }
+
+    /// Skip backend `index` after it answered `RateLimited`
+    fn cool_down(&self, index: usize, retry_after: Option<Duration>) {
+        let cooldown = retry_after.unwrap_or(self.cooldown);
+        tracing::warn!(
+            "Load-balanced backend {} rate limited, skipping it for {:?}",
+            index,
+            cooldown
+        );
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state[index].cooling_until = Some(Instant::now() + cooldown);
+    }
+}
+
+#[async_trait]
+impl Model for LoadBalancedModel {
+    async fn generate(&self, request: &ModelRequest) -> AofResult<ModelResponse> {
+        loop {
+            let index = self.next_backend()?;
+            match self.backends[index].model.generate(request).await {
+                Err(AofError::RateLimited { retry_after }) => self.cool_down(index, retry_after),
+                result => return result,
+            }
+        }
+    }
+
+    async fn generate_stream(
+        &self,
+        request: &ModelRequest,
+    ) -> AofResult<Pin<Box<dyn Stream<Item = AofResult<StreamChunk>> + Send>>> {
+        loop {
+            let index = self.next_backend()?;
+            match self.backends[index].model.generate_stream(request).await {
+                Err(AofError::RateLimited { retry_after }) => self.cool_down(index, retry_after),
+                result => return result,
+            }
+        }
+    }
+
+    fn config(&self) -> &ModelConfig {
+        self.backends[0].model.config()
+    }
+
+    fn provider(&self) -> ModelProvider {
+        self.backends[0].model.provider()
+    }
+
+    fn count_tokens(&self, text: &str) -> usize {
+        self.backends[0].model.count_tokens(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aof_core::{StopReason, Usage};
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Backend counting its calls, optionally answering `RateLimited`
+    struct CountingModel {
+        calls: Arc<AtomicUsize>,
+        rate_limited: Arc<AtomicBool>,
+        config: ModelConfig,
+    }
+
+    impl CountingModel {
+        fn new() -> (Self, Arc<AtomicUsize>, Arc<AtomicBool>) {
+            let calls = Arc::new(AtomicUsize::new(0));
+            let rate_limited = Arc::new(AtomicBool::new(false));
+            let model = Self {
+                calls: Arc::clone(&calls),
+                rate_limited: Arc::clone(&rate_limited),
+                config: ModelConfig {
+                    model: "gpt-4o".to_string(),
+                    provider: ModelProvider::OpenAI,
+                    api_key: None,
+                    endpoint: None,
+                    temperature: 0.7,
+                    max_tokens: None,
+                    timeout_secs: 60,
+                    headers: HashMap::new(),
+                    extra: HashMap::new(),
+                },
+            };
+            (model, calls, rate_limited)
+        }
+    }
+
+    #[async_trait]
+    impl Model for CountingModel {
+        async fn generate(&self, _request: &ModelRequest) -> AofResult<ModelResponse> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.rate_limited.load(Ordering::SeqCst) {
+                return Err(AofError::rate_limited(Some(Duration::from_secs(10))));
+            }
+            Ok(ModelResponse {
+                content: "ok".to_string(),
+                tool_calls: vec![],
+                stop_reason: StopReason::EndTurn,
+                usage: Usage::default(),
+                stop_detail: None,
+                metadata: HashMap::new(),
+            })
+        }
+
+        async fn generate_stream(
+            &self,
+            _request: &ModelRequest,
+        ) -> AofResult<Pin<Box<dyn Stream<Item = AofResult<StreamChunk>> + Send>>> {
+            unimplemented!("not used by these tests")
+        }
+
+        fn config(&self) -> &ModelConfig {
+            &self.config
+        }
+
+        fn provider(&self) -> ModelProvider {
+            ModelProvider::OpenAI
+        }
+    }
+
+    fn balanced(
+        weights: &[u32],
+    ) -> (
+        LoadBalancedModel,
+        Vec<Arc<AtomicUsize>>,
+        Vec<Arc<AtomicBool>>,
+    ) {
+        let mut backends: Vec<(Box<dyn Model>, u32)> = Vec::new();
+        let mut calls = Vec::new();
+        let mut limits = Vec::new();
+        for &weight in weights {
+            let (model, count, limited) = CountingModel::new();
+            backends.push((Box::new(model), weight));
+            calls.push(count);
+            limits.push(limited);
+        }
+        (LoadBalancedModel::new(backends).unwrap(), calls, limits)
+    }
+
+    fn counts(calls: &[Arc<AtomicUsize>]) -> Vec<usize> {
+        calls
+            .iter()
+            .map(|count| count.load(Ordering::SeqCst))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_calls_follow_the_weights() {
+        let (model, calls, _) = balanced(&[5, 3, 2]);
+
+        // Interleaved within each round of ten calls
+        for _ in 0..5 {
+            model.generate(&ModelRequest::default()).await.unwrap();
+        }
+        assert_eq!(counts(&calls), [3, 1, 1]);
+
+        for _ in 0..95 {
+            model.generate(&ModelRequest::default()).await.unwrap();
+        }
+        assert_eq!(counts(&calls), [50, 30, 20]);
+
+        assert!(LoadBalancedModel::new(vec![]).is_err());
+        let (zero, _, _) = CountingModel::new();
+        assert!(LoadBalancedModel::new(vec![(Box::new(zero), 0)]).is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rate_limited_backend_is_skipped_until_it_cools_down() {
+        let (model, calls, limits) = balanced(&[1, 1]);
+        limits[0].store(true, Ordering::SeqCst);
+
+        // The limited call moves on to the other backend
+        for _ in 0..4 {
+            model.generate(&ModelRequest::default()).await.unwrap();
+        }
+        assert_eq!(counts(&calls), [1, 4]);
+
+        // Back in rotation once its Retry-After has passed
+        limits[0].store(false, Ordering::SeqCst);
+        tokio::time::advance(Duration::from_secs(11)).await;
+        for _ in 0..4 {
+            model.generate(&ModelRequest::default()).await.unwrap();
+        }
+        assert_eq!(counts(&calls), [3, 6]);
+
+        // With every backend limited the caller is told when to retry
+        limits[0].store(true, Ordering::SeqCst);
+        limits[1].store(true, Ordering::SeqCst);
+        let err = model.generate(&ModelRequest::default()).await.unwrap_err();
+        assert!(matches!(
+            err,
+            AofError::RateLimited {
+                retry_after: Some(delay)
+            } if delay == Duration::from_secs(10)
+        ));
+    }
+}
@@ -0,0 +1,526 @@
+// Text embeddings for semantic memory and retrieval
+
+use aof_core::{AofError, AofResult, ModelConfig, ModelProvider};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use crate::provider::http_error;
+use crate::provider::ollama::{normalize_host, DEFAULT_OLLAMA_HOST};
+
+/// Maximum inputs per OpenAI embeddings request
+const OPENAI_MAX_BATCH: usize = 2048;
+
+/// Maximum requests per Gemini `batchEmbedContents` call
+const GOOGLE_MAX_BATCH: usize = 100;
+
+/// Inputs per Ollama `/api/embed` call (no hard limit; keeps local requests small)
+const OLLAMA_MAX_BATCH: usize = 64;
+
+/// Text embedding model
+#[async_trait]
+pub trait Embedder: Send + Sync {
+    /// Embed each text, returning one vector per input in the same order
+    ///
+    /// Inputs are split into batches that respect the provider's limits.
+    async fn embed(&self, texts: &[String]) -> AofResult<Vec<Vec<f32>>>;
+
+    /// Vector dimension (0 until known for unrecognised models)
+    fn embedding_dim(&self) -> usize;
+
+    /// Provider type
+    fn provider(&self) -> ModelProvider;
+}
+
+/// Known output dimensions for common embedding models
+fn known_dimension(model: &str) -> Option<usize> {
+    let model = model.rsplit('/').next().unwrap_or(model);
+    let model = model.split(':').next().unwrap_or(model);
+    match model {
+        "text-embedding-3-small" | "text-embedding-ada-002" => Some(1536),
+        "text-embedding-3-large" => Some(3072),
+        "text-embedding-004" | "text-embedding-005" | "embedding-001" => Some(768),
+        "gemini-embedding-001" => Some(3072),
+        "nomic-embed-text" => Some(768),
+        "mxbai-embed-large" => Some(1024),
+        "all-minilm" => Some(384),
+        _ => None,
+    }
+}
+
+/// Batching, dimension tracking and HTTP client shared by all embedders
+struct EmbedderCore {
+    config: ModelConfig,
+    client: Client,
+    batch_size: usize,
+    dimension: AtomicUsize,
+}
+
+impl EmbedderCore {
+    fn new(config: ModelConfig, max_batch: usize) -> AofResult<Self> {
+        // `extra.dimensions` overrides the model default (OpenAI v3 models support shortening)
+        let dimension = config
+            .extra
+            .get("dimensions")
+            .and_then(|v| v.as_u64())
+            .map(|d| d as usize)
+            .or_else(|| known_dimension(&config.model))
+            .unwrap_or(0);
+
+        let batch_size = config
+            .extra
+            .get("batch_size")
+            .and_then(|v| v.as_u64())
+            .map(|b| (b as usize).clamp(1, max_batch))
+            .unwrap_or(max_batch);
+
+        // Build HTTP client with timeout
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(|e| AofError::model(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self {
+            config,
+            client,
+            batch_size,
+            dimension: AtomicUsize::new(dimension),
+        })
+    }
+
+    /// POST a JSON body and decode the JSON response
+    async fn post<T: for<'de> Deserialize<'de>>(
+        &self,
+        url: &str,
+        bearer: Option<&str>,
+        body: &impl Serialize,
+    ) -> AofResult<T> {
+        let mut request = self.client.post(url).json(body);
+        if let Some(token) = bearer {
+            request = request.bearer_auth(token);
+        }
+        for (name, value) in &self.config.headers {
+            request = request.header(name, value);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| AofError::model(format!("Embedding request failed: {}", e)))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(http_error("Embedding API error", response).await);
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| AofError::model(format!("Failed to parse embedding response: {}", e)))
+    }
+
+    /// Check a batch result and record the dimension on first use
+    fn check_batch(&self, expected: usize, vectors: &[Vec<f32>]) -> AofResult<()> {
+        if vectors.len() != expected {
+            return Err(AofError::model(format!(
+                "Embedding API returned {} vectors for {} inputs",
+                vectors.len(),
+                expected
+            )));
+        }
+
+        for vector in vectors {
+            let dim = match self.dimension.load(Ordering::Relaxed) {
+                0 => {
+                    self.dimension.store(vector.len(), Ordering::Relaxed);
+                    vector.len()
+                }
+                dim => dim,
+            };
+            if vector.len() != dim {
+                return Err(AofError::model(format!(
+                    "Embedding has dimension {}, expected {}",
+                    vector.len(),
+                    dim
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// OpenAI embeddings (`/embeddings`)
+pub struct OpenAIEmbedder {
+    core: EmbedderCore,
+    api_key: String,
+    endpoint: String,
+}
+
+impl OpenAIEmbedder {
+    /// Create new OpenAI embedder
+    pub fn new(config: ModelConfig) -> AofResult<Self> {
+        let api_key = config
+            .api_key
+            .clone()
+            .or_else(|| std::env::var("OPENAI_API_KEY").ok())
+            .ok_or_else(|| {
+                AofError::config("OPENAI_API_KEY not found in config or environment")
+            })?;
+
+        let endpoint = config
+            .endpoint
+            .clone()
+            .unwrap_or_else(|| "https://api.openai.com/v1".to_string())
+            .trim_end_matches('/')
+            .to_string();
+
+        Ok(Self {
+            core: EmbedderCore::new(config, OPENAI_MAX_BATCH)?,
+            api_key,
+            endpoint,
+        })
+    }
+}
+
+#[async_trait]
+impl Embedder for OpenAIEmbedder {
+    async fn embed(&self, texts: &[String]) -> AofResult<Vec<Vec<f32>>> {
+        let url = format!("{}/embeddings", self.endpoint);
+        let mut vectors = Vec::with_capacity(texts.len());
+
+        for batch in texts.chunks(self.core.batch_size) {
+            let mut body = json!({
+                "model": self.core.config.model,
+                "input": batch,
+            });
+            if let Some(dimensions) = self.core.config.extra.get("dimensions") {
+                body["dimensions"] = dimensions.clone();
+            }
+
+            let response: OpenAIEmbeddingResponse =
+                self.core.post(&url, Some(&self.api_key), &body).await?;
+
+            // Results carry their input index; don't rely on response order
+            let mut data = response.data;
+            data.sort_by_key(|d| d.index);
+            let batch_vectors: Vec<Vec<f32>> = data.into_iter().map(|d| d.embedding).collect();
+
+            self.core.check_batch(batch.len(), &batch_vectors)?;
+            vectors.extend(batch_vectors);
+        }
+
+        Ok(vectors)
+    }
+
+    fn embedding_dim(&self) -> usize {
+        self.core.dimension.load(Ordering::Relaxed)
+    }
+
+    fn provider(&self) -> ModelProvider {
+        ModelProvider::OpenAI
+    }
+}
+
+/// Google Gemini embeddings (`batchEmbedContents`)
+pub struct GoogleEmbedder {
+    core: EmbedderCore,
+    api_key: String,
+    endpoint: String,
+}
+
+impl GoogleEmbedder {
+    /// Create new Google embedder
+    pub fn new(config: ModelConfig) -> AofResult<Self> {
+        let api_key = config
+            .api_key
+            .clone()
+            .or_else(|| std::env::var("GOOGLE_API_KEY").ok())
+            .ok_or_else(|| {
+                AofError::config("GOOGLE_API_KEY not found in config or environment")
+            })?;
+
+        let endpoint = config
+            .endpoint
+            .clone()
+            .unwrap_or_else(|| "https://generativelanguage.googleapis.com/v1beta".to_string())
+            .trim_end_matches('/')
+            .to_string();
+
+        Ok(Self {
+            core: EmbedderCore::new(config, GOOGLE_MAX_BATCH)?,
+            api_key,
+            endpoint,
+        })
+    }
+}
+
+#[async_trait]
+impl Embedder for GoogleEmbedder {
+    async fn embed(&self, texts: &[String]) -> AofResult<Vec<Vec<f32>>> {
+        let model = format!("models/{}", self.core.config.model);
+        let url = format!(
+            "{}/{}:batchEmbedContents?key={}",
+            self.endpoint, model, self.api_key
+        );
+        let mut vectors = Vec::with_capacity(texts.len());
+
+        for batch in texts.chunks(self.core.batch_size) {
+            let requests: Vec<serde_json::Value> = batch
+                .iter()
+                .map(|text| {
+                    let mut request = json!({
+                        "model": model,
+                        "content": { "parts": [{ "text": text }] },
+                    });
+                    if let Some(dimensions) = self.core.config.extra.get("dimensions") {
+                        request["outputDimensionality"] = dimensions.clone();
+                    }
+                    request
+                })
+                .collect();
+
+            let response: GoogleEmbeddingResponse = self
+                .core
+                .post(&url, None, &json!({ "requests": requests }))
+                .await?;
+            let batch_vectors: Vec<Vec<f32>> =
+                response.embeddings.into_iter().map(|e| e.values).collect();
+
+            self.core.check_batch(batch.len(), &batch_vectors)?;
+            vectors.extend(batch_vectors);
+        }
+
+        Ok(vectors)
+    }
+
+    fn embedding_dim(&self) -> usize {
+        self.core.dimension.load(Ordering::Relaxed)
+    }
+
+    fn provider(&self) -> ModelProvider {
+        ModelProvider::Google
+    }
+}
+
+/// Ollama embeddings (`/api/embed`)
+pub struct OllamaEmbedder {
+    core: EmbedderCore,
+    endpoint: String,
+}
+
+impl OllamaEmbedder {
+    /// Create new Ollama embedder
+    pub fn new(config: ModelConfig) -> AofResult<Self> {
+        let host = config
+            .endpoint
+            .clone()
+            .or_else(|| std::env::var("OLLAMA_HOST").ok())
+            .unwrap_or_else(|| DEFAULT_OLLAMA_HOST.to_string());
+
+        Ok(Self {
+            endpoint: normalize_host(&host),
+            core: EmbedderCore::new(config, OLLAMA_MAX_BATCH)?,
+        })
+    }
+}
+
+#[async_trait]
+impl Embedder for OllamaEmbedder {
+    async fn embed(&self, texts: &[String]) -> AofResult<Vec<Vec<f32>>> {
+        let url = format!("{}/api/embed", self.endpoint);
+        let mut vectors = Vec::with_capacity(texts.len());
+
+        for batch in texts.chunks(self.core.batch_size) {
+            let body = json!({
+                "model": self.core.config.model,
+                "input": batch,
+            });
+
+            let response: OllamaEmbeddingResponse = self.core.post(&url, None, &body).await?;
+
+            self.core.check_batch(batch.len(), &response.embeddings)?;
+            vectors.extend(response.embeddings);
+        }
+
+        Ok(vectors)
+    }
+
+    fn embedding_dim(&self) -> usize {
+        self.core.dimension.load(Ordering::Relaxed)
+    }
+
+    fn provider(&self) -> ModelProvider {
+        ModelProvider::Ollama
+    }
+}
+
+// Embedding API types
+
+#[derive(Debug, Deserialize)]
+struct OpenAIEmbeddingResponse {
+    data: Vec<OpenAIEmbedding>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAIEmbedding {
+    #[serde(default)]
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GoogleEmbeddingResponse {
+    #[serde(default)]
+    embeddings: Vec<GoogleEmbedding>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GoogleEmbedding {
+    values: Vec<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaEmbeddingResponse {
+    #[serde(default)]
+    embeddings: Vec<Vec<f32>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProviderFactory;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+
+    fn embedding_config(provider: ModelProvider, model: &str) -> ModelConfig {
+        ModelConfig {
+            model: model.to_string(),
+            provider,
+            api_key: Some("test".to_string()),
+            endpoint: None,
+            temperature: 0.0,
+            max_tokens: None,
+            timeout_secs: 60,
+            headers: HashMap::new(),
+            extra: HashMap::new(),
+        }
+    }
+
+    /// Serve OpenAI-style embedding responses, recording each batch size
+    async fn mock_embedding_server(dim: usize) -> (String, Arc<std::sync::Mutex<Vec<usize>>>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let batches = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = batches.clone();
+
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                let (reader, mut writer) = socket.into_split();
+                let mut reader = BufReader::new(reader);
+
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).await.unwrap();
+                    let line = line.trim_end();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).await.unwrap();
+                let request: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+                let inputs = request["input"].as_array().unwrap().len();
+                recorded.lock().unwrap().push(inputs);
+
+                // Return results out of order to check index sorting
+                let data: Vec<serde_json::Value> = (0..inputs)
+                    .rev()
+                    .map(|index| json!({ "index": index, "embedding": vec![index as f32; dim] }))
+                    .collect();
+                let response = json!({ "data": data }).to_string();
+                writer
+                    .write_all(
+                        format!(
+                            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                            response.len(),
+                            response
+                        )
+                        .as_bytes(),
+                    )
+                    .await
+                    .unwrap();
+            }
+        });
+
+        (format!("http://{}/v1", addr), batches)
+    }
+
+    #[tokio::test]
+    async fn test_create_embedder() {
+        let embedder = ProviderFactory::create_embedder(embedding_config(
+            ModelProvider::OpenAI,
+            "text-embedding-3-small",
+        ))
+        .unwrap();
+        assert_eq!(embedder.provider(), ModelProvider::OpenAI);
+        assert_eq!(embedder.embedding_dim(), 1536);
+
+        let embedder = ProviderFactory::create_embedder(embedding_config(
+            ModelProvider::Google,
+            "text-embedding-004",
+        ))
+        .unwrap();
+        assert_eq!(embedder.embedding_dim(), 768);
+
+        let embedder = ProviderFactory::create_embedder(embedding_config(
+            ModelProvider::Ollama,
+            "nomic-embed-text:latest",
+        ))
+        .unwrap();
+        assert_eq!(embedder.provider(), ModelProvider::Ollama);
+        assert_eq!(embedder.embedding_dim(), 768);
+
+        assert!(ProviderFactory::create_embedder(embedding_config(
+            ModelProvider::Anthropic,
+            "claude-3-5-sonnet"
+        ))
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_embed_round_trip_in_batches() {
+        let (endpoint, batches) = mock_embedding_server(8).await;
+
+        let mut config = embedding_config(ModelProvider::OpenAI, "custom-embedder");
+        config.endpoint = Some(endpoint);
+        config.extra.insert("batch_size".to_string(), json!(2));
+        let embedder = OpenAIEmbedder::new(config).unwrap();
+        assert_eq!(embedder.embedding_dim(), 0);
+
+        let texts: Vec<String> = ["disk full", "oom killed", "crash loop"]
+            .iter()
+            .map(|t| t.to_string())
+            .collect();
+        let vectors = embedder.embed(&texts).await.unwrap();
+
+        assert_eq!(vectors.len(), 3);
+        assert!(vectors.iter().all(|v| v.len() == 8));
+        // Second batch restarts at index 0
+        assert_eq!(vectors[1][0], 1.0);
+        assert_eq!(vectors[2][0], 0.0);
+        assert_eq!(embedder.embedding_dim(), 8);
+        assert_eq!(*batches.lock().unwrap(), vec![2, 1]);
+    }
+}
@@ -3,9 +3,13 @@
 // Supports: Anthropic, OpenAI, Bedrock, Azure, Ollama
 // Optimized for minimal allocations and fast streaming
 
+pub mod balance;
+pub mod embedding;
 pub mod provider;
 pub mod stream;
 
+pub use balance::LoadBalancedModel;
+pub use embedding::Embedder;
 pub use provider::{LlmProvider, ProviderFactory};
 
 // Re-export from aof-core
@@ -1,12 +1,135 @@
 use aof_core::{AofError, AofResult, Model, ModelConfig, ModelProvider};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::embedding::{Embedder, GoogleEmbedder, OllamaEmbedder, OpenAIEmbedder};
 
 pub mod anthropic;
 pub mod google;
+pub mod ollama;
 pub mod openai;
 
 #[cfg(feature = "bedrock")]
 pub mod bedrock;
 
+/// Groq's OpenAI-compatible API
+const GROQ_API_URL: &str = "https://api.groq.com/openai/v1";
+
+/// Point an OpenAI-compatible config at Groq
+///
+/// Requires `GROQ_API_KEY` (or an explicit key) so an OpenAI key is never
+/// sent to Groq by the OpenAI client's own fallback.
+fn groq_config(mut config: ModelConfig) -> AofResult<ModelConfig> {
+    if config.endpoint.is_none() {
+        config.endpoint = Some(GROQ_API_URL.to_string());
+    }
+    if config.api_key.is_none() {
+        config.api_key = Some(std::env::var("GROQ_API_KEY").map_err(|_| {
+            AofError::config("GROQ_API_KEY not found in config or environment")
+        })?);
+    }
+    Ok(config)
+}
+
+/// Error-body fragments providers use when a prompt exceeds the context window
+const CONTEXT_OVERFLOW_MARKERS: &[&str] = &[
+    // OpenAI, Groq and Azure error code
+    "context_length_exceeded",
+    // OpenAI-compatible servers (vLLM, LiteLLM)
+    "maximum context length",
+    // Anthropic
+    "prompt is too long",
+    // Gemini
+    "exceeds the maximum number of tokens",
+    // Bedrock
+    "input is too long",
+    "too many input tokens",
+    // llama.cpp and Ollama
+    "exceeds the available context size",
+];
+
+/// Whether an error response reports a prompt longer than the context window
+pub(crate) fn is_context_overflow(status: u16, body: &str) -> bool {
+    if !matches!(status, 400 | 413) {
+        return false;
+    }
+    let body = body.to_lowercase();
+    CONTEXT_OVERFLOW_MARKERS
+        .iter()
+        .any(|marker| body.contains(marker))
+}
+
+/// Structured error for a non-success HTTP response
+///
+/// 429 becomes `RateLimited` carrying the `Retry-After` delay, a prompt
+/// longer than the context window becomes `ContextOverflow`, and any other
+/// status becomes `Http` with the response body.
+pub(crate) async fn http_error(context: &str, response: reqwest::Response) -> AofError {
+    let status = response.status().as_u16();
+    if status == 429 {
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_retry_after);
+        tracing::warn!("{}: rate limited (retry after {:?})", context, retry_after);
+        return AofError::rate_limited(retry_after);
+    }
+
+    let body = response
+        .text()
+        .await
+        .unwrap_or_else(|_| "Unknown error".to_string());
+    if is_context_overflow(status, &body) {
+        return AofError::context_overflow(format!("{}: {}", context, body));
+    }
+    AofError::http(status, format!("{}: {}", context, body))
+}
+
+/// Fail a request carrying images for a provider without image input
+pub(crate) fn reject_images(provider: &str, request: &aof_core::ModelRequest) -> AofResult<()> {
+    if request.has_images() {
+        return Err(AofError::model(format!(
+            "{} models don't take image inputs; use an OpenAI, Anthropic or Google vision model",
+            provider
+        )));
+    }
+    Ok(())
+}
+
+/// Custom headers for a request: `ModelConfig.headers` overridden by the
+/// request's own `request_headers`
+///
+/// Names are case-insensitive; entries that aren't valid HTTP headers are skipped.
+pub(crate) fn custom_headers(
+    config: &ModelConfig,
+    request_headers: &HashMap<String, String>,
+) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for (name, value) in config.headers.iter().chain(request_headers) {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            headers.insert(name, value);
+        }
+    }
+    headers
+}
+
+/// Parse a `Retry-After` header: delay in seconds or an HTTP date
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let delay = at.signed_duration_since(chrono::Utc::now());
+    Some(delay.to_std().unwrap_or(Duration::ZERO))
+}
+
 /// LLM provider trait
 pub trait LlmProvider {
     fn create(config: ModelConfig) -> AofResult<Box<dyn Model>>;
@@ -16,43 +139,236 @@ pub trait LlmProvider {
 pub struct ProviderFactory;
 
 impl ProviderFactory {
-    pub async fn create(config: ModelConfig) -> AofResult<Box<dyn Model>> {
+    /// Create a model for `config`
+    ///
+    /// A `Custom` provider is resolved from the model id when it names a
+    /// known model family.
+    pub async fn create(mut config: ModelConfig) -> AofResult<Box<dyn Model>> {
+        if config.provider == ModelProvider::Custom {
+            if let Some(provider) = ModelProvider::infer_from_model(&config.model) {
+                config.provider = provider;
+            }
+        }
+
         match config.provider {
             ModelProvider::Anthropic => anthropic::AnthropicProvider::create(config),
             ModelProvider::OpenAI => openai::OpenAIProvider::create(config),
             ModelProvider::Google => google::GoogleProvider::create(config),
-            ModelProvider::Groq => {
-                // Groq uses OpenAI-compatible API with different endpoint
-                let mut groq_config = config;
-                if groq_config.endpoint.is_none() {
-                    groq_config.endpoint = Some("https://api.groq.com/openai/v1".to_string());
-                }
-                if groq_config.api_key.is_none() {
-                    groq_config.api_key = std::env::var("GROQ_API_KEY").ok();
-                }
-                openai::OpenAIProvider::create(groq_config)
-            }
+            ModelProvider::Groq => openai::OpenAIProvider::create(groq_config(config)?),
             #[cfg(feature = "bedrock")]
             ModelProvider::Bedrock => bedrock::BedrockProvider::create(config).await,
             #[cfg(not(feature = "bedrock"))]
             ModelProvider::Bedrock => Err(AofError::config("Bedrock provider not enabled - requires 'bedrock' feature")),
-            ModelProvider::Ollama => {
-                // Ollama uses OpenAI-compatible API at localhost
-                let mut ollama_config = config;
-                if ollama_config.endpoint.is_none() {
-                    ollama_config.endpoint = Some(
-                        std::env::var("OLLAMA_HOST")
-                            .unwrap_or_else(|_| "http://localhost:11434/v1".to_string())
-                    );
-                }
-                // Ollama doesn't require API key
-                if ollama_config.api_key.is_none() {
-                    ollama_config.api_key = Some("ollama".to_string());
-                }
-                openai::OpenAIProvider::create(ollama_config)
-            }
+            ModelProvider::Ollama => ollama::OllamaProvider::create(config),
             ModelProvider::Azure => Err(AofError::config("Azure provider not yet implemented")),
             ModelProvider::Custom => Err(AofError::config("Custom provider requires manual implementation")),
         }
     }
+
+    /// Create an embedder for `config`
+    ///
+    /// Supported for OpenAI, Google and Ollama embedding models.
+    pub fn create_embedder(config: ModelConfig) -> AofResult<Box<dyn Embedder>> {
+        match config.provider {
+            ModelProvider::OpenAI => Ok(Box::new(OpenAIEmbedder::new(config)?)),
+            ModelProvider::Google => Ok(Box::new(GoogleEmbedder::new(config)?)),
+            ModelProvider::Ollama => Ok(Box::new(OllamaEmbedder::new(config)?)),
+            provider => Err(AofError::config(format!(
+                "Embeddings are not supported for provider '{}'",
+                provider.name()
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_context_overflow_detection() {
+        let overflows = [
+            r#"{"error":{"message":"This model's maximum context length is 128000 tokens.","code":"context_length_exceeded"}}"#,
+            r#"{"type":"error","error":{"type":"invalid_request_error","message":"prompt is too long: 210000 tokens > 200000 maximum"}}"#,
+            r#"{"error":{"code":400,"message":"The input token count (1200000) exceeds the maximum number of tokens allowed (1048576)."}}"#,
+            "ValidationException: Input is too long for requested model.",
+            r#"{"error":"the request exceeds the available context size, try increasing it"}"#,
+        ];
+        for body in overflows {
+            assert!(is_context_overflow(400, body), "{}", body);
+        }
+
+        assert!(!is_context_overflow(400, r#"{"error":"invalid model"}"#));
+        assert!(!is_context_overflow(500, "maximum context length"));
+    }
+
+    #[test]
+    fn test_groq_provider_construction() {
+        let config = ModelConfig {
+            model: "llama-3.3-70b-versatile".to_string(),
+            provider: ModelProvider::Groq,
+            api_key: Some("gsk-test".to_string()),
+            endpoint: None,
+            temperature: 0.7,
+            max_tokens: None,
+            timeout_secs: 60,
+            headers: HashMap::new(),
+            extra: HashMap::new(),
+        };
+
+        let model = openai::OpenAIModel::new(groq_config(config).unwrap()).unwrap();
+
+        assert_eq!(
+            model.completions_url(),
+            "https://api.groq.com/openai/v1/chat/completions"
+        );
+        assert_eq!(
+            model.request_headers(&HashMap::new())[reqwest::header::AUTHORIZATION],
+            "Bearer gsk-test"
+        );
+        assert_eq!(model.provider(), ModelProvider::Groq);
+    }
+
+    #[tokio::test]
+    async fn test_custom_provider_inferred_from_model() {
+        let config = ModelConfig {
+            model: "claude-3-5-sonnet-20241022".to_string(),
+            provider: ModelProvider::Custom,
+            api_key: Some("sk-ant-test".to_string()),
+            endpoint: None,
+            temperature: 0.7,
+            max_tokens: None,
+            timeout_secs: 60,
+            headers: HashMap::new(),
+            extra: HashMap::new(),
+        };
+
+        let model = ProviderFactory::create(config.clone()).await.unwrap();
+        assert_eq!(model.provider(), ModelProvider::Anthropic);
+
+        let config = ModelConfig {
+            model: "acme-large".to_string(),
+            ..config
+        };
+        assert!(ProviderFactory::create(config).await.is_err());
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(parse_retry_after("7"), Some(Duration::from_secs(7)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_response_carries_retry_after() {
+        use aof_core::{ModelRequest, RequestMessage};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = vec![0; 8192];
+            let _ = socket.read(&mut request).await.unwrap();
+            let body = r#"{"error":{"message":"Rate limit reached"}}"#;
+            let response = format!(
+                "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 7\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        });
+
+        let config = ModelConfig {
+            model: "gpt-4o".to_string(),
+            provider: ModelProvider::OpenAI,
+            api_key: Some("sk-test".to_string()),
+            endpoint: Some(format!("http://{}/v1", addr)),
+            temperature: 0.7,
+            max_tokens: None,
+            timeout_secs: 60,
+            headers: HashMap::new(),
+            extra: HashMap::new(),
+        };
+        let model = ProviderFactory::create(config).await.unwrap();
+        let request = ModelRequest {
+            messages: vec![RequestMessage {
+                role: aof_core::model::MessageRole::User,
+                content: "hi".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        match model.generate(&request).await {
+            Err(AofError::RateLimited { retry_after }) => {
+                assert_eq!(retry_after, Some(Duration::from_secs(7)));
+            }
+            other => panic!("expected RateLimited, got {:?}", other.map(|r| r.content)),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_headers_override_config_headers() {
+        use aof_core::{ModelRequest, RequestMessage};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = vec![0; 8192];
+            let len = socket.read(&mut request).await.unwrap();
+            let body = r#"{"choices":[{"message":{"role":"assistant","content":"ok"},"finish_reason":"stop"}],"usage":{"prompt_tokens":1,"completion_tokens":1}}"#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&request[..len]).to_lowercase()
+        });
+
+        let config = ModelConfig {
+            model: "gpt-4o".to_string(),
+            provider: ModelProvider::OpenAI,
+            api_key: Some("sk-test".to_string()),
+            endpoint: Some(format!("http://{}/v1", addr)),
+            temperature: 0.7,
+            max_tokens: None,
+            timeout_secs: 60,
+            headers: HashMap::from([
+                ("X-Tenant-Id".to_string(), "default".to_string()),
+                ("X-Team".to_string(), "sre".to_string()),
+            ]),
+            extra: HashMap::new(),
+        };
+        let model = ProviderFactory::create(config).await.unwrap();
+        let request = ModelRequest {
+            messages: vec![RequestMessage {
+                role: aof_core::model::MessageRole::User,
+                content: "hi".to_string(),
+                ..Default::default()
+            }],
+            request_headers: HashMap::from([
+                ("x-tenant-id".to_string(), "acme".to_string()),
+                ("X-Budget-Token".to_string(), "budget-123".to_string()),
+            ]),
+            ..Default::default()
+        };
+
+        model.generate(&request).await.unwrap();
+        let sent = server.await.unwrap();
+
+        let header_lines: Vec<_> = sent.lines().collect();
+        assert!(header_lines.contains(&"x-tenant-id: acme"));
+        assert!(!sent.contains("default"));
+        assert!(header_lines.contains(&"x-team: sre"));
+        assert!(header_lines.contains(&"x-budget-token: budget-123"));
+    }
 }
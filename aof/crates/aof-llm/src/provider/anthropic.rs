@@ -1,7 +1,7 @@
-use aof_core::model::{StopReason, Usage};
+use aof_core::model::{MessageRole, StopReason, Usage};
 use aof_core::{
-    AofError, AofResult, Model, ModelConfig, ModelProvider, ModelRequest, ModelResponse,
-    StreamChunk, ToolCall,
+    AofError, AofResult, ContentPart, Model, ModelConfig, ModelProvider, ModelRequest,
+    ModelResponse, StreamChunk, ToolCall,
 };
 use async_trait::async_trait;
 use futures::{Stream, StreamExt};
@@ -12,9 +12,14 @@ use std::pin::Pin;
 use std::time::Duration;
 use tracing::{debug, error};
 
+use super::{custom_headers, http_error};
+
 const ANTHROPIC_API_URL: &str = "https://api.anthropic.com/v1/messages";
 const ANTHROPIC_API_VERSION: &str = "2023-06-01";
 
+/// Tool the model is forced to call when a response schema is requested
+const STRUCTURED_OUTPUT_TOOL: &str = "structured_output";
+
 /// Anthropic provider
 pub struct AnthropicProvider;
 
@@ -50,57 +55,90 @@ pub struct AnthropicModel {
 }
 
 impl AnthropicModel {
-    /// Build base request with headers
-    fn build_request(&self, endpoint: &str) -> RequestBuilder {
+    /// Build base request with headers, custom ones included
+    fn build_request(
+        &self,
+        endpoint: &str,
+        request_headers: &HashMap<String, String>,
+    ) -> RequestBuilder {
         self.client
             .post(endpoint)
             .header("x-api-key", &self.api_key)
             .header("anthropic-version", ANTHROPIC_API_VERSION)
             .header("content-type", "application/json")
+            .headers(custom_headers(&self.config, request_headers))
     }
 
     /// Convert AOF request to Anthropic API format
     fn to_anthropic_request(&self, request: &ModelRequest) -> AnthropicRequest {
         // Convert messages
-        let messages: Vec<AnthropicMessage> = request
-            .messages
-            .iter()
-            .filter_map(|msg| {
+        let mut messages: Vec<AnthropicMessage> = Vec::new();
+        // Tool results without an id answer the previous tool calls in order
+        let mut pending_tool_ids = std::collections::VecDeque::new();
+        for msg in &request.messages {
+            let (role, content) = match msg.role {
                 // Skip system messages (handled separately)
-                if msg.role == aof_core::model::MessageRole::System {
-                    return None;
+                MessageRole::System => continue,
+                MessageRole::Tool => {
+                    let tool_use_id = msg
+                        .tool_call_id
+                        .clone()
+                        .or_else(|| pending_tool_ids.front().cloned())
+                        .unwrap_or_default();
+                    pending_tool_ids.retain(|id| *id != tool_use_id);
+                    let result = AnthropicContent::ToolResult {
+                        tool_use_id,
+                        content: msg.content.clone(),
+                    };
+
+                    // Results of parallel tool calls go back as one user turn
+                    match messages.last_mut() {
+                        Some(last) if last.answers_tool_calls() => {
+                            last.content.push(result);
+                            continue;
+                        }
+                        _ => ("user", vec![result]),
+                    }
                 }
-
-                // Convert message content
-                let mut content = vec![AnthropicContent::Text {
-                    text: msg.content.clone(),
-                }];
-
-                // Add tool results if present
-                if let Some(ref tool_calls) = msg.tool_calls {
+                MessageRole::User => {
+                    let mut content = Vec::new();
+                    if msg.parts.is_empty() || !msg.content.trim().is_empty() {
+                        content.push(AnthropicContent::Text {
+                            text: msg.content.clone(),
+                        });
+                    }
+                    content.extend(msg.parts.iter().map(AnthropicContent::from_part));
+                    ("user", content)
+                }
+                MessageRole::Assistant => {
+                    let tool_calls = msg.tool_calls.as_deref().unwrap_or_default();
+                    // Anthropic rejects blank text next to tool calls
+                    let mut content = Vec::new();
+                    if tool_calls.is_empty() || !msg.content.trim().is_empty() {
+                        content.push(AnthropicContent::Text {
+                            text: msg.content.clone(),
+                        });
+                    }
                     for tool_call in tool_calls {
-                        content.push(AnthropicContent::ToolResult {
-                            tool_use_id: tool_call.id.clone(),
-                            content: serde_json::to_string(&tool_call.arguments)
-                                .unwrap_or_default(),
+                        pending_tool_ids.push_back(tool_call.id.clone());
+                        content.push(AnthropicContent::ToolUse {
+                            id: tool_call.id.clone(),
+                            name: tool_call.name.clone(),
+                            input: tool_call.arguments.clone(),
                         });
                     }
+                    ("assistant", content)
                 }
+            };
 
-                Some(AnthropicMessage {
-                    role: match msg.role {
-                        aof_core::model::MessageRole::User => "user".to_string(),
-                        aof_core::model::MessageRole::Assistant => "assistant".to_string(),
-                        aof_core::model::MessageRole::Tool => "user".to_string(), // Tool results as user
-                        aof_core::model::MessageRole::System => return None,
-                    },
-                    content,
-                })
-            })
-            .collect();
+            messages.push(AnthropicMessage {
+                role: role.to_string(),
+                content,
+            });
+        }
 
         // Convert tools
-        let tools: Vec<AnthropicTool> = request
+        let mut tools: Vec<AnthropicTool> = request
             .tools
             .iter()
             .map(|tool| AnthropicTool {
@@ -110,6 +148,23 @@ impl AnthropicModel {
             })
             .collect();
 
+        // Structured output: the schema becomes a tool whose input is the answer.
+        // It is forced only when no real tools compete with it.
+        let mut tool_choice = None;
+        if let Some(schema) = &request.response_schema {
+            if tools.is_empty() {
+                tool_choice = Some(serde_json::json!({
+                    "type": "tool",
+                    "name": STRUCTURED_OUTPUT_TOOL,
+                }));
+            }
+            tools.push(AnthropicTool {
+                name: STRUCTURED_OUTPUT_TOOL.to_string(),
+                description: "Deliver the final answer in the required format".to_string(),
+                input_schema: schema.clone(),
+            });
+        }
+
         AnthropicRequest {
             model: self.config.model.clone(),
             messages,
@@ -118,6 +173,7 @@ impl AnthropicModel {
                 .max_tokens
                 .or(self.config.max_tokens)
                 .unwrap_or(4096),
+            stop_sequences: request.stop_sequences.clone(),
             temperature: request.temperature.or(Some(self.config.temperature)),
             stream: Some(request.stream),
             tools: if tools.is_empty() {
@@ -125,6 +181,7 @@ impl AnthropicModel {
             } else {
                 Some(tools)
             },
+            tool_choice,
         }
     }
 
@@ -139,6 +196,11 @@ impl AnthropicModel {
                 AnthropicContentBlock::Text { text } => {
                     content.push_str(&text);
                 }
+                AnthropicContentBlock::ToolUse { name, input, .. }
+                    if name == STRUCTURED_OUTPUT_TOOL =>
+                {
+                    content = input.to_string();
+                }
                 AnthropicContentBlock::ToolUse {
                     id,
                     name,
@@ -150,6 +212,7 @@ impl AnthropicModel {
                         arguments: input,
                     });
                 }
+                AnthropicContentBlock::Other => {}
             }
         }
 
@@ -158,9 +221,18 @@ impl AnthropicModel {
             Some("end_turn") => StopReason::EndTurn,
             Some("max_tokens") => StopReason::MaxTokens,
             Some("stop_sequence") => StopReason::StopSequence,
+            Some("refusal") => StopReason::ContentFilter,
+            // A structured answer alone ends the turn
+            Some("tool_use") if tool_calls.is_empty() => StopReason::EndTurn,
             Some("tool_use") => StopReason::ToolUse,
             _ => StopReason::EndTurn,
         };
+        let stop_detail = match stop_reason {
+            // Whatever Claude said while declining
+            StopReason::ContentFilter => Some(content.trim().to_string()).filter(|s| !s.is_empty()),
+            StopReason::StopSequence => response.stop_sequence,
+            _ => None,
+        };
 
         ModelResponse {
             content,
@@ -170,6 +242,7 @@ impl AnthropicModel {
                 input_tokens: response.usage.input_tokens,
                 output_tokens: response.usage.output_tokens,
             },
+            stop_detail,
             metadata: HashMap::new(),
         }
     }
@@ -211,6 +284,9 @@ impl AnthropicModel {
             AnthropicStreamEvent::ContentBlockDelta { delta: AnthropicDelta::TextDelta { text }, .. } => {
                 Some(Ok(StreamChunk::ContentDelta { delta: text }))
             }
+            AnthropicStreamEvent::ContentBlockDelta { delta: AnthropicDelta::ThinkingDelta { thinking }, .. } => {
+                Some(Ok(StreamChunk::ThinkingDelta { delta: thinking }))
+            }
             AnthropicStreamEvent::ContentBlockStart { .. } => None,
             AnthropicStreamEvent::ContentBlockDelta { .. } => None,
             AnthropicStreamEvent::MessageDelta {
@@ -222,6 +298,7 @@ impl AnthropicModel {
                     Some("max_tokens") => StopReason::MaxTokens,
                     Some("stop_sequence") => StopReason::StopSequence,
                     Some("tool_use") => StopReason::ToolUse,
+                    Some("refusal") => StopReason::ContentFilter,
                     _ => StopReason::EndTurn,
                 };
 
@@ -231,6 +308,7 @@ impl AnthropicModel {
                         output_tokens: stream_usage.output_tokens,
                     },
                     stop_reason,
+                    stop_detail: delta.stop_sequence,
                 }))
             }
             _ => None,
@@ -249,7 +327,7 @@ impl Model for AnthropicModel {
         let api_request = self.to_anthropic_request(request);
 
         let response = self
-            .build_request(ANTHROPIC_API_URL)
+            .build_request(ANTHROPIC_API_URL, &request.request_headers)
             .json(&api_request)
             .send()
             .await
@@ -257,15 +335,9 @@ impl Model for AnthropicModel {
 
         let status = response.status();
         if !status.is_success() {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            error!("Anthropic API error {}: {}", status, error_text);
-            return Err(AofError::model(format!(
-                "API error {}: {}",
-                status, error_text
-            )));
+            let error = http_error("Anthropic API error", response).await;
+            error!("{}", error);
+            return Err(error);
         }
 
         let api_response: AnthropicResponse = response
@@ -289,7 +361,7 @@ impl Model for AnthropicModel {
         api_request.stream = Some(true);
 
         let response = self
-            .build_request(ANTHROPIC_API_URL)
+            .build_request(ANTHROPIC_API_URL, &request.request_headers)
             .json(&api_request)
             .send()
             .await
@@ -297,15 +369,9 @@ impl Model for AnthropicModel {
 
         let status = response.status();
         if !status.is_success() {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            error!("Anthropic API error {}: {}", status, error_text);
-            return Err(AofError::model(format!(
-                "API error {}: {}",
-                status, error_text
-            )));
+            let error = http_error("Anthropic API error", response).await;
+            error!("{}", error);
+            return Err(error);
         }
 
         let byte_stream = response.bytes_stream();
@@ -379,12 +445,16 @@ struct AnthropicRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     system: Option<String>,
     max_tokens: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stop_sequences: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     temperature: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     stream: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tools: Option<Vec<AnthropicTool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Serialize)]
@@ -393,13 +463,60 @@ struct AnthropicMessage {
     content: Vec<AnthropicContent>,
 }
 
+impl AnthropicMessage {
+    /// Whether this is a user turn carrying only tool results
+    fn answers_tool_calls(&self) -> bool {
+        self.role == "user"
+            && self
+                .content
+                .iter()
+                .all(|c| matches!(c, AnthropicContent::ToolResult { .. }))
+    }
+}
+
 #[derive(Debug, Serialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 enum AnthropicContent {
-    Text { text: String },
+    Text {
+        text: String,
+    },
+    Image {
+        source: AnthropicImageSource,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
     ToolResult { tool_use_id: String, content: String },
 }
 
+impl AnthropicContent {
+    fn from_part(part: &ContentPart) -> Self {
+        match part {
+            ContentPart::Text { text } => Self::Text { text: text.clone() },
+            ContentPart::ImageUrl { image_url } => Self::Image {
+                source: match image_url.as_base64() {
+                    Some((media_type, data)) => AnthropicImageSource::Base64 {
+                        media_type: media_type.to_string(),
+                        data: data.to_string(),
+                    },
+                    None => AnthropicImageSource::Url {
+                        url: image_url.url.clone(),
+                    },
+                },
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum AnthropicImageSource {
+    Base64 { media_type: String, data: String },
+    Url { url: String },
+}
+
 #[derive(Debug, Serialize)]
 struct AnthropicTool {
     name: String,
@@ -417,6 +534,9 @@ struct AnthropicResponse {
     content: Vec<AnthropicContentBlock>,
     model: String,
     stop_reason: Option<String>,
+    /// The custom stop sequence that ended generation, if any
+    #[serde(default)]
+    stop_sequence: Option<String>,
     usage: AnthropicUsage,
 }
 
@@ -431,6 +551,9 @@ enum AnthropicContentBlock {
         name: String,
         input: serde_json::Value,
     },
+    /// Thinking and other blocks that are not part of the answer
+    #[serde(other)]
+    Other,
 }
 
 #[derive(Debug, Deserialize)]
@@ -479,13 +602,19 @@ struct AnthropicStreamMessage {
 #[serde(tag = "type", rename_all = "snake_case")]
 enum AnthropicDelta {
     TextDelta { text: String },
+    ThinkingDelta { thinking: String },
     #[allow(dead_code)]
     InputJsonDelta { partial_json: String },
+    /// Signature closing a thinking block
+    #[serde(other)]
+    Other,
 }
 
 #[derive(Debug, Deserialize)]
 struct AnthropicMessageDelta {
     stop_reason: Option<String>,
+    #[serde(default)]
+    stop_sequence: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -595,4 +724,249 @@ mod tests {
             std::env::set_var("ANTHROPIC_API_KEY", val);
         }
     }
+
+    #[test]
+    fn test_response_schema_forces_structured_tool() {
+        let model = AnthropicModel {
+            config: ModelConfig {
+                model: "claude-3-5-sonnet-20241022".to_string(),
+                provider: ModelProvider::Anthropic,
+                api_key: Some("test-key".to_string()),
+                endpoint: None,
+                temperature: 0.7,
+                max_tokens: Some(4096),
+                timeout_secs: 60,
+                headers: HashMap::new(),
+                extra: HashMap::new(),
+            },
+            api_key: "test-key".to_string(),
+            client: Client::new(),
+        };
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": { "healthy": { "type": "boolean" } }
+        });
+        let request = ModelRequest {
+            response_schema: Some(schema.clone()),
+            ..Default::default()
+        };
+
+        let api_request = serde_json::to_value(model.to_anthropic_request(&request)).unwrap();
+        assert_eq!(api_request["tools"][0]["name"], STRUCTURED_OUTPUT_TOOL);
+        assert_eq!(api_request["tools"][0]["input_schema"], schema);
+        assert_eq!(api_request["tool_choice"]["name"], STRUCTURED_OUTPUT_TOOL);
+
+        let api_response: AnthropicResponse = serde_json::from_value(serde_json::json!({
+            "id": "msg_1",
+            "type": "message",
+            "role": "assistant",
+            "content": [{
+                "type": "tool_use",
+                "id": "toolu_1",
+                "name": STRUCTURED_OUTPUT_TOOL,
+                "input": { "healthy": true }
+            }],
+            "model": "claude-3-5-sonnet-20241022",
+            "stop_reason": "tool_use",
+            "usage": { "input_tokens": 10, "output_tokens": 5 }
+        }))
+        .unwrap();
+
+        let response = model.convert_anthropic_response(api_response);
+        assert_eq!(response.content, r#"{"healthy":true}"#);
+        assert!(response.tool_calls.is_empty());
+        assert_eq!(response.stop_reason, StopReason::EndTurn);
+    }
+
+    #[test]
+    fn test_stop_sequences_sent_and_reported() {
+        let model = AnthropicModel {
+            config: ModelConfig {
+                model: "claude-3-5-sonnet-20241022".to_string(),
+                provider: ModelProvider::Anthropic,
+                api_key: Some("test-key".to_string()),
+                endpoint: None,
+                temperature: 0.7,
+                max_tokens: Some(4096),
+                timeout_secs: 60,
+                headers: HashMap::new(),
+                extra: HashMap::new(),
+            },
+            api_key: "test-key".to_string(),
+            client: Client::new(),
+        };
+        let request = ModelRequest {
+            stop_sequences: vec!["---END SUMMARY---".to_string()],
+            ..Default::default()
+        };
+
+        let api_request = serde_json::to_value(model.to_anthropic_request(&request)).unwrap();
+        assert_eq!(
+            api_request["stop_sequences"],
+            serde_json::json!(["---END SUMMARY---"])
+        );
+
+        let api_response: AnthropicResponse = serde_json::from_value(serde_json::json!({
+            "id": "msg_1",
+            "type": "message",
+            "role": "assistant",
+            "content": [{ "type": "text", "text": "3 pods restarting" }],
+            "model": "claude-3-5-sonnet-20241022",
+            "stop_reason": "stop_sequence",
+            "stop_sequence": "---END SUMMARY---",
+            "usage": { "input_tokens": 10, "output_tokens": 5 }
+        }))
+        .unwrap();
+
+        let response = model.convert_anthropic_response(api_response);
+        assert_eq!(response.content, "3 pods restarting");
+        assert_eq!(response.stop_reason, StopReason::StopSequence);
+        assert_eq!(response.stop_detail.as_deref(), Some("---END SUMMARY---"));
+
+        // Nothing is sent when no stop sequences are configured
+        let request = ModelRequest {
+            stop_sequences: vec![],
+            ..request
+        };
+        let api_request = serde_json::to_value(model.to_anthropic_request(&request)).unwrap();
+        assert!(api_request.get("stop_sequences").is_none());
+    }
+
+    #[test]
+    fn test_parallel_tool_results_reference_their_calls() {
+        let model = AnthropicModel {
+            config: ModelConfig {
+                model: "claude-3-5-sonnet-20241022".to_string(),
+                provider: ModelProvider::Anthropic,
+                api_key: Some("test-key".to_string()),
+                endpoint: None,
+                temperature: 0.7,
+                max_tokens: Some(4096),
+                timeout_secs: 60,
+                headers: HashMap::new(),
+                extra: HashMap::new(),
+            },
+            api_key: "test-key".to_string(),
+            client: Client::new(),
+        };
+        let message = |role, content: &str, tool_calls, tool_call_id: Option<&str>| {
+            aof_core::RequestMessage {
+                role,
+                content: content.to_string(),
+                tool_calls,
+                tool_call_id: tool_call_id.map(String::from),
+                parts: vec![],
+            }
+        };
+        let call = |id: &str, name: &str| ToolCall {
+            id: id.to_string(),
+            name: name.to_string(),
+            arguments: serde_json::json!({ "namespace": "prod" }),
+        };
+        let request = ModelRequest {
+            messages: vec![
+                message(MessageRole::User, "check the cluster", None, None),
+                message(
+                    MessageRole::Assistant,
+                    "",
+                    Some(vec![
+                        call("toolu_1", "kubectl_get"),
+                        call("toolu_2", "prometheus_query"),
+                    ]),
+                    None,
+                ),
+                message(MessageRole::Tool, "cpu at 40%", None, Some("toolu_2")),
+                message(MessageRole::Tool, "3 pods running", None, Some("toolu_1")),
+            ],
+            ..Default::default()
+        };
+
+        let api_request = serde_json::to_value(model.to_anthropic_request(&request)).unwrap();
+        let messages = api_request["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(
+            messages[1],
+            serde_json::json!({
+                "role": "assistant",
+                "content": [
+                    { "type": "tool_use", "id": "toolu_1", "name": "kubectl_get", "input": { "namespace": "prod" } },
+                    { "type": "tool_use", "id": "toolu_2", "name": "prometheus_query", "input": { "namespace": "prod" } },
+                ]
+            })
+        );
+        // Both results answer in one user turn, each naming its own call
+        assert_eq!(
+            messages[2],
+            serde_json::json!({
+                "role": "user",
+                "content": [
+                    { "type": "tool_result", "tool_use_id": "toolu_2", "content": "cpu at 40%" },
+                    { "type": "tool_result", "tool_use_id": "toolu_1", "content": "3 pods running" },
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_image_parts_sent_as_image_blocks() {
+        let model = AnthropicModel {
+            config: ModelConfig {
+                model: "claude-3-5-sonnet-20241022".to_string(),
+                provider: ModelProvider::Anthropic,
+                api_key: Some("test-key".to_string()),
+                endpoint: None,
+                temperature: 0.7,
+                max_tokens: Some(4096),
+                timeout_secs: 60,
+                headers: HashMap::new(),
+                extra: HashMap::new(),
+            },
+            api_key: "test-key".to_string(),
+            client: Client::new(),
+        };
+        let request = ModelRequest {
+            messages: vec![aof_core::RequestMessage {
+                role: MessageRole::User,
+                content: "Compare these graphs".to_string(),
+                tool_calls: None,
+                tool_call_id: None,
+                parts: vec![
+                    ContentPart::image_base64("image/png", "iVBORw0KGgo="),
+                    ContentPart::image_url("https://example.com/latency.png"),
+                ],
+            }],
+            ..Default::default()
+        };
+
+        let api_request = serde_json::to_value(model.to_anthropic_request(&request)).unwrap();
+        assert_eq!(
+            api_request["messages"][0]["content"],
+            serde_json::json!([
+                { "type": "text", "text": "Compare these graphs" },
+                {
+                    "type": "image",
+                    "source": { "type": "base64", "media_type": "image/png", "data": "iVBORw0KGgo=" }
+                },
+                {
+                    "type": "image",
+                    "source": { "type": "url", "url": "https://example.com/latency.png" }
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_thinking_deltas_are_streamed_separately() {
+        let line = r#"data: {"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"The pod is crash looping"}}"#;
+        assert!(matches!(
+            AnthropicModel::parse_stream_event_static(line),
+            Some(Ok(StreamChunk::ThinkingDelta { delta })) if delta == "The pod is crash looping"
+        ));
+
+        // Thinking blocks and their signatures are not answer content
+        let line = r#"data: {"type":"content_block_start","index":0,"content_block":{"type":"thinking","thinking":""}}"#;
+        assert!(AnthropicModel::parse_stream_event_static(line).is_none());
+        let line = r#"data: {"type":"content_block_delta","index":0,"delta":{"type":"signature_delta","signature":"EqQB"}}"#;
+        assert!(AnthropicModel::parse_stream_event_static(line).is_none());
+    }
 }
@@ -11,10 +11,13 @@ use tracing::{debug, error, warn};
 
 #[cfg(feature = "bedrock")]
 use aws_sdk_bedrockruntime::{
+    config::http::HttpRequest,
     operation::converse::ConverseOutput,
     types::{
-        ContentBlock, ConversationRole, ConverseStreamOutput as StreamOutputEnum, Message as BedrockMessage,
-        SystemContentBlock, Tool as BedrockTool, ToolConfiguration, ToolInputSchema, ToolSpecification,
+        ContentBlock, ContentBlockDelta, ContentBlockStart, ConversationRole,
+        ConverseStreamOutput as StreamOutputEnum, Message as BedrockMessage, SystemContentBlock,
+        Tool as BedrockTool, ToolConfiguration, ToolInputSchema, ToolResultBlock,
+        ToolResultContentBlock, ToolSpecification, ToolUseBlock,
     },
     Client,
 };
@@ -24,6 +27,46 @@ use aws_smithy_types::Document;
 
 const MAX_RETRIES: u32 = 3;
 
+/// Model family behind a Bedrock model id
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BedrockModelFamily {
+    /// `anthropic.claude-*`
+    Anthropic,
+    /// `meta.llama*`
+    Meta,
+    /// Any other vendor (Amazon, Mistral, Cohere, ...)
+    Other,
+}
+
+impl BedrockModelFamily {
+    /// Detect the family from a model id or cross-region inference profile
+    /// (e.g. `us.anthropic.claude-3-5-sonnet-20241022-v2:0`)
+    pub fn from_model_id(model_id: &str) -> Self {
+        let vendor = model_id
+            .split('.')
+            .find(|part| !matches!(*part, "us" | "eu" | "apac" | "global"))
+            .unwrap_or_default();
+
+        match vendor {
+            "anthropic" => Self::Anthropic,
+            "meta" => Self::Meta,
+            _ => Self::Other,
+        }
+    }
+
+    /// Whether Converse accepts a tool configuration for this model
+    ///
+    /// Llama 3.0 and Llama 2 do not support tool use; Llama 3.1+ does.
+    pub fn supports_tools(self, model_id: &str) -> bool {
+        match self {
+            Self::Meta => !(model_id.contains("llama2")
+                || model_id.contains("llama3-8b")
+                || model_id.contains("llama3-70b")),
+            Self::Anthropic | Self::Other => true,
+        }
+    }
+}
+
 #[cfg(feature = "bedrock")]
 fn json_to_document(value: &serde_json::Value) -> Document {
     match value {
@@ -78,6 +121,78 @@ fn document_to_json(doc: &Document) -> Result<serde_json::Value, String> {
     }
 }
 
+#[cfg(feature = "bedrock")]
+fn map_stop_reason(reason: &str) -> StopReason {
+    match reason {
+        "end_turn" => StopReason::EndTurn,
+        "max_tokens" => StopReason::MaxTokens,
+        "stop_sequence" => StopReason::StopSequence,
+        "tool_use" => StopReason::ToolUse,
+        "content_filtered" | "guardrail_intervened" => StopReason::ContentFilter,
+        _ => StopReason::EndTurn,
+    }
+}
+
+/// Stop sequences for the inference config, omitted when there are none
+#[cfg(feature = "bedrock")]
+fn stop_sequences(request: &ModelRequest) -> Option<Vec<String>> {
+    Some(request.stop_sequences.clone()).filter(|sequences| !sequences.is_empty())
+}
+
+/// Request hook adding the config's and `request`'s custom headers
+///
+/// The SDK runs it before SigV4 signing, so the headers are signed too.
+#[cfg(feature = "bedrock")]
+fn add_custom_headers(
+    config: &ModelConfig,
+    request: &ModelRequest,
+) -> impl Fn(&mut HttpRequest) + Send + Sync + 'static {
+    let headers: Vec<(String, String)> = super::custom_headers(config, &request.request_headers)
+        .iter()
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect();
+    move |http| {
+        for (name, value) in &headers {
+            http.headers_mut().insert(name.clone(), value.clone());
+        }
+    }
+}
+
+/// Map an SDK failure to a structured error using the HTTP status, if any
+#[cfg(feature = "bedrock")]
+fn sdk_error<E: std::error::Error + 'static>(
+    context: &str,
+    error: aws_sdk_bedrockruntime::error::SdkError<E, aws_sdk_bedrockruntime::config::http::HttpResponse>,
+) -> AofError {
+    let status = error.raw_response().map(|response| response.status().as_u16());
+    let message = format!(
+        "{}: {}",
+        context,
+        aws_sdk_bedrockruntime::error::DisplayErrorContext(&error)
+    );
+    use aws_sdk_bedrockruntime::error::SdkError;
+    match (status, &error) {
+        // Bedrock throttling carries no Retry-After header
+        (Some(429), _) => AofError::rate_limited(None),
+        (Some(status), _) if crate::provider::is_context_overflow(status, &message) => {
+            AofError::context_overflow(message)
+        }
+        (Some(status), _) => AofError::http(status, message),
+        (None, SdkError::TimeoutError(_)) => AofError::Timeout(message),
+        (None, SdkError::DispatchFailure(_)) => AofError::Io(std::io::Error::other(message)),
+        (None, _) => AofError::model(message),
+    }
+}
+
+#[cfg(feature = "bedrock")]
+fn build_message(role: ConversationRole, content: Vec<ContentBlock>) -> AofResult<BedrockMessage> {
+    BedrockMessage::builder()
+        .role(role)
+        .set_content(Some(content))
+        .build()
+        .map_err(|e| AofError::model(format!("Failed to build message: {}", e)))
+}
+
 /// Bedrock provider implementation
 #[allow(dead_code)]
 pub struct BedrockProvider {
@@ -121,34 +236,90 @@ impl BedrockProvider {
 
     #[cfg(feature = "bedrock")]
     fn convert_messages(&self, request: &ModelRequest) -> AofResult<Vec<BedrockMessage>> {
+        super::reject_images("Bedrock", request)?;
         let mut messages = Vec::new();
+        // Tool results without an id answer the previous assistant tool calls in order
+        let mut pending_tool_ids = std::collections::VecDeque::new();
+        let mut tool_results = Vec::new();
 
         for msg in &request.messages {
-            if msg.role == MessageRole::System {
-                continue; // System messages handled separately
+            if msg.role == MessageRole::Tool {
+                let tool_use_id = msg
+                    .tool_call_id
+                    .clone()
+                    .or_else(|| pending_tool_ids.front().cloned())
+                    .ok_or_else(|| AofError::model("Tool result without a matching tool call"))?;
+                pending_tool_ids.retain(|id| *id != tool_use_id);
+                tool_results.push(ContentBlock::ToolResult(
+                    ToolResultBlock::builder()
+                        .tool_use_id(tool_use_id)
+                        .content(ToolResultContentBlock::Text(msg.content.clone()))
+                        .build()
+                        .map_err(|e| AofError::model(format!("Failed to build tool result: {}", e)))?,
+                ));
+                continue;
+            }
+
+            // Consecutive tool results go back as a single user turn
+            if !tool_results.is_empty() {
+                messages.push(build_message(ConversationRole::User, std::mem::take(&mut tool_results))?);
             }
 
             let role = match msg.role {
                 MessageRole::User => ConversationRole::User,
                 MessageRole::Assistant => ConversationRole::Assistant,
-                MessageRole::Tool => ConversationRole::User, // Tool results as user
-                MessageRole::System => continue,
+                MessageRole::System | MessageRole::Tool => continue, // System handled separately
             };
 
-            let content = vec![ContentBlock::Text(msg.content.clone())];
+            // Bedrock rejects blank text blocks
+            let mut content = Vec::new();
+            if !msg.content.trim().is_empty() {
+                content.push(ContentBlock::Text(msg.content.clone()));
+            }
 
-            messages.push(
-                BedrockMessage::builder()
-                    .role(role)
-                    .set_content(Some(content))
-                    .build()
-                    .map_err(|e| AofError::model(format!("Failed to build message: {}", e)))?,
-            );
+            for tool_call in msg.tool_calls.iter().flatten() {
+                pending_tool_ids.push_back(tool_call.id.clone());
+                content.push(ContentBlock::ToolUse(
+                    ToolUseBlock::builder()
+                        .tool_use_id(&tool_call.id)
+                        .name(&tool_call.name)
+                        .input(json_to_document(&tool_call.arguments))
+                        .build()
+                        .map_err(|e| AofError::model(format!("Failed to build tool use: {}", e)))?,
+                ));
+            }
+
+            if !content.is_empty() {
+                messages.push(build_message(role, content)?);
+            }
+        }
+
+        if !tool_results.is_empty() {
+            messages.push(build_message(ConversationRole::User, tool_results)?);
         }
 
         Ok(messages)
     }
 
+    /// Tool configuration for the request, if the model supports tools
+    #[cfg(feature = "bedrock")]
+    fn tool_config(&self, request: &ModelRequest) -> AofResult<Option<ToolConfiguration>> {
+        if request.tools.is_empty() {
+            return Ok(None);
+        }
+
+        let family = BedrockModelFamily::from_model_id(&self.config.model);
+        if !family.supports_tools(&self.config.model) {
+            warn!(
+                "Bedrock model {} does not support tool use; sending request without tools",
+                self.config.model
+            );
+            return Ok(None);
+        }
+
+        self.convert_tools(&request.tools).map(Some)
+    }
+
     #[cfg(feature = "bedrock")]
     fn convert_tools(&self, tools: &[aof_core::ModelToolDefinition]) -> AofResult<ToolConfiguration> {
         let mut tool_specs = Vec::new();
@@ -178,14 +349,7 @@ impl BedrockProvider {
         let mut content = String::new();
         let mut tool_calls = Vec::new();
 
-        let stop_reason = match output.stop_reason().as_str() {
-            "end_turn" => StopReason::EndTurn,
-            "max_tokens" => StopReason::MaxTokens,
-            "stop_sequence" => StopReason::StopSequence,
-            "tool_use" => StopReason::ToolUse,
-            "content_filtered" => StopReason::ContentFilter,
-            _ => StopReason::EndTurn,
-        };
+        let stop_reason = map_stop_reason(output.stop_reason().as_str());
 
         let usage = output.usage().map(|u| Usage {
             input_tokens: u.input_tokens() as usize,
@@ -234,6 +398,7 @@ impl BedrockProvider {
             tool_calls,
             stop_reason,
             usage,
+            stop_detail: None,
             metadata: HashMap::new(),
         })
     }
@@ -249,9 +414,15 @@ impl BedrockProvider {
         for attempt in 0..MAX_RETRIES {
             match f().await {
                 Ok(result) => return Ok(result),
+                Err(e) if !e.is_transient() => return Err(e),
                 Err(e) => {
                     if attempt < MAX_RETRIES - 1 {
-                        let delay = std::time::Duration::from_millis(100 * 2_u64.pow(attempt));
+                        let delay = match &e {
+                            AofError::RateLimited {
+                                retry_after: Some(retry_after),
+                            } => *retry_after,
+                            _ => std::time::Duration::from_millis(100 * 2_u64.pow(attempt)),
+                        };
                         warn!(
                             "Request failed (attempt {}/{}): {}. Retrying in {:?}",
                             attempt + 1,
@@ -300,19 +471,21 @@ impl Model for BedrockProvider {
                         aws_sdk_bedrockruntime::types::InferenceConfiguration::builder()
                             .temperature(temperature)
                             .max_tokens(max_tokens as i32)
+                            .set_stop_sequences(stop_sequences(request))
                             .build()
                     );
 
                 // Add tools if present
-                if !request.tools.is_empty() {
-                    let tool_config = self.convert_tools(&request.tools)?;
+                if let Some(tool_config) = self.tool_config(request)? {
                     builder = builder.tool_config(tool_config);
                 }
 
                 let result = builder
+                    .customize()
+                    .mutate_request(add_custom_headers(&self.config, request))
                     .send()
                     .await
-                    .map_err(|e| AofError::model(format!("Bedrock API error: {}", e)))?;
+                    .map_err(|e| sdk_error("Bedrock API error", e))?;
 
                 Ok(result)
             })
@@ -355,63 +528,93 @@ impl Model for BedrockProvider {
                 aws_sdk_bedrockruntime::types::InferenceConfiguration::builder()
                     .temperature(temperature)
                     .max_tokens(max_tokens as i32)
+                    .set_stop_sequences(stop_sequences(request))
                     .build()
             );
 
         // Add tools if present
-        if !request.tools.is_empty() {
-            let tool_config = self.convert_tools(&request.tools)?;
+        if let Some(tool_config) = self.tool_config(request)? {
             builder = builder.tool_config(tool_config);
         }
 
         let mut stream = builder
+            .customize()
+            .mutate_request(add_custom_headers(&self.config, request))
             .send()
             .await
-            .map_err(|e| AofError::model(format!("Bedrock streaming API error: {}", e)))?;
+            .map_err(|e| sdk_error("Bedrock streaming API error", e))?;
 
         let output_stream = async_stream::stream! {
+            // Tool input arrives as JSON fragments between block start and stop
+            let mut tool_use: Option<(String, String, String)> = None;
+            let mut stop_reason = StopReason::EndTurn;
+            let mut usage = Usage::default();
+
             while let Some(event) = stream.stream.recv().await.transpose() {
                 match event {
-                    Ok(output) => {
-                        match output {
-                            StreamOutputEnum::ContentBlockDelta(delta) => {
-                                if let Some(text_delta) = delta.delta().and_then(|d| d.as_text().ok()) {
-                                    yield Ok(StreamChunk::ContentDelta {
-                                        delta: text_delta.to_string(),
-                                    });
-                                }
-                            }
-                            StreamOutputEnum::MessageStop(stop) => {
-                                let stop_reason = match stop.stop_reason().as_str() {
-                                    "end_turn" => StopReason::EndTurn,
-                                    "max_tokens" => StopReason::MaxTokens,
-                                    "stop_sequence" => StopReason::StopSequence,
-                                    "tool_use" => StopReason::ToolUse,
-                                    "content_filtered" => StopReason::ContentFilter,
-                                    _ => StopReason::EndTurn,
-                                };
-
-                                yield Ok(StreamChunk::Done {
-                                    usage: Usage::default(),
-                                    stop_reason,
-                                });
+                    Ok(StreamOutputEnum::ContentBlockStart(start)) => {
+                        if let Some(ContentBlockStart::ToolUse(block)) = start.start() {
+                            tool_use = Some((
+                                block.tool_use_id().to_string(),
+                                block.name().to_string(),
+                                String::new(),
+                            ));
+                        }
+                    }
+                    Ok(StreamOutputEnum::ContentBlockDelta(delta)) => match delta.delta() {
+                        Some(ContentBlockDelta::Text(text)) => {
+                            yield Ok(StreamChunk::ContentDelta { delta: text.clone() });
+                        }
+                        Some(ContentBlockDelta::ToolUse(tool_delta)) => {
+                            if let Some((_, _, input)) = tool_use.as_mut() {
+                                input.push_str(tool_delta.input());
                             }
-                            StreamOutputEnum::Metadata(metadata) => {
-                                if let Some(_usage) = metadata.usage() {
-                                    // Store usage for final Done event
-                                    continue;
+                        }
+                        _ => {}
+                    },
+                    Ok(StreamOutputEnum::ContentBlockStop(_)) => {
+                        if let Some((id, name, input)) = tool_use.take() {
+                            let arguments = if input.trim().is_empty() {
+                                json!({})
+                            } else {
+                                match serde_json::from_str(&input) {
+                                    Ok(arguments) => arguments,
+                                    Err(e) => {
+                                        yield Err(AofError::model(format!(
+                                            "Invalid tool input from Bedrock: {}",
+                                            e
+                                        )));
+                                        break;
+                                    }
                                 }
-                            }
-                            _ => continue,
+                            };
+                            yield Ok(StreamChunk::ToolCall {
+                                tool_call: ToolCall { id, name, arguments },
+                            });
                         }
                     }
+                    Ok(StreamOutputEnum::MessageStop(stop)) => {
+                        stop_reason = map_stop_reason(stop.stop_reason().as_str());
+                    }
+                    Ok(StreamOutputEnum::Metadata(metadata)) => {
+                        if let Some(token_usage) = metadata.usage() {
+                            usage = Usage {
+                                input_tokens: token_usage.input_tokens() as usize,
+                                output_tokens: token_usage.output_tokens() as usize,
+                            };
+                        }
+                    }
+                    Ok(_) => continue,
                     Err(e) => {
                         error!("Bedrock stream error: {}", e);
                         yield Err(AofError::model(format!("Stream error: {}", e)));
-                        break;
+                        return;
                     }
                 }
             }
+
+            // Usage arrives in the metadata event after MessageStop
+            yield Ok(StreamChunk::Done { usage, stop_reason, stop_detail: None });
         };
 
         Ok(Box::pin(output_stream))
@@ -445,6 +648,7 @@ impl Model for BedrockProvider {
 #[cfg(all(test, feature = "bedrock"))]
 mod tests {
     use super::*;
+    use aof_core::RequestMessage;
 
     #[tokio::test]
     async fn test_provider_creation() {
@@ -490,4 +694,110 @@ mod tests {
         let tokens = provider.count_tokens("Hello, world!");
         assert!(tokens > 0);
     }
+
+    #[test]
+    fn test_model_family_detection() {
+        let claude = "us.anthropic.claude-3-5-sonnet-20241022-v2:0";
+        assert_eq!(BedrockModelFamily::from_model_id(claude), BedrockModelFamily::Anthropic);
+        assert_eq!(
+            BedrockModelFamily::from_model_id("meta.llama3-1-70b-instruct-v1:0"),
+            BedrockModelFamily::Meta
+        );
+        assert_eq!(
+            BedrockModelFamily::from_model_id("amazon.titan-text-express-v1"),
+            BedrockModelFamily::Other
+        );
+
+        assert!(BedrockModelFamily::Anthropic.supports_tools(claude));
+        assert!(BedrockModelFamily::Meta.supports_tools("meta.llama3-1-70b-instruct-v1:0"));
+        assert!(!BedrockModelFamily::Meta.supports_tools("meta.llama3-8b-instruct-v1:0"));
+    }
+
+    #[test]
+    fn test_convert_messages_pairs_tool_results() {
+
+        let config = ModelConfig {
+            model: "anthropic.claude-3-sonnet-20240229-v1:0".to_string(),
+            provider: ModelProvider::Bedrock,
+            api_key: None,
+            endpoint: None,
+            temperature: 0.7,
+            max_tokens: Some(4096),
+            timeout_secs: 60,
+            headers: HashMap::new(),
+            extra: HashMap::new(),
+        };
+        let client = Client::from_conf(
+            aws_sdk_bedrockruntime::Config::builder()
+                .behavior_version(aws_config::BehaviorVersion::latest())
+                .region(aws_config::Region::new("us-east-1"))
+                .build(),
+        );
+        let provider = BedrockProvider {
+            config,
+            client,
+            region: "us-east-1".to_string(),
+        };
+
+        let message = |role, content: &str, tool_calls| RequestMessage {
+            role,
+            content: content.to_string(),
+            tool_calls,
+            tool_call_id: None,
+            parts: vec![],
+        };
+        let request = ModelRequest {
+            messages: vec![
+                message(MessageRole::User, "check pods", None),
+                message(
+                    MessageRole::Assistant,
+                    "",
+                    Some(vec![
+                        ToolCall {
+                            id: "call-1".to_string(),
+                            name: "kubectl".to_string(),
+                            arguments: json!({"args": "get pods"}),
+                        },
+                        ToolCall {
+                            id: "call-2".to_string(),
+                            name: "kubectl".to_string(),
+                            arguments: json!({"args": "get nodes"}),
+                        },
+                    ]),
+                ),
+                message(MessageRole::Tool, "pod-a Running", None),
+                message(MessageRole::Tool, "node-1 Ready", None),
+            ],
+            system: None,
+            tools: vec![],
+            temperature: None,
+            max_tokens: None,
+            stop_sequences: vec![],
+            seed: None,
+            stream: false,
+            request_headers: HashMap::new(),
+            extra: HashMap::new(),
+            response_schema: None,
+        };
+
+        let messages = provider.convert_messages(&request).unwrap();
+        assert_eq!(messages.len(), 3);
+
+        // Blank assistant text is dropped, leaving only the tool uses
+        assert_eq!(messages[1].role(), &ConversationRole::Assistant);
+        assert_eq!(messages[1].content().len(), 2);
+        assert!(matches!(&messages[1].content()[0], ContentBlock::ToolUse(block) if block.tool_use_id() == "call-1"));
+
+        // Both results come back in one user turn, matched to their calls
+        assert_eq!(messages[2].role(), &ConversationRole::User);
+        let ids: Vec<&str> = messages[2]
+            .content()
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::ToolResult(result) => Some(result.tool_use_id()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(ids, vec!["call-1", "call-2"]);
+    }
 }
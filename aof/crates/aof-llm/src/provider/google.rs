@@ -1,6 +1,6 @@
 use aof_core::{
-    model::MessageRole, AofError, AofResult, Model, ModelConfig, ModelProvider, ModelRequest,
-    ModelResponse, StopReason, StreamChunk, ToolCall, Usage,
+    model::MessageRole, AofError, AofResult, ContentPart, Model, ModelConfig, ModelProvider,
+    ModelRequest, ModelResponse, StopReason, StreamChunk, ToolCall, Usage,
 };
 use async_trait::async_trait;
 use futures::stream::{Stream, StreamExt};
@@ -10,6 +10,8 @@ use std::collections::HashMap;
 use std::pin::Pin;
 use std::time::Duration;
 
+use super::{custom_headers, http_error};
+
 /// Google/Gemini provider
 pub struct GoogleProvider;
 
@@ -60,7 +62,10 @@ impl GoogleModel {
     }
 
     /// Build request payload for Gemini API
-    fn build_request(&self, request: &ModelRequest) -> GeminiRequest {
+    ///
+    /// Gemini only takes inline images, so image URLs other than base64
+    /// `data:` URLs are rejected.
+    fn build_request(&self, request: &ModelRequest) -> AofResult<GeminiRequest> {
         // Convert messages to Gemini format
         // Note: Gemini uses "user" and "model" roles only. Tool responses use functionResponse parts.
         let mut contents: Vec<GeminiContent> = Vec::new();
@@ -68,9 +73,18 @@ impl GoogleModel {
         for (i, m) in request.messages.iter().enumerate() {
             match m.role {
                 MessageRole::User => {
+                    let mut parts = Vec::new();
+                    if m.parts.is_empty() || !m.content.trim().is_empty() {
+                        parts.push(GeminiPart::Text {
+                            text: m.content.clone(),
+                        });
+                    }
+                    for part in &m.parts {
+                        parts.push(GeminiPart::from_content_part(part)?);
+                    }
                     contents.push(GeminiContent {
                         role: "user".to_string(),
-                        parts: vec![GeminiPart::Text { text: m.content.clone() }],
+                        parts,
                     });
                 }
                 MessageRole::Assistant => {
@@ -103,17 +117,23 @@ impl GoogleModel {
                     // Gemini doesn't have system role in contents, skip (handled via system_instruction)
                 }
                 MessageRole::Tool => {
-                    // Tool responses need functionResponse format
-                    // Try to get the tool name from the previous assistant message's tool calls
-                    let tool_name = if i > 0 {
-                        request.messages.get(i - 1)
-                            .and_then(|prev| prev.tool_calls.as_ref())
-                            .and_then(|tcs| tcs.first())
-                            .map(|tc| tc.name.clone())
-                            .unwrap_or_else(|| "unknown".to_string())
-                    } else {
-                        "unknown".to_string()
-                    };
+                    // Tool responses need functionResponse format, which names
+                    // the function: look up the tool call this result answers,
+                    // else take the previous assistant message's first call
+                    let tool_name = match &m.tool_call_id {
+                        Some(id) => request.messages[..i]
+                            .iter()
+                            .rev()
+                            .filter_map(|prev| prev.tool_calls.as_ref())
+                            .flatten()
+                            .find(|tc| tc.id == *id),
+                        None => i
+                            .checked_sub(1)
+                            .and_then(|prev| request.messages[prev].tool_calls.as_ref())
+                            .and_then(|tcs| tcs.first()),
+                    }
+                    .map(|tc| tc.name.clone())
+                    .unwrap_or_else(|| "unknown".to_string());
 
                     // Parse content as JSON or wrap as string
                     let response_data = serde_json::from_str::<serde_json::Value>(&m.content)
@@ -155,20 +175,30 @@ impl GoogleModel {
             None
         };
 
-        // Generation config
+        // Generation config. Gemini rejects JSON mode combined with function
+        // calling, so the response schema only applies to tool-less requests.
+        let response_schema = request
+            .response_schema
+            .clone()
+            .filter(|_| tools.is_none());
         let generation_config = GeminiGenerationConfig {
             temperature: request.temperature.or(Some(self.config.temperature)),
             max_output_tokens: request.max_tokens.or(self.config.max_tokens),
+            stop_sequences: request.stop_sequences.clone(),
             top_p: None,
             top_k: None,
+            response_mime_type: response_schema
+                .as_ref()
+                .map(|_| "application/json".to_string()),
+            response_schema,
         };
 
-        GeminiRequest {
+        Ok(GeminiRequest {
             contents,
             system_instruction,
             tools,
             generation_config: Some(generation_config),
-        }
+        })
     }
 
     /// Parse Gemini response to ModelResponse
@@ -285,6 +315,7 @@ impl GoogleModel {
             tool_calls,
             stop_reason,
             usage,
+            stop_detail: None,
             metadata: HashMap::new(),
         })
     }
@@ -294,7 +325,7 @@ impl GoogleModel {
 impl Model for GoogleModel {
     async fn generate(&self, request: &ModelRequest) -> AofResult<ModelResponse> {
         tracing::warn!("=== GOOGLE PROVIDER generate() START ===");
-        let payload = self.build_request(request);
+        let payload = self.build_request(request)?;
 
         tracing::warn!(
             "[GOOGLE] API CALL: model={}, messages={}, system={:?}, tools={}",
@@ -322,6 +353,7 @@ impl Model for GoogleModel {
             .client
             .post(&url)
             .header(header::CONTENT_TYPE, "application/json")
+            .headers(custom_headers(&self.config, &request.request_headers))
             .json(&payload)
             .send()
             .await
@@ -334,15 +366,9 @@ impl Model for GoogleModel {
 
         let status = response.status();
         if !status.is_success() {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            tracing::error!("[GOOGLE] API ERROR: {} - {}", status, error_text);
-            return Err(AofError::model(format!(
-                "Gemini API error ({}): {}",
-                status, error_text
-            )));
+            let error = http_error("Gemini API error", response).await;
+            tracing::error!("{}", error);
+            return Err(error);
         }
 
         tracing::warn!("[GOOGLE] Parsing JSON response...");
@@ -362,7 +388,7 @@ impl Model for GoogleModel {
         &self,
         request: &ModelRequest,
     ) -> AofResult<Pin<Box<dyn Stream<Item = AofResult<StreamChunk>> + Send>>> {
-        let payload = self.build_request(request);
+        let payload = self.build_request(request)?;
 
         tracing::debug!(
             "Sending Gemini streaming request: model={}, contents={}",
@@ -380,6 +406,7 @@ impl Model for GoogleModel {
             .client
             .post(&url)
             .header(header::CONTENT_TYPE, "application/json")
+            .headers(custom_headers(&self.config, &request.request_headers))
             .json(&payload)
             .send()
             .await
@@ -387,14 +414,7 @@ impl Model for GoogleModel {
 
         let status = response.status();
         if !status.is_success() {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(AofError::model(format!(
-                "Gemini streaming error ({}): {}",
-                status, error_text
-            )));
+            return Err(http_error("Gemini streaming error", response).await);
         }
 
         // Convert bytes stream to SSE stream
@@ -495,6 +515,7 @@ fn parse_gemini_stream_chunk(line: &str) -> Option<AofResult<StreamChunk>> {
                 })
                 .unwrap_or_default(),
             stop_reason,
+            stop_detail: None,
         }));
     }
 
@@ -535,6 +556,39 @@ enum GeminiPart {
         #[serde(rename = "functionResponse")]
         function_response: GeminiFunctionResponse,
     },
+    InlineData {
+        #[serde(rename = "inlineData")]
+        inline_data: GeminiInlineData,
+    },
+}
+
+impl GeminiPart {
+    fn from_content_part(part: &ContentPart) -> AofResult<Self> {
+        match part {
+            ContentPart::Text { text } => Ok(Self::Text { text: text.clone() }),
+            ContentPart::ImageUrl { image_url } => {
+                let (mime_type, data) = image_url.as_base64().ok_or_else(|| {
+                    AofError::model(format!(
+                        "Gemini needs images inline as base64 data, not a URL: {}",
+                        image_url.url
+                    ))
+                })?;
+                Ok(Self::InlineData {
+                    inline_data: GeminiInlineData {
+                        mime_type: mime_type.to_string(),
+                        data: data.to_string(),
+                    },
+                })
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct GeminiInlineData {
+    #[serde(rename = "mimeType")]
+    mime_type: String,
+    data: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -567,10 +621,16 @@ struct GeminiGenerationConfig {
     temperature: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     max_output_tokens: Option<usize>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stop_sequences: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     top_p: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     top_k: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_mime_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_schema: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -641,4 +701,32 @@ mod tests {
         let count = model.count_tokens("Hello, world!");
         assert!(count >= 3 && count <= 4);
     }
+
+    #[test]
+    fn test_response_schema_in_generation_config() {
+        let config = ModelConfig {
+            model: "gemini-2.0-flash".to_string(),
+            provider: ModelProvider::Google,
+            api_key: Some("test".to_string()),
+            endpoint: None,
+            temperature: 0.7,
+            max_tokens: Some(1000),
+            timeout_secs: 60,
+            headers: HashMap::new(),
+            extra: HashMap::new(),
+        };
+        let model = GoogleModel::new(config).unwrap();
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": { "healthy": { "type": "boolean" } }
+        });
+        let request = ModelRequest {
+            response_schema: Some(schema.clone()),
+            ..Default::default()
+        };
+
+        let body = serde_json::to_value(model.build_request(&request).unwrap()).unwrap();
+        assert_eq!(body["generation_config"]["response_mime_type"], "application/json");
+        assert_eq!(body["generation_config"]["response_schema"], schema);
+    }
 }
@@ -0,0 +1,735 @@
+use aof_core::{
+    model::MessageRole, AofError, AofResult, Model, ModelConfig, ModelProvider, ModelRequest,
+    ModelResponse, StopReason, StreamChunk, ToolCall, Usage,
+};
+use async_trait::async_trait;
+use futures::stream::{Stream, StreamExt};
+use reqwest::{header, Client};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::pin::Pin;
+use std::time::Duration;
+
+use super::{custom_headers, http_error, reject_images};
+
+/// Default Ollama server address
+pub(crate) const DEFAULT_OLLAMA_HOST: &str = "http://localhost:11434";
+
+/// Ollama provider (native `/api/chat` API)
+pub struct OllamaProvider;
+
+impl OllamaProvider {
+    pub fn create(config: ModelConfig) -> AofResult<Box<dyn Model>> {
+        Ok(Box::new(OllamaModel::new(config)?))
+    }
+
+    /// Load the configured model into memory ahead of the first request
+    pub async fn preload(config: ModelConfig) -> AofResult<()> {
+        OllamaModel::new(config)?.preload().await
+    }
+}
+
+/// Ollama model implementation
+pub struct OllamaModel {
+    config: ModelConfig,
+    client: Client,
+    endpoint: String,
+}
+
+impl OllamaModel {
+    /// Create new Ollama model
+    pub fn new(config: ModelConfig) -> AofResult<Self> {
+        // Use custom endpoint, then OLLAMA_HOST, then the local default
+        let host = config
+            .endpoint
+            .clone()
+            .or_else(|| std::env::var("OLLAMA_HOST").ok())
+            .unwrap_or_else(|| DEFAULT_OLLAMA_HOST.to_string());
+        let endpoint = normalize_host(&host);
+
+        // Build HTTP client with timeout
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(|e| AofError::model(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(Self {
+            config,
+            client,
+            endpoint,
+        })
+    }
+
+    /// Chat URL for the configured host
+    pub(crate) fn chat_url(&self) -> String {
+        format!("{}/api/chat", self.endpoint)
+    }
+
+    /// How long Ollama keeps the model loaded after a request
+    ///
+    /// Taken from `keep_alive` in the config's `extra`, either a duration
+    /// string like `"30m"` or a number of seconds (`-1` keeps it loaded).
+    fn keep_alive(&self) -> Option<serde_json::Value> {
+        self.config.extra.get("keep_alive").cloned()
+    }
+
+    /// Load the model into memory without generating anything
+    ///
+    /// Ollama loads a model on a chat request with no messages, so the
+    /// first real request doesn't pay for the load.
+    pub async fn preload(&self) -> AofResult<()> {
+        let payload = OllamaRequest {
+            model: self.config.model.clone(),
+            messages: Vec::new(),
+            stream: false,
+            tools: None,
+            format: None,
+            options: OllamaOptions::default(),
+            keep_alive: self.keep_alive(),
+        };
+
+        tracing::debug!("Preloading Ollama model: {}", payload.model);
+        self.send(&payload, &HashMap::new()).await?;
+        Ok(())
+    }
+
+    /// Content-type and custom headers, plus `request_headers` for this request
+    fn request_headers(&self, request_headers: &HashMap<String, String>) -> header::HeaderMap {
+        let mut headers = header::HeaderMap::new();
+        headers.insert(
+            header::CONTENT_TYPE,
+            header::HeaderValue::from_static("application/json"),
+        );
+        headers.extend(custom_headers(&self.config, request_headers));
+        headers
+    }
+
+    /// Build request payload
+    fn build_request(&self, request: &ModelRequest, stream: bool) -> OllamaRequest {
+        let mut messages = Vec::with_capacity(request.messages.len() + 1);
+        if let Some(system) = &request.system {
+            messages.push(OllamaMessage {
+                role: "system".to_string(),
+                content: system.clone(),
+                tool_calls: None,
+            });
+        }
+
+        messages.extend(request.messages.iter().map(|m| OllamaMessage {
+            role: match m.role {
+                MessageRole::User => "user",
+                MessageRole::Assistant => "assistant",
+                MessageRole::System => "system",
+                MessageRole::Tool => "tool",
+            }
+            .to_string(),
+            content: m.content.clone(),
+            tool_calls: m.tool_calls.as_ref().map(|calls| {
+                calls
+                    .iter()
+                    .map(|tc| OllamaToolCall {
+                        id: Some(tc.id.clone()),
+                        function: OllamaFunction {
+                            name: tc.name.clone(),
+                            arguments: tc.arguments.clone(),
+                        },
+                    })
+                    .collect()
+            }),
+        }));
+
+        let tools = if request.tools.is_empty() {
+            None
+        } else {
+            Some(
+                request
+                    .tools
+                    .iter()
+                    .map(|t| {
+                        serde_json::json!({
+                            "type": "function",
+                            "function": {
+                                "name": t.name,
+                                "description": t.description,
+                                "parameters": t.parameters,
+                            }
+                        })
+                    })
+                    .collect(),
+            )
+        };
+
+        OllamaRequest {
+            model: self.config.model.clone(),
+            messages,
+            stream,
+            tools,
+            format: request.response_schema.clone(),
+            options: OllamaOptions {
+                temperature: request.temperature.or(Some(self.config.temperature)),
+                num_predict: request.max_tokens.or(self.config.max_tokens),
+                stop: request.stop_sequences.clone(),
+                seed: request.seed,
+            },
+            keep_alive: self.keep_alive(),
+        }
+    }
+
+    /// Send a chat request with `request_headers` and fail on non-success status
+    async fn send(
+        &self,
+        payload: &OllamaRequest,
+        request_headers: &HashMap<String, String>,
+    ) -> AofResult<reqwest::Response> {
+        let response = self
+            .client
+            .post(self.chat_url())
+            .headers(self.request_headers(request_headers))
+            .json(payload)
+            .send()
+            .await
+            .map_err(|e| AofError::model(format!("Ollama API request failed: {}", e)))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(http_error("Ollama API error", response).await);
+        }
+
+        Ok(response)
+    }
+}
+
+/// Turn an `OLLAMA_HOST` value into a base URL
+///
+/// Accepts bare `host:port` values like Ollama itself does, and strips a
+/// trailing `/v1` left over from OpenAI-compatible configurations.
+pub(crate) fn normalize_host(host: &str) -> String {
+    let host = host.trim().trim_end_matches('/');
+    let host = host.strip_suffix("/v1").unwrap_or(host);
+    if host.contains("://") {
+        host.to_string()
+    } else {
+        format!("http://{}", host)
+    }
+}
+
+fn map_done_reason(done_reason: Option<&str>, has_tool_calls: bool) -> StopReason {
+    if has_tool_calls {
+        return StopReason::ToolUse;
+    }
+    match done_reason {
+        Some("length") => StopReason::MaxTokens,
+        _ => StopReason::EndTurn,
+    }
+}
+
+fn convert_tool_calls(calls: Vec<OllamaToolCall>, offset: usize) -> Vec<ToolCall> {
+    calls
+        .into_iter()
+        .enumerate()
+        .map(|(index, tc)| ToolCall {
+            // Ollama does not always assign ids; tool results are matched by position
+            id: tc
+                .id
+                .unwrap_or_else(|| format!("call_{}", offset + index)),
+            name: tc.function.name,
+            arguments: tc.function.arguments,
+        })
+        .collect()
+}
+
+/// Incremental parser for Ollama's newline-delimited JSON stream
+#[derive(Default)]
+struct OllamaStreamState {
+    /// Bytes of an incomplete line carried across reads
+    buffer: Vec<u8>,
+    /// Parsed chunks waiting to be yielded
+    pending: VecDeque<AofResult<StreamChunk>>,
+    /// Tool calls seen so far
+    tool_calls: usize,
+    /// Set once `done: true` (or an error) has been seen
+    finished: bool,
+}
+
+impl OllamaStreamState {
+    /// Feed bytes from the network, parsing every complete line
+    fn push_bytes(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+        while let Some(newline) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=newline).collect();
+            self.parse_line(&line);
+            if self.finished {
+                self.buffer.clear();
+                return;
+            }
+        }
+    }
+
+    /// Handle the end of the byte stream
+    fn finish(&mut self) {
+        if !self.finished && !self.buffer.is_empty() {
+            let line = std::mem::take(&mut self.buffer);
+            self.parse_line(&line);
+        }
+        if !self.finished {
+            self.finished = true;
+            self.pending
+                .push_back(Err(AofError::model("Ollama stream ended before completion")));
+        }
+    }
+
+    fn parse_line(&mut self, line: &[u8]) {
+        let line = String::from_utf8_lossy(line);
+        let line = line.trim();
+        if line.is_empty() || self.finished {
+            return;
+        }
+
+        let chunk: OllamaChatResponse = match serde_json::from_str(line) {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                self.finished = true;
+                self.pending.push_back(Err(AofError::model(format!(
+                    "Failed to parse chunk: {}",
+                    e
+                ))));
+                return;
+            }
+        };
+
+        if let Some(error) = chunk.error {
+            self.finished = true;
+            self.pending
+                .push_back(Err(AofError::model(format!("Ollama stream error: {}", error))));
+            return;
+        }
+
+        if let Some(message) = chunk.message {
+            if !message.content.is_empty() {
+                self.pending.push_back(Ok(StreamChunk::ContentDelta {
+                    delta: message.content,
+                }));
+            }
+            let calls = convert_tool_calls(message.tool_calls.unwrap_or_default(), self.tool_calls);
+            self.tool_calls += calls.len();
+            self.pending.extend(
+                calls
+                    .into_iter()
+                    .map(|tool_call| Ok(StreamChunk::ToolCall { tool_call })),
+            );
+        }
+
+        if chunk.done {
+            self.finished = true;
+            self.pending.push_back(Ok(StreamChunk::Done {
+                usage: Usage {
+                    input_tokens: chunk.prompt_eval_count,
+                    output_tokens: chunk.eval_count,
+                },
+                stop_reason: map_done_reason(chunk.done_reason.as_deref(), self.tool_calls > 0),
+                stop_detail: None,
+            }));
+        }
+    }
+}
+
+#[async_trait]
+impl Model for OllamaModel {
+    async fn generate(&self, request: &ModelRequest) -> AofResult<ModelResponse> {
+        reject_images("Ollama", request)?;
+        let payload = self.build_request(request, false);
+
+        tracing::debug!(
+            "Sending Ollama request: model={}, messages={}",
+            payload.model,
+            payload.messages.len()
+        );
+
+        let response: OllamaChatResponse = self
+            .send(&payload, &request.request_headers)
+            .await?
+            .json()
+            .await
+            .map_err(|e| AofError::model(format!("Failed to parse Ollama response: {}", e)))?;
+
+        if let Some(error) = response.error {
+            return Err(AofError::model(format!("Ollama API error: {}", error)));
+        }
+
+        let message = response.message.unwrap_or_default();
+        let tool_calls = convert_tool_calls(message.tool_calls.unwrap_or_default(), 0);
+        let stop_reason = map_done_reason(response.done_reason.as_deref(), !tool_calls.is_empty());
+
+        Ok(ModelResponse {
+            content: message.content,
+            tool_calls,
+            stop_reason,
+            usage: Usage {
+                input_tokens: response.prompt_eval_count,
+                output_tokens: response.eval_count,
+            },
+            stop_detail: None,
+            metadata: HashMap::new(),
+        })
+    }
+
+    async fn generate_stream(
+        &self,
+        request: &ModelRequest,
+    ) -> AofResult<Pin<Box<dyn Stream<Item = AofResult<StreamChunk>> + Send>>> {
+        reject_images("Ollama", request)?;
+        let payload = self.build_request(request, true);
+
+        tracing::debug!(
+            "Sending Ollama streaming request: model={}, messages={}",
+            payload.model,
+            payload.messages.len()
+        );
+
+        let bytes = self
+            .send(&payload, &request.request_headers)
+            .await?
+            .bytes_stream()
+            .boxed();
+
+        let stream = futures::stream::unfold(
+            (bytes, OllamaStreamState::default()),
+            |(mut bytes, mut state)| async move {
+                loop {
+                    if let Some(chunk) = state.pending.pop_front() {
+                        return Some((chunk, (bytes, state)));
+                    }
+                    if state.finished {
+                        return None;
+                    }
+                    match bytes.next().await {
+                        Some(Ok(data)) => state.push_bytes(&data),
+                        Some(Err(e)) => {
+                            state.finished = true;
+                            return Some((
+                                Err(AofError::model(format!("Stream error: {}", e))),
+                                (bytes, state),
+                            ));
+                        }
+                        None => state.finish(),
+                    }
+                }
+            },
+        );
+
+        Ok(Box::pin(stream))
+    }
+
+    fn config(&self) -> &ModelConfig {
+        &self.config
+    }
+
+    fn provider(&self) -> ModelProvider {
+        ModelProvider::Ollama
+    }
+}
+
+// Ollama API types
+
+#[derive(Debug, Serialize)]
+struct OllamaRequest {
+    model: String,
+    messages: Vec<OllamaMessage>,
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<serde_json::Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<serde_json::Value>,
+    options: OllamaOptions,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keep_alive: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct OllamaOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_predict: Option<usize>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stop: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct OllamaMessage {
+    #[serde(default)]
+    role: String,
+    #[serde(default)]
+    content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tool_calls: Option<Vec<OllamaToolCall>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OllamaToolCall {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    function: OllamaFunction,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OllamaFunction {
+    name: String,
+    #[serde(default)]
+    arguments: serde_json::Value,
+}
+
+/// Response body, and each line of a streamed response
+#[derive(Debug, Deserialize)]
+struct OllamaChatResponse {
+    #[serde(default)]
+    message: Option<OllamaMessage>,
+    #[serde(default)]
+    done: bool,
+    #[serde(default)]
+    done_reason: Option<String>,
+    #[serde(default)]
+    prompt_eval_count: usize,
+    #[serde(default)]
+    eval_count: usize,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn test_config(endpoint: Option<String>) -> ModelConfig {
+        ModelConfig {
+            model: "llama3.1:8b".to_string(),
+            provider: ModelProvider::Ollama,
+            api_key: None,
+            endpoint,
+            temperature: 0.7,
+            max_tokens: None,
+            timeout_secs: 60,
+            headers: HashMap::new(),
+            extra: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_normalize_host() {
+        assert_eq!(normalize_host("http://localhost:11434/v1"), "http://localhost:11434");
+        assert_eq!(normalize_host("http://gpu-box:11434/"), "http://gpu-box:11434");
+        assert_eq!(normalize_host("0.0.0.0:11434"), "http://0.0.0.0:11434");
+    }
+
+    #[test]
+    fn test_stream_state_buffers_partial_lines() {
+        let mut state = OllamaStreamState::default();
+        state.push_bytes(br#"{"message":{"role":"assistant","content":"Hel"#);
+        assert!(state.pending.is_empty());
+
+        state.push_bytes(b"lo\"},\"done\":false}\n{\"message\":{\"content\":\"\xC3");
+        state.push_bytes(b"\xA9\"},\"done\":false}\n");
+        state.push_bytes(
+            br#"{"message":{"content":""},"done":true,"done_reason":"length","prompt_eval_count":12,"eval_count":3}"#,
+        );
+        state.finish();
+
+        let chunks: Vec<StreamChunk> = state.pending.into_iter().map(|c| c.unwrap()).collect();
+        assert_eq!(chunks.len(), 3);
+        assert!(matches!(&chunks[0], StreamChunk::ContentDelta { delta } if delta == "Hello"));
+        assert!(matches!(&chunks[1], StreamChunk::ContentDelta { delta } if delta == "é"));
+        match &chunks[2] {
+            StreamChunk::Done {
+                usage, stop_reason, ..
+            } => {
+                assert_eq!(usage.input_tokens, 12);
+                assert_eq!(usage.output_tokens, 3);
+                assert_eq!(*stop_reason, StopReason::MaxTokens);
+            }
+            other => panic!("expected Done chunk, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_stream_state_truncated_stream() {
+        let mut state = OllamaStreamState::default();
+        state.push_bytes(b"{\"message\":{\"content\":\"partial\"},\"done\":false}\n");
+        state.finish();
+
+        assert!(state.pending.pop_front().unwrap().is_ok());
+        assert!(state.pending.pop_front().unwrap().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_generate_stream_against_mock_server() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = vec![0; 8192];
+            let _ = socket.read(&mut request).await.unwrap();
+
+            // Lines are split across writes to exercise buffering
+            let writes = [
+                "{\"message\":{\"role\":\"assistant\",\"content\":\"Pods \"},\"done\":false}\n{\"message\":{\"role\":\"assis",
+                "tant\",\"content\":\"are healthy\"},\"done\":false}\n",
+                "{\"message\":{\"role\":\"assistant\",\"content\":\"\",\"tool_calls\":[{\"function\":{\"name\":\"kubectl\",\"arguments\":{\"args\":\"get pods\"}}}]},\"done\":false}\n",
+                "{\"message\":{\"role\":\"assistant\",\"content\":\"\"},\"done\":true,\"done_reason\":\"stop\",\"prompt_eval_count\":21,\"eval_count\":8}\n",
+            ];
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Type: application/x-ndjson\r\nTransfer-Encoding: chunked\r\n\r\n")
+                .await
+                .unwrap();
+            for write in writes {
+                let chunk = format!("{:x}\r\n{}\r\n", write.len(), write);
+                socket.write_all(chunk.as_bytes()).await.unwrap();
+                socket.flush().await.unwrap();
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            socket.write_all(b"0\r\n\r\n").await.unwrap();
+        });
+
+        let model = OllamaModel::new(test_config(Some(format!("http://{}/v1", addr)))).unwrap();
+        let request = ModelRequest {
+            messages: vec![aof_core::RequestMessage {
+                role: MessageRole::User,
+                content: "check pods".to_string(),
+                tool_calls: None,
+                tool_call_id: None,
+                parts: vec![],
+            }],
+            stream: true,
+            ..Default::default()
+        };
+
+        let chunks: Vec<StreamChunk> = model
+            .generate_stream(&request)
+            .await
+            .unwrap()
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+
+        assert_eq!(chunks.len(), 4);
+        assert!(matches!(&chunks[0], StreamChunk::ContentDelta { delta } if delta == "Pods "));
+        assert!(matches!(&chunks[1], StreamChunk::ContentDelta { delta } if delta == "are healthy"));
+        match &chunks[2] {
+            StreamChunk::ToolCall { tool_call } => {
+                assert_eq!(tool_call.id, "call_0");
+                assert_eq!(tool_call.name, "kubectl");
+                assert_eq!(tool_call.arguments["args"], "get pods");
+            }
+            other => panic!("expected ToolCall chunk, got {:?}", other),
+        }
+        match &chunks[3] {
+            StreamChunk::Done {
+                usage, stop_reason, ..
+            } => {
+                assert_eq!(usage.input_tokens, 21);
+                assert_eq!(usage.output_tokens, 8);
+                assert_eq!(*stop_reason, StopReason::ToolUse);
+            }
+            other => panic!("expected Done chunk, got {:?}", other),
+        }
+    }
+
+    /// Serve one request on `listener`, returning its JSON body
+    async fn capture_request_body(
+        listener: tokio::net::TcpListener,
+        response: &'static str,
+    ) -> serde_json::Value {
+        let (mut socket, _) = listener.accept().await.unwrap();
+
+        // Read until the whole body has arrived
+        let mut request = Vec::new();
+        let body_start = loop {
+            let mut buf = [0; 4096];
+            let len = socket.read(&mut buf).await.unwrap();
+            request.extend_from_slice(&buf[..len]);
+            let text = String::from_utf8_lossy(&request);
+            if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                let length: usize = head
+                    .lines()
+                    .find_map(|line| {
+                        line.to_lowercase()
+                            .strip_prefix("content-length:")
+                            .map(|v| v.trim().parse().unwrap())
+                    })
+                    .unwrap_or(0);
+                if body.len() >= length {
+                    break head.len() + 4;
+                }
+            }
+        };
+
+        let reply = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            response.len(),
+            response
+        );
+        socket.write_all(reply.as_bytes()).await.unwrap();
+
+        serde_json::from_slice(&request[body_start..]).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_image_inputs_rejected() {
+        let model = OllamaModel::new(test_config(None)).unwrap();
+        let request = ModelRequest {
+            messages: vec![aof_core::RequestMessage {
+                role: MessageRole::User,
+                content: "What is in this picture?".to_string(),
+                tool_calls: None,
+                tool_call_id: None,
+                parts: vec![aof_core::ContentPart::image_url(
+                    "https://example.com/pod.png",
+                )],
+            }],
+            ..Default::default()
+        };
+
+        let err = model.generate(&request).await.unwrap_err();
+        assert!(err.to_string().contains("don't take image inputs"));
+    }
+
+    #[tokio::test]
+    async fn test_keep_alive_sent_with_requests() {
+        let mut config = test_config(None);
+        config
+            .extra
+            .insert("keep_alive".to_string(), serde_json::json!("30m"));
+        let request = ModelRequest {
+            messages: vec![aof_core::RequestMessage {
+                role: MessageRole::User,
+                content: "check pods".to_string(),
+                tool_calls: None,
+                tool_call_id: None,
+                parts: vec![],
+            }],
+            ..Default::default()
+        };
+
+        let model = OllamaModel::new(config.clone()).unwrap();
+        let body = serde_json::to_value(model.build_request(&request, false)).unwrap();
+        assert_eq!(body["keep_alive"], "30m");
+
+        // Preloading sends no messages, so nothing is generated
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        config.endpoint = Some(format!("http://{}", listener.local_addr().unwrap()));
+        let server = tokio::spawn(capture_request_body(
+            listener,
+            r#"{"model":"llama3.1:8b","message":{"role":"assistant","content":""},"done":true,"done_reason":"load"}"#,
+        ));
+
+        OllamaProvider::preload(config).await.unwrap();
+
+        let body = server.await.unwrap();
+        assert_eq!(body["model"], "llama3.1:8b");
+        assert_eq!(body["messages"], serde_json::json!([]));
+        assert_eq!(body["keep_alive"], "30m");
+    }
+}
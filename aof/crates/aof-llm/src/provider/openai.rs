@@ -1,6 +1,6 @@
 use aof_core::{
-    model::MessageRole, AofError, AofResult, Model, ModelConfig, ModelProvider, ModelRequest,
-    ModelResponse, StopReason, StreamChunk, ToolCall, Usage,
+    model::MessageRole, AofError, AofResult, ContentPart, Model, ModelConfig, ModelProvider,
+    ModelRequest, ModelResponse, RequestMessage, StopReason, StreamChunk, ToolCall, Usage,
 };
 use async_trait::async_trait;
 use futures::stream::{Stream, StreamExt};
@@ -10,6 +10,8 @@ use std::collections::HashMap;
 use std::pin::Pin;
 use std::time::Duration;
 
+use super::{custom_headers, http_error};
+
 /// OpenAI provider
 pub struct OpenAIProvider;
 
@@ -59,6 +61,28 @@ impl OpenAIModel {
         })
     }
 
+    /// Chat completions URL for the configured endpoint
+    pub(crate) fn completions_url(&self) -> String {
+        format!("{}/chat/completions", self.endpoint.trim_end_matches('/'))
+    }
+
+    /// Auth, content-type and custom headers, plus `request_headers` for this request
+    pub(crate) fn request_headers(
+        &self,
+        request_headers: &HashMap<String, String>,
+    ) -> header::HeaderMap {
+        let mut headers = header::HeaderMap::new();
+        if let Ok(value) = header::HeaderValue::from_str(&format!("Bearer {}", self.api_key)) {
+            headers.insert(header::AUTHORIZATION, value);
+        }
+        headers.insert(
+            header::CONTENT_TYPE,
+            header::HeaderValue::from_static("application/json"),
+        );
+        headers.extend(custom_headers(&self.config, request_headers));
+        headers
+    }
+
     /// Build request payload
     fn build_request(&self, request: &ModelRequest) -> OpenAIRequest {
         // Convert messages
@@ -66,9 +90,10 @@ impl OpenAIModel {
             // Add system message first
             let mut msgs = vec![OpenAIMessage {
                 role: "system".to_string(),
-                content: Some(system.clone()),
+                content: Some(OpenAIContent::Text(system.clone())),
                 tool_calls: None,
                 tool_call_id: None,
+                refusal: None,
             }];
             msgs.extend(request.messages.iter().map(|m| OpenAIMessage {
                 role: match m.role {
@@ -78,7 +103,7 @@ impl OpenAIModel {
                     MessageRole::Tool => "tool",
                 }
                 .to_string(),
-                content: Some(m.content.clone()),
+                content: Some(OpenAIContent::from_message(m)),
                 tool_calls: m.tool_calls.as_ref().map(|calls| {
                     calls
                         .iter()
@@ -93,7 +118,8 @@ impl OpenAIModel {
                         })
                         .collect()
                 }),
-                tool_call_id: None,
+                tool_call_id: m.tool_call_id.clone(),
+                refusal: None,
             }));
             msgs
         } else {
@@ -108,7 +134,7 @@ impl OpenAIModel {
                         MessageRole::Tool => "tool",
                     }
                     .to_string(),
-                    content: Some(m.content.clone()),
+                    content: Some(OpenAIContent::from_message(m)),
                     tool_calls: m.tool_calls.as_ref().map(|calls| {
                         calls
                             .iter()
@@ -123,7 +149,8 @@ impl OpenAIModel {
                             })
                             .collect()
                     }),
-                    tool_call_id: None,
+                    tool_call_id: m.tool_call_id.clone(),
+                    refusal: None,
                 })
                 .collect()
         };
@@ -153,8 +180,19 @@ impl OpenAIModel {
             messages,
             temperature: request.temperature.or(Some(self.config.temperature)),
             max_tokens: request.max_tokens.or(self.config.max_tokens),
+            stop: request.stop_sequences.clone(),
+            seed: request.seed,
             stream: Some(request.stream),
             tools,
+            response_format: request.response_schema.as_ref().map(|schema| {
+                serde_json::json!({
+                    "type": "json_schema",
+                    "json_schema": {
+                        "name": "response",
+                        "schema": schema,
+                    }
+                })
+            }),
         }
     }
 
@@ -165,12 +203,10 @@ impl OpenAIModel {
             .first()
             .ok_or_else(|| AofError::model("No choices in OpenAI response"))?;
 
-        let content = choice
-            .message
-            .content
-            .as_ref()
-            .cloned()
-            .unwrap_or_default();
+        let content = match &choice.message.content {
+            Some(OpenAIContent::Text(text)) => text.clone(),
+            _ => String::new(),
+        };
 
         // Parse tool calls
         let tool_calls = choice
@@ -193,8 +229,10 @@ impl OpenAIModel {
             })
             .unwrap_or_default();
 
-        // Map finish reason
+        // Map finish reason; a refusal arrives with an ordinary "stop"
+        let refusal = choice.message.refusal.clone();
         let stop_reason = match choice.finish_reason.as_deref() {
+            _ if refusal.is_some() => StopReason::ContentFilter,
             Some("stop") => StopReason::EndTurn,
             Some("length") => StopReason::MaxTokens,
             Some("tool_calls") | Some("function_call") => StopReason::ToolUse,
@@ -207,53 +245,98 @@ impl OpenAIModel {
             output_tokens: response.usage.completion_tokens,
         };
 
+        let mut metadata = HashMap::new();
+        if let Some(fingerprint) = response.system_fingerprint {
+            metadata.insert(
+                "system_fingerprint".to_string(),
+                serde_json::Value::String(fingerprint),
+            );
+        }
+
         Ok(ModelResponse {
             content,
             tool_calls,
             stop_reason,
             usage,
-            metadata: HashMap::new(),
+            stop_detail: refusal,
+            metadata,
         })
     }
 
 }
 
 /// Parse OpenAI streaming chunk (free function to avoid lifetime issues)
-fn parse_openai_stream_chunk(line: &str) -> Option<AofResult<StreamChunk>> {
+///
+/// One SSE line can carry several deltas (reasoning with content, or the
+/// last content with the finish reason), so it may yield several chunks.
+/// Refusal deltas collect in `refusal` until the finish chunk reports them.
+fn parse_openai_stream_chunk(
+    line: &str,
+    refusal: &mut Option<String>,
+) -> Vec<AofResult<StreamChunk>> {
     // Skip empty lines and comments
-    if line.is_empty() || !line.starts_with("data: ") {
-        return None;
-    }
-
-    let data = line.strip_prefix("data: ")?;
+    let Some(data) = line.strip_prefix("data: ") else {
+        return Vec::new();
+    };
 
     // Check for [DONE] marker
     if data.trim() == "[DONE]" {
-        return None;
+        return Vec::new();
     }
 
     // Parse JSON
     let chunk: OpenAIStreamChunk = match serde_json::from_str(data) {
         Ok(c) => c,
-        Err(e) => return Some(Err(AofError::model(format!("Failed to parse chunk: {}", e)))),
+        Err(e) => {
+            return vec![Err(AofError::model(format!(
+                "Failed to parse chunk: {}",
+                e
+            )))]
+        }
+    };
+
+    // Groq reports usage in `x_groq`, OpenAI (with include_usage) in `usage`
+    let usage = chunk
+        .usage
+        .as_ref()
+        .or(chunk.x_groq.as_ref().and_then(|x| x.usage.as_ref()))
+        .map(|u| Usage {
+            input_tokens: u.prompt_tokens,
+            output_tokens: u.completion_tokens,
+        })
+        .unwrap_or_default();
+
+    let Some(choice) = chunk.choices.first() else {
+        return Vec::new();
     };
+    let mut chunks = Vec::new();
 
-    let choice = chunk.choices.first()?;
+    // Handle reasoning delta
+    if let Some(reasoning) = &choice.delta.reasoning_content {
+        chunks.push(Ok(StreamChunk::ThinkingDelta {
+            delta: reasoning.clone(),
+        }));
+    }
 
-    // Handle content delta
+    // Handle content delta, which may arrive alongside the reasoning
     if let Some(content) = &choice.delta.content {
-        return Some(Ok(StreamChunk::ContentDelta {
+        chunks.push(Ok(StreamChunk::ContentDelta {
             delta: content.clone(),
         }));
     }
 
+    // Handle refusal delta, reported once the stream finishes
+    if let Some(delta) = &choice.delta.refusal {
+        refusal.get_or_insert_with(String::new).push_str(delta);
+    }
+
     // Handle tool calls
     if let Some(tool_calls) = &choice.delta.tool_calls {
         for tc in tool_calls {
             if let Some(func) = &tc.function {
                 if let (Some(name), Some(args)) = (&func.name, &func.arguments) {
                     if let Ok(arguments) = serde_json::from_str(args) {
-                        return Some(Ok(StreamChunk::ToolCall {
+                        chunks.push(Ok(StreamChunk::ToolCall {
                             tool_call: ToolCall {
                                 id: tc.id.clone().unwrap_or_default(),
                                 name: name.clone(),
@@ -266,9 +349,11 @@ fn parse_openai_stream_chunk(line: &str) -> Option<AofResult<StreamChunk>> {
         }
     }
 
-    // Handle finish
+    // Handle finish; as in `parse_response`, a refusal ends with "stop"
     if let Some(finish_reason) = &choice.finish_reason {
+        let refusal = refusal.take();
         let stop_reason = match finish_reason.as_str() {
+            _ if refusal.is_some() => StopReason::ContentFilter,
             "stop" => StopReason::EndTurn,
             "length" => StopReason::MaxTokens,
             "tool_calls" | "function_call" => StopReason::ToolUse,
@@ -276,16 +361,14 @@ fn parse_openai_stream_chunk(line: &str) -> Option<AofResult<StreamChunk>> {
             _ => StopReason::EndTurn,
         };
 
-        return Some(Ok(StreamChunk::Done {
-            usage: Usage {
-                input_tokens: 0, // OpenAI doesn't provide usage in stream
-                output_tokens: 0,
-            },
+        chunks.push(Ok(StreamChunk::Done {
+            usage,
             stop_reason,
+            stop_detail: refusal,
         }));
     }
 
-    None
+    chunks
 }
 
 #[async_trait]
@@ -301,9 +384,8 @@ impl Model for OpenAIModel {
 
         let response = self
             .client
-            .post(format!("{}/chat/completions", self.endpoint))
-            .header(header::AUTHORIZATION, format!("Bearer {}", self.api_key))
-            .header(header::CONTENT_TYPE, "application/json")
+            .post(self.completions_url())
+            .headers(self.request_headers(&request.request_headers))
             .json(&payload)
             .send()
             .await
@@ -311,14 +393,7 @@ impl Model for OpenAIModel {
 
         let status = response.status();
         if !status.is_success() {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(AofError::model(format!(
-                "OpenAI API error ({}): {}",
-                status, error_text
-            )));
+            return Err(http_error("OpenAI API error", response).await);
         }
 
         let openai_response: OpenAIResponse = response
@@ -344,9 +419,8 @@ impl Model for OpenAIModel {
 
         let response = self
             .client
-            .post(format!("{}/chat/completions", self.endpoint))
-            .header(header::AUTHORIZATION, format!("Bearer {}", self.api_key))
-            .header(header::CONTENT_TYPE, "application/json")
+            .post(self.completions_url())
+            .headers(self.request_headers(&request.request_headers))
             .json(&payload)
             .send()
             .await
@@ -354,14 +428,7 @@ impl Model for OpenAIModel {
 
         let status = response.status();
         if !status.is_success() {
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(AofError::model(format!(
-                "OpenAI streaming error ({}): {}",
-                status, error_text
-            )));
+            return Err(http_error("OpenAI streaming error", response).await);
         }
 
         // Convert bytes stream to SSE stream
@@ -371,27 +438,26 @@ impl Model for OpenAIModel {
             .map(|result| {
                 result.map_err(|e| AofError::model(format!("Stream error: {}", e)))
             })
-            .scan(String::new(), |buffer, chunk_result| {
+            .scan(StreamState::default(), |state, chunk_result| {
                 let chunk = match chunk_result {
                     Ok(c) => c,
                     Err(e) => return futures::future::ready(Some(vec![Err(e)])),
                 };
 
                 // Append to buffer
-                buffer.push_str(&String::from_utf8_lossy(&chunk));
+                state.buffer.push_str(&String::from_utf8_lossy(&chunk));
 
                 // Split by newlines and process complete lines
                 let mut results = Vec::new();
+                let buffer = std::mem::take(&mut state.buffer);
                 let lines: Vec<&str> = buffer.split('\n').collect();
 
                 // Keep the last incomplete line in buffer
                 if let Some((last, complete)) = lines.split_last() {
                     for line in complete {
-                        if let Some(chunk) = parse_openai_stream_chunk(line) {
-                            results.push(chunk);
-                        }
+                        results.extend(parse_openai_stream_chunk(line, &mut state.refusal));
                     }
-                    *buffer = last.to_string();
+                    state.buffer = last.to_string();
                 }
 
                 futures::future::ready(Some(results))
@@ -406,7 +472,8 @@ impl Model for OpenAIModel {
     }
 
     fn provider(&self) -> ModelProvider {
-        ModelProvider::OpenAI
+        // Also serves OpenAI-compatible providers (Groq, Ollama)
+        self.config.provider
     }
 
     fn count_tokens(&self, text: &str) -> usize {
@@ -416,6 +483,13 @@ impl Model for OpenAIModel {
     }
 }
 
+/// Line buffer and refusal text carried between streamed chunks
+#[derive(Default)]
+struct StreamState {
+    buffer: String,
+    refusal: Option<String>,
+}
+
 // OpenAI API types
 
 #[derive(Debug, Serialize)]
@@ -426,21 +500,55 @@ struct OpenAIRequest {
     temperature: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     max_tokens: Option<usize>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stop: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     stream: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tools: Option<Vec<OpenAITool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 struct OpenAIMessage {
     role: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    content: Option<String>,
+    content: Option<OpenAIContent>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_calls: Option<Vec<OpenAIToolCall>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_call_id: Option<String>,
+    /// Why the model declined to answer (responses only)
+    #[serde(default, skip_serializing)]
+    refusal: Option<String>,
+}
+
+/// Message content: plain text, or text and images for vision models
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum OpenAIContent {
+    Text(String),
+    Parts(Vec<ContentPart>),
+}
+
+impl OpenAIContent {
+    fn from_message(message: &RequestMessage) -> Self {
+        if message.parts.is_empty() {
+            return Self::Text(message.content.clone());
+        }
+
+        let mut parts = Vec::with_capacity(message.parts.len() + 1);
+        if !message.content.trim().is_empty() {
+            parts.push(ContentPart::Text {
+                text: message.content.clone(),
+            });
+        }
+        parts.extend(message.parts.iter().cloned());
+        Self::Parts(parts)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -473,6 +581,10 @@ struct OpenAIFunctionDef {
 struct OpenAIResponse {
     choices: Vec<OpenAIChoice>,
     usage: OpenAIUsage,
+    /// Backend configuration that served the request; changes when OpenAI
+    /// updates it, even for seeded requests
+    #[serde(default)]
+    system_fingerprint: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -490,6 +602,17 @@ struct OpenAIUsage {
 #[derive(Debug, Deserialize)]
 struct OpenAIStreamChunk {
     choices: Vec<OpenAIStreamChoice>,
+    #[serde(default)]
+    usage: Option<OpenAIUsage>,
+    #[serde(default)]
+    x_groq: Option<GroqStreamExtra>,
+}
+
+/// Groq-specific data attached to the final stream chunk
+#[derive(Debug, Deserialize)]
+struct GroqStreamExtra {
+    #[serde(default)]
+    usage: Option<OpenAIUsage>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -502,6 +625,11 @@ struct OpenAIStreamChoice {
 struct OpenAIDelta {
     #[serde(default)]
     content: Option<String>,
+    /// Reasoning text (`reasoning_content` on DeepSeek, `reasoning` on Groq)
+    #[serde(default, alias = "reasoning")]
+    reasoning_content: Option<String>,
+    #[serde(default)]
+    refusal: Option<String>,
     #[serde(default)]
     tool_calls: Option<Vec<OpenAIStreamToolCall>>,
 }
@@ -553,14 +681,188 @@ mod tests {
 
     #[test]
     fn test_stream_chunk_parsing() {
+        let mut refusal = None;
+
         // Test content delta
         let line = r#"data: {"choices":[{"delta":{"content":"Hello"},"finish_reason":null}]}"#;
-        let chunk = parse_openai_stream_chunk(line);
-        assert!(chunk.is_some());
+        let chunk = parse_openai_stream_chunk(line, &mut refusal);
+        assert_eq!(chunk.len(), 1);
+
+        // Test reasoning delta
+        let line = r#"data: {"choices":[{"delta":{"reasoning_content":"Check pods"},"finish_reason":null}]}"#;
+        assert!(matches!(
+            parse_openai_stream_chunk(line, &mut refusal).as_slice(),
+            [Ok(StreamChunk::ThinkingDelta { delta })] if delta == "Check pods"
+        ));
+
+        // Test reasoning and content in the same delta
+        let line = r#"data: {"choices":[{"delta":{"reasoning":"Pods are fine","content":"All healthy"},"finish_reason":null}]}"#;
+        assert!(matches!(
+            parse_openai_stream_chunk(line, &mut refusal).as_slice(),
+            [
+                Ok(StreamChunk::ThinkingDelta { delta: thinking }),
+                Ok(StreamChunk::ContentDelta { delta: content }),
+            ] if thinking == "Pods are fine" && content == "All healthy"
+        ));
+
+        // Test Groq usage on the final chunk
+        let line = r#"data: {"choices":[{"delta":{},"finish_reason":"stop"}],"x_groq":{"usage":{"prompt_tokens":12,"completion_tokens":7}}}"#;
+        match parse_openai_stream_chunk(line, &mut refusal).as_slice() {
+            [Ok(StreamChunk::Done {
+                usage, stop_reason, ..
+            })] => {
+                assert_eq!(usage.input_tokens, 12);
+                assert_eq!(usage.output_tokens, 7);
+                assert_eq!(*stop_reason, StopReason::EndTurn);
+            }
+            other => panic!("expected Done chunk, got {:?}", other),
+        }
 
         // Test [DONE] marker
         let line = "data: [DONE]";
-        let chunk = parse_openai_stream_chunk(line);
-        assert!(chunk.is_none());
+        let chunk = parse_openai_stream_chunk(line, &mut refusal);
+        assert!(chunk.is_empty());
+    }
+
+    #[test]
+    fn test_stream_refusal_ends_with_content_filter() {
+        let mut refusal = None;
+        for line in [
+            r#"data: {"choices":[{"delta":{"refusal":"I can't help "},"finish_reason":null}]}"#,
+            r#"data: {"choices":[{"delta":{"refusal":"with that."},"finish_reason":null}]}"#,
+        ] {
+            assert!(parse_openai_stream_chunk(line, &mut refusal).is_empty());
+        }
+
+        let line = r#"data: {"choices":[{"delta":{},"finish_reason":"stop"}]}"#;
+        match parse_openai_stream_chunk(line, &mut refusal).as_slice() {
+            [Ok(StreamChunk::Done {
+                stop_reason,
+                stop_detail,
+                ..
+            })] => {
+                assert_eq!(*stop_reason, StopReason::ContentFilter);
+                assert_eq!(stop_detail.as_deref(), Some("I can't help with that."));
+            }
+            other => panic!("expected Done chunk, got {:?}", other),
+        }
+        assert!(refusal.is_none());
+    }
+
+    #[test]
+    fn test_response_schema_sets_response_format() {
+        let config = ModelConfig {
+            model: "gpt-4o".to_string(),
+            provider: ModelProvider::OpenAI,
+            api_key: Some("test".to_string()),
+            endpoint: None,
+            temperature: 0.7,
+            max_tokens: Some(1000),
+            timeout_secs: 60,
+            headers: HashMap::new(),
+            extra: HashMap::new(),
+        };
+        let model = OpenAIModel::new(config).unwrap();
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": { "healthy": { "type": "boolean" } }
+        });
+        let request = ModelRequest {
+            response_schema: Some(schema.clone()),
+            ..Default::default()
+        };
+
+        let body = serde_json::to_value(model.build_request(&request)).unwrap();
+        assert_eq!(body["response_format"]["type"], "json_schema");
+        assert_eq!(body["response_format"]["json_schema"]["schema"], schema);
+    }
+
+    #[test]
+    fn test_image_message_sent_as_content_parts() {
+        let config = ModelConfig {
+            model: "gpt-4o".to_string(),
+            provider: ModelProvider::OpenAI,
+            api_key: Some("test".to_string()),
+            endpoint: None,
+            temperature: 0.7,
+            max_tokens: Some(1000),
+            timeout_secs: 60,
+            headers: HashMap::new(),
+            extra: HashMap::new(),
+        };
+        let model = OpenAIModel::new(config).unwrap();
+        let mut context = aof_core::AgentContext::new("");
+        context.add_image_message(
+            "What is wrong with this dashboard?",
+            aof_core::ImageUrl {
+                url: "https://example.com/grafana.png".to_string(),
+                detail: Some("high".to_string()),
+            },
+        );
+        let message = &context.messages[0];
+        let request = ModelRequest {
+            messages: vec![RequestMessage {
+                role: MessageRole::User,
+                content: message.content.clone(),
+                parts: message.parts.clone(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let body = serde_json::to_value(model.build_request(&request)).unwrap();
+        assert_eq!(
+            body["messages"][0]["content"],
+            serde_json::json!([
+                { "type": "text", "text": "What is wrong with this dashboard?" },
+                {
+                    "type": "image_url",
+                    "image_url": { "url": "https://example.com/grafana.png", "detail": "high" }
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_seed_sent_and_fingerprint_reported() {
+        let config = ModelConfig {
+            model: "gpt-4o".to_string(),
+            provider: ModelProvider::OpenAI,
+            api_key: Some("test".to_string()),
+            endpoint: None,
+            temperature: 0.7,
+            max_tokens: Some(1000),
+            timeout_secs: 60,
+            headers: HashMap::new(),
+            extra: HashMap::new(),
+        };
+        let model = OpenAIModel::new(config).unwrap();
+        let request = ModelRequest {
+            temperature: Some(0.0),
+            seed: Some(42),
+            ..Default::default()
+        };
+
+        let body = serde_json::to_value(model.build_request(&request)).unwrap();
+        assert_eq!(body["seed"], 42);
+
+        let unseeded = ModelRequest {
+            seed: None,
+            ..request
+        };
+        let body = serde_json::to_value(model.build_request(&unseeded)).unwrap();
+        assert!(body.get("seed").is_none());
+
+        let response: OpenAIResponse = serde_json::from_value(serde_json::json!({
+            "choices": [{
+                "message": { "role": "assistant", "content": "All pods healthy" },
+                "finish_reason": "stop"
+            }],
+            "usage": { "prompt_tokens": 12, "completion_tokens": 4 },
+            "system_fingerprint": "fp_44709d6fcb"
+        }))
+        .unwrap();
+        let response = model.parse_response(response).unwrap();
+        assert_eq!(response.metadata["system_fingerprint"], "fp_44709d6fcb");
     }
 }
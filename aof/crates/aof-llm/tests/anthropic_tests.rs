@@ -159,14 +159,12 @@ fn test_anthropic_request_building() {
         messages: vec![RequestMessage {
             role: MessageRole::User,
             content: "Hello".to_string(),
-            tool_calls: None,
+            ..Default::default()
         }],
         system: Some("You are a helpful assistant.".to_string()),
-        tools: vec![],
         temperature: Some(0.5),
         max_tokens: Some(1000),
-        stream: false,
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     // This would normally be tested via the build_request_body method
@@ -210,14 +208,10 @@ fn test_anthropic_with_tools() {
         messages: vec![RequestMessage {
             role: MessageRole::User,
             content: "What's the weather in NYC?".to_string(),
-            tool_calls: None,
+            ..Default::default()
         }],
-        system: None,
         tools: vec![tool],
-        temperature: None,
-        max_tokens: None,
-        stream: false,
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     assert_eq!(request.tools.len(), 1);
@@ -244,14 +238,12 @@ fn test_anthropic_temperature_override() {
         messages: vec![RequestMessage {
             role: MessageRole::User,
             content: "Test".to_string(),
-            tool_calls: None,
+            ..Default::default()
         }],
-        system: None,
-        tools: vec![],
-        temperature: Some(0.2), // Override
+        temperature: Some(0.2),
+        // Override
         max_tokens: None,
-        stream: false,
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     assert_eq!(request.temperature, Some(0.2));
@@ -277,14 +269,12 @@ fn test_anthropic_max_tokens_override() {
         messages: vec![RequestMessage {
             role: MessageRole::User,
             content: "Test".to_string(),
-            tool_calls: None,
+            ..Default::default()
         }],
-        system: None,
-        tools: vec![],
-        temperature: None,
-        max_tokens: Some(1024), // Override
-        stream: false,
-        extra: HashMap::new(),
+        max_tokens: Some(1024),
+        // Override
+        stop_sequences: vec![],
+        ..Default::default()
     };
 
     assert_eq!(request.max_tokens, Some(1024));
@@ -297,25 +287,20 @@ fn test_anthropic_multiple_messages() {
             RequestMessage {
                 role: MessageRole::User,
                 content: "Hello".to_string(),
-                tool_calls: None,
+                ..Default::default()
             },
             RequestMessage {
                 role: MessageRole::Assistant,
                 content: "Hi there!".to_string(),
-                tool_calls: None,
+                ..Default::default()
             },
             RequestMessage {
                 role: MessageRole::User,
                 content: "How are you?".to_string(),
-                tool_calls: None,
+                ..Default::default()
             },
         ],
-        system: None,
-        tools: vec![],
-        temperature: None,
-        max_tokens: None,
-        stream: false,
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     assert_eq!(request.messages.len(), 3);
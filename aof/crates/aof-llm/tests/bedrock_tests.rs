@@ -189,14 +189,12 @@ fn test_bedrock_request_structure() {
         messages: vec![RequestMessage {
             role: MessageRole::User,
             content: "Hello".to_string(),
-            tool_calls: None,
+            ..Default::default()
         }],
         system: Some("You are a helpful assistant.".to_string()),
-        tools: vec![],
         temperature: Some(0.5),
         max_tokens: Some(1000),
-        stream: false,
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     assert_eq!(request.messages.len(), 1);
@@ -227,14 +225,12 @@ async fn test_bedrock_temperature_override() {
             messages: vec![RequestMessage {
                 role: MessageRole::User,
                 content: "Test".to_string(),
-                tool_calls: None,
+                ..Default::default()
             }],
-            system: None,
-            tools: vec![],
-            temperature: Some(0.2), // Override
+            temperature: Some(0.2),
+            // Override
             max_tokens: None,
-            stream: false,
-            extra: HashMap::new(),
+            ..Default::default()
         };
 
         assert_eq!(request.temperature, Some(0.2));
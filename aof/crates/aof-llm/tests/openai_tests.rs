@@ -194,14 +194,11 @@ fn test_openai_with_tools() {
         messages: vec![RequestMessage {
             role: MessageRole::User,
             content: "What's the weather in NYC?".to_string(),
-            tool_calls: None,
+            ..Default::default()
         }],
         system: Some("You are a helpful assistant.".to_string()),
         tools: vec![tool],
-        temperature: None,
-        max_tokens: None,
-        stream: false,
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     assert_eq!(request.tools.len(), 1);
@@ -211,19 +208,13 @@ fn test_openai_with_tools() {
 #[test]
 fn test_openai_system_message_handling() {
     let request = ModelRequest {
-        messages: vec![
-            RequestMessage {
-                role: MessageRole::User,
-                content: "Hello".to_string(),
-                tool_calls: None,
-            },
-        ],
+        messages: vec![RequestMessage {
+            role: MessageRole::User,
+            content: "Hello".to_string(),
+            ..Default::default()
+        }],
         system: Some("You are a helpful assistant.".to_string()),
-        tools: vec![],
-        temperature: None,
-        max_tokens: None,
-        stream: false,
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     assert!(request.system.is_some());
@@ -250,14 +241,12 @@ fn test_openai_temperature_override() {
         messages: vec![RequestMessage {
             role: MessageRole::User,
             content: "Test".to_string(),
-            tool_calls: None,
+            ..Default::default()
         }],
-        system: None,
-        tools: vec![],
-        temperature: Some(0.2), // Override
+        temperature: Some(0.2),
+        // Override
         max_tokens: None,
-        stream: false,
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     assert_eq!(request.temperature, Some(0.2));
@@ -270,25 +259,20 @@ fn test_openai_multiple_messages() {
             RequestMessage {
                 role: MessageRole::User,
                 content: "Hello".to_string(),
-                tool_calls: None,
+                ..Default::default()
             },
             RequestMessage {
                 role: MessageRole::Assistant,
                 content: "Hi there!".to_string(),
-                tool_calls: None,
+                ..Default::default()
             },
             RequestMessage {
                 role: MessageRole::User,
                 content: "How are you?".to_string(),
-                tool_calls: None,
+                ..Default::default()
             },
         ],
-        system: None,
-        tools: vec![],
-        temperature: None,
-        max_tokens: None,
-        stream: false,
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     assert_eq!(request.messages.len(), 3);
@@ -90,19 +90,15 @@ fn test_model_config_serialization() {
 #[test]
 fn test_model_request_serialization() {
     let request = ModelRequest {
-        messages: vec![
-            RequestMessage {
-                role: aof_core::model::MessageRole::User,
-                content: "Hello".to_string(),
-                tool_calls: None,
-            },
-        ],
+        messages: vec![RequestMessage {
+            role: aof_core::model::MessageRole::User,
+            content: "Hello".to_string(),
+            ..Default::default()
+        }],
         system: Some("You are helpful".to_string()),
-        tools: vec![],
         temperature: Some(0.7),
         max_tokens: Some(1000),
-        stream: false,
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     let json = serde_json::to_string(&request).unwrap();
@@ -120,6 +116,7 @@ fn test_model_response_serialization() {
             input_tokens: 10,
             output_tokens: 5,
         },
+        stop_detail: None,
         metadata: HashMap::new(),
     };
 
@@ -1,17 +1,41 @@
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use tracing::{debug, info};
+use std::time::{Duration, Instant};
+use tokio::sync::{watch, RwLock};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
 
 use crate::transport::{McpRequest, McpTransport, TransportType};
 use aof_core::tool::ToolDefinition;
 use aof_core::{AofError, AofResult};
 
+/// How long `call_tool` waits for a response unless configured otherwise
+pub const DEFAULT_CALL_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// JSON-RPC "method not found" error code
+const METHOD_NOT_FOUND: i32 = -32601;
+
+/// Liveness of the server behind an `McpClient`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConnectionHealth {
+    /// Whether the last check (initialize or ping) succeeded
+    pub healthy: bool,
+
+    /// Round-trip time of the last successful ping
+    pub last_ping: Option<Duration>,
+}
+
 /// MCP client
 pub struct McpClient {
     transport: Arc<RwLock<Box<dyn McpTransport>>>,
     tools: Arc<RwLock<HashMap<String, ToolDefinition>>>,
     initialized: Arc<RwLock<bool>>,
+    call_timeout: Duration,
+    keep_alive_interval: Option<Duration>,
+    ping_supported: Arc<AtomicBool>,
+    health: Arc<watch::Sender<ConnectionHealth>>,
+    keep_alive: std::sync::Mutex<Option<JoinHandle<()>>>,
 }
 
 impl McpClient {
@@ -21,9 +45,26 @@ impl McpClient {
             transport: Arc::new(RwLock::new(transport)),
             tools: Arc::new(RwLock::new(HashMap::new())),
             initialized: Arc::new(RwLock::new(false)),
+            call_timeout: DEFAULT_CALL_TIMEOUT,
+            keep_alive_interval: None,
+            ping_supported: Arc::new(AtomicBool::new(true)),
+            health: Arc::new(watch::channel(ConnectionHealth::default()).0),
+            keep_alive: std::sync::Mutex::new(None),
         }
     }
 
+    /// Set how long `call_tool` waits before giving up on the server
+    pub fn with_call_timeout(mut self, timeout: Duration) -> Self {
+        self.call_timeout = timeout;
+        self
+    }
+
+    /// Ping the server every `interval` once initialized
+    pub fn with_keep_alive(mut self, interval: Duration) -> Self {
+        self.keep_alive_interval = Some(interval);
+        self
+    }
+
     /// Initialize MCP connection with optional server initialization options
     pub async fn initialize_with_options(&self, init_options: Option<serde_json::Value>) -> AofResult<()> {
         info!("Initializing MCP client");
@@ -73,6 +114,12 @@ impl McpClient {
         }
 
         *self.initialized.write().await = true;
+        self.health.send_modify(|health| health.healthy = true);
+
+        if let Some(interval) = self.keep_alive_interval {
+            self.start_keep_alive(interval);
+        }
+
         Ok(())
     }
 
@@ -82,6 +129,10 @@ impl McpClient {
     }
 
     /// Call an MCP tool
+    ///
+    /// If the server hasn't answered within the call timeout the request is
+    /// abandoned, the server is sent `notifications/cancelled`, and
+    /// `AofError::Timeout` is returned.
     pub async fn call_tool(&self, name: &str, arguments: serde_json::Value) -> AofResult<serde_json::Value> {
         if !*self.initialized.read().await {
             return Err(AofError::mcp("Client not initialized"));
@@ -98,13 +149,99 @@ impl McpClient {
         );
 
         let transport = self.transport.read().await;
-        let response = transport.request(&request).await?;
+        let call = transport.request(&request);
+        let response = match tokio::time::timeout(self.call_timeout, call).await {
+            Ok(response) => response?,
+            Err(_) => {
+                warn!("MCP tool '{}' timed out, cancelling {}", name, request.id);
+                let cancel = serde_json::json!({
+                    "requestId": request.id,
+                    "reason": "timeout"
+                });
+                if let Err(e) = transport.notify("notifications/cancelled", cancel).await {
+                    debug!("Failed to send cancellation for {}: {}", request.id, e);
+                }
+                return Err(AofError::Timeout(format!(
+                    "MCP tool '{}' did not respond within {:?}",
+                    name, self.call_timeout
+                )));
+            }
+        };
 
         response
             .result
             .ok_or_else(|| AofError::mcp("No result in response"))
     }
 
+    /// Check that the server is responsive, returning the round-trip time
+    ///
+    /// Uses the MCP `ping` request, falling back to `tools/list` for servers
+    /// that don't implement it. Updates [`is_healthy`](Self::is_healthy) and
+    /// [`last_ping`](Self::last_ping).
+    pub async fn ping(&self) -> AofResult<Duration> {
+        if !*self.initialized.read().await {
+            return Err(AofError::mcp("Client not initialized"));
+        }
+
+        ping_server(
+            &self.transport,
+            self.call_timeout,
+            &self.ping_supported,
+            &self.health,
+        )
+        .await
+    }
+
+    /// Ping the server every `interval` in the background
+    ///
+    /// A failed ping marks the connection unhealthy; callers that want to
+    /// reconnect can watch [`health_changes`](Self::health_changes). Replaces
+    /// any keep-alive task already running.
+    pub fn start_keep_alive(&self, interval: Duration) {
+        let transport = self.transport.clone();
+        let timeout = self.call_timeout;
+        let ping_supported = self.ping_supported.clone();
+        let health = self.health.clone();
+
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await; // The first tick completes immediately
+
+            loop {
+                ticker.tick().await;
+                let _ = ping_server(&transport, timeout, &ping_supported, &health).await;
+            }
+        });
+
+        let mut keep_alive = self.keep_alive.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(previous) = keep_alive.replace(task) {
+            previous.abort();
+        }
+    }
+
+    /// Stop the background keep-alive task, if any
+    pub fn stop_keep_alive(&self) {
+        let mut keep_alive = self.keep_alive.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(task) = keep_alive.take() {
+            task.abort();
+        }
+    }
+
+    /// Whether the server answered the last initialize or ping
+    pub fn is_healthy(&self) -> bool {
+        self.health.borrow().healthy
+    }
+
+    /// Round-trip time of the last successful ping
+    pub fn last_ping(&self) -> Option<Duration> {
+        self.health.borrow().last_ping
+    }
+
+    /// Subscribe to health updates, e.g. to reconnect when the server dies
+    pub fn health_changes(&self) -> watch::Receiver<ConnectionHealth> {
+        self.health.subscribe()
+    }
+
     /// List available tools
     pub async fn list_tools(&self) -> AofResult<Vec<ToolDefinition>> {
         let tools = self.tools.read().await;
@@ -114,11 +251,84 @@ impl McpClient {
     /// Shutdown client
     pub async fn shutdown(&self) -> AofResult<()> {
         info!("Shutting down MCP client");
+        self.stop_keep_alive();
+        self.health.send_modify(|health| health.healthy = false);
         let mut transport = self.transport.write().await;
         transport.shutdown().await
     }
 }
 
+impl Drop for McpClient {
+    fn drop(&mut self) {
+        self.stop_keep_alive();
+    }
+}
+
+/// Send a ping and record the outcome in `health`
+async fn ping_server(
+    transport: &RwLock<Box<dyn McpTransport>>,
+    timeout: Duration,
+    ping_supported: &AtomicBool,
+    health: &watch::Sender<ConnectionHealth>,
+) -> AofResult<Duration> {
+    let started = Instant::now();
+    let result = match tokio::time::timeout(timeout, send_ping(transport, ping_supported)).await {
+        Ok(result) => result,
+        Err(_) => Err(AofError::Timeout(format!(
+            "MCP server did not answer ping within {:?}",
+            timeout
+        ))),
+    };
+
+    match result {
+        Ok(()) => {
+            let elapsed = started.elapsed();
+            debug!("MCP ping answered in {:?}", elapsed);
+            health.send_modify(|health| {
+                health.healthy = true;
+                health.last_ping = Some(elapsed);
+            });
+            Ok(elapsed)
+        }
+        Err(e) => {
+            warn!("MCP ping failed: {}", e);
+            health.send_modify(|health| health.healthy = false);
+            Err(e)
+        }
+    }
+}
+
+async fn send_ping(
+    transport: &RwLock<Box<dyn McpTransport>>,
+    ping_supported: &AtomicBool,
+) -> AofResult<()> {
+    let transport = transport.read().await;
+
+    if ping_supported.load(Ordering::Relaxed) {
+        let ping = McpRequest::new("ping", serde_json::json!({}));
+        match transport.request(&ping).await {
+            Err(e) if is_method_not_found(&e) => {
+                debug!("MCP server doesn't support ping, using tools/list");
+                ping_supported.store(false, Ordering::Relaxed);
+            }
+            result => return result.map(|_| ()),
+        }
+    }
+
+    transport
+        .request(&McpRequest::new("tools/list", serde_json::json!({})))
+        .await
+        .map(|_| ())
+}
+
+/// Transports report JSON-RPC errors as `MCP error <code>: <message>`
+fn is_method_not_found(error: &AofError) -> bool {
+    matches!(
+        error,
+        AofError::Mcp(message) if message.starts_with(&format!("MCP error {}:", METHOD_NOT_FOUND))
+    )
+}
+
 /// MCP client builder
 pub struct McpClientBuilder {
     transport_type: TransportType,
@@ -126,6 +336,8 @@ pub struct McpClientBuilder {
     args: Vec<String>,
     endpoint: Option<String>,
     env_vars: HashMap<String, String>,
+    call_timeout: Duration,
+    keep_alive: Option<Duration>,
 }
 
 impl McpClientBuilder {
@@ -136,6 +348,8 @@ impl McpClientBuilder {
             args: Vec::new(),
             endpoint: None,
             env_vars: HashMap::new(),
+            call_timeout: DEFAULT_CALL_TIMEOUT,
+            keep_alive: None,
         }
     }
 
@@ -151,6 +365,18 @@ impl McpClientBuilder {
         self
     }
 
+    /// Set the per-call timeout for `call_tool` (default: 60s)
+    pub fn with_call_timeout(mut self, timeout: Duration) -> Self {
+        self.call_timeout = timeout;
+        self
+    }
+
+    /// Ping the server every `interval` once connected (default: off)
+    pub fn with_keep_alive(mut self, interval: Duration) -> Self {
+        self.keep_alive = Some(interval);
+        self
+    }
+
     #[cfg(feature = "sse")]
     pub fn sse(mut self, endpoint: impl Into<String>) -> Self {
         self.transport_type = TransportType::Sse;
@@ -196,7 +422,11 @@ impl McpClientBuilder {
             }
         };
 
-        Ok(McpClient::new(transport))
+        let mut client = McpClient::new(transport).with_call_timeout(self.call_timeout);
+        if let Some(interval) = self.keep_alive {
+            client = client.with_keep_alive(interval);
+        }
+        Ok(client)
     }
 }
 
@@ -6,7 +6,7 @@
 pub mod client;
 pub mod transport;
 
-pub use client::{McpClient, McpClientBuilder};
+pub use client::{ConnectionHealth, McpClient, McpClientBuilder};
 pub use transport::{McpTransport, TransportType};
 
 // Re-export from aof-core
@@ -8,6 +8,14 @@ pub trait McpTransport: Send + Sync {
     /// Send a request and receive response
     async fn request(&self, request: &McpRequest) -> AofResult<McpResponse>;
 
+    /// Send a notification, which gets no response
+    ///
+    /// Used for `notifications/cancelled` when a caller gives up on a
+    /// request; transports that can't deliver notifications ignore it.
+    async fn notify(&self, _method: &str, _params: serde_json::Value) -> AofResult<()> {
+        Ok(())
+    }
+
     /// Get transport type
     fn transport_type(&self) -> TransportType;
 
@@ -51,6 +59,7 @@ impl McpRequest {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct McpResponse {
     pub jsonrpc: String,
+    #[serde(deserialize_with = "deserialize_id")]
     pub id: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub result: Option<serde_json::Value>,
@@ -58,6 +67,21 @@ pub struct McpResponse {
     pub error: Option<McpError>,
 }
 
+/// Accept numeric JSON-RPC ids as well as strings
+fn deserialize_id<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::String(id) => Ok(id),
+        serde_json::Value::Number(id) => Ok(id.to_string()),
+        other => Err(serde::de::Error::custom(format!(
+            "invalid JSON-RPC id: {}",
+            other
+        ))),
+    }
+}
+
 /// MCP error
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct McpError {
@@ -1,18 +1,24 @@
 use async_trait::async_trait;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::{Child, ChildStdin, ChildStdout, Command};
-use tokio::sync::Mutex;
-use tracing::{debug, info};
+use tokio::sync::{oneshot, Mutex};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
 
 use super::{McpRequest, McpResponse, McpTransport, TransportType};
 use aof_core::{AofError, AofResult};
 
+/// Waiters for in-flight requests, keyed by JSON-RPC id
+type PendingMap = Arc<std::sync::Mutex<HashMap<String, oneshot::Sender<McpResponse>>>>;
+
 /// Stdio transport for MCP
 pub struct StdioTransport {
     process: Arc<Mutex<Option<Child>>>,
     stdin: Arc<Mutex<Option<ChildStdin>>>,
-    stdout: Arc<Mutex<Option<BufReader<ChildStdout>>>>,
+    pending: PendingMap,
+    reader: Option<JoinHandle<()>>,
     command: String,
     args: Vec<String>,
     env_vars: std::collections::HashMap<String, String>,
@@ -23,7 +29,8 @@ impl StdioTransport {
         Self {
             process: Arc::new(Mutex::new(None)),
             stdin: Arc::new(Mutex::new(None)),
-            stdout: Arc::new(Mutex::new(None)),
+            pending: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            reader: None,
             command: command.into(),
             args,
             env_vars: std::collections::HashMap::new(),
@@ -41,6 +48,96 @@ impl StdioTransport {
         self.env_vars.extend(vars);
         self
     }
+
+    /// Number of requests still waiting for a response
+    pub fn pending_requests(&self) -> usize {
+        lock_pending(&self.pending).len()
+    }
+
+    async fn write_line(&self, line: &str) -> AofResult<()> {
+        let mut stdin_guard = self.stdin.lock().await;
+        let stdin = stdin_guard
+            .as_mut()
+            .ok_or_else(|| AofError::mcp("Transport not initialized"))?;
+
+        stdin
+            .write_all(line.as_bytes())
+            .await
+            .map_err(|e| AofError::mcp(format!("Failed to write request: {}", e)))?;
+
+        stdin
+            .write_all(b"\n")
+            .await
+            .map_err(|e| AofError::mcp(format!("Failed to write newline: {}", e)))?;
+
+        stdin
+            .flush()
+            .await
+            .map_err(|e| AofError::mcp(format!("Failed to flush request: {}", e)))
+    }
+}
+
+fn lock_pending(
+    pending: &PendingMap,
+) -> std::sync::MutexGuard<'_, HashMap<String, oneshot::Sender<McpResponse>>> {
+    pending.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Removes a request from the pending map when its caller stops waiting,
+/// whether it got a response, timed out or was dropped
+struct PendingGuard {
+    pending: PendingMap,
+    id: String,
+}
+
+impl Drop for PendingGuard {
+    fn drop(&mut self) {
+        lock_pending(&self.pending).remove(&self.id);
+    }
+}
+
+/// Read responses from the server and hand each one to the waiter with its id
+///
+/// Runs until the server closes stdout; dropping the remaining waiters then
+/// fails their requests instead of leaving them hanging.
+async fn read_responses(stdout: ChildStdout, pending: PendingMap) {
+    let mut lines = BufReader::new(stdout).lines();
+
+    loop {
+        let line = match lines.next_line().await {
+            Ok(Some(line)) => line,
+            Ok(None) => break,
+            Err(e) => {
+                warn!("Failed to read MCP response: {}", e);
+                break;
+            }
+        };
+
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue; // Skip empty lines
+        }
+
+        debug!("Received MCP response: {}", trimmed);
+
+        // Skip non-JSON lines (likely debug output) and server notifications
+        let response = match serde_json::from_str::<McpResponse>(trimmed) {
+            Ok(response) => response,
+            Err(e) => {
+                debug!("Skipping non-JSON line: {} (error: {})", trimmed, e);
+                continue;
+            }
+        };
+
+        match lock_pending(&pending).remove(&response.id) {
+            Some(waiter) => {
+                let _ = waiter.send(response);
+            }
+            None => debug!("Dropping response for unknown request id {}", response.id),
+        }
+    }
+
+    lock_pending(&pending).clear();
 }
 
 #[async_trait]
@@ -89,65 +186,32 @@ impl McpTransport for StdioTransport {
             .ok_or_else(|| AofError::mcp("Failed to get stdout"))?;
 
         *self.stdin.lock().await = Some(stdin);
-        *self.stdout.lock().await = Some(BufReader::new(stdout));
         *self.process.lock().await = Some(child);
+        self.reader = Some(tokio::spawn(read_responses(stdout, self.pending.clone())));
 
         Ok(())
     }
 
     async fn request(&self, request: &McpRequest) -> AofResult<McpResponse> {
-        let mut stdin_guard = self.stdin.lock().await;
-        let stdin = stdin_guard
-            .as_mut()
-            .ok_or_else(|| AofError::mcp("Transport not initialized"))?;
+        if self.reader.is_none() {
+            return Err(AofError::mcp("Transport not initialized"));
+        }
 
-        let mut stdout_guard = self.stdout.lock().await;
-        let stdout = stdout_guard
-            .as_mut()
-            .ok_or_else(|| AofError::mcp("Transport not initialized"))?;
+        // Register before sending so a fast response can't arrive unclaimed
+        let (tx, rx) = oneshot::channel();
+        lock_pending(&self.pending).insert(request.id.clone(), tx);
+        let _guard = PendingGuard {
+            pending: self.pending.clone(),
+            id: request.id.clone(),
+        };
 
-        // Send request
         let request_json = serde_json::to_string(request)?;
         debug!("Sending MCP request: {}", request_json);
+        self.write_line(&request_json).await?;
 
-        stdin
-            .write_all(request_json.as_bytes())
-            .await
-            .map_err(|e| AofError::mcp(format!("Failed to write request: {}", e)))?;
-
-        stdin
-            .write_all(b"\n")
+        let response = rx
             .await
-            .map_err(|e| AofError::mcp(format!("Failed to write newline: {}", e)))?;
-
-        // Read response, skipping empty lines and non-JSON output
-        let response = loop {
-            let mut response_line = String::new();
-            let bytes_read = stdout
-                .read_line(&mut response_line)
-                .await
-                .map_err(|e| AofError::mcp(format!("Failed to read response: {}", e)))?;
-
-            if bytes_read == 0 {
-                return Err(AofError::mcp("MCP server closed connection".to_string()));
-            }
-
-            let trimmed = response_line.trim();
-            if trimmed.is_empty() {
-                continue; // Skip empty lines
-            }
-
-            debug!("Received MCP response: {}", trimmed);
-
-            // Try to parse as JSON
-            match serde_json::from_str::<McpResponse>(trimmed) {
-                Ok(response) => break response,
-                Err(e) => {
-                    debug!("Skipping non-JSON line: {} (error: {})", trimmed, e);
-                    continue; // Skip non-JSON lines (likely debug output)
-                }
-            }
-        };
+            .map_err(|_| AofError::mcp("MCP server closed connection".to_string()))?;
 
         if let Some(error) = &response.error {
             return Err(AofError::mcp(format!(
@@ -159,11 +223,26 @@ impl McpTransport for StdioTransport {
         Ok(response)
     }
 
+    async fn notify(&self, method: &str, params: serde_json::Value) -> AofResult<()> {
+        let notification = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+        });
+        debug!("Sending MCP notification: {}", notification);
+        self.write_line(&notification.to_string()).await
+    }
+
     fn transport_type(&self) -> TransportType {
         TransportType::Stdio
     }
 
     async fn shutdown(&mut self) -> AofResult<()> {
+        if let Some(reader) = self.reader.take() {
+            reader.abort();
+        }
+        lock_pending(&self.pending).clear();
+
         let mut process_guard = self.process.lock().await;
         if let Some(mut process) = process_guard.take() {
             debug!("Shutting down stdio transport");
@@ -0,0 +1,190 @@
+//! Tests for `McpClient` against a mock stdio server
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use aof_core::AofError;
+use aof_mcp::transport::stdio::StdioTransport;
+use aof_mcp::transport::{McpRequest, McpTransport};
+use aof_mcp::McpClientBuilder;
+
+fn mock_server() -> String {
+    format!(
+        "{}/tests/fixtures/mock_mcp_server.sh",
+        env!("CARGO_MANIFEST_DIR")
+    )
+}
+
+/// smoke-test-mcp binary named by `SMOKE_TEST_MCP`, as for the aofctl tests
+fn smoke_test_mcp() -> String {
+    std::env::var("SMOKE_TEST_MCP")
+        .expect("SMOKE_TEST_MCP must name a smoke-test-mcp binary (cargo build -p smoke-test-mcp)")
+}
+
+#[tokio::test]
+async fn test_call_tool_times_out_when_server_stalls() {
+    let timeout = Duration::from_millis(300);
+    let client = McpClientBuilder::new()
+        .stdio("sh", vec![mock_server()])
+        .with_call_timeout(timeout)
+        .build()
+        .unwrap();
+    client.initialize().await.unwrap();
+
+    let started = Instant::now();
+    let err = client
+        .call_tool("echo", serde_json::json!({"message": "hello"}))
+        .await
+        .unwrap_err();
+    let elapsed = started.elapsed();
+
+    assert!(
+        matches!(err, AofError::Timeout(_)),
+        "unexpected error: {}",
+        err
+    );
+    assert!(elapsed >= timeout);
+    assert!(
+        elapsed < timeout + Duration::from_secs(2),
+        "took {:?}",
+        elapsed
+    );
+
+    client.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+#[ignore = "needs SMOKE_TEST_MCP set to a built smoke-test-mcp binary"]
+async fn test_call_tool_times_out_when_responses_are_delayed() {
+    let timeout = Duration::from_millis(100);
+    let client = McpClientBuilder::new()
+        .stdio(smoke_test_mcp(), vec![])
+        .with_env("SMOKE_DELAY_MS", "500")
+        .with_call_timeout(timeout)
+        .build()
+        .unwrap();
+    client.initialize().await.unwrap();
+
+    let started = Instant::now();
+    let err = client
+        .call_tool("add", serde_json::json!({"a": 1, "b": 2}))
+        .await
+        .unwrap_err();
+    let elapsed = started.elapsed();
+
+    assert!(
+        matches!(err, AofError::Timeout(_)),
+        "unexpected error: {}",
+        err
+    );
+    assert!(elapsed >= timeout);
+    assert!(elapsed < Duration::from_millis(500), "took {:?}", elapsed);
+
+    client.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_concurrent_calls_get_their_own_responses() {
+    let client = McpClientBuilder::new()
+        .stdio("sh", vec![mock_server()])
+        .with_env("MOCK_MCP_CALLS", "echo")
+        .build()
+        .unwrap();
+    client.initialize().await.unwrap();
+    let client = Arc::new(client);
+
+    // Earlier calls answer later, so responses come back out of order
+    let mut calls = tokio::task::JoinSet::new();
+    for i in 0..5 {
+        let client = client.clone();
+        calls.spawn(async move {
+            let message = format!("payload-{}", i);
+            let delay = 0.1 * (5 - i) as f64;
+            let result = client
+                .call_tool(
+                    "echo",
+                    serde_json::json!({"message": message, "delay": delay}),
+                )
+                .await
+                .unwrap();
+            (message, result)
+        });
+    }
+
+    let mut answered = 0;
+    while let Some(call) = calls.join_next().await {
+        let (message, result) = call.unwrap();
+        assert_eq!(result["message"], message);
+        answered += 1;
+    }
+    assert_eq!(answered, 5);
+
+    client.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_abandoned_request_is_removed_from_pending() {
+    let mut transport = StdioTransport::new("sh", vec![mock_server()]);
+    transport.init().await.unwrap();
+
+    let request = McpRequest::new("tools/call", serde_json::json!({"name": "echo"}));
+    let result =
+        tokio::time::timeout(Duration::from_millis(100), transport.request(&request)).await;
+
+    assert!(result.is_err());
+    assert_eq!(transport.pending_requests(), 0);
+
+    // The transport is still usable afterwards
+    let request = McpRequest::new("tools/list", serde_json::json!({}));
+    let response = transport.request(&request).await.unwrap();
+    assert_eq!(response.id, request.id);
+
+    transport.shutdown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_ping_records_round_trip() {
+    let client = McpClientBuilder::new()
+        .stdio("sh", vec![mock_server()])
+        .build()
+        .unwrap();
+    client.initialize().await.unwrap();
+    assert!(client.is_healthy());
+    assert!(client.last_ping().is_none());
+
+    let rtt = client.ping().await.unwrap();
+    assert!(client.is_healthy());
+    assert_eq!(client.last_ping(), Some(rtt));
+
+    client.shutdown().await.unwrap();
+    assert!(!client.is_healthy());
+}
+
+#[tokio::test]
+async fn test_keep_alive_marks_unresponsive_server_unhealthy() {
+    // Answer initialize and tools/list, then go silent
+    let client = McpClientBuilder::new()
+        .stdio("sh", vec![mock_server()])
+        .with_env("MOCK_MCP_MAX_RESPONSES", "2")
+        .with_call_timeout(Duration::from_millis(200))
+        .with_keep_alive(Duration::from_millis(100))
+        .build()
+        .unwrap();
+    client.initialize().await.unwrap();
+    assert!(client.is_healthy());
+
+    let mut health = client.health_changes();
+    tokio::time::timeout(
+        Duration::from_secs(5),
+        health.wait_for(|health| !health.healthy),
+    )
+    .await
+    .expect("keep-alive never marked the server unhealthy")
+    .unwrap();
+
+    assert!(!client.is_healthy());
+    assert!(client.last_ping().is_none());
+    assert!(matches!(client.ping().await, Err(AofError::Timeout(_))));
+
+    client.shutdown().await.unwrap();
+}
@@ -0,0 +1,36 @@
+#!/bin/sh
+# Minimal MCP server for client tests.
+#
+# Answers `initialize` and `tools/list`. `tools/call` requests are never
+# answered, simulating a stalled server, unless MOCK_MCP_CALLS=echo: then
+# each call returns its arguments after sleeping `arguments.delay` seconds,
+# in the background so responses can arrive out of order.
+#
+# With MOCK_MCP_MAX_RESPONSES=N the server stops answering after N requests.
+
+answered=0
+
+while IFS= read -r line; do
+    id=$(printf '%s\n' "$line" | sed -n 's/.*"id":"\([^"]*\)".*/\1/p')
+    [ -z "$id" ] && continue # notifications get no response
+
+    answered=$((answered + 1))
+    if [ -n "$MOCK_MCP_MAX_RESPONSES" ] && [ "$answered" -gt "$MOCK_MCP_MAX_RESPONSES" ]; then
+        continue
+    fi
+
+    case "$line" in
+        *'"method":"tools/call"'*)
+            [ "$MOCK_MCP_CALLS" = "echo" ] || continue
+            args=$(printf '%s\n' "$line" | sed -n 's/.*"arguments":\({[^}]*}\).*/\1/p')
+            delay=$(printf '%s\n' "$args" | sed -n 's/.*"delay":\([0-9.]*\).*/\1/p')
+            (sleep "${delay:-0}"; printf '{"jsonrpc":"2.0","id":"%s","result":%s}\n' "$id" "$args") &
+            ;;
+        *'"method":"tools/list"'*)
+            printf '{"jsonrpc":"2.0","id":"%s","result":{"tools":[{"name":"echo","description":"Echo a message","parameters":{"type":"object"}}]}}\n' "$id"
+            ;;
+        *)
+            printf '{"jsonrpc":"2.0","id":"%s","result":{}}\n' "$id"
+            ;;
+    esac
+done
@@ -3,12 +3,15 @@
 use aof_core::{AofResult, MemoryBackend, MemoryEntry, MemoryQuery};
 use async_trait::async_trait;
 use dashmap::DashMap;
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tracing::{debug, warn};
 
 /// High-performance in-memory backend using DashMap
 ///
 /// Provides lock-free concurrent access to memory entries with automatic
-/// TTL expiry on read (lazy cleanup).
+/// TTL expiry on read (lazy cleanup). Expired entries are also swept whenever
+/// keys are listed, and periodically when built with `with_sweep_interval`.
 #[derive(Clone)]
 pub struct InMemoryBackend {
     /// DashMap for lock-free concurrent access
@@ -30,6 +33,26 @@ impl InMemoryBackend {
         }
     }
 
+    /// Sweep expired entries every `interval` in a background task
+    ///
+    /// The task stops once every clone of the backend has been dropped. Must
+    /// be called from within a Tokio runtime; otherwise expired entries are
+    /// only swept by `list_keys` and `retrieve`.
+    pub fn with_sweep_interval(self, interval: Duration) -> Self {
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn(sweep_periodically(Arc::downgrade(&self.store), interval));
+            }
+            Err(_) => warn!("No Tokio runtime; in-memory backend will not sweep in the background"),
+        }
+        self
+    }
+
+    /// Remove every expired entry, returning how many were removed
+    pub fn sweep_expired(&self) -> usize {
+        sweep(&self.store)
+    }
+
     /// Get the number of entries
     pub fn len(&self) -> usize {
         self.store.len()
@@ -78,6 +101,8 @@ impl MemoryBackend for InMemoryBackend {
     }
 
     async fn list_keys(&self, prefix: Option<&str>) -> AofResult<Vec<String>> {
+        self.sweep_expired();
+
         let keys: Vec<String> = match prefix {
             Some(p) => self
                 .store
@@ -95,6 +120,28 @@ impl MemoryBackend for InMemoryBackend {
         Ok(())
     }
 
+    async fn store_many(&self, entries: Vec<(String, MemoryEntry)>) -> AofResult<()> {
+        for (key, entry) in entries {
+            self.store.insert(key, entry);
+        }
+        Ok(())
+    }
+
+    async fn retrieve_many(&self, keys: &[String]) -> AofResult<Vec<Option<MemoryEntry>>> {
+        Ok(keys
+            .iter()
+            .map(|key| {
+                let entry = self.store.get(key)?.value().clone();
+                if entry.is_expired() {
+                    self.store.remove(key);
+                    None
+                } else {
+                    Some(entry)
+                }
+            })
+            .collect())
+    }
+
     async fn search(&self, query: &MemoryQuery) -> AofResult<Vec<MemoryEntry>> {
         let mut results = Vec::new();
 
@@ -102,6 +149,10 @@ impl MemoryBackend for InMemoryBackend {
         for entry_ref in self.store.iter() {
             let entry = entry_ref.value();
 
+            if entry.is_expired() {
+                continue;
+            }
+
             // Check prefix filter
             if let Some(ref prefix) = query.prefix {
                 if !entry.key.starts_with(prefix) {
@@ -126,6 +177,28 @@ impl MemoryBackend for InMemoryBackend {
     }
 }
 
+fn sweep(store: &DashMap<String, MemoryEntry>) -> usize {
+    let before = store.len();
+    store.retain(|_, entry| !entry.is_expired());
+    before.saturating_sub(store.len())
+}
+
+async fn sweep_periodically(store: Weak<DashMap<String, MemoryEntry>>, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.tick().await;
+
+    loop {
+        ticker.tick().await;
+        let Some(store) = store.upgrade() else {
+            break;
+        };
+        let removed = sweep(&store);
+        if removed > 0 {
+            debug!("Swept {} expired memory entries", removed);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -246,6 +319,49 @@ mod tests {
         assert_eq!(backend.len(), 0);
     }
 
+    /// Entry that expires `ms` milliseconds from now
+    fn expiring_entry(key: &str, ms: u64) -> MemoryEntry {
+        let mut entry = MemoryEntry::new(key, json!({"data": "test"})).with_ttl(1);
+        entry.timestamp -= 1000 - ms;
+        entry
+    }
+
+    #[tokio::test]
+    async fn test_list_keys_skips_expired_entries() {
+        let backend = InMemoryBackend::new();
+        backend
+            .store("short", expiring_entry("short", 50))
+            .await
+            .unwrap();
+        backend
+            .store("kept", MemoryEntry::new("kept", json!(1)))
+            .await
+            .unwrap();
+
+        assert_eq!(backend.list_keys(None).await.unwrap().len(), 2);
+
+        sleep(Duration::from_millis(100)).await;
+
+        // Never retrieved, yet gone from the listing and from storage
+        let keys = backend.list_keys(None).await.unwrap();
+        assert_eq!(keys, vec!["kept".to_string()]);
+        assert_eq!(backend.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_background_sweep_removes_expired_entries() {
+        let backend = InMemoryBackend::new().with_sweep_interval(Duration::from_millis(20));
+        backend
+            .store("short", expiring_entry("short", 50))
+            .await
+            .unwrap();
+        assert_eq!(backend.len(), 1);
+
+        sleep(Duration::from_millis(150)).await;
+
+        assert!(backend.is_empty());
+    }
+
     #[tokio::test]
     async fn test_search_with_metadata() {
         let backend = InMemoryBackend::new();
@@ -6,59 +6,145 @@ use aof_core::{AofError, AofResult, Memory, MemoryBackend, MemoryEntry};
 use async_trait::async_trait;
 use std::sync::Arc;
 
+/// Separator between a namespace and the keys inside it
+const NAMESPACE_SEPARATOR: char = ':';
+
 /// Simple memory wrapper that implements the Memory trait
 ///
 /// Provides a high-level interface over MemoryBackend for easier usage.
 pub struct SimpleMemory {
     backend: Arc<dyn MemoryBackend>,
+
+    /// Prefix added to every key (empty for the root view)
+    namespace: String,
 }
 
 impl SimpleMemory {
     /// Create a new SimpleMemory with the given backend
     pub fn new(backend: Arc<dyn MemoryBackend>) -> Self {
-        Self { backend }
+        Self {
+            backend,
+            namespace: String::new(),
+        }
     }
 
     /// Create a new SimpleMemory with InMemoryBackend
     pub fn in_memory() -> Self {
         Self::new(Arc::new(memory::InMemoryBackend::new()))
     }
-}
 
-#[async_trait]
-impl Memory for SimpleMemory {
-    async fn store(&self, key: &str, value: serde_json::Value) -> AofResult<()> {
-        let entry = MemoryEntry::new(key, value);
-        self.backend.store(key, entry).await
+    /// View of this memory whose keys all live under `prefix`
+    ///
+    /// The view shares the backend, but `list_keys` and `clear` only see its
+    /// own keys, so views with different prefixes never collide. Namespacing
+    /// a view nests the new prefix inside the existing one.
+    pub fn namespaced(&self, prefix: &str) -> Self {
+        Self {
+            backend: Arc::clone(&self.backend),
+            namespace: format!("{}{}{}", self.namespace, prefix, NAMESPACE_SEPARATOR),
+        }
     }
 
-    async fn retrieve<T: serde::de::DeserializeOwned>(&self, key: &str) -> AofResult<Option<T>> {
-        match self.backend.retrieve(key).await? {
+    /// Full prefix of this view (`None` for the root view)
+    pub fn namespace(&self) -> Option<&str> {
+        self.namespace
+            .strip_suffix(NAMESPACE_SEPARATOR)
+            .filter(|namespace| !namespace.is_empty())
+    }
+
+    fn backend_key(&self, key: &str) -> String {
+        format!("{}{}", self.namespace, key)
+    }
+
+    /// Deserialize a retrieved entry, deleting it instead if it has expired
+    async fn decode<T: serde::de::DeserializeOwned>(
+        &self,
+        backend_key: &str,
+        entry: Option<MemoryEntry>,
+    ) -> AofResult<Option<T>> {
+        match entry {
             Some(entry) => {
                 if entry.is_expired() {
                     // Lazy cleanup: delete expired entry
-                    let _ = self.backend.delete(key).await;
+                    let _ = self.backend.delete(backend_key).await;
                     Ok(None)
                 } else {
-                    let value = serde_json::from_value(entry.value)
-                        .map_err(|e| AofError::memory(format!("Failed to deserialize value: {}", e)))?;
+                    let value = serde_json::from_value(entry.value).map_err(|e| {
+                        AofError::memory(format!("Failed to deserialize value: {}", e))
+                    })?;
                     Ok(Some(value))
                 }
             }
             None => Ok(None),
         }
     }
+}
+
+#[async_trait]
+impl Memory for SimpleMemory {
+    async fn store(&self, key: &str, value: serde_json::Value) -> AofResult<()> {
+        let key = self.backend_key(key);
+        let entry = MemoryEntry::new(&key, value);
+        self.backend.store(&key, entry).await
+    }
+
+    async fn retrieve<T: serde::de::DeserializeOwned>(&self, key: &str) -> AofResult<Option<T>> {
+        let key = self.backend_key(key);
+        let entry = self.backend.retrieve(&key).await?;
+        self.decode(&key, entry).await
+    }
 
     async fn delete(&self, key: &str) -> AofResult<()> {
-        self.backend.delete(key).await
+        self.backend.delete(&self.backend_key(key)).await
     }
 
     async fn list_keys(&self) -> AofResult<Vec<String>> {
-        self.backend.list_keys(None).await
+        if self.namespace.is_empty() {
+            return self.backend.list_keys(None).await;
+        }
+
+        let keys = self.backend.list_keys(Some(&self.namespace)).await?;
+        Ok(keys
+            .into_iter()
+            .filter_map(|key| key.strip_prefix(&self.namespace).map(str::to_string))
+            .collect())
     }
 
     async fn clear(&self) -> AofResult<()> {
-        self.backend.clear().await
+        if self.namespace.is_empty() {
+            return self.backend.clear().await;
+        }
+
+        for key in self.backend.list_keys(Some(&self.namespace)).await? {
+            self.backend.delete(&key).await?;
+        }
+        Ok(())
+    }
+
+    async fn store_many(&self, values: Vec<(String, serde_json::Value)>) -> AofResult<()> {
+        let entries = values
+            .into_iter()
+            .map(|(key, value)| {
+                let key = self.backend_key(&key);
+                let entry = MemoryEntry::new(&key, value);
+                (key, entry)
+            })
+            .collect();
+        self.backend.store_many(entries).await
+    }
+
+    async fn retrieve_many<T: serde::de::DeserializeOwned + Send>(
+        &self,
+        keys: &[String],
+    ) -> AofResult<Vec<Option<T>>> {
+        let keys: Vec<String> = keys.iter().map(|key| self.backend_key(key)).collect();
+        let entries = self.backend.retrieve_many(&keys).await?;
+
+        let mut values = Vec::with_capacity(keys.len());
+        for (key, entry) in keys.iter().zip(entries) {
+            values.push(self.decode(key, entry).await?);
+        }
+        Ok(values)
     }
 }
 
@@ -66,6 +152,58 @@ impl Memory for SimpleMemory {
 mod tests {
     use super::*;
     use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Backend counting the calls reaching it
+    #[derive(Default)]
+    struct CountingBackend {
+        inner: memory::InMemoryBackend,
+        calls: AtomicUsize,
+    }
+
+    impl CountingBackend {
+        fn count(&self) {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[async_trait]
+    impl MemoryBackend for CountingBackend {
+        async fn store(&self, key: &str, entry: MemoryEntry) -> AofResult<()> {
+            self.count();
+            self.inner.store(key, entry).await
+        }
+
+        async fn retrieve(&self, key: &str) -> AofResult<Option<MemoryEntry>> {
+            self.count();
+            self.inner.retrieve(key).await
+        }
+
+        async fn delete(&self, key: &str) -> AofResult<()> {
+            self.count();
+            self.inner.delete(key).await
+        }
+
+        async fn list_keys(&self, prefix: Option<&str>) -> AofResult<Vec<String>> {
+            self.count();
+            self.inner.list_keys(prefix).await
+        }
+
+        async fn clear(&self) -> AofResult<()> {
+            self.count();
+            self.inner.clear().await
+        }
+
+        async fn store_many(&self, entries: Vec<(String, MemoryEntry)>) -> AofResult<()> {
+            self.count();
+            self.inner.store_many(entries).await
+        }
+
+        async fn retrieve_many(&self, keys: &[String]) -> AofResult<Vec<Option<MemoryEntry>>> {
+            self.count();
+            self.inner.retrieve_many(keys).await
+        }
+    }
 
     #[tokio::test]
     async fn test_simple_memory_store_retrieve() {
@@ -117,4 +255,61 @@ mod tests {
         let keys = memory.list_keys().await.unwrap();
         assert_eq!(keys.len(), 0);
     }
+
+    #[tokio::test]
+    async fn test_namespaced_views_do_not_collide() {
+        let memory = SimpleMemory::in_memory();
+        let first = memory.namespaced("agent:ops:session-1");
+        let second = memory.namespaced("agent:ops:session-2");
+        assert_eq!(first.namespace(), Some("agent:ops:session-1"));
+        assert_eq!(memory.namespace(), None);
+
+        first.store("conversation", json!(["first"])).await.unwrap();
+        second
+            .store("conversation", json!(["second"]))
+            .await
+            .unwrap();
+
+        let value: Option<serde_json::Value> = first.retrieve("conversation").await.unwrap();
+        assert_eq!(value, Some(json!(["first"])));
+        let value: Option<serde_json::Value> = second.retrieve("conversation").await.unwrap();
+        assert_eq!(value, Some(json!(["second"])));
+        assert_eq!(
+            first.list_keys().await.unwrap(),
+            vec!["conversation".to_string()]
+        );
+        assert_eq!(memory.list_keys().await.unwrap().len(), 2);
+
+        // Clearing one view leaves the other untouched
+        first.clear().await.unwrap();
+        assert!(first.list_keys().await.unwrap().is_empty());
+        let value: Option<serde_json::Value> = second.retrieve("conversation").await.unwrap();
+        assert_eq!(value, Some(json!(["second"])));
+        assert_eq!(
+            memory.list_keys().await.unwrap(),
+            vec!["agent:ops:session-2:conversation".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_batch_operations_make_one_backend_call() {
+        let backend = Arc::new(CountingBackend::default());
+        let memory =
+            SimpleMemory::new(Arc::clone(&backend) as Arc<dyn MemoryBackend>).namespaced("session");
+
+        memory
+            .store_many(vec![
+                ("a".to_string(), json!(1)),
+                ("b".to_string(), json!(2)),
+                ("c".to_string(), json!(3)),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(backend.calls.load(Ordering::SeqCst), 1);
+
+        let keys = ["c", "missing", "a", "b"].map(String::from);
+        let values: Vec<Option<i64>> = memory.retrieve_many(&keys).await.unwrap();
+        assert_eq!(values, vec![Some(3), None, Some(1), Some(2)]);
+        assert_eq!(backend.calls.load(Ordering::SeqCst), 2);
+    }
 }
@@ -7,17 +7,22 @@
 //! 4. Repeat until EndTurn or max iterations
 
 use aof_core::{
-    AgentConfig, AgentContext, AofError, AofResult, Memory, MessageRole, Model, ModelRequest,
-    ModelToolDefinition, RequestMessage, StopReason, StreamChunk, ToolCall, ToolExecutor, ToolInput, ToolResult,
+    AgentConfig, AgentContext, AofError, AofResult, ErrorKnowledgeBase, ErrorRecord,
+    ExecutionMetadata, Memory, MessageRole, Model, ModelRequest, ModelRetryPolicy, ModelToolDefinition, Pricing, RequestMessage, StopReason, StreamChunk, ToolCall,
+    ToolExecutor, ToolInput, ToolResult, Usage,
 };
+use super::delegation::{DEPTH_CONTEXT_KEY, SESSION_CONTEXT_KEY, TRACE_CONTEXT_KEY};
+use super::health::{AgentHealth, ComponentHealth, UnavailableTools};
 use aof_memory::SimpleMemory;
 use futures::StreamExt;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::sync::{mpsc, Semaphore};
+use tokio::sync::{mpsc, oneshot, Semaphore};
 use tokio::task::JoinSet;
-use tracing::{debug, error, info, warn};
+use tracing::{debug, error, field, info, info_span, instrument, warn, Instrument, Span};
 
 /// Stream event types for real-time agent execution updates
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,6 +46,8 @@ pub enum StreamEvent {
         tool_id: String,
         success: bool,
         execution_time_ms: u64,
+        /// Attempts the tool needed (1 when not retried)
+        attempts: u32,
         #[serde(skip_serializing_if = "Option::is_none")]
         error: Option<String>,
     },
@@ -65,6 +72,12 @@ pub enum StreamEvent {
         execution_time_ms: u64,
         input_tokens: usize,
         output_tokens: usize,
+        /// Trace id shared by this run's log lines
+        trace_id: String,
+    },
+    /// Non-fatal warning, such as nearing the iteration limit
+    Warning {
+        message: String,
     },
     /// Error occurred
     Error {
@@ -82,6 +95,80 @@ enum ErrorCategory {
     Terminal,
 }
 
+impl ErrorCategory {
+    fn as_str(self) -> &'static str {
+        match self {
+            ErrorCategory::Retryable => "retryable",
+            ErrorCategory::Terminal => "terminal",
+        }
+    }
+}
+
+/// First tool retry delay; doubles with each further attempt
+const RETRY_INITIAL_BACKOFF_MS: u64 = 1000;
+
+/// Longest server-requested `Retry-After` the executor will wait out
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// How long a failed model call marks the model unhealthy when no newer call
+/// has succeeded
+const DEFAULT_MODEL_HEALTH_TTL: Duration = Duration::from_secs(60);
+
+/// Concurrency permits shared by the tools of a parallel batch
+const MAX_PARALLEL_TOOLS: u32 = 10;
+
+/// Error reported for tools aborted by a `fail_fast` batch
+const TOOL_CANCELLED_ERROR: &str = "Cancelled: another tool in the batch failed";
+
+/// Session memory key holding the full conversation
+pub const CONVERSATION_KEY: &str = "conversation";
+
+/// Prefix of the session memory keys recording individual turns
+const TURN_KEY_PREFIX: &str = "turn:";
+
+/// Key read from the memory backend to check that it is reachable
+const MEMORY_PROBE_KEY: &str = "health:probe";
+
+/// Tokens kept free for the model's reply when the agent sets no `max_tokens`
+const DEFAULT_RESPONSE_RESERVE: usize = 4096;
+
+/// Times a model call is repeated after shrinking an overflowing conversation
+const MAX_OVERFLOW_RETRIES: usize = 3;
+
+/// Instructions for the model when it summarizes pruned conversation turns
+const SUMMARY_PROMPT: &str = "Summarize the following conversation in a few sentences. \
+Keep the facts, decisions and open questions later turns may rely on.";
+
+/// What the executor does when the model rejects the conversation as too long
+///
+/// Set with `on_context_overflow` in the agent config or
+/// `AgentExecutor::with_context_overflow_policy`.
+pub use aof_core::ContextOverflowPolicy;
+
+/// What the executor does when the model ends its turn without content or tool calls
+///
+/// Set with `on_empty_response` in the agent config or
+/// `AgentExecutor::with_empty_response_policy`.
+pub use aof_core::EmptyResponsePolicy;
+
+/// Produces a short summary of conversation turns dropped by history pruning
+///
+/// Without one, the executor asks its own model for the summary.
+pub type HistorySummarizer = Arc<dyn Fn(&[aof_core::Message]) -> String + Send + Sync>;
+
+/// Conversation session of an agent found in its memory
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionInfo {
+    /// Session id, as passed to `AgentContext::with_session_id`
+    pub session_id: String,
+
+    /// Messages in the stored conversation
+    pub message_count: usize,
+
+    /// When the conversation was last stored (Unix epoch seconds)
+    pub last_updated: u64,
+}
+
 /// Agent executor that manages the execution lifecycle
 ///
 /// This is the core execution engine that orchestrates the interaction
@@ -96,24 +183,316 @@ pub struct AgentExecutor {
     /// Tool executor (optional)
     tool_executor: Option<Arc<dyn ToolExecutor>>,
 
-    /// Memory backend (optional)
+    /// Memory as passed in, shared with other agents and sessions (optional)
+    base_memory: Option<Arc<SimpleMemory>>,
+
+    /// This session's namespace within `base_memory`
     memory: Option<Arc<SimpleMemory>>,
+
+    /// Identifies this executor's session in its memory namespace
+    session_id: String,
+
+    /// Summarizer for pruned history (optional)
+    summarizer: Option<HistorySummarizer>,
+
+    /// Token prices for cost estimates
+    pricing: Arc<Pricing>,
+
+    /// Knowledge base recording failed tool and model calls (optional)
+    error_kb: Option<Arc<ErrorKnowledgeBase>>,
+
+    /// Largest tool result inserted into the message history, in bytes
+    max_tool_result_bytes: Option<usize>,
+
+    /// Recovery when the conversation exceeds the model's context window
+    context_overflow: ContextOverflowPolicy,
+
+    /// Handling of a final answer with no content
+    empty_response: EmptyResponsePolicy,
+
+    /// Executions in flight and when the agent was last used
+    activity: Activity,
+
+    /// Model reachability as of the last model call, and when it was made
+    model_health: parking_lot::Mutex<(ComponentHealth, Instant)>,
+
+    /// How long a model outage is reported after the call that found it
+    model_health_ttl: Duration,
+
+    /// Tools left out at load time because their source was unreachable
+    unavailable_tools: Vec<UnavailableTools>,
 }
 
 impl AgentExecutor {
     /// Create a new agent executor
+    ///
+    /// Conversation state is kept under a fresh session namespace of
+    /// `memory` (see `with_session_id`).
     pub fn new(
         config: AgentConfig,
         model: Box<dyn Model>,
         tool_executor: Option<Arc<dyn ToolExecutor>>,
         memory: Option<Arc<SimpleMemory>>,
     ) -> Self {
+        let context_overflow = config.on_context_overflow;
+        let empty_response = config.on_empty_response;
+        let session_id = uuid::Uuid::new_v4().to_string();
+        let memory_view = memory
+            .as_ref()
+            .map(|memory| session_memory(memory, &config.name, &session_id));
         Self {
             config,
             model,
             tool_executor,
-            memory,
+            base_memory: memory,
+            memory: memory_view,
+            session_id,
+            summarizer: None,
+            pricing: Arc::new(Pricing::default()),
+            error_kb: None,
+            max_tool_result_bytes: None,
+            context_overflow,
+            empty_response,
+            activity: Activity::new(),
+            model_health: parking_lot::Mutex::new((ComponentHealth::unknown(), Instant::now())),
+            model_health_ttl: DEFAULT_MODEL_HEALTH_TTL,
+            unavailable_tools: Vec::new(),
+        }
+    }
+
+    /// Continue the session `session_id` instead of starting a new one
+    ///
+    /// Memory keys are namespaced by agent name and session id, so two
+    /// sessions of the same agent never overwrite each other's history.
+    pub fn with_session_id(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = session_id.into();
+        self.memory = self
+            .base_memory
+            .as_ref()
+            .map(|memory| session_memory(memory, &self.config.name, &self.session_id));
+        self
+    }
+
+    /// Use `pricing` instead of the built-in prices for cost estimates
+    pub fn with_pricing(mut self, pricing: Arc<Pricing>) -> Self {
+        self.pricing = pricing;
+        self
+    }
+
+    /// Record failed tool calls and model errors into `error_kb`
+    pub fn with_error_knowledge_base(mut self, error_kb: Arc<ErrorKnowledgeBase>) -> Self {
+        self.error_kb = Some(error_kb);
+        self
+    }
+
+    /// Truncate tool results larger than `max_bytes` in the message history
+    ///
+    /// The full result is stored in the session's memory (when configured)
+    /// under `tool_result:<call id>` so it can still be retrieved.
+    pub fn with_max_tool_result_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_tool_result_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Set how a conversation too long for the model's context window is handled
+    pub fn with_context_overflow_policy(mut self, policy: ContextOverflowPolicy) -> Self {
+        self.context_overflow = policy;
+        self
+    }
+
+    /// Set how a final answer with no content is handled
+    pub fn with_empty_response_policy(mut self, policy: EmptyResponsePolicy) -> Self {
+        self.empty_response = policy;
+        self
+    }
+
+    /// Set how long `health` reports the model as unhealthy after a call
+    /// found it down, unless a newer call succeeds
+    pub fn with_model_health_ttl(mut self, ttl: Duration) -> Self {
+        self.model_health_ttl = ttl;
+        self
+    }
+
+    /// Record tools the agent was loaded without, reported by `health`
+    pub fn with_unavailable_tools(mut self, unavailable: Vec<UnavailableTools>) -> Self {
+        self.unavailable_tools = unavailable;
+        self
+    }
+
+    /// Build the history message for a tool result, truncating it if needed
+    async fn tool_result_message(
+        &self,
+        tool_call: &ToolCall,
+        data: &serde_json::Value,
+    ) -> aof_core::Message {
+        let mut content = serde_json::to_string(data).unwrap_or_else(|_| "{}".to_string());
+
+        if let Some(max_bytes) = self.max_tool_result_bytes.filter(|max| content.len() > *max) {
+            let end = truncate_to_char_boundary(&content, max_bytes).len();
+            let omitted = content.len() - end;
+
+            let key = format!("tool_result:{}", tool_call.id);
+            let stored = match &self.memory {
+                Some(memory) => match memory.store(&key, data.clone()).await {
+                    Ok(()) => true,
+                    Err(e) => {
+                        warn!("Failed to store full result of {}: {}", tool_call.name, e);
+                        false
+                    }
+                },
+                None => false,
+            };
+
+            content.truncate(end);
+            if stored {
+                content.push_str(&format!(
+                    "\n[truncated, {} bytes omitted, retrievable via key {}]",
+                    omitted, key
+                ));
+            } else {
+                content.push_str(&format!("\n[truncated, {} bytes omitted]", omitted));
+            }
+            info!(
+                "Truncated {} result to {} bytes ({} omitted)",
+                tool_call.name, end, omitted
+            );
+        }
+
+        aof_core::Message {
+            role: MessageRole::Tool,
+            content,
+            tool_calls: None,
+            tool_call_id: Some(tool_call.id.clone()),
+            parts: vec![],
+        }
+    }
+
+    /// Add one model call's usage (and its cost, if priced) to the run totals
+    fn record_usage(&self, metadata: &mut ExecutionMetadata, usage: &Usage) {
+        let model = &self.model.config().model;
+        metadata.input_tokens += usage.input_tokens;
+        metadata.output_tokens += usage.output_tokens;
+        if let Some(cost) = usage.cost(model, &self.pricing) {
+            *metadata.cost_usd.get_or_insert(0.0) += cost;
+        }
+        metadata.model = Some(model.clone());
+    }
+
+    /// Apply the agent's `on_empty_response` policy to a finished model turn
+    ///
+    /// Returns whether the turn should be asked again, or the error failing
+    /// the run when it is empty and may not be retried.
+    fn check_empty_response(
+        &self,
+        stop_reason: StopReason,
+        answer: &str,
+        tool_calls: &[ToolCall],
+        retried: &mut bool,
+    ) -> AofResult<bool> {
+        if !is_empty_answer(stop_reason, answer, tool_calls) {
+            return Ok(false);
+        }
+        if self.empty_response == EmptyResponsePolicy::Retry && !*retried {
+            warn!(
+                "Empty response from model, asking again: {}",
+                self.config.name
+            );
+            *retried = true;
+            return Ok(true);
+        }
+        error!("Empty response from model: {}", self.config.name);
+        Err(AofError::model("empty response"))
+    }
+
+    /// Warning once a run reaches the agent's `warn_iteration_threshold`
+    fn iteration_warning(&self, iteration: usize) -> Option<String> {
+        let threshold = self.config.warn_iteration_threshold?;
+        (iteration >= threshold).then(|| {
+            format!(
+                "Agent {} is at iteration {} of {} (warning threshold {})",
+                self.config.name, iteration, self.config.max_iterations, threshold
+            )
+        })
+    }
+
+    /// Message for stopping a run that has made `used` tool calls when
+    /// `requested` more would exceed `max_tool_calls`
+    fn tool_budget_exhausted(&self, used: usize, requested: usize) -> Option<String> {
+        let budget = self.config.max_tool_calls?;
+        (used + requested > budget).then(|| {
+            format!(
+                "Tool call budget exhausted ({} of {} used, {} more requested), stopping with partial results",
+                used, budget, requested
+            )
+        })
+    }
+
+    /// Whether a failed tool in a parallel batch cancels the rest
+    ///
+    /// Enabled with `fail_fast: true` in the agent config; by default every
+    /// tool in the batch runs to completion.
+    fn tool_fail_fast(&self) -> bool {
+        self.config.fail_fast
+    }
+
+    /// Retry policy for model calls
+    ///
+    /// Set with `model_retry_policy` in the agent config (e.g.
+    /// `{max_attempts: 5, initial_backoff_ms: 500}`); omitted fields keep
+    /// their defaults. Only transient errors are retried, whatever the policy.
+    fn model_retry_policy(&self) -> ModelRetryPolicy {
+        self.config.model_retry_policy.unwrap_or_default()
+    }
+
+    /// Concurrency permits a call to `tool_name` holds while it runs
+    ///
+    /// Set per tool with the `tool_weights` map in the agent config (e.g.
+    /// `run_build: 5`) so expensive tools limit the total load of a batch.
+    /// Unlisted tools weigh 1; weights are capped at `MAX_PARALLEL_TOOLS`.
+    fn tool_weight(&self, tool_name: &str) -> u32 {
+        self.config
+            .tool_weights
+            .get(tool_name)
+            .map_or(1, |w| (*w).clamp(1, MAX_PARALLEL_TOOLS))
+    }
+
+    /// Whether repeated calls to `tool_name` can share one execution
+    ///
+    /// Only read-only tools listed in `cacheable_tools` in the agent config
+    /// qualify; a tool with side effects always runs once per call.
+    fn tool_cacheable(&self, tool_name: &str) -> bool {
+        self.config.cacheable_tools.iter().any(|t| t == tool_name)
+    }
+
+    /// Collapse calls to cacheable tools repeating an earlier call's arguments
+    ///
+    /// Returns the calls to run and, for each original call, the index of the
+    /// run whose result it shares.
+    fn dedup_tool_calls(&self, tool_calls: &[ToolCall]) -> (Vec<ToolCall>, Vec<usize>) {
+        let mut unique: Vec<ToolCall> = Vec::new();
+        let mut sources = Vec::with_capacity(tool_calls.len());
+
+        for tool_call in tool_calls {
+            let earlier = unique
+                .iter()
+                .position(|run| run.name == tool_call.name && run.arguments == tool_call.arguments);
+            match earlier.filter(|_| self.tool_cacheable(&tool_call.name)) {
+                Some(idx) => sources.push(idx),
+                None => {
+                    sources.push(unique.len());
+                    unique.push(tool_call.clone());
+                }
+            }
         }
+
+        (unique, sources)
+    }
+
+    /// Summarize turns dropped by history pruning with `summarizer` instead
+    /// of the agent's model
+    pub fn with_history_summarizer(mut self, summarizer: HistorySummarizer) -> Self {
+        self.summarizer = Some(summarizer);
+        self
     }
 
     /// Execute the agent with streaming support for real-time updates
@@ -125,6 +504,10 @@ impl AgentExecutor {
     /// 4. Handle tool calls and emit events
     /// 5. Repeat until done or max iterations
     ///
+    /// If the receiving end of `stream_tx` is dropped, the run stops at once,
+    /// including any in-flight model or tool call, and returns an error rather
+    /// than generating events nobody reads.
+    ///
     /// # Arguments
     /// * `ctx` - Agent context (mutable for state updates)
     /// * `stream_tx` - Channel sender for streaming events
@@ -151,22 +534,60 @@ impl AgentExecutor {
     /// let result = executor.execute_streaming(&mut ctx, tx).await;
     /// # }
     /// ```
+    #[instrument(
+        name = "agent_execution",
+        skip_all,
+        fields(
+            agent = %self.config.name,
+            trace_id = field::Empty,
+            input_tokens = field::Empty,
+            output_tokens = field::Empty,
+            success = field::Empty,
+        )
+    )]
     pub async fn execute_streaming(
         &self,
         ctx: &mut AgentContext,
         stream_tx: mpsc::Sender<StreamEvent>,
+    ) -> AofResult<String> {
+        let _run = self.activity.start();
+        let trace_id = start_trace(ctx);
+        let result = tokio::select! {
+            biased;
+            result = self.execute_streaming_loop(ctx, stream_tx.clone(), &trace_id) => result,
+            _ = stream_tx.closed() => {
+                warn!("Stream consumer gone, stopping agent: {}", self.config.name);
+                Err(AofError::agent("Stream consumer gone"))
+            }
+        };
+        finish_trace(&ctx.metadata, result.is_ok());
+        result
+    }
+
+    async fn execute_streaming_loop(
+        &self,
+        ctx: &mut AgentContext,
+        stream_tx: mpsc::Sender<StreamEvent>,
+        trace_id: &str,
     ) -> AofResult<String> {
         info!("Starting streaming agent execution: {}", self.config.name);
         let execution_start = Instant::now();
 
         // Add user message if not already in history
         if ctx.messages.is_empty() {
-            ctx.add_message(MessageRole::User, ctx.input.clone());
+            ctx.add_message_with_parts(
+                MessageRole::User,
+                ctx.input.clone(),
+                ctx.input_parts.clone(),
+            );
         }
 
         let mut iteration = 0;
         let max_iterations = self.config.max_iterations;
         let mut accumulated_content = String::new();
+        let mut iteration_warned = false;
+        let mut run_tool_calls = 0;
+        let mut empty_retried = false;
 
         loop {
             iteration += 1;
@@ -187,33 +608,56 @@ impl AgentExecutor {
                 max_iterations,
             }).await;
 
-            debug!("Agent iteration {}/{} for: {}", iteration, max_iterations, self.config.name);
-
-            // Build model request with streaming enabled
-            let mut request = self.build_model_request(ctx)?;
-            request.stream = true;
+            if !iteration_warned {
+                if let Some(message) = self.iteration_warning(iteration) {
+                    warn!("{}", message);
+                    let _ = stream_tx.send(StreamEvent::Warning { message }).await;
+                    iteration_warned = true;
+                }
+            }
 
-            // Call model streaming API
-            let stream_result = self.model.generate_stream(&request).await;
+            debug!("Agent iteration {}/{} for: {}", iteration, max_iterations, self.config.name);
 
-            let mut stream = match stream_result {
-                Ok(s) => s,
-                Err(e) => {
-                    let error_msg = format!("Model streaming failed: {}", e);
-                    let _ = stream_tx.send(StreamEvent::Error {
-                        message: error_msg.clone(),
-                    }).await;
-                    return Err(AofError::agent(error_msg));
+            // Call model streaming API, shrinking the conversation and
+            // calling again while it overflows the context window
+            let mut overflow_retries = 0;
+            let (mut stream, model_span) = loop {
+                let mut request = self.build_model_request(ctx)?;
+                request.stream = true;
+
+                let model_span = self.model_call_span();
+                match self
+                    .with_model_retry(|| self.model.generate_stream(&request))
+                    .instrument(model_span.clone())
+                    .await
+                {
+                    Ok(stream) => break (stream, model_span),
+                    Err(e) => {
+                        record_model_call(&model_span, None);
+                        if self
+                            .recover_from_overflow(&e, ctx, &mut overflow_retries)
+                            .await
+                        {
+                            continue;
+                        }
+                        let _ = stream_tx
+                            .send(StreamEvent::Error {
+                                message: format!("Model streaming failed: {}", e),
+                            })
+                            .await;
+                        return Err(e);
+                    }
                 }
             };
 
             let mut iteration_content = String::new();
             let mut tool_calls_buffer: Vec<ToolCall> = Vec::new();
             let mut current_stop_reason = StopReason::EndTurn;
+            let mut stop_detail = None;
             let mut usage = aof_core::Usage::default();
 
             // Process stream chunks
-            while let Some(chunk_result) = stream.next().await {
+            while let Some(chunk_result) = stream.next().instrument(model_span.clone()).await {
                 match chunk_result {
                     Ok(chunk) => {
                         match chunk {
@@ -231,6 +675,12 @@ impl AgentExecutor {
                                     ),
                                 }).await;
                             }
+                            StreamChunk::ThinkingDelta { delta } => {
+                                // Reasoning is surfaced but never part of the answer
+                                let _ = stream_tx
+                                    .send(StreamEvent::Thinking { content: delta })
+                                    .await;
+                            }
                             StreamChunk::ToolCall { tool_call } => {
                                 // Emit tool call start event
                                 let _ = stream_tx.send(StreamEvent::ToolCallStart {
@@ -241,14 +691,21 @@ impl AgentExecutor {
 
                                 tool_calls_buffer.push(tool_call);
                             }
-                            StreamChunk::Done { usage: chunk_usage, stop_reason } => {
+                            StreamChunk::Done {
+                                usage: chunk_usage,
+                                stop_reason,
+                                stop_detail: detail,
+                            } => {
                                 usage = chunk_usage;
                                 current_stop_reason = stop_reason;
+                                stop_detail = detail;
                                 break;
                             }
                         }
                     }
                     Err(e) => {
+                        record_model_call(&model_span, None);
+                        self.record_model_error(&e, 1);
                         let error_msg = format!("Stream chunk error: {}", e);
                         let _ = stream_tx.send(StreamEvent::Error {
                             message: error_msg.clone(),
@@ -259,10 +716,37 @@ impl AgentExecutor {
                 }
             }
 
+            record_model_call(&model_span, Some(&usage));
+            drop(model_span);
+
             // Update usage statistics
-            ctx.metadata.input_tokens += usage.input_tokens;
-            ctx.metadata.output_tokens += usage.output_tokens;
-            ctx.metadata.model = Some(self.model.config().model.clone());
+            self.record_usage(&mut ctx.metadata, &usage);
+
+            // Earlier iterations' text is part of the answer, so only an
+            // entirely empty one counts
+            let answer = format!("{}{}", accumulated_content, iteration_content);
+            match self.check_empty_response(
+                current_stop_reason,
+                &answer,
+                &tool_calls_buffer,
+                &mut empty_retried,
+            ) {
+                Ok(false) => {}
+                Ok(true) => {
+                    let message = "Empty response from model, asking again".to_string();
+                    let _ = stream_tx.send(StreamEvent::Warning { message }).await;
+                    iteration -= 1;
+                    continue;
+                }
+                Err(error) => {
+                    let _ = stream_tx
+                        .send(StreamEvent::Error {
+                            message: error.to_string(),
+                        })
+                        .await;
+                    return Err(error);
+                }
+            }
 
             debug!(
                 "Model stream complete - stop_reason: {:?}, content length: {}, tool_calls: {}",
@@ -276,6 +760,8 @@ impl AgentExecutor {
                 role: MessageRole::Assistant,
                 content: iteration_content.clone(),
                 tool_calls: None,
+                tool_call_id: None,
+                parts: vec![],
             };
 
             if !tool_calls_buffer.is_empty() {
@@ -304,12 +790,37 @@ impl AgentExecutor {
                         execution_time_ms: ctx.metadata.execution_time_ms,
                         input_tokens: ctx.metadata.input_tokens,
                         output_tokens: ctx.metadata.output_tokens,
+                        trace_id: trace_id.to_string(),
                     }).await;
 
                     return Ok(accumulated_content);
                 }
 
                 StopReason::ToolUse => {
+                    if let Some(message) =
+                        self.tool_budget_exhausted(run_tool_calls, tool_calls_buffer.len())
+                    {
+                        warn!("{}", message);
+                        // The calls never run, so keep them out of the history
+                        if let Some(assistant_msg) = ctx.messages.last_mut() {
+                            assistant_msg.tool_calls = None;
+                        }
+                        ctx.metadata.execution_time_ms =
+                            execution_start.elapsed().as_millis() as u64;
+
+                        let _ = stream_tx.send(StreamEvent::Warning { message }).await;
+                        let _ = stream_tx.send(StreamEvent::Done {
+                            content: accumulated_content.clone(),
+                            total_iterations: iteration,
+                            execution_time_ms: ctx.metadata.execution_time_ms,
+                            input_tokens: ctx.metadata.input_tokens,
+                            output_tokens: ctx.metadata.output_tokens,
+                            trace_id: trace_id.to_string(),
+                        }).await;
+
+                        return Ok(accumulated_content);
+                    }
+
                     // Log tool calls for visibility
                     info!("→ TOOL CALLS: {}", tool_calls_buffer.iter()
                         .map(|tc| tc.name.clone())
@@ -324,9 +835,16 @@ impl AgentExecutor {
 
                     // Execute tools and emit events
                     debug!("Executing {} tool calls", tool_calls_buffer.len());
-                    let tool_results = self.execute_tools_streaming(&tool_calls_buffer, &stream_tx).await?;
+                    let tool_results = self
+                        .execute_tools_streaming(
+                            &tool_calls_buffer,
+                            &stream_tx,
+                            &self.tool_context(ctx),
+                        )
+                        .await?;
 
                     ctx.metadata.tool_calls += tool_results.len();
+                    run_tool_calls += tool_results.len();
 
                     // Add tool results to context and log them
                     for (tool_call, result) in tool_calls_buffer.iter().zip(tool_results.iter()) {
@@ -335,7 +853,7 @@ impl AgentExecutor {
                             let result_summary = match &result.data {
                                 serde_json::Value::String(s) => {
                                     if s.len() > 100 {
-                                        format!("{}...", &s[..100])
+                                        format!("{}...", truncate_to_char_boundary(s, 100))
                                     } else {
                                         s.clone()
                                     }
@@ -343,7 +861,7 @@ impl AgentExecutor {
                                 other => {
                                     let s = other.to_string();
                                     if s.len() > 100 {
-                                        format!("{}...", &s[..100])
+                                        format!("{}...", truncate_to_char_boundary(&s, 100))
                                     } else {
                                         s
                                     }
@@ -362,12 +880,7 @@ impl AgentExecutor {
                         };
                         ctx.tool_results.push(agent_result);
 
-                        let tool_msg = aof_core::Message {
-                            role: MessageRole::Tool,
-                            content: serde_json::to_string(&result.data)
-                                .unwrap_or_else(|_| "{}".to_string()),
-                            tool_calls: None,
-                        };
+                        let tool_msg = self.tool_result_message(tool_call, &result.data).await;
                         ctx.messages.push(tool_msg);
                     }
 
@@ -385,6 +898,7 @@ impl AgentExecutor {
                         execution_time_ms: ctx.metadata.execution_time_ms,
                         input_tokens: ctx.metadata.input_tokens,
                         output_tokens: ctx.metadata.output_tokens,
+                        trace_id: trace_id.to_string(),
                     }).await;
 
                     return Ok(accumulated_content);
@@ -400,13 +914,18 @@ impl AgentExecutor {
                         execution_time_ms: ctx.metadata.execution_time_ms,
                         input_tokens: ctx.metadata.input_tokens,
                         output_tokens: ctx.metadata.output_tokens,
+                        trace_id: trace_id.to_string(),
                     }).await;
 
                     return Ok(accumulated_content);
                 }
 
                 StopReason::ContentFilter => {
-                    let error_msg = "Content filter triggered by model".to_string();
+                    // Without a detail, whatever the model streamed is its refusal
+                    let detail = stop_detail.or_else(|| {
+                        Some(iteration_content.trim().to_string()).filter(|c| !c.is_empty())
+                    });
+                    let error_msg = content_filter_message(detail.as_deref());
                     let _ = stream_tx.send(StreamEvent::Error {
                         message: error_msg.clone(),
                     }).await;
@@ -417,6 +936,49 @@ impl AgentExecutor {
         }
     }
 
+    /// Execute with streaming until finished or cancelled
+    ///
+    /// Firing (or dropping) the sender of `cancel_rx` drops the in-flight
+    /// execution, including any pending model or tool call, emits an `Error`
+    /// event and returns an error.
+    pub async fn execute_streaming_cancellable(
+        &self,
+        ctx: &mut AgentContext,
+        stream_tx: mpsc::Sender<StreamEvent>,
+        mut cancel_rx: oneshot::Receiver<()>,
+    ) -> AofResult<String> {
+        tokio::select! {
+            result = self.execute_streaming(ctx, stream_tx.clone()) => {
+                result
+            }
+            _ = &mut cancel_rx => {
+                info!("Streaming execution cancelled: {}", self.config.name);
+                let _ = stream_tx.send(StreamEvent::Error {
+                    message: "Execution cancelled by user".to_string(),
+                }).await;
+                Err(AofError::agent("Execution cancelled".to_string()))
+            }
+        }
+    }
+
+    /// Execute (non-streaming) until finished or cancelled
+    ///
+    /// Like `execute_streaming_cancellable`: firing (or dropping) the sender
+    /// of `cancel_rx` drops the in-flight execution and returns an error.
+    pub async fn execute_cancellable(
+        &self,
+        ctx: &mut AgentContext,
+        mut cancel_rx: oneshot::Receiver<()>,
+    ) -> AofResult<String> {
+        tokio::select! {
+            result = self.execute(ctx) => result,
+            _ = &mut cancel_rx => {
+                info!("Execution cancelled: {}", self.config.name);
+                Err(AofError::agent("Execution cancelled".to_string()))
+            }
+        }
+    }
+
     /// Execute the agent with the given context (non-streaming)
     ///
     /// This runs the main execution loop:
@@ -426,12 +988,33 @@ impl AgentExecutor {
     /// 4. Store conversation turn in memory
     /// 5. Handle response (execute tools if needed)
     /// 6. Repeat until done or max iterations
+    #[instrument(
+        name = "agent_execution",
+        skip_all,
+        fields(
+            agent = %self.config.name,
+            trace_id = field::Empty,
+            input_tokens = field::Empty,
+            output_tokens = field::Empty,
+            success = field::Empty,
+        )
+    )]
     pub async fn execute(&self, context: &mut AgentContext) -> AofResult<String> {
+        let _run = self.activity.start();
+        start_trace(context);
+        let result = self.execute_loop(context).await;
+        finish_trace(&context.metadata, result.is_ok());
+        result
+    }
+
+    async fn execute_loop(&self, context: &mut AgentContext) -> AofResult<String> {
         warn!("=== AGENT EXECUTOR START === name={}", self.config.name);
         let execution_start = Instant::now();
 
-        // Restore conversation history from memory if available
-        if let Some(memory) = &self.memory {
+        // Restore conversation history from memory if available, unless the
+        // context already carries its own (e.g. resumed from a snapshot)
+        let memory = self.history_memory(context);
+        if let Some(memory) = memory.as_ref().filter(|_| context.messages.is_empty()) {
             warn!("[EXECUTOR] Restoring conversation history from memory...");
             self.restore_conversation_history(context, memory).await?;
             warn!("[EXECUTOR] Memory restore complete, messages count: {}", context.messages.len());
@@ -441,10 +1024,17 @@ impl AgentExecutor {
         // This is critical for multi-turn conversations - even if we restored history,
         // we need to add the NEW user query that triggered this execution
         warn!("[EXECUTOR] Adding current user input to context: {:?}", context.input.chars().take(50).collect::<String>());
-        context.add_message(MessageRole::User, context.input.clone());
+        context.add_message_with_parts(
+            MessageRole::User,
+            context.input.clone(),
+            context.input_parts.clone(),
+        );
 
         let mut iteration = 0;
         let max_iterations = self.config.max_iterations;
+        let mut iteration_warned = false;
+        let mut run_tool_calls = 0;
+        let mut empty_retried = false;
         warn!("[EXECUTOR] Starting execution loop, max_iterations={}", max_iterations);
 
         loop {
@@ -466,48 +1056,86 @@ impl AgentExecutor {
                 iteration, max_iterations, self.config.name
             );
 
-            // Build model request
-            warn!("[EXECUTOR] Building model request...");
-            let request = match self.build_model_request(context) {
-                Ok(req) => {
-                    warn!("[EXECUTOR] Model request built: messages={}, tools={}, system={:?}",
-                        req.messages.len(),
-                        req.tools.len(),
-                        req.system.as_ref().map(|s| s.chars().take(30).collect::<String>())
-                    );
-                    req
-                }
-                Err(e) => {
-                    error!("[EXECUTOR] Failed to build model request: {:?}", e);
-                    return Err(e);
+            if !iteration_warned {
+                if let Some(message) = self.iteration_warning(iteration) {
+                    warn!("{}", message);
+                    iteration_warned = true;
                 }
-            };
+            }
 
-            // Call model
-            warn!("[EXECUTOR] Calling model.generate()...");
-            let generate_start = Instant::now();
-            let response = match self.model.generate(&request).await {
-                Ok(resp) => {
-                    warn!("[EXECUTOR] model.generate() SUCCESS in {}ms: stop_reason={:?}, content_len={}, tool_calls={}",
-                        generate_start.elapsed().as_millis(),
-                        resp.stop_reason,
-                        resp.content.len(),
-                        resp.tool_calls.len()
-                    );
-                    resp
-                }
-                Err(e) => {
-                    error!("[EXECUTOR] model.generate() FAILED in {}ms: {:?}",
-                        generate_start.elapsed().as_millis(), e
-                    );
-                    return Err(AofError::agent(format!("Model generation failed: {}", e)));
+            // Call model, shrinking the conversation and calling again while
+            // it overflows the context window
+            let mut overflow_retries = 0;
+            let response = loop {
+                // Build model request
+                warn!("[EXECUTOR] Building model request...");
+                let request = match self.build_model_request(context) {
+                    Ok(req) => {
+                        warn!(
+                            "[EXECUTOR] Model request built: messages={}, tools={}, system={:?}",
+                            req.messages.len(),
+                            req.tools.len(),
+                            req.system
+                                .as_ref()
+                                .map(|s| s.chars().take(30).collect::<String>())
+                        );
+                        req
+                    }
+                    Err(e) => {
+                        error!("[EXECUTOR] Failed to build model request: {:?}", e);
+                        return Err(e);
+                    }
+                };
+
+                // Call model
+                warn!("[EXECUTOR] Calling model.generate()...");
+                let generate_start = Instant::now();
+                let model_span = self.model_call_span();
+                match self
+                    .with_model_retry(|| self.model.generate(&request))
+                    .instrument(model_span.clone())
+                    .await
+                {
+                    Ok(resp) => {
+                        record_model_call(&model_span, Some(&resp.usage));
+                        warn!("[EXECUTOR] model.generate() SUCCESS in {}ms: stop_reason={:?}, content_len={}, tool_calls={}",
+                            generate_start.elapsed().as_millis(),
+                            resp.stop_reason,
+                            resp.content.len(),
+                            resp.tool_calls.len()
+                        );
+                        break resp;
+                    }
+                    Err(e) => {
+                        record_model_call(&model_span, None);
+                        if self
+                            .recover_from_overflow(&e, context, &mut overflow_retries)
+                            .await
+                        {
+                            continue;
+                        }
+                        error!(
+                            "[EXECUTOR] model.generate() FAILED in {}ms: {:?}",
+                            generate_start.elapsed().as_millis(),
+                            e
+                        );
+                        return Err(e);
+                    }
                 }
             };
 
             // Update usage statistics
-            context.metadata.input_tokens += response.usage.input_tokens;
-            context.metadata.output_tokens += response.usage.output_tokens;
-            context.metadata.model = Some(self.model.config().model.clone());
+            self.record_usage(&mut context.metadata, &response.usage);
+
+            if self.check_empty_response(
+                response.stop_reason,
+                &response.content,
+                &response.tool_calls,
+                &mut empty_retried,
+            )? {
+                iteration -= 1;
+                continue;
+            }
 
             debug!(
                 "Model response - stop_reason: {:?}, content length: {}, tool_calls: {}",
@@ -521,6 +1149,8 @@ impl AgentExecutor {
                 role: MessageRole::Assistant,
                 content: response.content.clone(),
                 tool_calls: None,
+                tool_call_id: None,
+                parts: vec![],
             };
 
             if !response.tool_calls.is_empty() {
@@ -530,7 +1160,7 @@ impl AgentExecutor {
             context.messages.push(assistant_msg);
 
             // Store conversation turn in memory after each response
-            if let Some(memory) = &self.memory {
+            if let Some(memory) = &memory {
                 self.store_conversation_turn(context, memory, iteration).await?;
             }
 
@@ -546,6 +1176,19 @@ impl AgentExecutor {
                 }
 
                 StopReason::ToolUse => {
+                    if let Some(message) =
+                        self.tool_budget_exhausted(run_tool_calls, response.tool_calls.len())
+                    {
+                        warn!("{}", message);
+                        // The calls never run, so keep them out of the history
+                        if let Some(assistant_msg) = context.messages.last_mut() {
+                            assistant_msg.tool_calls = None;
+                        }
+                        context.metadata.execution_time_ms =
+                            execution_start.elapsed().as_millis() as u64;
+                        return Ok(response.content);
+                    }
+
                     // Log tool calls for visibility
                     info!("→ TOOL CALLS: {}", response.tool_calls.iter()
                         .map(|tc| tc.name.clone())
@@ -560,9 +1203,12 @@ impl AgentExecutor {
 
                     // Execute tools
                     debug!("Executing {} tool calls", response.tool_calls.len());
-                    let tool_results = self.execute_tools(&response.tool_calls).await?;
+                    let tool_results = self
+                        .execute_tools(&response.tool_calls, &self.tool_context(context))
+                        .await?;
 
                     context.metadata.tool_calls += tool_results.len();
+                    run_tool_calls += tool_results.len();
 
                     // Add tool results to context and log them
                     for (tool_call, result) in response.tool_calls.iter().zip(tool_results.iter()) {
@@ -571,7 +1217,7 @@ impl AgentExecutor {
                             let result_summary = match &result.data {
                                 serde_json::Value::String(s) => {
                                     if s.len() > 100 {
-                                        format!("{}...", &s[..100])
+                                        format!("{}...", truncate_to_char_boundary(s, 100))
                                     } else {
                                         s.clone()
                                     }
@@ -579,7 +1225,7 @@ impl AgentExecutor {
                                 other => {
                                     let s = other.to_string();
                                     if s.len() > 100 {
-                                        format!("{}...", &s[..100])
+                                        format!("{}...", truncate_to_char_boundary(&s, 100))
                                     } else {
                                         s
                                     }
@@ -600,12 +1246,7 @@ impl AgentExecutor {
                         context.tool_results.push(agent_result);
 
                         // Add tool result message to history
-                        let tool_msg = aof_core::Message {
-                            role: MessageRole::Tool,
-                            content: serde_json::to_string(&result.data)
-                                .unwrap_or_else(|_| "{}".to_string()),
-                            tool_calls: None,
-                        };
+                        let tool_msg = self.tool_result_message(tool_call, &result.data).await;
                         context.messages.push(tool_msg);
                     }
 
@@ -627,9 +1268,9 @@ impl AgentExecutor {
 
                 StopReason::ContentFilter => {
                     error!("Content filter triggered");
-                    return Err(AofError::agent(
-                        "Content filter triggered by model".to_string(),
-                    ));
+                    return Err(AofError::agent(content_filter_message(
+                        response.stop_detail.as_deref(),
+                    )));
                 }
             }
         }
@@ -652,6 +1293,8 @@ impl AgentExecutor {
                 },
                 content: m.content.clone(),
                 tool_calls: m.tool_calls.clone(),
+                tool_call_id: m.tool_call_id.clone(),
+                parts: m.parts.clone(),
             })
             .collect();
 
@@ -690,19 +1333,69 @@ impl AgentExecutor {
             tools,
             temperature: Some(self.config.temperature),
             max_tokens: self.config.max_tokens,
+            stop_sequences: self.config.stop_sequences.clone(),
+            seed: self.config.seed,
             stream: false,
+            response_schema: self.config.response_schema.clone(),
+            request_headers: context.request_headers.clone(),
             extra: std::collections::HashMap::new(),
         })
     }
 
     /// Execute a list of tool calls with streaming events
+    ///
+    /// Duplicate calls to cacheable tools run once; each duplicate still gets
+    /// its own completion event and result.
     async fn execute_tools_streaming(
         &self,
         tool_calls: &[ToolCall],
         stream_tx: &mpsc::Sender<StreamEvent>,
+        tool_context: &HashMap<String, serde_json::Value>,
     ) -> AofResult<Vec<ToolResult>> {
-        const MAX_PARALLEL_TOOLS: usize = 10;
+        let (unique, sources) = self.dedup_tool_calls(tool_calls);
+        if unique.len() == tool_calls.len() {
+            return self
+                .run_tool_calls_streaming(tool_calls, stream_tx, tool_context)
+                .await;
+        }
+
+        info!(
+            "Running {} tool calls as {} after collapsing duplicates",
+            tool_calls.len(),
+            unique.len()
+        );
+        let results = self
+            .run_tool_calls_streaming(&unique, stream_tx, tool_context)
+            .await?;
+
+        let mut shared = Vec::with_capacity(tool_calls.len());
+        for (tool_call, &source) in tool_calls.iter().zip(&sources) {
+            let result = results[source].clone();
+            if tool_call.id != unique[source].id {
+                let _ = stream_tx
+                    .send(StreamEvent::ToolCallComplete {
+                        tool_name: tool_call.name.clone(),
+                        tool_id: tool_call.id.clone(),
+                        success: result.success,
+                        execution_time_ms: result.execution_time_ms,
+                        attempts: result.attempts,
+                        error: result.error.clone(),
+                    })
+                    .await;
+            }
+            shared.push(result);
+        }
+
+        Ok(shared)
+    }
 
+    /// Run every call in `tool_calls`, emitting a completion event for each
+    async fn run_tool_calls_streaming(
+        &self,
+        tool_calls: &[ToolCall],
+        stream_tx: &mpsc::Sender<StreamEvent>,
+        tool_context: &HashMap<String, serde_json::Value>,
+    ) -> AofResult<Vec<ToolResult>> {
         let executor = self
             .tool_executor
             .as_ref()
@@ -715,7 +1408,9 @@ impl AgentExecutor {
         // Single tool - execute with retry and emit events
         if tool_calls.len() == 1 {
             debug!("Executing single tool with streaming: {}", tool_calls[0].name);
-            let result = self.execute_tool_with_retry(executor, &tool_calls[0]).await;
+            let result = self
+                .execute_tool_with_retry(executor, &tool_calls[0], tool_context)
+                .await;
 
             // Emit tool complete event
             let _ = stream_tx.send(StreamEvent::ToolCallComplete {
@@ -723,6 +1418,7 @@ impl AgentExecutor {
                 tool_id: tool_calls[0].id.clone(),
                 success: result.success,
                 execution_time_ms: result.execution_time_ms,
+                attempts: result.attempts,
                 error: result.error.clone(),
             }).await;
 
@@ -737,7 +1433,7 @@ impl AgentExecutor {
         );
 
         let parallel_start = Instant::now();
-        let semaphore = Arc::new(Semaphore::new(MAX_PARALLEL_TOOLS));
+        let semaphore = Arc::new(Semaphore::new(MAX_PARALLEL_TOOLS as usize));
         let mut join_set = JoinSet::new();
 
 
@@ -746,23 +1442,30 @@ impl AgentExecutor {
             let tool_call_clone = tool_call.clone();
             let executor_clone = Arc::clone(executor);
             let semaphore_clone = Arc::clone(&semaphore);
+            let weight = self.tool_weight(&tool_call.name);
             let config_name = self.config.name.clone();
+            let error_kb = self.error_kb.clone();
+            let tool_context = tool_context.clone();
 
             join_set.spawn(async move {
-                let _permit = semaphore_clone.acquire().await.unwrap();
+                let _permit = semaphore_clone.acquire_many(weight).await.unwrap();
                 debug!("Executing tool [{}]: {}", idx, tool_call_clone.name);
 
                 let result = Self::execute_tool_with_retry_static(
                     &executor_clone,
                     &tool_call_clone,
-                    &config_name
+                    &config_name,
+                    error_kb.as_ref(),
+                    &tool_context,
                 ).await;
 
                 (idx, tool_call_clone, result)
-            });
+            }.in_current_span());
         }
 
         // Collect results and emit events
+        let fail_fast = self.tool_fail_fast();
+        let mut cancelled = false;
         let mut results = vec![None; tool_calls.len()];
         let mut success_count = 0;
         let mut failure_count = 0;
@@ -774,6 +1477,15 @@ impl AgentExecutor {
                         success_count += 1;
                     } else {
                         failure_count += 1;
+                        if fail_fast && !cancelled {
+                            warn!(
+                                "Tool {} failed, cancelling {} in-flight tools (fail_fast)",
+                                tool_call.name,
+                                join_set.len()
+                            );
+                            join_set.abort_all();
+                            cancelled = true;
+                        }
                     }
 
                     // Emit tool complete event
@@ -782,11 +1494,14 @@ impl AgentExecutor {
                         tool_id: tool_call.id.clone(),
                         success: tool_result.success,
                         execution_time_ms: tool_result.execution_time_ms,
+                        attempts: tool_result.attempts,
                         error: tool_result.error.clone(),
                     }).await;
 
                     results[idx] = Some(tool_result);
                 }
+                // Aborted by fail_fast; reported as cancelled below
+                Err(e) if e.is_cancelled() => {}
                 Err(e) => {
                     error!("Task join error: {}", e);
                     failure_count += 1;
@@ -796,12 +1511,31 @@ impl AgentExecutor {
                             data: serde_json::Value::Null,
                             error: Some(format!("Task failed to join: {}", e)),
                             execution_time_ms: 0,
+                            ..Default::default()
                         });
                     }
                 }
             }
         }
 
+        if cancelled {
+            for (idx, result) in results.iter_mut().enumerate() {
+                if result.is_none() {
+                    let _ = stream_tx
+                        .send(StreamEvent::ToolCallComplete {
+                            tool_name: tool_calls[idx].name.clone(),
+                            tool_id: tool_calls[idx].id.clone(),
+                            success: false,
+                            execution_time_ms: 0,
+                            attempts: 0,
+                            error: Some(TOOL_CANCELLED_ERROR.to_string()),
+                        })
+                        .await;
+                    *result = Some(cancelled_tool_result());
+                }
+            }
+        }
+
         let parallel_duration = parallel_start.elapsed();
         info!(
             "Parallel tool execution completed: {} tools in {}ms ({} success, {} failures)",
@@ -822,6 +1556,7 @@ impl AgentExecutor {
                         data: serde_json::Value::Null,
                         error: Some("Tool execution did not complete".to_string()),
                         execution_time_ms: 0,
+                        ..Default::default()
                     }
                 })
             })
@@ -831,9 +1566,36 @@ impl AgentExecutor {
     }
 
     /// Execute a list of tool calls in parallel with semaphore-based concurrency control
-    async fn execute_tools(&self, tool_calls: &[ToolCall]) -> AofResult<Vec<ToolResult>> {
-        const MAX_PARALLEL_TOOLS: usize = 10;
+    ///
+    /// Duplicate calls to cacheable tools run once and share the result.
+    async fn execute_tools(
+        &self,
+        tool_calls: &[ToolCall],
+        tool_context: &HashMap<String, serde_json::Value>,
+    ) -> AofResult<Vec<ToolResult>> {
+        let (unique, sources) = self.dedup_tool_calls(tool_calls);
+        if unique.len() == tool_calls.len() {
+            return self.run_tool_calls(tool_calls, tool_context).await;
+        }
+
+        info!(
+            "Running {} tool calls as {} after collapsing duplicates",
+            tool_calls.len(),
+            unique.len()
+        );
+        let results = self.run_tool_calls(&unique, tool_context).await?;
+        Ok(sources
+            .into_iter()
+            .map(|idx| results[idx].clone())
+            .collect())
+    }
 
+    /// Run every call in `tool_calls`, in parallel when there are several
+    async fn run_tool_calls(
+        &self,
+        tool_calls: &[ToolCall],
+        tool_context: &HashMap<String, serde_json::Value>,
+    ) -> AofResult<Vec<ToolResult>> {
         let executor = self
             .tool_executor
             .as_ref()
@@ -847,7 +1609,9 @@ impl AgentExecutor {
         // Single tool - execute with retry and resilience
         if tool_calls.len() == 1 {
             debug!("Executing single tool with resilience: {}", tool_calls[0].name);
-            let result = self.execute_tool_with_retry(executor, &tool_calls[0]).await;
+            let result = self
+                .execute_tool_with_retry(executor, &tool_calls[0], tool_context)
+                .await;
             return Ok(vec![result]);
         }
 
@@ -859,7 +1623,7 @@ impl AgentExecutor {
         );
 
         let parallel_start = Instant::now();
-        let semaphore = Arc::new(Semaphore::new(MAX_PARALLEL_TOOLS));
+        let semaphore = Arc::new(Semaphore::new(MAX_PARALLEL_TOOLS as usize));
         let mut join_set = JoinSet::new();
 
         // Spawn tasks for each tool call with resilience
@@ -867,13 +1631,16 @@ impl AgentExecutor {
             let tool_call_clone = tool_call.clone();
             let executor_clone = Arc::clone(executor);
             let semaphore_clone = Arc::clone(&semaphore);
+            let weight = self.tool_weight(&tool_call.name);
 
             // Clone self methods needed for retry logic
             let config_name = self.config.name.clone();
+            let error_kb = self.error_kb.clone();
+            let tool_context = tool_context.clone();
 
             join_set.spawn(async move {
-                // Acquire semaphore permit to limit concurrency
-                let _permit = semaphore_clone.acquire().await.unwrap();
+                // Acquire one permit per unit of tool weight to limit load
+                let _permit = semaphore_clone.acquire_many(weight).await.unwrap();
 
                 debug!("Executing tool [{}] with resilience: {}", idx, tool_call_clone.name);
 
@@ -881,14 +1648,18 @@ impl AgentExecutor {
                 let result = Self::execute_tool_with_retry_static(
                     &executor_clone,
                     &tool_call_clone,
-                    &config_name
+                    &config_name,
+                    error_kb.as_ref(),
+                    &tool_context,
                 ).await;
 
                 (idx, result)
-            });
+            }.in_current_span());
         }
 
         // Collect results while maintaining order
+        let fail_fast = self.tool_fail_fast();
+        let mut cancelled = false;
         let mut results = vec![None; tool_calls.len()];
         let mut success_count = 0;
         let mut failure_count = 0;
@@ -900,9 +1671,20 @@ impl AgentExecutor {
                         success_count += 1;
                     } else {
                         failure_count += 1;
+                        if fail_fast && !cancelled {
+                            warn!(
+                                "Tool {} failed, cancelling {} in-flight tools (fail_fast)",
+                                tool_calls[idx].name,
+                                join_set.len()
+                            );
+                            join_set.abort_all();
+                            cancelled = true;
+                        }
                     }
                     results[idx] = Some(tool_result);
                 }
+                // Aborted by fail_fast; reported as cancelled below
+                Err(e) if e.is_cancelled() => {}
                 Err(e) => {
                     error!("Task join error: {}", e);
                     failure_count += 1;
@@ -914,6 +1696,7 @@ impl AgentExecutor {
                             data: serde_json::Value::Null,
                             error: Some(format!("Task failed to join: {}", e)),
                             execution_time_ms: 0,
+                            ..Default::default()
                         });
                     }
                 }
@@ -936,12 +1719,16 @@ impl AgentExecutor {
             .enumerate()
             .map(|(idx, opt_result)| {
                 opt_result.unwrap_or_else(|| {
+                    if cancelled {
+                        return cancelled_tool_result();
+                    }
                     error!("Tool at index {} did not complete", idx);
                     ToolResult {
                         success: false,
                         data: serde_json::Value::Null,
                         error: Some("Tool execution did not complete".to_string()),
                         execution_time_ms: 0,
+                        ..Default::default()
                     }
                 })
             })
@@ -950,13 +1737,39 @@ impl AgentExecutor {
         Ok(final_results)
     }
 
-    /// Get agent configuration
-    pub fn config(&self) -> &AgentConfig {
-        &self.config
-    }
-
-    /// Get model reference
-    pub fn model(&self) -> &dyn Model {
+    /// Execute the agent and deserialize its final response as JSON
+    ///
+    /// The response is checked against the agent's `response_schema` when one
+    /// is configured. Markdown code fences around the JSON are tolerated.
+    pub async fn execute_structured<T: DeserializeOwned>(
+        &self,
+        context: &mut AgentContext,
+    ) -> AofResult<T> {
+        let content = self.execute(context).await?;
+        let json = strip_code_fence(&content);
+
+        let value: serde_json::Value = serde_json::from_str(json).map_err(|e| {
+            AofError::agent(format!("Model response is not valid JSON: {}", e))
+        })?;
+
+        if let Some(schema) = &self.config.response_schema {
+            check_schema(&value, schema, "$").map_err(|e| {
+                AofError::agent(format!("Model response does not match schema: {}", e))
+            })?;
+        }
+
+        serde_json::from_value(value).map_err(|e| {
+            AofError::agent(format!("Failed to deserialize model response: {}", e))
+        })
+    }
+
+    /// Get agent configuration
+    pub fn config(&self) -> &AgentConfig {
+        &self.config
+    }
+
+    /// Get model reference
+    pub fn model(&self) -> &dyn Model {
         self.model.as_ref()
     }
 
@@ -965,20 +1778,201 @@ impl AgentExecutor {
         self.tool_executor.as_ref()
     }
 
-    /// Get memory reference
+    /// Get this session's memory
     pub fn memory(&self) -> Option<&Arc<SimpleMemory>> {
         self.memory.as_ref()
     }
 
+    /// Memory shared across sessions, as passed to `new`
+    pub(crate) fn base_memory(&self) -> Option<&Arc<SimpleMemory>> {
+        self.base_memory.as_ref()
+    }
+
+    /// Id of this executor's memory session
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    /// Whether an execution is in flight
+    pub fn is_running(&self) -> bool {
+        self.activity
+            .running
+            .load(std::sync::atomic::Ordering::SeqCst)
+            > 0
+    }
+
+    /// When an execution last started or finished (creation time before the first)
+    pub fn last_used(&self) -> Instant {
+        *self.activity.last_used.lock()
+    }
+
+    /// Delete this agent's memory, across all of its sessions
+    pub async fn clear_memory(&self) -> AofResult<()> {
+        if let Some(memory) = &self.base_memory {
+            memory
+                .namespaced(&format!("agent:{}", self.config.name))
+                .clear()
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Tools the agent was loaded without because their source was unreachable
+    pub fn unavailable_tools(&self) -> &[UnavailableTools] {
+        &self.unavailable_tools
+    }
+
+    /// Health of the agent's model, tools and memory
+    ///
+    /// The model's health is that of its last call, with an outage going
+    /// back to unknown once `with_model_health_ttl` passes without calls;
+    /// tools report their own cached state and memory is probed with one
+    /// read. Tools left out at load time make the tools unhealthy.
+    pub async fn health(&self) -> AgentHealth {
+        let tools = match self.unavailable_tools.first() {
+            Some(unavailable) => Some(ComponentHealth::unhealthy(format!(
+                "{} tools unavailable: {}",
+                unavailable.source, unavailable.error
+            ))),
+            None => self
+                .tool_executor
+                .as_ref()
+                .map(|tools| match tools.health_check() {
+                    Ok(()) => ComponentHealth::healthy(),
+                    Err(e) => ComponentHealth::unhealthy(e.to_string()),
+                }),
+        };
+        let memory = match &self.base_memory {
+            Some(memory) => Some(
+                match memory.retrieve::<serde_json::Value>(MEMORY_PROBE_KEY).await {
+                    Ok(_) => ComponentHealth::healthy(),
+                    Err(e) => ComponentHealth::unhealthy(e.to_string()),
+                },
+            ),
+            None => None,
+        };
+
+        let model = match &*self.model_health.lock() {
+            (health, since) if health.is_unhealthy() && since.elapsed() > self.model_health_ttl => {
+                ComponentHealth::unknown()
+            }
+            (health, _) => health.clone(),
+        };
+
+        AgentHealth {
+            name: self.config.name.clone(),
+            model,
+            tools,
+            memory,
+            unavailable_tools: self.unavailable_tools.clone(),
+        }
+    }
+
+    /// Sessions with a stored conversation, most recently updated first
+    pub async fn list_sessions(&self) -> AofResult<Vec<SessionInfo>> {
+        let Some(memory) = &self.base_memory else {
+            return Ok(Vec::new());
+        };
+        let memory = memory.namespaced(&format!("agent:{}", self.config.name));
+        let keys = memory.list_keys().await?;
+
+        // Keys are `{session}:conversation` and `{session}:turn:{iteration}`;
+        // session ids may themselves contain colons
+        let conversation_suffix = format!(":{}", CONVERSATION_KEY);
+        let turn_marker = format!(":{}", TURN_KEY_PREFIX);
+        let conversation_keys: Vec<String> = keys
+            .iter()
+            .filter(|key| key.ends_with(&conversation_suffix))
+            .cloned()
+            .collect();
+        let turn_keys: Vec<String> = keys
+            .iter()
+            .filter(|key| {
+                key.rsplit_once(&turn_marker)
+                    .is_some_and(|(_, iteration)| iteration.parse::<usize>().is_ok())
+            })
+            .cloned()
+            .collect();
+
+        let mut last_updated: HashMap<&str, u64> = HashMap::new();
+        let turns = memory
+            .retrieve_many::<serde_json::Value>(&turn_keys)
+            .await?;
+        for (key, turn) in turn_keys.iter().zip(turns) {
+            let (session_id, _) = key.rsplit_once(&turn_marker).unwrap();
+            let timestamp = turn
+                .and_then(|turn| turn["timestamp"].as_u64())
+                .unwrap_or(0);
+            let latest = last_updated.entry(session_id).or_default();
+            *latest = (*latest).max(timestamp);
+        }
+
+        let conversations = memory
+            .retrieve_many::<Vec<aof_core::Message>>(&conversation_keys)
+            .await?;
+        let mut sessions: Vec<SessionInfo> = conversation_keys
+            .iter()
+            .zip(conversations)
+            .filter_map(|(key, messages)| {
+                let session_id = key.strip_suffix(&conversation_suffix)?;
+                Some(SessionInfo {
+                    session_id: session_id.to_string(),
+                    message_count: messages?.len(),
+                    last_updated: last_updated.get(session_id).copied().unwrap_or(0),
+                })
+            })
+            .collect();
+        sessions.sort_by(|a, b| {
+            b.last_updated
+                .cmp(&a.last_updated)
+                .then_with(|| a.session_id.cmp(&b.session_id))
+        });
+        Ok(sessions)
+    }
+
+    /// `ToolInput` context for the tool calls of `context`'s run
+    ///
+    /// Tells the delegation tool how deep the run is and which session and
+    /// trace it belongs to.
+    fn tool_context(&self, context: &AgentContext) -> HashMap<String, serde_json::Value> {
+        let session_id = context.session_id.as_deref().unwrap_or(&self.session_id);
+        let mut tool_context = HashMap::from([
+            (
+                DEPTH_CONTEXT_KEY.to_string(),
+                context.delegation_depth.into(),
+            ),
+            (SESSION_CONTEXT_KEY.to_string(), session_id.into()),
+        ]);
+        if let Some(trace_id) = &context.metadata.trace_id {
+            tool_context.insert(TRACE_CONTEXT_KEY.to_string(), trace_id.as_str().into());
+        }
+        tool_context
+    }
+
+    /// Memory holding the conversation history of `context`'s session
+    ///
+    /// Runs naming a session (`AgentContext::session_id`) use that session's
+    /// namespace; the others continue this executor's session.
+    fn history_memory(&self, context: &AgentContext) -> Option<Arc<SimpleMemory>> {
+        match &context.session_id {
+            Some(session_id) => self
+                .base_memory
+                .as_ref()
+                .map(|memory| session_memory(memory, &self.config.name, session_id)),
+            None => self.memory.clone(),
+        }
+    }
+
     /// Restore conversation history from memory
     async fn restore_conversation_history(
         &self,
         context: &mut AgentContext,
         memory: &Arc<SimpleMemory>,
     ) -> AofResult<()> {
-        let conversation_key = format!("agent:{}:conversation", self.config.name);
-
-        if let Some(history) = memory.retrieve::<Vec<aof_core::Message>>(&conversation_key).await? {
+        if let Some(history) = memory
+            .retrieve::<Vec<aof_core::Message>>(CONVERSATION_KEY)
+            .await?
+        {
             debug!(
                 "Restored {} messages from memory for agent: {}",
                 history.len(),
@@ -986,7 +1980,7 @@ impl AgentExecutor {
             );
 
             // Prune history if it exceeds context window
-            let pruned_history = self.prune_conversation_history(history);
+            let pruned_history = self.prune_conversation_history(history).await;
             context.messages = pruned_history;
         } else {
             debug!("No conversation history found for agent: {}", self.config.name);
@@ -1002,15 +1996,12 @@ impl AgentExecutor {
         memory: &Arc<SimpleMemory>,
         iteration: usize,
     ) -> AofResult<()> {
-        let conversation_key = format!("agent:{}:conversation", self.config.name);
-        let turn_key = format!("agent:{}:turn:{}", self.config.name, iteration);
+        let turn_key = format!("{}{}", TURN_KEY_PREFIX, iteration);
 
         // Store full conversation history
         let conversation_value = serde_json::to_value(&context.messages)
             .map_err(|e| AofError::memory(format!("Failed to serialize messages: {}", e)))?;
 
-        memory.store(&conversation_key, conversation_value).await?;
-
         // Store individual turn with metadata for semantic search
         let turn_value = serde_json::json!({
             "iteration": iteration,
@@ -1024,7 +2015,12 @@ impl AgentExecutor {
                 .as_secs()
         });
 
-        memory.store(&turn_key, turn_value).await?;
+        memory
+            .store_many(vec![
+                (CONVERSATION_KEY.to_string(), conversation_value),
+                (turn_key, turn_value),
+            ])
+            .await?;
 
         debug!(
             "Stored conversation turn {} for agent: {}",
@@ -1034,63 +2030,208 @@ impl AgentExecutor {
         Ok(())
     }
 
-    /// Prune conversation history to fit context window
-    fn prune_conversation_history(&self, mut history: Vec<aof_core::Message>) -> Vec<aof_core::Message> {
-        const MAX_MESSAGES: usize = 100;
+    /// Prune conversation history to fit the context window
+    ///
+    /// The window is the agent's `AgentConfig::context_window`, minus
+    /// `max_tokens` reserved for the reply.
+    /// Dropped turns are summarized when the agent has a history summarizer
+    /// or its `on_context_overflow` policy is `summarize`.
+    async fn prune_conversation_history(
+        &self,
+        history: Vec<aof_core::Message>,
+    ) -> Vec<aof_core::Message> {
+        let context_window = self.config.context_window();
+        let reserve = self.config.max_tokens.unwrap_or(DEFAULT_RESPONSE_RESERVE);
+
+        let summarize =
+            self.summarizer.is_some() || self.context_overflow == ContextOverflowPolicy::Summarize;
+
+        self.prune_history(history, context_window.saturating_sub(reserve), summarize)
+            .await
+    }
+
+    /// Drop the oldest messages of `history` until it fits `budget` tokens
+    ///
+    /// The system prompt, system messages and the newest message are always
+    /// kept, and the kept messages never start with an orphaned tool result.
+    /// With `summarize`, dropped messages are replaced with a summary (see
+    /// `summarize_history`).
+    async fn prune_history(
+        &self,
+        history: Vec<aof_core::Message>,
+        budget: usize,
+        summarize: bool,
+    ) -> Vec<aof_core::Message> {
+        let count = |m: &aof_core::Message| self.model.count_tokens(&m.content);
+
+        let (system_messages, mut messages): (Vec<_>, Vec<_>) = history
+            .into_iter()
+            .partition(|m| m.role == MessageRole::System);
+        let used = self
+            .config
+            .system_prompt
+            .as_deref()
+            .map(|p| self.model.count_tokens(p))
+            .unwrap_or(0)
+            + system_messages.iter().map(count).sum::<usize>();
+        let mut remaining: usize = messages.iter().map(count).sum();
+
+        let mut dropped = 0;
+        while dropped + 1 < messages.len()
+            && (used + remaining > budget
+                || (dropped > 0 && messages[dropped].role == MessageRole::Tool))
+        {
+            remaining -= count(&messages[dropped]);
+            dropped += 1;
+        }
 
-        if history.len() > MAX_MESSAGES {
+        let mut pruned = system_messages;
+        if dropped > 0 {
             warn!(
-                "Pruning conversation history from {} to {} messages for agent: {}",
-                history.len(),
-                MAX_MESSAGES,
+                "Pruning {} of {} messages to fit {} tokens for agent: {}",
+                dropped,
+                messages.len(),
+                budget,
                 self.config.name
             );
 
-            // Keep system messages and most recent messages
-            let system_messages: Vec<_> = history
-                .iter()
-                .filter(|m| m.role == MessageRole::System)
-                .cloned()
-                .collect();
+            let recent = messages.split_off(dropped);
+            if summarize {
+                if let Some(summary) = self.summarize_history(&messages).await {
+                    debug!("Summarized {} pruned messages", dropped);
+                    pruned.push(summary_message(&summary));
+                }
+            }
+            messages = recent;
+        }
 
-            // Take most recent non-system messages
-            let recent_messages: Vec<_> = history
-                .into_iter()
-                .filter(|m| m.role != MessageRole::System)
-                .rev()
-                .take(MAX_MESSAGES - system_messages.len())
-                .collect::<Vec<_>>()
-                .into_iter()
-                .rev()
-                .collect();
+        pruned.extend(messages);
+        pruned
+    }
+
+    /// Summary of the pruned `messages`
+    ///
+    /// Uses the history summarizer if one is set, otherwise asks the agent's
+    /// model. A failed model call is logged and yields no summary, so the
+    /// messages are dropped as under `ContextOverflowPolicy::Prune`.
+    async fn summarize_history(&self, messages: &[aof_core::Message]) -> Option<String> {
+        if let Some(summarizer) = &self.summarizer {
+            return Some(summarizer(messages));
+        }
 
-            // Combine system + recent
-            history = system_messages;
-            history.extend(recent_messages);
+        let transcript = messages
+            .iter()
+            .map(|m| format!("{:?}: {}", m.role, m.content))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let request = ModelRequest {
+            messages: vec![RequestMessage {
+                role: aof_core::model::MessageRole::User,
+                content: transcript,
+                ..Default::default()
+            }],
+            system: Some(SUMMARY_PROMPT.to_string()),
+            temperature: Some(0.0),
+            max_tokens: self.config.max_tokens,
+            ..Default::default()
+        };
+
+        let model_span = self.model_call_span();
+        match self
+            .model
+            .generate(&request)
+            .instrument(model_span.clone())
+            .await
+        {
+            Ok(response) if !response.content.trim().is_empty() => {
+                record_model_call(&model_span, Some(&response.usage));
+                Some(response.content)
+            }
+            Ok(response) => {
+                record_model_call(&model_span, Some(&response.usage));
+                warn!(
+                    "Model returned an empty summary for agent: {}",
+                    self.config.name
+                );
+                None
+            }
+            Err(e) => {
+                record_model_call(&model_span, None);
+                warn!(
+                    "Failed to summarize pruned messages for agent {}: {}",
+                    self.config.name, e
+                );
+                None
+            }
+        }
+    }
+
+    /// Shrink the conversation if `error` is a context overflow the agent's
+    /// `on_context_overflow` policy recovers from
+    ///
+    /// Returns whether the model should be called again, at most
+    /// `MAX_OVERFLOW_RETRIES` times per model call (counted in `retries`).
+    async fn recover_from_overflow(
+        &self,
+        error: &AofError,
+        context: &mut AgentContext,
+        retries: &mut usize,
+    ) -> bool {
+        if !matches!(error, AofError::ContextOverflow(_))
+            || self.context_overflow == ContextOverflowPolicy::Error
+            || *retries >= MAX_OVERFLOW_RETRIES
+        {
+            return false;
         }
+        *retries += 1;
+
+        warn!("Context window exceeded, shrinking conversation: {}", error);
+        self.shrink_overflowing_context(context).await
+    }
 
-        history
+    /// Shrink the conversation after the model rejected it as too long
+    ///
+    /// Prunes it to half its estimated tokens, summarizing the dropped
+    /// messages under `ContextOverflowPolicy::Summarize`. Returns false when
+    /// that leaves no fewer messages, e.g. when only a summary could go.
+    async fn shrink_overflowing_context(&self, context: &mut AgentContext) -> bool {
+        let before = context.messages.len();
+        let tokens = self
+            .config
+            .system_prompt
+            .as_deref()
+            .map(|p| self.model.count_tokens(p))
+            .unwrap_or(0)
+            + context
+                .messages
+                .iter()
+                .map(|m| self.model.count_tokens(&m.content))
+                .sum::<usize>();
+
+        context.messages = self
+            .prune_history(
+                std::mem::take(&mut context.messages),
+                tokens / 2,
+                self.context_overflow == ContextOverflowPolicy::Summarize,
+            )
+            .await;
+        context.messages.len() < before
     }
 
     /// Cleanup expired memory entries
     pub async fn cleanup_expired_memory(&self) -> AofResult<()> {
         if let Some(memory) = &self.memory {
-            let prefix = format!("agent:{}:", self.config.name);
-
-            // List all keys for this agent
+            // The session's memory view only lists this session's keys
             let keys = memory.list_keys().await?;
-            let agent_keys: Vec<_> = keys.iter()
-                .filter(|k| k.starts_with(&prefix))
-                .collect();
 
             debug!(
                 "Checking {} memory entries for expiry for agent: {}",
-                agent_keys.len(),
+                keys.len(),
                 self.config.name
             );
 
             // Memory backend handles lazy cleanup on retrieve
-            for key in agent_keys {
+            for key in &keys {
                 let _: Option<serde_json::Value> = memory.retrieve(key).await?;
             }
         }
@@ -1104,18 +2245,19 @@ impl AgentExecutor {
         query: &str,
     ) -> AofResult<Vec<aof_core::MemoryEntry>> {
         if let Some(memory) = &self.memory {
-            let prefix = format!("agent:{}:turn:", self.config.name);
-
-            let keys = memory.list_keys().await?;
-            let mut entries = Vec::new();
+            let keys: Vec<String> = memory
+                .list_keys()
+                .await?
+                .into_iter()
+                .filter(|key| key.starts_with(TURN_KEY_PREFIX))
+                .collect();
+            let values = memory.retrieve_many::<serde_json::Value>(&keys).await?;
 
-            for key in keys {
-                if key.starts_with(&prefix) {
-                    if let Some(value) = memory.retrieve::<serde_json::Value>(&key).await? {
-                        entries.push(aof_core::MemoryEntry::new(key, value));
-                    }
-                }
-            }
+            let entries: Vec<_> = keys
+                .into_iter()
+                .zip(values)
+                .filter_map(|(key, value)| Some(aof_core::MemoryEntry::new(key, value?)))
+                .collect();
 
             debug!(
                 "Found {} memory entries matching query '{}' for agent: {}",
@@ -1135,23 +2277,84 @@ impl AgentExecutor {
         &self,
         executor: &Arc<dyn ToolExecutor>,
         tool_call: &ToolCall,
+        tool_context: &HashMap<String, serde_json::Value>,
     ) -> ToolResult {
-        Self::execute_tool_with_retry_static(executor, tool_call, &self.config.name).await
+        Self::execute_tool_with_retry_static(
+            executor,
+            tool_call,
+            &self.config.name,
+            self.error_kb.as_ref(),
+            tool_context,
+        )
+        .await
     }
 
     /// Execute a single tool call with timeout, retry, and validation (static method for parallel tasks)
+    ///
+    /// Runs in a `tool` span recording the outcome and number of attempts.
     async fn execute_tool_with_retry_static(
         executor: &Arc<dyn ToolExecutor>,
         tool_call: &ToolCall,
         agent_name: &str,
+        error_kb: Option<&Arc<ErrorKnowledgeBase>>,
+        tool_context: &HashMap<String, serde_json::Value>,
+    ) -> ToolResult {
+        let span = info_span!(
+            "tool",
+            agent = %agent_name,
+            tool = %tool_call.name,
+            tool_id = %tool_call.id,
+            success = field::Empty,
+            attempts = field::Empty,
+        );
+        let result =
+            Self::run_tool_with_retry(executor, tool_call, agent_name, error_kb, tool_context)
+                .instrument(span.clone())
+                .await;
+
+        span.record("success", result.success);
+        span.record("attempts", result.attempts);
+        result
+    }
+
+    async fn run_tool_with_retry(
+        executor: &Arc<dyn ToolExecutor>,
+        tool_call: &ToolCall,
+        agent_name: &str,
+        error_kb: Option<&Arc<ErrorKnowledgeBase>>,
+        tool_context: &HashMap<String, serde_json::Value>,
     ) -> ToolResult {
         const MAX_RETRIES: u32 = 3;
-        const INITIAL_BACKOFF_MS: u64 = 1000; // 1 second
         const TIMEOUT_SECS: u64 = 30; // 30 seconds per attempt
 
         let mut attempt = 0;
         let tool_start = Instant::now();
 
+        // Malformed arguments never reach the tool; the error tells the model
+        // what to fix on its next turn
+        if let Err(e) = Self::validate_tool_arguments(executor, tool_call) {
+            let error_msg = e.to_string();
+            warn!(
+                "[{}] Tool {} not run: {}",
+                agent_name, tool_call.name, error_msg
+            );
+            Self::record_tool_error(
+                error_kb,
+                agent_name,
+                &tool_call.name,
+                &error_msg,
+                Self::categorize_error(&e),
+                1,
+            );
+            return ToolResult {
+                success: false,
+                data: serde_json::Value::Null,
+                error: Some(error_msg),
+                execution_time_ms: tool_start.elapsed().as_millis() as u64,
+                attempts: 1,
+            };
+        }
+
         while attempt < MAX_RETRIES {
             attempt += 1;
             let attempt_start = Instant::now();
@@ -1162,7 +2365,7 @@ impl AgentExecutor {
             );
 
             // Execute with timeout
-            let input = ToolInput::new(tool_call.arguments.clone());
+            let input = tool_input(tool_call, tool_context);
             let timeout_duration = Duration::from_secs(TIMEOUT_SECS);
 
             let result = tokio::time::timeout(
@@ -1184,7 +2387,7 @@ impl AgentExecutor {
 
                     // Timeout is retryable - check if we should retry
                     if attempt < MAX_RETRIES {
-                        let backoff = INITIAL_BACKOFF_MS * (2_u64.pow(attempt - 1));
+                        let backoff = RETRY_INITIAL_BACKOFF_MS * (2_u64.pow(attempt - 1));
                         info!(
                             "[{}] Retrying tool {} after {}ms backoff",
                             agent_name, tool_call.name, backoff
@@ -1194,11 +2397,20 @@ impl AgentExecutor {
                     }
 
                     // Max retries exceeded
+                    Self::record_tool_error(
+                        error_kb,
+                        agent_name,
+                        &tool_call.name,
+                        &format!("Tool {} timed out after {}s", tool_call.name, TIMEOUT_SECS),
+                        ErrorCategory::Retryable,
+                        attempt,
+                    );
                     return ToolResult {
                         success: false,
                         data: serde_json::Value::Null,
                         error: Some(error_msg),
                         execution_time_ms: tool_start.elapsed().as_millis() as u64,
+                        attempts: attempt,
                     };
                 }
 
@@ -1214,16 +2426,26 @@ impl AgentExecutor {
                                 );
 
                                 // Validation errors are terminal (not retryable)
+                                Self::record_tool_error(
+                                    error_kb,
+                                    agent_name,
+                                    &tool_call.name,
+                                    &validation_error,
+                                    ErrorCategory::Terminal,
+                                    attempt,
+                                );
                                 return ToolResult {
                                     success: false,
                                     data: serde_json::Value::Null,
                                     error: Some(format!("Validation failed: {}", validation_error)),
                                     execution_time_ms: attempt_duration.as_millis() as u64,
+                                    attempts: attempt,
                                 };
                             }
 
                             // Update execution time and log metrics
                             result.execution_time_ms = attempt_duration.as_millis() as u64;
+                            result.attempts = attempt;
                             info!(
                                 "[{}] Tool {} succeeded on attempt {} in {}ms",
                                 agent_name, tool_call.name, attempt, result.execution_time_ms
@@ -1248,12 +2470,12 @@ impl AgentExecutor {
                             match error_category {
                                 ErrorCategory::Retryable => {
                                     if attempt < MAX_RETRIES {
-                                        let backoff = INITIAL_BACKOFF_MS * (2_u64.pow(attempt - 1));
+                                        let backoff = Self::retry_backoff(&e, attempt);
                                         info!(
                                             "[{}] Retrying tool {} after {}ms backoff (retryable error: {})",
-                                            agent_name, tool_call.name, backoff, error_msg
+                                            agent_name, tool_call.name, backoff.as_millis(), error_msg
                                         );
-                                        tokio::time::sleep(Duration::from_millis(backoff)).await;
+                                        tokio::time::sleep(backoff).await;
                                         continue;
                                     }
                                 }
@@ -1267,11 +2489,20 @@ impl AgentExecutor {
                             }
 
                             // Return error result (max retries or terminal error)
+                            Self::record_tool_error(
+                                error_kb,
+                                agent_name,
+                                &tool_call.name,
+                                &error_msg,
+                                error_category,
+                                attempt,
+                            );
                             return ToolResult {
                                 success: false,
                                 data: serde_json::Value::Null,
                                 error: Some(error_msg),
                                 execution_time_ms: attempt_duration.as_millis() as u64,
+                                attempts: attempt,
                             };
                         }
                     }
@@ -1288,17 +2519,149 @@ impl AgentExecutor {
                 tool_call.name, MAX_RETRIES
             )),
             execution_time_ms: tool_start.elapsed().as_millis() as u64,
+            attempts: MAX_RETRIES,
+        }
+    }
+
+    /// Span covering one model call, from the request to its last chunk
+    fn model_call_span(&self) -> Span {
+        info_span!(
+            "model_call",
+            agent = %self.config.name,
+            model = %self.config.model,
+            input_tokens = field::Empty,
+            output_tokens = field::Empty,
+            success = field::Empty,
+        )
+    }
+
+    /// Run a model call, retrying transient failures
+    ///
+    /// Rate limits wait exactly the server's `Retry-After`; other transient
+    /// errors back off exponentially. Attempts and the initial backoff come
+    /// from the agent's `model_retry_policy`.
+    async fn with_model_retry<T, F, Fut>(&self, mut call: F) -> AofResult<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = AofResult<T>>,
+    {
+        let policy = self.model_retry_policy();
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            match call().await {
+                Err(e)
+                    if attempt < policy.max_attempts
+                        && Self::categorize_error(&e) == ErrorCategory::Retryable =>
+                {
+                    let backoff = policy.backoff(&e, attempt);
+                    warn!(
+                        "[{}] Model call failed (attempt {}/{}): {}. Retrying in {}ms",
+                        self.config.name,
+                        attempt,
+                        policy.max_attempts,
+                        e,
+                        backoff.as_millis()
+                    );
+                    tokio::time::sleep(backoff).await;
+                }
+                Err(e) => {
+                    self.record_model_error(&e, attempt);
+                    if Self::indicates_outage(&e) {
+                        *self.model_health.lock() =
+                            (ComponentHealth::unhealthy(e.to_string()), Instant::now());
+                    }
+                    return Err(e);
+                }
+                result => {
+                    *self.model_health.lock() = (ComponentHealth::healthy(), Instant::now());
+                    return result;
+                }
+            }
+        }
+    }
+
+    /// Record a failed model call into the error knowledge base, if any
+    fn record_model_error(&self, error: &AofError, attempts: u32) {
+        let Some(error_kb) = &self.error_kb else {
+            return;
+        };
+        let category = Self::categorize_error(error);
+        let record = ErrorRecord::new(
+            "Model",
+            &error.to_string(),
+            &format!(
+                "agent={} model={} category={} attempts={}",
+                self.config.name,
+                self.model.config().model,
+                category.as_str(),
+                attempts
+            ),
+        )
+        .with_tag(&format!("agent:{}", self.config.name))
+        .with_tag(&format!("model:{}", self.model.config().model))
+        .with_tag(category.as_str());
+        error_kb.record(record);
+    }
+
+    /// Record a failed tool call into the error knowledge base, if any
+    fn record_tool_error(
+        error_kb: Option<&Arc<ErrorKnowledgeBase>>,
+        agent_name: &str,
+        tool_name: &str,
+        message: &str,
+        category: ErrorCategory,
+        attempts: u32,
+    ) {
+        let Some(error_kb) = error_kb else {
+            return;
+        };
+        let record = ErrorRecord::new(
+            "Tool",
+            message,
+            &format!(
+                "agent={} tool={} category={} attempts={}",
+                agent_name,
+                tool_name,
+                category.as_str(),
+                attempts
+            ),
+        )
+        .with_tag(&format!("agent:{}", agent_name))
+        .with_tag(&format!("tool:{}", tool_name))
+        .with_tag(category.as_str());
+        error_kb.record(record);
+    }
+
+    /// Delay before retry `attempt` (1-based) after `error`
+    fn retry_backoff(error: &AofError, attempt: u32) -> Duration {
+        ModelRetryPolicy::default().backoff(error, attempt)
+    }
+
+    /// Whether a failed model call suggests the provider is down
+    ///
+    /// A rate limit means it is up but busy, and errors about the request
+    /// itself say nothing about it.
+    fn indicates_outage(error: &AofError) -> bool {
+        match error {
+            AofError::RateLimited { .. } | AofError::Http { status: 429, .. } => false,
+            e => Self::categorize_error(e) == ErrorCategory::Retryable || e.is_transient(),
         }
     }
 
     /// Categorize errors as retryable or terminal
     fn categorize_error(error: &AofError) -> ErrorCategory {
         match error {
-            // Network-related errors are retryable
-            AofError::Timeout(_) => ErrorCategory::Retryable,
-            AofError::Io(_) => ErrorCategory::Retryable,
+            // Don't stall the run for long server-requested waits
+            AofError::RateLimited {
+                retry_after: Some(retry_after),
+            } if *retry_after > MAX_RETRY_AFTER => ErrorCategory::Terminal,
+
+            // Rate limits, timeouts, I/O and HTTP 408/429/5xx are retryable
+            e if e.is_transient() => ErrorCategory::Retryable,
+            AofError::Http { .. } => ErrorCategory::Terminal,
 
-            // Model and MCP errors might be transient
+            // Unstructured model and MCP errors might be transient
             AofError::Model(msg) if msg.contains("timeout") || msg.contains("network") => {
                 ErrorCategory::Retryable
             }
@@ -1321,6 +2684,29 @@ impl AgentExecutor {
         }
     }
 
+    /// Check a tool call's arguments against the tool's declared input schema
+    ///
+    /// Tools without a schema, and tools the executor doesn't list, take any
+    /// arguments.
+    fn validate_tool_arguments(
+        executor: &Arc<dyn ToolExecutor>,
+        tool_call: &ToolCall,
+    ) -> AofResult<()> {
+        let Some(definition) = executor
+            .list_tools()
+            .into_iter()
+            .find(|tool| tool.name == tool_call.name)
+        else {
+            return Ok(());
+        };
+        if definition.parameters.is_null() {
+            return Ok(());
+        }
+
+        check_schema(&tool_call.arguments, &definition.parameters, "$")
+            .map_err(|e| AofError::tool(format!("argument validation failed: {}", e)))
+    }
+
     /// Validate tool result data
     fn validate_tool_result(result: &ToolResult) -> Result<(), String> {
         // Check if error occurred but success flag is true
@@ -1379,9 +2765,205 @@ impl AgentExecutor {
     }
 }
 
+/// Error reported when the model stops on its content filter
+fn content_filter_message(detail: Option<&str>) -> String {
+    match detail {
+        Some(detail) => format!("Content filter triggered by model: {}", detail),
+        None => "Content filter triggered by model".to_string(),
+    }
+}
+
+/// Whether the model ended its turn without an answer or tool calls
+fn is_empty_answer(stop_reason: StopReason, content: &str, tool_calls: &[ToolCall]) -> bool {
+    stop_reason == StopReason::EndTurn && content.trim().is_empty() && tool_calls.is_empty()
+}
+
+/// View of `memory` holding one session of one agent
+fn session_memory(memory: &SimpleMemory, agent_name: &str, session_id: &str) -> Arc<SimpleMemory> {
+    Arc::new(memory.namespaced(&format!("agent:{}:{}", agent_name, session_id)))
+}
+
+/// Executions in flight and the time of the last start or finish
+struct Activity {
+    running: std::sync::atomic::AtomicUsize,
+    last_used: parking_lot::Mutex<Instant>,
+}
+
+impl Activity {
+    fn new() -> Self {
+        Self {
+            running: std::sync::atomic::AtomicUsize::new(0),
+            last_used: parking_lot::Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Mark an execution in flight until the returned guard is dropped
+    fn start(&self) -> RunGuard<'_> {
+        self.running
+            .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        *self.last_used.lock() = Instant::now();
+        RunGuard(self)
+    }
+}
+
+/// Execution in flight, counted in its agent's `Activity`
+struct RunGuard<'a>(&'a Activity);
+
+impl Drop for RunGuard<'_> {
+    fn drop(&mut self) {
+        *self.0.last_used.lock() = Instant::now();
+        self.0
+            .running
+            .fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+/// History message standing in for summarized earlier turns
+fn summary_message(summary: &str) -> aof_core::Message {
+    aof_core::Message {
+        role: MessageRole::User,
+        content: format!("[Summary of earlier conversation]\n{}", summary),
+        tool_calls: None,
+        tool_call_id: None,
+        parts: vec![],
+    }
+}
+
+/// Input for a tool call, carrying the calling run's `tool_context`
+fn tool_input(
+    tool_call: &ToolCall,
+    tool_context: &HashMap<String, serde_json::Value>,
+) -> ToolInput {
+    ToolInput::with_context(tool_call.arguments.clone(), tool_context.clone())
+}
+
+/// Give a run a trace id, recorded on the current `agent_execution` span
+///
+/// Delegated runs keep the id their caller derived for them, so a trace can
+/// be followed across agents; other runs get a fresh one.
+fn start_trace(context: &mut AgentContext) -> String {
+    let trace_id = match &context.metadata.trace_id {
+        Some(trace_id) if context.delegation_depth > 0 => trace_id.clone(),
+        _ => uuid::Uuid::new_v4().to_string(),
+    };
+    Span::current().record("trace_id", trace_id.as_str());
+    context.metadata.trace_id = Some(trace_id.clone());
+    trace_id
+}
+
+/// Record a finished run's token usage and outcome on its `agent_execution` span
+fn finish_trace(metadata: &ExecutionMetadata, success: bool) {
+    let span = Span::current();
+    span.record("input_tokens", metadata.input_tokens);
+    span.record("output_tokens", metadata.output_tokens);
+    span.record("success", success);
+}
+
+/// Record a finished model call's token usage and outcome on its span
+fn record_model_call(span: &Span, usage: Option<&Usage>) {
+    span.record("success", usage.is_some());
+    if let Some(usage) = usage {
+        span.record("input_tokens", usage.input_tokens);
+        span.record("output_tokens", usage.output_tokens);
+    }
+}
+
+/// Result reported for a tool aborted because another tool in its batch failed
+fn cancelled_tool_result() -> ToolResult {
+    ToolResult {
+        success: false,
+        data: serde_json::Value::Null,
+        error: Some(TOOL_CANCELLED_ERROR.to_string()),
+        execution_time_ms: 0,
+        attempts: 0,
+    }
+}
+
+/// Longest prefix of `s` that fits in `max_bytes` without splitting a character
+fn truncate_to_char_boundary(s: &str, max_bytes: usize) -> &str {
+    if s.len() <= max_bytes {
+        return s;
+    }
+    let mut end = max_bytes;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
+
+/// Strip a surrounding ```json ... ``` fence, if present
+fn strip_code_fence(content: &str) -> &str {
+    let trimmed = content.trim();
+    trimmed
+        .strip_prefix("```")
+        .and_then(|rest| rest.strip_suffix("```"))
+        .map(|inner| inner.strip_prefix("json").unwrap_or(inner).trim())
+        .unwrap_or(trimmed)
+}
+
+/// Check a value against the common subset of JSON Schema
+///
+/// Supports `type`, `enum`, `properties`, `required` and `items`.
+fn check_schema(
+    value: &serde_json::Value,
+    schema: &serde_json::Value,
+    path: &str,
+) -> Result<(), String> {
+    use serde_json::Value;
+
+    if let Some(expected) = schema.get("type").and_then(Value::as_str) {
+        let matches = match expected {
+            "object" => value.is_object(),
+            "array" => value.is_array(),
+            "string" => value.is_string(),
+            "number" => value.is_number(),
+            "integer" => value.is_i64() || value.is_u64(),
+            "boolean" => value.is_boolean(),
+            "null" => value.is_null(),
+            _ => true,
+        };
+        if !matches {
+            return Err(format!("{}: expected {}", path, expected));
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            return Err(format!("{}: value not in enum", path));
+        }
+    }
+
+    if let Some(object) = value.as_object() {
+        if let Some(required) = schema.get("required").and_then(Value::as_array) {
+            for field in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(field) {
+                    return Err(format!("{}: missing required field '{}'", path, field));
+                }
+            }
+        }
+
+        if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+            for (field, field_schema) in properties {
+                if let Some(field_value) = object.get(field) {
+                    check_schema(field_value, field_schema, &format!("{}.{}", path, field))?;
+                }
+            }
+        }
+    }
+
+    if let (Some(items), Some(item_schema)) = (value.as_array(), schema.get("items")) {
+        for (index, item) in items.iter().enumerate() {
+            check_schema(item, item_schema, &format!("{}[{}]", path, index))?;
+        }
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::executor::health::HealthStatus;
     use aof_core::{ModelConfig, ModelProvider, ModelResponse, StopReason, Usage};
     use async_trait::async_trait;
     use std::collections::HashMap;
@@ -1431,6 +3013,7 @@ mod tests {
                         input_tokens: 100,
                         output_tokens: 50,
                     },
+                    stop_detail: None,
                     metadata: HashMap::new(),
                 })
             }
@@ -1459,12 +3042,8 @@ mod tests {
             name: "test-agent".to_string(),
             system_prompt: Some("You are a helpful assistant".to_string()),
             model: "test-model".to_string(),
-            tools: vec![],
-            memory: None,
-            max_iterations: 10,
-            temperature: 0.7,
             max_tokens: Some(1000),
-            extra: HashMap::new(),
+            ..Default::default()
         };
 
         let model = Box::new(MockModel::new(vec![ModelResponse {
@@ -1475,6 +3054,7 @@ mod tests {
                 input_tokens: 100,
                 output_tokens: 50,
             },
+            stop_detail: None,
             metadata: HashMap::new(),
         }]));
 
@@ -1486,20 +3066,1027 @@ mod tests {
         assert_eq!(response, "Hello! How can I help?");
         assert_eq!(context.metadata.input_tokens, 100);
         assert_eq!(context.metadata.output_tokens, 50);
+        // Unpriced model
+        assert_eq!(context.metadata.cost_usd, None);
     }
 
-    #[tokio::test]
-    async fn test_agent_executor_max_iterations() {
+    /// Fails with `error` the first time, then answers "recovered"
+    struct FlakyModel {
+        error: std::sync::Mutex<Option<AofError>>,
+        calls: Arc<std::sync::atomic::AtomicUsize>,
+        inner: MockModel,
+    }
+
+    #[async_trait]
+    impl Model for FlakyModel {
+        async fn generate(&self, request: &ModelRequest) -> AofResult<ModelResponse> {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            if let Some(error) = self.error.lock().unwrap().take() {
+                return Err(error);
+            }
+            self.inner.generate(request).await
+        }
+
+        async fn generate_stream(
+            &self,
+            _request: &ModelRequest,
+        ) -> AofResult<std::pin::Pin<Box<dyn futures::Stream<Item = AofResult<aof_core::StreamChunk>> + Send>>>
+        {
+            unimplemented!()
+        }
+
+        fn config(&self) -> &ModelConfig {
+            self.inner.config()
+        }
+
+        fn provider(&self) -> ModelProvider {
+            ModelProvider::Custom
+        }
+    }
+
+    #[test]
+    fn test_retry_backoff_honors_retry_after() {
+        let rate_limited = AofError::rate_limited(Some(Duration::from_secs(7)));
+        assert_eq!(AgentExecutor::retry_backoff(&rate_limited, 1), Duration::from_secs(7));
+        assert_eq!(AgentExecutor::retry_backoff(&rate_limited, 3), Duration::from_secs(7));
+        assert_eq!(
+            AgentExecutor::categorize_error(&rate_limited),
+            ErrorCategory::Retryable
+        );
+
+        // Without Retry-After, fall back to exponential backoff
+        let unavailable = AofError::http(503, "overloaded");
+        assert_eq!(AgentExecutor::retry_backoff(&unavailable, 2), Duration::from_secs(2));
+        assert_eq!(AgentExecutor::categorize_error(&unavailable), ErrorCategory::Retryable);
+
+        assert_eq!(
+            AgentExecutor::categorize_error(&AofError::http(401, "bad key")),
+            ErrorCategory::Terminal
+        );
+        assert_eq!(
+            AgentExecutor::categorize_error(&AofError::rate_limited(Some(Duration::from_secs(3600)))),
+            ErrorCategory::Terminal
+        );
+    }
+
+    #[tokio::test]
+    async fn test_model_call_retried_after_rate_limit() {
+        let config = AgentConfig {
+            name: "test-agent".to_string(),
+            model: "test-model".to_string(),
+            ..Default::default()
+        };
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let model = FlakyModel {
+            error: std::sync::Mutex::new(Some(AofError::rate_limited(Some(Duration::from_millis(20))))),
+            calls: Arc::clone(&calls),
+            inner: MockModel::new(vec![]),
+        };
+        let executor = AgentExecutor::new(config, Box::new(model), None, None);
+
+        let start = Instant::now();
+        let response = executor.execute(&mut AgentContext::new("Hello")).await.unwrap();
+        let elapsed = start.elapsed();
+
+        assert_eq!(response, "Done");
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+        // Waited the Retry-After delay, not the 1s exponential backoff
+        assert!(elapsed >= Duration::from_millis(20));
+        assert!(elapsed < Duration::from_millis(RETRY_INITIAL_BACKOFF_MS));
+    }
+
+    #[tokio::test]
+    async fn test_model_retry_policy() {
+        let run = |error: AofError, policy: ModelRetryPolicy| async move {
+            let config = AgentConfig {
+                name: "test-agent".to_string(),
+                model: "test-model".to_string(),
+                model_retry_policy: Some(policy),
+                ..Default::default()
+            };
+            let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+            let model = FlakyModel {
+                error: std::sync::Mutex::new(Some(error)),
+                calls: Arc::clone(&calls),
+                inner: MockModel::new(vec![]),
+            };
+            let executor = AgentExecutor::new(config, Box::new(model), None, None);
+            let result = executor.execute(&mut AgentContext::new("Hello")).await;
+            (result, calls.load(std::sync::atomic::Ordering::SeqCst))
+        };
+
+        // A transient error is retried once with the policy's backoff
+        let start = Instant::now();
+        let (result, calls) = run(
+            AofError::http(503, "overloaded"),
+            ModelRetryPolicy {
+                initial_backoff_ms: 10,
+                ..Default::default()
+            },
+        )
+        .await;
+        assert_eq!(result.unwrap(), "Done");
+        assert_eq!(calls, 2);
+        assert!(start.elapsed() < Duration::from_millis(RETRY_INITIAL_BACKOFF_MS));
+
+        // Bad requests and auth failures are never retried
+        for status in [400, 401] {
+            let (result, calls) = run(
+                AofError::http(status, "rejected"),
+                ModelRetryPolicy {
+                    max_attempts: 5,
+                    initial_backoff_ms: 10,
+                },
+            )
+            .await;
+            assert!(
+                matches!(result, Err(AofError::Http { status: s, .. }) if s == status),
+                "{:?}",
+                result
+            );
+            assert_eq!(calls, 1);
+        }
+
+        // A single attempt disables retries; the error surfaces unchanged
+        let (result, calls) = run(
+            AofError::http(503, "overloaded"),
+            ModelRetryPolicy {
+                max_attempts: 1,
+                ..Default::default()
+            },
+        )
+        .await;
+        assert!(matches!(result, Err(AofError::Http { status: 503, .. })));
+        assert_eq!(calls, 1);
+
+        let (result, _) = run(
+            AofError::rate_limited(Some(Duration::from_secs(30))),
+            ModelRetryPolicy {
+                max_attempts: 1,
+                ..Default::default()
+            },
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(AofError::RateLimited { retry_after: Some(d) }) if d == Duration::from_secs(30)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_model_health_after_failures() {
+        let failing = |error: AofError| {
+            let config = AgentConfig {
+                name: "test-agent".to_string(),
+                model: "test-model".to_string(),
+                model_retry_policy: Some(ModelRetryPolicy {
+                    max_attempts: 1,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            };
+            let model = FlakyModel {
+                error: std::sync::Mutex::new(Some(error)),
+                calls: Arc::new(std::sync::atomic::AtomicUsize::new(0)),
+                inner: MockModel::new(vec![]),
+            };
+            AgentExecutor::new(config, Box::new(model), None, None)
+        };
+
+        // A rate limited provider is up
+        for error in [
+            AofError::rate_limited(None),
+            AofError::http(429, "slow down"),
+        ] {
+            let executor = failing(error);
+            let result = executor.execute(&mut AgentContext::new("Hi")).await;
+            assert!(result.is_err());
+            assert_eq!(executor.health().await.model.status, HealthStatus::Unknown);
+        }
+
+        // An outage is reported until its TTL passes
+        let executor = failing(AofError::http(503, "overloaded"));
+        let result = executor.execute(&mut AgentContext::new("Hi")).await;
+        assert!(result.is_err());
+        assert!(executor.health().await.model.is_unhealthy());
+        let executor = executor.with_model_health_ttl(Duration::ZERO);
+        assert_eq!(executor.health().await.model.status, HealthStatus::Unknown);
+
+        // ...or a call succeeds
+        let executor = failing(AofError::http(503, "overloaded"));
+        let result = executor.execute(&mut AgentContext::new("Hi")).await;
+        assert!(result.is_err());
+        let result = executor.execute(&mut AgentContext::new("Hi")).await;
+        assert!(result.is_ok());
+        assert_eq!(executor.health().await.model.status, HealthStatus::Healthy);
+    }
+
+    #[tokio::test]
+    async fn test_execution_cost_from_pricing() {
+        let config = AgentConfig {
+            name: "test-agent".to_string(),
+            model: "test-model".to_string(),
+            ..Default::default()
+        };
+        let pricing = Pricing::empty().with_price("mock-model", aof_core::ModelPrice::new(2.0, 10.0));
+        let executor = AgentExecutor::new(config, Box::new(MockModel::new(vec![])), None, None)
+            .with_pricing(Arc::new(pricing));
+
+        let mut context = AgentContext::new("Hello");
+        executor.execute(&mut context).await.unwrap();
+
+        // 100 input tokens at $2/M + 50 output tokens at $10/M
+        let cost = context.metadata.cost_usd.unwrap();
+        assert!((cost - 0.0007).abs() < 1e-12);
+    }
+
+    /// Tool executor whose tools always fail with a config error
+    struct FailingToolExecutor;
+
+    #[async_trait]
+    impl ToolExecutor for FailingToolExecutor {
+        async fn execute_tool(&self, _name: &str, _input: ToolInput) -> AofResult<ToolResult> {
+            Err(AofError::config("kubeconfig not found"))
+        }
+
+        fn list_tools(&self) -> Vec<aof_core::ToolDefinition> {
+            vec![]
+        }
+
+        fn get_tool(&self, _name: &str) -> Option<Arc<dyn aof_core::Tool>> {
+            None
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_tool_recorded_in_error_knowledge_base() {
+        let config = AgentConfig {
+            name: "test-agent".to_string(),
+            model: "test-model".to_string(),
+            ..Default::default()
+        };
+        let model = Box::new(MockModel::new(vec![ModelResponse {
+            content: String::new(),
+            tool_calls: vec![ToolCall {
+                id: "1".to_string(),
+                name: "kubectl".to_string(),
+                arguments: serde_json::json!({}),
+            }],
+            stop_reason: StopReason::ToolUse,
+            usage: Usage {
+                input_tokens: 100,
+                output_tokens: 50,
+            },
+            stop_detail: None,
+            metadata: HashMap::new(),
+        }]));
+        let error_kb = Arc::new(ErrorKnowledgeBase::new());
+        let executor = AgentExecutor::new(config, model, Some(Arc::new(FailingToolExecutor)), None)
+            .with_error_knowledge_base(Arc::clone(&error_kb));
+
+        let mut context = AgentContext::new("List pods");
+        executor.execute(&mut context).await.unwrap();
+
+        let failures = error_kb.find_by_tag("tool:kubectl");
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].error_type, "Tool");
+        assert!(failures[0].message.contains("kubeconfig not found"));
+        // Config errors are terminal, so the tool ran only once
+        assert!(failures[0].tags.contains(&"terminal".to_string()));
+        assert!(failures[0].tags.contains(&"agent:test-agent".to_string()));
+        assert!(failures[0].context.contains("category=terminal attempts=1"));
+        assert_eq!(
+            error_kb.occurrences_by_tag("tool:"),
+            vec![("tool:kubectl".to_string(), 1)]
+        );
+    }
+
+    /// Tool executor that is rate limited on its first call only
+    struct FlakyToolExecutor {
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait]
+    impl ToolExecutor for FlakyToolExecutor {
+        async fn execute_tool(&self, _name: &str, _input: ToolInput) -> AofResult<ToolResult> {
+            if self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst) == 0 {
+                return Err(AofError::rate_limited(Some(Duration::from_millis(10))));
+            }
+            Ok(ToolResult::success(serde_json::json!("pods listed")))
+        }
+
+        fn list_tools(&self) -> Vec<aof_core::ToolDefinition> {
+            vec![]
+        }
+
+        fn get_tool(&self, _name: &str) -> Option<Arc<dyn aof_core::Tool>> {
+            None
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_result_reports_attempts() {
+        let executor: Arc<dyn ToolExecutor> = Arc::new(FlakyToolExecutor {
+            calls: std::sync::atomic::AtomicUsize::new(0),
+        });
+        let tool_call = ToolCall {
+            id: "1".to_string(),
+            name: "kubectl".to_string(),
+            arguments: serde_json::json!({}),
+        };
+
+        let result = AgentExecutor::execute_tool_with_retry_static(
+            &executor,
+            &tool_call,
+            "test-agent",
+            None,
+            &HashMap::new(),
+        )
+        .await;
+
+        assert!(result.success);
+        assert_eq!(result.attempts, 2);
+        assert!(result.retried());
+    }
+
+    /// Tool executor with a `scale` tool taking a deployment and replica count
+    #[derive(Default)]
+    struct ScaleToolExecutor {
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait]
+    impl ToolExecutor for ScaleToolExecutor {
+        async fn execute_tool(&self, _name: &str, _input: ToolInput) -> AofResult<ToolResult> {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(ToolResult::success(serde_json::json!("scaled")))
+        }
+
+        fn list_tools(&self) -> Vec<aof_core::ToolDefinition> {
+            vec![aof_core::ToolDefinition {
+                name: "scale".to_string(),
+                description: "Scale a deployment".to_string(),
+                parameters: serde_json::json!({
+                    "type": "object",
+                    "properties": {
+                        "deployment": {"type": "string"},
+                        "replicas": {"type": "integer"}
+                    },
+                    "required": ["deployment", "replicas"]
+                }),
+            }]
+        }
+
+        fn get_tool(&self, _name: &str) -> Option<Arc<dyn aof_core::Tool>> {
+            None
+        }
+    }
+
+    /// Call `scale` with `arguments`, returning the result and tool runs
+    async fn call_scale(arguments: serde_json::Value) -> (ToolResult, usize) {
+        let tools = Arc::new(ScaleToolExecutor::default());
+        let executor: Arc<dyn ToolExecutor> = tools.clone();
+        let tool_call = ToolCall {
+            id: "1".to_string(),
+            name: "scale".to_string(),
+            arguments,
+        };
+
+        let result = AgentExecutor::execute_tool_with_retry_static(
+            &executor,
+            &tool_call,
+            "test-agent",
+            None,
+            &HashMap::new(),
+        )
+        .await;
+        (
+            result,
+            tools.calls.load(std::sync::atomic::Ordering::SeqCst),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_tool_arguments_matching_schema() {
+        let (result, calls) =
+            call_scale(serde_json::json!({"deployment": "api", "replicas": 3})).await;
+
+        assert!(result.success);
+        assert_eq!(calls, 1);
+    }
+
+    #[tokio::test]
+    async fn test_tool_arguments_missing_required_field() {
+        let (result, calls) = call_scale(serde_json::json!({"deployment": "api"})).await;
+
+        assert!(!result.success);
+        assert_eq!(calls, 0);
+        assert_eq!(result.attempts, 1);
+        assert_eq!(
+            result.error.as_deref(),
+            Some("Tool execution error: argument validation failed: $: missing required field 'replicas'")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tool_arguments_with_wrong_type() {
+        let (result, calls) =
+            call_scale(serde_json::json!({"deployment": "api", "replicas": "three"})).await;
+
+        assert!(!result.success);
+        assert_eq!(calls, 0);
+        assert_eq!(
+            result.error.as_deref(),
+            Some("Tool execution error: argument validation failed: $.replicas: expected integer")
+        );
+    }
+
+    /// Tool executor where `fail` errors immediately and `slow` takes a minute
+    struct MixedSpeedToolExecutor;
+
+    #[async_trait]
+    impl ToolExecutor for MixedSpeedToolExecutor {
+        async fn execute_tool(&self, name: &str, _input: ToolInput) -> AofResult<ToolResult> {
+            if name == "fail" {
+                return Err(AofError::config("kubeconfig not found"));
+            }
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(ToolResult::success(serde_json::json!("done")))
+        }
+
+        fn list_tools(&self) -> Vec<aof_core::ToolDefinition> {
+            vec![]
+        }
+
+        fn get_tool(&self, _name: &str) -> Option<Arc<dyn aof_core::Tool>> {
+            None
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fail_fast_cancels_in_flight_tools() {
+        let config: AgentConfig =
+            serde_yaml::from_str("name: test-agent\nmodel: test-model\nfail_fast: true").unwrap();
+        let executor = AgentExecutor::new(
+            config,
+            Box::new(MockModel::new(vec![])),
+            Some(Arc::new(MixedSpeedToolExecutor)),
+            None,
+        );
+        let tool_calls: Vec<ToolCall> = ["slow", "fail"]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| ToolCall {
+                id: i.to_string(),
+                name: name.to_string(),
+                arguments: serde_json::json!({}),
+            })
+            .collect();
+
+        let results = tokio::time::timeout(
+            Duration::from_secs(2),
+            executor.execute_tools(&tool_calls, &HashMap::new()),
+        )
+        .await
+        .expect("fail_fast should not wait for the slow tool")
+        .unwrap();
+
+        assert!(!results[0].success);
+        assert_eq!(results[0].error.as_deref(), Some(TOOL_CANCELLED_ERROR));
+        assert!(!results[1].success);
+        assert_eq!(
+            results[1].error.as_deref(),
+            Some("Configuration error: kubeconfig not found")
+        );
+    }
+
+    /// Tool executor recording the peak number of tools running at once
+    #[derive(Default)]
+    struct ConcurrencyTrackingToolExecutor {
+        running: std::sync::atomic::AtomicUsize,
+        peak: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait]
+    impl ToolExecutor for ConcurrencyTrackingToolExecutor {
+        async fn execute_tool(&self, _name: &str, _input: ToolInput) -> AofResult<ToolResult> {
+            use std::sync::atomic::Ordering::SeqCst;
+
+            let running = self.running.fetch_add(1, SeqCst) + 1;
+            self.peak.fetch_max(running, SeqCst);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            self.running.fetch_sub(1, SeqCst);
+            Ok(ToolResult::success(serde_json::json!("built")))
+        }
+
+        fn list_tools(&self) -> Vec<aof_core::ToolDefinition> {
+            vec![]
+        }
+
+        fn get_tool(&self, _name: &str) -> Option<Arc<dyn aof_core::Tool>> {
+            None
+        }
+    }
+
+    #[tokio::test]
+    async fn test_heavy_tools_hold_multiple_permits() {
+        let config: AgentConfig = serde_yaml::from_str(
+            "name: test-agent\nmodel: test-model\ntool_weights:\n  run_build: 5",
+        )
+        .unwrap();
+        let tools = Arc::new(ConcurrencyTrackingToolExecutor::default());
+        let executor = AgentExecutor::new(
+            config,
+            Box::new(MockModel::new(vec![])),
+            Some(tools.clone()),
+            None,
+        );
+        assert_eq!(executor.tool_weight("run_build"), 5);
+        assert_eq!(executor.tool_weight("query_db"), 1);
+
+        let tool_calls: Vec<ToolCall> = (0..6)
+            .map(|i| ToolCall {
+                id: i.to_string(),
+                name: "run_build".to_string(),
+                arguments: serde_json::json!({}),
+            })
+            .collect();
+
+        let results = executor
+            .execute_tools(&tool_calls, &HashMap::new())
+            .await
+            .unwrap();
+
+        assert!(results.iter().all(|r| r.success));
+        // Ten permits fit two weight-5 builds at a time
+        assert_eq!(tools.peak.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    /// Tool executor returning a large multi-byte payload
+    struct OversizedToolExecutor;
+
+    #[async_trait]
+    impl ToolExecutor for OversizedToolExecutor {
+        async fn execute_tool(&self, _name: &str, _input: ToolInput) -> AofResult<ToolResult> {
+            Ok(ToolResult::success(serde_json::json!({ "pods": "é".repeat(5_000) })))
+        }
+
+        fn list_tools(&self) -> Vec<aof_core::ToolDefinition> {
+            vec![]
+        }
+
+        fn get_tool(&self, _name: &str) -> Option<Arc<dyn aof_core::Tool>> {
+            None
+        }
+    }
+
+    #[tokio::test]
+    async fn test_oversized_tool_result_truncated() {
+        use aof_memory::InMemoryBackend;
+
+        let config = AgentConfig {
+            name: "test-agent".to_string(),
+            model: "test-model".to_string(),
+            ..Default::default()
+        };
+        let model = Box::new(MockModel::new(vec![ModelResponse {
+            content: String::new(),
+            tool_calls: vec![ToolCall {
+                id: "call-1".to_string(),
+                name: "kubectl".to_string(),
+                arguments: serde_json::json!({}),
+            }],
+            stop_reason: StopReason::ToolUse,
+            usage: Usage {
+                input_tokens: 100,
+                output_tokens: 50,
+            },
+            stop_detail: None,
+            metadata: HashMap::new(),
+        }]));
+        let memory = Arc::new(SimpleMemory::new(Arc::new(InMemoryBackend::new())));
+        let executor = AgentExecutor::new(
+            config,
+            model,
+            Some(Arc::new(OversizedToolExecutor)),
+            Some(Arc::clone(&memory)),
+        )
+        // Odd limit so the cut lands inside a two-byte character
+        .with_max_tool_result_bytes(1_000);
+
+        let mut context = AgentContext::new("List pods");
+        executor.execute(&mut context).await.unwrap();
+
+        let tool_msg = context
+            .messages
+            .iter()
+            .find(|m| m.role == MessageRole::Tool)
+            .unwrap();
+        let (kept, note) = tool_msg.content.split_once('\n').unwrap();
+        assert_eq!(kept.len(), 999);
+        assert_eq!(
+            note,
+            format!(
+                "[truncated, {} bytes omitted, retrievable via key tool_result:call-1]",
+                "{\"pods\":\"\"}".len() + 10_000 - 999
+            )
+        );
+
+        let full: serde_json::Value = executor
+            .memory()
+            .unwrap()
+            .retrieve("tool_result:call-1")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(full["pods"].as_str().unwrap().chars().count(), 5_000);
+    }
+
+    #[tokio::test]
+    async fn test_sessions_keep_separate_histories() {
+        use aof_memory::InMemoryBackend;
+
+        let config = AgentConfig {
+            name: "test-agent".to_string(),
+            model: "test-model".to_string(),
+            ..Default::default()
+        };
+        let memory = Arc::new(SimpleMemory::new(Arc::new(InMemoryBackend::new())));
+        let executor =
+            AgentExecutor::new(config, Box::new(MockModel::new(vec![])), None, Some(memory));
+
+        let run = |session_id: Option<&str>, input: &str| {
+            let mut context = AgentContext::new(input);
+            context.session_id = session_id.map(String::from);
+            let executor = &executor;
+            async move {
+                executor.execute(&mut context).await.unwrap();
+                context
+                    .messages
+                    .into_iter()
+                    .filter(|m| m.role == MessageRole::User)
+                    .map(|m| m.content)
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(run(Some("alice"), "Check pods").await, ["Check pods"]);
+        assert_eq!(run(Some("bob"), "Check nodes").await, ["Check nodes"]);
+        assert_eq!(
+            run(Some("alice"), "And the logs?").await,
+            ["Check pods", "And the logs?"]
+        );
+        assert_eq!(
+            run(Some("bob"), "And the events?").await,
+            ["Check nodes", "And the events?"]
+        );
+
+        // Runs without a session continue the executor's own
+        assert_eq!(run(None, "Status?").await, ["Status?"]);
+        assert_eq!(run(None, "Again?").await, ["Status?", "Again?"]);
+    }
+
+    #[tokio::test]
+    async fn test_execute_structured() {
+        #[derive(Debug, Deserialize)]
+        struct Verdict {
+            healthy: bool,
+            reason: String,
+        }
+
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": {
+                "healthy": { "type": "boolean" },
+                "reason": { "type": "string" }
+            },
+            "required": ["healthy", "reason"]
+        });
+        let executor_for = |content: &str| {
+            let config = AgentConfig {
+                name: "test-agent".to_string(),
+                model: "test-model".to_string(),
+                max_iterations: 2,
+                response_schema: Some(schema.clone()),
+                ..Default::default()
+            };
+            let model = Box::new(MockModel::new(vec![ModelResponse {
+                content: content.to_string(),
+                tool_calls: vec![],
+                stop_reason: StopReason::EndTurn,
+                usage: Usage {
+                    input_tokens: 10,
+                    output_tokens: 5,
+                },
+                stop_detail: None,
+                metadata: HashMap::new(),
+            }]));
+            AgentExecutor::new(config, model, None, None)
+        };
+
+        let executor = executor_for("```json\n{\"healthy\": false, \"reason\": \"disk full\"}\n```");
+        let verdict: Verdict = executor
+            .execute_structured(&mut AgentContext::new("check"))
+            .await
+            .unwrap();
+        assert!(!verdict.healthy);
+        assert_eq!(verdict.reason, "disk full");
+
+        let executor = executor_for("The node looks healthy");
+        let err = executor
+            .execute_structured::<Verdict>(&mut AgentContext::new("check"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not valid JSON"));
+
+        let executor = executor_for("{\"healthy\": \"yes\", \"reason\": \"ok\"}");
+        let err = executor
+            .execute_structured::<Verdict>(&mut AgentContext::new("check"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("$.healthy: expected boolean"));
+    }
+
+    fn pruning_executor(context_window: usize, max_tokens: usize) -> AgentExecutor {
+        let config = AgentConfig {
+            name: "test-agent".to_string(),
+            system_prompt: Some("x".repeat(400)),
+            model: "test-model".to_string(),
+            max_iterations: 2,
+            max_tokens: Some(max_tokens),
+            context_window: Some(context_window),
+            ..Default::default()
+        };
+        AgentExecutor::new(config, Box::new(MockModel::new(vec![])), None, None)
+    }
+
+    fn message(role: MessageRole, chars: usize, tag: &str) -> aof_core::Message {
+        aof_core::Message {
+            role,
+            content: format!("{}{}", tag, "a".repeat(chars - tag.len())),
+            tool_calls: None,
+            tool_call_id: None,
+            parts: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_prune_history_by_token_budget() {
+        // 1000-token window, 200 reserved, 100 for the system prompt: 700 left
+        let executor = pruning_executor(1000, 200);
+
+        // Many short messages fit even though there are lots of them
+        let short: Vec<_> = (0..150)
+            .map(|i| message(MessageRole::User, 16, &i.to_string()))
+            .collect();
+        assert_eq!(executor.prune_conversation_history(short).await.len(), 150);
+
+        // Three 300-token messages do not; the oldest one is dropped
+        let long = vec![
+            message(MessageRole::System, 40, "rules"),
+            message(MessageRole::User, 1200, "first"),
+            message(MessageRole::Assistant, 1200, "second"),
+            message(MessageRole::User, 1200, "third"),
+        ];
+        let pruned = executor.prune_conversation_history(long).await;
+        assert_eq!(pruned.len(), 3);
+        assert_eq!(pruned[0].role, MessageRole::System);
+        assert!(pruned[1].content.starts_with("second"));
+        assert!(pruned[2].content.starts_with("third"));
+
+        // The newest message is kept even when it alone exceeds the budget
+        let huge = vec![
+            message(MessageRole::User, 400, "old"),
+            message(MessageRole::User, 8000, "huge"),
+        ];
+        let pruned = executor.prune_conversation_history(huge).await;
+        assert_eq!(pruned.len(), 1);
+        assert!(pruned[0].content.starts_with("huge"));
+    }
+
+    #[tokio::test]
+    async fn test_prune_history_summarizes_dropped_messages() {
+        let executor = pruning_executor(1000, 200).with_history_summarizer(Arc::new(
+            |dropped: &[aof_core::Message]| format!("{} earlier messages", dropped.len()),
+        ));
+
+        let history = vec![
+            message(MessageRole::User, 1200, "first"),
+            message(MessageRole::Assistant, 1200, "second"),
+            message(MessageRole::User, 1200, "third"),
+        ];
+        let pruned = executor.prune_conversation_history(history).await;
+
+        assert_eq!(pruned.len(), 3);
+        assert_eq!(
+            pruned[0].content,
+            "[Summary of earlier conversation]\n1 earlier messages"
+        );
+        assert!(pruned[1].content.starts_with("second"));
+    }
+
+    /// Model rejecting requests with more than `max_messages` messages
+    struct ContextLimitedModel {
+        max_messages: usize,
+        config: ModelConfig,
+    }
+
+    #[async_trait]
+    impl Model for ContextLimitedModel {
+        async fn generate(&self, request: &ModelRequest) -> AofResult<ModelResponse> {
+            let content = if request.messages.len() > self.max_messages {
+                return Err(AofError::context_overflow(
+                    "Anthropic API error: prompt is too long: 210000 tokens > 200000 maximum",
+                ));
+            } else {
+                format!("Answer based on: {}", request.messages[0].content)
+            };
+
+            Ok(ModelResponse {
+                content,
+                tool_calls: vec![],
+                stop_reason: StopReason::EndTurn,
+                usage: Usage {
+                    input_tokens: 10,
+                    output_tokens: 5,
+                },
+                stop_detail: None,
+                metadata: HashMap::new(),
+            })
+        }
+
+        async fn generate_stream(
+            &self,
+            _request: &ModelRequest,
+        ) -> AofResult<std::pin::Pin<Box<dyn futures::Stream<Item = AofResult<aof_core::StreamChunk>> + Send>>>
+        {
+            unimplemented!()
+        }
+
+        fn config(&self) -> &ModelConfig {
+            &self.config
+        }
+
+        fn provider(&self) -> ModelProvider {
+            ModelProvider::Custom
+        }
+    }
+
+    fn overflowing_context() -> AgentContext {
+        let mut context = AgentContext::new("so what should we do?");
+        for i in 0..3 {
+            context.add_message(MessageRole::User, format!("question {}", i));
+            context.add_message(MessageRole::Assistant, format!("answer {}", i));
+        }
+        context
+    }
+
+    #[tokio::test]
+    async fn test_empty_response_policy() {
+        let empty = || ModelResponse {
+            content: " ".to_string(),
+            tool_calls: vec![],
+            stop_reason: StopReason::EndTurn,
+            usage: Usage::default(),
+            stop_detail: None,
+            metadata: HashMap::new(),
+        };
+        let run = |spec: &str, responses: Vec<ModelResponse>| {
+            let config: AgentConfig = serde_yaml::from_str(spec).unwrap();
+            let executor =
+                AgentExecutor::new(config, Box::new(MockModel::new(responses)), None, None);
+            async move { executor.execute(&mut AgentContext::new("Status?")).await }
+        };
+
+        // Fails by default
+        let err = run("name: checker\nmodel: mock-model", vec![empty()])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AofError::Model(ref message) if message == "empty response"));
+
+        // Asked once more with `retry`; the mock then answers "Done"
+        let retry = "name: checker\nmodel: mock-model\non_empty_response: retry";
+        assert_eq!(run(retry, vec![empty()]).await.unwrap(), "Done");
+        assert!(run(retry, vec![empty(), empty()]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_context_overflow_summarizes_and_retries() {
+        let config: AgentConfig = serde_yaml::from_str(
+            "name: test-agent\nmodel: mock-model\non_context_overflow: summarize",
+        )
+        .unwrap();
+        let model = ContextLimitedModel {
+            max_messages: 4,
+            config: MockModel::new(vec![]).config,
+        };
+        let executor = AgentExecutor::new(config, Box::new(model), None, None)
+            .with_history_summarizer(Arc::new(|dropped: &[aof_core::Message]| {
+                format!("{} earlier messages", dropped.len())
+            }));
+
+        let mut context = overflowing_context();
+        let response = executor.execute(&mut context).await.unwrap();
+
+        // 7 messages of 17 tokens overflow; the oldest 5 (10 tokens) are
+        // summarized, leaving 1 summary + 2 which fit
+        assert_eq!(
+            response,
+            "Answer based on: [Summary of earlier conversation]\n5 earlier messages"
+        );
+        assert_eq!(context.messages.len(), 4);
+        assert_eq!(context.messages[1].content, "answer 2");
+        assert_eq!(context.metadata.input_tokens, 10);
+    }
+
+    #[tokio::test]
+    async fn test_context_overflow_summarizes_with_the_agent_model() {
+        let config: AgentConfig = serde_yaml::from_str(
+            "name: test-agent\nmodel: mock-model\non_context_overflow: summarize",
+        )
+        .unwrap();
+        let model = ContextLimitedModel {
+            max_messages: 4,
+            config: MockModel::new(vec![]).config,
+        };
+        let executor = AgentExecutor::new(config, Box::new(model), None, None);
+
+        let mut context = overflowing_context();
+        executor.execute(&mut context).await.unwrap();
+
+        // The model was asked to summarize the 5 pruned messages, and its
+        // summary took their place ahead of the 2 kept ones
+        assert_eq!(context.messages.len(), 4);
+        let summary = &context.messages[0];
+        assert_eq!(summary.role, MessageRole::User);
+        assert!(summary
+            .content
+            .starts_with("[Summary of earlier conversation]\nAnswer based on: User: question 0"));
+        assert!(summary.content.ends_with("User: question 2"));
+        assert_eq!(context.messages[1].content, "answer 2");
+        assert_eq!(context.messages[2].content, "so what should we do?");
+    }
+
+    #[tokio::test]
+    async fn test_context_overflow_gives_up_when_nothing_can_go() {
+        let config: AgentConfig = serde_yaml::from_str(
+            "name: test-agent\nmodel: mock-model\non_context_overflow: summarize",
+        )
+        .unwrap();
+        // Not even a summary and the newest message fit
+        let model = ContextLimitedModel {
+            max_messages: 1,
+            config: MockModel::new(vec![]).config,
+        };
+        let executor = AgentExecutor::new(config, Box::new(model), None, None)
+            .with_history_summarizer(Arc::new(|_: &[aof_core::Message]| "summary".to_string()));
+
+        // Replacing the summary with a new one frees nothing, so the run
+        // fails instead of retrying forever
+        let err = executor
+            .execute(&mut overflowing_context())
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("Context window exceeded"),
+            "{}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn test_context_overflow_fails_by_default() {
+        let config: AgentConfig =
+            serde_yaml::from_str("name: test-agent\nmodel: mock-model").unwrap();
+        let model = ContextLimitedModel {
+            max_messages: 4,
+            config: MockModel::new(vec![]).config,
+        };
+        let executor = AgentExecutor::new(config, Box::new(model), None, None);
+
+        let err = executor
+            .execute(&mut overflowing_context())
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("Context window exceeded"),
+            "{}",
+            err
+        );
+
+        // Prune drops history without asking for a summary
+        let executor = executor.with_context_overflow_policy(ContextOverflowPolicy::Prune);
+        let response = executor.execute(&mut overflowing_context()).await.unwrap();
+        assert_eq!(response, "Answer based on: answer 2");
+    }
+
+    #[tokio::test]
+    async fn test_agent_executor_max_iterations() {
         let config = AgentConfig {
             name: "test-agent".to_string(),
-            system_prompt: None,
             model: "test-model".to_string(),
-            tools: vec![],
-            memory: None,
             max_iterations: 2,
-            temperature: 0.7,
-            max_tokens: None,
-            extra: HashMap::new(),
+            ..Default::default()
         };
 
         // Mock model that keeps requesting tools
@@ -1516,6 +4103,7 @@ mod tests {
                     input_tokens: 100,
                     output_tokens: 50,
                 },
+                stop_detail: None,
                 metadata: HashMap::new(),
             },
             ModelResponse {
@@ -1530,6 +4118,7 @@ mod tests {
                     input_tokens: 100,
                     output_tokens: 50,
                 },
+                stop_detail: None,
                 metadata: HashMap::new(),
             },
             ModelResponse {
@@ -1540,6 +4129,7 @@ mod tests {
                     input_tokens: 100,
                     output_tokens: 50,
                 },
+                stop_detail: None,
                 metadata: HashMap::new(),
             },
         ]));
@@ -1553,4 +4143,493 @@ mod tests {
         // so it will fail on tool execution first
         assert!(result.is_err());
     }
+
+    /// Model whose streaming call never returns, like a hung LLM request
+    struct StalledModel {
+        config: ModelConfig,
+    }
+
+    #[async_trait]
+    impl Model for StalledModel {
+        async fn generate(&self, _request: &ModelRequest) -> AofResult<ModelResponse> {
+            futures::future::pending().await
+        }
+
+        async fn generate_stream(
+            &self,
+            _request: &ModelRequest,
+        ) -> AofResult<std::pin::Pin<Box<dyn futures::Stream<Item = AofResult<aof_core::StreamChunk>> + Send>>>
+        {
+            futures::future::pending().await
+        }
+
+        fn config(&self) -> &ModelConfig {
+            &self.config
+        }
+
+        fn provider(&self) -> ModelProvider {
+            ModelProvider::Custom
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_streaming_cancellable_aborts_in_flight_call() {
+        let config: AgentConfig = serde_yaml::from_str("name: stalled\nmodel: mock-model").unwrap();
+        let model = Box::new(StalledModel {
+            config: MockModel::new(vec![]).config,
+        });
+        let executor = AgentExecutor::new(config, model, None, None);
+
+        let (stream_tx, mut stream_rx) = mpsc::channel(100);
+        let (cancel_tx, cancel_rx) = oneshot::channel();
+        let run = tokio::spawn(async move {
+            let mut ctx = AgentContext::new("hello");
+            executor
+                .execute_streaming_cancellable(&mut ctx, stream_tx, cancel_rx)
+                .await
+        });
+
+        // The model call is in flight once the first iteration has started
+        assert!(matches!(
+            stream_rx.recv().await,
+            Some(StreamEvent::IterationStart { iteration: 1, .. })
+        ));
+        cancel_tx.send(()).unwrap();
+
+        let result = tokio::time::timeout(Duration::from_secs(1), run)
+            .await
+            .expect("cancellation should stop the execution")
+            .unwrap();
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Agent error: Execution cancelled"
+        );
+        assert!(matches!(
+            stream_rx.recv().await,
+            Some(StreamEvent::Error { message }) if message == "Execution cancelled by user"
+        ));
+    }
+
+    /// Model that streams a fixed sequence of chunks
+    struct ScriptedStreamModel {
+        chunks: Vec<StreamChunk>,
+        config: ModelConfig,
+    }
+
+    #[async_trait]
+    impl Model for ScriptedStreamModel {
+        async fn generate(&self, _request: &ModelRequest) -> AofResult<ModelResponse> {
+            unimplemented!()
+        }
+
+        async fn generate_stream(
+            &self,
+            _request: &ModelRequest,
+        ) -> AofResult<std::pin::Pin<Box<dyn futures::Stream<Item = AofResult<aof_core::StreamChunk>> + Send>>>
+        {
+            Ok(Box::pin(futures::stream::iter(
+                self.chunks.clone().into_iter().map(Ok),
+            )))
+        }
+
+        fn config(&self) -> &ModelConfig {
+            &self.config
+        }
+
+        fn provider(&self) -> ModelProvider {
+            ModelProvider::Custom
+        }
+    }
+
+    #[tokio::test]
+    async fn test_thinking_deltas_stream_separately_from_content() {
+        let config: AgentConfig = serde_yaml::from_str("name: thinker\nmodel: mock-model").unwrap();
+        let thinking = |delta: &str| StreamChunk::ThinkingDelta {
+            delta: delta.to_string(),
+        };
+        let model = Box::new(ScriptedStreamModel {
+            chunks: vec![
+                thinking("The pod restarts "),
+                thinking("on OOM."),
+                StreamChunk::ContentDelta {
+                    delta: "Raise the memory limit.".to_string(),
+                },
+                StreamChunk::Done {
+                    usage: Usage::default(),
+                    stop_reason: StopReason::EndTurn,
+                    stop_detail: None,
+                },
+            ],
+            config: MockModel::new(vec![]).config,
+        });
+        let executor = AgentExecutor::new(config, model, None, None);
+
+        let (stream_tx, mut stream_rx) = mpsc::channel(100);
+        let mut ctx = AgentContext::new("why is my pod restarting?");
+        let response = executor.execute_streaming(&mut ctx, stream_tx).await.unwrap();
+
+        assert_eq!(response, "Raise the memory limit.");
+
+        let mut thinking_events = Vec::new();
+        let mut text_events = Vec::new();
+        while let Some(event) = stream_rx.recv().await {
+            match event {
+                StreamEvent::Thinking { content } => thinking_events.push(content),
+                StreamEvent::TextDelta { delta, .. } => text_events.push(delta),
+                _ => {}
+            }
+        }
+        assert_eq!(thinking_events, vec!["The pod restarts ", "on OOM."]);
+        assert_eq!(text_events, vec!["Raise the memory limit."]);
+    }
+
+    /// Tool executor where every tool succeeds
+    struct EchoToolExecutor;
+
+    #[async_trait]
+    impl ToolExecutor for EchoToolExecutor {
+        async fn execute_tool(&self, name: &str, _input: ToolInput) -> AofResult<ToolResult> {
+            Ok(ToolResult::success(serde_json::json!({ "tool": name })))
+        }
+
+        fn list_tools(&self) -> Vec<aof_core::ToolDefinition> {
+            vec![]
+        }
+
+        fn get_tool(&self, _name: &str) -> Option<Arc<dyn aof_core::Tool>> {
+            None
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_results_reference_their_calls() {
+        let call = |id: &str, name: &str| ToolCall {
+            id: id.to_string(),
+            name: name.to_string(),
+            arguments: serde_json::json!({}),
+        };
+        let model = MockModel::new(vec![ModelResponse {
+            content: String::new(),
+            tool_calls: vec![
+                call("call_pods", "kubectl_get"),
+                call("call_cpu", "prometheus_query"),
+            ],
+            stop_reason: StopReason::ToolUse,
+            usage: Usage::default(),
+            stop_detail: None,
+            metadata: HashMap::new(),
+        }]);
+        let config: AgentConfig =
+            serde_yaml::from_str("name: checker\nmodel: mock-model").unwrap();
+        let executor =
+            AgentExecutor::new(config, Box::new(model), Some(Arc::new(EchoToolExecutor)), None);
+
+        let mut context = AgentContext::new("check the cluster");
+        executor.execute(&mut context).await.unwrap();
+
+        let request = executor.build_model_request(&context).unwrap();
+        let results: Vec<_> = request
+            .messages
+            .iter()
+            .filter(|m| m.role == aof_core::model::MessageRole::Tool)
+            .map(|m| (m.tool_call_id.as_deref(), m.content.as_str()))
+            .collect();
+        assert_eq!(
+            results,
+            [
+                (Some("call_pods"), r#"{"tool":"kubectl_get"}"#),
+                (Some("call_cpu"), r#"{"tool":"prometheus_query"}"#),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_input_images_reach_the_model_request() {
+        let config: AgentConfig = serde_yaml::from_str("name: checker\nmodel: mock-model").unwrap();
+        let executor = AgentExecutor::new(config, Box::new(MockModel::new(vec![])), None, None);
+        let image = aof_core::ContentPart::image_base64("image/png", "iVBORw0KGgo=");
+
+        let mut context =
+            AgentContext::new("What does this alert show?").with_input_part(image.clone());
+        executor.execute(&mut context).await.unwrap();
+
+        let request = executor.build_model_request(&context).unwrap();
+        assert_eq!(request.messages[0].content, "What does this alert show?");
+        assert_eq!(request.messages[0].parts, [image]);
+        assert!(request.has_images());
+    }
+
+    #[test]
+    fn test_context_headers_reach_the_model_request() {
+        let config: AgentConfig = serde_yaml::from_str("name: checker\nmodel: mock-model").unwrap();
+        let executor = AgentExecutor::new(config, Box::new(MockModel::new(vec![])), None, None);
+
+        let context =
+            AgentContext::new("Check the cluster").with_request_header("X-Tenant-Id", "acme");
+        let request = executor.build_model_request(&context).unwrap();
+
+        assert_eq!(request.request_headers["X-Tenant-Id"], "acme");
+    }
+
+    /// Model streaming text deltas forever, counting them
+    struct EndlessStreamModel {
+        deltas: Arc<std::sync::atomic::AtomicUsize>,
+        config: ModelConfig,
+    }
+
+    #[async_trait]
+    impl Model for EndlessStreamModel {
+        async fn generate(&self, _request: &ModelRequest) -> AofResult<ModelResponse> {
+            unimplemented!()
+        }
+
+        async fn generate_stream(
+            &self,
+            _request: &ModelRequest,
+        ) -> AofResult<std::pin::Pin<Box<dyn futures::Stream<Item = AofResult<aof_core::StreamChunk>> + Send>>>
+        {
+            let deltas = self.deltas.clone();
+            let next_delta = |deltas: Arc<std::sync::atomic::AtomicUsize>| async move {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                deltas.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                let chunk = StreamChunk::ContentDelta {
+                    delta: "token ".to_string(),
+                };
+                Some((Ok(chunk), deltas))
+            };
+            Ok(Box::pin(futures::stream::unfold(deltas, next_delta)))
+        }
+
+        fn config(&self) -> &ModelConfig {
+            &self.config
+        }
+
+        fn provider(&self) -> ModelProvider {
+            ModelProvider::Custom
+        }
+    }
+
+    #[tokio::test]
+    async fn test_streaming_stops_when_consumer_is_gone() {
+        let config: AgentConfig = serde_yaml::from_str("name: chatty\nmodel: mock-model").unwrap();
+        let deltas = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let model = Box::new(EndlessStreamModel {
+            deltas: deltas.clone(),
+            config: MockModel::new(vec![]).config,
+        });
+        let executor = AgentExecutor::new(config, model, None, None);
+
+        let (stream_tx, mut stream_rx) = mpsc::channel(4);
+        let run = tokio::spawn(async move {
+            let mut ctx = AgentContext::new("hello");
+            executor.execute_streaming(&mut ctx, stream_tx).await
+        });
+
+        while !matches!(stream_rx.recv().await, Some(StreamEvent::TextDelta { .. })) {}
+        drop(stream_rx);
+
+        let result = tokio::time::timeout(Duration::from_secs(1), run)
+            .await
+            .expect("a dropped consumer should stop the execution")
+            .unwrap();
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Agent error: Stream consumer gone"
+        );
+
+        // Nothing more is pulled from the model once the run has stopped
+        let generated = deltas.load(std::sync::atomic::Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(deltas.load(std::sync::atomic::Ordering::SeqCst), generated);
+    }
+
+    /// Model streaming a different chunk sequence on each call
+    struct TurnStreamModel {
+        turns: std::sync::Mutex<Vec<Vec<StreamChunk>>>,
+        config: ModelConfig,
+    }
+
+    #[async_trait]
+    impl Model for TurnStreamModel {
+        async fn generate(&self, _request: &ModelRequest) -> AofResult<ModelResponse> {
+            unimplemented!()
+        }
+
+        async fn generate_stream(
+            &self,
+            _request: &ModelRequest,
+        ) -> AofResult<std::pin::Pin<Box<dyn futures::Stream<Item = AofResult<aof_core::StreamChunk>> + Send>>>
+        {
+            let chunks = self.turns.lock().unwrap().remove(0);
+            Ok(Box::pin(futures::stream::iter(chunks.into_iter().map(Ok))))
+        }
+
+        fn config(&self) -> &ModelConfig {
+            &self.config
+        }
+
+        fn provider(&self) -> ModelProvider {
+            ModelProvider::Custom
+        }
+    }
+
+    #[tokio::test]
+    async fn test_soft_iteration_threshold_warns_and_continues() {
+        let config: AgentConfig = serde_yaml::from_str(
+            "name: checker\nmodel: mock-model\nmax_iterations: 5\nwarn_iteration_threshold: 2",
+        )
+        .unwrap();
+        let done = |stop_reason| StreamChunk::Done {
+            usage: Usage::default(),
+            stop_reason,
+            stop_detail: None,
+        };
+        let tool_turn = |id: &str| {
+            vec![
+                StreamChunk::ToolCall {
+                    tool_call: ToolCall {
+                        id: id.to_string(),
+                        name: "kubectl_get".to_string(),
+                        arguments: serde_json::json!({}),
+                    },
+                },
+                done(StopReason::ToolUse),
+            ]
+        };
+        let model = Box::new(TurnStreamModel {
+            turns: std::sync::Mutex::new(vec![
+                tool_turn("1"),
+                tool_turn("2"),
+                vec![
+                    StreamChunk::ContentDelta {
+                        delta: "All clear".to_string(),
+                    },
+                    done(StopReason::EndTurn),
+                ],
+            ]),
+            config: MockModel::new(vec![]).config,
+        });
+        let executor = AgentExecutor::new(config, model, Some(Arc::new(EchoToolExecutor)), None);
+
+        let (stream_tx, mut stream_rx) = mpsc::channel(100);
+        let mut ctx = AgentContext::new("check the cluster");
+        let response = executor.execute_streaming(&mut ctx, stream_tx).await.unwrap();
+
+        assert_eq!(response, "All clear");
+        assert_eq!(ctx.metadata.tool_calls, 2);
+
+        let mut warnings = Vec::new();
+        while let Some(event) = stream_rx.recv().await {
+            if let StreamEvent::Warning { message } = event {
+                warnings.push(message);
+            }
+        }
+        assert_eq!(
+            warnings,
+            ["Agent checker is at iteration 2 of 5 (warning threshold 2)"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tool_call_budget_stops_run() {
+        let config: AgentConfig =
+            serde_yaml::from_str("name: checker\nmodel: mock-model\nmax_tool_calls: 2").unwrap();
+        let responses = (1..=3)
+            .map(|step| ModelResponse {
+                content: format!("step {}", step),
+                tool_calls: vec![ToolCall {
+                    id: step.to_string(),
+                    name: "kubectl_get".to_string(),
+                    arguments: serde_json::json!({}),
+                }],
+                stop_reason: StopReason::ToolUse,
+                usage: Usage::default(),
+                stop_detail: None,
+                metadata: HashMap::new(),
+            })
+            .collect();
+        let executor = AgentExecutor::new(
+            config,
+            Box::new(MockModel::new(responses)),
+            Some(Arc::new(EchoToolExecutor)),
+            None,
+        );
+
+        let mut context = AgentContext::new("check the cluster");
+        let response = executor.execute(&mut context).await.unwrap();
+
+        assert_eq!(response, "step 3");
+        assert_eq!(context.metadata.tool_calls, 2);
+        let last = context.messages.last().unwrap();
+        assert_eq!(last.content, "step 3");
+        assert!(last.tool_calls.is_none());
+    }
+
+    /// Tool executor counting how often each tool runs
+    #[derive(Default)]
+    struct CountingToolExecutor {
+        calls: std::sync::Mutex<HashMap<String, usize>>,
+    }
+
+    #[async_trait]
+    impl ToolExecutor for CountingToolExecutor {
+        async fn execute_tool(&self, name: &str, _input: ToolInput) -> AofResult<ToolResult> {
+            let mut calls = self.calls.lock().unwrap();
+            *calls.entry(name.to_string()).or_default() += 1;
+            Ok(ToolResult::success(serde_json::json!({ "tool": name })))
+        }
+
+        fn list_tools(&self) -> Vec<aof_core::ToolDefinition> {
+            vec![]
+        }
+
+        fn get_tool(&self, _name: &str) -> Option<Arc<dyn aof_core::Tool>> {
+            None
+        }
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_cacheable_tool_calls_run_once() {
+        let config: AgentConfig = serde_yaml::from_str(
+            "name: checker\nmodel: mock-model\ncacheable_tools: [kubectl_get]",
+        )
+        .unwrap();
+        let call = |id: &str, name: &str| ToolCall {
+            id: id.to_string(),
+            name: name.to_string(),
+            arguments: serde_json::json!({ "resource": "pods" }),
+        };
+        let model = MockModel::new(vec![ModelResponse {
+            content: String::new(),
+            tool_calls: vec![
+                call("1", "kubectl_get"),
+                call("2", "kubectl_get"),
+                call("3", "restart_pod"),
+                call("4", "restart_pod"),
+            ],
+            stop_reason: StopReason::ToolUse,
+            usage: Usage::default(),
+            stop_detail: None,
+            metadata: HashMap::new(),
+        }]);
+        let tools = Arc::new(CountingToolExecutor::default());
+        let executor = AgentExecutor::new(config, Box::new(model), Some(tools.clone()), None);
+
+        let mut context = AgentContext::new("check the pods");
+        executor.execute(&mut context).await.unwrap();
+
+        // The read-only call runs once; the mutating one is never collapsed
+        let calls = tools.calls.lock().unwrap().clone();
+        assert_eq!(calls["kubectl_get"], 1);
+        assert_eq!(calls["restart_pod"], 2);
+
+        let tool_messages: Vec<_> = context
+            .messages
+            .iter()
+            .filter(|m| m.role == MessageRole::Tool)
+            .collect();
+        assert_eq!(tool_messages.len(), 4);
+        assert_eq!(tool_messages[0].content, tool_messages[1].content);
+        assert_eq!(context.metadata.tool_calls, 4);
+    }
 }
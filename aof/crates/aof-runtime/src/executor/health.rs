@@ -0,0 +1,150 @@
+//! Health of the runtime and the services its agents depend on
+//!
+//! Model reachability is taken from each agent's last model call rather than
+//! probed, so checking health never spends tokens; an outage is reported
+//! until a call succeeds or its TTL passes, and rate limits don't count. MCP connections report the
+//! outcome of their last initialize or ping, and memory backends are probed
+//! with a single read. Tools of MCP servers that were unreachable when the
+//! agent loaded are reported as unavailable.
+
+use serde::{Deserialize, Serialize};
+
+/// State of a single component
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    /// Working as of the last call or check
+    Healthy,
+    /// Not used yet, e.g. a model before the agent's first run
+    Unknown,
+    /// Failing or unreachable
+    Unhealthy,
+}
+
+/// Health of a component, with the error that made it unhealthy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComponentHealth {
+    /// Current state
+    pub status: HealthStatus,
+
+    /// Last error, for unhealthy components
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ComponentHealth {
+    /// Component working as expected
+    pub fn healthy() -> Self {
+        Self {
+            status: HealthStatus::Healthy,
+            error: None,
+        }
+    }
+
+    /// Component not checked yet
+    pub fn unknown() -> Self {
+        Self {
+            status: HealthStatus::Unknown,
+            error: None,
+        }
+    }
+
+    /// Component failing with `error`
+    pub fn unhealthy(error: impl Into<String>) -> Self {
+        Self {
+            status: HealthStatus::Unhealthy,
+            error: Some(error.into()),
+        }
+    }
+
+    /// Whether the component is known to be failing
+    pub fn is_unhealthy(&self) -> bool {
+        self.status == HealthStatus::Unhealthy
+    }
+}
+
+/// Health of a loaded agent's model, tools and memory
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgentHealth {
+    /// Agent name
+    pub name: String,
+
+    /// Reachability of the model provider, as of the last model call
+    pub model: ComponentHealth,
+
+    /// Tool connections such as MCP servers (`None` without tools)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tools: Option<ComponentHealth>,
+
+    /// Memory backend (`None` without memory)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<ComponentHealth>,
+
+    /// Tools the agent was loaded without
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unavailable_tools: Vec<UnavailableTools>,
+}
+
+impl AgentHealth {
+    /// Whether any of the agent's components is failing
+    pub fn is_unhealthy(&self) -> bool {
+        self.model.is_unhealthy()
+            || self
+                .tools
+                .as_ref()
+                .is_some_and(ComponentHealth::is_unhealthy)
+            || self
+                .memory
+                .as_ref()
+                .is_some_and(ComponentHealth::is_unhealthy)
+    }
+
+    /// Whether a component the agent can't run without is failing
+    ///
+    /// The model and memory are critical; an agent still runs while some of
+    /// its tools fail.
+    pub fn has_critical_failure(&self) -> bool {
+        self.model.is_unhealthy()
+            || self
+                .memory
+                .as_ref()
+                .is_some_and(ComponentHealth::is_unhealthy)
+    }
+}
+
+/// Tools an agent was loaded without because their source couldn't be reached
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnavailableTools {
+    /// Where the tools come from, e.g. `mcp`
+    pub source: String,
+
+    /// Names of the missing tools
+    pub tools: Vec<String>,
+
+    /// Why the source couldn't be used
+    pub error: String,
+}
+
+/// Health of the runtime, as reported by `Runtime::health`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuntimeHealth {
+    /// Agents loaded in the runtime
+    pub loaded_agents: usize,
+
+    /// Health of each loaded agent, sorted by name
+    pub agents: Vec<AgentHealth>,
+}
+
+impl RuntimeHealth {
+    /// Whether no component of any loaded agent is failing
+    ///
+    /// Components not checked yet count as healthy.
+    pub fn is_healthy(&self) -> bool {
+        !self.agents.iter().any(AgentHealth::is_unhealthy)
+    }
+
+    /// Whether a loaded agent's model or memory is failing
+    pub fn has_critical_failure(&self) -> bool {
+        self.agents.iter().any(AgentHealth::has_critical_failure)
+    }
+}
@@ -1,7 +1,17 @@
 //! Agent executor module - Core execution logic
 
 pub mod agent_executor;
+pub mod command_policy;
+pub mod delegation;
+pub mod health;
+pub mod prompt_cache;
 pub mod runtime;
 
-pub use agent_executor::{AgentExecutor, StreamEvent};
-pub use runtime::Runtime;
+pub use agent_executor::{
+    AgentExecutor, ContextOverflowPolicy, EmptyResponsePolicy, HistorySummarizer, SessionInfo,
+    StreamEvent, CONVERSATION_KEY,
+};
+pub use command_policy::CommandPolicy;
+pub use health::{AgentHealth, ComponentHealth, HealthStatus, RuntimeHealth, UnavailableTools};
+pub use prompt_cache::{PromptCache, PromptCacheConfig};
+pub use runtime::{CompositeToolExecutor, Runtime, SystemToolConfig, SYSTEM_TOOLS};
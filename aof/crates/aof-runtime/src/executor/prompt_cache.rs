@@ -0,0 +1,454 @@
+//! Prompt cache - reuse model responses to identical requests
+//!
+//! An agent with `prompt_cache` in its config gets its model wrapped in a
+//! [`PromptCache`]: a request identical to a recent one (same messages,
+//! tools and parameters) is answered from the cache instead of the provider.
+//!
+//! ```yaml
+//! prompt_cache:
+//!   max_entries: 256
+//!   ttl_secs: 600
+//! ```
+//!
+//! Only completed `generate` responses with an answer or tool calls are
+//! stored; errors, empty, truncated and filtered responses always go back to
+//! the provider. A streaming request never fills the cache, but one matching
+//! a stored response replays it as a single stream.
+
+use aof_core::{
+    AofResult, Model, ModelConfig, ModelProvider, ModelRequest, ModelResponse, StopReason,
+    StreamChunk, Usage,
+};
+use async_trait::async_trait;
+use futures::Stream;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::pin::Pin;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+type ChunkStream = Pin<Box<dyn Stream<Item = AofResult<StreamChunk>> + Send>>;
+
+/// Settings of the prompt cache, from `prompt_cache` in the agent config
+pub use aof_core::PromptCacheConfig;
+
+/// Cached response and its bookkeeping
+struct CacheEntry {
+    /// Canonical request the response answers, checked on every hit
+    request: String,
+    response: ModelResponse,
+    stored_at: Instant,
+    last_used: u64,
+}
+
+/// Responses by request key, with a use counter for LRU eviction
+#[derive(Default)]
+struct CacheEntries {
+    entries: HashMap<u64, CacheEntry>,
+    uses: u64,
+}
+
+/// Model wrapper answering repeated requests from an LRU cache with a TTL
+pub struct PromptCache {
+    inner: Box<dyn Model>,
+    max_entries: usize,
+    ttl: Duration,
+    entries: Mutex<CacheEntries>,
+}
+
+impl PromptCache {
+    pub fn new(inner: Box<dyn Model>, config: PromptCacheConfig) -> Self {
+        Self {
+            inner,
+            max_entries: config.max_entries.max(1),
+            ttl: Duration::from_secs(config.ttl_secs),
+            entries: Mutex::new(CacheEntries::default()),
+        }
+    }
+
+    /// Number of responses currently cached, expired ones included
+    pub fn len(&self) -> usize {
+        self.entries.lock().entries.len()
+    }
+
+    /// Whether nothing is cached
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Stored response for `request`, if it hasn't expired
+    ///
+    /// The copy reports no token usage, since the provider wasn't called.
+    fn lookup(&self, key: u64, request: &str) -> Option<ModelResponse> {
+        let mut cache = self.entries.lock();
+        cache.uses += 1;
+        let uses = cache.uses;
+
+        let entry = cache.entries.get_mut(&key)?;
+        if entry.request != request {
+            // A different request with the same hash
+            return None;
+        }
+        if entry.stored_at.elapsed() > self.ttl {
+            cache.entries.remove(&key);
+            return None;
+        }
+        entry.last_used = uses;
+
+        let mut response = entry.response.clone();
+        response.usage = Usage::default();
+        response.metadata.insert(
+            "prompt_cache_hit".to_string(),
+            serde_json::Value::Bool(true),
+        );
+        Some(response)
+    }
+
+    /// Store `response` for `request`, evicting the least recently used entry when full
+    fn store(&self, key: u64, request: String, response: &ModelResponse) {
+        let mut cache = self.entries.lock();
+        cache.uses += 1;
+        let uses = cache.uses;
+
+        if !cache.entries.contains_key(&key) && cache.entries.len() >= self.max_entries {
+            let lru = cache
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| *key);
+            if let Some(lru) = lru {
+                cache.entries.remove(&lru);
+            }
+        }
+        cache.entries.insert(
+            key,
+            CacheEntry {
+                request,
+                response: response.clone(),
+                stored_at: Instant::now(),
+                last_used: uses,
+            },
+        );
+    }
+}
+
+/// `request` serialized with object keys in sorted order, plus its hash
+///
+/// `stream` is left out so streaming requests find completed responses, and
+/// sorting keys keeps map ordering from changing the result.
+fn canonical_request(request: &ModelRequest) -> AofResult<(u64, String)> {
+    let mut value = serde_json::to_value(request)?;
+    if let Some(fields) = value.as_object_mut() {
+        fields.remove("stream");
+    }
+    let canonical = serde_json::to_string(&sort_keys(value))?;
+    let mut hasher = DefaultHasher::new();
+    canonical.hash(&mut hasher);
+    Ok((hasher.finish(), canonical))
+}
+
+fn sort_keys(value: serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+
+    match value {
+        Value::Array(items) => Value::Array(items.into_iter().map(sort_keys).collect()),
+        Value::Object(fields) => {
+            let mut fields: Vec<_> = fields.into_iter().collect();
+            fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Value::Object(
+                fields
+                    .into_iter()
+                    .map(|(key, value)| (key, sort_keys(value)))
+                    .collect(),
+            )
+        }
+        value => value,
+    }
+}
+
+/// Whether `response` is a complete answer worth reusing
+fn is_cacheable(response: &ModelResponse) -> bool {
+    matches!(
+        response.stop_reason,
+        StopReason::EndTurn | StopReason::ToolUse | StopReason::StopSequence
+    ) && (!response.content.trim().is_empty() || !response.tool_calls.is_empty())
+}
+
+/// `response` as the chunks a stream would have delivered
+fn response_chunks(response: ModelResponse) -> Vec<AofResult<StreamChunk>> {
+    let mut chunks = Vec::with_capacity(response.tool_calls.len() + 2);
+    if !response.content.is_empty() {
+        chunks.push(Ok(StreamChunk::ContentDelta {
+            delta: response.content,
+        }));
+    }
+    chunks.extend(
+        response
+            .tool_calls
+            .into_iter()
+            .map(|tool_call| Ok(StreamChunk::ToolCall { tool_call })),
+    );
+    chunks.push(Ok(StreamChunk::Done {
+        usage: response.usage,
+        stop_reason: response.stop_reason,
+        stop_detail: response.stop_detail,
+    }));
+    chunks
+}
+
+#[async_trait]
+impl Model for PromptCache {
+    async fn generate(&self, request: &ModelRequest) -> AofResult<ModelResponse> {
+        let (key, canonical) = canonical_request(request)?;
+        if let Some(response) = self.lookup(key, &canonical) {
+            debug!("Prompt cache hit for {}", self.inner.config().model);
+            return Ok(response);
+        }
+
+        let response = self.inner.generate(request).await?;
+        if is_cacheable(&response) {
+            self.store(key, canonical, &response);
+        }
+        Ok(response)
+    }
+
+    /// Replays a cached response in one go; a miss streams from the model uncached
+    async fn generate_stream(&self, request: &ModelRequest) -> AofResult<ChunkStream> {
+        let (key, canonical) = canonical_request(request)?;
+        if let Some(response) = self.lookup(key, &canonical) {
+            debug!(
+                "Prompt cache hit for {} (stream)",
+                self.inner.config().model
+            );
+            return Ok(Box::pin(futures::stream::iter(response_chunks(response))));
+        }
+
+        self.inner.generate_stream(request).await
+    }
+
+    fn config(&self) -> &ModelConfig {
+        self.inner.config()
+    }
+
+    fn provider(&self) -> ModelProvider {
+        self.inner.provider()
+    }
+
+    fn count_tokens(&self, text: &str) -> usize {
+        self.inner.count_tokens(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aof_core::{model::MessageRole, RequestMessage, StopReason};
+    use futures::StreamExt;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Answer and stop reason for the n-th call
+    type Reply = fn(usize) -> (String, StopReason);
+
+    /// Model answering with the number of calls made so far
+    struct CountingModel {
+        calls: Arc<AtomicUsize>,
+        config: ModelConfig,
+        reply: Reply,
+    }
+
+    #[async_trait]
+    impl Model for CountingModel {
+        async fn generate(&self, _request: &ModelRequest) -> AofResult<ModelResponse> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            let (content, stop_reason) = (self.reply)(call);
+            Ok(ModelResponse {
+                content,
+                tool_calls: vec![],
+                stop_reason,
+                usage: Usage {
+                    input_tokens: 10,
+                    output_tokens: 5,
+                },
+                stop_detail: None,
+                metadata: HashMap::new(),
+            })
+        }
+
+        async fn generate_stream(&self, request: &ModelRequest) -> AofResult<ChunkStream> {
+            let response = self.generate(request).await?;
+            Ok(Box::pin(futures::stream::iter(response_chunks(response))))
+        }
+
+        fn config(&self) -> &ModelConfig {
+            &self.config
+        }
+
+        fn provider(&self) -> ModelProvider {
+            ModelProvider::Custom
+        }
+    }
+
+    fn cached_model(config: PromptCacheConfig) -> (PromptCache, Arc<AtomicUsize>) {
+        cached_model_replying(config, |call| {
+            (format!("answer {}", call), StopReason::EndTurn)
+        })
+    }
+
+    fn cached_model_replying(
+        config: PromptCacheConfig,
+        reply: Reply,
+    ) -> (PromptCache, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let inner = CountingModel {
+            calls: calls.clone(),
+            config: ModelConfig {
+                model: "mock-model".to_string(),
+                provider: ModelProvider::Custom,
+                api_key: None,
+                endpoint: None,
+                temperature: 0.0,
+                max_tokens: None,
+                timeout_secs: 60,
+                headers: HashMap::new(),
+                extra: HashMap::new(),
+            },
+            reply,
+        };
+        (PromptCache::new(Box::new(inner), config), calls)
+    }
+
+    fn request(prompt: &str) -> ModelRequest {
+        ModelRequest {
+            messages: vec![RequestMessage {
+                role: MessageRole::User,
+                content: prompt.to_string(),
+                ..Default::default()
+            }],
+            temperature: Some(0.0),
+            extra: HashMap::from([
+                ("top_p".to_string(), serde_json::json!(1.0)),
+                ("user".to_string(), serde_json::json!("ops")),
+            ]),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_identical_request_hits_the_cache() {
+        let (model, calls) = cached_model(PromptCacheConfig::default());
+
+        let first = model
+            .generate(&request("Summarize the outage"))
+            .await
+            .unwrap();
+        let second = model
+            .generate(&request("Summarize the outage"))
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(second.content, first.content);
+        assert_eq!(second.usage.input_tokens, 0);
+        assert_eq!(second.metadata["prompt_cache_hit"], true);
+
+        // Any difference in the request is a miss
+        let mut warmer = request("Summarize the outage");
+        warmer.temperature = Some(0.7);
+        model.generate(&warmer).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // A streaming request replays the cached response without a call
+        let mut streaming = request("Summarize the outage");
+        streaming.stream = true;
+        let chunks: Vec<_> = model
+            .generate_stream(&streaming)
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert!(matches!(
+            &chunks[..],
+            [StreamChunk::ContentDelta { delta }, StreamChunk::Done { .. }] if *delta == first.content
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_streamed_responses_are_not_cached() {
+        let (model, calls) = cached_model(PromptCacheConfig::default());
+
+        let mut streaming = request("Check the pods");
+        streaming.stream = true;
+        for _ in 0..2 {
+            let _: Vec<_> = model
+                .generate_stream(&streaming)
+                .await
+                .unwrap()
+                .collect()
+                .await;
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert!(model.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_expired_and_evicted_entries_miss() {
+        let (model, calls) = cached_model(PromptCacheConfig {
+            max_entries: 2,
+            ttl_secs: 0,
+        });
+        model.generate(&request("a")).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        model.generate(&request("a")).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        let (model, calls) = cached_model(PromptCacheConfig {
+            max_entries: 2,
+            ttl_secs: 60,
+        });
+        for prompt in ["a", "b", "a", "c"] {
+            model.generate(&request(prompt)).await.unwrap();
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(model.len(), 2);
+
+        // "b" was least recently used when "c" arrived
+        model.generate(&request("a")).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        model.generate(&request("b")).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_incomplete_responses_are_not_cached() {
+        for reply in [
+            (|_| (String::new(), StopReason::EndTurn)) as Reply,
+            |_| ("  ".to_string(), StopReason::EndTurn),
+            |_| ("The pods are".to_string(), StopReason::MaxTokens),
+            |_| (String::new(), StopReason::ContentFilter),
+        ] {
+            let (model, calls) = cached_model_replying(PromptCacheConfig::default(), reply);
+            model.generate(&request("Check the pods")).await.unwrap();
+            model.generate(&request("Check the pods")).await.unwrap();
+            assert_eq!(calls.load(Ordering::SeqCst), 2);
+            assert!(model.is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hash_collision_is_a_miss() {
+        let (model, _calls) = cached_model(PromptCacheConfig::default());
+        let response = model.generate(&request("a")).await.unwrap();
+
+        // Another request stored under the same key doesn't answer this one
+        let (key, canonical) = canonical_request(&request("a")).unwrap();
+        let (_, other) = canonical_request(&request("b")).unwrap();
+        assert!(model.lookup(key, &other).is_none());
+        assert_eq!(
+            model.lookup(key, &canonical).unwrap().content,
+            response.content
+        );
+    }
+}
@@ -3,19 +3,74 @@
 //! The Runtime loads agent configurations, creates models, tools, and memory,
 //! and executes agents with proper lifecycle management.
 
-use super::{AgentExecutor, agent_executor::StreamEvent};
+use super::command_policy::CommandPolicy;
+use super::delegation::DelegationToolExecutor;
+use super::health::{RuntimeHealth, UnavailableTools};
+use super::prompt_cache::PromptCache;
+use super::{AgentExecutor, SessionInfo, agent_executor::StreamEvent};
+use crate::replay::{
+    Cassette, RecordingModel, RecordingToolExecutor, ReplayConfig, ReplayModel, ReplayToolExecutor,
+};
 use aof_core::{
-    AgentConfig, AgentContext, AofError, AofResult, ModelConfig, ModelProvider, Tool,
-    ToolDefinition, ToolExecutor, ToolInput,
+    AgentConfig, AgentContext, AofError, AofResult, ContextSnapshot, ErrorKnowledgeBase,
+    ErrorStats, Model, ModelConfig, ModelProvider, Pricing, Tool, ToolDefinition, ToolExecutor,
+    ToolInput, Validate,
 };
 use aof_llm::create_model;
+use aof_llm::provider::ollama::OllamaProvider;
 use aof_mcp::McpClientBuilder;
 use aof_memory::{InMemoryBackend, SimpleMemory};
 use async_trait::async_trait;
+use parking_lot::RwLock;
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::mpsc;
-use tracing::{debug, info};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, Semaphore};
+use tokio::task::JoinSet;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::Stream;
+use tracing::{debug, error, info, warn};
+
+/// Default number of agents `execute_all` runs at the same time
+const DEFAULT_MAX_CONCURRENT_AGENTS: usize = 4;
+
+/// Default number of events buffered between an `execute_stream` run and its consumer
+const DEFAULT_EVENT_STREAM_BUFFER: usize = 100;
+
+/// Environment variables passed to system tools by default
+const DEFAULT_ENV_ALLOWLIST: &[&str] = &[
+    "PATH", "HOME", "USER", "LANG", "LC_ALL", "TERM", "TMPDIR", "SHELL", "KUBECONFIG",
+];
+
+/// Tools run locally by the system tool executor; all other tools come from MCP
+pub const SYSTEM_TOOLS: &[&str] = &["shell", "kubectl", "bash", "sh", "python", "node"];
+
+/// Limits applied to system tools (`shell`, `kubectl`, `python`, ...)
+#[derive(Debug, Clone)]
+pub struct SystemToolConfig {
+    /// Binaries, arguments, output size and working directory allowed for
+    /// every agent; an agent's `command_policy` can only narrow it
+    pub policy: CommandPolicy,
+
+    /// Maximum run time of a single command
+    pub timeout: Duration,
+
+    /// Environment variables passed through to commands; all others are dropped
+    pub env_allowlist: Vec<String>,
+}
+
+impl Default for SystemToolConfig {
+    fn default() -> Self {
+        Self {
+            policy: CommandPolicy::default(),
+            timeout: Duration::from_secs(60),
+            env_allowlist: DEFAULT_ENV_ALLOWLIST.iter().map(|v| v.to_string()).collect(),
+        }
+    }
+}
+
+/// Loaded agents by name, shared with their delegation tools
+pub(crate) type AgentRegistry = RwLock<HashMap<String, Arc<AgentExecutor>>>;
 
 /// Top-level runtime for agent execution
 ///
@@ -27,19 +82,106 @@ use tracing::{debug, info};
 /// - Executing agents with proper lifecycle management
 pub struct Runtime {
     /// Loaded agents
-    agents: HashMap<String, Arc<AgentExecutor>>,
+    agents: Arc<AgentRegistry>,
+
+    /// Maximum agents executed concurrently by `execute_all`
+    max_concurrent_agents: usize,
+
+    /// Limits for system tools of agents loaded afterwards
+    system_tool_config: SystemToolConfig,
+
+    /// Token prices for cost estimates of agents loaded afterwards
+    pricing: Arc<Pricing>,
+
+    /// Failed tool and model calls of agents loaded afterwards
+    error_kb: Arc<ErrorKnowledgeBase>,
+
+    /// Cassette recording or replaying model and tool responses
+    replay: Option<Cassette>,
+
+    /// Events buffered for `execute_stream` consumers before the agent waits
+    event_stream_buffer: usize,
+
+    /// MCP server command for agents loaded afterwards, `AOF_MCP_SERVER` by
+    /// default (`None` to look for smoke-test-mcp in the standard locations)
+    mcp_server: Option<String>,
 }
 
 impl Runtime {
     /// Create a new runtime instance
     pub fn new() -> Self {
         Self {
-            agents: HashMap::new(),
+            agents: Arc::new(RwLock::new(HashMap::new())),
+            max_concurrent_agents: DEFAULT_MAX_CONCURRENT_AGENTS,
+            system_tool_config: SystemToolConfig::default(),
+            pricing: Arc::new(Pricing::default()),
+            error_kb: Arc::new(ErrorKnowledgeBase::new()),
+            replay: None,
+            event_stream_buffer: DEFAULT_EVENT_STREAM_BUFFER,
+            mcp_server: std::env::var("AOF_MCP_SERVER").ok(),
         }
     }
 
+    /// Record or replay model and tool responses of agents loaded afterwards
+    ///
+    /// In record mode the cassette is created (or truncated) immediately; in
+    /// replay mode it is read in full, and agents never contact a real model
+    /// or start their tools.
+    pub fn with_replay(mut self, config: ReplayConfig) -> AofResult<Self> {
+        info!(
+            "Using {:?} cassette: {}",
+            config.mode,
+            config.cassette.display()
+        );
+        self.replay = Some(Cassette::open(&config)?);
+        Ok(self)
+    }
+
+    /// Set the token prices used for cost estimates of agents loaded afterwards
+    pub fn with_pricing(mut self, pricing: Pricing) -> Self {
+        self.pricing = Arc::new(pricing);
+        self
+    }
+
+    /// Share `error_kb` with the agents loaded afterwards
+    pub fn with_error_knowledge_base(mut self, error_kb: Arc<ErrorKnowledgeBase>) -> Self {
+        self.error_kb = error_kb;
+        self
+    }
+
+    /// Set the limits applied to system tools of agents loaded afterwards
+    pub fn with_system_tool_config(mut self, config: SystemToolConfig) -> Self {
+        self.system_tool_config = config;
+        self
+    }
+
+    /// Set how many agents `execute_all` may run at the same time
+    pub fn with_max_concurrent_agents(mut self, limit: usize) -> Self {
+        self.max_concurrent_agents = limit.max(1);
+        self
+    }
+
+    /// Set how many events `execute_stream` buffers for a slow consumer
+    ///
+    /// Once the buffer is full the agent waits for the consumer to catch up.
+    pub fn with_event_stream_buffer(mut self, capacity: usize) -> Self {
+        self.event_stream_buffer = capacity.max(1);
+        self
+    }
+
+    /// Set the MCP server started for the MCP tools of agents loaded afterwards
+    ///
+    /// Overrides the `AOF_MCP_SERVER` environment variable.
+    pub fn with_mcp_server(mut self, command: impl Into<String>) -> Self {
+        self.mcp_server = Some(command.into());
+        self
+    }
+
     /// Load an agent from YAML configuration file
     ///
+    /// `${VAR}` and `${VAR:-default}` references are expanded from the
+    /// environment before the YAML is parsed.
+    ///
     /// # Arguments
     /// * `config_path` - Path to the YAML configuration file
     ///
@@ -53,15 +195,19 @@ impl Runtime {
             AofError::config(format!("Failed to read config file {}: {}", config_path, e))
         })?;
 
-        let config: AgentConfig = serde_yaml::from_str(&config_content).map_err(|e| {
-            AofError::config(format!("Failed to parse YAML config: {}", e))
-        })?;
+        let config = AgentConfig::from_yaml(&config_content)?;
 
         self.load_agent_from_config(config).await
     }
 
     /// Load an agent from configuration struct
     ///
+    /// The config is validated first; every problem found is reported in a
+    /// single `AofError::Config`. When an MCP server can't be reached the
+    /// agent is loaded without its tools, which are listed by
+    /// `AgentExecutor::unavailable_tools`, unless the config sets
+    /// `require_all_tools`.
+    ///
     /// # Arguments
     /// * `config` - Agent configuration
     ///
@@ -69,43 +215,263 @@ impl Runtime {
     /// The agent name for later execution
     pub async fn load_agent_from_config(&mut self, config: AgentConfig) -> AofResult<String> {
         let agent_name = config.name.clone();
-        info!("Loading agent: {}", agent_name);
+        let executor = self.build_agent(config).await?;
+
+        self.agents
+            .write()
+            .insert(agent_name.clone(), Arc::new(executor));
+        info!("Agent loaded successfully: {}", agent_name);
+
+        Ok(agent_name)
+    }
+
+    /// Build an agent the way `load_agent_from_config` does, without loading it
+    ///
+    /// Only needs a shared reference, so callers keeping the runtime behind
+    /// a lock can build (and start MCP servers) under a read lock and take
+    /// the write lock just for `add_agent`.
+    pub async fn build_agent(&self, config: AgentConfig) -> AofResult<AgentExecutor> {
+        config.validate()?;
+        info!("Loading agent: {}", config.name);
+
+        self.preload_model(&config).await?;
+        self.build_executor(config, None).await
+    }
+
+    /// Reload a loaded agent with a new configuration
+    ///
+    /// Rebuilds the agent's model and tools and swaps the new executor in.
+    /// The agent's memory and session are carried over so its conversation
+    /// continues.
+    /// Executions already in flight finish on the previous executor.
+    ///
+    /// # Arguments
+    /// * `name` - Name of the loaded agent
+    /// * `config` - New agent configuration (its `name` must match)
+    pub async fn reload_agent(&mut self, name: &str, config: AgentConfig) -> AofResult<()> {
+        if config.name != name {
+            return Err(AofError::config(format!(
+                "Cannot reload agent '{}' with config for '{}'",
+                name, config.name
+            )));
+        }
+        config.validate()?;
+
+        let current = self
+            .get_agent(name)
+            .ok_or_else(|| AofError::agent(format!("Agent not found: {}", name)))?;
+        let memory = current.base_memory().cloned();
+
+        info!("Reloading agent: {}", name);
+        let executor = self
+            .build_executor(config, memory)
+            .await?
+            .with_session_id(current.session_id());
+
+        self.agents
+            .write()
+            .insert(name.to_string(), Arc::new(executor));
+        info!("Agent reloaded successfully: {}", name);
+
+        Ok(())
+    }
+
+    /// Add an agent built outside the runtime, e.g. one with a custom model
+    ///
+    /// Replaces a loaded agent of the same name.
+    pub fn add_agent(&mut self, executor: AgentExecutor) -> String {
+        let agent_name = executor.config().name.clone();
+        self.agents
+            .write()
+            .insert(agent_name.clone(), Arc::new(executor));
+        info!("Agent added: {}", agent_name);
+
+        agent_name
+    }
+
+    /// Remove a loaded agent
+    ///
+    /// Executions already in flight are not interrupted.
+    pub fn remove_agent(&mut self, name: &str) -> AofResult<()> {
+        if self.agents.write().remove(name).is_none() {
+            return Err(AofError::agent(format!("Agent not found: {}", name)));
+        }
+
+        info!("Agent removed: {}", name);
+        Ok(())
+    }
+
+    /// Unload an idle agent to free its resources
+    ///
+    /// Fails while the agent is executing. With `clear_memory`, the agent's
+    /// conversations are also deleted from its memory backend, which matters
+    /// when the backend is shared with other agents.
+    pub async fn unload_agent(&mut self, name: &str, clear_memory: bool) -> AofResult<()> {
+        let executor = {
+            let mut agents = self.agents.write();
+            match agents.get(name) {
+                None => return Err(AofError::agent(format!("Agent not found: {}", name))),
+                Some(executor) if executor.is_running() => {
+                    return Err(AofError::InvalidState(format!(
+                        "Agent {} is executing and cannot be unloaded",
+                        name
+                    )));
+                }
+                Some(_) => agents.remove(name),
+            }
+        };
+
+        if let Some(executor) = executor.filter(|_| clear_memory) {
+            executor.clear_memory().await?;
+        }
+
+        info!("Agent unloaded: {}", name);
+        Ok(())
+    }
+
+    // Helper: Load an Ollama agent's model ahead of its first request when
+    // the config sets `preload: true`; a failed preload is only logged
+    async fn preload_model(&self, config: &AgentConfig) -> AofResult<()> {
+        if !config.preload || self.replay.is_some() {
+            return Ok(());
+        }
+
+        let model_config = self.create_model_config(config)?;
+        if model_config.provider != ModelProvider::Ollama {
+            return Ok(());
+        }
+
+        info!(
+            "Preloading model {} for agent {}",
+            model_config.model, config.name
+        );
+        if let Err(e) = OllamaProvider::preload(model_config).await {
+            warn!("Failed to preload model for agent {}: {}", config.name, e);
+        }
+        Ok(())
+    }
+
+    // Helper: Create an agent executor, reusing `memory` when given
+    async fn build_executor(
+        &self,
+        config: AgentConfig,
+        memory: Option<Arc<SimpleMemory>>,
+    ) -> AofResult<AgentExecutor> {
+        let agent_name = config.name.clone();
 
         // Create model from config
         let model_config = self.create_model_config(&config)?;
-        let model = create_model(model_config).await?;
+        let model: Box<dyn Model> = match &self.replay {
+            Some(Cassette::Replay(cassette)) => {
+                Box::new(ReplayModel::new(model_config, Arc::clone(cassette)))
+            }
+            Some(Cassette::Record(cassette)) => Box::new(RecordingModel::new(
+                create_model(model_config).await?,
+                Arc::clone(cassette),
+            )),
+            None => create_model(model_config).await?,
+        };
+        let prompt_cache = config
+            .prompt_cache
+            .as_ref()
+            .and_then(|cache| cache.settings());
+        let model: Box<dyn Model> = match prompt_cache {
+            Some(cache) => {
+                debug!("Enabling prompt cache for agent: {}", agent_name);
+                Box::new(PromptCache::new(model, cache))
+            }
+            None => model,
+        };
         debug!("Model created for agent: {}", agent_name);
 
-        // Create tool executor if tools are specified
-        // Only create MCP executor for tools that are MCP-based (not system tools like shell, kubectl)
-        let tool_executor: Option<Arc<dyn ToolExecutor>> = if !config.tools.is_empty() {
-            let system_tools = ["shell", "kubectl", "bash", "sh", "python", "node"];
-            let has_system_tools = config.tools.iter().any(|t| system_tools.contains(&t.as_str()));
-            let has_mcp_tools = config.tools.iter().any(|t| !system_tools.contains(&t.as_str()));
-
-            if has_system_tools && !has_mcp_tools {
-                debug!("Agent has only system tools, creating system executor");
-                Some(self.create_system_executor(&config.tools)?)
-            } else if has_mcp_tools {
-                Some(self.create_tool_executor(&config.tools).await?)
-            } else {
-                None
-            }
-        } else {
-            None
+        let mut unavailable_tools = Vec::new();
+        let tool_executor = match &self.replay {
+            Some(Cassette::Replay(cassette)) if !config.tools.is_empty() => Some(
+                Arc::new(ReplayToolExecutor::new(Arc::clone(cassette))) as Arc<dyn ToolExecutor>,
+            ),
+            Some(Cassette::Replay(_)) => None,
+            Some(Cassette::Record(cassette)) => self
+                .create_tools(&config, &mut unavailable_tools)
+                .await?
+                .map(|inner| {
+                    Arc::new(RecordingToolExecutor::new(inner, Arc::clone(cassette)))
+                        as Arc<dyn ToolExecutor>
+                }),
+            None => self.create_tools(&config, &mut unavailable_tools).await?,
         };
 
         // Create memory backend
-        let memory = self.create_memory(&config)?;
-        debug!("Memory backend created for agent: {}", agent_name);
+        let memory = match memory {
+            Some(memory) => memory,
+            None => {
+                let memory = self.create_memory(&config)?;
+                debug!("Memory backend created for agent: {}", agent_name);
+                memory
+            }
+        };
 
-        // Create agent executor
-        let executor = AgentExecutor::new(config, model, tool_executor, Some(memory));
+        let executor = AgentExecutor::new(config, model, tool_executor, Some(memory))
+            .with_pricing(Arc::clone(&self.pricing))
+            .with_error_knowledge_base(Arc::clone(&self.error_kb))
+            .with_unavailable_tools(unavailable_tools);
+        Ok(executor)
+    }
 
-        self.agents.insert(agent_name.clone(), Arc::new(executor));
-        info!("Agent loaded successfully: {}", agent_name);
+    /// Create the tool executor for the agent's configured tools, if any
+    ///
+    /// Tools of an unreachable MCP server are left out and added to
+    /// `unavailable`, unless the agent sets `require_all_tools`.
+    async fn create_tools(
+        &self,
+        config: &AgentConfig,
+        unavailable: &mut Vec<UnavailableTools>,
+    ) -> AofResult<Option<Arc<dyn ToolExecutor>>> {
+        // Create tool executors: system tools (shell, kubectl, ...) run locally,
+        // everything else goes through MCP. Agents using both get a composite.
+        let (system_tool_names, mcp_tool_names): (Vec<String>, Vec<String>) = config
+            .tools
+            .iter()
+            .cloned()
+            .partition(|t| SYSTEM_TOOLS.contains(&t.as_str()));
+
+        let mut executors: Vec<Arc<dyn ToolExecutor>> = Vec::new();
+        if !system_tool_names.is_empty() {
+            debug!("Creating system executor for tools: {:?}", system_tool_names);
+            let agent_policy = CommandPolicy::from_agent_config(config)?;
+            executors.push(self.create_system_executor(&system_tool_names, agent_policy)?);
+        }
+        if !mcp_tool_names.is_empty() {
+            match self.create_tool_executor(&mcp_tool_names).await {
+                Ok(executor) => executors.push(executor),
+                Err(e) if !config.require_all_tools => {
+                    warn!(
+                        "Loading agent {} without its MCP tools {:?}: {}",
+                        config.name, mcp_tool_names, e
+                    );
+                    unavailable.push(UnavailableTools {
+                        source: "mcp".to_string(),
+                        tools: mcp_tool_names,
+                        error: e.to_string(),
+                    });
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        if let Some(delegation) =
+            DelegationToolExecutor::from_agent_config(config, Arc::downgrade(&self.agents))
+        {
+            debug!("Enabling agent delegation for: {}", config.name);
+            executors.push(Arc::new(delegation));
+        }
 
-        Ok(agent_name)
+        Ok(match executors.len() {
+            0 => None,
+            1 => executors.pop(),
+            _ => {
+                debug!("Agent mixes system and MCP tools, creating composite executor");
+                Some(Arc::new(CompositeToolExecutor::new(executors)?))
+            }
+        })
     }
 
     /// Execute an agent with the given input
@@ -118,14 +484,34 @@ impl Runtime {
     /// The agent's final response
     pub async fn execute(&self, agent_name: &str, input: &str) -> AofResult<String> {
         let executor = self
-            .agents
-            .get(agent_name)
+            .get_agent(agent_name)
             .ok_or_else(|| AofError::agent(format!("Agent not found: {}", agent_name)))?;
 
         let mut context = AgentContext::new(input);
         executor.execute(&mut context).await
     }
 
+    /// Execute an agent, sending extra HTTP headers with its model requests
+    ///
+    /// # Arguments
+    /// * `agent_name` - Name of the loaded agent
+    /// * `input` - User input/query
+    /// * `headers` - Headers for this execution only (e.g. a tenant id for an
+    ///   LLM gateway), overriding the model config's headers of the same name
+    ///
+    /// # Returns
+    /// The agent's final response
+    pub async fn execute_with_headers(
+        &self,
+        agent_name: &str,
+        input: &str,
+        headers: HashMap<String, String>,
+    ) -> AofResult<String> {
+        let mut context = AgentContext::new(input);
+        context.request_headers = headers;
+        self.execute_with_context(agent_name, &mut context).await
+    }
+
     /// Execute an agent with a pre-built context
     ///
     /// # Arguments
@@ -140,13 +526,76 @@ impl Runtime {
         context: &mut AgentContext,
     ) -> AofResult<String> {
         let executor = self
-            .agents
-            .get(agent_name)
+            .get_agent(agent_name)
             .ok_or_else(|| AofError::agent(format!("Agent not found: {}", agent_name)))?;
 
         executor.execute(context).await
     }
 
+    /// Continue a conversation captured with `AgentContext::to_snapshot`
+    ///
+    /// # Arguments
+    /// * `agent_name` - Name of the loaded agent
+    /// * `snapshot` - Conversation to resume
+    /// * `input` - Next user input/query
+    ///
+    /// # Returns
+    /// The agent's response and a snapshot including this turn
+    pub async fn execute_with_snapshot(
+        &self,
+        agent_name: &str,
+        snapshot: ContextSnapshot,
+        input: &str,
+    ) -> AofResult<(String, ContextSnapshot)> {
+        let mut context = AgentContext::from_snapshot(snapshot);
+        context.input = input.to_string();
+
+        let response = self.execute_with_context(agent_name, &mut context).await?;
+        Ok((response, context.to_snapshot()))
+    }
+
+    /// Conversation sessions stored in an agent's memory, most recent first
+    ///
+    /// # Arguments
+    /// * `agent_name` - Name of the loaded agent
+    pub async fn list_sessions(&self, agent_name: &str) -> AofResult<Vec<SessionInfo>> {
+        let executor = self
+            .get_agent(agent_name)
+            .ok_or_else(|| AofError::agent(format!("Agent not found: {}", agent_name)))?;
+
+        executor.list_sessions().await
+    }
+
+    /// Continue a conversation session stored in an agent's memory
+    ///
+    /// # Arguments
+    /// * `agent_name` - Name of the loaded agent
+    /// * `session_id` - Session to continue, as listed by `list_sessions`
+    /// * `input` - Next user input/query
+    ///
+    /// # Returns
+    /// The agent's response
+    pub async fn resume_session(
+        &self,
+        agent_name: &str,
+        session_id: &str,
+        input: &str,
+    ) -> AofResult<String> {
+        let sessions = self.list_sessions(agent_name).await?;
+        if !sessions
+            .iter()
+            .any(|session| session.session_id == session_id)
+        {
+            return Err(AofError::agent(format!(
+                "Session not found for agent {}: {}",
+                agent_name, session_id
+            )));
+        }
+
+        let mut context = AgentContext::new(input).with_session_id(session_id);
+        self.execute_with_context(agent_name, &mut context).await
+    }
+
     /// Execute an agent with streaming support for real-time updates
     ///
     /// # Arguments
@@ -185,14 +634,90 @@ impl Runtime {
         stream_tx: mpsc::Sender<StreamEvent>,
     ) -> AofResult<String> {
         let executor = self
-            .agents
-            .get(agent_name)
+            .get_agent(agent_name)
             .ok_or_else(|| AofError::agent(format!("Agent not found: {}", agent_name)))?;
 
         let mut context = AgentContext::new(input);
         executor.execute_streaming(&mut context, stream_tx).await
     }
 
+    /// Execute an agent and consume its events as a [`Stream`]
+    ///
+    /// Unlike [`Runtime::execute_streaming`], the channel is owned internally:
+    /// the agent runs on a background task and the returned stream yields its
+    /// events, ending after the final `Done` or `Error`. Failures that occur
+    /// before the agent emits anything (such as an unknown agent) surface as a
+    /// single `Error` event. Dropping the stream stops the run, so no tokens
+    /// are spent on events nobody reads.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use tokio_stream::StreamExt;
+    /// # use aof_runtime::Runtime;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut runtime = Runtime::new();
+    /// runtime.load_agent_from_file("config.yaml").await?;
+    ///
+    /// let mut events = runtime.execute_stream("my-agent", "Hello");
+    /// while let Some(event) = events.next().await {
+    ///     println!("Event: {:?}", event);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn execute_stream(
+        &self,
+        agent_name: &str,
+        input: &str,
+    ) -> impl Stream<Item = StreamEvent> + Send + 'static {
+        let buffer = self.event_stream_buffer;
+        let (tx, rx) = mpsc::channel(buffer);
+        let executor = self.get_agent(agent_name);
+        let agent_name = agent_name.to_string();
+        let input = input.to_string();
+
+        tokio::spawn(async move {
+            let Some(executor) = executor else {
+                let _ = tx
+                    .send(StreamEvent::Error {
+                        message: format!("Agent not found: {}", agent_name),
+                    })
+                    .await;
+                return;
+            };
+
+            // Forward events until the terminal one, so the stream ends there
+            // even if the executor fails without reporting it. Forwarding
+            // stops when the consumer is gone, and dropping `inner_rx` then
+            // stops the run.
+            let (inner_tx, inner_rx) = mpsc::channel(buffer);
+            let mut context = AgentContext::new(&input);
+            let run = executor.execute_streaming(&mut context, inner_tx);
+            let forward = async {
+                let mut inner_rx = inner_rx;
+                while let Some(event) = inner_rx.recv().await {
+                    let terminal =
+                        matches!(event, StreamEvent::Done { .. } | StreamEvent::Error { .. });
+                    if tx.send(event).await.is_err() || terminal {
+                        return true;
+                    }
+                }
+                false
+            };
+
+            let (result, finished) = tokio::join!(run, forward);
+            if let (Err(e), false) = (result, finished) {
+                let _ = tx
+                    .send(StreamEvent::Error {
+                        message: e.to_string(),
+                    })
+                    .await;
+            }
+        });
+
+        ReceiverStream::new(rx)
+    }
+
     /// Execute an agent with streaming and a pre-built context
     ///
     /// # Arguments
@@ -209,8 +734,7 @@ impl Runtime {
         stream_tx: mpsc::Sender<StreamEvent>,
     ) -> AofResult<String> {
         let executor = self
-            .agents
-            .get(agent_name)
+            .get_agent(agent_name)
             .ok_or_else(|| AofError::agent(format!("Agent not found: {}", agent_name)))?;
 
         executor.execute_streaming(context, stream_tx).await
@@ -258,58 +782,166 @@ impl Runtime {
         agent_name: &str,
         input: &str,
         stream_tx: mpsc::Sender<StreamEvent>,
-        mut cancel_rx: tokio::sync::oneshot::Receiver<()>,
+        cancel_rx: tokio::sync::oneshot::Receiver<()>,
     ) -> AofResult<String> {
         let executor = self
-            .agents
-            .get(agent_name)
+            .get_agent(agent_name)
             .ok_or_else(|| AofError::agent(format!("Agent not found: {}", agent_name)))?;
 
         let mut context = AgentContext::new(input);
+        executor
+            .execute_streaming_cancellable(&mut context, stream_tx, cancel_rx)
+            .await
+    }
 
-        tokio::select! {
-            result = executor.execute_streaming(&mut context, stream_tx.clone()) => {
-                result
-            }
-            _ = &mut cancel_rx => {
-                let _ = stream_tx.send(StreamEvent::Error {
-                    message: "Execution cancelled by user".to_string(),
-                }).await;
-                Err(AofError::agent("Execution cancelled".to_string()))
+    /// Execute several agents concurrently with the same input
+    ///
+    /// At most `max_concurrent_agents` run at once. A failing agent does not
+    /// fail the batch; its error is returned under its name instead.
+    ///
+    /// # Arguments
+    /// * `agent_names` - Names of loaded agents
+    /// * `input` - User input/query sent to every agent
+    ///
+    /// # Returns
+    /// Each agent's response or error, keyed by agent name
+    pub async fn execute_all(
+        &self,
+        agent_names: &[String],
+        input: &str,
+    ) -> AofResult<HashMap<String, AofResult<String>>> {
+        info!(
+            "Executing {} agents in parallel (max concurrency: {})",
+            agent_names.len(),
+            self.max_concurrent_agents
+        );
+
+        let semaphore = Arc::new(Semaphore::new(self.max_concurrent_agents));
+        let mut join_set = JoinSet::new();
+        let mut results = HashMap::new();
+
+        for name in agent_names {
+            let Some(executor) = self.get_agent(name) else {
+                results.insert(
+                    name.clone(),
+                    Err(AofError::agent(format!("Agent not found: {}", name))),
+                );
+                continue;
+            };
+
+            let semaphore_clone = Arc::clone(&semaphore);
+            let name = name.clone();
+            let input = input.to_string();
+
+            join_set.spawn(async move {
+                let _permit = semaphore_clone.acquire().await.unwrap();
+                debug!("Executing agent: {}", name);
+
+                let mut context = AgentContext::new(input);
+                let result = executor.execute(&mut context).await;
+                (name, result)
+            });
+        }
+
+        while let Some(task_result) = join_set.join_next().await {
+            match task_result {
+                Ok((name, result)) => {
+                    results.insert(name, result);
+                }
+                Err(e) => error!("Agent task join error: {}", e),
             }
         }
+
+        // Agents whose task panicked never reported back
+        for name in agent_names {
+            results.entry(name.clone()).or_insert_with(|| {
+                Err(AofError::agent(format!(
+                    "Agent {} did not complete",
+                    name
+                )))
+            });
+        }
+
+        Ok(results)
     }
 
     /// List all loaded agents
     pub fn list_agents(&self) -> Vec<String> {
-        self.agents.keys().cloned().collect()
+        self.agents.read().keys().cloned().collect()
     }
 
     /// Get agent executor by name
     pub fn get_agent(&self, name: &str) -> Option<Arc<AgentExecutor>> {
-        self.agents.get(name).cloned()
+        self.agents.read().get(name).cloned()
+    }
+
+    /// Number of loaded agents
+    pub fn loaded_agent_count(&self) -> usize {
+        self.agents.read().len()
+    }
+
+    /// When a loaded agent last started or finished an execution
+    ///
+    /// Agents that never ran report their load time, so an eviction policy
+    /// can pick the least recently used agent by comparing these.
+    pub fn agent_last_used(&self, name: &str) -> Option<Instant> {
+        self.agents.read().get(name).map(|agent| agent.last_used())
+    }
+
+    /// Health of the loaded agents' models, tools and memory backends
+    ///
+    /// Cheap enough for a health endpoint: no model is called (see
+    /// `AgentExecutor::health`).
+    pub async fn health(&self) -> RuntimeHealth {
+        let agents: Vec<_> = self.agents.read().values().cloned().collect();
+        let mut agents = futures::future::join_all(agents.iter().map(|agent| agent.health())).await;
+        agents.sort_by(|a, b| a.name.cmp(&b.name));
+
+        RuntimeHealth {
+            loaded_agents: agents.len(),
+            agents,
+        }
+    }
+
+    /// Knowledge base of failed tool and model calls
+    pub fn error_knowledge_base(&self) -> &Arc<ErrorKnowledgeBase> {
+        &self.error_kb
+    }
+
+    /// Statistics over the recorded tool and model errors
+    pub fn error_stats(&self) -> ErrorStats {
+        self.error_kb.stats()
+    }
+
+    /// Tools by number of recorded failures, most failing first
+    pub fn tool_failure_counts(&self) -> Vec<(String, usize)> {
+        self.error_kb
+            .occurrences_by_tag("tool:")
+            .into_iter()
+            .map(|(tag, count)| (tag["tool:".len()..].to_string(), count))
+            .collect()
     }
 
     // Helper: Create model config from agent config
     fn create_model_config(&self, config: &AgentConfig) -> AofResult<ModelConfig> {
         // Parse model string (format: "provider:model" or just "model")
-        let (provider, model) = if config.model.contains(':') {
-            let parts: Vec<&str> = config.model.splitn(2, ':').collect();
-            let provider = match parts[0].to_lowercase().as_str() {
-                "anthropic" => ModelProvider::Anthropic,
-                "openai" => ModelProvider::OpenAI,
-                "google" => ModelProvider::Google,
-                "bedrock" => ModelProvider::Bedrock,
-                "azure" => ModelProvider::Azure,
-                "ollama" => ModelProvider::Ollama,
-                "groq" => ModelProvider::Groq,
-                _ => ModelProvider::Custom,
-            };
-            (provider, parts[1].to_string())
-        } else {
-            // Default to Anthropic if no provider specified
-            (ModelProvider::Anthropic, config.model.clone())
-        };
+        let explicit = config.model.split_once(':').and_then(|(prefix, model)| {
+            ModelProvider::from_name(prefix).map(|provider| (provider, model.to_string()))
+        });
+        let (provider, model) = explicit.unwrap_or_else(|| {
+            // Default to Anthropic for models of no well-known family
+            let provider =
+                ModelProvider::infer_from_model(&config.model).unwrap_or(ModelProvider::Anthropic);
+            (provider, config.model.clone())
+        });
+
+        // Ollama keeps the model loaded for `keep_alive` after each request
+        let mut extra = HashMap::new();
+        if provider == ModelProvider::Ollama {
+            if let Some(keep_alive) = &config.keep_alive {
+                extra.insert("keep_alive".to_string(), serde_json::json!(keep_alive));
+            }
+        }
 
         Ok(ModelConfig {
             model,
@@ -320,7 +952,7 @@ impl Runtime {
             max_tokens: config.max_tokens,
             timeout_secs: 60,
             headers: HashMap::new(),
-            extra: HashMap::new(),
+            extra,
         })
     }
 
@@ -331,8 +963,11 @@ impl Runtime {
     ) -> AofResult<Arc<dyn ToolExecutor>> {
         info!("Creating tool executor with {} tools", tool_names.len());
 
-        // Find smoke-test-mcp binary in standard locations
-        let mcp_path = if std::path::Path::new("/usr/local/bin/smoke-test-mcp").exists() {
+        // Use the configured server, or find smoke-test-mcp binary in
+        // standard locations
+        let mcp_path = if let Some(command) = &self.mcp_server {
+            command.clone()
+        } else if std::path::Path::new("/usr/local/bin/smoke-test-mcp").exists() {
             "/usr/local/bin/smoke-test-mcp".to_string()
         } else if std::path::Path::new("/usr/bin/smoke-test-mcp").exists() {
             "/usr/bin/smoke-test-mcp".to_string()
@@ -356,9 +991,32 @@ impl Runtime {
 
         info!("MCP client initialized successfully with tools: {:?}", tool_names);
 
+        // Advertise the server's own descriptions and schemas of the tools
+        let server_tools = mcp_client.list_tools().await.unwrap_or_default();
+        let tools = tool_names
+            .iter()
+            .map(|name| {
+                server_tools
+                    .iter()
+                    .find(|tool| &tool.name == name)
+                    .cloned()
+                    .unwrap_or_else(|| {
+                        warn!("MCP server does not list tool: {}", name);
+                        ToolDefinition {
+                            name: name.clone(),
+                            description: format!("MCP tool: {}", name),
+                            parameters: serde_json::json!({
+                                "type": "object",
+                                "properties": {},
+                            }),
+                        }
+                    })
+            })
+            .collect();
+
         Ok(Arc::new(McpToolExecutor {
             client: Arc::new(mcp_client),
-            tool_names: tool_names.to_vec(),
+            tools,
         }))
     }
 
@@ -366,10 +1024,16 @@ impl Runtime {
     fn create_system_executor(
         &self,
         tool_names: &[String],
+        agent_policy: Option<CommandPolicy>,
     ) -> AofResult<Arc<dyn ToolExecutor>> {
         info!("Creating system tool executor with {} tools", tool_names.len());
+        let mut config = self.system_tool_config.clone();
+        if let Some(agent_policy) = agent_policy {
+            config.policy = config.policy.narrowed_by(agent_policy);
+        }
         Ok(Arc::new(SystemToolExecutor {
             tool_names: tool_names.to_vec(),
+            config,
         }))
     }
 
@@ -386,24 +1050,89 @@ impl Default for Runtime {
     }
 }
 
-/// MCP-based tool executor implementation
-struct McpToolExecutor {
-    client: Arc<aof_mcp::McpClient>,
-    tool_names: Vec<String>,
+/// Tool executor that dispatches to several executors by tool name
+///
+/// Each tool name must be provided by exactly one executor.
+pub struct CompositeToolExecutor {
+    executors: Vec<Arc<dyn ToolExecutor>>,
+}
+
+impl CompositeToolExecutor {
+    /// Combine executors, failing if two of them provide the same tool
+    pub fn new(executors: Vec<Arc<dyn ToolExecutor>>) -> AofResult<Self> {
+        let mut seen = std::collections::HashSet::new();
+        for executor in &executors {
+            for tool in executor.list_tools() {
+                if !seen.insert(tool.name.clone()) {
+                    return Err(AofError::tool(format!(
+                        "Tool '{}' is provided by more than one executor",
+                        tool.name
+                    )));
+                }
+            }
+        }
+
+        Ok(Self { executors })
+    }
+
+    fn executor_for(&self, name: &str) -> Option<&Arc<dyn ToolExecutor>> {
+        self.executors
+            .iter()
+            .find(|executor| executor.list_tools().iter().any(|tool| tool.name == name))
+    }
 }
 
 #[async_trait]
-impl ToolExecutor for McpToolExecutor {
+impl ToolExecutor for CompositeToolExecutor {
     async fn execute_tool(
         &self,
         name: &str,
         input: ToolInput,
     ) -> AofResult<aof_core::ToolResult> {
-        debug!("Executing MCP tool: {}", name);
-        let start = std::time::Instant::now();
+        let executor = self
+            .executor_for(name)
+            .ok_or_else(|| AofError::tool(format!("Unknown tool: {}", name)))?;
 
-        // Call MCP tool
-        let result = self
+        executor.execute_tool(name, input).await
+    }
+
+    fn list_tools(&self) -> Vec<ToolDefinition> {
+        self.executors
+            .iter()
+            .flat_map(|executor| executor.list_tools())
+            .collect()
+    }
+
+    fn get_tool(&self, name: &str) -> Option<Arc<dyn Tool>> {
+        self.executor_for(name)
+            .and_then(|executor| executor.get_tool(name))
+    }
+
+    fn health_check(&self) -> AofResult<()> {
+        self.executors
+            .iter()
+            .try_for_each(|executor| executor.health_check())
+    }
+}
+
+/// MCP-based tool executor implementation
+struct McpToolExecutor {
+    client: Arc<aof_mcp::McpClient>,
+    tools: Vec<ToolDefinition>,
+}
+
+#[async_trait]
+impl ToolExecutor for McpToolExecutor {
+    async fn execute_tool(
+        &self,
+        name: &str,
+        input: ToolInput,
+    ) -> AofResult<aof_core::ToolResult> {
+        debug!("Executing MCP tool: {}", name);
+        let start = std::time::Instant::now();
+
+        // Call MCP tool
+        let result = self
             .client
             .call_tool(name, input.arguments)
             .await
@@ -416,34 +1145,33 @@ impl ToolExecutor for McpToolExecutor {
             data: result,
             error: None,
             execution_time_ms,
+            ..Default::default()
         })
     }
 
     fn list_tools(&self) -> Vec<ToolDefinition> {
-        // In a real implementation, this would query MCP for tool definitions
-        // For now, return basic definitions
-        self.tool_names
-            .iter()
-            .map(|name| ToolDefinition {
-                name: name.clone(),
-                description: format!("MCP tool: {}", name),
-                parameters: serde_json::json!({
-                    "type": "object",
-                    "properties": {},
-                }),
-            })
-            .collect()
+        self.tools.clone()
     }
 
     fn get_tool(&self, _name: &str) -> Option<Arc<dyn Tool>> {
         // MCP tools are dynamically resolved, not stored as objects
         None
     }
+
+    fn health_check(&self) -> AofResult<()> {
+        // Outcome of the last initialize or ping; no round trip here
+        if self.client.is_healthy() {
+            Ok(())
+        } else {
+            Err(AofError::mcp("MCP server is not responding"))
+        }
+    }
 }
 
 /// System tool executor for shell, kubectl, and other local commands
 struct SystemToolExecutor {
     tool_names: Vec<String>,
+    config: SystemToolConfig,
 }
 
 #[async_trait]
@@ -469,6 +1197,16 @@ impl ToolExecutor for SystemToolExecutor {
             )));
         };
 
+        // Policy violations are terminal: fail the call rather than report a result
+        let policy = &self.config.policy;
+        match name {
+            "shell" | "bash" | "sh" => policy.check_shell(&command)?,
+            "kubectl" => policy.check_program("kubectl", &command)?,
+            "python" => policy.check_program("python3", &command)?,
+            "node" => policy.check_program("node", &command)?,
+            _ => {}
+        }
+
         // Execute the command based on tool type
         let result = match name {
             "shell" | "bash" | "sh" => {
@@ -481,7 +1219,7 @@ impl ToolExecutor for SystemToolExecutor {
             }
             "python" => {
                 // Execute Python code
-                self.execute_command("python3", &[&command]).await
+                self.execute_command("python3", &["-c", &command]).await
             }
             "node" => {
                 // Execute Node.js code
@@ -496,17 +1234,33 @@ impl ToolExecutor for SystemToolExecutor {
         let execution_time_ms = start.elapsed().as_millis() as u64;
 
         match result {
-            Ok(data) => Ok(aof_core::ToolResult {
+            Ok(data) if data["success"] == serde_json::Value::Bool(true) => Ok(aof_core::ToolResult {
                 success: true,
                 data,
                 error: None,
                 execution_time_ms,
+                ..Default::default()
             }),
+            Ok(data) => {
+                let error = format!(
+                    "Command exited with code {}: {}",
+                    data["exit_code"],
+                    data["stderr"].as_str().unwrap_or_default().trim()
+                );
+                Ok(aof_core::ToolResult {
+                    success: false,
+                    data,
+                    error: Some(error),
+                    execution_time_ms,
+                    ..Default::default()
+                })
+            }
             Err(e) => Ok(aof_core::ToolResult {
                 success: false,
                 data: serde_json::json!({}),
                 error: Some(e.to_string()),
                 execution_time_ms,
+                ..Default::default()
             }),
         }
     }
@@ -599,7 +1353,7 @@ impl ToolExecutor for SystemToolExecutor {
 
 impl SystemToolExecutor {
     async fn execute_shell_command(&self, command: &str) -> AofResult<serde_json::Value> {
-        self.execute_command("sh", &["-c", command]).await
+        self.spawn_command("sh", &["-c", command]).await
     }
 
     async fn execute_kubectl_command(&self, command: &str) -> AofResult<serde_json::Value> {
@@ -612,6 +1366,14 @@ impl SystemToolExecutor {
         &self,
         program: &str,
         args: &[&str],
+    ) -> AofResult<serde_json::Value> {
+        self.spawn_command(program, args).await
+    }
+
+    async fn spawn_command(
+        &self,
+        program: &str,
+        args: &[&str],
     ) -> AofResult<serde_json::Value> {
         debug!(
             "Executing command: {} {}",
@@ -619,17 +1381,36 @@ impl SystemToolExecutor {
             args.join(" ")
         );
 
-        let output = tokio::process::Command::new(program)
-            .args(args)
-            .output()
+        let mut cmd = tokio::process::Command::new(program);
+        cmd.args(args).env_clear().kill_on_drop(true);
+        for key in &self.config.env_allowlist {
+            if let Ok(value) = std::env::var(key) {
+                cmd.env(key, value);
+            }
+        }
+        if let Some(dir) = &self.config.policy.working_dir {
+            cmd.current_dir(dir);
+        }
+
+        let output = tokio::time::timeout(self.config.timeout, cmd.output())
             .await
+            .map_err(|_| AofError::Timeout(format!(
+                "{} did not finish within {}s",
+                program,
+                self.config.timeout.as_secs_f64()
+            )))?
             .map_err(|e| AofError::tool(format!(
                 "Failed to execute {}: {}",
                 program, e
             )))?;
 
-        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        let mut stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let mut stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        let mut truncated = false;
+        if let Some(max) = self.config.policy.max_output_bytes {
+            truncated |= truncate_output(&mut stdout, max);
+            truncated |= truncate_output(&mut stderr, max);
+        }
         let exit_code = output.status.code().unwrap_or(-1);
 
         debug!(
@@ -637,18 +1418,36 @@ impl SystemToolExecutor {
             exit_code, stdout, stderr
         );
 
-        Ok(serde_json::json!({
+        let mut data = serde_json::json!({
             "exit_code": exit_code,
             "stdout": stdout,
             "stderr": stderr,
             "success": output.status.success()
-        }))
+        });
+        if truncated {
+            data["truncated"] = serde_json::Value::Bool(true);
+        }
+        Ok(data)
     }
 }
 
+/// Cut `output` to at most `max` bytes on a char boundary; true if it was cut
+fn truncate_output(output: &mut String, max: usize) -> bool {
+    if output.len() <= max {
+        return false;
+    }
+    let mut end = max;
+    while !output.is_char_boundary(end) {
+        end -= 1;
+    }
+    output.truncate(end);
+    true
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use aof_core::{DelegationConfig, FeatureToggle};
 
     #[test]
     fn test_runtime_creation() {
@@ -656,20 +1455,823 @@ mod tests {
         assert_eq!(runtime.list_agents().len(), 0);
     }
 
+    fn ollama_config(name: &str, max_iterations: usize) -> AgentConfig {
+        AgentConfig {
+            name: name.to_string(),
+            model: "ollama:llama3".to_string(),
+            max_iterations,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reload_agent() {
+        use aof_core::Memory;
+
+        let mut runtime = Runtime::new();
+        runtime
+            .load_agent_from_config(ollama_config("reloadable", 5))
+            .await
+            .unwrap();
+
+        let before = runtime.get_agent("reloadable").unwrap();
+        before
+            .memory()
+            .unwrap()
+            .store("conversation", serde_json::json!(["hi"]))
+            .await
+            .unwrap();
+
+        runtime
+            .reload_agent("reloadable", ollama_config("reloadable", 20))
+            .await
+            .unwrap();
+
+        let after = runtime.get_agent("reloadable").unwrap();
+        assert_eq!(after.config().max_iterations, 20);
+        assert_eq!(before.config().max_iterations, 5);
+        assert_eq!(after.session_id(), before.session_id());
+        assert!(after
+            .memory()
+            .unwrap()
+            .retrieve::<serde_json::Value>("conversation")
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn test_load_agent_rejects_invalid_config() {
+        let mut runtime = Runtime::new();
+
+        let config = AgentConfig {
+            temperature: 3.0,
+            ..ollama_config("", 0)
+        };
+        let err = runtime.load_agent_from_config(config).await.unwrap_err();
+
+        assert!(matches!(err, AofError::Config(_)));
+        let message = err.to_string();
+        assert!(message.contains("3 validation error(s)"));
+        assert!(message.contains("name: must not be empty"));
+        assert!(message.contains("max_iterations: must be at least 1"));
+        assert!(message.contains("temperature: must be between 0.0 and 2.0"));
+        assert!(runtime.list_agents().is_empty());
+
+        runtime
+            .load_agent_from_config(ollama_config("valid", 5))
+            .await
+            .unwrap();
+        assert!(runtime
+            .reload_agent("valid", ollama_config("valid", 0))
+            .await
+            .is_err());
+        assert_eq!(runtime.get_agent("valid").unwrap().config().max_iterations, 5);
+    }
+
+    #[tokio::test]
+    async fn test_load_agent_without_unreachable_mcp_tools() {
+        let mut runtime = Runtime::new().with_mcp_server("/nonexistent/aof-mcp-server");
+        let config = AgentConfig {
+            tools: vec!["shell".to_string(), "echo".to_string()],
+            ..ollama_config("degraded", 5)
+        };
+        runtime
+            .load_agent_from_config(config.clone())
+            .await
+            .unwrap();
+
+        let agent = runtime.get_agent("degraded").unwrap();
+        let tools: Vec<String> = agent
+            .tool_executor()
+            .unwrap()
+            .list_tools()
+            .into_iter()
+            .map(|tool| tool.name)
+            .collect();
+        assert_eq!(tools, ["shell"]);
+        let unavailable = agent.unavailable_tools();
+        assert_eq!(unavailable.len(), 1);
+        assert_eq!(unavailable[0].source, "mcp");
+        assert_eq!(unavailable[0].tools, ["echo"]);
+
+        let health = agent.health().await;
+        assert!(health.tools.unwrap().is_unhealthy());
+        assert_eq!(health.unavailable_tools, unavailable);
+
+        let strict = AgentConfig {
+            name: "strict".to_string(),
+            require_all_tools: true,
+            ..config
+        };
+        assert!(runtime.load_agent_from_config(strict).await.is_err());
+        assert!(runtime.get_agent("strict").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_reload_agent_name_mismatch() {
+        let mut runtime = Runtime::new();
+        runtime
+            .load_agent_from_config(ollama_config("reloadable", 5))
+            .await
+            .unwrap();
+
+        assert!(runtime
+            .reload_agent("reloadable", ollama_config("other", 5))
+            .await
+            .is_err());
+        assert!(runtime
+            .reload_agent("missing", ollama_config("missing", 5))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_remove_agent() {
+        let mut runtime = Runtime::new();
+        runtime
+            .load_agent_from_config(ollama_config("removable", 5))
+            .await
+            .unwrap();
+
+        runtime.remove_agent("removable").unwrap();
+        assert!(runtime.list_agents().is_empty());
+        assert!(runtime.remove_agent("removable").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unload_agent_clears_memory() {
+        use aof_core::Memory;
+
+        let mut runtime = Runtime::new();
+        for name in ["kept", "cleared"] {
+            runtime
+                .load_agent_from_config(ollama_config(name, 5))
+                .await
+                .unwrap();
+        }
+        assert_eq!(runtime.loaded_agent_count(), 2);
+
+        let mut memories = Vec::new();
+        for name in ["kept", "cleared"] {
+            let agent = runtime.get_agent(name).unwrap();
+            agent
+                .memory()
+                .unwrap()
+                .store("conversation", serde_json::json!(["hi"]))
+                .await
+                .unwrap();
+            memories.push(Arc::clone(agent.base_memory().unwrap()));
+        }
+
+        runtime.unload_agent("kept", false).await.unwrap();
+        runtime.unload_agent("cleared", true).await.unwrap();
+
+        assert_eq!(runtime.loaded_agent_count(), 0);
+        let err = runtime.execute("cleared", "hello").await.unwrap_err();
+        assert!(err.to_string().contains("Agent not found"));
+        assert!(runtime.unload_agent("cleared", true).await.is_err());
+        assert_eq!(memories[0].list_keys().await.unwrap().len(), 1);
+        assert!(memories[1].list_keys().await.unwrap().is_empty());
+    }
+
+    /// Model whose reply waits until `release` is notified
+    struct BlockedModel {
+        release: Arc<tokio::sync::Notify>,
+        inner: FixedModel,
+    }
+
+    #[async_trait]
+    impl aof_core::Model for BlockedModel {
+        async fn generate(
+            &self,
+            request: &aof_core::ModelRequest,
+        ) -> AofResult<aof_core::ModelResponse> {
+            self.release.notified().await;
+            self.inner.generate(request).await
+        }
+
+        async fn generate_stream(
+            &self,
+            _request: &aof_core::ModelRequest,
+        ) -> AofResult<
+            std::pin::Pin<Box<dyn futures::Stream<Item = AofResult<aof_core::StreamChunk>> + Send>>,
+        > {
+            unimplemented!()
+        }
+
+        fn config(&self) -> &ModelConfig {
+            self.inner.config()
+        }
+
+        fn provider(&self) -> ModelProvider {
+            ModelProvider::Custom
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unload_agent_refused_while_executing() {
+        let release = Arc::new(tokio::sync::Notify::new());
+        let mut runtime = Runtime::new();
+        runtime.add_agent(AgentExecutor::new(
+            ollama_config("busy", 5),
+            Box::new(BlockedModel {
+                release: Arc::clone(&release),
+                inner: FixedModel::new("done"),
+            }),
+            None,
+            None,
+        ));
+        let loaded_at = runtime.agent_last_used("busy").unwrap();
+
+        let agent = runtime.get_agent("busy").unwrap();
+        let run = tokio::spawn(async move { agent.execute(&mut AgentContext::new("hi")).await });
+        while !runtime.get_agent("busy").unwrap().is_running() {
+            tokio::task::yield_now().await;
+        }
+
+        let err = runtime.unload_agent("busy", false).await.unwrap_err();
+        assert!(matches!(err, AofError::InvalidState(_)));
+
+        release.notify_one();
+        assert_eq!(run.await.unwrap().unwrap(), "done");
+        assert!(runtime.agent_last_used("busy").unwrap() > loaded_at);
+        runtime.unload_agent("busy", false).await.unwrap();
+        assert_eq!(runtime.agent_last_used("busy"), None);
+    }
+
+    /// Model that answers every request with a fixed reply
+    ///
+    /// A reply of `"{history}"` is replaced by the user messages seen so far.
+    struct FixedModel {
+        reply: String,
+        config: ModelConfig,
+    }
+
+    impl FixedModel {
+        fn new(reply: &str) -> Self {
+            Self {
+                reply: reply.to_string(),
+                config: ModelConfig {
+                    model: "mock-model".to_string(),
+                    provider: ModelProvider::Custom,
+                    api_key: None,
+                    endpoint: None,
+                    temperature: 0.7,
+                    max_tokens: None,
+                    timeout_secs: 60,
+                    headers: HashMap::new(),
+                    extra: HashMap::new(),
+                },
+            }
+        }
+    }
+
+    #[async_trait]
+    impl aof_core::Model for FixedModel {
+        async fn generate(
+            &self,
+            request: &aof_core::ModelRequest,
+        ) -> AofResult<aof_core::ModelResponse> {
+            let content = if self.reply == "{history}" {
+                request
+                    .messages
+                    .iter()
+                    .filter(|m| m.role == aof_core::model::MessageRole::User)
+                    .map(|m| m.content.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" | ")
+            } else {
+                self.reply.clone()
+            };
+
+            Ok(aof_core::ModelResponse {
+                content,
+                tool_calls: vec![],
+                stop_reason: aof_core::StopReason::EndTurn,
+                usage: aof_core::Usage {
+                    input_tokens: 10,
+                    output_tokens: 5,
+                },
+                stop_detail: None,
+                metadata: HashMap::new(),
+            })
+        }
+
+        async fn generate_stream(
+            &self,
+            _request: &aof_core::ModelRequest,
+        ) -> AofResult<
+            std::pin::Pin<
+                Box<dyn futures::Stream<Item = AofResult<aof_core::StreamChunk>> + Send>,
+            >,
+        > {
+            Ok(Box::pin(futures::stream::iter([
+                Ok(aof_core::StreamChunk::ContentDelta {
+                    delta: self.reply.clone(),
+                }),
+                Ok(aof_core::StreamChunk::Done {
+                    usage: aof_core::Usage::default(),
+                    stop_reason: aof_core::StopReason::EndTurn,
+                    stop_detail: None,
+                }),
+            ])))
+        }
+
+        fn config(&self) -> &ModelConfig {
+            &self.config
+        }
+
+        fn provider(&self) -> ModelProvider {
+            ModelProvider::Custom
+        }
+    }
+
+    fn runtime_with_fixed_agent(name: &str, reply: &str) -> Runtime {
+        let runtime = Runtime::new();
+        let executor = AgentExecutor::new(
+            ollama_config(name, 5),
+            Box::new(FixedModel::new(reply)),
+            None,
+            None,
+        );
+        runtime
+            .agents
+            .write()
+            .insert(name.to_string(), Arc::new(executor));
+        runtime
+    }
+
+    #[tokio::test]
+    async fn test_execute_with_snapshot_round_trip() {
+        let runtime = runtime_with_fixed_agent("chat", "{history}");
+        let (first, snapshot) = runtime
+            .execute_with_snapshot("chat", ContextSnapshot::default(), "disk alert on node-1")
+            .await
+            .unwrap();
+        assert_eq!(first, "disk alert on node-1");
+
+        // Resume in a fresh runtime from the serialized blob
+        let blob = serde_json::to_string(&snapshot).unwrap();
+        let runtime = runtime_with_fixed_agent("chat", "{history}");
+        let (second, snapshot) = runtime
+            .execute_with_snapshot("chat", serde_json::from_str(&blob).unwrap(), "what next?")
+            .await
+            .unwrap();
+
+        assert_eq!(second, "disk alert on node-1 | what next?");
+        assert_eq!(snapshot.messages.len(), 4);
+        assert_eq!(snapshot.metadata.input_tokens, 20);
+        assert_eq!(snapshot.metadata.output_tokens, 10);
+    }
+
+    #[tokio::test]
+    async fn test_list_and_resume_sessions() {
+        let mut runtime = Runtime::new();
+        runtime.add_agent(AgentExecutor::new(
+            ollama_config("chat", 5),
+            Box::new(FixedModel::new("{history}")),
+            None,
+            Some(Arc::new(SimpleMemory::in_memory())),
+        ));
+
+        for (session_id, input) in [
+            ("alice", "disk alert on node-1"),
+            ("bob", "pod crashloop"),
+            ("alice", "what next?"),
+        ] {
+            let mut context = AgentContext::new(input).with_session_id(session_id);
+            runtime
+                .execute_with_context("chat", &mut context)
+                .await
+                .unwrap();
+        }
+
+        let mut sessions = runtime.list_sessions("chat").await.unwrap();
+        sessions.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+        let counts: Vec<_> = sessions
+            .iter()
+            .map(|session| (session.session_id.as_str(), session.message_count))
+            .collect();
+        assert_eq!(counts, [("alice", 4), ("bob", 2)]);
+        assert!(sessions.iter().all(|session| session.last_updated > 0));
+
+        let reply = runtime
+            .resume_session("chat", "bob", "still failing")
+            .await
+            .unwrap();
+        assert_eq!(reply, "pod crashloop | still failing");
+
+        let err = runtime
+            .resume_session("chat", "carol", "hello")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Session not found"));
+        assert!(runtime.list_sessions("missing").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_execute_stream_ends_with_done() {
+        use tokio_stream::StreamExt;
+
+        let runtime = runtime_with_fixed_agent("chat", "streamed reply");
+        let events: Vec<StreamEvent> = runtime.execute_stream("chat", "hello").collect().await;
+
+        assert!(matches!(
+            events.first(),
+            Some(StreamEvent::IterationStart { iteration: 1, .. })
+        ));
+        assert!(events.iter().any(
+            |e| matches!(e, StreamEvent::TextDelta { delta, .. } if delta == "streamed reply")
+        ));
+        match events.last() {
+            Some(StreamEvent::Done { content, .. }) => assert_eq!(content, "streamed reply"),
+            other => panic!("expected Done last, got {:?}", other),
+        }
+
+        let events: Vec<StreamEvent> = runtime.execute_stream("missing", "hello").collect().await;
+        match events.as_slice() {
+            [StreamEvent::Error { message }] => assert_eq!(message, "Agent not found: missing"),
+            other => panic!("expected a single Error, got {:?}", other),
+        }
+    }
+
+    /// Model that delegates its input to `target`, then reports the answer
+    struct RouterModel {
+        target: String,
+        config: ModelConfig,
+    }
+
+    #[async_trait]
+    impl aof_core::Model for RouterModel {
+        async fn generate(
+            &self,
+            request: &aof_core::ModelRequest,
+        ) -> AofResult<aof_core::ModelResponse> {
+            let delegated = request
+                .messages
+                .iter()
+                .find(|m| m.role == aof_core::model::MessageRole::Tool);
+            let (content, tool_calls, stop_reason) = match delegated {
+                Some(answer) => (
+                    format!("Router: {}", answer.content),
+                    vec![],
+                    aof_core::StopReason::EndTurn,
+                ),
+                None => (
+                    String::new(),
+                    vec![aof_core::ToolCall {
+                        id: "delegate-1".to_string(),
+                        name: crate::executor::delegation::DELEGATE_TOOL.to_string(),
+                        arguments: serde_json::json!({
+                            "agent": self.target,
+                            "prompt": request.messages[0].content,
+                        }),
+                    }],
+                    aof_core::StopReason::ToolUse,
+                ),
+            };
+
+            Ok(aof_core::ModelResponse {
+                content,
+                tool_calls,
+                stop_reason,
+                usage: aof_core::Usage::default(),
+                stop_detail: None,
+                metadata: HashMap::new(),
+            })
+        }
+
+        async fn generate_stream(
+            &self,
+            _request: &aof_core::ModelRequest,
+        ) -> AofResult<
+            std::pin::Pin<
+                Box<dyn futures::Stream<Item = AofResult<aof_core::StreamChunk>> + Send>,
+            >,
+        > {
+            unimplemented!()
+        }
+
+        fn config(&self) -> &ModelConfig {
+            &self.config
+        }
+
+        fn provider(&self) -> ModelProvider {
+            ModelProvider::Custom
+        }
+    }
+
+    #[tokio::test]
+    async fn test_agent_delegates_to_another_agent() {
+        let runtime = runtime_with_fixed_agent("b", "{history} -> 3 pods crash looping");
+
+        let mut config = ollama_config("a", 5);
+        config.delegation = Some(FeatureToggle::Config(DelegationConfig { max_depth: 1 }));
+        let delegation =
+            DelegationToolExecutor::from_agent_config(&config, Arc::downgrade(&runtime.agents))
+                .unwrap();
+        let router = AgentExecutor::new(
+            config,
+            Box::new(RouterModel {
+                target: "b".to_string(),
+                config: FixedModel::new("").config,
+            }),
+            Some(Arc::new(delegation)),
+            None,
+        );
+        runtime
+            .agents
+            .write()
+            .insert("a".to_string(), Arc::new(router));
+
+        let output = runtime.execute("a", "check the payments pods").await.unwrap();
+        assert!(output.starts_with("Router: "), "{}", output);
+        assert!(output.contains("3 pods crash looping"), "{}", output);
+    }
+
+    #[tokio::test]
+    async fn test_delegation_stops_at_max_depth() {
+        let runtime = runtime_with_fixed_agent("b", "done");
+        let mut config = ollama_config("a", 5);
+        config.delegation = Some(FeatureToggle::Config(DelegationConfig { max_depth: 2 }));
+        let delegation =
+            DelegationToolExecutor::from_agent_config(&config, Arc::downgrade(&runtime.agents))
+                .unwrap();
+        let input = |depth: usize| {
+            ToolInput::with_context(
+                serde_json::json!({ "agent": "b", "prompt": "again" }),
+                HashMap::from([("delegation_depth".to_string(), depth.into())]),
+            )
+        };
+
+        let result = delegation
+            .execute_tool("delegate_to_agent", input(1))
+            .await
+            .unwrap();
+        assert_eq!(result.data, "done");
+
+        let err = delegation
+            .execute_tool("delegate_to_agent", input(2))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("depth limit (2) reached"), "{}", err);
+
+        // Without the flag no delegation tool is registered
+        assert!(DelegationToolExecutor::from_agent_config(
+            &ollama_config("c", 5),
+            Arc::downgrade(&runtime.agents)
+        )
+        .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_delegated_run_continues_the_callers_session() {
+        let mut runtime = Runtime::new();
+        runtime.add_agent(AgentExecutor::new(
+            ollama_config("b", 5),
+            Box::new(FixedModel::new("done")),
+            None,
+            Some(Arc::new(SimpleMemory::in_memory())),
+        ));
+        let mut config = ollama_config("a", 5);
+        config.delegation = Some(FeatureToggle::Enabled(true));
+        let delegation =
+            DelegationToolExecutor::from_agent_config(&config, Arc::downgrade(&runtime.agents))
+                .unwrap();
+
+        let input = ToolInput::with_context(
+            serde_json::json!({ "agent": "b", "prompt": "check the pods" }),
+            HashMap::from([
+                ("delegation_depth".to_string(), 0.into()),
+                ("session_id".to_string(), "incident-42".into()),
+                ("trace_id".to_string(), "trace-1".into()),
+            ]),
+        );
+        delegation
+            .execute_tool("delegate_to_agent", input)
+            .await
+            .unwrap();
+
+        let sessions = runtime.list_sessions("b").await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].session_id, "incident-42/b");
+    }
+
+    #[tokio::test]
+    async fn test_execute_all() {
+        let runtime = Runtime::new().with_max_concurrent_agents(1);
+        for (name, reply) in [("first", "from first"), ("second", "from second")] {
+            let executor = AgentExecutor::new(
+                ollama_config(name, 5),
+                Box::new(FixedModel::new(reply)),
+                None,
+                None,
+            );
+            runtime
+                .agents
+                .write()
+                .insert(name.to_string(), Arc::new(executor));
+        }
+
+        let names = vec![
+            "first".to_string(),
+            "second".to_string(),
+            "missing".to_string(),
+        ];
+        let results = runtime.execute_all(&names, "hello").await.unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results["first"].as_ref().unwrap(), "from first");
+        assert_eq!(results["second"].as_ref().unwrap(), "from second");
+        assert!(results["missing"].is_err());
+    }
+
+    fn shell_executor(config: SystemToolConfig) -> SystemToolExecutor {
+        SystemToolExecutor {
+            tool_names: vec!["shell".to_string()],
+            config,
+        }
+    }
+
+    fn shell_input(command: &str) -> ToolInput {
+        ToolInput::new(serde_json::json!({ "command": command }))
+    }
+
+    #[tokio::test]
+    async fn test_system_tool_echo() {
+        let executor = shell_executor(SystemToolConfig::default());
+
+        let result = executor
+            .execute_tool("shell", shell_input("echo hello"))
+            .await
+            .unwrap();
+
+        assert!(result.success);
+        assert_eq!(result.data["exit_code"], 0);
+        assert_eq!(result.data["stdout"], "hello\n");
+    }
+
+    #[tokio::test]
+    async fn test_system_tool_non_zero_exit() {
+        let executor = shell_executor(SystemToolConfig::default());
+
+        let result = executor
+            .execute_tool("shell", shell_input("echo oops >&2; exit 3"))
+            .await
+            .unwrap();
+
+        assert!(!result.success);
+        assert_eq!(result.data["exit_code"], 3);
+        assert!(result.error.unwrap().contains("oops"));
+    }
+
+    #[tokio::test]
+    async fn test_system_tool_limits() {
+        std::env::set_var("AOF_TEST_SECRET", "hunter2");
+        let executor = shell_executor(SystemToolConfig {
+            policy: CommandPolicy {
+                allowed_binaries: Some(["echo".to_string(), "sleep".to_string()].into()),
+                working_dir: Some(std::env::temp_dir()),
+                ..Default::default()
+            },
+            timeout: Duration::from_millis(200),
+            ..Default::default()
+        });
+
+        let result = executor
+            .execute_tool("shell", shell_input("echo \"[$AOF_TEST_SECRET]\""))
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert_eq!(result.data["stdout"], "[]\n");
+
+        let err = executor
+            .execute_tool("shell", shell_input("echo hi | cat /etc/passwd"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("binary 'cat' is not allowed"));
+
+        let result = executor
+            .execute_tool("shell", shell_input("sleep 5"))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("did not finish"));
+    }
+
+    #[tokio::test]
+    async fn test_system_tool_command_policy() {
+        let executor = shell_executor(SystemToolConfig {
+            policy: CommandPolicy {
+                allowed_binaries: Some(["printf".to_string()].into()),
+                max_output_bytes: Some(4),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+
+        let result = executor
+            .execute_tool("shell", shell_input("printf 'hello world'"))
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert_eq!(result.data["stdout"], "hell");
+        assert_eq!(result.data["truncated"], true);
+
+        // Violations fail the call itself, so the agent doesn't retry them
+        let err = executor
+            .execute_tool("shell", shell_input("rm -rf /tmp/aof"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AofError::Tool(_)));
+    }
+
+    /// Executor exposing fixed tools that echo which executor ran them
+    struct NamedToolExecutor {
+        label: &'static str,
+        tools: Vec<&'static str>,
+    }
+
+    #[async_trait]
+    impl ToolExecutor for NamedToolExecutor {
+        async fn execute_tool(
+            &self,
+            name: &str,
+            _input: ToolInput,
+        ) -> AofResult<aof_core::ToolResult> {
+            Ok(aof_core::ToolResult {
+                success: true,
+                data: serde_json::json!({ "executor": self.label, "tool": name }),
+                error: None,
+                execution_time_ms: 0,
+                ..Default::default()
+            })
+        }
+
+        fn list_tools(&self) -> Vec<ToolDefinition> {
+            self.tools
+                .iter()
+                .map(|name| ToolDefinition {
+                    name: name.to_string(),
+                    description: format!("{} tool", self.label),
+                    parameters: serde_json::json!({ "type": "object" }),
+                })
+                .collect()
+        }
+
+        fn get_tool(&self, _name: &str) -> Option<Arc<dyn Tool>> {
+            None
+        }
+    }
+
+    #[tokio::test]
+    async fn test_composite_tool_executor() {
+        let composite = CompositeToolExecutor::new(vec![
+            Arc::new(NamedToolExecutor { label: "system", tools: vec!["kubectl"] }),
+            Arc::new(NamedToolExecutor { label: "mcp", tools: vec!["search_logs"] }),
+        ])
+        .unwrap();
+
+        let names: Vec<String> = composite.list_tools().into_iter().map(|t| t.name).collect();
+        assert_eq!(names, vec!["kubectl", "search_logs"]);
+
+        let result = composite
+            .execute_tool("kubectl", ToolInput::new(serde_json::json!({})))
+            .await
+            .unwrap();
+        assert_eq!(result.data["executor"], "system");
+
+        let result = composite
+            .execute_tool("search_logs", ToolInput::new(serde_json::json!({})))
+            .await
+            .unwrap();
+        assert_eq!(result.data["executor"], "mcp");
+
+        assert!(composite
+            .execute_tool("unknown", ToolInput::new(serde_json::json!({})))
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_composite_tool_executor_rejects_duplicates() {
+        let result = CompositeToolExecutor::new(vec![
+            Arc::new(NamedToolExecutor { label: "system", tools: vec!["shell"] }),
+            Arc::new(NamedToolExecutor { label: "mcp", tools: vec!["shell"] }),
+        ]);
+
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_model_config_parsing() {
         let runtime = Runtime::new();
 
         let config = AgentConfig {
             name: "test-agent".to_string(),
-            system_prompt: None,
             model: "anthropic:claude-3-5-sonnet-20241022".to_string(),
-            tools: vec![],
-            memory: None,
-            max_iterations: 10,
-            temperature: 0.7,
-            max_tokens: None,
-            extra: HashMap::new(),
+            ..Default::default()
         };
 
         let model_config = runtime.create_model_config(&config).unwrap();
@@ -684,18 +2286,46 @@ mod tests {
 
         let config = AgentConfig {
             name: "test-agent".to_string(),
-            system_prompt: None,
             model: "gpt-4".to_string(),
-            tools: vec![],
-            memory: None,
-            max_iterations: 10,
-            temperature: 0.7,
-            max_tokens: None,
-            extra: HashMap::new(),
+            ..Default::default()
         };
 
         let model_config = runtime.create_model_config(&config).unwrap();
-        assert_eq!(model_config.provider, ModelProvider::Anthropic);
+        assert_eq!(model_config.provider, ModelProvider::OpenAI);
         assert_eq!(model_config.model, "gpt-4");
+
+        let config = AgentConfig {
+            model: "gemini-2.0-flash".to_string(),
+            ..config
+        };
+        let model_config = runtime.create_model_config(&config).unwrap();
+        assert_eq!(model_config.provider, ModelProvider::Google);
+
+        let config = AgentConfig {
+            model: "llama-3.3-70b-versatile".to_string(),
+            ..config
+        };
+        let model_config = runtime.create_model_config(&config).unwrap();
+        assert_eq!(model_config.provider, ModelProvider::Groq);
+
+        let config = AgentConfig {
+            model: "in-house-model".to_string(),
+            ..config
+        };
+        let model_config = runtime.create_model_config(&config).unwrap();
+        assert_eq!(model_config.provider, ModelProvider::Anthropic);
+    }
+
+    #[test]
+    fn test_model_config_passes_ollama_keep_alive() {
+        let runtime = Runtime::new();
+        let config: AgentConfig =
+            serde_yaml::from_str("name: local\nmodel: ollama:llama3.1:8b\nkeep_alive: 30m")
+                .unwrap();
+
+        let model_config = runtime.create_model_config(&config).unwrap();
+        assert_eq!(model_config.provider, ModelProvider::Ollama);
+        assert_eq!(model_config.model, "llama3.1:8b");
+        assert_eq!(model_config.extra["keep_alive"], "30m");
     }
 }
@@ -6,14 +6,31 @@
 //! - Context management
 //! - Error handling and recovery
 //! - Task orchestration
+//!
+//! With the `otel` feature, [`telemetry`] exports agent executions as
+//! OpenTelemetry spans.
 
 pub mod executor;
 pub mod orchestrator;
+pub mod replay;
 pub mod task;
+#[cfg(feature = "otel")]
+pub mod telemetry;
 
-pub use executor::{AgentExecutor, Runtime, StreamEvent};
-pub use orchestrator::RuntimeOrchestrator;
-pub use task::{Task, TaskHandle, TaskStatus};
+pub use executor::{
+    AgentExecutor, AgentHealth, CommandPolicy, ComponentHealth, CompositeToolExecutor,
+    ContextOverflowPolicy, EmptyResponsePolicy, HealthStatus, HistorySummarizer, PromptCache,
+    PromptCacheConfig, Runtime, RuntimeHealth, SessionInfo, StreamEvent, SystemToolConfig,
+    UnavailableTools, CONVERSATION_KEY, SYSTEM_TOOLS,
+};
+pub use orchestrator::{
+    OrchestratorStats, RuntimeOrchestrator, RuntimeOrchestratorBuilder, TaskRejected,
+};
+pub use replay::{
+    RecordingModel, RecordingToolExecutor, ReplayConfig, ReplayMode, ReplayModel,
+    ReplayToolExecutor,
+};
+pub use task::{ProgressReporter, Task, TaskHandle, TaskOutput, TaskProgress, TaskStatus};
 
 // Re-export core types
 pub use aof_core::{AofError, AofResult};
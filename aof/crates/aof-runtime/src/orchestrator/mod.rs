@@ -3,12 +3,16 @@
 //! Coordinates multiple tasks and agents, providing advanced scheduling
 //! and execution management capabilities.
 
-use crate::task::{Task, TaskHandle, TaskResult, TaskStatus};
+use crate::task::{ProgressReporter, Task, TaskHandle, TaskOutput, TaskResult, TaskStatus};
 use aof_core::{AofError, AofResult};
 use dashmap::DashMap;
-use std::sync::Arc;
-use tokio::sync::Semaphore;
-use tracing::{debug, info, warn};
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::{Mutex, Semaphore};
+use tracing::{debug, info, info_span, warn, Instrument};
 
 /// Runtime orchestrator for task management
 ///
@@ -26,6 +30,47 @@ pub struct RuntimeOrchestrator {
 
     /// Max concurrent tasks
     max_concurrent: usize,
+
+    /// Max pending + running tasks accepted by `try_submit_task`
+    max_queue_depth: Option<usize>,
+
+    /// Lifetime counters, unaffected by cleanup
+    counters: Arc<LifetimeCounters>,
+
+    /// Submission sequence number of each tracked task, for queue positions
+    submission_order: Arc<DashMap<String, u64>>,
+
+    /// How long finished tasks stay tracked before cleanup removes them
+    retention: Duration,
+
+    /// Held by `try_submit_task` from the depth check until the task is
+    /// tracked, so concurrent submitters can't overshoot `max_queue_depth`
+    admission: Mutex<()>,
+}
+
+/// Monotonic task counters kept for the lifetime of the orchestrator
+#[derive(Debug, Default)]
+struct LifetimeCounters {
+    submitted: AtomicU64,
+    completed: AtomicU64,
+    failed: AtomicU64,
+    cancelled: AtomicU64,
+    /// Sum of reported task costs (USD), stored as `f64` bits
+    cost_usd: AtomicU64,
+}
+
+impl LifetimeCounters {
+    fn add_cost(&self, cost_usd: f64) {
+        let _ = self
+            .cost_usd
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+                Some((f64::from_bits(bits) + cost_usd).to_bits())
+            });
+    }
+
+    fn cost_usd(&self) -> f64 {
+        f64::from_bits(self.cost_usd.load(Ordering::Relaxed))
+    }
 }
 
 impl RuntimeOrchestrator {
@@ -36,11 +81,19 @@ impl RuntimeOrchestrator {
 
     /// Create orchestrator with custom concurrency limit
     pub fn with_max_concurrent(max_concurrent: usize) -> Self {
-        Self {
-            tasks: Arc::new(DashMap::new()),
-            semaphore: Arc::new(Semaphore::new(max_concurrent)),
-            max_concurrent,
-        }
+        Self::builder().max_concurrent(max_concurrent).build()
+    }
+
+    /// Create an orchestrator builder
+    pub fn builder() -> RuntimeOrchestratorBuilder {
+        RuntimeOrchestratorBuilder::new()
+    }
+
+    /// Bound the number of pending + running tasks accepted by
+    /// [`try_submit_task`](Self::try_submit_task)
+    pub fn with_max_queue_depth(mut self, max_queue_depth: usize) -> Self {
+        self.max_queue_depth = Some(max_queue_depth);
+        self
     }
 
     /// Submit a task for execution
@@ -51,22 +104,101 @@ impl RuntimeOrchestrator {
         let handle = Arc::new(TaskHandle::new(task));
 
         self.tasks.insert(task_id.clone(), Arc::clone(&handle));
+        let sequence = self.counters.submitted.fetch_add(1, Ordering::Relaxed);
+        self.submission_order.insert(task_id.clone(), sequence);
         info!("Task submitted: {}", task_id);
 
         handle
     }
 
+    /// Submit a task unless the queue is at capacity
+    ///
+    /// Rejects the task when pending + running tasks already reach
+    /// `max_queue_depth`. Without a configured depth this behaves like
+    /// [`submit_task`](Self::submit_task).
+    pub async fn try_submit_task(&self, task: Task) -> Result<Arc<TaskHandle>, TaskRejected> {
+        if let Some(max_depth) = self.max_queue_depth {
+            let _admission = self.admission.lock().await;
+            let depth = self.queue_depth().await;
+            if depth >= max_depth {
+                warn!(
+                    "Task rejected: {} (queue depth {}/{})",
+                    task.id, depth, max_depth
+                );
+                return Err(TaskRejected {
+                    task_id: task.id,
+                    depth,
+                    max_depth,
+                });
+            }
+            return Ok(self.submit_task(task));
+        }
+
+        Ok(self.submit_task(task))
+    }
+
+    /// Number of tracked tasks that are pending or running
+    pub async fn queue_depth(&self) -> usize {
+        let mut depth = 0;
+
+        for entry in self.tasks.iter() {
+            if matches!(
+                entry.value().status().await,
+                TaskStatus::Pending | TaskStatus::Running
+            ) {
+                depth += 1;
+            }
+        }
+
+        depth
+    }
+
+    /// Execution slots not currently held by a running task
+    ///
+    /// Zero means newly executed tasks wait in the queue.
+    pub fn available_permits(&self) -> usize {
+        self.semaphore.available_permits()
+    }
+
+    /// 1-based position of a pending task among the pending tasks, oldest first
+    ///
+    /// Returns `None` when the task is unknown or no longer pending.
+    pub async fn queue_position(&self, task_id: &str) -> Option<usize> {
+        let handle = self.get_task(task_id)?;
+        if handle.status().await != TaskStatus::Pending {
+            return None;
+        }
+        let sequence = *self.submission_order.get(task_id)?;
+
+        let mut position = 1;
+        for entry in self.tasks.iter() {
+            let earlier = self
+                .submission_order
+                .get(entry.key())
+                .is_some_and(|other| *other < sequence);
+            if earlier && entry.value().status().await == TaskStatus::Pending {
+                position += 1;
+            }
+        }
+
+        Some(position)
+    }
+
     /// Execute a task asynchronously
     ///
-    /// This starts the task execution in the background
-    pub async fn execute_task<F, Fut>(
+    /// This starts the task execution in the background. The executor gets a
+    /// [`ProgressReporter`] it can use to publish intermediate progress, and
+    /// returns its output either as a `String` or as a [`TaskOutput`] whose
+    /// token usage and cost are recorded in the [`TaskResult`].
+    pub async fn execute_task<F, Fut, T>(
         &self,
         task_id: &str,
         executor: F,
     ) -> AofResult<Arc<TaskHandle>>
     where
-        F: FnOnce(Task) -> Fut + Send + 'static,
-        Fut: std::future::Future<Output = AofResult<String>> + Send + 'static,
+        F: FnOnce(Task, ProgressReporter) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = AofResult<T>> + Send + 'static,
+        T: Into<TaskOutput> + Send + 'static,
     {
         let handle = self
             .tasks
@@ -76,40 +208,87 @@ impl RuntimeOrchestrator {
 
         let semaphore = Arc::clone(&self.semaphore);
         let handle_clone: Arc<TaskHandle> = Arc::clone(&handle);
+        let counters = Arc::clone(&self.counters);
+        let span = info_span!("task", task_id = %task_id);
 
         // Spawn task execution
-        tokio::spawn(async move {
-            // Acquire semaphore permit
-            let _permit = semaphore.acquire().await.unwrap();
+        tokio::spawn(
+            async move {
+                let task = handle_clone.task().await;
+                let task_id = task.id.clone();
+                let progress = handle_clone.progress_reporter();
 
-            let task = handle_clone.task().await;
-            let task_id = task.id.clone();
+                // Acquire semaphore permit; it is released when this future ends
+                let _permit = match semaphore.acquire().await {
+                    Ok(permit) => permit,
+                    Err(_) => {
+                        let result = TaskResult::failure(
+                            task_id.clone(),
+                            "orchestrator is shutting down".to_string(),
+                        );
+                        handle_clone.set_result(result).await;
+                        counters.failed.fetch_add(1, Ordering::Relaxed);
+                        handle_clone.update_status(TaskStatus::Failed).await;
+                        warn!("Task failed: {} - semaphore closed", task_id);
+                        return;
+                    }
+                };
 
-            handle_clone.update_status(TaskStatus::Running).await;
-            debug!("Task started: {}", task_id);
+                // Cancelled while queued
+                if handle_clone.status().await == TaskStatus::Cancelled {
+                    debug!("Task cancelled before start: {}", task_id);
+                    return;
+                }
 
-            let start = std::time::Instant::now();
+                handle_clone.update_status(TaskStatus::Running).await;
+                debug!("Task started: {}", task_id);
 
-            // Execute task
-            match executor(task).await {
-                Ok(output) => {
-                    let result = TaskResult::success(task_id.clone(), output)
-                        .with_execution_time(start.elapsed().as_millis() as u64);
+                let start = std::time::Instant::now();
 
-                    handle_clone.set_result(result).await;
-                    handle_clone.update_status(TaskStatus::Completed).await;
-                    info!("Task completed: {}", task_id);
-                }
-                Err(e) => {
-                    let result = TaskResult::failure(task_id.clone(), e.to_string())
-                        .with_execution_time(start.elapsed().as_millis() as u64);
+                // Execute task on its own tokio task so a panic surfaces as a
+                // JoinError instead of leaving the task stuck in Running
+                let outcome =
+                    tokio::spawn(async move { executor(task, progress).await }.in_current_span())
+                        .await;
+                let execution_time = start.elapsed().as_millis() as u64;
 
-                    handle_clone.set_result(result).await;
-                    handle_clone.update_status(TaskStatus::Failed).await;
-                    warn!("Task failed: {} - {}", task_id, e);
+                // A cancelled task keeps the result `cancel_task` recorded
+                if handle_clone.status().await == TaskStatus::Cancelled {
+                    debug!("Cancelled task stopped: {}", task_id);
+                    return;
                 }
+
+                let error = match outcome {
+                    Ok(Ok(output)) => {
+                        let output: TaskOutput = output.into();
+                        let mut result = TaskResult::success(task_id.clone(), output.output)
+                            .with_execution_time(execution_time)
+                            .with_tokens(output.usage.input_tokens, output.usage.output_tokens);
+                        if let Some(cost_usd) = output.cost_usd {
+                            result = result.with_cost(cost_usd);
+                            counters.add_cost(cost_usd);
+                        }
+
+                        handle_clone.set_result(result).await;
+                        counters.completed.fetch_add(1, Ordering::Relaxed);
+                        handle_clone.update_status(TaskStatus::Completed).await;
+                        info!("Task completed: {}", task_id);
+                        return;
+                    }
+                    Ok(Err(e)) => e.to_string(),
+                    Err(join_error) => join_error_message(join_error),
+                };
+
+                let result = TaskResult::failure(task_id.clone(), error.clone())
+                    .with_execution_time(execution_time);
+
+                handle_clone.set_result(result).await;
+                counters.failed.fetch_add(1, Ordering::Relaxed);
+                handle_clone.update_status(TaskStatus::Failed).await;
+                warn!("Task failed: {} - {}", task_id, error);
             }
-        });
+            .instrument(span),
+        );
 
         Ok(handle)
     }
@@ -150,6 +329,7 @@ impl RuntimeOrchestrator {
                     "Task cancelled by user".to_string(),
                 );
                 handle.set_result(result).await;
+                self.counters.cancelled.fetch_add(1, Ordering::Relaxed);
                 info!("Task cancelled: {}", task_id);
                 Ok(())
             } else {
@@ -163,26 +343,11 @@ impl RuntimeOrchestrator {
         }
     }
 
-    /// Remove completed/failed tasks from tracking
+    /// Remove finished tasks from tracking
+    ///
+    /// Tasks that finished within the configured retention window are kept.
     pub async fn cleanup_finished_tasks(&self) {
-        let mut to_remove = Vec::new();
-
-        for entry in self.tasks.iter() {
-            let handle = entry.value();
-            let status = handle.status().await;
-
-            if matches!(
-                status,
-                TaskStatus::Completed | TaskStatus::Failed | TaskStatus::Cancelled
-            ) {
-                to_remove.push(entry.key().clone());
-            }
-        }
-
-        for task_id in to_remove {
-            self.tasks.remove(&task_id);
-            debug!("Cleaned up task: {}", task_id);
-        }
+        cleanup_finished(&self.tasks, &self.submission_order, self.retention).await;
     }
 
     /// Get orchestrator statistics
@@ -202,6 +367,11 @@ impl RuntimeOrchestrator {
 
         stats.max_concurrent = self.max_concurrent;
         stats.available_permits = self.semaphore.available_permits();
+        stats.total_submitted = self.counters.submitted.load(Ordering::Relaxed);
+        stats.total_completed = self.counters.completed.load(Ordering::Relaxed);
+        stats.total_failed = self.counters.failed.load(Ordering::Relaxed);
+        stats.total_cancelled = self.counters.cancelled.load(Ordering::Relaxed);
+        stats.total_cost_usd = self.counters.cost_usd();
 
         stats
     }
@@ -213,8 +383,174 @@ impl Default for RuntimeOrchestrator {
     }
 }
 
+/// Orchestrator builder
+pub struct RuntimeOrchestratorBuilder {
+    max_concurrent: usize,
+    max_queue_depth: Option<usize>,
+    cleanup_interval: Option<Duration>,
+    retention: Duration,
+}
+
+impl RuntimeOrchestratorBuilder {
+    /// Create a new builder
+    pub fn new() -> Self {
+        Self {
+            max_concurrent: 10,
+            max_queue_depth: None,
+            cleanup_interval: None,
+            retention: Duration::ZERO,
+        }
+    }
+
+    /// Set how many tasks may run at once (default: 10)
+    pub fn max_concurrent(mut self, max_concurrent: usize) -> Self {
+        self.max_concurrent = max_concurrent;
+        self
+    }
+
+    /// Bound the pending + running tasks accepted by `try_submit_task`
+    pub fn max_queue_depth(mut self, max_queue_depth: usize) -> Self {
+        self.max_queue_depth = Some(max_queue_depth);
+        self
+    }
+
+    /// Drop finished tasks from tracking every `interval` (default: off)
+    pub fn cleanup_interval(mut self, interval: Duration) -> Self {
+        self.cleanup_interval = Some(interval);
+        self
+    }
+
+    /// Keep finished tasks queryable for `retention` before cleanup drops
+    /// them (default: none)
+    pub fn retention(mut self, retention: Duration) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// Build the orchestrator
+    ///
+    /// With a cleanup interval this starts the cleanup loop, which ends when
+    /// the orchestrator is dropped. Outside a Tokio runtime there is no loop
+    /// and finished tasks are only dropped by `cleanup_finished_tasks`.
+    pub fn build(self) -> RuntimeOrchestrator {
+        let orchestrator = RuntimeOrchestrator {
+            tasks: Arc::new(DashMap::new()),
+            semaphore: Arc::new(Semaphore::new(self.max_concurrent)),
+            max_concurrent: self.max_concurrent,
+            max_queue_depth: self.max_queue_depth,
+            counters: Arc::new(LifetimeCounters::default()),
+            submission_order: Arc::new(DashMap::new()),
+            retention: self.retention,
+            admission: Mutex::new(()),
+        };
+
+        if let Some(interval) = self.cleanup_interval {
+            match tokio::runtime::Handle::try_current() {
+                Ok(handle) => {
+                    handle.spawn(cleanup_periodically(
+                        Arc::downgrade(&orchestrator.tasks),
+                        Arc::downgrade(&orchestrator.submission_order),
+                        interval,
+                        self.retention,
+                    ));
+                }
+                Err(_) => {
+                    warn!("No Tokio runtime; finished tasks will not be cleaned up periodically")
+                }
+            }
+        }
+
+        orchestrator
+    }
+}
+
+impl Default for RuntimeOrchestratorBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Stop tracking tasks that finished more than `retention` ago
+async fn cleanup_finished(
+    tasks: &DashMap<String, Arc<TaskHandle>>,
+    submission_order: &DashMap<String, u64>,
+    retention: Duration,
+) {
+    let mut to_remove = Vec::new();
+
+    for entry in tasks.iter() {
+        let handle = entry.value();
+        if !handle.status().await.is_finished() {
+            continue;
+        }
+
+        let expired = handle
+            .completed_at()
+            .await
+            .map_or(true, |completed_at| completed_at.elapsed() >= retention);
+        if expired {
+            to_remove.push(entry.key().clone());
+        }
+    }
+
+    for task_id in to_remove {
+        tasks.remove(&task_id);
+        submission_order.remove(&task_id);
+        debug!("Cleaned up task: {}", task_id);
+    }
+}
+
+async fn cleanup_periodically(
+    tasks: Weak<DashMap<String, Arc<TaskHandle>>>,
+    submission_order: Weak<DashMap<String, u64>>,
+    interval: Duration,
+    retention: Duration,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.tick().await;
+
+    loop {
+        ticker.tick().await;
+        let (Some(tasks), Some(submission_order)) = (tasks.upgrade(), submission_order.upgrade())
+        else {
+            break;
+        };
+        cleanup_finished(&tasks, &submission_order, retention).await;
+    }
+}
+
+/// Describe why a spawned executor did not return a result
+fn join_error_message(error: tokio::task::JoinError) -> String {
+    if !error.is_panic() {
+        return "task was aborted".to_string();
+    }
+
+    let payload = error.into_panic();
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic payload".to_string());
+    format!("task panicked: {}", message)
+}
+
+/// Returned by [`RuntimeOrchestrator::try_submit_task`] when the queue is full
+#[derive(Debug, Clone, Error)]
+#[error("Task {task_id} rejected: queue depth {depth} reached limit {max_depth}")]
+pub struct TaskRejected {
+    /// ID of the rejected task
+    pub task_id: String,
+    /// Pending + running tasks at the time of submission
+    pub depth: usize,
+    /// Configured `max_queue_depth`
+    pub max_depth: usize,
+}
+
 /// Orchestrator statistics
-#[derive(Debug, Clone, Default)]
+///
+/// `pending` through `cancelled` count currently-tracked tasks; the `total_*`
+/// fields are lifetime counters that survive [`RuntimeOrchestrator::cleanup_finished_tasks`].
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct OrchestratorStats {
     pub pending: usize,
     pub running: usize,
@@ -223,11 +559,22 @@ pub struct OrchestratorStats {
     pub cancelled: usize,
     pub max_concurrent: usize,
     pub available_permits: usize,
+    /// Estimated cost (USD) of the tasks that completed since creation
+    pub total_cost_usd: f64,
+    /// Tasks submitted since the orchestrator was created
+    pub total_submitted: u64,
+    /// Tasks that completed successfully since creation
+    pub total_completed: u64,
+    /// Tasks that failed since creation
+    pub total_failed: u64,
+    /// Tasks cancelled since creation
+    pub total_cancelled: u64,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::time::Duration;
 
     #[tokio::test]
     async fn test_orchestrator_creation() {
@@ -271,7 +618,7 @@ mod tests {
         let handle = orchestrator.submit_task(task);
 
         orchestrator
-            .execute_task("task-1", |_task| async {
+            .execute_task("task-1", |_task, _progress| async {
                 tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
                 Ok("Success!".to_string())
             })
@@ -285,6 +632,440 @@ mod tests {
         assert_eq!(status, TaskStatus::Completed);
     }
 
+    #[tokio::test]
+    async fn test_execute_task_records_usage() {
+        let orchestrator = RuntimeOrchestrator::new();
+        let handle = orchestrator.submit_task(Task::new(
+            "task-1".to_string(),
+            "Test Task".to_string(),
+            "test-agent".to_string(),
+            "test input".to_string(),
+        ));
+
+        orchestrator
+            .execute_task("task-1", |_task, _progress| async {
+                let metadata = aof_core::ExecutionMetadata {
+                    input_tokens: 1200,
+                    output_tokens: 300,
+                    cost_usd: Some(0.0081),
+                    ..Default::default()
+                };
+                Ok(TaskOutput::from_metadata("Success!".to_string(), &metadata))
+            })
+            .await
+            .unwrap();
+
+        let result = handle
+            .wait_with_timeout(Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert_eq!(result.output, "Success!");
+        assert_eq!(result.input_tokens, 1200);
+        assert_eq!(result.output_tokens, 300);
+        assert_eq!(result.cost_usd, Some(0.0081));
+    }
+
+    #[tokio::test]
+    async fn test_try_submit_task_rejects_when_full() {
+        let orchestrator = RuntimeOrchestrator::new().with_max_queue_depth(2);
+
+        let make_task = |id: &str| {
+            Task::new(
+                id.to_string(),
+                "Test Task".to_string(),
+                "test-agent".to_string(),
+                "test input".to_string(),
+            )
+        };
+
+        orchestrator
+            .try_submit_task(make_task("task-1"))
+            .await
+            .unwrap();
+        orchestrator
+            .try_submit_task(make_task("task-2"))
+            .await
+            .unwrap();
+
+        let Err(rejected) = orchestrator.try_submit_task(make_task("task-3")).await else {
+            panic!("expected task-3 to be rejected");
+        };
+        assert_eq!(rejected.task_id, "task-3");
+        assert_eq!(rejected.depth, 2);
+        assert_eq!(rejected.max_depth, 2);
+        assert!(orchestrator.get_task("task-3").is_none());
+
+        // Finishing a task frees a slot
+        orchestrator.cancel_task("task-1").await.unwrap();
+        assert!(orchestrator
+            .try_submit_task(make_task("task-3"))
+            .await
+            .is_ok());
+
+        // The unbounded path is unaffected
+        orchestrator.submit_task(make_task("task-4"));
+        assert_eq!(orchestrator.queue_depth().await, 3);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_try_submit_task_never_exceeds_depth_concurrently() {
+        let orchestrator = Arc::new(RuntimeOrchestrator::new().with_max_queue_depth(5));
+
+        let submitters: Vec<_> = (0..50)
+            .map(|i| {
+                let orchestrator = Arc::clone(&orchestrator);
+                tokio::spawn(async move {
+                    orchestrator
+                        .try_submit_task(Task::new(
+                            format!("task-{}", i),
+                            "Test Task".to_string(),
+                            "test-agent".to_string(),
+                            "test input".to_string(),
+                        ))
+                        .await
+                        .is_ok()
+                })
+            })
+            .collect();
+
+        let mut accepted = 0;
+        for submitter in submitters {
+            if submitter.await.unwrap() {
+                accepted += 1;
+            }
+        }
+        assert_eq!(accepted, 5);
+        assert_eq!(orchestrator.queue_depth().await, 5);
+    }
+
+    #[tokio::test]
+    async fn test_queue_position_counts_earlier_pending_tasks() {
+        let orchestrator = RuntimeOrchestrator::with_max_concurrent(1);
+        for id in ["task-1", "task-2", "task-3"] {
+            orchestrator.submit_task(Task::new(
+                id.to_string(),
+                "Test Task".to_string(),
+                "test-agent".to_string(),
+                "test input".to_string(),
+            ));
+        }
+
+        // task-1 takes the only slot; the others wait behind it
+        let (release, released) = tokio::sync::oneshot::channel::<()>();
+        let running = orchestrator
+            .execute_task("task-1", |_task, _progress| async move {
+                let _ = released.await;
+                Ok("done".to_string())
+            })
+            .await
+            .unwrap();
+        while running.status().await != TaskStatus::Running {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(orchestrator.available_permits(), 0);
+
+        assert_eq!(orchestrator.queue_position("task-1").await, None);
+        assert_eq!(orchestrator.queue_position("task-2").await, Some(1));
+        assert_eq!(orchestrator.queue_position("task-3").await, Some(2));
+        assert_eq!(orchestrator.queue_position("missing").await, None);
+
+        orchestrator.cancel_task("task-2").await.unwrap();
+        assert_eq!(orchestrator.queue_position("task-3").await, Some(1));
+        release.send(()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_builder_limits_concurrency() {
+        let orchestrator = RuntimeOrchestrator::builder()
+            .max_concurrent(2)
+            .max_queue_depth(5)
+            .build();
+        let running = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let peak = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let (release_tx, _) = tokio::sync::broadcast::channel::<()>(1);
+
+        let mut handles = Vec::new();
+        for id in ["task-1", "task-2", "task-3"] {
+            orchestrator
+                .try_submit_task(Task::new(
+                    id.to_string(),
+                    "Test Task".to_string(),
+                    "test-agent".to_string(),
+                    "test input".to_string(),
+                ))
+                .await
+                .unwrap();
+
+            let running = Arc::clone(&running);
+            let peak = Arc::clone(&peak);
+            let mut release = release_tx.subscribe();
+            let handle = orchestrator
+                .execute_task(id, move |_task, _progress| async move {
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    let _ = release.recv().await;
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    Ok("done".to_string())
+                })
+                .await
+                .unwrap();
+            handles.push(handle);
+        }
+
+        while running.load(Ordering::SeqCst) < 2 {
+            tokio::task::yield_now().await;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // Two tasks hold the slots; the third waits for one
+        assert_eq!(orchestrator.available_permits(), 0);
+        assert_eq!(handles[2].status().await, TaskStatus::Pending);
+        assert_eq!(orchestrator.queue_position("task-3").await, Some(1));
+
+        // The third task was already subscribed, so one release frees all
+        release_tx.send(()).unwrap();
+        for handle in &handles {
+            assert!(handle.wait().await.unwrap().success);
+        }
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_builder_cleans_up_finished_tasks() {
+        let orchestrator = RuntimeOrchestrator::builder()
+            .cleanup_interval(Duration::from_millis(20))
+            .build();
+        orchestrator.submit_task(Task::new(
+            "task-1".to_string(),
+            "Test Task".to_string(),
+            "test-agent".to_string(),
+            "test input".to_string(),
+        ));
+        let handle = orchestrator
+            .execute_task("task-1", |_task, _progress| async {
+                Ok("done".to_string())
+            })
+            .await
+            .unwrap();
+        handle.wait().await.unwrap();
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        assert!(orchestrator.list_tasks().is_empty());
+        assert_eq!(orchestrator.stats().await.total_completed, 1);
+    }
+
+    #[test]
+    fn test_builder_outside_runtime_skips_cleanup_loop() {
+        let orchestrator = RuntimeOrchestrator::builder()
+            .cleanup_interval(Duration::from_millis(20))
+            .build();
+        assert!(orchestrator.list_tasks().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_keeps_tasks_within_retention() {
+        let orchestrator = RuntimeOrchestrator::builder()
+            .retention(Duration::from_millis(200))
+            .build();
+        for id in ["old", "fresh"] {
+            orchestrator.submit_task(Task::new(
+                id.to_string(),
+                "Test Task".to_string(),
+                "test-agent".to_string(),
+                "test input".to_string(),
+            ));
+        }
+
+        let old = orchestrator.get_task("old").unwrap();
+        old.update_status(TaskStatus::Completed).await;
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        let fresh = orchestrator.get_task("fresh").unwrap();
+        fresh.update_status(TaskStatus::Completed).await;
+
+        orchestrator.cleanup_finished_tasks().await;
+
+        assert!(orchestrator.get_task("old").is_none());
+        assert!(orchestrator.get_task("fresh").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_lifetime_counters_survive_cleanup() {
+        let orchestrator = RuntimeOrchestrator::new();
+
+        let task = Task::new(
+            "task-1".to_string(),
+            "Test Task".to_string(),
+            "test-agent".to_string(),
+            "test input".to_string(),
+        );
+
+        let handle = orchestrator.submit_task(task);
+        orchestrator
+            .execute_task("task-1", |_task, _progress| async {
+                Ok("Success!".to_string())
+            })
+            .await
+            .unwrap();
+
+        for _ in 0..50 {
+            if handle.status().await == TaskStatus::Completed {
+                break;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(handle.status().await, TaskStatus::Completed);
+
+        orchestrator.cleanup_finished_tasks().await;
+
+        let stats = orchestrator.stats().await;
+        assert_eq!(stats.completed, 0);
+        assert_eq!(stats.total_submitted, 1);
+        assert_eq!(stats.total_completed, 1);
+        assert_eq!(stats.total_failed, 0);
+
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["total_completed"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_panicking_executor_fails_task() {
+        let orchestrator = RuntimeOrchestrator::with_max_concurrent(1);
+
+        for id in ["task-1", "task-2"] {
+            orchestrator.submit_task(Task::new(
+                id.to_string(),
+                "Test Task".to_string(),
+                "test-agent".to_string(),
+                "test input".to_string(),
+            ));
+        }
+
+        let handle = orchestrator
+            .execute_task::<_, _, String>("task-1", |_task, _progress| async {
+                panic!("executor blew up");
+            })
+            .await
+            .unwrap();
+
+        let result = handle
+            .wait_with_timeout(Duration::from_secs(2))
+            .await
+            .unwrap();
+        assert_eq!(handle.status().await, TaskStatus::Failed);
+        assert!(!result.success);
+        assert_eq!(
+            result.error.as_deref(),
+            Some("task panicked: executor blew up")
+        );
+
+        // The permit was released, so the next task still runs
+        let next = orchestrator
+            .execute_task("task-2", |_task, _progress| async {
+                Ok("Success!".to_string())
+            })
+            .await
+            .unwrap();
+        let result = next
+            .wait_with_timeout(Duration::from_secs(2))
+            .await
+            .unwrap();
+        assert!(result.success);
+
+        let stats = orchestrator.stats().await;
+        assert_eq!(stats.total_failed, 1);
+        assert_eq!(stats.total_completed, 1);
+        assert_eq!(stats.available_permits, 1);
+    }
+
+    #[tokio::test]
+    async fn test_execute_task_reports_progress() {
+        let orchestrator = RuntimeOrchestrator::new();
+
+        let task = Task::new(
+            "task-1".to_string(),
+            "Test Task".to_string(),
+            "test-agent".to_string(),
+            "test input".to_string(),
+        );
+
+        let handle = orchestrator.submit_task(task);
+
+        let reader_handle = Arc::clone(&handle);
+        let reader = tokio::spawn(async move {
+            let mut observed = Vec::new();
+            loop {
+                let percent = reader_handle.progress().await.percent;
+                if observed.last() != Some(&percent) {
+                    observed.push(percent);
+                }
+                if percent == 100 {
+                    return observed;
+                }
+                tokio::time::sleep(tokio::time::Duration::from_millis(5)).await;
+            }
+        });
+
+        orchestrator
+            .execute_task("task-1", |_task, progress| async move {
+                for (percent, step) in [(25, "fetching"), (50, "analyzing"), (100, "done")] {
+                    progress.report_progress(percent, step).await;
+                    tokio::time::sleep(tokio::time::Duration::from_millis(30)).await;
+                }
+                Ok("Success!".to_string())
+            })
+            .await
+            .unwrap();
+
+        let observed = reader.await.unwrap();
+        assert!(observed.windows(2).all(|w| w[0] < w[1]));
+        assert!(observed.contains(&25));
+        assert!(observed.contains(&50));
+        assert_eq!(observed.last(), Some(&100));
+
+        handle.wait().await.unwrap();
+        assert_eq!(handle.progress().await.message, "done");
+    }
+
+    #[tokio::test]
+    async fn test_stats_accumulate_cost() {
+        let orchestrator = RuntimeOrchestrator::new();
+
+        for (id, cost) in [("task-1", 0.25), ("task-2", 0.5)] {
+            let handle = orchestrator.submit_task(Task::new(
+                id.to_string(),
+                "Test Task".to_string(),
+                "test-agent".to_string(),
+                "test input".to_string(),
+            ));
+            orchestrator
+                .execute_task(id, move |_task, _progress| async move {
+                    let metadata = aof_core::ExecutionMetadata {
+                        cost_usd: Some(cost),
+                        ..Default::default()
+                    };
+                    Ok(TaskOutput::from_metadata("done".to_string(), &metadata))
+                })
+                .await
+                .unwrap();
+            handle
+                .wait_with_timeout(Duration::from_secs(5))
+                .await
+                .unwrap();
+        }
+
+        let stats = orchestrator.stats().await;
+        assert_eq!(stats.completed, 2);
+        assert!((stats.total_cost_usd - 0.75).abs() < 1e-9);
+
+        // The cost outlives the tasks it was reported for
+        orchestrator.cleanup_finished_tasks().await;
+        let stats = orchestrator.stats().await;
+        assert_eq!(stats.completed, 0);
+        assert!((stats.total_cost_usd - 0.75).abs() < 1e-9);
+    }
+
     #[tokio::test]
     async fn test_cancel_task() {
         let orchestrator = RuntimeOrchestrator::new();
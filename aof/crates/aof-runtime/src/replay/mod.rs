@@ -0,0 +1,654 @@
+//! Deterministic record/replay of agent executions
+//!
+//! In record mode [`RecordingModel`] and [`RecordingToolExecutor`] wrap the
+//! real model and tools and append every response to a JSONL cassette. In
+//! replay mode [`ReplayModel`] and [`ReplayToolExecutor`] answer from that
+//! cassette in recorded order, ignoring the actual request, so a run can be
+//! reproduced without network access or side effects.
+
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use aof_core::{
+    AofError, AofResult, Model, ModelConfig, ModelProvider, ModelRequest, ModelResponse,
+    StreamChunk, Tool, ToolDefinition, ToolExecutor, ToolInput, ToolResult,
+};
+use async_trait::async_trait;
+use futures::{Stream, StreamExt};
+use parking_lot::Mutex;
+use serde::de::Error as _;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+type ChunkStream = Pin<Box<dyn Stream<Item = AofResult<StreamChunk>> + Send>>;
+
+/// Whether a run is recorded to or replayed from a cassette
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReplayMode {
+    /// Call the real model and tools and record their responses
+    Record,
+    /// Answer from a previously recorded cassette
+    Replay,
+}
+
+/// Record/replay settings for a [`Runtime`](crate::Runtime)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayConfig {
+    /// Record or replay
+    pub mode: ReplayMode,
+
+    /// Path of the JSONL cassette
+    pub cassette: PathBuf,
+}
+
+impl ReplayConfig {
+    /// Record responses to `cassette`, overwriting it
+    pub fn record(cassette: impl Into<PathBuf>) -> Self {
+        Self {
+            mode: ReplayMode::Record,
+            cassette: cassette.into(),
+        }
+    }
+
+    /// Replay responses from `cassette`
+    pub fn replay(cassette: impl Into<PathBuf>) -> Self {
+        Self {
+            mode: ReplayMode::Replay,
+            cassette: cassette.into(),
+        }
+    }
+}
+
+/// One recorded response; each is written as a line of the cassette
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CassetteEntry {
+    /// Result of `Model::generate`
+    Model {
+        response: Result<ModelResponse, RecordedError>,
+    },
+    /// Chunks produced by `Model::generate_stream`
+    Stream {
+        chunks: Vec<Result<StreamChunk, RecordedError>>,
+    },
+    /// Result of `ToolExecutor::execute_tool`
+    Tool {
+        name: String,
+        result: Result<ToolResult, RecordedError>,
+    },
+}
+
+/// An [`AofError`] as stored in a cassette
+///
+/// Keeps the variant and its fields so replay fails with the same error the
+/// recorded run saw, e.g. a rate limit the executor backs off from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordedError {
+    Agent(String),
+    Model(String),
+    RateLimited { retry_after: Option<Duration> },
+    Http { status: u16, message: String },
+    ContextOverflow(String),
+    Tool(String),
+    Memory(String),
+    Mcp(String),
+    Config(String),
+    Serialization(String),
+    Yaml(String),
+    Io(String),
+    InvalidState(String),
+    Timeout(String),
+    ResourceExhausted(String),
+    Unknown(String),
+}
+
+impl From<&AofError> for RecordedError {
+    fn from(error: &AofError) -> Self {
+        match error {
+            AofError::Agent(m) => Self::Agent(m.clone()),
+            AofError::Model(m) => Self::Model(m.clone()),
+            AofError::RateLimited { retry_after } => Self::RateLimited {
+                retry_after: *retry_after,
+            },
+            AofError::Http { status, message } => Self::Http {
+                status: *status,
+                message: message.clone(),
+            },
+            AofError::ContextOverflow(m) => Self::ContextOverflow(m.clone()),
+            AofError::Tool(m) => Self::Tool(m.clone()),
+            AofError::Memory(m) => Self::Memory(m.clone()),
+            AofError::Mcp(m) => Self::Mcp(m.clone()),
+            AofError::Config(m) => Self::Config(m.clone()),
+            AofError::Serialization(e) => Self::Serialization(e.to_string()),
+            AofError::Yaml(e) => Self::Yaml(e.to_string()),
+            AofError::Io(e) => Self::Io(e.to_string()),
+            AofError::InvalidState(m) => Self::InvalidState(m.clone()),
+            AofError::Timeout(m) => Self::Timeout(m.clone()),
+            AofError::ResourceExhausted(m) => Self::ResourceExhausted(m.clone()),
+            AofError::Unknown(m) => Self::Unknown(m.clone()),
+        }
+    }
+}
+
+impl From<RecordedError> for AofError {
+    fn from(error: RecordedError) -> Self {
+        match error {
+            RecordedError::Agent(m) => Self::Agent(m),
+            RecordedError::Model(m) => Self::Model(m),
+            RecordedError::RateLimited { retry_after } => Self::RateLimited { retry_after },
+            RecordedError::Http { status, message } => Self::Http { status, message },
+            RecordedError::ContextOverflow(m) => Self::ContextOverflow(m),
+            RecordedError::Tool(m) => Self::Tool(m),
+            RecordedError::Memory(m) => Self::Memory(m),
+            RecordedError::Mcp(m) => Self::Mcp(m),
+            RecordedError::Config(m) => Self::Config(m),
+            RecordedError::Serialization(m) => Self::Serialization(serde_json::Error::custom(m)),
+            RecordedError::Yaml(m) => Self::Yaml(serde_yaml::Error::custom(m)),
+            RecordedError::Io(m) => Self::Io(std::io::Error::other(m)),
+            RecordedError::InvalidState(m) => Self::InvalidState(m),
+            RecordedError::Timeout(m) => Self::Timeout(m),
+            RecordedError::ResourceExhausted(m) => Self::ResourceExhausted(m),
+            RecordedError::Unknown(m) => Self::Unknown(m),
+        }
+    }
+}
+
+/// Append-only cassette written in record mode
+pub struct CassetteWriter {
+    file: Mutex<File>,
+}
+
+impl CassetteWriter {
+    /// Create (or truncate) the cassette at `path`
+    pub fn create(path: impl AsRef<Path>) -> AofResult<Self> {
+        Ok(Self {
+            file: Mutex::new(File::create(path)?),
+        })
+    }
+
+    /// Append `entry` as one JSON line
+    pub fn append(&self, entry: &CassetteEntry) -> AofResult<()> {
+        let line = serde_json::to_string(entry)?;
+        let mut file = self.file.lock();
+        writeln!(file, "{}", line)?;
+        file.flush()?;
+        Ok(())
+    }
+}
+
+/// Recorded responses consumed in order in replay mode
+///
+/// Model responses are replayed in recording order; tool results are queued
+/// per tool name so parallel tool calls replay regardless of completion order.
+pub struct CassetteReader {
+    model: Mutex<VecDeque<Result<ModelResponse, RecordedError>>>,
+    streams: Mutex<VecDeque<Vec<Result<StreamChunk, RecordedError>>>>,
+    tools: Mutex<HashMap<String, VecDeque<Result<ToolResult, RecordedError>>>>,
+}
+
+impl CassetteReader {
+    /// Load the cassette at `path`
+    pub fn open(path: impl AsRef<Path>) -> AofResult<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| {
+            AofError::config(format!("Failed to open cassette {}: {}", path.display(), e))
+        })?;
+
+        let mut model = VecDeque::new();
+        let mut streams = VecDeque::new();
+        let mut tools: HashMap<String, VecDeque<_>> = HashMap::new();
+
+        for (index, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: CassetteEntry = serde_json::from_str(&line).map_err(|e| {
+                AofError::config(format!(
+                    "Invalid cassette entry at {}:{}: {}",
+                    path.display(),
+                    index + 1,
+                    e
+                ))
+            })?;
+            match entry {
+                CassetteEntry::Model { response } => model.push_back(response),
+                CassetteEntry::Stream { chunks } => streams.push_back(chunks),
+                CassetteEntry::Tool { name, result } => {
+                    tools.entry(name).or_default().push_back(result)
+                }
+            }
+        }
+
+        Ok(Self {
+            model: Mutex::new(model),
+            streams: Mutex::new(streams),
+            tools: Mutex::new(tools),
+        })
+    }
+
+    fn next_model(&self) -> AofResult<ModelResponse> {
+        self.model
+            .lock()
+            .pop_front()
+            .ok_or_else(|| {
+                AofError::InvalidState("Replay cassette has no more model responses".into())
+            })?
+            .map_err(AofError::from)
+    }
+
+    fn next_stream(&self) -> AofResult<Vec<Result<StreamChunk, RecordedError>>> {
+        self.streams.lock().pop_front().ok_or_else(|| {
+            AofError::InvalidState("Replay cassette has no more model streams".into())
+        })
+    }
+
+    fn next_tool(&self, name: &str) -> AofResult<ToolResult> {
+        self.tools
+            .lock()
+            .get_mut(name)
+            .and_then(VecDeque::pop_front)
+            .ok_or_else(|| {
+                AofError::InvalidState(format!(
+                    "Replay cassette has no more results for tool '{}'",
+                    name
+                ))
+            })?
+            .map_err(AofError::from)
+    }
+}
+
+/// Cassette opened for a [`ReplayConfig`]
+#[derive(Clone)]
+pub(crate) enum Cassette {
+    Record(Arc<CassetteWriter>),
+    Replay(Arc<CassetteReader>),
+}
+
+impl Cassette {
+    pub(crate) fn open(config: &ReplayConfig) -> AofResult<Self> {
+        Ok(match config.mode {
+            ReplayMode::Record => Self::Record(Arc::new(CassetteWriter::create(&config.cassette)?)),
+            ReplayMode::Replay => Self::Replay(Arc::new(CassetteReader::open(&config.cassette)?)),
+        })
+    }
+}
+
+/// Model wrapper that records every response
+pub struct RecordingModel {
+    inner: Box<dyn Model>,
+    cassette: Arc<CassetteWriter>,
+}
+
+impl RecordingModel {
+    pub fn new(inner: Box<dyn Model>, cassette: Arc<CassetteWriter>) -> Self {
+        Self { inner, cassette }
+    }
+}
+
+#[async_trait]
+impl Model for RecordingModel {
+    async fn generate(&self, request: &ModelRequest) -> AofResult<ModelResponse> {
+        let response = self.inner.generate(request).await;
+        self.cassette.append(&CassetteEntry::Model {
+            response: response.as_ref().map_err(RecordedError::from).cloned(),
+        })?;
+        response
+    }
+
+    /// Streams are buffered in full before being recorded and passed on
+    async fn generate_stream(&self, request: &ModelRequest) -> AofResult<ChunkStream> {
+        let chunks: Vec<AofResult<StreamChunk>> =
+            self.inner.generate_stream(request).await?.collect().await;
+
+        self.cassette.append(&CassetteEntry::Stream {
+            chunks: chunks
+                .iter()
+                .map(|chunk| chunk.as_ref().map_err(RecordedError::from).cloned())
+                .collect(),
+        })?;
+
+        Ok(Box::pin(futures::stream::iter(chunks)))
+    }
+
+    fn config(&self) -> &ModelConfig {
+        self.inner.config()
+    }
+
+    fn provider(&self) -> ModelProvider {
+        self.inner.provider()
+    }
+
+    fn count_tokens(&self, text: &str) -> usize {
+        self.inner.count_tokens(text)
+    }
+}
+
+/// Model that answers from a cassette, ignoring the request
+pub struct ReplayModel {
+    config: ModelConfig,
+    cassette: Arc<CassetteReader>,
+}
+
+impl ReplayModel {
+    pub fn new(config: ModelConfig, cassette: Arc<CassetteReader>) -> Self {
+        Self { config, cassette }
+    }
+}
+
+#[async_trait]
+impl Model for ReplayModel {
+    async fn generate(&self, _request: &ModelRequest) -> AofResult<ModelResponse> {
+        debug!("Replaying model response for {}", self.config.model);
+        self.cassette.next_model()
+    }
+
+    async fn generate_stream(&self, _request: &ModelRequest) -> AofResult<ChunkStream> {
+        let chunks = self.cassette.next_stream()?;
+        Ok(Box::pin(futures::stream::iter(
+            chunks
+                .into_iter()
+                .map(|chunk| chunk.map_err(AofError::from)),
+        )))
+    }
+
+    fn config(&self) -> &ModelConfig {
+        &self.config
+    }
+
+    fn provider(&self) -> ModelProvider {
+        self.config.provider
+    }
+}
+
+/// Tool executor wrapper that records every tool result
+pub struct RecordingToolExecutor {
+    inner: Arc<dyn ToolExecutor>,
+    cassette: Arc<CassetteWriter>,
+}
+
+impl RecordingToolExecutor {
+    pub fn new(inner: Arc<dyn ToolExecutor>, cassette: Arc<CassetteWriter>) -> Self {
+        Self { inner, cassette }
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for RecordingToolExecutor {
+    async fn execute_tool(&self, name: &str, input: ToolInput) -> AofResult<ToolResult> {
+        let result = self.inner.execute_tool(name, input).await;
+        self.cassette.append(&CassetteEntry::Tool {
+            name: name.to_string(),
+            result: result.as_ref().map_err(RecordedError::from).cloned(),
+        })?;
+        result
+    }
+
+    fn list_tools(&self) -> Vec<ToolDefinition> {
+        self.inner.list_tools()
+    }
+
+    fn get_tool(&self, name: &str) -> Option<Arc<dyn Tool>> {
+        self.inner.get_tool(name)
+    }
+
+    fn health_check(&self) -> AofResult<()> {
+        self.inner.health_check()
+    }
+}
+
+/// Tool executor that answers from a cassette, ignoring the input
+///
+/// It exposes no tool definitions; the replayed model does not need them.
+pub struct ReplayToolExecutor {
+    cassette: Arc<CassetteReader>,
+}
+
+impl ReplayToolExecutor {
+    pub fn new(cassette: Arc<CassetteReader>) -> Self {
+        Self { cassette }
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for ReplayToolExecutor {
+    async fn execute_tool(&self, name: &str, _input: ToolInput) -> AofResult<ToolResult> {
+        debug!("Replaying result for tool {}", name);
+        self.cassette.next_tool(name)
+    }
+
+    fn list_tools(&self) -> Vec<ToolDefinition> {
+        vec![]
+    }
+
+    fn get_tool(&self, _name: &str) -> Option<Arc<dyn Tool>> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AgentExecutor, Runtime};
+    use aof_core::model::MessageRole;
+    use aof_core::{AgentConfig, AgentContext, StopReason, ToolCall, Usage};
+
+    /// Model that calls `clock` once, then reports what the tool returned
+    struct ScriptedModel {
+        config: ModelConfig,
+    }
+
+    #[async_trait]
+    impl Model for ScriptedModel {
+        async fn generate(&self, request: &ModelRequest) -> AofResult<ModelResponse> {
+            let last = request.messages.last().unwrap();
+            let (content, tool_calls, stop_reason) = if last.role == MessageRole::Tool {
+                (
+                    format!("The clock reads {}", last.content),
+                    vec![],
+                    StopReason::EndTurn,
+                )
+            } else {
+                let call = ToolCall {
+                    id: "call-1".to_string(),
+                    name: "clock".to_string(),
+                    arguments: serde_json::json!({}),
+                };
+                (String::new(), vec![call], StopReason::ToolUse)
+            };
+
+            Ok(ModelResponse {
+                content,
+                tool_calls,
+                stop_reason,
+                usage: Usage {
+                    input_tokens: 10,
+                    output_tokens: 5,
+                },
+                stop_detail: None,
+                metadata: HashMap::new(),
+            })
+        }
+
+        async fn generate_stream(&self, _request: &ModelRequest) -> AofResult<ChunkStream> {
+            unimplemented!()
+        }
+
+        fn config(&self) -> &ModelConfig {
+            &self.config
+        }
+
+        fn provider(&self) -> ModelProvider {
+            self.config.provider
+        }
+    }
+
+    /// Tool returning a different value on every call
+    struct ClockToolExecutor;
+
+    #[async_trait]
+    impl ToolExecutor for ClockToolExecutor {
+        async fn execute_tool(&self, _name: &str, _input: ToolInput) -> AofResult<ToolResult> {
+            let nanos = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos();
+            Ok(ToolResult::success(serde_json::json!(nanos.to_string())))
+        }
+
+        fn list_tools(&self) -> Vec<ToolDefinition> {
+            vec![]
+        }
+
+        fn get_tool(&self, _name: &str) -> Option<Arc<dyn Tool>> {
+            None
+        }
+    }
+
+    fn model_config() -> ModelConfig {
+        ModelConfig {
+            model: "llama3".to_string(),
+            provider: ModelProvider::Ollama,
+            api_key: None,
+            endpoint: None,
+            temperature: 0.7,
+            max_tokens: None,
+            timeout_secs: 60,
+            headers: HashMap::new(),
+            extra: HashMap::new(),
+        }
+    }
+
+    fn agent_config() -> AgentConfig {
+        AgentConfig {
+            name: "replay-agent".to_string(),
+            model: "ollama:llama3".to_string(),
+            tools: vec!["shell".to_string()],
+            max_iterations: 5,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_then_replay_is_identical() {
+        let cassette =
+            std::env::temp_dir().join(format!("aof-replay-test-{}.jsonl", std::process::id()));
+
+        // Record a live run: one tool-use iteration, then the final answer
+        let writer = Arc::new(CassetteWriter::create(&cassette).unwrap());
+        let recorder = AgentExecutor::new(
+            agent_config(),
+            Box::new(RecordingModel::new(
+                Box::new(ScriptedModel {
+                    config: model_config(),
+                }),
+                Arc::clone(&writer),
+            )),
+            Some(Arc::new(RecordingToolExecutor::new(
+                Arc::new(ClockToolExecutor),
+                writer,
+            ))),
+            None,
+        );
+        let mut recorded = AgentContext::new("What time is it?");
+        let output = recorder.execute(&mut recorded).await.unwrap();
+        assert!(output.starts_with("The clock reads"));
+        assert_eq!(recorded.metadata.tool_calls, 1);
+
+        let lines = std::fs::read_to_string(&cassette).unwrap();
+        assert_eq!(lines.lines().count(), 3);
+
+        // Replay without the real model or tool, with a different input
+        let reader = Arc::new(CassetteReader::open(&cassette).unwrap());
+        let replayer = AgentExecutor::new(
+            agent_config(),
+            Box::new(ReplayModel::new(model_config(), Arc::clone(&reader))),
+            Some(Arc::new(ReplayToolExecutor::new(reader))),
+            None,
+        );
+        let mut replayed = AgentContext::new("Ignored");
+        assert_eq!(replayer.execute(&mut replayed).await.unwrap(), output);
+        assert_eq!(
+            replayed.tool_results[0].result,
+            recorded.tool_results[0].result
+        );
+
+        // The runtime builds the same replaying agent from a ReplayConfig
+        let mut runtime = Runtime::new()
+            .with_replay(ReplayConfig::replay(&cassette))
+            .unwrap();
+        runtime
+            .load_agent_from_config(agent_config())
+            .await
+            .unwrap();
+        assert_eq!(
+            runtime.execute("replay-agent", "Ignored").await.unwrap(),
+            output
+        );
+
+        // Once the cassette is used up, replay fails instead of going live
+        let err = runtime.execute("replay-agent", "Again").await.unwrap_err();
+        assert!(err.to_string().contains("no more model responses"));
+
+        std::fs::remove_file(&cassette).ok();
+    }
+    #[test]
+    fn test_replayed_errors_keep_their_variant() {
+        let errors = [
+            AofError::RateLimited {
+                retry_after: Some(Duration::from_secs(7)),
+            },
+            AofError::Http {
+                status: 502,
+                message: "bad gateway".to_string(),
+            },
+            AofError::Timeout("model did not answer".to_string()),
+            AofError::ContextOverflow("too long".to_string()),
+            AofError::Io(std::io::Error::other("disk full")),
+        ];
+        let cassette = std::env::temp_dir().join(format!(
+            "aof-replay-errors-test-{}.jsonl",
+            std::process::id()
+        ));
+        let writer = CassetteWriter::create(&cassette).unwrap();
+        for error in &errors {
+            writer
+                .append(&CassetteEntry::Model {
+                    response: Err(error.into()),
+                })
+                .unwrap();
+        }
+        writer
+            .append(&CassetteEntry::Tool {
+                name: "shell".to_string(),
+                result: Err((&AofError::Timeout("sleep".to_string())).into()),
+            })
+            .unwrap();
+
+        let reader = CassetteReader::open(&cassette).unwrap();
+        std::fs::remove_file(&cassette).ok();
+        for error in &errors {
+            let replayed = reader.next_model().unwrap_err();
+            assert_eq!(
+                std::mem::discriminant(&replayed),
+                std::mem::discriminant(error)
+            );
+            assert_eq!(replayed.to_string(), error.to_string());
+        }
+        assert!(matches!(
+            reader.next_model().unwrap_err(),
+            AofError::InvalidState(_)
+        ));
+        assert!(matches!(
+            reader.next_tool("shell").unwrap_err(),
+            AofError::Timeout(_)
+        ));
+    }
+}
@@ -2,9 +2,10 @@
 //!
 //! Provides task scheduling and execution coordination for agents.
 
-use aof_core::{AgentContext, AofResult};
+use aof_core::{AofError, AofResult, ExecutionMetadata, Usage};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
 /// Task execution status
@@ -18,6 +19,13 @@ pub enum TaskStatus {
     Cancelled,
 }
 
+impl TaskStatus {
+    /// Whether the task has completed, failed or been cancelled
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Self::Completed | Self::Failed | Self::Cancelled)
+    }
+}
+
 /// Task representation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Task {
@@ -72,10 +80,38 @@ impl Task {
     }
 }
 
+/// Latest progress reported by a running task
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskProgress {
+    /// Completion percentage (0-100)
+    pub percent: u8,
+
+    /// Description of the current step
+    pub message: String,
+}
+
+/// Progress reporter handed to task executors
+#[derive(Debug, Clone)]
+pub struct ProgressReporter {
+    progress: Arc<RwLock<TaskProgress>>,
+}
+
+impl ProgressReporter {
+    /// Report progress; percentages above 100 are clamped
+    pub async fn report_progress(&self, percent: u8, message: impl Into<String>) {
+        *self.progress.write().await = TaskProgress {
+            percent: percent.min(100),
+            message: message.into(),
+        };
+    }
+}
+
 /// Task handle for async operations
 pub struct TaskHandle {
     task: Arc<RwLock<Task>>,
     result: Arc<RwLock<Option<TaskResult>>>,
+    progress: Arc<RwLock<TaskProgress>>,
+    completed_at: Arc<RwLock<Option<Instant>>>,
 }
 
 impl TaskHandle {
@@ -84,6 +120,8 @@ impl TaskHandle {
         Self {
             task: Arc::new(RwLock::new(task)),
             result: Arc::new(RwLock::new(None)),
+            progress: Arc::new(RwLock::new(TaskProgress::default())),
+            completed_at: Arc::new(RwLock::new(None)),
         }
     }
 
@@ -97,26 +135,75 @@ impl TaskHandle {
         self.task.read().await.clone()
     }
 
+    /// Latest reported progress
+    pub async fn progress(&self) -> TaskProgress {
+        self.progress.read().await.clone()
+    }
+
+    /// Reporter that updates this task's progress
+    pub fn progress_reporter(&self) -> ProgressReporter {
+        ProgressReporter {
+            progress: Arc::clone(&self.progress),
+        }
+    }
+
     /// Wait for task completion
     pub async fn wait(&self) -> AofResult<TaskResult> {
         loop {
             let status = self.status().await;
-            match status {
-                TaskStatus::Completed | TaskStatus::Failed | TaskStatus::Cancelled => {
-                    if let Some(result) = self.result.read().await.clone() {
-                        return Ok(result);
-                    }
-                }
-                _ => {
-                    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+            if matches!(
+                status,
+                TaskStatus::Completed | TaskStatus::Failed | TaskStatus::Cancelled
+            ) {
+                if let Some(result) = self.result.read().await.clone() {
+                    return Ok(result);
                 }
             }
+
+            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+        }
+    }
+
+    /// Wait for task completion, giving up after `timeout`
+    ///
+    /// Returns `AofError::Timeout` if the task has not finished in time.
+    /// The task itself keeps running and can still be awaited later.
+    pub async fn wait_with_timeout(&self, timeout: Duration) -> AofResult<TaskResult> {
+        match tokio::time::timeout(timeout, self.wait()).await {
+            Ok(result) => result,
+            Err(_) => {
+                let task_id = self.task.read().await.id.clone();
+                Err(AofError::Timeout(format!(
+                    "Task {} did not complete within {}s",
+                    task_id,
+                    timeout.as_secs()
+                )))
+            }
         }
     }
 
+    /// When the task completed, failed or was cancelled
+    pub async fn completed_at(&self) -> Option<Instant> {
+        *self.completed_at.read().await
+    }
+
     /// Update task status
+    ///
+    /// The first move into a finished status records `completed_at`.
     pub async fn update_status(&self, status: TaskStatus) {
         self.task.write().await.status = status;
+
+        if status.is_finished() {
+            self.completed_at
+                .write()
+                .await
+                .get_or_insert_with(Instant::now);
+        }
+    }
+
+    /// Result of a finished task, if any
+    pub async fn result(&self) -> Option<TaskResult> {
+        self.result.read().await.clone()
     }
 
     /// Set task result
@@ -150,6 +237,10 @@ pub struct TaskResult {
 
     #[serde(default)]
     pub output_tokens: usize,
+
+    /// Estimated cost (USD), when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
 }
 
 impl TaskResult {
@@ -163,6 +254,7 @@ impl TaskResult {
             execution_time_ms: 0,
             input_tokens: 0,
             output_tokens: 0,
+            cost_usd: None,
         }
     }
 
@@ -176,6 +268,7 @@ impl TaskResult {
             execution_time_ms: 0,
             input_tokens: 0,
             output_tokens: 0,
+            cost_usd: None,
         }
     }
 
@@ -191,6 +284,50 @@ impl TaskResult {
         self.output_tokens = output_tokens;
         self
     }
+
+    /// Set estimated cost (USD)
+    pub fn with_cost(mut self, cost_usd: f64) -> Self {
+        self.cost_usd = Some(cost_usd);
+        self
+    }
+}
+
+/// Output of a task executor, with the token usage behind it
+///
+/// Executors returning a plain `String` report no usage.
+#[derive(Debug, Clone, Default)]
+pub struct TaskOutput {
+    /// Output/result
+    pub output: String,
+
+    /// Tokens used to produce the output
+    pub usage: Usage,
+
+    /// Estimated cost (USD), when known
+    pub cost_usd: Option<f64>,
+}
+
+impl TaskOutput {
+    /// Output with the usage and cost recorded in an agent run's metadata
+    pub fn from_metadata(output: String, metadata: &ExecutionMetadata) -> Self {
+        Self {
+            output,
+            usage: Usage {
+                input_tokens: metadata.input_tokens,
+                output_tokens: metadata.output_tokens,
+            },
+            cost_usd: metadata.cost_usd,
+        }
+    }
+}
+
+impl From<String> for TaskOutput {
+    fn from(output: String) -> Self {
+        Self {
+            output,
+            ..Self::default()
+        }
+    }
 }
 
 #[cfg(test)]
@@ -239,6 +376,32 @@ mod tests {
 
         handle.update_status(TaskStatus::Running).await;
         assert_eq!(handle.status().await, TaskStatus::Running);
+        assert!(handle.completed_at().await.is_none());
+
+        handle.update_status(TaskStatus::Completed).await;
+        let completed_at = handle.completed_at().await.unwrap();
+        handle.update_status(TaskStatus::Failed).await;
+        assert_eq!(handle.completed_at().await, Some(completed_at));
+    }
+
+    #[tokio::test]
+    async fn test_progress_reporter_clamps() {
+        let handle = TaskHandle::new(Task::new(
+            "task-1".to_string(),
+            "Test Task".to_string(),
+            "test-agent".to_string(),
+            "test input".to_string(),
+        ));
+        assert_eq!(handle.progress().await, TaskProgress::default());
+
+        handle
+            .progress_reporter()
+            .report_progress(150, "overshoot")
+            .await;
+
+        let progress = handle.progress().await;
+        assert_eq!(progress.percent, 100);
+        assert_eq!(progress.message, "overshoot");
     }
 
     #[test]
@@ -255,4 +418,32 @@ mod tests {
         assert!(!result.success);
         assert_eq!(result.error, Some("Failed!".to_string()));
     }
+
+    #[tokio::test]
+    async fn test_wait_with_timeout() {
+        let handle = TaskHandle::new(Task::new(
+            "task-1".to_string(),
+            "Test Task".to_string(),
+            "test-agent".to_string(),
+            "test input".to_string(),
+        ));
+
+        let err = handle
+            .wait_with_timeout(Duration::from_millis(50))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AofError::Timeout(_)));
+
+        handle
+            .set_result(TaskResult::success("task-1".to_string(), "done".to_string()))
+            .await;
+        handle.update_status(TaskStatus::Completed).await;
+
+        let result = handle
+            .wait_with_timeout(Duration::from_secs(1))
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert_eq!(result.output, "done");
+    }
 }
@@ -0,0 +1,72 @@
+//! OpenTelemetry export of agent executions (`otel` feature)
+//!
+//! The executor records an `agent_execution` span per run with `model_call`
+//! and `tool` child spans carrying token counts, attempts and success; their
+//! durations are the spans' lifetimes. [`otel_layer`] turns those spans into
+//! OpenTelemetry spans, and [`init_tracing_with_otel`] installs it next to the
+//! usual log output, exporting over OTLP/HTTP.
+
+use aof_core::logging::{fmt_layer, LogFormat};
+use aof_core::{AofError, AofResult};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::trace::SdkTracer;
+use opentelemetry_sdk::Resource;
+use tracing::Subscriber;
+use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::EnvFilter;
+
+pub use opentelemetry_sdk::trace::SdkTracerProvider;
+
+/// Service name reported on exported spans
+pub const SERVICE_NAME: &str = "aof";
+
+/// Layer exporting tracing spans through `provider`
+pub fn otel_layer<S>(provider: &SdkTracerProvider) -> OpenTelemetryLayer<S, SdkTracer>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    tracing_opentelemetry::layer().with_tracer(provider.tracer("aof-runtime"))
+}
+
+/// Tracer provider batching spans to an OTLP/HTTP collector
+///
+/// `endpoint` is the full traces URL (e.g. `http://localhost:4318/v1/traces`);
+/// without one the exporter reads the standard `OTEL_EXPORTER_OTLP_*`
+/// environment variables.
+pub fn otlp_tracer_provider(endpoint: Option<&str>) -> AofResult<SdkTracerProvider> {
+    let mut builder = opentelemetry_otlp::SpanExporter::builder().with_http();
+    if let Some(endpoint) = endpoint {
+        builder = builder.with_endpoint(endpoint);
+    }
+    let exporter = builder
+        .build()
+        .map_err(|e| AofError::config(format!("Failed to create OTLP exporter: {}", e)))?;
+
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name(SERVICE_NAME).build())
+        .build())
+}
+
+/// Install the global subscriber: `AOF_LOG_FORMAT` logs on stdout plus OTLP export
+///
+/// `default_filter` applies when `RUST_LOG` is unset. Keep the returned
+/// provider and call `shutdown` on exit so buffered spans are flushed.
+pub fn init_tracing_with_otel(
+    default_filter: &str,
+    endpoint: Option<&str>,
+) -> AofResult<SdkTracerProvider> {
+    let provider = otlp_tracer_provider(endpoint)?;
+
+    tracing_subscriber::registry()
+        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| default_filter.into()))
+        .with(fmt_layer(LogFormat::from_env(), std::io::stdout))
+        .with(otel_layer(&provider))
+        .init();
+
+    Ok(provider)
+}
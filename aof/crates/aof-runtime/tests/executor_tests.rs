@@ -56,6 +56,7 @@ impl Model for MockModel {
                     input_tokens: 10,
                     output_tokens: 5,
                 },
+                stop_detail: None,
                 metadata: HashMap::new(),
             })
         }
@@ -116,12 +117,8 @@ async fn test_executor_simple_execution() {
         name: "test-agent".to_string(),
         system_prompt: Some("You are a test assistant".to_string()),
         model: "test-model".to_string(),
-        tools: vec![],
-        memory: None,
-        max_iterations: 10,
-        temperature: 0.7,
         max_tokens: Some(1000),
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     let responses = vec![ModelResponse {
@@ -132,6 +129,7 @@ async fn test_executor_simple_execution() {
             input_tokens: 50,
             output_tokens: 10,
         },
+        stop_detail: None,
         metadata: HashMap::new(),
     }];
 
@@ -150,14 +148,9 @@ async fn test_executor_simple_execution() {
 async fn test_executor_with_tool_calls() {
     let config = AgentConfig {
         name: "tool-agent".to_string(),
-        system_prompt: None,
         model: "test-model".to_string(),
         tools: vec!["test_tool".to_string()],
-        memory: None,
-        max_iterations: 10,
-        temperature: 0.7,
-        max_tokens: None,
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     let responses = vec![
@@ -174,6 +167,7 @@ async fn test_executor_with_tool_calls() {
                 input_tokens: 100,
                 output_tokens: 20,
             },
+            stop_detail: None,
             metadata: HashMap::new(),
         },
         // Second response: final answer
@@ -185,6 +179,7 @@ async fn test_executor_with_tool_calls() {
                 input_tokens: 150,
                 output_tokens: 30,
             },
+            stop_detail: None,
             metadata: HashMap::new(),
         },
     ];
@@ -206,14 +201,10 @@ async fn test_executor_with_tool_calls() {
 async fn test_executor_max_iterations() {
     let config = AgentConfig {
         name: "limited-agent".to_string(),
-        system_prompt: None,
         model: "test-model".to_string(),
         tools: vec!["test_tool".to_string()],
-        memory: None,
         max_iterations: 2,
-        temperature: 0.7,
-        max_tokens: None,
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     // Create responses that always request tools (infinite loop)
@@ -227,6 +218,7 @@ async fn test_executor_max_iterations() {
             }],
             stop_reason: StopReason::ToolUse,
             usage: Usage::default(),
+            stop_detail: None,
             metadata: HashMap::new(),
         },
         ModelResponse {
@@ -238,6 +230,7 @@ async fn test_executor_max_iterations() {
             }],
             stop_reason: StopReason::ToolUse,
             usage: Usage::default(),
+            stop_detail: None,
             metadata: HashMap::new(),
         },
         ModelResponse {
@@ -249,6 +242,7 @@ async fn test_executor_max_iterations() {
             }],
             stop_reason: StopReason::ToolUse,
             usage: Usage::default(),
+            stop_detail: None,
             metadata: HashMap::new(),
         },
     ];
@@ -269,14 +263,9 @@ async fn test_executor_max_iterations() {
 async fn test_executor_tool_failure() {
     let config = AgentConfig {
         name: "failing-tool-agent".to_string(),
-        system_prompt: None,
         model: "test-model".to_string(),
         tools: vec!["test_tool".to_string()],
-        memory: None,
-        max_iterations: 10,
-        temperature: 0.7,
-        max_tokens: None,
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     let responses = vec![
@@ -289,6 +278,7 @@ async fn test_executor_tool_failure() {
             }],
             stop_reason: StopReason::ToolUse,
             usage: Usage::default(),
+            stop_detail: None,
             metadata: HashMap::new(),
         },
         ModelResponse {
@@ -296,6 +286,7 @@ async fn test_executor_tool_failure() {
             tool_calls: vec![],
             stop_reason: StopReason::EndTurn,
             usage: Usage::default(),
+            stop_detail: None,
             metadata: HashMap::new(),
         },
     ];
@@ -324,14 +315,8 @@ async fn test_executor_stop_reasons() {
     for (stop_reason, expected_content) in test_cases {
         let config = AgentConfig {
             name: "test-agent".to_string(),
-            system_prompt: None,
             model: "test-model".to_string(),
-            tools: vec![],
-            memory: None,
-            max_iterations: 10,
-            temperature: 0.7,
-            max_tokens: None,
-            extra: HashMap::new(),
+            ..Default::default()
         };
 
         let responses = vec![ModelResponse {
@@ -339,6 +324,7 @@ async fn test_executor_stop_reasons() {
             tool_calls: vec![],
             stop_reason,
             usage: Usage::default(),
+            stop_detail: None,
             metadata: HashMap::new(),
         }];
 
@@ -356,14 +342,8 @@ async fn test_executor_stop_reasons() {
 async fn test_executor_content_filter() {
     let config = AgentConfig {
         name: "filtered-agent".to_string(),
-        system_prompt: None,
         model: "test-model".to_string(),
-        tools: vec![],
-        memory: None,
-        max_iterations: 10,
-        temperature: 0.7,
-        max_tokens: None,
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     let responses = vec![ModelResponse {
@@ -371,6 +351,7 @@ async fn test_executor_content_filter() {
         tool_calls: vec![],
         stop_reason: StopReason::ContentFilter,
         usage: Usage::default(),
+        stop_detail: None,
         metadata: HashMap::new(),
     }];
 
@@ -382,3 +363,113 @@ async fn test_executor_content_filter() {
 
     assert!(result.is_err());
 }
+
+#[tokio::test]
+async fn test_executor_content_filter_reports_detail() {
+    let config = AgentConfig {
+        name: "filtered-agent".to_string(),
+        model: "test-model".to_string(),
+        ..Default::default()
+    };
+
+    let responses = vec![ModelResponse {
+        content: String::new(),
+        tool_calls: vec![],
+        stop_reason: StopReason::ContentFilter,
+        usage: Usage::default(),
+        stop_detail: Some("I can't help with disabling audit logging.".to_string()),
+        metadata: HashMap::new(),
+    }];
+
+    let model = Box::new(MockModel::new(responses));
+    let executor = AgentExecutor::new(config, model, None, None);
+
+    let mut context = AgentContext::new("Turn off the audit log");
+    let err = executor.execute(&mut context).await.unwrap_err();
+
+    assert!(err
+        .to_string()
+        .contains("Content filter triggered by model: I can't help with disabling audit logging."));
+}
+
+/// Log output captured in memory
+#[derive(Clone, Default)]
+struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+impl std::io::Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_concurrent_runs_have_distinct_trace_ids() {
+    use aof_core::logging::{fmt_layer, LogFormat};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    let logs = CapturedLogs::default();
+    let writer = logs.clone();
+    let _guard = tracing::subscriber::set_default(
+        tracing_subscriber::registry().with(fmt_layer(LogFormat::Json, move || writer.clone())),
+    );
+
+    let run = |name: &'static str| async move {
+        let config = AgentConfig {
+            name: name.to_string(),
+            model: "test-model".to_string(),
+            tools: vec!["test_tool".to_string()],
+            ..Default::default()
+        };
+        let responses = vec![ModelResponse {
+            content: String::new(),
+            tool_calls: vec![ToolCall {
+                id: "call_1".to_string(),
+                name: "test_tool".to_string(),
+                arguments: serde_json::json!({}),
+            }],
+            stop_reason: StopReason::ToolUse,
+            usage: Usage::default(),
+            stop_detail: None,
+            metadata: HashMap::new(),
+        }];
+        let executor = AgentExecutor::new(
+            config,
+            Box::new(MockModel::new(responses)),
+            Some(Arc::new(MockToolExecutor { should_fail: false })),
+            None,
+        );
+
+        let mut context = AgentContext::new("Check the cluster");
+        executor.execute(&mut context).await.unwrap();
+        (name, context.metadata.trace_id.unwrap())
+    };
+    let (a, b) = tokio::join!(run("agent-a"), run("agent-b"));
+    assert_ne!(a.1, b.1);
+    let expected = HashMap::from([a, b]);
+
+    // Every line logged inside a run, tool calls included, carries its trace id
+    let output = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+    let mut tool_lines = HashMap::new();
+    for line in output.lines() {
+        let json: serde_json::Value = serde_json::from_str(line).unwrap();
+        let Some(spans) = json["spans"].as_array() else {
+            continue;
+        };
+        let run = spans
+            .iter()
+            .find(|span| span["name"] == "agent_execution")
+            .unwrap();
+        let agent = run["agent"].as_str().unwrap();
+        assert_eq!(run["trace_id"].as_str(), Some(expected[agent].as_str()));
+        if spans.iter().any(|span| span["name"] == "tool") {
+            *tool_lines.entry(agent.to_string()).or_insert(0) += 1;
+        }
+    }
+    assert!(tool_lines["agent-a"] > 0);
+    assert!(tool_lines["agent-b"] > 0);
+}
@@ -0,0 +1,206 @@
+//! OpenTelemetry export tests (run with `--features otel`)
+
+#![cfg(feature = "otel")]
+
+use aof_core::{
+    AgentConfig, AgentContext, AofResult, Model, ModelConfig, ModelProvider, ModelRequest,
+    ModelResponse, StopReason, StreamChunk, ToolCall, ToolDefinition, ToolExecutor, ToolInput,
+    ToolResult, Usage,
+};
+use aof_runtime::executor::AgentExecutor;
+use aof_runtime::telemetry::otel_layer;
+use async_trait::async_trait;
+use opentelemetry::trace::SpanId;
+use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider, SpanData};
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use tracing_subscriber::layer::SubscriberExt;
+
+/// Model replaying canned responses
+struct ScriptedModel {
+    responses: Mutex<Vec<ModelResponse>>,
+    config: ModelConfig,
+}
+
+impl ScriptedModel {
+    fn new(mut responses: Vec<ModelResponse>) -> Self {
+        responses.reverse();
+        Self {
+            responses: Mutex::new(responses),
+            config: ModelConfig {
+                model: "mock-model".to_string(),
+                provider: ModelProvider::Custom,
+                api_key: None,
+                endpoint: None,
+                temperature: 0.7,
+                max_tokens: None,
+                timeout_secs: 60,
+                headers: HashMap::new(),
+                extra: HashMap::new(),
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl Model for ScriptedModel {
+    async fn generate(&self, _request: &ModelRequest) -> AofResult<ModelResponse> {
+        Ok(self.responses.lock().unwrap().pop().unwrap())
+    }
+
+    async fn generate_stream(
+        &self,
+        _request: &ModelRequest,
+    ) -> AofResult<Pin<Box<dyn futures::Stream<Item = AofResult<StreamChunk>> + Send>>> {
+        unimplemented!("Stream not implemented in mock")
+    }
+
+    fn config(&self) -> &ModelConfig {
+        &self.config
+    }
+
+    fn provider(&self) -> ModelProvider {
+        ModelProvider::Custom
+    }
+}
+
+/// Tool executor where every tool succeeds
+struct EchoToolExecutor;
+
+#[async_trait]
+impl ToolExecutor for EchoToolExecutor {
+    async fn execute_tool(&self, name: &str, _input: ToolInput) -> AofResult<ToolResult> {
+        Ok(ToolResult::success(serde_json::json!({ "tool": name })))
+    }
+
+    fn list_tools(&self) -> Vec<ToolDefinition> {
+        ["kubectl_get", "prometheus_query"]
+            .into_iter()
+            .map(|name| ToolDefinition {
+                name: name.to_string(),
+                description: "A test tool".to_string(),
+                parameters: serde_json::json!({}),
+            })
+            .collect()
+    }
+
+    fn get_tool(&self, _name: &str) -> Option<Arc<dyn aof_core::Tool>> {
+        None
+    }
+}
+
+fn response(tool_calls: Vec<ToolCall>, input_tokens: usize, output_tokens: usize) -> ModelResponse {
+    ModelResponse {
+        content: "All pods healthy".to_string(),
+        stop_reason: if tool_calls.is_empty() {
+            StopReason::EndTurn
+        } else {
+            StopReason::ToolUse
+        },
+        tool_calls,
+        usage: Usage {
+            input_tokens,
+            output_tokens,
+        },
+        stop_detail: None,
+        metadata: HashMap::new(),
+    }
+}
+
+fn attribute(span: &SpanData, key: &str) -> Option<String> {
+    span.attributes
+        .iter()
+        .find(|kv| kv.key.as_str() == key)
+        .map(|kv| kv.value.to_string())
+}
+
+#[tokio::test]
+async fn test_two_tool_run_exports_span_tree() {
+    let exporter = InMemorySpanExporter::default();
+    let provider = SdkTracerProvider::builder()
+        .with_simple_exporter(exporter.clone())
+        .build();
+    let _guard = tracing::subscriber::set_default(
+        tracing_subscriber::registry().with(otel_layer(&provider)),
+    );
+
+    let config = AgentConfig {
+        name: "sre-agent".to_string(),
+        model: "test-model".to_string(),
+        tools: vec!["kubectl_get".to_string(), "prometheus_query".to_string()],
+        ..Default::default()
+    };
+    let tool_calls = ["kubectl_get", "prometheus_query"]
+        .into_iter()
+        .enumerate()
+        .map(|(i, name)| ToolCall {
+            id: format!("call_{}", i),
+            name: name.to_string(),
+            arguments: serde_json::json!({}),
+        })
+        .collect();
+    let model = ScriptedModel::new(vec![
+        response(tool_calls, 100, 20),
+        response(vec![], 150, 30),
+    ]);
+    let executor = AgentExecutor::new(
+        config,
+        Box::new(model),
+        Some(Arc::new(EchoToolExecutor)),
+        None,
+    );
+
+    let mut context = AgentContext::new("Check the cluster");
+    executor.execute(&mut context).await.unwrap();
+    provider.force_flush().unwrap();
+    let spans = exporter.get_finished_spans().unwrap();
+
+    // One root span for the run
+    let runs: Vec<_> = spans
+        .iter()
+        .filter(|span| span.name == "agent_execution")
+        .collect();
+    assert_eq!(runs.len(), 1);
+    let run = runs[0];
+    assert_eq!(run.parent_span_id, SpanId::INVALID);
+    assert_eq!(attribute(run, "agent").as_deref(), Some("sre-agent"));
+    assert_eq!(attribute(run, "trace_id"), context.metadata.trace_id);
+    assert_eq!(attribute(run, "input_tokens").as_deref(), Some("250"));
+    assert_eq!(attribute(run, "output_tokens").as_deref(), Some("50"));
+    assert_eq!(attribute(run, "success").as_deref(), Some("true"));
+
+    let children = |name: &str| -> Vec<&SpanData> {
+        spans
+            .iter()
+            .filter(|span| span.name == name)
+            .inspect(|span| {
+                assert_eq!(span.parent_span_id, run.span_context.span_id());
+                assert_eq!(span.span_context.trace_id(), run.span_context.trace_id());
+            })
+            .collect()
+    };
+
+    // A model call per iteration, each with its own token counts
+    let model_calls = children("model_call");
+    let tokens: Vec<_> = model_calls
+        .iter()
+        .map(|span| attribute(span, "input_tokens").unwrap())
+        .collect();
+    assert_eq!(tokens, ["100", "150"]);
+    assert!(model_calls
+        .iter()
+        .all(|span| attribute(span, "success").as_deref() == Some("true")));
+
+    // Both tool calls, run in parallel, under the same run
+    let mut tools: Vec<_> = children("tool")
+        .into_iter()
+        .map(|span| {
+            assert_eq!(attribute(span, "success").as_deref(), Some("true"));
+            assert_eq!(attribute(span, "attempts").as_deref(), Some("1"));
+            attribute(span, "tool").unwrap()
+        })
+        .collect();
+    tools.sort();
+    assert_eq!(tools, ["kubectl_get", "prometheus_query"]);
+}
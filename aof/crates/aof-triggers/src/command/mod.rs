@@ -52,12 +52,26 @@ pub enum CommandType {
 }
 
 impl CommandType {
+    /// Lowercase command name, as used in metric labels
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Run => "run",
+            Self::Create => "create",
+            Self::Status => "status",
+            Self::Cancel => "cancel",
+            Self::List => "list",
+            Self::Help => "help",
+            Self::Info => "info",
+        }
+    }
+
     /// Parse command type from string
     pub fn from_str(s: &str) -> Result<Self, CommandError> {
         match s.to_lowercase().as_str() {
             "run" | "execute" | "start" => Ok(Self::Run),
             "create" | "new" | "spawn" => Ok(Self::Create),
-            "status" | "check" | "info" => Ok(Self::Status),
+            "status" | "check" => Ok(Self::Status),
+            "info" => Ok(Self::Info),
             "cancel" | "stop" | "abort" => Ok(Self::Cancel),
             "list" | "ls" | "show" => Ok(Self::List),
             "help" | "h" => Ok(Self::Help),
@@ -100,10 +114,10 @@ impl TriggerTarget {
     /// Parse target from string
     pub fn from_str(s: &str) -> Result<Self, CommandError> {
         match s.to_lowercase().as_str() {
-            "agent" | "ag" => Ok(Self::Agent),
-            "task" | "t" => Ok(Self::Task),
-            "fleet" | "f" => Ok(Self::Fleet),
-            "flow" | "workflow" | "w" => Ok(Self::Flow),
+            "agent" | "agents" | "ag" => Ok(Self::Agent),
+            "task" | "tasks" | "t" => Ok(Self::Task),
+            "fleet" | "fleets" | "f" => Ok(Self::Fleet),
+            "flow" | "flows" | "workflow" | "workflows" | "w" => Ok(Self::Flow),
             _ => Err(CommandError::InvalidTarget(s.to_string())),
         }
     }
@@ -209,8 +223,10 @@ impl TriggerCommand {
     /// Supported formats:
     /// - `/run agent agent-name task description`
     /// - `/create fleet fleet-name --size=5`
+    /// - `/create agent reviewer --prompt="You review pull requests"`
     /// - `/status task task-id`
     /// - `/list agents`
+    /// - `/info` (with a target, `/info task task-id` is a status check)
     /// - `/help`
     pub fn parse(msg: &TriggerMessage) -> Result<Self, CommandError> {
         let text = msg.text.trim();
@@ -222,20 +238,25 @@ impl TriggerCommand {
             ));
         }
 
-        let parts: Vec<&str> = text[1..].split_whitespace().collect();
+        let parts = tokenize(&text[1..]);
 
         if parts.is_empty() {
             return Err(CommandError::InvalidFormat("Empty command".to_string()));
         }
 
-        let command_type = CommandType::from_str(parts[0])?;
+        let mut command_type = CommandType::from_str(&parts[0])?;
         let context = CommandContext::from_message(msg);
 
-        // Handle help command (no target needed)
-        if command_type == CommandType::Help {
+        // `/info` alone shows system info; about a target it means its status
+        if command_type == CommandType::Info && parts.len() > 1 {
+            command_type = CommandType::Status;
+        }
+
+        // Handle help and info commands (no target needed)
+        if matches!(command_type, CommandType::Help | CommandType::Info) {
             return Ok(Self::new(
                 command_type,
-                TriggerTarget::Agent, // Default, unused for help
+                TriggerTarget::Agent, // Default, unused for help and info
                 Vec::new(),
                 context,
             ));
@@ -246,23 +267,23 @@ impl TriggerCommand {
             return Err(CommandError::MissingArgument("target".to_string()));
         }
 
-        let target = TriggerTarget::from_str(parts[1])?;
+        let target = TriggerTarget::from_str(&parts[1])?;
 
         // Remaining parts are arguments
         let mut args = Vec::new();
         let mut params = HashMap::new();
 
-        for part in parts.iter().skip(2) {
-            if part.starts_with("--") {
+        for part in parts.into_iter().skip(2) {
+            if let Some(param) = part.strip_prefix("--") {
                 // Named parameter: --key=value
-                if let Some((key, value)) = part[2..].split_once('=') {
+                if let Some((key, value)) = param.split_once('=') {
                     params.insert(key.to_string(), value.to_string());
                 } else {
                     // Flag: --key (value = "true")
-                    params.insert(part[2..].to_string(), "true".to_string());
+                    params.insert(param.to_string(), "true".to_string());
                 }
             } else {
-                args.push(part.to_string());
+                args.push(part);
             }
         }
 
@@ -273,6 +294,34 @@ impl TriggerCommand {
     }
 }
 
+/// Split command text on whitespace, keeping quoted `--key="..."` values whole
+///
+/// The quotes around a parameter value are dropped; quotes anywhere else
+/// are left as typed.
+fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+
+    for c in text.chars() {
+        match c {
+            '"' if quoted => quoted = false,
+            '"' if current.starts_with("--") && current.ends_with('=') => quoted = true,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    tokens
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -317,6 +366,21 @@ mod tests {
         assert_eq!(cmd.get_param("region").unwrap(), "us-east");
     }
 
+    #[test]
+    fn test_parse_quoted_param() {
+        let msg = create_test_message(
+            "/create agent reviewer --prompt=\"You review  pull requests\" --force say \"hi\"",
+        );
+        let cmd = TriggerCommand::parse(&msg).unwrap();
+
+        assert_eq!(
+            cmd.get_param("prompt").unwrap(),
+            "You review  pull requests"
+        );
+        assert_eq!(cmd.get_param("force").unwrap(), "true");
+        assert_eq!(cmd.args, ["reviewer", "say", "\"hi\""]);
+    }
+
     #[test]
     fn test_parse_help() {
         let msg = create_test_message("/help");
@@ -325,6 +389,26 @@ mod tests {
         assert_eq!(cmd.command_type, CommandType::Help);
     }
 
+    #[test]
+    fn test_parse_info_routes_on_target() {
+        let cmd = TriggerCommand::parse(&create_test_message("/info")).unwrap();
+        assert_eq!(cmd.command_type, CommandType::Info);
+
+        let cmd = TriggerCommand::parse(&create_test_message("/info task x")).unwrap();
+        assert_eq!(cmd.command_type, CommandType::Status);
+        assert_eq!(cmd.target, TriggerTarget::Task);
+
+        let cmd = TriggerCommand::parse(&create_test_message("/status task x")).unwrap();
+        assert_eq!(cmd.command_type, CommandType::Status);
+        assert_eq!(cmd.target, TriggerTarget::Task);
+        assert_eq!(cmd.get_arg(0).unwrap(), "x");
+
+        let cmd = TriggerCommand::parse(&create_test_message("/check agent y")).unwrap();
+        assert_eq!(cmd.command_type, CommandType::Status);
+        assert_eq!(cmd.target, TriggerTarget::Agent);
+        assert_eq!(cmd.get_arg(0).unwrap(), "y");
+    }
+
     #[test]
     fn test_parse_invalid_command() {
         let msg = create_test_message("/invalid agent test");
@@ -344,6 +428,8 @@ mod tests {
         assert_eq!(TriggerTarget::from_str("agent").unwrap(), TriggerTarget::Agent);
         assert_eq!(TriggerTarget::from_str("task").unwrap(), TriggerTarget::Task);
         assert_eq!(TriggerTarget::from_str("fleet").unwrap(), TriggerTarget::Fleet);
+        assert_eq!(TriggerTarget::from_str("agents").unwrap(), TriggerTarget::Agent);
+        assert_eq!(TriggerTarget::from_str("tasks").unwrap(), TriggerTarget::Task);
         assert!(TriggerTarget::from_str("invalid").is_err());
     }
 }
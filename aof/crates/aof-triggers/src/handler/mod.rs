@@ -3,16 +3,49 @@
 //! This module coordinates message handling across platforms,
 //! parsing commands, and executing them through the runtime.
 
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
+use serde::Serialize;
+use tokio::sync::{mpsc, oneshot, RwLock};
 use tracing::{debug, error, info, warn};
 
-use crate::command::{CommandError, CommandType, TriggerCommand, TriggerTarget};
+use crate::command::{CommandContext, CommandError, CommandType, TriggerCommand, TriggerTarget};
+use crate::metrics;
 use crate::platforms::{TriggerMessage, TriggerPlatform};
-use crate::response::{TriggerResponse, TriggerResponseBuilder};
-use aof_core::{AgentContext, AofError, AofResult};
-use aof_runtime::{RuntimeOrchestrator, Task, TaskStatus};
+use crate::response::{ResponseStatus, TriggerResponse, TriggerResponseBuilder};
+use aof_core::{AgentConfig, AgentContext, AofError, AofResult, MessageRole};
+use aof_runtime::{
+    AgentExecutor, ProgressReporter, Runtime, RuntimeHealth, RuntimeOrchestrator, StreamEvent,
+    Task, TaskHandle, TaskOutput, TaskStatus, SYSTEM_TOOLS,
+};
+
+/// Minimum interval between progress edits while agent text is streaming
+const STREAM_EDIT_INTERVAL: Duration = Duration::from_secs(3);
+
+/// How long `/cancel task` waits for a stopped run to report its partial output
+const CANCEL_REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Acknowledgement sent when `ack_message` is not set
+const DEFAULT_ACK_MESSAGE: &str = "Processing your request...";
+
+/// Model used by agents run without a loaded config
+const DEFAULT_MODEL: &str = "claude-3-5-sonnet-20241022";
+
+/// Task metadata key naming the conversation session the agent continues
+const SESSION_ID_KEY: &str = "session_id";
+
+/// Parameters accepted by `/create agent`
+const CREATE_AGENT_PARAMS: &[&str] = &[
+    "model",
+    "prompt",
+    "tools",
+    "max-iterations",
+    "temperature",
+    "max-tokens",
+    "force",
+];
 
 /// Helper trait to convert CommandError to AofError
 trait CommandErrorExt<T> {
@@ -34,11 +67,37 @@ pub struct TriggerHandlerConfig {
     /// Auto-acknowledge commands
     pub auto_ack: bool,
 
+    /// Acknowledgement text (default "Processing your request...")
+    pub ack_message: Option<String>,
+
+    /// Commands answered without an acknowledgement, as they reply at once
+    pub no_ack_commands: Vec<CommandType>,
+
     /// Maximum concurrent tasks per user
     pub max_tasks_per_user: usize,
 
     /// Command timeout in seconds
     pub command_timeout_secs: u64,
+
+    /// Post incremental agent output while a run is in progress
+    pub stream_updates: bool,
+
+    /// Ignore redelivered messages seen within this many seconds (0 disables)
+    pub dedup_window_secs: u64,
+
+    /// Maximum number of recent message IDs remembered for deduplication
+    pub dedup_capacity: usize,
+
+    /// Users allowed to create agents with `/create` (nobody when empty)
+    pub agent_creators: Vec<String>,
+
+    /// Tools agents created with `/create` may use (when `None`, any tool
+    /// except the system tools such as `shell` and `kubectl`)
+    pub creatable_tools: Option<Vec<String>>,
+
+    /// How long `/status fleet` still reports on a fleet run after its last
+    /// member finished
+    pub fleet_run_retention_secs: u64,
 }
 
 impl Default for TriggerHandlerConfig {
@@ -46,12 +105,105 @@ impl Default for TriggerHandlerConfig {
         Self {
             verbose: false,
             auto_ack: true,
+            ack_message: None,
+            no_ack_commands: vec![
+                CommandType::Status,
+                CommandType::List,
+                CommandType::Help,
+                CommandType::Info,
+            ],
             max_tasks_per_user: 3,
             command_timeout_secs: 300, // 5 minutes
+            stream_updates: false,
+            dedup_window_secs: 600, // 10 minutes
+            dedup_capacity: 10_000,
+            agent_creators: Vec::new(),
+            creatable_tools: None,
+            fleet_run_retention_secs: 3600, // 1 hour
         }
     }
 }
 
+impl TriggerHandlerConfig {
+    /// Whether agents created with `/create` may use `tool`
+    pub fn can_create_with_tool(&self, tool: &str) -> bool {
+        match &self.creatable_tools {
+            Some(tools) => tools.iter().any(|t| t == tool),
+            None => !SYSTEM_TOOLS.contains(&tool),
+        }
+    }
+
+    /// Whether `command_type` is acknowledged before it is handled
+    pub fn should_ack(&self, command_type: CommandType) -> bool {
+        self.auto_ack && !self.no_ack_commands.contains(&command_type)
+    }
+}
+
+/// Recently handled message keys, oldest first
+#[derive(Default)]
+struct RecentMessages {
+    order: VecDeque<(String, Instant)>,
+    keys: HashSet<String>,
+}
+
+impl RecentMessages {
+    /// Remember `key`, returning `false` if it was already seen within `window`
+    fn insert(&mut self, key: String, window: Duration, capacity: usize) -> bool {
+        let now = Instant::now();
+        while let Some((oldest, seen_at)) = self.order.front() {
+            if now.duration_since(*seen_at) < window && self.order.len() < capacity {
+                break;
+            }
+            self.keys.remove(oldest);
+            self.order.pop_front();
+        }
+
+        if !self.keys.insert(key.clone()) {
+            return false;
+        }
+        self.order.push_back((key, now));
+        true
+    }
+}
+
+/// Tasks started together by one `/run fleet` command
+#[derive(Debug, Clone)]
+struct FleetRun {
+    /// Name of the fleet that was run
+    fleet: String,
+
+    /// (agent name, task id) for each member that was started
+    members: Vec<(String, String)>,
+
+    /// Final status of each finished member, by task id
+    outcomes: HashMap<String, TaskStatus>,
+
+    /// When the last member finished
+    finished_at: Option<Instant>,
+}
+
+/// Loaded agent, as listed by `/list agents` and `GET /agents`
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentSummary {
+    /// Agent name
+    pub name: String,
+
+    /// Model the agent runs on
+    pub model: String,
+
+    /// Number of tools configured for the agent
+    pub tools: usize,
+}
+
+/// Cancellation token of a started agent run, fired by `/cancel task`
+struct RunCancellation {
+    /// Stops the run's execution when fired (or dropped)
+    cancel: oneshot::Sender<()>,
+
+    /// Output the run produced before it was stopped
+    partial: oneshot::Receiver<String>,
+}
+
 /// Central trigger handler
 ///
 /// Routes messages from platforms to appropriate handlers and
@@ -60,6 +212,9 @@ pub struct TriggerHandler {
     /// Runtime orchestrator for task execution
     orchestrator: Arc<RuntimeOrchestrator>,
 
+    /// Runtime holding the agents `/run agent` executes by name
+    runtime: Arc<RwLock<Runtime>>,
+
     /// Registered platforms
     platforms: HashMap<String, Arc<dyn TriggerPlatform>>,
 
@@ -68,6 +223,19 @@ pub struct TriggerHandler {
 
     /// User task counters (user_id -> active task count)
     user_tasks: Arc<dashmap::DashMap<String, usize>>,
+
+    /// Recently handled messages, for dropping webhook redeliveries
+    recent_messages: Mutex<RecentMessages>,
+
+    /// Registered fleets (fleet name -> member agent names)
+    fleets: HashMap<String, Vec<String>>,
+
+    /// Fleet runs, by run id, kept for `fleet_run_retention_secs` after
+    /// they finish
+    fleet_runs: Arc<dashmap::DashMap<String, FleetRun>>,
+
+    /// Cancellation tokens of started agent runs, by task id
+    cancellations: Arc<dashmap::DashMap<String, RunCancellation>>,
 }
 
 impl TriggerHandler {
@@ -75,9 +243,14 @@ impl TriggerHandler {
     pub fn new(orchestrator: Arc<RuntimeOrchestrator>) -> Self {
         Self {
             orchestrator,
+            runtime: Arc::new(RwLock::new(Runtime::new())),
             platforms: HashMap::new(),
             config: TriggerHandlerConfig::default(),
             user_tasks: Arc::new(dashmap::DashMap::new()),
+            recent_messages: Mutex::new(RecentMessages::default()),
+            fleets: HashMap::new(),
+            fleet_runs: Arc::new(dashmap::DashMap::new()),
+            cancellations: Arc::new(dashmap::DashMap::new()),
         }
     }
 
@@ -85,9 +258,14 @@ impl TriggerHandler {
     pub fn with_config(orchestrator: Arc<RuntimeOrchestrator>, config: TriggerHandlerConfig) -> Self {
         Self {
             orchestrator,
+            runtime: Arc::new(RwLock::new(Runtime::new())),
             platforms: HashMap::new(),
             config,
             user_tasks: Arc::new(dashmap::DashMap::new()),
+            recent_messages: Mutex::new(RecentMessages::default()),
+            fleets: HashMap::new(),
+            fleet_runs: Arc::new(dashmap::DashMap::new()),
+            cancellations: Arc::new(dashmap::DashMap::new()),
         }
     }
 
@@ -98,6 +276,47 @@ impl TriggerHandler {
         self.platforms.insert(name.to_string(), platform);
     }
 
+    /// Share `runtime` with the handler
+    ///
+    /// `/run agent` executes agents loaded in it with their own config, and
+    /// `/create agent` loads new ones into it. Agents it doesn't know run
+    /// with a default config.
+    pub fn set_runtime(&mut self, runtime: Arc<RwLock<Runtime>>) {
+        self.runtime = runtime;
+    }
+
+    /// Register a fleet of agents that `/run fleet <name>` runs together
+    pub fn register_fleet(&mut self, name: impl Into<String>, agents: Vec<String>) {
+        let name = name.into();
+        info!("Registering fleet: {} ({} agents)", name, agents.len());
+        self.fleets.insert(name, agents);
+    }
+
+    /// Agents loaded in the runtime, sorted by name
+    pub async fn agents(&self) -> Vec<AgentSummary> {
+        let runtime = self.runtime.read().await;
+        let mut agents: Vec<_> = runtime
+            .list_agents()
+            .into_iter()
+            .filter_map(|name| runtime.get_agent(&name))
+            .map(|executor| {
+                let config = executor.config();
+                AgentSummary {
+                    name: config.name.clone(),
+                    model: config.model.clone(),
+                    tools: config.tools.len(),
+                }
+            })
+            .collect();
+        agents.sort_by(|a, b| a.name.cmp(&b.name));
+        agents
+    }
+
+    /// Health of the runtime's agents and the services they depend on
+    pub async fn health(&self) -> RuntimeHealth {
+        self.runtime.read().await.health().await
+    }
+
     /// Get registered platform
     pub fn get_platform(&self, name: &str) -> Option<&Arc<dyn TriggerPlatform>> {
         self.platforms.get(name)
@@ -116,13 +335,25 @@ impl TriggerHandler {
             .get(platform)
             .ok_or_else(|| aof_core::AofError::agent(format!("Unknown platform: {}", platform)))?;
 
+        // Platforms redeliver webhooks they consider timed out; run each message once
+        if self.is_duplicate(platform, &message) {
+            info!(
+                "Ignoring duplicate message {} from {} (channel: {})",
+                message.id, platform, message.channel_id
+            );
+            return Ok(());
+        }
+
         // Check if user has too many active tasks
         if let Some(count) = self.user_tasks.get(&message.user.id) {
             if *count >= self.config.max_tasks_per_user {
                 let response = TriggerResponseBuilder::new()
-                    .text(format!(
-                        "You have too many active tasks ({}). Please wait for some to complete.",
-                        *count
+                    .text(Self::format_error_text_for_platform(
+                        platform,
+                        &format!(
+                            "You have too many active tasks ({}). Please wait for some to complete.",
+                            *count
+                        ),
                     ))
                     .error()
                     .build();
@@ -137,33 +368,42 @@ impl TriggerHandler {
             Ok(cmd) => cmd,
             Err(e) => {
                 warn!("Failed to parse command: {}", e);
-                let response = self.handle_parse_error(&message, e).await;
+                let response = self.handle_parse_error(platform, &message, e).await;
                 let _ = platform_impl.send_response(&message.channel_id, response).await;
                 return Ok(());
             }
         };
 
-        // Auto-acknowledge if enabled
-        if self.config.auto_ack {
-            let ack = TriggerResponseBuilder::new()
-                .text("Processing your request...")
-                .build();
-            let _ = platform_impl.send_response(&message.channel_id, ack).await;
+        // Auto-acknowledge if enabled, only to the sender where possible
+        if self.config.should_ack(cmd.command_type) {
+            let mut ack = TriggerResponseBuilder::new().text(
+                self.config
+                    .ack_message
+                    .as_deref()
+                    .unwrap_or(DEFAULT_ACK_MESSAGE),
+            );
+            if platform_impl.supports_ephemeral() {
+                ack = ack.ephemeral_to(message.user.id.clone());
+            }
+            let _ = platform_impl
+                .send_response(&message.channel_id, ack.build())
+                .await;
         }
 
         // Execute command
         let response = match self.execute_command(cmd).await {
-            Ok(resp) => resp,
+            Ok(resp) => Self::format_response_for_platform(platform, resp),
             Err(e) => {
                 error!("Command execution failed: {}", e);
                 TriggerResponseBuilder::new()
-                    .text(format!("Command failed: {}", e))
+                    .text(Self::format_error_for_platform(platform, &e))
                     .error()
                     .build()
             }
         };
 
         // Send response
+        let response = drop_unsupported_attachments(platform_impl.as_ref(), response);
         if let Err(e) = platform_impl.send_response(&message.channel_id, response).await {
             error!("Failed to send response: {:?}", e);
         }
@@ -178,7 +418,10 @@ impl TriggerHandler {
             cmd.command_type, cmd.target, cmd.context.user_id
         );
 
-        match cmd.command_type {
+        let command = cmd.command_type.as_str();
+        let start = Instant::now();
+
+        let result = match cmd.command_type {
             CommandType::Run => self.handle_run_command(cmd).await,
             CommandType::Create => self.handle_create_command(cmd).await,
             CommandType::Status => self.handle_status_command(cmd).await,
@@ -186,15 +429,24 @@ impl TriggerHandler {
             CommandType::List => self.handle_list_command(cmd).await,
             CommandType::Help => Ok(self.handle_help_command(cmd).await),
             CommandType::Info => Ok(self.handle_info_command(cmd).await),
-        }
+        };
+
+        metrics::record_command(command, result.is_ok(), start.elapsed());
+        result
     }
 
     /// Handle run command
+    ///
+    /// By default the task runs in the background and the final status is
+    /// posted to the channel when it finishes. With `--wait` the handler
+    /// awaits the task (bounded by `command_timeout_secs`) and replies once
+    /// with the agent output.
     async fn handle_run_command(&self, cmd: TriggerCommand) -> AofResult<TriggerResponse> {
         match cmd.target {
             TriggerTarget::Agent => {
                 let agent_name = cmd.get_arg(0).map_cmd_err()?;
                 let input = cmd.args[1..].join(" ");
+                let wait = cmd.get_param("wait").is_some_and(|v| v != "false");
 
                 // Create task
                 let task_id = format!("trigger-{}-{}", cmd.context.user_id, uuid::Uuid::new_v4());
@@ -203,145 +455,111 @@ impl TriggerHandler {
                     format!("{} (user: {})", agent_name, cmd.context.user_id),
                     agent_name.to_string(),
                     input.clone(),
-                );
-
-                // Submit to orchestrator
-                let handle = self.orchestrator.submit_task(task);
+                )
+                .with_metadata(SESSION_ID_KEY.to_string(), session_id(&cmd.context).into());
+
+                // Submit to orchestrator; with every slot busy the task queues
+                let at_capacity = self.orchestrator.available_permits() == 0;
+                if let Err(rejected) = self.orchestrator.try_submit_task(task).await {
+                    return Ok(TriggerResponseBuilder::new()
+                        .text(format!(
+                            "System at capacity: {} tasks already queued or running (limit {}). Please try again later.",
+                            rejected.depth, rejected.max_depth
+                        ))
+                        .error()
+                        .build());
+                }
 
                 // Track user task
                 self.increment_user_tasks(&cmd.context.user_id);
 
-                // Execute task through runtime with AgentExecutor
-                let user_id = cmd.context.user_id.clone();
-                let user_tasks = Arc::clone(&self.user_tasks);
-                let orchestrator = Arc::clone(&self.orchestrator);
-                let task_id_clone = task_id.clone();
-                let agent_name_clone = agent_name.to_string();
-                let platform = cmd.context.platform.clone();
-                let channel_id = cmd.context.channel_id.clone();
-                let platforms = self.platforms.clone();
-
-                tokio::spawn(async move {
-                    // Execute task through orchestrator
-                    let result = orchestrator
-                        .execute_task(&task_id_clone, |task| async move {
-                            // Create AgentContext
-                            let mut context = AgentContext::new(&task.input);
-
-                            // Create a minimal agent configuration for the task
-                            use aof_core::{AgentConfig, ModelConfig, ModelProvider};
-                            use aof_llm::ProviderFactory;
-                            use aof_runtime::AgentExecutor;
-                            use aof_memory::{InMemoryBackend, SimpleMemory};
-                            use std::collections::HashMap;
-
-                            let config = AgentConfig {
-                                name: task.agent_name.clone(),
-                                system_prompt: Some("You are a helpful AI assistant.".to_string()),
-                                model: "claude-3-5-sonnet-20241022".to_string(),
-                                tools: vec![],
-                                memory: None,
-                                max_iterations: 10,
-                                temperature: 0.7,
-                                max_tokens: Some(4096),
-                                extra: HashMap::new(),
-                            };
-
-                            // Create model
-                            let model_config = ModelConfig {
-                                model: "claude-3-5-sonnet-20241022".to_string(),
-                                provider: ModelProvider::Anthropic,
-                                api_key: std::env::var("ANTHROPIC_API_KEY").ok(),
-                                endpoint: None,
-                                temperature: 0.7,
-                                max_tokens: Some(4096),
-                                timeout_secs: 60,
-                                headers: HashMap::new(),
-                                extra: HashMap::new(),
-                            };
-
-                            let model = match ProviderFactory::create(model_config).await {
-                                Ok(m) => m,
-                                Err(e) => {
-                                    return Ok(format!("Failed to create model: {}", e));
-                                }
-                            };
-
-                            // Create memory backend
-                            let memory_backend = InMemoryBackend::new();
-                            let memory = std::sync::Arc::new(SimpleMemory::new(std::sync::Arc::new(memory_backend)));
-
-                            // Create AgentExecutor with model and memory, but no tool executor for now
-                            let executor = AgentExecutor::new(
-                                config,
-                                model,
-                                None, // No tool executor for trigger-based agents
-                                Some(memory),
-                            );
-
-                            // Execute the agent
-                            match executor.execute(&mut context).await {
-                                Ok(response) => Ok(response),
-                                Err(e) => Ok(format!("Agent execution failed: {}", e)),
-                            }
-                        })
-                        .await;
-
-                    // Send completion notification to platform
-                    if let Some(platform_impl) = platforms.get(&platform) {
-                        let response = match result {
-                            Ok(_handle) => {
-                                // Wait for task completion
-                                tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-
-                                if let Some(task_handle) = orchestrator.get_task(&task_id_clone) {
-                                    let status = task_handle.status().await;
-
-                                    match status {
-                                        TaskStatus::Completed => {
-                                            TriggerResponseBuilder::new()
-                                                .text(format!("✅ Task completed: `{}`", task_id_clone))
-                                                .success()
-                                                .build()
-                                        }
-                                        TaskStatus::Failed => {
-                                            TriggerResponseBuilder::new()
-                                                .text(format!("❌ Task failed: `{}`", task_id_clone))
-                                                .error()
-                                                .build()
-                                        }
-                                        _ => {
-                                            TriggerResponseBuilder::new()
-                                                .text(format!("ℹ️ Task status: {:?} - `{}`", status, task_id_clone))
-                                                .build()
-                                        }
-                                    }
-                                } else {
-                                    TriggerResponseBuilder::new()
-                                        .text("Task execution started but handle lost")
-                                        .build()
-                                }
-                            }
-                            Err(e) => TriggerResponseBuilder::new()
-                                .text(format!("Task execution error: {}", e))
-                                .error()
-                                .build(),
-                        };
+                // Relay streamed output to the originating channel when enabled
+                let stream_tx = if self.config.stream_updates {
+                    self.platforms.get(&cmd.context.platform).map(|platform_impl| {
+                        let (tx, rx) = mpsc::channel(100);
+                        tokio::spawn(forward_stream_updates(
+                            Arc::clone(platform_impl),
+                            cmd.context.platform.clone(),
+                            cmd.context.channel_id.clone(),
+                            cmd.context.message_id.clone(),
+                            rx,
+                        ));
+                        tx
+                    })
+                } else {
+                    None
+                };
 
-                        let _ = platform_impl.send_response(&channel_id, response).await;
+                // Execute task through runtime with AgentExecutor
+                let loaded = self.runtime.read().await.get_agent(agent_name);
+                let (cancel_rx, partial_tx) = self.register_cancellation(&task_id);
+                let handle = match self
+                    .orchestrator
+                    .execute_task(&task_id, move |task, progress| {
+                        run_agent_task(task, progress, loaded, stream_tx, cancel_rx, partial_tx)
+                    })
+                    .await
+                {
+                    Ok(handle) => handle,
+                    Err(e) => {
+                        self.cancellations.remove(&task_id);
+                        self.decrement_user_tasks(&cmd.context.user_id);
+                        return Err(e);
                     }
+                };
+
+                // Release the user's task slot once the task finishes, and in
+                // fire-and-forget mode report the final status to the channel
+                self.watch_task(Arc::clone(&handle), &cmd, !wait);
+
+                if wait {
+                    let timeout = Duration::from_secs(self.config.command_timeout_secs);
+
+                    return Ok(match handle.wait_with_timeout(timeout).await {
+                        Ok(result) if result.success => TriggerResponseBuilder::new()
+                            .text(format!(
+                                "Task completed: `{}`\nAgent: {}\n\n{}",
+                                task_id, agent_name, result.output
+                            ))
+                            .success()
+                            .build(),
+                        Ok(result) => TriggerResponseBuilder::new()
+                            .text(format!(
+                                "Task failed: `{}`\nAgent: {}\n{}",
+                                task_id,
+                                agent_name,
+                                result.error.unwrap_or_default()
+                            ))
+                            .error()
+                            .build(),
+                        Err(e) => TriggerResponseBuilder::new()
+                            .text(format!(
+                                "{}\nUse `/status task {}` to check progress",
+                                e, task_id
+                            ))
+                            .warning()
+                            .build(),
+                    });
+                }
 
-                    // Decrement user task count
-                    if let Some(mut count) = user_tasks.get_mut(&user_id) {
-                        if *count > 0 {
-                            *count -= 1;
-                        }
-                    }
-                });
+                let queue_position = if at_capacity {
+                    self.orchestrator.queue_position(&task_id).await
+                } else {
+                    None
+                };
+                if let Some(position) = queue_position {
+                    return Ok(TriggerResponseBuilder::new()
+                        .text(format!(
+                            "System at capacity, task `{}` queued at position {}\nAgent: {}\nUse `/status task {}` to check progress",
+                            task_id, position, agent_name, task_id
+                        ))
+                        .warning()
+                        .build());
+                }
 
                 Ok(TriggerResponseBuilder::new()
                     .text(format!(
-                        "✓ Task started: `{}`\nAgent: {}\nInput: {}\nUse `/status task {}` to check progress",
+                        "Task started: `{}`\nAgent: {}\nInput: {}\nUse `/status task {}` to check progress",
                         task_id, agent_name,
                         if input.len() > 50 { format!("{}...", &input[..50]) } else { input },
                         task_id
@@ -349,6 +567,7 @@ impl TriggerHandler {
                     .success()
                     .build())
             }
+            TriggerTarget::Fleet => self.handle_run_fleet(cmd).await,
             _ => Ok(TriggerResponseBuilder::new()
                 .text(format!("Run command not supported for {:?}", cmd.target))
                 .error()
@@ -356,10 +575,327 @@ impl TriggerHandler {
         }
     }
 
+    /// Run every member of a registered fleet on the same input
+    ///
+    /// Each member runs as its own task and counts against the user's task
+    /// limit; the reply lists them under a fleet run id that
+    /// `/status fleet <id>` reports on until `fleet_run_retention_secs`
+    /// after every member has finished.
+    async fn handle_run_fleet(&self, cmd: TriggerCommand) -> AofResult<TriggerResponse> {
+        let fleet_name = cmd.get_arg(0).map_cmd_err()?;
+        let input = cmd.args[1..].join(" ");
+        let Some(agents) = self.fleets.get(fleet_name) else {
+            return Ok(TriggerResponseBuilder::new()
+                .text(format!("Fleet not found: `{}`", fleet_name))
+                .error()
+                .build());
+        };
+
+        let active = self
+            .user_tasks
+            .get(&cmd.context.user_id)
+            .map_or(0, |count| *count);
+        if active + agents.len() > self.config.max_tasks_per_user {
+            return Ok(TriggerResponseBuilder::new()
+                .text(format!(
+                    "Fleet `{}` runs {} agents but you can start {} more tasks ({} active). Please wait for some to complete.",
+                    fleet_name,
+                    agents.len(),
+                    self.config.max_tasks_per_user.saturating_sub(active),
+                    active
+                ))
+                .error()
+                .build());
+        }
+
+        // Registered before any member starts, so a failure part-way can stop
+        // the members already started
+        self.prune_fleet_runs();
+        let run_id = format!("fleet-{}-{}", cmd.context.user_id, uuid::Uuid::new_v4());
+        self.fleet_runs.insert(
+            run_id.clone(),
+            FleetRun {
+                fleet: fleet_name.to_string(),
+                members: Vec::new(),
+                outcomes: HashMap::new(),
+                finished_at: None,
+            },
+        );
+        let mut members = Vec::new();
+        let mut handles = Vec::new();
+        let mut not_started = Vec::new();
+
+        for agent_name in agents {
+            let task_id = format!("{}-{}", run_id, agent_name);
+            let task = Task::new(
+                task_id.clone(),
+                format!(
+                    "{} (fleet: {}, user: {})",
+                    agent_name, fleet_name, cmd.context.user_id
+                ),
+                agent_name.clone(),
+                input.clone(),
+            )
+            .with_metadata(SESSION_ID_KEY.to_string(), session_id(&cmd.context).into());
+            if self.orchestrator.try_submit_task(task).await.is_err() {
+                not_started.push(agent_name.as_str());
+                continue;
+            }
+
+            self.increment_user_tasks(&cmd.context.user_id);
+            let loaded = self.runtime.read().await.get_agent(agent_name);
+            let (cancel_rx, partial_tx) = self.register_cancellation(&task_id);
+            let handle = match self
+                .orchestrator
+                .execute_task(&task_id, move |task, progress| {
+                    run_agent_task(task, progress, loaded, None, cancel_rx, partial_tx)
+                })
+                .await
+            {
+                Ok(handle) => handle,
+                Err(e) => {
+                    self.cancellations.remove(&task_id);
+                    self.decrement_user_tasks(&cmd.context.user_id);
+                    self.cancel_fleet_run(&run_id).await;
+                    return Err(e);
+                }
+            };
+            handles.push((task_id.clone(), Arc::clone(&handle)));
+            self.watch_task(handle, &cmd, false);
+            if let Some(mut run) = self.fleet_runs.get_mut(&run_id) {
+                run.members.push((agent_name.clone(), task_id.clone()));
+            }
+            members.push((agent_name.clone(), task_id));
+        }
+
+        if members.is_empty() {
+            self.fleet_runs.remove(&run_id);
+            return Ok(TriggerResponseBuilder::new()
+                .text(format!(
+                    "System at capacity: no member of fleet `{}` could be queued. Please try again later.",
+                    fleet_name
+                ))
+                .error()
+                .build());
+        }
+
+        let mut text = format!("Fleet started: `{}`\nFleet: {}\n", run_id, fleet_name);
+        for (agent_name, task_id) in &members {
+            text.push_str(&format!("\n• {}: `{}`", agent_name, task_id));
+        }
+        if !not_started.is_empty() {
+            text.push_str(&format!(
+                "\n\nNot started (system at capacity): {}",
+                not_started.join(", ")
+            ));
+        }
+        text.push_str(&format!(
+            "\n\nUse `/status fleet {}` to check progress",
+            run_id
+        ));
+
+        let response = TriggerResponseBuilder::new().text(text);
+        let response = if not_started.is_empty() {
+            response.success()
+        } else {
+            response.warning()
+        };
+
+        // Record each member's outcome, so the run can still be reported on
+        // once the orchestrator has dropped its tasks
+        let fleet_runs = Arc::clone(&self.fleet_runs);
+        tokio::spawn(async move {
+            for (task_id, handle) in handles {
+                let _ = handle.wait().await;
+                let status = handle.status().await;
+                if let Some(mut run) = fleet_runs.get_mut(&run_id) {
+                    run.outcomes.insert(task_id, status);
+                }
+            }
+            if let Some(mut run) = fleet_runs.get_mut(&run_id) {
+                run.finished_at = Some(Instant::now());
+            }
+        });
+        Ok(response.build())
+    }
+
+    /// Forget fleet runs finished longer than `fleet_run_retention_secs` ago
+    fn prune_fleet_runs(&self) {
+        let retention = Duration::from_secs(self.config.fleet_run_retention_secs);
+        self.fleet_runs.retain(|_, run| {
+            run.finished_at
+                .map_or(true, |finished_at| finished_at.elapsed() < retention)
+        });
+    }
+
+    /// Stop every member of a fleet run started so far and forget the run
+    async fn cancel_fleet_run(&self, run_id: &str) {
+        let Some((_, run)) = self.fleet_runs.remove(run_id) else {
+            return;
+        };
+        for (_, task_id) in run.members {
+            let _ = self.orchestrator.cancel_task(&task_id).await;
+            if let Some((_, running)) = self.cancellations.remove(&task_id) {
+                let _ = running.cancel.send(());
+            }
+        }
+    }
+
+    /// Record a started task's outcome and release the user's task slot
+    /// once it finishes
+    ///
+    /// With `notify` the final status is also posted to the channel the
+    /// command came from.
+    fn watch_task(&self, handle: Arc<TaskHandle>, cmd: &TriggerCommand, notify: bool) {
+        let user_tasks = Arc::clone(&self.user_tasks);
+        let cancellations = Arc::clone(&self.cancellations);
+        let user_id = cmd.context.user_id.clone();
+        let platform = cmd.context.platform.clone();
+        let notify = if notify {
+            self.platforms
+                .get(&cmd.context.platform)
+                .cloned()
+                .map(|platform_impl| (platform_impl, cmd.context.channel_id.clone()))
+        } else {
+            None
+        };
+
+        tokio::spawn(async move {
+            let result = handle.wait().await;
+            cancellations.remove(&handle.task().await.id);
+            metrics::record_task(match handle.status().await {
+                TaskStatus::Completed => "completed",
+                TaskStatus::Cancelled => "cancelled",
+                _ => "failed",
+            });
+
+            if let Some((platform_impl, channel_id)) = notify {
+                let response = match result {
+                    Ok(result) if result.success => TriggerResponseBuilder::new()
+                        .text(format!("Task completed: `{}`", result.task_id))
+                        .success()
+                        .build(),
+                    Ok(result) => TriggerResponseBuilder::new()
+                        .text(format!(
+                            "Task failed: `{}`\n{}",
+                            result.task_id,
+                            result.error.unwrap_or_default()
+                        ))
+                        .error()
+                        .build(),
+                    Err(e) => TriggerResponseBuilder::new()
+                        .text(format!("Task execution error: {}", e))
+                        .error()
+                        .build(),
+                };
+
+                let response = Self::format_response_for_platform(&platform, response);
+                let response = drop_unsupported_attachments(platform_impl.as_ref(), response);
+                let _ = platform_impl.send_response(&channel_id, response).await;
+            }
+
+            // Decrement user task count
+            if let Some(mut count) = user_tasks.get_mut(&user_id) {
+                if *count > 0 {
+                    *count -= 1;
+                }
+            }
+        });
+    }
+
     /// Handle create command
+    ///
+    /// `/create agent <name> --model=... --prompt="..."` loads a new agent
+    /// into the shared runtime; an existing agent is only replaced with
+    /// `--force`.
     async fn handle_create_command(&self, cmd: TriggerCommand) -> AofResult<TriggerResponse> {
+        if cmd.target != TriggerTarget::Agent {
+            return Ok(TriggerResponseBuilder::new()
+                .text(format!("Create command not supported for {:?}", cmd.target))
+                .error()
+                .build());
+        }
+
+        if !self.config.agent_creators.contains(&cmd.context.user_id) {
+            return Ok(TriggerResponseBuilder::new()
+                .text("You are not allowed to create agents")
+                .error()
+                .build());
+        }
+
+        let agent_name = cmd.get_arg(0).map_cmd_err()?;
+        let config = match agent_config_from_params(agent_name, &cmd.params) {
+            Ok(config) => config,
+            Err(message) => {
+                return Ok(TriggerResponseBuilder::new()
+                    .text(format!("Invalid agent `{}`: {}", agent_name, message))
+                    .error()
+                    .build());
+            }
+        };
+        let denied: Vec<&str> = config
+            .tools
+            .iter()
+            .map(String::as_str)
+            .filter(|t| !self.config.can_create_with_tool(t))
+            .collect();
+        if !denied.is_empty() {
+            return Ok(TriggerResponseBuilder::new()
+                .text(format!(
+                    "Invalid agent `{}`: tools not allowed: {}",
+                    agent_name,
+                    denied.join(", ")
+                ))
+                .error()
+                .build());
+        }
+        let force = cmd.get_param("force").is_some_and(|v| v != "false");
+        let model = config.model.clone();
+        let exists = || {
+            Ok(TriggerResponseBuilder::new()
+                .text(format!(
+                    "Agent already exists: `{}`\nUse `--force` to replace it",
+                    agent_name
+                ))
+                .error()
+                .build())
+        };
+
+        // Build under the read lock (this may start MCP servers) so other
+        // commands keep running, then take the write lock only to add it
+        let executor = {
+            let runtime = self.runtime.read().await;
+            if !force && runtime.get_agent(agent_name).is_some() {
+                return exists();
+            }
+            match runtime.build_agent(config).await {
+                Ok(executor) => executor,
+                Err(e) => {
+                    return Ok(TriggerResponseBuilder::new()
+                        .text(format!("Failed to create agent `{}`: {}", agent_name, e))
+                        .error()
+                        .build());
+                }
+            }
+        };
+
+        let mut runtime = self.runtime.write().await;
+        if !force && runtime.get_agent(agent_name).is_some() {
+            return exists();
+        }
+        runtime.add_agent(executor);
+        drop(runtime);
+        info!(
+            "Agent created by {} via {}: {}",
+            cmd.context.user_id, cmd.context.platform, agent_name
+        );
+
         Ok(TriggerResponseBuilder::new()
-            .text("Create command not yet implemented")
+            .text(format!(
+                "Agent created: `{}`\nModel: {}\nUse `/run agent {} <input>` to run it",
+                agent_name, model, agent_name
+            ))
+            .success()
             .build())
     }
 
@@ -374,19 +910,22 @@ impl TriggerHandler {
                     let status = handle.status().await;
 
                     // Build detailed status message
-                    let status_icon = match status {
-                        TaskStatus::Pending => "⏳",
-                        TaskStatus::Running => "▶️",
-                        TaskStatus::Completed => "✅",
-                        TaskStatus::Failed => "❌",
-                        TaskStatus::Cancelled => "🚫",
-                    };
+                    let status_icon = status_icon(status);
 
                     let mut text = format!(
                         "{} **Task Status**\n\n**ID:** `{}`\n**Name:** {}\n**Agent:** {}\n**Status:** {:?}",
                         status_icon, task.id, task.name, task.agent_name, status
                     );
 
+                    // Add latest progress if the executor reported any
+                    let progress = handle.progress().await;
+                    if progress.percent > 0 || !progress.message.is_empty() {
+                        text.push_str(&format!("\n**Progress:** {}%", progress.percent));
+                        if !progress.message.is_empty() {
+                            text.push_str(&format!(" - {}", progress.message));
+                        }
+                    }
+
                     // Add priority if set
                     if task.priority > 0 {
                         text.push_str(&format!("\n**Priority:** {}", task.priority));
@@ -413,11 +952,51 @@ impl TriggerHandler {
                         .build())
                 } else {
                     Ok(TriggerResponseBuilder::new()
-                        .text(format!("❌ Task not found: `{}`", task_id))
+                        .text(format!("Task not found: `{}`", task_id))
                         .error()
                         .build())
                 }
             }
+            TriggerTarget::Fleet => {
+                let run_id = cmd.get_arg(0).map_cmd_err()?;
+                self.prune_fleet_runs();
+                let Some(run) = self.fleet_runs.get(run_id).map(|run| run.clone()) else {
+                    return Ok(TriggerResponseBuilder::new()
+                        .text(format!("Fleet run not found or expired: `{}`", run_id))
+                        .error()
+                        .build());
+                };
+
+                let mut finished = 0;
+                let mut lines = String::new();
+                for (agent_name, task_id) in &run.members {
+                    let status = match self.orchestrator.get_task(task_id) {
+                        Some(handle) => Some(handle.status().await),
+                        None => run.outcomes.get(task_id).copied(),
+                    };
+                    let line = match status {
+                        Some(status) => {
+                            if status.is_finished() {
+                                finished += 1;
+                            }
+                            format!("{} {}: {:?}", status_icon(status), agent_name, status)
+                        }
+                        None => format!("• {}: no longer tracked", agent_name),
+                    };
+                    lines.push_str(&format!("\n{} (`{}`)", line, task_id));
+                }
+
+                Ok(TriggerResponseBuilder::new()
+                    .text(format!(
+                        "**Fleet Status**\n\n**ID:** `{}`\n**Fleet:** {}\n**Finished:** {}/{}\n{}",
+                        run_id,
+                        run.fleet,
+                        finished,
+                        run.members.len(),
+                        lines
+                    ))
+                    .build())
+            }
             _ => Ok(TriggerResponseBuilder::new()
                 .text(format!("Status not supported for {:?}", cmd.target))
                 .error()
@@ -426,21 +1005,50 @@ impl TriggerHandler {
     }
 
     /// Handle cancel command
+    ///
+    /// Marks the task cancelled and stops its agent run; the reply carries
+    /// whatever output the run produced before it was stopped.
     async fn handle_cancel_command(&self, cmd: TriggerCommand) -> AofResult<TriggerResponse> {
         match cmd.target {
             TriggerTarget::Task => {
                 let task_id = cmd.get_arg(0).map_cmd_err()?;
+                let was_running = match self.orchestrator.get_task(task_id) {
+                    Some(handle) => handle.status().await == TaskStatus::Running,
+                    None => false,
+                };
 
-                match self.orchestrator.cancel_task(task_id).await {
-                    Ok(_) => Ok(TriggerResponseBuilder::new()
-                        .text(format!("✓ Task cancelled: {}", task_id))
-                        .success()
-                        .build()),
-                    Err(e) => Ok(TriggerResponseBuilder::new()
+                if let Err(e) = self.orchestrator.cancel_task(task_id).await {
+                    return Ok(TriggerResponseBuilder::new()
                         .text(format!("Failed to cancel task: {}", e))
                         .error()
-                        .build()),
+                        .build());
                 }
+
+                let partial = match self.cancellations.remove(task_id) {
+                    Some((_, run)) => {
+                        // A queued run never starts, so there is nothing to wait for
+                        if run.cancel.send(()).is_ok() && was_running {
+                            tokio::time::timeout(CANCEL_REPLY_TIMEOUT, run.partial)
+                                .await
+                                .ok()
+                                .and_then(Result::ok)
+                                .unwrap_or_default()
+                        } else {
+                            String::new()
+                        }
+                    }
+                    None => String::new(),
+                };
+
+                let text = if partial.is_empty() {
+                    format!("Task cancelled: {}", task_id)
+                } else {
+                    format!(
+                        "Task cancelled: {}\n\nPartial output:\n{}",
+                        task_id, partial
+                    )
+                };
+                Ok(TriggerResponseBuilder::new().text(text).success().build())
             }
             _ => Ok(TriggerResponseBuilder::new()
                 .text(format!("Cancel not supported for {:?}", cmd.target))
@@ -475,13 +1083,7 @@ impl TriggerHandler {
                     for (i, task_id) in task_ids.iter().take(display_limit).enumerate() {
                         if let Some(handle) = self.orchestrator.get_task(task_id) {
                             let status = handle.status().await;
-                            let icon = match status {
-                                TaskStatus::Pending => "⏳",
-                                TaskStatus::Running => "▶️",
-                                TaskStatus::Completed => "✅",
-                                TaskStatus::Failed => "❌",
-                                TaskStatus::Cancelled => "🚫",
-                            };
+                            let icon = status_icon(status);
                             text.push_str(&format!("\n{}. {} `{}`", i + 1, icon, task_id));
                         } else {
                             text.push_str(&format!("\n{}. `{}`", i + 1, task_id));
@@ -497,6 +1099,25 @@ impl TriggerHandler {
 
                 Ok(TriggerResponseBuilder::new().text(text).build())
             }
+            TriggerTarget::Agent => {
+                let agents = self.agents().await;
+
+                let mut text = format!("🤖 **Loaded Agents ({})**\n", agents.len());
+                for agent in &agents {
+                    text.push_str(&format!(
+                        "\n• `{}` - {} ({} {})",
+                        agent.name,
+                        agent.model,
+                        agent.tools,
+                        if agent.tools == 1 { "tool" } else { "tools" }
+                    ));
+                }
+                if agents.is_empty() {
+                    text.push_str("\n_No agents loaded_");
+                }
+
+                Ok(TriggerResponseBuilder::new().text(text).build())
+            }
             _ => Ok(TriggerResponseBuilder::new()
                 .text(format!("List not supported for {:?}", cmd.target))
                 .error()
@@ -511,9 +1132,14 @@ impl TriggerHandler {
 
 **Basic Commands:**
 • `/run agent <name> <input>` - Run an agent
+• `/run fleet <name> <input>` - Run every agent in a fleet
 • `/status task <id>` - Check task status
+• `/status fleet <id>` - Check a fleet run
+• `/create agent <name> --model=<model> --prompt="..."` - Create an agent
 • `/cancel task <id>` - Cancel a running task
 • `/list tasks` - List all tasks
+• `/list agents` - List loaded agents
+• `/info` - Show system info
 • `/help` - Show this help
 
 **Examples:**
@@ -568,6 +1194,7 @@ impl TriggerHandler {
     /// Handle parse error
     async fn handle_parse_error(
         &self,
+        platform: &str,
         _message: &TriggerMessage,
         error: CommandError,
     ) -> TriggerResponse {
@@ -586,7 +1213,41 @@ impl TriggerHandler {
             }
         };
 
-        TriggerResponseBuilder::new().text(text).error().build()
+        TriggerResponseBuilder::new()
+            .text(Self::format_error_text_for_platform(platform, &text))
+            .error()
+            .build()
+    }
+
+    /// Whether `message` was already handled within the dedup window
+    fn is_duplicate(&self, platform: &str, message: &TriggerMessage) -> bool {
+        if self.config.dedup_window_secs == 0 {
+            return false;
+        }
+
+        // Message IDs are only unique per chat on some platforms
+        let key = format!("{}:{}:{}", platform, message.channel_id, message.id);
+        let mut recent = self.recent_messages.lock().unwrap_or_else(|e| e.into_inner());
+        !recent.insert(
+            key,
+            Duration::from_secs(self.config.dedup_window_secs),
+            self.config.dedup_capacity.max(1),
+        )
+    }
+
+    /// Register the token `/cancel task` fires to stop `task_id`'s run
+    ///
+    /// Returns the receiver the run's executor watches and the sender the
+    /// run reports its partial output on once stopped.
+    fn register_cancellation(
+        &self,
+        task_id: &str,
+    ) -> (oneshot::Receiver<()>, oneshot::Sender<String>) {
+        let (cancel, cancel_rx) = oneshot::channel();
+        let (partial_tx, partial) = oneshot::channel();
+        self.cancellations
+            .insert(task_id.to_string(), RunCancellation { cancel, partial });
+        (cancel_rx, partial_tx)
     }
 
     /// Increment user task count
@@ -597,10 +1258,19 @@ impl TriggerHandler {
             .or_insert(1);
     }
 
+    /// Decrement user task count
+    fn decrement_user_tasks(&self, user_id: &str) {
+        if let Some(mut count) = self.user_tasks.get_mut(user_id) {
+            if *count > 0 {
+                *count -= 1;
+            }
+        }
+    }
+
     /// Format error for specific platform
     ///
     /// Provides platform-specific error formatting to enhance user experience
-    fn format_error_for_platform(&self, platform: &str, error: &AofError) -> String {
+    fn format_error_for_platform(platform: &str, error: &AofError) -> String {
         // Base error message
         let base_msg = match error {
             AofError::Agent(msg) => format!("Agent Error: {}", msg),
@@ -612,6 +1282,11 @@ impl TriggerHandler {
             _ => format!("Error: {}", error),
         };
 
+        Self::format_error_text_for_platform(platform, &base_msg)
+    }
+
+    /// Format a plain error message for specific platform
+    fn format_error_text_for_platform(platform: &str, base_msg: &str) -> String {
         // Platform-specific formatting
         match platform.to_lowercase().as_str() {
             "slack" => {
@@ -638,7 +1313,7 @@ impl TriggerHandler {
     }
 
     /// Format success message for specific platform
-    fn format_success_for_platform(&self, platform: &str, message: &str) -> String {
+    fn format_success_for_platform(platform: &str, message: &str) -> String {
         match platform.to_lowercase().as_str() {
             "slack" => format!("✅ *Success*\n{}", message),
             "discord" => format!("✅ **Success**\n{}", message),
@@ -646,11 +1321,449 @@ impl TriggerHandler {
             _ => format!("✅ {}", message),
         }
     }
+
+    /// Apply platform-specific formatting to a command response based on its status
+    fn format_response_for_platform(platform: &str, mut response: TriggerResponse) -> TriggerResponse {
+        response.text = match response.status {
+            ResponseStatus::Success => Self::format_success_for_platform(platform, &response.text),
+            ResponseStatus::Error => Self::format_error_text_for_platform(platform, &response.text),
+            ResponseStatus::Warning | ResponseStatus::Info => return response,
+        };
+        response
+    }
+}
+
+/// Remove attachments from `response` if `platform_impl` can't upload files
+fn drop_unsupported_attachments(
+    platform_impl: &dyn TriggerPlatform,
+    mut response: TriggerResponse,
+) -> TriggerResponse {
+    if !response.attachments.is_empty() && !platform_impl.supports_files() {
+        warn!(
+            "Skipping {} attachments: {} does not support files",
+            response.attachments.len(),
+            platform_impl.platform_name()
+        );
+        response.attachments.clear();
+    }
+    response
+}
+
+/// Icon shown next to a task status
+fn status_icon(status: TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Pending => "⏳",
+        TaskStatus::Running => "▶️",
+        TaskStatus::Completed => "✅",
+        TaskStatus::Failed => "❌",
+        TaskStatus::Cancelled => "🚫",
+    }
+}
+
+/// Build the `AgentConfig` for `/create agent` from its named parameters
+fn agent_config_from_params(
+    name: &str,
+    params: &HashMap<String, String>,
+) -> Result<AgentConfig, String> {
+    if let Some(unknown) = params
+        .keys()
+        .find(|key| !CREATE_AGENT_PARAMS.contains(&key.as_str()))
+    {
+        return Err(format!("unknown parameter `--{}`", unknown));
+    }
+
+    fn number<T: std::str::FromStr>(
+        params: &HashMap<String, String>,
+        key: &str,
+    ) -> Result<Option<T>, String> {
+        params
+            .get(key)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| format!("`--{}` must be a number, got `{}`", key, value))
+            })
+            .transpose()
+    }
+
+    Ok(AgentConfig {
+        name: name.to_string(),
+        system_prompt: params.get("prompt").cloned(),
+        model: params
+            .get("model")
+            .cloned()
+            .unwrap_or_else(|| DEFAULT_MODEL.to_string()),
+        tools: params
+            .get("tools")
+            .map(|tools| {
+                tools
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|tool| !tool.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default(),
+        max_iterations: number(params, "max-iterations")?.unwrap_or(10),
+        temperature: number(params, "temperature")?.unwrap_or(0.7),
+        max_tokens: number(params, "max-tokens")?,
+        ..Default::default()
+    })
+}
+
+/// Conversation session of the user issuing a command in a channel
+///
+/// Each user gets their own history per channel, so conversations of
+/// different users or chats never mix.
+fn session_id(context: &CommandContext) -> String {
+    format!(
+        "{}:{}:{}",
+        context.platform, context.channel_id, context.user_id
+    )
+}
+
+/// Execute a trigger task
+///
+/// Agents loaded in the runtime run as `loaded`; any other agent name gets a
+/// default agent configuration. When `stream_tx` is set the streaming
+/// executor is used and its events are forwarded to the channel.
+/// Firing `cancel_rx` stops the run, which then sends the output it produced
+/// so far on `partial_tx`. The output carries the run's token usage and
+/// cost; a run that fails, including its model setup, fails the task.
+async fn run_agent_task(
+    task: Task,
+    progress: ProgressReporter,
+    loaded: Option<Arc<AgentExecutor>>,
+    stream_tx: Option<mpsc::Sender<StreamEvent>>,
+    cancel_rx: oneshot::Receiver<()>,
+    partial_tx: oneshot::Sender<String>,
+) -> AofResult<TaskOutput> {
+    // Create AgentContext, continuing the conversation of the task's session
+    let mut context = AgentContext::new(&task.input);
+    context.session_id = task
+        .metadata
+        .get(SESSION_ID_KEY)
+        .and_then(|v| v.as_str())
+        .map(String::from);
+
+    let executor = match loaded {
+        Some(executor) => executor,
+        None => {
+            progress.report_progress(10, "Creating model").await;
+            let executor = default_agent_executor(&task.agent_name)
+                .await
+                .map_err(|e| AofError::model(format!("Failed to create model: {}", e)))?;
+            Arc::new(executor)
+        }
+    };
+
+    // Execute the agent until it finishes or is cancelled
+    progress.report_progress(25, "Running agent").await;
+    let result = match stream_tx {
+        Some(tx) => {
+            executor
+                .execute_streaming_cancellable(&mut context, tx, cancel_rx)
+                .await
+        }
+        None => executor.execute_cancellable(&mut context, cancel_rx).await,
+    };
+    // Only read when `/cancel task` stopped the run
+    let _ = partial_tx.send(partial_output(&context));
+
+    progress.report_progress(100, "Finished").await;
+    Ok(TaskOutput::from_metadata(result?, &context.metadata))
+}
+
+/// Assistant output of the current run in `context`, skipping restored history
+///
+/// The run starts after the last user message: the executor adds none after
+/// the input, and pruning only drops the oldest messages (standing in a
+/// summary for them), so whatever follows it belongs to this run.
+fn partial_output(context: &AgentContext) -> String {
+    let start = context
+        .messages
+        .iter()
+        .rposition(|m| m.role == MessageRole::User)
+        .map_or(0, |i| i + 1);
+    context.messages[start..]
+        .iter()
+        .filter(|m| m.role == MessageRole::Assistant && !m.content.is_empty())
+        .map(|m| m.content.as_str())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Agent executor with a minimal default configuration for `agent_name`
+async fn default_agent_executor(agent_name: &str) -> AofResult<AgentExecutor> {
+    use aof_core::{ModelConfig, ModelProvider};
+    use aof_llm::ProviderFactory;
+    use aof_memory::{InMemoryBackend, SimpleMemory};
+
+    // Create a minimal agent configuration for the task
+    let config = AgentConfig {
+        name: agent_name.to_string(),
+        system_prompt: Some("You are a helpful AI assistant.".to_string()),
+        model: DEFAULT_MODEL.to_string(),
+        max_tokens: Some(4096),
+        ..Default::default()
+    };
+
+    // Create model
+    let model_config = ModelConfig {
+        model: DEFAULT_MODEL.to_string(),
+        provider: ModelProvider::Anthropic,
+        api_key: std::env::var("ANTHROPIC_API_KEY").ok(),
+        endpoint: None,
+        temperature: 0.7,
+        max_tokens: Some(4096),
+        timeout_secs: 60,
+        headers: HashMap::new(),
+        extra: HashMap::new(),
+    };
+    let model = ProviderFactory::create(model_config).await?;
+
+    // Create memory backend
+    let memory_backend = InMemoryBackend::new();
+    let memory = Arc::new(SimpleMemory::new(Arc::new(memory_backend)));
+
+    // Create AgentExecutor with model and memory, but no tool executor for now
+    Ok(AgentExecutor::new(
+        config,
+        model,
+        None, // No tool executor for trigger-based agents
+        Some(memory),
+    ))
+}
+
+/// Relay agent stream events to the platform as message edits
+///
+/// An edit is posted at the end of every iteration, at most every
+/// `STREAM_EDIT_INTERVAL` while text is streaming, and once more with the
+/// final content or error.
+async fn forward_stream_updates(
+    platform_impl: Arc<dyn TriggerPlatform>,
+    platform: String,
+    channel_id: String,
+    message_id: String,
+    mut rx: mpsc::Receiver<StreamEvent>,
+) {
+    let mut buffer = String::new();
+    let mut last_edit = Instant::now();
+
+    while let Some(event) = rx.recv().await {
+        let (response, finished) = match event {
+            StreamEvent::TextDelta { delta, .. } => {
+                buffer.push_str(&delta);
+                if last_edit.elapsed() < STREAM_EDIT_INTERVAL {
+                    continue;
+                }
+                (TriggerResponseBuilder::new().text(format!("{}…", buffer)).build(), false)
+            }
+            StreamEvent::IterationComplete { iteration, .. } => {
+                let text = if buffer.is_empty() {
+                    format!("Working... (iteration {} complete)", iteration)
+                } else {
+                    buffer.clone()
+                };
+                (TriggerResponseBuilder::new().text(text).build(), false)
+            }
+            StreamEvent::Done { content, .. } => {
+                (TriggerResponseBuilder::new().text(content).success().build(), true)
+            }
+            StreamEvent::Error { message } => {
+                (TriggerResponseBuilder::new().text(message).error().build(), true)
+            }
+            _ => continue,
+        };
+
+        let response = TriggerHandler::format_response_for_platform(&platform, response);
+        if let Err(e) = platform_impl
+            .edit_response(&channel_id, &message_id, response)
+            .await
+        {
+            warn!("Failed to post stream update: {:?}", e);
+        }
+        last_edit = Instant::now();
+
+        if finished {
+            break;
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::platforms::{PlatformError, TriggerUser};
+    use async_trait::async_trait;
+    use tokio::sync::Mutex;
+
+    /// Platform that records every response sent through it
+    struct RecordingPlatform {
+        name: &'static str,
+        ephemeral: bool,
+        sent: Mutex<Vec<TriggerResponse>>,
+        edits: Mutex<Vec<(String, TriggerResponse)>>,
+    }
+
+    impl RecordingPlatform {
+        fn new(name: &'static str) -> Arc<Self> {
+            Arc::new(Self {
+                name,
+                ephemeral: false,
+                sent: Mutex::new(Vec::new()),
+                edits: Mutex::new(Vec::new()),
+            })
+        }
+
+        fn with_ephemeral(name: &'static str) -> Arc<Self> {
+            Arc::new(Self {
+                name,
+                ephemeral: true,
+                sent: Mutex::new(Vec::new()),
+                edits: Mutex::new(Vec::new()),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl TriggerPlatform for RecordingPlatform {
+        async fn parse_message(
+            &self,
+            _raw: &[u8],
+            _headers: &HashMap<String, String>,
+        ) -> Result<TriggerMessage, PlatformError> {
+            Err(PlatformError::UnsupportedMessageType)
+        }
+
+        async fn send_response(
+            &self,
+            _channel: &str,
+            response: TriggerResponse,
+        ) -> Result<(), PlatformError> {
+            self.sent.lock().await.push(response);
+            Ok(())
+        }
+
+        async fn edit_response(
+            &self,
+            _channel: &str,
+            message_id: &str,
+            response: TriggerResponse,
+        ) -> Result<(), PlatformError> {
+            self.edits.lock().await.push((message_id.to_string(), response));
+            Ok(())
+        }
+
+        fn platform_name(&self) -> &'static str {
+            self.name
+        }
+
+        async fn verify_signature(&self, _payload: &[u8], _signature: &str) -> bool {
+            true
+        }
+
+        fn bot_name(&self) -> &str {
+            "aofbot"
+        }
+
+        fn supports_ephemeral(&self) -> bool {
+            self.ephemeral
+        }
+    }
+
+    fn message(platform: &str, text: &str) -> TriggerMessage {
+        let user = TriggerUser {
+            id: "user123".to_string(),
+            username: Some("testuser".to_string()),
+            display_name: None,
+            is_bot: false,
+        };
+
+        TriggerMessage::new(
+            "msg1".to_string(),
+            platform.to_string(),
+            "channel1".to_string(),
+            user,
+            text.to_string(),
+        )
+    }
+
+    async fn responses_with_config(
+        platform: &'static str,
+        config: TriggerHandlerConfig,
+        text: &str,
+    ) -> Vec<TriggerResponse> {
+        let recorder = RecordingPlatform::new(platform);
+        let mut handler = TriggerHandler::with_config(Arc::new(RuntimeOrchestrator::new()), config);
+        handler.register_platform(recorder.clone());
+
+        handler
+            .handle_message(platform, message(platform, text))
+            .await
+            .unwrap();
+
+        let sent = recorder.sent.lock().await;
+        sent.clone()
+    }
+
+    /// Model whose calls never return
+    struct StalledModel(aof_core::ModelConfig);
+
+    #[async_trait]
+    impl aof_core::Model for StalledModel {
+        async fn generate(
+            &self,
+            _request: &aof_core::ModelRequest,
+        ) -> AofResult<aof_core::ModelResponse> {
+            std::future::pending().await
+        }
+
+        async fn generate_stream(
+            &self,
+            _request: &aof_core::ModelRequest,
+        ) -> AofResult<
+            std::pin::Pin<Box<dyn futures::Stream<Item = AofResult<aof_core::StreamChunk>> + Send>>,
+        > {
+            std::future::pending().await
+        }
+
+        fn config(&self) -> &aof_core::ModelConfig {
+            &self.0
+        }
+
+        fn provider(&self) -> aof_core::ModelProvider {
+            aof_core::ModelProvider::Custom
+        }
+    }
+
+    /// Agent that keeps running until it is cancelled
+    fn stalled_agent(name: &str) -> AgentExecutor {
+        let model = StalledModel(aof_core::ModelConfig {
+            model: "mock-model".to_string(),
+            provider: aof_core::ModelProvider::Custom,
+            api_key: None,
+            endpoint: None,
+            temperature: 0.7,
+            max_tokens: None,
+            timeout_secs: 60,
+            headers: HashMap::new(),
+            extra: HashMap::new(),
+        });
+        let config = AgentConfig {
+            name: name.to_string(),
+            model: "mock-model".to_string(),
+            ..Default::default()
+        };
+        AgentExecutor::new(config, Box::new(model), None, None)
+    }
+
+    async fn last_response(platform: &'static str, text: &str) -> TriggerResponse {
+        responses_with_config(platform, TriggerHandlerConfig::default(), text)
+            .await
+            .pop()
+            .expect("no response sent")
+    }
 
     #[tokio::test]
     async fn test_handler_creation() {
@@ -660,4 +1773,817 @@ mod tests {
         assert_eq!(handler.platforms.len(), 0);
         assert!(handler.config.auto_ack);
     }
+
+    #[tokio::test]
+    async fn test_list_is_not_acknowledged() {
+        let responses =
+            responses_with_config("slack", TriggerHandlerConfig::default(), "/list tasks").await;
+
+        assert_eq!(responses.len(), 1);
+        assert!(!responses[0].text.contains("Processing your request"));
+    }
+
+    #[tokio::test]
+    async fn test_run_uses_custom_ack_message() {
+        let config = TriggerHandlerConfig {
+            ack_message: Some("On it, give me a minute".to_string()),
+            ..Default::default()
+        };
+        let responses =
+            responses_with_config("slack", config, "/run agent helper check disk").await;
+
+        assert_eq!(responses[0].text, "On it, give me a minute");
+        assert_eq!(responses[0].ephemeral_to, None);
+        assert!(responses.len() > 1);
+    }
+
+    #[tokio::test]
+    async fn test_ack_is_ephemeral_where_supported() {
+        let recorder = RecordingPlatform::with_ephemeral("slack");
+        let mut handler = TriggerHandler::new(Arc::new(RuntimeOrchestrator::new()));
+        handler.register_platform(recorder.clone());
+
+        handler
+            .handle_message("slack", message("slack", "/run agent helper check disk"))
+            .await
+            .unwrap();
+
+        let sent = recorder.sent.lock().await;
+        assert_eq!(sent[0].text, "Processing your request...");
+        assert_eq!(sent[0].ephemeral_to.as_deref(), Some("user123"));
+        assert_eq!(sent[1].ephemeral_to, None);
+    }
+
+    #[test]
+    fn test_should_ack() {
+        let config = TriggerHandlerConfig::default();
+        assert!(config.should_ack(CommandType::Run));
+        assert!(!config.should_ack(CommandType::Status));
+
+        let config = TriggerHandlerConfig {
+            auto_ack: false,
+            ..Default::default()
+        };
+        assert!(!config.should_ack(CommandType::Run));
+    }
+
+    #[tokio::test]
+    async fn test_slack_command_failure_is_code_fenced() {
+        // Missing agent name makes the run command fail during execution
+        let response = last_response("slack", "/run agent").await;
+
+        assert_eq!(response.status, ResponseStatus::Error);
+        assert!(response.text.starts_with("❌ *Error*"));
+        assert!(response.text.contains("```Configuration Error:"));
+        assert!(response.text.ends_with("```"));
+    }
+
+    #[tokio::test]
+    async fn test_telegram_command_failure_uses_backticks() {
+        let response = last_response("telegram", "/run agent").await;
+
+        assert_eq!(response.status, ResponseStatus::Error);
+        assert!(response.text.starts_with("❌ *Error*\n`Configuration Error:"));
+        assert!(response.text.ends_with('`'));
+        assert!(!response.text.contains("```"));
+    }
+
+    #[tokio::test]
+    async fn test_parse_error_is_formatted_for_platform() {
+        let response = last_response("slack", "/frobnicate agent").await;
+
+        assert_eq!(response.status, ResponseStatus::Error);
+        assert!(response.text.contains("```Unknown command: frobnicate"));
+    }
+
+    #[test]
+    fn test_success_formatting_for_platform() {
+        let response = TriggerResponseBuilder::new()
+            .text("Task cancelled: t1")
+            .success()
+            .build();
+
+        let slack = TriggerHandler::format_response_for_platform("slack", response.clone());
+        assert_eq!(slack.text, "✅ *Success*\nTask cancelled: t1");
+
+        let discord = TriggerHandler::format_response_for_platform("discord", response);
+        assert_eq!(discord.text, "✅ **Success**\nTask cancelled: t1");
+    }
+
+    #[tokio::test]
+    async fn test_run_fire_and_forget_replies_immediately() {
+        let config = TriggerHandlerConfig {
+            auto_ack: false,
+            ..Default::default()
+        };
+        let responses = responses_with_config("slack", config, "/run agent helper check disk").await;
+
+        assert_eq!(responses.len(), 1);
+        assert_eq!(responses[0].status, ResponseStatus::Success);
+        assert!(responses[0].text.contains("Task started: `trigger-user123-"));
+        assert!(responses[0].text.contains("Input: check disk"));
+    }
+
+    #[tokio::test]
+    async fn test_run_with_wait_reports_failed_run() {
+        use aof_runtime::replay::{CassetteEntry, CassetteWriter, RecordedError};
+        use aof_runtime::ReplayConfig;
+
+        // The agent's only model call fails
+        let cassette = std::env::temp_dir().join(format!(
+            "aof-trigger-failed-run-test-{}.jsonl",
+            std::process::id()
+        ));
+        CassetteWriter::create(&cassette)
+            .unwrap()
+            .append(&CassetteEntry::Model {
+                response: Err(RecordedError::Config("invalid API key".to_string())),
+            })
+            .unwrap();
+        let mut runtime = Runtime::new()
+            .with_replay(ReplayConfig::replay(&cassette))
+            .unwrap();
+        runtime
+            .load_agent_from_config(AgentConfig {
+                name: "helper".to_string(),
+                model: "anthropic:claude-3-5-sonnet-20241022".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let orchestrator = Arc::new(RuntimeOrchestrator::new());
+        let recorder = RecordingPlatform::new("slack");
+        let config = TriggerHandlerConfig {
+            auto_ack: false,
+            ..Default::default()
+        };
+        let mut handler = TriggerHandler::with_config(Arc::clone(&orchestrator), config);
+        handler.register_platform(recorder.clone());
+        handler.set_runtime(Arc::new(RwLock::new(runtime)));
+        handler
+            .handle_message(
+                "slack",
+                message("slack", "/run agent helper check disk --wait"),
+            )
+            .await
+            .unwrap();
+        let _ = std::fs::remove_file(&cassette);
+
+        let sent = recorder.sent.lock().await;
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].status, ResponseStatus::Error);
+        assert!(sent[0].text.contains("Task failed: `trigger-user123-"));
+        assert!(sent[0].text.contains("invalid API key"));
+
+        let task_id = orchestrator.list_tasks().pop().unwrap();
+        let handle = orchestrator.get_task(&task_id).unwrap();
+        assert_eq!(handle.status().await, TaskStatus::Failed);
+    }
+
+    #[test]
+    fn test_attachments_dropped_without_file_support() {
+        use crate::response::{Attachment, AttachmentType};
+
+        let response = TriggerResponseBuilder::new()
+            .text("Report ready")
+            .attachment(Attachment {
+                attachment_type: AttachmentType::File,
+                url: "https://example.com/report.csv".to_string(),
+                filename: None,
+                title: None,
+            })
+            .build();
+
+        let recorder = RecordingPlatform::new("slack");
+        assert!(!recorder.supports_files());
+        let response = drop_unsupported_attachments(recorder.as_ref(), response);
+        assert!(response.attachments.is_empty());
+        assert_eq!(response.text, "Report ready");
+    }
+
+    #[tokio::test]
+    async fn test_create_agent_then_run_it() {
+        use aof_core::{ModelResponse, StopReason, Usage};
+        use aof_runtime::replay::{CassetteEntry, CassetteWriter};
+        use aof_runtime::ReplayConfig;
+
+        // The created agent answers from a cassette instead of a live model
+        let cassette = std::env::temp_dir().join(format!(
+            "aof-trigger-create-test-{}.jsonl",
+            std::process::id()
+        ));
+        CassetteWriter::create(&cassette)
+            .unwrap()
+            .append(&CassetteEntry::Model {
+                response: Ok(ModelResponse {
+                    content: "Looks good to me".to_string(),
+                    tool_calls: vec![],
+                    stop_reason: StopReason::EndTurn,
+                    usage: Usage::default(),
+                    stop_detail: None,
+                    metadata: HashMap::new(),
+                }),
+            })
+            .unwrap();
+        let runtime = Runtime::new()
+            .with_replay(ReplayConfig::replay(&cassette))
+            .unwrap();
+
+        let recorder = RecordingPlatform::new("slack");
+        let config = TriggerHandlerConfig {
+            auto_ack: false,
+            agent_creators: vec!["user123".to_string()],
+            ..Default::default()
+        };
+        let mut handler = TriggerHandler::with_config(Arc::new(RuntimeOrchestrator::new()), config);
+        handler.register_platform(recorder.clone());
+        handler.set_runtime(Arc::new(RwLock::new(runtime)));
+
+        let commands = [
+            "/create agent reviewer --model=anthropic:claude-3-5-sonnet-20241022 --prompt=\"You review pull requests\"",
+            "/create agent reviewer --model=openai:gpt-4o",
+            "/run agent reviewer check this diff --wait",
+        ];
+        for (i, text) in commands.into_iter().enumerate() {
+            let mut msg = message("slack", text);
+            msg.id = format!("msg{}", i);
+            handler.handle_message("slack", msg).await.unwrap();
+        }
+        let _ = std::fs::remove_file(&cassette);
+
+        let sent = recorder.sent.lock().await;
+        assert_eq!(sent[0].status, ResponseStatus::Success);
+        assert!(sent[0].text.contains("Agent created: `reviewer`"));
+
+        // An existing agent is kept unless --force is given
+        assert_eq!(sent[1].status, ResponseStatus::Error);
+        assert!(sent[1].text.contains("Agent already exists: `reviewer`"));
+
+        assert_eq!(sent[2].status, ResponseStatus::Success);
+        assert!(sent[2].text.contains("Agent: reviewer"));
+        assert!(sent[2].text.contains("Looks good to me"));
+    }
+
+    async fn create_response(config: TriggerHandlerConfig, text: &str) -> TriggerResponse {
+        let config = TriggerHandlerConfig {
+            agent_creators: vec!["user123".to_string()],
+            ..config
+        };
+        responses_with_config("slack", config, text)
+            .await
+            .pop()
+            .expect("no response sent")
+    }
+
+    #[tokio::test]
+    async fn test_create_agent_rejects_unknown_params() {
+        let response = create_response(
+            TriggerHandlerConfig::default(),
+            "/create agent reviewer --modle=gpt-4o",
+        )
+        .await;
+        assert_eq!(response.status, ResponseStatus::Error);
+        assert!(response.text.contains("unknown parameter `--modle`"));
+    }
+
+    #[tokio::test]
+    async fn test_create_agent_requires_listed_creator() {
+        let response = last_response("slack", "/create agent reviewer --model=openai:gpt-4o").await;
+        assert_eq!(response.status, ResponseStatus::Error);
+        assert!(response.text.contains("not allowed to create agents"));
+    }
+
+    #[tokio::test]
+    async fn test_create_agent_rejects_system_tools_by_default() {
+        let text = "/create agent ops --model=openai:gpt-4o --tools=shell,kubectl";
+        let response = create_response(TriggerHandlerConfig::default(), text).await;
+        assert_eq!(response.status, ResponseStatus::Error);
+        assert!(response.text.contains("tools not allowed: shell, kubectl"));
+
+        // An explicit allow-list replaces the default one
+        let config = TriggerHandlerConfig {
+            creatable_tools: Some(vec!["kubectl".to_string()]),
+            ..Default::default()
+        };
+        let response = create_response(config, text).await;
+        assert!(response.text.contains("tools not allowed: shell"));
+        assert!(!response.text.contains("kubectl"));
+    }
+
+    #[tokio::test]
+    async fn test_run_at_capacity_reports_queue_position() {
+        let orchestrator =
+            Arc::new(RuntimeOrchestrator::with_max_concurrent(1).with_max_queue_depth(2));
+
+        // Occupy the only execution slot
+        orchestrator.submit_task(Task::new(
+            "busy".to_string(),
+            "Busy Task".to_string(),
+            "helper".to_string(),
+            "long job".to_string(),
+        ));
+        let (_release, released) = tokio::sync::oneshot::channel::<()>();
+        let busy = orchestrator
+            .execute_task("busy", |_task, _progress| async move {
+                let _ = released.await;
+                Ok("done".to_string())
+            })
+            .await
+            .unwrap();
+        while busy.status().await != TaskStatus::Running {
+            tokio::task::yield_now().await;
+        }
+
+        let recorder = RecordingPlatform::new("slack");
+        let config = TriggerHandlerConfig {
+            auto_ack: false,
+            ..Default::default()
+        };
+        let mut handler = TriggerHandler::with_config(orchestrator, config);
+        handler.register_platform(recorder.clone());
+
+        handler
+            .handle_message("slack", message("slack", "/run agent helper check disk"))
+            .await
+            .unwrap();
+        let mut second = message("slack", "/run agent helper check memory");
+        second.id = "msg2".to_string();
+        handler.handle_message("slack", second).await.unwrap();
+
+        let sent = recorder.sent.lock().await;
+        assert_eq!(sent[0].status, ResponseStatus::Warning);
+        assert!(sent[0]
+            .text
+            .contains("System at capacity, task `trigger-user123-"));
+        assert!(sent[0].text.contains("queued at position 1"));
+        assert!(!sent[0].text.contains("Task started"));
+
+        // The queue limit turns the next task away
+        assert_eq!(sent[1].status, ResponseStatus::Error);
+        assert!(sent[1]
+            .text
+            .contains("System at capacity: 2 tasks already queued or running"));
+    }
+
+    #[tokio::test]
+    async fn test_run_fleet_starts_a_task_per_agent() {
+        let orchestrator = Arc::new(RuntimeOrchestrator::new());
+        let recorder = RecordingPlatform::new("slack");
+        let config = TriggerHandlerConfig {
+            auto_ack: false,
+            ..Default::default()
+        };
+        let mut handler = TriggerHandler::with_config(Arc::clone(&orchestrator), config);
+        handler.register_platform(recorder.clone());
+        handler.register_fleet(
+            "oncall",
+            vec!["k8s-ops".to_string(), "log-analyzer".to_string()],
+        );
+        let mut runtime = Runtime::new();
+        runtime.add_agent(stalled_agent("k8s-ops"));
+        runtime.add_agent(stalled_agent("log-analyzer"));
+        handler.set_runtime(Arc::new(RwLock::new(runtime)));
+
+        handler
+            .handle_message("slack", message("slack", "/run fleet oncall check prod"))
+            .await
+            .unwrap();
+
+        let mut tasks = orchestrator.list_tasks();
+        tasks.sort();
+        assert_eq!(tasks.len(), 2);
+        assert!(tasks[0].starts_with("fleet-user123-") && tasks[0].ends_with("-k8s-ops"));
+        assert!(tasks[1].ends_with("-log-analyzer"));
+
+        let started = recorder.sent.lock().await[0].clone();
+        assert_eq!(started.status, ResponseStatus::Success);
+        assert!(started.text.contains(&format!("k8s-ops: `{}`", tasks[0])));
+        assert!(started
+            .text
+            .contains(&format!("log-analyzer: `{}`", tasks[1])));
+
+        // The run id reports on both members
+        let run_id = tasks[0].trim_end_matches("-k8s-ops").to_string();
+        let mut status = message("slack", &format!("/status fleet {}", run_id));
+        status.id = "msg2".to_string();
+        handler.handle_message("slack", status).await.unwrap();
+
+        let report = recorder.sent.lock().await[1].clone();
+        assert!(report.text.contains("**Fleet:** oncall"));
+        assert!(report.text.contains(&format!("(`{}`)", tasks[0])));
+        assert!(report.text.contains(&format!("(`{}`)", tasks[1])));
+
+        // The finished run is still reported on, with each member's outcome
+        for (i, task_id) in tasks.iter().enumerate() {
+            let mut cancel = message("slack", &format!("/cancel task {}", task_id));
+            cancel.id = format!("cancel{}", i);
+            handler.handle_message("slack", cancel).await.unwrap();
+        }
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while handler
+                .fleet_runs
+                .get(&run_id)
+                .unwrap()
+                .finished_at
+                .is_none()
+            {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("fleet run was not marked finished");
+
+        let mut status = message("slack", &format!("/status fleet {}", run_id));
+        status.id = "msg3".to_string();
+        handler.handle_message("slack", status).await.unwrap();
+        let report = recorder.sent.lock().await.last().unwrap().clone();
+        assert_ne!(report.status, ResponseStatus::Error, "{}", report.text);
+        assert!(report.text.contains("**Finished:** 2/2"), "{}", report.text);
+        assert!(
+            report.text.contains("k8s-ops: Cancelled"),
+            "{}",
+            report.text
+        );
+
+        // and forgotten once the retention window has passed
+        handler.config.fleet_run_retention_secs = 0;
+        let mut status = message("slack", &format!("/status fleet {}", run_id));
+        status.id = "msg4".to_string();
+        handler.handle_message("slack", status).await.unwrap();
+        let report = recorder.sent.lock().await.last().unwrap().clone();
+        assert_eq!(report.status, ResponseStatus::Error);
+        assert!(report.text.contains("Fleet run not found or expired"));
+        assert!(handler.fleet_runs.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_run_fleet_counts_every_member_against_the_task_limit() {
+        let orchestrator = Arc::new(RuntimeOrchestrator::new());
+        let recorder = RecordingPlatform::new("slack");
+        let config = TriggerHandlerConfig {
+            auto_ack: false,
+            max_tasks_per_user: 2,
+            ..Default::default()
+        };
+        let mut handler = TriggerHandler::with_config(Arc::clone(&orchestrator), config);
+        handler.register_platform(recorder.clone());
+        handler.register_fleet(
+            "oncall",
+            vec![
+                "k8s-ops".to_string(),
+                "log-analyzer".to_string(),
+                "db-ops".to_string(),
+            ],
+        );
+
+        handler
+            .handle_message("slack", message("slack", "/run fleet oncall check prod"))
+            .await
+            .unwrap();
+
+        let sent = recorder.sent.lock().await;
+        assert_eq!(sent[0].status, ResponseStatus::Error);
+        assert!(sent[0]
+            .text
+            .contains("Fleet `oncall` runs 3 agents but you can start 2 more tasks"));
+        assert!(orchestrator.list_tasks().is_empty());
+        assert!(handler.fleet_runs.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_agents() {
+        use aof_runtime::ReplayConfig;
+
+        // Agents answer from an (empty) cassette, so they load offline
+        let cassette = std::env::temp_dir().join(format!(
+            "aof-trigger-list-agents-{}.jsonl",
+            std::process::id()
+        ));
+        std::fs::write(&cassette, "").unwrap();
+        let mut runtime = Runtime::new()
+            .with_replay(ReplayConfig::replay(&cassette))
+            .unwrap();
+        let _ = std::fs::remove_file(&cassette);
+        for (name, model, tools) in [
+            (
+                "k8s-ops",
+                "anthropic:claude-3-5-sonnet-20241022",
+                vec!["kubectl", "shell"],
+            ),
+            ("log-analyzer", "openai:gpt-4o", vec!["shell"]),
+        ] {
+            let config = AgentConfig {
+                name: name.to_string(),
+                model: model.to_string(),
+                tools: tools.into_iter().map(String::from).collect(),
+                ..Default::default()
+            };
+            runtime.load_agent_from_config(config).await.unwrap();
+        }
+
+        let recorder = RecordingPlatform::new("slack");
+        let mut handler = TriggerHandler::new(Arc::new(RuntimeOrchestrator::new()));
+        handler.register_platform(recorder.clone());
+        handler.set_runtime(Arc::new(RwLock::new(runtime)));
+
+        handler
+            .handle_message("slack", message("slack", "/list agents"))
+            .await
+            .unwrap();
+
+        let sent = recorder.sent.lock().await;
+        assert_eq!(sent.len(), 1);
+        assert_eq!(
+            sent[0].text,
+            "🤖 **Loaded Agents (2)**\n\
+             \n• `k8s-ops` - anthropic:claude-3-5-sonnet-20241022 (2 tools)\
+             \n• `log-analyzer` - openai:gpt-4o (1 tool)"
+        );
+
+        let response = last_response("slack", "/list agents").await;
+        assert!(response.text.contains("_No agents loaded_"));
+    }
+
+    #[tokio::test]
+    async fn test_run_unknown_fleet_is_an_error() {
+        let response = last_response("slack", "/run fleet missing check prod").await;
+        assert_eq!(response.status, ResponseStatus::Error);
+        assert!(response.text.contains("Fleet not found: `missing`"));
+    }
+
+    #[tokio::test]
+    async fn test_status_shows_task_progress() {
+        let orchestrator = Arc::new(RuntimeOrchestrator::new());
+        let handle = orchestrator.submit_task(Task::new(
+            "task-1".to_string(),
+            "Test Task".to_string(),
+            "helper".to_string(),
+            "check disk".to_string(),
+        ));
+        handle
+            .progress_reporter()
+            .report_progress(50, "analyzing")
+            .await;
+
+        let recorder = RecordingPlatform::new("slack");
+        let config = TriggerHandlerConfig {
+            auto_ack: false,
+            ..Default::default()
+        };
+        let mut handler = TriggerHandler::with_config(orchestrator, config);
+        handler.register_platform(recorder.clone());
+
+        handler
+            .handle_message("slack", message("slack", "/status task task-1"))
+            .await
+            .unwrap();
+
+        let sent = recorder.sent.lock().await;
+        assert!(sent[0].text.contains("**Progress:** 50% - analyzing"));
+    }
+
+    #[tokio::test]
+    async fn test_redelivered_message_is_handled_once() {
+        let recorder = RecordingPlatform::new("slack");
+        let config = TriggerHandlerConfig {
+            auto_ack: false,
+            ..Default::default()
+        };
+        let mut handler = TriggerHandler::with_config(Arc::new(RuntimeOrchestrator::new()), config);
+        handler.register_platform(recorder.clone());
+
+        handler.handle_message("slack", message("slack", "/help")).await.unwrap();
+        handler.handle_message("slack", message("slack", "/help")).await.unwrap();
+        assert_eq!(recorder.sent.lock().await.len(), 1);
+
+        // A new message ID is executed
+        let mut other = message("slack", "/help");
+        other.id = "msg2".to_string();
+        handler.handle_message("slack", other).await.unwrap();
+        assert_eq!(recorder.sent.lock().await.len(), 2);
+    }
+
+    #[test]
+    fn test_recent_messages_bounded() {
+        let window = Duration::from_secs(60);
+        let mut recent = RecentMessages::default();
+
+        assert!(recent.insert("a".to_string(), window, 2));
+        assert!(!recent.insert("a".to_string(), window, 2));
+        assert!(recent.insert("b".to_string(), window, 2));
+
+        // Capacity reached: the oldest key is forgotten
+        assert!(recent.insert("c".to_string(), window, 2));
+        assert!(recent.insert("a".to_string(), window, 2));
+
+        // Expired keys are forgotten
+        assert!(recent.insert("d".to_string(), Duration::ZERO, 2));
+        assert!(recent.insert("d".to_string(), Duration::ZERO, 2));
+    }
+
+    #[tokio::test]
+    async fn test_stream_updates_edit_message() {
+        use aof_core::StopReason;
+
+        let recorder = RecordingPlatform::new("slack");
+        let (tx, rx) = mpsc::channel(16);
+
+        let forwarder = tokio::spawn(forward_stream_updates(
+            recorder.clone(),
+            "slack".to_string(),
+            "channel1".to_string(),
+            "msg1".to_string(),
+            rx,
+        ));
+
+        for event in [
+            StreamEvent::TextDelta { delta: "Disk usage".to_string(), timestamp: None },
+            StreamEvent::IterationComplete { iteration: 1, stop_reason: StopReason::ToolUse },
+            StreamEvent::TextDelta { delta: " is 42%".to_string(), timestamp: None },
+            StreamEvent::Done {
+                content: "Disk usage is 42%".to_string(),
+                total_iterations: 2,
+                execution_time_ms: 10,
+                input_tokens: 0,
+                output_tokens: 0,
+                trace_id: "trace-1".to_string(),
+            },
+        ] {
+            tx.send(event).await.unwrap();
+        }
+        forwarder.await.unwrap();
+
+        let edits = recorder.edits.lock().await;
+        assert_eq!(edits.len(), 2);
+        assert!(edits.iter().all(|(message_id, _)| message_id == "msg1"));
+        assert_eq!(edits[0].1.text, "Disk usage");
+        assert_eq!(edits[1].1.status, ResponseStatus::Success);
+        assert_eq!(edits[1].1.text, "✅ *Success*\nDisk usage is 42%");
+        assert!(recorder.sent.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_stops_running_agent() {
+        use aof_core::{
+            Model, ModelConfig, ModelProvider, ModelRequest, ModelResponse, StopReason,
+            StreamChunk, ToolCall, ToolDefinition, ToolExecutor, ToolInput, ToolResult, Usage,
+        };
+        use std::pin::Pin;
+        use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+        /// Sets its flag when dropped
+        struct DropFlag(Arc<AtomicBool>);
+
+        impl Drop for DropFlag {
+            fn drop(&mut self) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+
+        /// Model that asks for one tool call, then never answers
+        struct SlowModel {
+            calls: Arc<AtomicUsize>,
+            dropped: Arc<AtomicBool>,
+            config: ModelConfig,
+        }
+
+        #[async_trait]
+        impl Model for SlowModel {
+            async fn generate(&self, _request: &ModelRequest) -> AofResult<ModelResponse> {
+                if self.calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                    return Ok(ModelResponse {
+                        content: "Checked 3 of 5 pods".to_string(),
+                        tool_calls: vec![ToolCall {
+                            id: "call_1".to_string(),
+                            name: "kubectl_get".to_string(),
+                            arguments: serde_json::json!({}),
+                        }],
+                        stop_reason: StopReason::ToolUse,
+                        usage: Usage::default(),
+                        stop_detail: None,
+                        metadata: HashMap::new(),
+                    });
+                }
+                let _guard = DropFlag(Arc::clone(&self.dropped));
+                std::future::pending().await
+            }
+
+            async fn generate_stream(
+                &self,
+                _request: &ModelRequest,
+            ) -> AofResult<Pin<Box<dyn futures::Stream<Item = AofResult<StreamChunk>> + Send>>>
+            {
+                unimplemented!("Stream not implemented in mock")
+            }
+
+            fn config(&self) -> &ModelConfig {
+                &self.config
+            }
+
+            fn provider(&self) -> ModelProvider {
+                ModelProvider::Custom
+            }
+        }
+
+        struct KubectlTool;
+
+        #[async_trait]
+        impl ToolExecutor for KubectlTool {
+            async fn execute_tool(&self, _name: &str, _input: ToolInput) -> AofResult<ToolResult> {
+                Ok(ToolResult::success(serde_json::json!({ "pods": 5 })))
+            }
+
+            fn list_tools(&self) -> Vec<ToolDefinition> {
+                vec![ToolDefinition {
+                    name: "kubectl_get".to_string(),
+                    description: "Get resources".to_string(),
+                    parameters: serde_json::json!({}),
+                }]
+            }
+
+            fn get_tool(&self, _name: &str) -> Option<Arc<dyn aof_core::Tool>> {
+                None
+            }
+        }
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let dropped = Arc::new(AtomicBool::new(false));
+        let model = SlowModel {
+            calls: Arc::clone(&calls),
+            dropped: Arc::clone(&dropped),
+            config: ModelConfig {
+                model: "mock-model".to_string(),
+                provider: ModelProvider::Custom,
+                api_key: None,
+                endpoint: None,
+                temperature: 0.7,
+                max_tokens: None,
+                timeout_secs: 60,
+                headers: HashMap::new(),
+                extra: HashMap::new(),
+            },
+        };
+        let config = AgentConfig {
+            name: "pod-checker".to_string(),
+            model: "mock-model".to_string(),
+            tools: vec!["kubectl_get".to_string()],
+            ..Default::default()
+        };
+        let mut runtime = Runtime::new();
+        runtime.add_agent(AgentExecutor::new(
+            config,
+            Box::new(model),
+            Some(Arc::new(KubectlTool)),
+            None,
+        ));
+
+        let orchestrator = Arc::new(RuntimeOrchestrator::new());
+        let recorder = RecordingPlatform::new("slack");
+        let config = TriggerHandlerConfig {
+            auto_ack: false,
+            ..Default::default()
+        };
+        let mut handler = TriggerHandler::with_config(Arc::clone(&orchestrator), config);
+        handler.register_platform(recorder.clone());
+        handler.set_runtime(Arc::new(RwLock::new(runtime)));
+
+        handler
+            .handle_message(
+                "slack",
+                message("slack", "/run agent pod-checker check pods"),
+            )
+            .await
+            .unwrap();
+        let task_id = orchestrator.list_tasks().pop().unwrap();
+
+        // Wait for the run to block on its second model call
+        while calls.load(Ordering::SeqCst) < 2 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(!dropped.load(Ordering::SeqCst));
+
+        let mut cancel = message("slack", &format!("/cancel task {}", task_id));
+        cancel.id = "msg2".to_string();
+        handler.handle_message("slack", cancel).await.unwrap();
+
+        // The pending model call was dropped with the run
+        assert!(dropped.load(Ordering::SeqCst));
+
+        let sent = recorder.sent.lock().await;
+        assert!(sent[0].text.contains("Task started"));
+        let cancelled = sent
+            .iter()
+            .find(|response| response.text.contains("Task cancelled"))
+            .expect("no cancellation response");
+        assert_eq!(cancelled.status, ResponseStatus::Success);
+        assert!(cancelled
+            .text
+            .contains("Partial output:\nChecked 3 of 5 pods"));
+        drop(sent);
+
+        let handle = orchestrator.get_task(&task_id).unwrap();
+        assert_eq!(handle.status().await, TaskStatus::Cancelled);
+        assert!(handler.cancellations.is_empty());
+    }
 }
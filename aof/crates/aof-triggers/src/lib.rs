@@ -10,6 +10,7 @@ use std::collections::HashMap;
 
 pub mod command;
 pub mod handler;
+pub mod metrics;
 pub mod platforms;
 pub mod response;
 pub mod server;
@@ -0,0 +1,70 @@
+//! Prometheus metrics for the trigger server
+//!
+//! Counters and histograms are recorded through the `metrics` facade and are
+//! no-ops until [`install`] sets up the Prometheus recorder, which the server
+//! does when `TriggerServerConfig::enable_metrics` is set.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+
+/// Webhooks received, labelled by `platform`
+pub const WEBHOOKS_RECEIVED: &str = "aof_trigger_webhooks_received_total";
+
+/// Commands executed, labelled by `command` and `outcome` (`ok`/`error`)
+pub const COMMANDS_EXECUTED: &str = "aof_trigger_commands_executed_total";
+
+/// Command execution latency in seconds, labelled by `command`
+pub const COMMAND_DURATION: &str = "aof_trigger_command_duration_seconds";
+
+/// Tasks that reached a terminal state, labelled by `status`
+pub const TASKS_FINISHED: &str = "aof_trigger_tasks_finished_total";
+
+/// Latency buckets (seconds) for `COMMAND_DURATION`
+const COMMAND_DURATION_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 300.0,
+];
+
+/// Handle of the process-wide recorder, once installed
+static HANDLE: Mutex<Option<PrometheusHandle>> = Mutex::new(None);
+
+/// Install the Prometheus recorder, or return the one already installed
+///
+/// The recorder is global, so every server in the process shares it.
+pub fn install() -> Result<PrometheusHandle, String> {
+    let mut handle = HANDLE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(handle) = handle.as_ref() {
+        return Ok(handle.clone());
+    }
+
+    let recorder = PrometheusBuilder::new()
+        .set_buckets_for_metric(
+            Matcher::Full(COMMAND_DURATION.to_string()),
+            COMMAND_DURATION_BUCKETS,
+        )
+        .map_err(|e| e.to_string())?
+        .build_recorder();
+    let new_handle = recorder.handle();
+    metrics::set_global_recorder(recorder).map_err(|e| e.to_string())?;
+
+    *handle = Some(new_handle.clone());
+    Ok(new_handle)
+}
+
+/// Count a webhook received for `platform`
+pub(crate) fn record_webhook(platform: &str) {
+    metrics::counter!(WEBHOOKS_RECEIVED, "platform" => platform.to_string()).increment(1);
+}
+
+/// Count an executed command and record its latency
+pub(crate) fn record_command(command: &'static str, success: bool, elapsed: Duration) {
+    let outcome = if success { "ok" } else { "error" };
+    metrics::counter!(COMMANDS_EXECUTED, "command" => command, "outcome" => outcome).increment(1);
+    metrics::histogram!(COMMAND_DURATION, "command" => command).record(elapsed.as_secs_f64());
+}
+
+/// Count a task that finished with `status`
+pub(crate) fn record_task(status: &'static str) {
+    metrics::counter!(TASKS_FINISHED, "status" => status).increment(1);
+}
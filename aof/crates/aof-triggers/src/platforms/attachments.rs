@@ -0,0 +1,169 @@
+//! Downloading response attachments for upload
+//!
+//! Responses reference attachments by URL. Platforms that upload files
+//! fetch them here first and send the bytes along; anything larger than the
+//! size cap is refused rather than buffered.
+
+use reqwest::multipart::Part;
+
+use super::PlatformError;
+use crate::response::Attachment;
+
+/// Largest attachment downloaded for upload (20 MiB)
+pub const MAX_ATTACHMENT_BYTES: usize = 20 * 1024 * 1024;
+
+/// Attachment downloaded and ready for upload
+#[derive(Debug, Clone)]
+pub(crate) struct AttachmentFile {
+    /// File name shown on the platform
+    pub filename: String,
+
+    /// MIME type reported by the server, if any
+    pub content_type: Option<String>,
+
+    /// File contents
+    pub bytes: Vec<u8>,
+}
+
+impl AttachmentFile {
+    /// Multipart form part carrying the file
+    pub fn part(&self) -> Result<Part, PlatformError> {
+        let part = Part::bytes(self.bytes.clone()).file_name(self.filename.clone());
+        match &self.content_type {
+            Some(content_type) => part.mime_str(content_type).map_err(|e| {
+                PlatformError::ParseError(format!("Invalid attachment content type: {}", e))
+            }),
+            None => Ok(part),
+        }
+    }
+}
+
+/// Download `attachment`, refusing files larger than `max_bytes`
+pub(crate) async fn fetch_attachment(
+    client: &reqwest::Client,
+    attachment: &Attachment,
+    max_bytes: usize,
+) -> Result<AttachmentFile, PlatformError> {
+    let too_large = || {
+        PlatformError::ApiError(format!(
+            "Attachment {} exceeds the {} byte upload limit",
+            attachment.url, max_bytes
+        ))
+    };
+
+    let mut response = client
+        .get(&attachment.url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| {
+            PlatformError::ApiError(format!(
+                "Failed to fetch attachment {}: {}",
+                attachment.url, e
+            ))
+        })?;
+
+    if response
+        .content_length()
+        .is_some_and(|len| len > max_bytes as u64)
+    {
+        return Err(too_large());
+    }
+
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from);
+
+    // The length header may be missing or wrong, so count while reading
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| {
+        PlatformError::ApiError(format!(
+            "Failed to fetch attachment {}: {}",
+            attachment.url, e
+        ))
+    })? {
+        if bytes.len() + chunk.len() > max_bytes {
+            return Err(too_large());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+
+    Ok(AttachmentFile {
+        filename: attachment_filename(attachment),
+        content_type,
+        bytes,
+    })
+}
+
+/// File name for `attachment`: its own, else the last URL path segment
+fn attachment_filename(attachment: &Attachment) -> String {
+    attachment
+        .filename
+        .clone()
+        .or_else(|| {
+            reqwest::Url::parse(&attachment.url).ok().and_then(|url| {
+                url.path_segments()
+                    .and_then(|mut segments| segments.next_back())
+                    .filter(|segment| !segment.is_empty())
+                    .map(String::from)
+            })
+        })
+        .unwrap_or_else(|| "attachment".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response::AttachmentType;
+
+    fn attachment(url: &str, filename: Option<&str>) -> Attachment {
+        Attachment {
+            attachment_type: AttachmentType::File,
+            url: url.to_string(),
+            filename: filename.map(String::from),
+            title: None,
+        }
+    }
+
+    #[test]
+    fn test_attachment_filename() {
+        assert_eq!(
+            attachment_filename(&attachment("https://example.com/logs/pod.log", None)),
+            "pod.log"
+        );
+        assert_eq!(
+            attachment_filename(&attachment(
+                "https://example.com/logs/pod.log",
+                Some("crash.log")
+            )),
+            "crash.log"
+        );
+        assert_eq!(
+            attachment_filename(&attachment("https://example.com/", None)),
+            "attachment"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_refuses_files_over_the_cap() {
+        let app = axum::Router::new().route(
+            "/report.csv",
+            axum::routing::get(|| async { "a,b\n1,2\n3,4\n" }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let client = reqwest::Client::new();
+        let report = attachment(&format!("http://{}/report.csv", addr), None);
+
+        let file = fetch_attachment(&client, &report, 1024).await.unwrap();
+        assert_eq!(file.filename, "report.csv");
+        assert_eq!(file.bytes, b"a,b\n1,2\n3,4\n");
+
+        let err = fetch_attachment(&client, &report, 8).await.unwrap_err();
+        assert!(err.to_string().contains("exceeds the 8 byte upload limit"));
+    }
+}
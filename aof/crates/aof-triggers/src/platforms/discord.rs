@@ -2,12 +2,15 @@
 //!
 //! This module provides Discord Gateway/HTTP integration for triggering AOF workflows.
 //! Supports slash commands, message components, and modal submissions with Ed25519
-//! signature verification.
+//! signature verification. Response attachments are posted to the channel
+//! as multipart message uploads.
 
+use super::attachments::{fetch_attachment, AttachmentFile, MAX_ATTACHMENT_BYTES};
 use super::{PlatformError, TriggerMessage, TriggerPlatform, TriggerUser};
-use crate::response::TriggerResponse;
+use crate::response::{Attachment, TriggerResponse};
 use async_trait::async_trait;
-use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use aof_core::{AofError, AofResult};
+use ed25519_dalek::{Signature, VerifyingKey};
 use hex;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -16,6 +19,15 @@ use tracing::{debug, error, info, warn};
 
 const DISCORD_API_BASE: &str = "https://discord.com/api/v10";
 
+/// Most files Discord accepts on one message
+const MAX_FILES_PER_MESSAGE: usize = 10;
+
+/// Header carrying the hex Ed25519 signature of an interaction
+const SIGNATURE_HEADER: &str = "x-signature-ed25519";
+
+/// Header carrying the timestamp that is signed together with the body
+const TIMESTAMP_HEADER: &str = "x-signature-timestamp";
+
 /// Discord-specific platform configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DiscordConfig {
@@ -33,56 +45,67 @@ pub struct DiscordConfig {
 
     /// Optional role IDs that are allowed to use commands
     pub allowed_roles: Option<Vec<String>>,
+
+    /// REST API base URL (default https://discord.com/api/v10)
+    #[serde(default = "default_api_base_url")]
+    pub api_base_url: String,
+}
+
+/// Default Discord REST API base URL
+pub fn default_api_base_url() -> String {
+    DISCORD_API_BASE.to_string()
 }
 
 /// Discord platform implementation with full Gateway/HTTP support
 pub struct DiscordPlatform {
     bot_token: String,
     application_id: String,
-    public_key: String,
+    api_base_url: String,
     client: Client,
     verifying_key: VerifyingKey,
 }
 
+/// Parse a hex-encoded Ed25519 public key
+fn parse_public_key(public_key: &str) -> Result<VerifyingKey, String> {
+    let key_bytes = hex::decode(public_key).map_err(|e| format!("Invalid public key: {}", e))?;
+    let key_array: [u8; 32] = key_bytes
+        .try_into()
+        .map_err(|_| "Invalid public key length".to_string())?;
+    VerifyingKey::from_bytes(&key_array).map_err(|e| format!("Invalid public key: {}", e))
+}
+
 impl DiscordPlatform {
     /// Create a new Discord platform adapter from PlatformConfig
-    pub fn new(config: super::PlatformConfig) -> Self {
+    ///
+    /// `webhook_secret` holds the application's hex public key; a missing or
+    /// invalid key is a configuration error, as no interaction could be verified.
+    pub fn new(config: super::PlatformConfig) -> AofResult<Self> {
         let bot_token = config.api_token.unwrap_or_default();
-        let public_key = config.webhook_secret.unwrap_or_default();
-
-        // Parse the public key for signature verification
-        let key_bytes = hex::decode(&public_key).unwrap_or_default();
-        let key_array: [u8; 32] = key_bytes.try_into().unwrap_or([0u8; 32]);
-        let verifying_key = VerifyingKey::from_bytes(&key_array).unwrap_or_else(|_| {
-            // Fallback to a valid (but insecure) key for testing
-            VerifyingKey::from_bytes(&[0u8; 32]).unwrap()
-        });
+        let public_key = config
+            .webhook_secret
+            .ok_or_else(|| AofError::config("Discord public key (webhook_secret) is not set"))?;
+        let verifying_key = parse_public_key(&public_key)
+            .map_err(|e| AofError::config(format!("Discord {}", e)))?;
 
         let client = Client::builder()
             .user_agent("AOF-Bot/0.1.0")
             .build()
             .unwrap_or_default();
 
-        Self {
+        Ok(Self {
             bot_token,
             application_id: "".to_string(),
-            public_key,
+            api_base_url: default_api_base_url(),
             client,
             verifying_key,
-        }
+        })
     }
 
     /// Create from Discord-specific config
     pub fn from_discord_config(config: DiscordConfig) -> Result<Self, PlatformError> {
         // Parse the public key for signature verification
-        let key_bytes = hex::decode(&config.public_key)
-            .map_err(|e| PlatformError::ParseError(format!("Invalid public key: {}", e)))?;
-
-        let key_array: [u8; 32] = key_bytes.try_into()
-            .map_err(|_| PlatformError::ParseError("Invalid public key length".to_string()))?;
-
-        let verifying_key = VerifyingKey::from_bytes(&key_array)
-            .map_err(|e| PlatformError::ParseError(format!("Invalid public key: {}", e)))?;
+        let verifying_key =
+            parse_public_key(&config.public_key).map_err(PlatformError::ParseError)?;
 
         let client = Client::builder()
             .user_agent("AOF-Bot/0.1.0")
@@ -92,7 +115,7 @@ impl DiscordPlatform {
         Ok(Self {
             bot_token: config.bot_token,
             application_id: config.application_id,
-            public_key: config.public_key,
+            api_base_url: config.api_base_url,
             client,
             verifying_key,
         })
@@ -121,7 +144,8 @@ impl DiscordPlatform {
     async fn register_command(&self, command: &DiscordCommand) -> Result<(), PlatformError> {
         let url = format!(
             "{}/applications/{}/commands",
-            DISCORD_API_BASE, self.application_id
+            self.api_base_url.trim_end_matches('/'),
+            self.application_id
         );
 
         let response = self.client
@@ -144,6 +168,77 @@ impl DiscordPlatform {
         Ok(())
     }
 
+    /// Post attachments to `channel` as messages with file uploads
+    ///
+    /// Files are downloaded from their URLs (up to `MAX_ATTACHMENT_BYTES`
+    /// each) and sent up to `MAX_FILES_PER_MESSAGE` per message. Attachments
+    /// that can't be fetched are skipped with a warning.
+    pub async fn post_attachments(
+        &self,
+        channel: &str,
+        attachments: &[Attachment],
+    ) -> Result<(), PlatformError> {
+        let mut files: Vec<(&Attachment, AttachmentFile)> = Vec::new();
+        for attachment in attachments {
+            match fetch_attachment(&self.client, attachment, MAX_ATTACHMENT_BYTES).await {
+                Ok(file) => files.push((attachment, file)),
+                Err(e) => warn!("Skipping attachment {}: {}", attachment.url, e),
+            }
+        }
+
+        let url = format!(
+            "{}/channels/{}/messages",
+            self.api_base_url.trim_end_matches('/'),
+            channel
+        );
+        for batch in files.chunks(MAX_FILES_PER_MESSAGE) {
+            let descriptions: Vec<serde_json::Value> = batch
+                .iter()
+                .enumerate()
+                .map(|(i, (attachment, file))| {
+                    serde_json::json!({
+                        "id": i,
+                        "filename": file.filename,
+                        "description": attachment.title,
+                    })
+                })
+                .collect();
+
+            let mut form = reqwest::multipart::Form::new().text(
+                "payload_json",
+                serde_json::json!({ "attachments": descriptions }).to_string(),
+            );
+            for (i, (_, file)) in batch.iter().enumerate() {
+                form = form.part(format!("files[{}]", i), file.part()?);
+            }
+
+            let response = self
+                .client
+                .post(&url)
+                .header("Authorization", format!("Bot {}", self.bot_token))
+                .multipart(form)
+                .send()
+                .await
+                .map_err(|e| PlatformError::ApiError(format!("Failed to upload files: {}", e)))?;
+
+            if !response.status().is_success() {
+                let error_text = response.text().await.unwrap_or_default();
+                error!("Discord API error: {}", error_text);
+                return Err(PlatformError::ApiError(format!(
+                    "Failed to upload files: {}",
+                    error_text
+                )));
+            }
+        }
+
+        debug!(
+            "Posted {} attachments to Discord channel {}",
+            files.len(),
+            channel
+        );
+        Ok(())
+    }
+
     fn create_agent_command(&self) -> DiscordCommand {
         DiscordCommand {
             name: "agent".to_string(),
@@ -233,6 +328,44 @@ impl DiscordPlatform {
             ],
         }
     }
+
+    /// Verify the Ed25519 signature of `timestamp + body`
+    fn verify_interaction(&self, timestamp: &str, signature_hex: &str, body: &[u8]) -> bool {
+        let signature_bytes = match hex::decode(signature_hex) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                error!("Failed to decode signature: {}", e);
+                return false;
+            }
+        };
+
+        let sig_array: [u8; 64] = match signature_bytes.try_into() {
+            Ok(arr) => arr,
+            Err(_) => {
+                error!("Invalid signature length");
+                return false;
+            }
+        };
+
+        let signature = Signature::from_bytes(&sig_array);
+
+        // Build message to verify: timestamp + payload
+        let mut message = Vec::with_capacity(timestamp.len() + body.len());
+        message.extend_from_slice(timestamp.as_bytes());
+        message.extend_from_slice(body);
+
+        // Strict verification rejects malleable and small-order signatures
+        match self.verifying_key.verify_strict(&message, &signature) {
+            Ok(_) => {
+                debug!("Discord signature verified successfully");
+                true
+            }
+            Err(e) => {
+                error!("Discord signature verification failed: {}", e);
+                false
+            }
+        }
+    }
 }
 
 #[async_trait]
@@ -240,14 +373,29 @@ impl TriggerPlatform for DiscordPlatform {
     async fn parse_message(
         &self,
         raw: &[u8],
-        _headers: &HashMap<String, String>,
+        headers: &HashMap<String, String>,
     ) -> Result<TriggerMessage, PlatformError> {
+        // Discord requires every interaction, PINGs included, to be verified
+        let (Some(signature), Some(timestamp)) =
+            (headers.get(SIGNATURE_HEADER), headers.get(TIMESTAMP_HEADER))
+        else {
+            warn!("Discord interaction without signature headers");
+            return Err(PlatformError::InvalidSignature(
+                "Missing signature headers".to_string(),
+            ));
+        };
+        if !self.verify_interaction(timestamp, signature, raw) {
+            return Err(PlatformError::InvalidSignature(
+                "Signature verification failed".to_string(),
+            ));
+        }
+
         let interaction: DiscordInteraction = serde_json::from_slice(raw)
             .map_err(|e| PlatformError::ParseError(format!("Failed to parse Discord payload: {}", e)))?;
 
-        // Handle ping (type 1)
+        // Answer PING (type 1) with PONG (type 1)
         if interaction.interaction_type == 1 {
-            return Err(PlatformError::UnsupportedMessageType);
+            return Err(PlatformError::ImmediateResponse(serde_json::json!({ "type": 1 })));
         }
 
         // Get user from member or direct user field
@@ -334,12 +482,16 @@ impl TriggerPlatform for DiscordPlatform {
 
     async fn send_response(
         &self,
-        _channel: &str,
-        _response: TriggerResponse,
+        channel: &str,
+        response: TriggerResponse,
     ) -> Result<(), PlatformError> {
         // Discord responses are sent via interaction callbacks
         // This method is not used for Discord - responses are sent directly in webhook handler
-        Ok(())
+        // Only attachments, which the callback can't carry, are posted here
+        if response.attachments.is_empty() {
+            return Ok(());
+        }
+        self.post_attachments(channel, &response.attachments).await
     }
 
     fn platform_name(&self) -> &'static str {
@@ -347,46 +499,13 @@ impl TriggerPlatform for DiscordPlatform {
     }
 
     async fn verify_signature(&self, payload: &[u8], signature_header: &str) -> bool {
-        // Discord sends signature as "timestamp.signature"
-        let parts: Vec<&str> = signature_header.split('.').collect();
-        if parts.len() != 2 {
-            warn!("Invalid Discord signature format");
-            return false;
-        }
-
-        let timestamp = parts[0];
-        let signature_hex = parts[1];
-
-        // Decode signature
-        let signature_bytes = match hex::decode(signature_hex) {
-            Ok(bytes) => bytes,
-            Err(e) => {
-                error!("Failed to decode signature: {}", e);
-                return false;
-            }
-        };
-
-        let sig_array: [u8; 64] = match signature_bytes.try_into() {
-            Ok(arr) => arr,
-            Err(_) => {
-                error!("Invalid signature length");
-                return false;
-            }
-        };
-
-        let signature = Signature::from_bytes(&sig_array);
-
-        // Build message to verify: timestamp + payload
-        let message = format!("{}{}", timestamp, String::from_utf8_lossy(payload));
-
-        // Verify signature
-        match self.verifying_key.verify(message.as_bytes(), &signature) {
-            Ok(_) => {
-                debug!("Discord signature verified successfully");
-                true
+        // Combined form of the two headers: "timestamp.signature"
+        match signature_header.split_once('.') {
+            Some((timestamp, signature_hex)) => {
+                self.verify_interaction(timestamp, signature_hex, payload)
             }
-            Err(e) => {
-                error!("Discord signature verification failed: {}", e);
+            None => {
+                warn!("Invalid Discord signature format");
                 false
             }
         }
@@ -403,6 +522,10 @@ impl TriggerPlatform for DiscordPlatform {
     fn supports_interactive(&self) -> bool {
         true
     }
+
+    fn supports_files(&self) -> bool {
+        true
+    }
 }
 
 // Discord API types
@@ -436,6 +559,7 @@ struct DiscordInteraction {
     id: String,
     #[serde(rename = "type")]
     interaction_type: u8,
+    #[serde(default)]
     token: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     data: Option<DiscordData>,
@@ -477,25 +601,55 @@ struct DiscordMember {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    fn signing_key() -> SigningKey {
+        SigningKey::from_bytes(&[7u8; 32])
+    }
 
     fn test_config() -> super::super::PlatformConfig {
         super::super::PlatformConfig {
             platform: "discord".to_string(),
             api_token: Some("test_token".to_string()),
-            webhook_secret: Some("0000000000000000000000000000000000000000000000000000000000000000".to_string()),
+            webhook_secret: Some(hex::encode(signing_key().verifying_key().to_bytes())),
             webhook_url: None,
         }
     }
 
+    /// Headers Discord would send for `body`, signed with `key`
+    fn signed_headers(key: &SigningKey, body: &str) -> HashMap<String, String> {
+        let timestamp = "1700000000";
+        let signature = key.sign(format!("{}{}", timestamp, body).as_bytes());
+
+        let mut headers = HashMap::new();
+        headers.insert(SIGNATURE_HEADER.to_string(), hex::encode(signature.to_bytes()));
+        headers.insert(TIMESTAMP_HEADER.to_string(), timestamp.to_string());
+        headers
+    }
+
     #[test]
     fn test_discord_platform_creation() {
-        let platform = DiscordPlatform::new(test_config());
+        let platform = DiscordPlatform::new(test_config()).unwrap();
         assert_eq!(platform.platform_name(), "discord");
     }
 
+    #[test]
+    fn test_missing_or_invalid_public_key_is_a_config_error() {
+        for secret in [None, Some("not-hex".to_string()), Some("00".repeat(31))] {
+            let config = super::super::PlatformConfig {
+                webhook_secret: secret,
+                ..test_config()
+            };
+            assert!(matches!(
+                DiscordPlatform::new(config),
+                Err(AofError::Config(_))
+            ));
+        }
+    }
+
     #[test]
     fn test_command_creation() {
-        let platform = DiscordPlatform::new(test_config());
+        let platform = DiscordPlatform::new(test_config()).unwrap();
         let agent_cmd = platform.create_agent_command();
         assert_eq!(agent_cmd.name, "agent");
         assert_eq!(agent_cmd.options.len(), 2);
@@ -521,8 +675,8 @@ mod tests {
             "channel_id": "888"
         }"#;
 
-        let platform = DiscordPlatform::new(test_config());
-        let headers = HashMap::new();
+        let platform = DiscordPlatform::new(test_config()).unwrap();
+        let headers = signed_headers(&signing_key(), interaction_json);
         let message = platform.parse_message(interaction_json.as_bytes(), &headers).await.unwrap();
 
         assert_eq!(message.platform, "discord");
@@ -530,4 +684,49 @@ mod tests {
         assert_eq!(message.channel_id, "888");
         assert!(message.text.contains("/agent"));
     }
+
+    #[tokio::test]
+    async fn test_verify_signature() {
+        let platform = DiscordPlatform::new(test_config()).unwrap();
+        let body = r#"{"id":"1","type":2,"token":"t"}"#;
+        let signature = signing_key().sign(format!("1700000000{}", body).as_bytes());
+        let header = format!("1700000000.{}", hex::encode(signature.to_bytes()));
+
+        assert!(platform.verify_signature(body.as_bytes(), &header).await);
+
+        // Tampered body or timestamp
+        let tampered = r#"{"id":"1","type":2,"token":"x"}"#;
+        assert!(!platform.verify_signature(tampered.as_bytes(), &header).await);
+        let replayed = format!("1700000001.{}", hex::encode(signature.to_bytes()));
+        assert!(!platform.verify_signature(body.as_bytes(), &replayed).await);
+    }
+
+    #[tokio::test]
+    async fn test_ping_answered_with_pong() {
+        let platform = DiscordPlatform::new(test_config()).unwrap();
+        let ping = r#"{"id":"1","type":1,"token":"t"}"#;
+
+        let result = platform
+            .parse_message(ping.as_bytes(), &signed_headers(&signing_key(), ping))
+            .await;
+        match result {
+            Err(PlatformError::ImmediateResponse(body)) => {
+                assert_eq!(body, serde_json::json!({ "type": 1 }))
+            }
+            other => panic!("expected PONG, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unsigned_or_forged_ping_rejected() {
+        let platform = DiscordPlatform::new(test_config()).unwrap();
+        let ping = r#"{"id":"1","type":1,"token":"t"}"#;
+
+        let result = platform.parse_message(ping.as_bytes(), &HashMap::new()).await;
+        assert!(matches!(result, Err(PlatformError::InvalidSignature(_))));
+
+        let forged = signed_headers(&SigningKey::from_bytes(&[9u8; 32]), ping);
+        let result = platform.parse_message(ping.as_bytes(), &forged).await;
+        assert!(matches!(result, Err(PlatformError::InvalidSignature(_))));
+    }
 }
@@ -6,6 +6,7 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Duration;
 use thiserror::Error;
 
 use crate::response::TriggerResponse;
@@ -27,6 +28,12 @@ pub enum PlatformError {
 
     #[error("Unsupported message type")]
     UnsupportedMessageType,
+
+    /// The webhook is a platform handshake (e.g. Slack `url_verification`)
+    /// that must be answered synchronously with this body instead of being
+    /// handled as a message. A JSON string is sent as plain text.
+    #[error("Immediate response required")]
+    ImmediateResponse(serde_json::Value),
 }
 
 /// User information from platform
@@ -127,6 +134,11 @@ impl TriggerMessage {
     }
 }
 
+/// How long the server waits for a verified message to be handled before
+/// acknowledging the webhook anyway; Slack retries deliveries not answered
+/// within 3 seconds
+pub const DEFAULT_ACK_DEADLINE: Duration = Duration::from_secs(2);
+
 /// Platform abstraction trait
 ///
 /// Implement this trait to add support for new messaging platforms.
@@ -143,7 +155,9 @@ pub trait TriggerPlatform: Send + Sync {
     /// * `headers` - HTTP headers for signature verification
     ///
     /// # Returns
-    /// Parsed TriggerMessage on success
+    /// Parsed TriggerMessage on success, or
+    /// `PlatformError::ImmediateResponse` for handshakes the server must
+    /// answer synchronously
     async fn parse_message(
         &self,
         raw: &[u8],
@@ -161,6 +175,26 @@ pub trait TriggerPlatform: Send + Sync {
         response: TriggerResponse,
     ) -> Result<(), PlatformError>;
 
+    /// Update a previously sent response in place
+    ///
+    /// Used to post incremental progress for long-running commands.
+    /// `message_id` identifies the message the updates belong to. Platforms
+    /// that cannot edit messages fall back to sending a new message.
+    ///
+    /// # Arguments
+    /// * `channel` - Channel/chat ID the message lives in
+    /// * `message_id` - ID of the message being updated
+    /// * `response` - Updated response content
+    async fn edit_response(
+        &self,
+        channel: &str,
+        message_id: &str,
+        response: TriggerResponse,
+    ) -> Result<(), PlatformError> {
+        let _ = message_id;
+        self.send_response(channel, response).await
+    }
+
     /// Get platform name identifier
     fn platform_name(&self) -> &'static str;
 
@@ -191,9 +225,29 @@ pub trait TriggerPlatform: Send + Sync {
     fn supports_files(&self) -> bool {
         false
     }
+
+    /// Check if platform can show a response to one user only
+    /// (`TriggerResponse::ephemeral_to`)
+    fn supports_ephemeral(&self) -> bool {
+        false
+    }
+
+    /// Body acknowledging a webhook delivery
+    ///
+    /// A JSON string is sent as plain text, as with `ImmediateResponse`.
+    fn ack_response(&self) -> serde_json::Value {
+        serde_json::json!({ "status": "accepted" })
+    }
+
+    /// How long the server waits for a verified message to be handled
+    /// before acknowledging the delivery and finishing in the background
+    fn ack_deadline(&self) -> Duration {
+        DEFAULT_ACK_DEADLINE
+    }
 }
 
 // Platform-specific implementations
+pub mod attachments;
 pub mod slack;
 pub mod discord;
 pub mod telegram;
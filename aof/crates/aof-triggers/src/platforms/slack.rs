@@ -5,6 +5,7 @@
 //! - Slash commands
 //! - Interactive components
 //! - Block Kit formatting
+//! - File attachments via the external upload API
 //! - HMAC-SHA256 signature verification
 
 use async_trait::async_trait;
@@ -12,13 +13,24 @@ use hmac::{Hmac, Mac};
 use serde::{Deserialize, Serialize};
 use sha2::Sha256;
 use std::collections::HashMap;
+use subtle::ConstantTimeEq;
 use tracing::{debug, error, warn};
 
+use super::attachments::{fetch_attachment, MAX_ATTACHMENT_BYTES};
 use super::{PlatformError, TriggerMessage, TriggerPlatform, TriggerUser};
-use crate::response::TriggerResponse;
+use crate::response::{Attachment, TriggerResponse};
 
 type HmacSha256 = Hmac<Sha256>;
 
+/// Header carrying the `v0=` request signature
+const SIGNATURE_HEADER: &str = "x-slack-signature";
+
+/// Header carrying the signed request timestamp (unix seconds)
+const TIMESTAMP_HEADER: &str = "x-slack-request-timestamp";
+
+/// Oldest request timestamp accepted, to prevent replays
+const MAX_REQUEST_AGE_SECS: u64 = 5 * 60;
+
 /// Slack platform adapter
 pub struct SlackPlatform {
     config: SlackConfig,
@@ -51,12 +63,21 @@ pub struct SlackConfig {
     /// Allowed channel IDs (optional)
     #[serde(default)]
     pub allowed_channels: Option<Vec<String>>,
+
+    /// Web API base URL (default https://slack.com/api)
+    #[serde(default = "default_api_base_url")]
+    pub api_base_url: String,
 }
 
 fn default_bot_name() -> String {
     "aofbot".to_string()
 }
 
+/// Default Slack Web API base URL
+pub fn default_api_base_url() -> String {
+    "https://slack.com/api".to_string()
+}
+
 /// Slack Events API payload
 #[derive(Debug, Clone, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -120,6 +141,30 @@ struct SlackApiResponse {
     error: Option<String>,
 }
 
+impl SlackApiResponse {
+    /// `Err` with Slack's error code unless the call succeeded
+    fn into_result(self) -> Result<(), PlatformError> {
+        if self.ok {
+            return Ok(());
+        }
+        error!("Slack API error: {:?}", self.error);
+        Err(PlatformError::ApiError(
+            self.error.unwrap_or_else(|| "Unknown error".to_string()),
+        ))
+    }
+}
+
+/// `files.getUploadURLExternal` response
+#[derive(Debug, Deserialize)]
+struct SlackUploadUrlResponse {
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    upload_url: Option<String>,
+    #[serde(default)]
+    file_id: Option<String>,
+}
+
 impl SlackPlatform {
     /// Create new Slack platform adapter
     pub fn new(config: SlackConfig) -> Result<Self, PlatformError> {
@@ -217,7 +262,19 @@ impl SlackPlatform {
         serde_json::json!({ "blocks": blocks })
     }
 
+    /// Get Web API method URL
+    fn api_url(&self, method: &str) -> String {
+        format!(
+            "{}/{}",
+            self.config.api_base_url.trim_end_matches('/'),
+            method
+        )
+    }
+
     /// Post message using chat.postMessage API
+    ///
+    /// Responses with `ephemeral_to` go through chat.postEphemeral instead,
+    /// visible only to that user.
     async fn post_message(
         &self,
         channel: &str,
@@ -235,9 +292,17 @@ impl SlackPlatform {
             payload["thread_ts"] = serde_json::json!(thread_ts);
         }
 
+        let method = match response.ephemeral_to {
+            Some(ref user) => {
+                payload["user"] = serde_json::json!(user);
+                "chat.postEphemeral"
+            }
+            None => "chat.postMessage",
+        };
+
         let api_response = self
             .client
-            .post("https://slack.com/api/chat.postMessage")
+            .post(self.api_url(method))
             .header("Authorization", format!("Bearer {}", self.config.bot_token))
             .header("Content-Type", "application/json")
             .json(&payload)
@@ -248,20 +313,94 @@ impl SlackPlatform {
             .await
             .map_err(|e| PlatformError::ParseError(format!("Failed to parse response: {}", e)))?;
 
-        if !api_response.ok {
-            error!("Slack API error: {:?}", api_response.error);
+        api_response.into_result()?;
+
+        debug!("Successfully posted message to Slack channel {}", channel);
+        Ok(())
+    }
+
+    /// Upload an attachment to `channel` with the external upload flow
+    ///
+    /// The file is downloaded from its URL (up to `MAX_ATTACHMENT_BYTES`),
+    /// sent to the URL handed out by `files.getUploadURLExternal`, then
+    /// shared to the channel (and thread) by `files.completeUploadExternal`.
+    async fn upload_file(
+        &self,
+        channel: &str,
+        thread_ts: Option<&str>,
+        attachment: &Attachment,
+    ) -> Result<(), PlatformError> {
+        let file = fetch_attachment(&self.client, attachment, MAX_ATTACHMENT_BYTES).await?;
+
+        let upload = self
+            .client
+            .post(self.api_url("files.getUploadURLExternal"))
+            .header("Authorization", format!("Bearer {}", self.config.bot_token))
+            .form(&[
+                ("filename", file.filename.clone()),
+                ("length", file.bytes.len().to_string()),
+            ])
+            .send()
+            .await
+            .map_err(|e| PlatformError::ApiError(format!("HTTP request failed: {}", e)))?
+            .json::<SlackUploadUrlResponse>()
+            .await
+            .map_err(|e| PlatformError::ParseError(format!("Failed to parse response: {}", e)))?;
+
+        let (Some(upload_url), Some(file_id)) = (upload.upload_url, upload.file_id) else {
+            error!("Slack API error: {:?}", upload.error);
             return Err(PlatformError::ApiError(
-                api_response.error.unwrap_or_else(|| "Unknown error".to_string()),
+                upload
+                    .error
+                    .unwrap_or_else(|| "Missing upload URL".to_string()),
             ));
+        };
+
+        let status = self
+            .client
+            .post(&upload_url)
+            .multipart(reqwest::multipart::Form::new().part("file", file.part()?))
+            .send()
+            .await
+            .map_err(|e| PlatformError::ApiError(format!("File upload failed: {}", e)))?
+            .status();
+        if !status.is_success() {
+            return Err(PlatformError::ApiError(format!(
+                "File upload failed with status {}",
+                status
+            )));
         }
 
-        debug!("Successfully posted message to Slack channel {}", channel);
+        let mut payload = serde_json::json!({
+            "files": [{
+                "id": file_id,
+                "title": attachment.title.clone().unwrap_or(file.filename),
+            }],
+            "channel_id": channel,
+        });
+        if let Some(thread_ts) = thread_ts {
+            payload["thread_ts"] = serde_json::json!(thread_ts);
+        }
+
+        self.client
+            .post(self.api_url("files.completeUploadExternal"))
+            .header("Authorization", format!("Bearer {}", self.config.bot_token))
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| PlatformError::ApiError(format!("HTTP request failed: {}", e)))?
+            .json::<SlackApiResponse>()
+            .await
+            .map_err(|e| PlatformError::ParseError(format!("Failed to parse response: {}", e)))?
+            .into_result()?;
+
+        debug!("Uploaded {} to Slack channel {}", attachment.url, channel);
         Ok(())
     }
 
     /// Get user info from Slack API
     async fn get_user_info(&self, user_id: &str) -> Result<TriggerUser, PlatformError> {
-        let url = format!("https://slack.com/api/users.info?user={}", user_id);
+        let url = format!("{}?user={}", self.api_url("users.info"), user_id);
 
         let user_info = self
             .client
@@ -292,6 +431,45 @@ impl SlackPlatform {
         })
     }
 
+    /// Verify a request signed at `timestamp`, as of unix time `now`
+    ///
+    /// Slack signs `v0:{timestamp}:{body}` with HMAC-SHA256 using the app's
+    /// signing secret. Requests older (or further in the future) than
+    /// `MAX_REQUEST_AGE_SECS` are rejected as possible replays.
+    fn verify_request(&self, body: &[u8], timestamp: &str, signature: &str, now: i64) -> bool {
+        let Ok(request_time) = timestamp.trim().parse::<i64>() else {
+            warn!("Invalid Slack request timestamp: {}", timestamp);
+            return false;
+        };
+        if now.abs_diff(request_time) > MAX_REQUEST_AGE_SECS {
+            warn!("Stale Slack request timestamp: {}", timestamp);
+            return false;
+        }
+
+        // Slack signature format: v0=<hex_signature>
+        let Some(provided) = signature
+            .strip_prefix("v0=")
+            .and_then(|hex_signature| hex::decode(hex_signature).ok())
+        else {
+            return false;
+        };
+
+        let mut mac = match HmacSha256::new_from_slice(self.config.signing_secret.as_bytes()) {
+            Ok(m) => m,
+            Err(e) => {
+                error!("HMAC setup failed: {}", e);
+                return false;
+            }
+        };
+        mac.update(b"v0:");
+        mac.update(timestamp.trim().as_bytes());
+        mac.update(b":");
+        mac.update(body);
+        let computed = mac.finalize().into_bytes();
+
+        computed.as_slice().ct_eq(&provided).into()
+    }
+
     /// Parse event payload
     fn parse_event_payload(&self, payload: &[u8]) -> Result<SlackEventPayload, PlatformError> {
         serde_json::from_slice(payload).map_err(|e| {
@@ -327,21 +505,30 @@ impl TriggerPlatform for SlackPlatform {
         headers: &HashMap<String, String>,
     ) -> Result<TriggerMessage, PlatformError> {
         // Verify signature first
-        if let Some(signature) = headers.get("x-slack-signature") {
-            if !self.verify_signature(raw, signature).await {
-                warn!("Invalid Slack signature");
-                return Err(PlatformError::InvalidSignature(
-                    "Signature verification failed".to_string(),
-                ));
-            }
+        let (Some(signature), Some(timestamp)) =
+            (headers.get(SIGNATURE_HEADER), headers.get(TIMESTAMP_HEADER))
+        else {
+            warn!("Slack request without signature headers");
+            return Err(PlatformError::InvalidSignature(
+                "Missing signature headers".to_string(),
+            ));
+        };
+        if !self.verify_request(raw, timestamp, signature, chrono::Utc::now().timestamp()) {
+            warn!("Invalid Slack signature");
+            return Err(PlatformError::InvalidSignature(
+                "Signature verification failed".to_string(),
+            ));
         }
 
         let event_payload = self.parse_event_payload(raw)?;
 
         match event_payload {
             SlackEventPayload::UrlVerification { challenge } => {
-                // This should be handled separately by the webhook handler
-                Err(PlatformError::UnsupportedMessageType)
+                // Slack expects the challenge echoed back in the HTTP response
+                debug!("Answering Slack url_verification challenge");
+                Err(PlatformError::ImmediateResponse(serde_json::Value::String(
+                    challenge,
+                )))
             }
             SlackEventPayload::EventCallback { team_id, event } => {
                 if !self.is_workspace_allowed(&team_id) {
@@ -409,7 +596,21 @@ impl TriggerPlatform for SlackPlatform {
         channel: &str,
         response: TriggerResponse,
     ) -> Result<(), PlatformError> {
-        self.post_message(channel, &response).await
+        self.post_message(channel, &response).await?;
+
+        // Attachments follow the text; one failing doesn't stop the rest
+        let mut result = Ok(());
+        for attachment in &response.attachments {
+            if let Err(e) = self
+                .upload_file(channel, response.thread_id.as_deref(), attachment)
+                .await
+            {
+                warn!("Failed to upload attachment {}: {}", attachment.url, e);
+                result = Err(e);
+            }
+        }
+
+        result
     }
 
     fn platform_name(&self) -> &'static str {
@@ -417,30 +618,19 @@ impl TriggerPlatform for SlackPlatform {
     }
 
     async fn verify_signature(&self, payload: &[u8], signature: &str) -> bool {
-        // Slack signature format: v0=<hex_signature>
-        if !signature.starts_with("v0=") {
-            return false;
-        }
-
-        let provided_signature = &signature[3..];
-
-        // In production, should also verify timestamp from X-Slack-Request-Timestamp
-        // to prevent replay attacks (reject if older than 5 minutes)
-
-        let mut mac = match HmacSha256::new_from_slice(self.config.signing_secret.as_bytes()) {
-            Ok(m) => m,
-            Err(e) => {
-                error!("HMAC setup failed: {}", e);
-                return false;
+        // Combined form of the two headers: "timestamp.v0=<hex_signature>"
+        match signature.split_once('.') {
+            Some((timestamp, signature)) => self.verify_request(
+                payload,
+                timestamp,
+                signature,
+                chrono::Utc::now().timestamp(),
+            ),
+            None => {
+                warn!("Invalid Slack signature format");
+                false
             }
-        };
-
-        mac.update(payload);
-
-        let result = mac.finalize();
-        let computed_signature = hex::encode(result.into_bytes());
-
-        computed_signature == provided_signature
+        }
     }
 
     fn bot_name(&self) -> &str {
@@ -458,6 +648,15 @@ impl TriggerPlatform for SlackPlatform {
     fn supports_files(&self) -> bool {
         true
     }
+
+    fn supports_ephemeral(&self) -> bool {
+        true
+    }
+
+    fn ack_response(&self) -> serde_json::Value {
+        // Slack only needs the 200; a body would be posted as a reply
+        serde_json::Value::String(String::new())
+    }
 }
 
 #[cfg(test)]
@@ -473,6 +672,7 @@ mod tests {
             bot_name: "testbot".to_string(),
             allowed_workspaces: None,
             allowed_channels: None,
+            api_base_url: default_api_base_url(),
         }
     }
 
@@ -493,6 +693,7 @@ mod tests {
             bot_name: "testbot".to_string(),
             allowed_workspaces: None,
             allowed_channels: None,
+            api_base_url: default_api_base_url(),
         };
         let platform = SlackPlatform::new(config);
         assert!(platform.is_err());
@@ -542,6 +743,56 @@ mod tests {
         assert!(!result); // Should be false for invalid format
     }
 
+    /// `v0=` signature of `body` sent at `timestamp`, keyed with `secret`
+    fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+        let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(format!("v0:{}:{}", timestamp, body).as_bytes());
+        format!("v0={}", hex::encode(mac.finalize().into_bytes()))
+    }
+
+    #[test]
+    fn test_verify_request() {
+        let platform = SlackPlatform::new(create_test_config()).unwrap();
+        let body = r#"{"type":"event_callback"}"#;
+        let now = 1_700_000_000;
+
+        // Correctly signed and fresh
+        let signature = sign("test-secret", now - 30, body);
+        assert!(platform.verify_request(body.as_bytes(), &(now - 30).to_string(), &signature, now));
+
+        // Correctly signed but 10 minutes old
+        let signature = sign("test-secret", now - 600, body);
+        assert!(!platform.verify_request(body.as_bytes(), &(now - 600).to_string(), &signature, now));
+
+        // Signed with the wrong secret
+        let signature = sign("wrong-secret", now, body);
+        assert!(!platform.verify_request(body.as_bytes(), &now.to_string(), &signature, now));
+
+        // Tampered body
+        let signature = sign("test-secret", now, body);
+        assert!(!platform.verify_request(b"{}", &now.to_string(), &signature, now));
+
+        // Timestamps at the ends of the range are stale, not an overflow
+        for timestamp in [i64::MIN, i64::MAX] {
+            let signature = sign("test-secret", timestamp, body);
+            assert!(!platform.verify_request(
+                body.as_bytes(),
+                &timestamp.to_string(),
+                &signature,
+                now
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unsigned_request_rejected() {
+        let platform = SlackPlatform::new(create_test_config()).unwrap();
+        let body = br#"{"type": "url_verification", "challenge": "abc"}"#;
+
+        let result = platform.parse_message(body, &HashMap::new()).await;
+        assert!(matches!(result, Err(PlatformError::InvalidSignature(_))));
+    }
+
     #[test]
     fn test_workspace_allowed() {
         let mut config = create_test_config();
@@ -3,16 +3,28 @@
 //! This module provides integration with Telegram's Bot API, supporting:
 //! - Text messages with /commands
 //! - Inline keyboards for interactive responses
+//! - Photo, document, video and audio attachments
 //! - Callback queries from button clicks
 //! - Webhook secret token verification
+//! - Long polling via `getUpdates` when no public webhook endpoint is available
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinSet;
 use tracing::{debug, error, info, warn};
 
+use super::attachments::{fetch_attachment, MAX_ATTACHMENT_BYTES};
 use super::{PlatformError, TriggerMessage, TriggerPlatform, TriggerUser};
-use crate::response::TriggerResponse;
+use crate::handler::TriggerHandler;
+use crate::response::{Attachment, AttachmentType, TriggerResponse};
+
+/// Delay before retrying a failed `getUpdates` call; doubles up to the max
+const POLL_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const POLL_MAX_BACKOFF: Duration = Duration::from_secs(60);
 
 /// Telegram platform adapter
 pub struct TelegramPlatform {
@@ -45,12 +57,28 @@ pub struct TelegramConfig {
     /// Allowed group/chat IDs (optional whitelist)
     #[serde(default)]
     pub allowed_groups: Option<Vec<i64>>,
+
+    /// Bot API base URL (default https://api.telegram.org)
+    #[serde(default = "default_api_base_url")]
+    pub api_base_url: String,
+
+    /// Long-poll timeout in seconds for `getUpdates` (default 30)
+    #[serde(default = "default_poll_timeout_secs")]
+    pub poll_timeout_secs: u64,
 }
 
 fn default_bot_name() -> String {
     "aofbot".to_string()
 }
 
+fn default_api_base_url() -> String {
+    "https://api.telegram.org".to_string()
+}
+
+fn default_poll_timeout_secs() -> u64 {
+    30
+}
+
 /// Telegram Update object (webhook payload)
 #[derive(Debug, Clone, Deserialize)]
 struct TelegramUpdate {
@@ -170,11 +198,118 @@ impl TelegramPlatform {
     /// Get API base URL
     fn api_url(&self, method: &str) -> String {
         format!(
-            "https://api.telegram.org/bot{}/{}",
-            self.config.bot_token, method
+            "{}/bot{}/{}",
+            self.config.api_base_url.trim_end_matches('/'),
+            self.config.bot_token,
+            method
         )
     }
 
+    /// Receive updates by long polling instead of webhooks
+    ///
+    /// Telegram delivers updates either to a webhook or via `getUpdates`, never
+    /// both, so any configured webhook is deleted first. Each update is
+    /// converted to a [`TriggerMessage`] and dispatched through `handler`,
+    /// which must have this platform registered to send responses. The
+    /// acknowledged `offset` advances past every update received, so none is
+    /// processed twice. Network errors are retried with exponential backoff.
+    ///
+    /// Returns once `shutdown` completes and in-flight messages are handled.
+    pub async fn run_long_poll(
+        &self,
+        handler: Arc<TriggerHandler>,
+        shutdown: impl Future<Output = ()>,
+    ) -> Result<(), PlatformError> {
+        self.delete_webhook().await?;
+        info!("Telegram long polling started");
+
+        tokio::pin!(shutdown);
+        let mut offset: Option<i64> = None;
+        let mut backoff = POLL_INITIAL_BACKOFF;
+        let mut in_flight = JoinSet::new();
+
+        loop {
+            let updates = tokio::select! {
+                _ = &mut shutdown => break,
+                updates = self.get_updates(offset) => updates,
+            };
+
+            let updates = match updates {
+                Ok(updates) => {
+                    backoff = POLL_INITIAL_BACKOFF;
+                    updates
+                }
+                Err(e) => {
+                    warn!("Telegram getUpdates failed, retrying in {:?}: {}", backoff, e);
+                    tokio::select! {
+                        _ = &mut shutdown => break,
+                        _ = tokio::time::sleep(backoff) => {}
+                    }
+                    backoff = (backoff * 2).min(POLL_MAX_BACKOFF);
+                    continue;
+                }
+            };
+
+            for update in updates {
+                offset = Some(offset.unwrap_or(0).max(update.update_id + 1));
+
+                let message = match self.update_to_message(update) {
+                    Ok(message) => message,
+                    Err(e) => {
+                        debug!("Skipping Telegram update: {}", e);
+                        continue;
+                    }
+                };
+
+                let handler = Arc::clone(&handler);
+                in_flight.spawn(async move {
+                    if let Err(e) = handler.handle_message("telegram", message).await {
+                        error!("Failed to handle message: {}", e);
+                    }
+                });
+            }
+
+            // Reap finished handlers so the set does not grow unbounded
+            while in_flight.try_join_next().is_some() {}
+        }
+
+        info!("Telegram long polling stopping");
+        while in_flight.join_next().await.is_some() {}
+        Ok(())
+    }
+
+    /// Fetch pending updates, waiting up to `poll_timeout_secs` for new ones
+    async fn get_updates(&self, offset: Option<i64>) -> Result<Vec<TelegramUpdate>, PlatformError> {
+        let mut params = serde_json::json!({
+            "timeout": self.config.poll_timeout_secs,
+            "allowed_updates": ["message", "callback_query", "inline_query"]
+        });
+        if let Some(offset) = offset {
+            params["offset"] = serde_json::json!(offset);
+        }
+
+        // The request outlives the long-poll timeout
+        let response: TelegramApiResponse<Vec<TelegramUpdate>> = self
+            .client
+            .post(self.api_url("getUpdates"))
+            .timeout(Duration::from_secs(self.config.poll_timeout_secs + 10))
+            .json(&params)
+            .send()
+            .await
+            .map_err(|e| PlatformError::ApiError(format!("HTTP request failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| PlatformError::ParseError(format!("Failed to parse response: {}", e)))?;
+
+        if response.ok {
+            Ok(response.result.unwrap_or_default())
+        } else {
+            Err(PlatformError::ApiError(
+                response.description.unwrap_or_else(|| "Unknown error".to_string()),
+            ))
+        }
+    }
+
     /// Set webhook
     pub async fn set_webhook(&self, url: &str) -> Result<bool, PlatformError> {
         let mut params = serde_json::json!({
@@ -275,6 +410,62 @@ impl TelegramPlatform {
         }
     }
 
+    /// Bot API method and form field for uploading `attachment_type`
+    fn upload_method(attachment_type: AttachmentType) -> (&'static str, &'static str) {
+        match attachment_type {
+            AttachmentType::Image => ("sendPhoto", "photo"),
+            AttachmentType::Video => ("sendVideo", "video"),
+            AttachmentType::Audio => ("sendAudio", "audio"),
+            AttachmentType::File => ("sendDocument", "document"),
+        }
+    }
+
+    /// Upload an attachment, captioned with its title
+    ///
+    /// The file is downloaded from its URL first (up to
+    /// `MAX_ATTACHMENT_BYTES`) and sent with the method matching its type.
+    pub async fn send_attachment(
+        &self,
+        chat_id: i64,
+        attachment: &Attachment,
+        reply_to: Option<i64>,
+    ) -> Result<i64, PlatformError> {
+        let file = fetch_attachment(&self.client, attachment, MAX_ATTACHMENT_BYTES).await?;
+        let (method, field) = Self::upload_method(attachment.attachment_type);
+
+        let mut form = reqwest::multipart::Form::new()
+            .text("chat_id", chat_id.to_string())
+            .part(field, file.part()?);
+        if let Some(ref title) = attachment.title {
+            form = form.text("caption", title.clone());
+        }
+        if let Some(reply_to_id) = reply_to {
+            form = form.text("reply_to_message_id", reply_to_id.to_string());
+        }
+
+        let response: TelegramApiResponse<TelegramMessage> = self
+            .client
+            .post(self.api_url(method))
+            .multipart(form)
+            .send()
+            .await
+            .map_err(|e| PlatformError::ApiError(format!("HTTP request failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| PlatformError::ParseError(format!("Failed to parse response: {}", e)))?;
+
+        if response.ok {
+            let message_id = response.result.map(|m| m.message_id).unwrap_or(0);
+            debug!("Sent Telegram {} attachment: {}", field, message_id);
+            Ok(message_id)
+        } else {
+            error!("Telegram API error: {:?}", response.description);
+            Err(PlatformError::ApiError(
+                response.description.unwrap_or_else(|| "Unknown error".to_string()),
+            ))
+        }
+    }
+
     /// Answer callback query
     pub async fn answer_callback_query(
         &self,
@@ -393,37 +584,9 @@ impl TelegramPlatform {
 
 *Support:* [GitHub](https://github\.com/yourusername/aof)"#.to_string()
     }
-}
-
-#[async_trait]
-impl TriggerPlatform for TelegramPlatform {
-    async fn parse_message(
-        &self,
-        raw: &[u8],
-        headers: &HashMap<String, String>,
-    ) -> Result<TriggerMessage, PlatformError> {
-        // Verify secret token if configured
-        if let Some(ref secret) = self.config.webhook_secret {
-            if let Some(token) = headers.get("x-telegram-bot-api-secret-token") {
-                if token != secret {
-                    warn!("Invalid Telegram secret token");
-                    return Err(PlatformError::InvalidSignature(
-                        "Invalid secret token".to_string(),
-                    ));
-                }
-            } else {
-                warn!("Missing Telegram secret token header");
-                return Err(PlatformError::InvalidSignature(
-                    "Missing secret token".to_string(),
-                ));
-            }
-        }
-
-        let update: TelegramUpdate = serde_json::from_slice(raw).map_err(|e| {
-            error!("Failed to parse Telegram update: {}", e);
-            PlatformError::ParseError(format!("Invalid Telegram update: {}", e))
-        })?;
 
+    /// Convert an update, from a webhook or `getUpdates`, into a message
+    fn update_to_message(&self, update: TelegramUpdate) -> Result<TriggerMessage, PlatformError> {
         // Handle callback query (button clicks)
         if let Some(callback) = update.callback_query {
             let user = callback.from;
@@ -529,6 +692,39 @@ impl TriggerPlatform for TelegramPlatform {
 
         Err(PlatformError::ParseError("No message in update".to_string()))
     }
+}
+
+#[async_trait]
+impl TriggerPlatform for TelegramPlatform {
+    async fn parse_message(
+        &self,
+        raw: &[u8],
+        headers: &HashMap<String, String>,
+    ) -> Result<TriggerMessage, PlatformError> {
+        // Verify secret token if configured
+        if let Some(ref secret) = self.config.webhook_secret {
+            if let Some(token) = headers.get("x-telegram-bot-api-secret-token") {
+                if token != secret {
+                    warn!("Invalid Telegram secret token");
+                    return Err(PlatformError::InvalidSignature(
+                        "Invalid secret token".to_string(),
+                    ));
+                }
+            } else {
+                warn!("Missing Telegram secret token header");
+                return Err(PlatformError::InvalidSignature(
+                    "Missing secret token".to_string(),
+                ));
+            }
+        }
+
+        let update: TelegramUpdate = serde_json::from_slice(raw).map_err(|e| {
+            error!("Failed to parse Telegram update: {}", e);
+            PlatformError::ParseError(format!("Invalid Telegram update: {}", e))
+        })?;
+
+        self.update_to_message(update)
+    }
 
     async fn send_response(
         &self,
@@ -549,7 +745,16 @@ impl TriggerPlatform for TelegramPlatform {
 
         self.send_message(chat_id, &text, reply_to, keyboard).await?;
 
-        Ok(())
+        // Attachments follow the text; one failing doesn't stop the rest
+        let mut result = Ok(());
+        for attachment in &response.attachments {
+            if let Err(e) = self.send_attachment(chat_id, attachment, reply_to).await {
+                warn!("Failed to send attachment {}: {}", attachment.url, e);
+                result = Err(e);
+            }
+        }
+
+        result
     }
 
     fn platform_name(&self) -> &'static str {
@@ -594,6 +799,8 @@ mod tests {
             bot_name: "testbot".to_string(),
             allowed_users: None,
             allowed_groups: None,
+            api_base_url: default_api_base_url(),
+            poll_timeout_secs: default_poll_timeout_secs(),
         }
     }
 
@@ -613,6 +820,8 @@ mod tests {
             bot_name: "".to_string(),
             allowed_users: None,
             allowed_groups: None,
+            api_base_url: default_api_base_url(),
+            poll_timeout_secs: default_poll_timeout_secs(),
         };
         let platform = TelegramPlatform::new(config);
         assert!(platform.is_err());
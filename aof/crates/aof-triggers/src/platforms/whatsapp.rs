@@ -11,6 +11,7 @@ use hmac::{Hmac, Mac};
 use serde::{Deserialize, Serialize};
 use sha2::Sha256;
 use std::collections::HashMap;
+use subtle::ConstantTimeEq;
 use tracing::{debug, error, info, warn};
 
 use super::{PlatformError, TriggerMessage, TriggerPlatform, TriggerUser};
@@ -18,6 +19,21 @@ use crate::response::TriggerResponse;
 
 type HmacSha256 = Hmac<Sha256>;
 
+/// Header carrying the `sha256=<hex>` HMAC of the raw webhook body
+const SIGNATURE_HEADER: &str = "x-hub-signature-256";
+
+/// Maximum length of a text message body
+const MAX_TEXT_LEN: usize = 4096;
+
+/// Maximum length of an interactive message body
+const MAX_INTERACTIVE_BODY_LEN: usize = 1024;
+
+/// Maximum number of reply buttons in one message
+const MAX_BUTTONS: usize = 3;
+
+/// Maximum number of rows in a list message
+const MAX_LIST_ROWS: usize = 10;
+
 /// WhatsApp platform adapter
 pub struct WhatsAppPlatform {
     config: WhatsAppConfig,
@@ -50,12 +66,20 @@ pub struct WhatsAppConfig {
     /// API version (default v18.0)
     #[serde(default = "default_api_version")]
     pub api_version: String,
+
+    /// Graph API base URL (default https://graph.facebook.com)
+    #[serde(default = "default_api_base_url")]
+    pub api_base_url: String,
 }
 
 fn default_api_version() -> String {
     "v18.0".to_string()
 }
 
+fn default_api_base_url() -> String {
+    "https://graph.facebook.com".to_string()
+}
+
 /// WhatsApp webhook payload
 #[derive(Debug, Clone, Deserialize)]
 struct WhatsAppWebhook {
@@ -115,6 +139,14 @@ struct WebhookMessage {
     text: Option<TextMessage>,
     #[serde(default)]
     interactive: Option<InteractiveResponse>,
+    #[serde(default)]
+    context: Option<MessageContext>,
+}
+
+/// Set when the user replied to an earlier message
+#[derive(Debug, Clone, Deserialize)]
+struct MessageContext {
+    id: String,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -190,6 +222,16 @@ impl WhatsAppPlatform {
         Ok(Self { config, client })
     }
 
+    /// `POST /{phone_number_id}/messages` endpoint of the Cloud API
+    fn messages_url(&self) -> String {
+        format!(
+            "{}/{}/{}/messages",
+            self.config.api_base_url.trim_end_matches('/'),
+            self.config.api_version,
+            self.config.phone_number_id
+        )
+    }
+
     /// Verify webhook subscription (GET request)
     pub fn verify_webhook(&self, mode: &str, token: &str, challenge: &str) -> Option<String> {
         if mode == "subscribe" && token == self.config.verify_token {
@@ -207,11 +249,7 @@ impl WhatsAppPlatform {
         to: &str,
         text: &str,
     ) -> Result<String, PlatformError> {
-        let url = format!(
-            "https://graph.facebook.com/{}/{}/messages",
-            self.config.api_version, self.config.phone_number_id
-        );
-
+        let url = self.messages_url();
         let payload = serde_json::json!({
             "messaging_product": "whatsapp",
             "recipient_type": "individual",
@@ -233,20 +271,16 @@ impl WhatsAppPlatform {
         body_text: &str,
         buttons: Vec<(String, String)>, // (id, title)
     ) -> Result<String, PlatformError> {
-        let url = format!(
-            "https://graph.facebook.com/{}/{}/messages",
-            self.config.api_version, self.config.phone_number_id
-        );
-
+        let url = self.messages_url();
         let button_objects: Vec<serde_json::Value> = buttons
             .into_iter()
-            .take(3) // WhatsApp allows max 3 buttons
+            .take(MAX_BUTTONS)
             .map(|(id, title)| {
                 serde_json::json!({
                     "type": "reply",
                     "reply": {
                         "id": id,
-                        "title": title.chars().take(20).collect::<String>() // Max 20 chars
+                        "title": truncate_chars(&title, 20) // Max 20 chars
                     }
                 })
             })
@@ -280,11 +314,7 @@ impl WhatsAppPlatform {
         button_text: &str,
         sections: Vec<ListSection>,
     ) -> Result<String, PlatformError> {
-        let url = format!(
-            "https://graph.facebook.com/{}/{}/messages",
-            self.config.api_version, self.config.phone_number_id
-        );
-
+        let url = self.messages_url();
         let section_objects: Vec<serde_json::Value> = sections
             .into_iter()
             .map(|section| {
@@ -347,15 +377,29 @@ impl WhatsAppPlatform {
             .await
             .map_err(|e| PlatformError::ApiError(format!("HTTP request failed: {}", e)))?;
 
-        let api_response: WhatsAppApiResponse = response
-            .json()
-            .await
-            .map_err(|e| PlatformError::ParseError(format!("Failed to parse response: {}", e)))?;
+        let status = response.status();
+        let api_response: WhatsAppApiResponse = match response.json().await {
+            Ok(api_response) => api_response,
+            Err(e) if status.is_success() => {
+                return Err(PlatformError::ParseError(format!(
+                    "Failed to parse response: {}",
+                    e
+                )))
+            }
+            Err(_) => {
+                error!("WhatsApp API returned HTTP {}", status);
+                return Err(PlatformError::ApiError(format!("HTTP {}", status)));
+            }
+        };
 
         if let Some(error) = api_response.error {
             error!("WhatsApp API error: {} (code: {})", error.message, error.code);
             return Err(PlatformError::ApiError(error.message));
         }
+        if !status.is_success() {
+            error!("WhatsApp API returned HTTP {}", status);
+            return Err(PlatformError::ApiError(format!("HTTP {}", status)));
+        }
 
         let message_id = api_response
             .messages
@@ -396,6 +440,134 @@ impl WhatsAppPlatform {
 
         format!("{} {}", status_emoji, response.text)
     }
+
+    /// Build the Cloud API message payloads that deliver `response` to `to`
+    ///
+    /// Text longer than WhatsApp's limit is split across several messages.
+    /// Up to three actions become reply buttons and more become a list
+    /// message; either way the interactive message comes last, carrying the
+    /// tail of the text as its body. `reply_to` quotes the original message.
+    fn response_payloads(&self, to: &str, response: &TriggerResponse) -> Vec<serde_json::Value> {
+        let text = self.format_response_text(response);
+        let interactive = !response.actions.is_empty();
+
+        let mut chunks = split_text(&text, MAX_TEXT_LEN);
+        let body = if interactive {
+            let last = chunks.pop().unwrap_or_default();
+            if last.chars().count() > MAX_INTERACTIVE_BODY_LEN {
+                chunks.push(last);
+                "Choose an action:".to_string()
+            } else {
+                last
+            }
+        } else {
+            String::new()
+        };
+
+        let mut payloads: Vec<serde_json::Value> = chunks
+            .into_iter()
+            .map(|chunk| {
+                serde_json::json!({
+                    "type": "text",
+                    "text": {
+                        "preview_url": false,
+                        "body": chunk
+                    }
+                })
+            })
+            .collect();
+
+        if response.actions.len() > MAX_BUTTONS {
+            let rows: Vec<serde_json::Value> = response
+                .actions
+                .iter()
+                .take(MAX_LIST_ROWS)
+                .map(|action| {
+                    serde_json::json!({
+                        "id": action.id,
+                        "title": truncate_chars(&action.label, 24)
+                    })
+                })
+                .collect();
+
+            payloads.push(serde_json::json!({
+                "type": "interactive",
+                "interactive": {
+                    "type": "list",
+                    "body": { "text": body },
+                    "action": {
+                        "button": "Options",
+                        "sections": [{ "title": "Actions", "rows": rows }]
+                    }
+                }
+            }));
+        } else if interactive {
+            let buttons: Vec<serde_json::Value> = response
+                .actions
+                .iter()
+                .map(|action| {
+                    serde_json::json!({
+                        "type": "reply",
+                        "reply": {
+                            "id": action.id,
+                            "title": truncate_chars(&action.label, 20)
+                        }
+                    })
+                })
+                .collect();
+
+            payloads.push(serde_json::json!({
+                "type": "interactive",
+                "interactive": {
+                    "type": "button",
+                    "body": { "text": body },
+                    "action": { "buttons": buttons }
+                }
+            }));
+        }
+
+        for (index, payload) in payloads.iter_mut().enumerate() {
+            payload["messaging_product"] = serde_json::json!("whatsapp");
+            payload["recipient_type"] = serde_json::json!("individual");
+            payload["to"] = serde_json::json!(to);
+            if let (0, Some(reply_to)) = (index, &response.reply_to) {
+                payload["context"] = serde_json::json!({ "message_id": reply_to });
+            }
+        }
+
+        payloads
+    }
+}
+
+/// Split `text` into chunks of at most `max_chars` characters, preferring
+/// to break at newlines
+fn split_text(text: &str, max_chars: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut rest = text;
+
+    while rest.chars().count() > max_chars {
+        let limit = rest
+            .char_indices()
+            .nth(max_chars)
+            .map(|(i, _)| i)
+            .unwrap_or(rest.len());
+        let split = match rest[..limit].rfind('\n') {
+            Some(newline) if newline > 0 => newline,
+            _ => limit,
+        };
+        chunks.push(rest[..split].to_string());
+        rest = rest[split..].trim_start_matches('\n');
+    }
+
+    if !rest.is_empty() || chunks.is_empty() {
+        chunks.push(rest.to_string());
+    }
+    chunks
+}
+
+/// First `max_chars` characters of `text`
+fn truncate_chars(text: &str, max_chars: usize) -> String {
+    text.chars().take(max_chars).collect()
 }
 
 /// List section for interactive list messages
@@ -420,14 +592,18 @@ impl TriggerPlatform for WhatsAppPlatform {
         raw: &[u8],
         headers: &HashMap<String, String>,
     ) -> Result<TriggerMessage, PlatformError> {
-        // Verify signature if present
-        if let Some(signature) = headers.get("x-hub-signature-256") {
-            if !self.verify_signature(raw, signature).await {
-                warn!("Invalid WhatsApp signature");
-                return Err(PlatformError::InvalidSignature(
-                    "Signature verification failed".to_string(),
-                ));
-            }
+        // Verify signature first
+        let Some(signature) = headers.get(SIGNATURE_HEADER) else {
+            warn!("WhatsApp request without signature header");
+            return Err(PlatformError::InvalidSignature(
+                "Missing signature header".to_string(),
+            ));
+        };
+        if !self.verify_signature(raw, signature).await {
+            warn!("Invalid WhatsApp signature");
+            return Err(PlatformError::InvalidSignature(
+                "Signature verification failed".to_string(),
+            ));
         }
 
         let webhook = self.parse_webhook_payload(raw)?;
@@ -463,7 +639,9 @@ impl TriggerPlatform for WhatsAppPlatform {
                     // Get contact name
                     let contact_name = value
                         .contacts
-                        .first()
+                        .iter()
+                        .find(|c| c.wa_id == message.from)
+                        .or_else(|| value.contacts.first())
                         .map(|c| c.profile.name.clone())
                         .unwrap_or_else(|| message.from.clone());
 
@@ -516,16 +694,23 @@ impl TriggerPlatform for WhatsAppPlatform {
                         serde_json::json!(value.metadata.display_phone_number),
                     );
 
+                    let timestamp = message
+                        .timestamp
+                        .parse::<i64>()
+                        .ok()
+                        .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+                        .unwrap_or_else(chrono::Utc::now);
+
                     return Ok(TriggerMessage {
                         id: message.id,
                         platform: "whatsapp".to_string(),
                         channel_id: message.from.clone(), // Use phone number as channel
                         user: trigger_user,
                         text,
-                        timestamp: chrono::Utc::now(),
+                        timestamp,
                         metadata,
                         thread_id: None,
-                        reply_to: None,
+                        reply_to: message.context.map(|c| c.id),
                     });
                 }
             }
@@ -539,20 +724,9 @@ impl TriggerPlatform for WhatsAppPlatform {
         channel: &str, // Phone number
         response: TriggerResponse,
     ) -> Result<(), PlatformError> {
-        let text = self.format_response_text(&response);
-
-        // If response has actions, send interactive buttons
-        if !response.actions.is_empty() {
-            let buttons: Vec<(String, String)> = response
-                .actions
-                .iter()
-                .take(3)
-                .map(|a| (a.id.clone(), a.label.clone()))
-                .collect();
-
-            self.send_interactive_buttons(channel, &text, buttons).await?;
-        } else {
-            self.send_text_message(channel, &text).await?;
+        let url = self.messages_url();
+        for payload in self.response_payloads(channel, &response) {
+            self.send_message_api(&url, &payload).await?;
         }
 
         Ok(())
@@ -564,11 +738,12 @@ impl TriggerPlatform for WhatsAppPlatform {
 
     async fn verify_signature(&self, payload: &[u8], signature: &str) -> bool {
         // WhatsApp signature format: sha256=<hex_signature>
-        if !signature.starts_with("sha256=") {
+        let Some(provided) = signature
+            .strip_prefix("sha256=")
+            .and_then(|hex_signature| hex::decode(hex_signature).ok())
+        else {
             return false;
-        }
-
-        let provided_signature = &signature[7..];
+        };
 
         let mut mac = match HmacSha256::new_from_slice(self.config.app_secret.as_bytes()) {
             Ok(m) => m,
@@ -580,10 +755,9 @@ impl TriggerPlatform for WhatsAppPlatform {
 
         mac.update(payload);
 
-        let result = mac.finalize();
-        let computed_signature = hex::encode(result.into_bytes());
+        let computed = mac.finalize().into_bytes();
 
-        computed_signature == provided_signature
+        computed.as_slice().ct_eq(&provided).into()
     }
 
     fn bot_name(&self) -> &str {
@@ -616,6 +790,7 @@ mod tests {
             business_account_id: None,
             allowed_numbers: None,
             api_version: "v18.0".to_string(),
+            api_base_url: default_api_base_url(),
         }
     }
 
@@ -636,6 +811,7 @@ mod tests {
             business_account_id: None,
             allowed_numbers: None,
             api_version: "v18.0".to_string(),
+            api_base_url: default_api_base_url(),
         };
         let platform = WhatsAppPlatform::new(config);
         assert!(platform.is_err());
@@ -692,4 +868,51 @@ mod tests {
         let result = platform.verify_signature(payload, invalid_sig).await;
         assert!(!result);
     }
+
+    #[tokio::test]
+    async fn test_verify_signature() {
+        let config = create_test_config();
+        let platform = WhatsAppPlatform::new(config).unwrap();
+
+        let payload = br#"{"object":"whatsapp_business_account","entry":[]}"#;
+        let mut mac = HmacSha256::new_from_slice(b"test-secret").unwrap();
+        mac.update(payload);
+        let signature = format!("sha256={}", hex::encode(mac.finalize().into_bytes()));
+
+        assert!(platform.verify_signature(payload, &signature).await);
+        assert!(!platform.verify_signature(b"tampered", &signature).await);
+        assert!(!platform.verify_signature(payload, "sha256=not-hex").await);
+    }
+
+    #[test]
+    fn test_long_response_is_split() {
+        let config = create_test_config();
+        let platform = WhatsAppPlatform::new(config).unwrap();
+
+        let line = "x".repeat(3000);
+        let mut response = TriggerResponse::text(format!("{}\n{}", line, line));
+        response.actions = vec![crate::response::Action {
+            id: "retry".to_string(),
+            label: "Retry".to_string(),
+            value: "retry".to_string(),
+            style: crate::response::ActionStyle::Primary,
+        }];
+
+        let payloads = platform.response_payloads("15551234567", &response);
+        assert_eq!(payloads.len(), 3);
+        assert_eq!(payloads[0]["type"], "text");
+        assert_eq!(payloads[1]["text"]["body"], line.as_str());
+        assert_eq!(payloads[2]["interactive"]["body"]["text"], "Choose an action:");
+        assert!(payloads
+            .iter()
+            .all(|p| p["to"] == "15551234567" && p["messaging_product"] == "whatsapp"));
+    }
+
+    #[test]
+    fn test_split_text() {
+        assert_eq!(split_text("short", 10), vec!["short"]);
+        assert_eq!(split_text("", 10), vec![""]);
+        assert_eq!(split_text("aaaa\nbbbb\ncc", 10), vec!["aaaa\nbbbb", "cc"]);
+        assert_eq!(split_text("ééééé", 2), vec!["éé", "éé", "é"]);
+    }
 }
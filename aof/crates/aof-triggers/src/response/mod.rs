@@ -63,6 +63,11 @@ pub struct TriggerResponse {
     /// Message ID to reply to
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_to: Option<String>,
+
+    /// Show the response only to this user, where the platform supports
+    /// ephemeral messages
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ephemeral_to: Option<String>,
 }
 
 impl TriggerResponse {
@@ -77,6 +82,7 @@ impl TriggerResponse {
             actions: Vec::new(),
             thread_id: None,
             reply_to: None,
+            ephemeral_to: None,
         }
     }
 
@@ -91,6 +97,7 @@ impl TriggerResponse {
             actions: Vec::new(),
             thread_id: None,
             reply_to: None,
+            ephemeral_to: None,
         }
     }
 
@@ -105,6 +112,7 @@ impl TriggerResponse {
             actions: Vec::new(),
             thread_id: None,
             reply_to: None,
+            ephemeral_to: None,
         }
     }
 
@@ -119,6 +127,7 @@ impl TriggerResponse {
             actions: Vec::new(),
             thread_id: None,
             reply_to: None,
+            ephemeral_to: None,
         }
     }
 
@@ -282,6 +291,7 @@ impl TriggerResponseBuilder {
                 actions: Vec::new(),
                 thread_id: None,
                 reply_to: None,
+                ephemeral_to: None,
             },
         }
     }
@@ -340,6 +350,12 @@ impl TriggerResponseBuilder {
         self
     }
 
+    /// Show only to `user_id`, where the platform supports ephemeral messages
+    pub fn ephemeral_to(mut self, user_id: String) -> Self {
+        self.response.ephemeral_to = Some(user_id);
+        self
+    }
+
     /// Build the response
     pub fn build(self) -> TriggerResponse {
         self.response
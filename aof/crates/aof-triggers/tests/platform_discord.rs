@@ -1,21 +1,37 @@
 //! Discord platform tests
 
 use aof_triggers::platforms::discord::{DiscordPlatform, DiscordConfig};
-use aof_triggers::platforms::TriggerPlatform;
+use aof_triggers::platforms::{PlatformError, TriggerPlatform};
+use ed25519_dalek::{Signer, SigningKey};
 use std::collections::HashMap;
 
+/// Application key pair the test interactions are signed with
+fn signing_key() -> SigningKey {
+    SigningKey::from_bytes(&[42u8; 32])
+}
+
 fn test_config() -> DiscordConfig {
     DiscordConfig {
         bot_token: "MTIzNDU2Nzg5.TEST.token".to_string(),
         application_id: "123456789012345678".to_string(),
-        // Valid Ed25519 public key (32 bytes in hex) - using a known valid test key
-        // This is the Y coordinate of a valid point on the Ed25519 curve
-        public_key: "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c".to_string(),
+        public_key: hex::encode(signing_key().verifying_key().to_bytes()),
         guild_ids: None,
         allowed_roles: None,
+        api_base_url: "https://discord.com/api/v10".to_string(),
     }
 }
 
+/// Signature headers for `payload` as Discord sends them
+fn signed_headers(key: &SigningKey, payload: &str) -> HashMap<String, String> {
+    let timestamp = "1700000000";
+    let signature = key.sign(format!("{}{}", timestamp, payload).as_bytes());
+
+    let mut headers = HashMap::new();
+    headers.insert("x-signature-ed25519".to_string(), hex::encode(signature.to_bytes()));
+    headers.insert("x-signature-timestamp".to_string(), timestamp.to_string());
+    headers
+}
+
 #[tokio::test]
 async fn test_discord_platform_creation() {
     let platform = DiscordPlatform::from_discord_config(test_config());
@@ -51,11 +67,28 @@ async fn test_discord_supports_interactive() {
 async fn test_discord_parse_ping() {
     let payload = r#"{"type": 1, "id": "123", "application_id": "456"}"#;
     let platform = DiscordPlatform::from_discord_config(test_config()).unwrap();
-    let headers = HashMap::new();
+    let headers = signed_headers(&signing_key(), payload);
     let result = platform.parse_message(payload.as_bytes(), &headers).await;
 
-    // Ping should return error (handled separately)
-    assert!(result.is_err());
+    // Ping is answered synchronously with a PONG
+    match result {
+        Err(PlatformError::ImmediateResponse(body)) => {
+            assert_eq!(body, serde_json::json!({ "type": 1 }));
+        }
+        other => panic!("expected PONG, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_discord_rejects_tampered_payload() {
+    let payload = r#"{"type": 1, "id": "123", "application_id": "456"}"#;
+    let platform = DiscordPlatform::from_discord_config(test_config()).unwrap();
+    let headers = signed_headers(&signing_key(), payload);
+
+    let tampered = r#"{"type": 1, "id": "124", "application_id": "456"}"#;
+    let result = platform.parse_message(tampered.as_bytes(), &headers).await;
+
+    assert!(matches!(result, Err(PlatformError::InvalidSignature(_))));
 }
 
 #[tokio::test]
@@ -83,7 +116,7 @@ async fn test_discord_parse_slash_command() {
         }
     }"#;
     let platform = DiscordPlatform::from_discord_config(test_config()).unwrap();
-    let headers = HashMap::new();
+    let headers = signed_headers(&signing_key(), payload);
     let message = platform.parse_message(payload.as_bytes(), &headers).await.unwrap();
 
     assert_eq!(message.platform, "discord");
@@ -91,3 +124,55 @@ async fn test_discord_parse_slash_command() {
     assert_eq!(message.channel_id, "3333333333333333333");
     assert!(message.text.contains("agent"));
 }
+
+#[tokio::test]
+async fn test_discord_posts_attachments_as_multipart_message() {
+    use aof_triggers::response::{Attachment, AttachmentType, TriggerResponseBuilder};
+    use axum::extract::State;
+    use axum::http::Uri;
+    use std::sync::{Arc, Mutex};
+
+    // Every call the mock API receives: (path, raw request body)
+    type RecordedCalls = Arc<Mutex<Vec<(String, String)>>>;
+    let calls = RecordedCalls::default();
+    let app = axum::Router::new()
+        .fallback(
+            |State(calls): State<RecordedCalls>, uri: Uri, body: axum::body::Bytes| async move {
+                calls.lock().unwrap().push((
+                    uri.path().to_string(),
+                    String::from_utf8_lossy(&body).into_owned(),
+                ));
+                format!("contents of {}", uri.path())
+            },
+        )
+        .with_state(calls.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let mut config = test_config();
+    config.api_base_url = format!("http://{}/api/v10", addr);
+    let platform = DiscordPlatform::from_discord_config(config).unwrap();
+
+    let attachment = |attachment_type, name: &str| Attachment {
+        attachment_type,
+        url: format!("http://{}/files/{}", addr, name),
+        filename: None,
+        title: None,
+    };
+    let response = TriggerResponseBuilder::new()
+        .text("Dashboards")
+        .attachment(attachment(AttachmentType::Image, "cpu.png"))
+        .attachment(attachment(AttachmentType::File, "events.json"))
+        .build();
+    platform.send_response("3333", response).await.unwrap();
+
+    let calls = calls.lock().unwrap();
+    let (path, body) = calls.last().unwrap();
+    assert_eq!(calls.len(), 3);
+    assert_eq!(path, "/api/v10/channels/3333/messages");
+    assert!(body.contains(r#"name="payload_json""#));
+    assert!(body.contains(r#"name="files[0]"; filename="cpu.png""#));
+    assert!(body.contains(r#"name="files[1]"; filename="events.json""#));
+    assert!(body.contains("contents of /files/events.json"));
+}
@@ -1,7 +1,9 @@
 //! Slack platform tests
 
 use aof_triggers::platforms::slack::{SlackPlatform, SlackConfig};
-use aof_triggers::platforms::TriggerPlatform;
+use aof_triggers::platforms::{PlatformError, TriggerPlatform};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
 use std::collections::HashMap;
 
 fn test_config() -> SlackConfig {
@@ -13,9 +15,25 @@ fn test_config() -> SlackConfig {
         bot_name: "aofbot".to_string(),
         allowed_workspaces: None,
         allowed_channels: None,
+        api_base_url: "https://slack.com/api".to_string(),
     }
 }
 
+/// Signature headers for `payload` as Slack sends them, signed now
+fn signed_headers(payload: &str) -> HashMap<String, String> {
+    let timestamp = chrono::Utc::now().timestamp().to_string();
+    let mut mac = Hmac::<Sha256>::new_from_slice(b"test_signing_secret").unwrap();
+    mac.update(format!("v0:{}:{}", timestamp, payload).as_bytes());
+
+    let mut headers = HashMap::new();
+    headers.insert(
+        "x-slack-signature".to_string(),
+        format!("v0={}", hex::encode(mac.finalize().into_bytes())),
+    );
+    headers.insert("x-slack-request-timestamp".to_string(), timestamp);
+    headers
+}
+
 #[tokio::test]
 async fn test_slack_platform_creation() {
     let platform = SlackPlatform::new(test_config());
@@ -37,7 +55,7 @@ async fn test_slack_parse_message_event() {
     }"#;
 
     let platform = SlackPlatform::new(test_config()).unwrap();
-    let headers = HashMap::new();
+    let headers = signed_headers(payload);
     let message = platform.parse_message(payload.as_bytes(), &headers).await.unwrap();
 
     assert_eq!(message.platform, "slack");
@@ -50,18 +68,23 @@ async fn test_slack_parse_message_event() {
 async fn test_slack_parse_url_verification() {
     let payload = r#"{"type": "url_verification", "challenge": "3eZbrw1aBm2rZgRNFdxV2595E9CY3gmdALWMmHkvFXO7tYXAYM8P"}"#;
     let platform = SlackPlatform::new(test_config()).unwrap();
-    let headers = HashMap::new();
+    let headers = signed_headers(payload);
     let result = platform.parse_message(payload.as_bytes(), &headers).await;
 
     // URL verification returns error with challenge
-    assert!(result.is_err());
+    match result {
+        Err(PlatformError::ImmediateResponse(body)) => {
+            assert_eq!(body, "3eZbrw1aBm2rZgRNFdxV2595E9CY3gmdALWMmHkvFXO7tYXAYM8P");
+        }
+        other => panic!("expected immediate response, got {:?}", other),
+    }
 }
 
 #[tokio::test]
 async fn test_slack_parse_missing_event() {
     let payload = r#"{"type": "event_callback", "team_id": "T0123"}"#;
     let platform = SlackPlatform::new(test_config()).unwrap();
-    let headers = HashMap::new();
+    let headers = signed_headers(payload);
     let result = platform.parse_message(payload.as_bytes(), &headers).await;
 
     assert!(result.is_err());
@@ -98,3 +121,78 @@ async fn test_slack_supports_interactive() {
     let platform = SlackPlatform::new(test_config()).unwrap();
     assert!(platform.supports_interactive());
 }
+
+#[tokio::test]
+async fn test_slack_uploads_attachments_externally() {
+    use aof_triggers::response::{Attachment, AttachmentType, TriggerResponseBuilder};
+    use axum::extract::State;
+    use axum::http::Uri;
+    use axum::Json;
+    use std::sync::{Arc, Mutex};
+
+    // Every call the mock Web API receives: (path, raw request body)
+    type RecordedCalls = Arc<Mutex<Vec<(String, String)>>>;
+    let calls = RecordedCalls::default();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = axum::Router::new()
+        .fallback(
+            move |State(calls): State<RecordedCalls>,
+                  uri: Uri,
+                  body: axum::body::Bytes| async move {
+                let path = uri.path().to_string();
+                calls
+                    .lock()
+                    .unwrap()
+                    .push((path.clone(), String::from_utf8_lossy(&body).into_owned()));
+                Json(match path.as_str() {
+                    "/files/rollout.txt" => serde_json::json!("rollout notes"),
+                    "/api/files.getUploadURLExternal" => serde_json::json!({
+                        "ok": true,
+                        "upload_url": format!("http://{}/upload/F123", addr),
+                        "file_id": "F123"
+                    }),
+                    _ => serde_json::json!({"ok": true}),
+                })
+            },
+        )
+        .with_state(calls.clone());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let mut config = test_config();
+    config.api_base_url = format!("http://{}/api", addr);
+    let platform = SlackPlatform::new(config).unwrap();
+
+    let response = TriggerResponseBuilder::new()
+        .text("Rollout finished")
+        .thread_id("1700000000.000100".to_string())
+        .attachment(Attachment {
+            attachment_type: AttachmentType::File,
+            url: format!("http://{}/files/rollout.txt", addr),
+            filename: None,
+            title: Some("Rollout notes".to_string()),
+        })
+        .build();
+    platform.send_response("C123", response).await.unwrap();
+
+    let calls = calls.lock().unwrap();
+    let paths: Vec<&str> = calls.iter().map(|(path, _)| path.as_str()).collect();
+    assert_eq!(
+        paths,
+        [
+            "/api/chat.postMessage",
+            "/files/rollout.txt",
+            "/api/files.getUploadURLExternal",
+            "/upload/F123",
+            "/api/files.completeUploadExternal",
+        ]
+    );
+    assert!(calls[2].1.contains("filename=rollout.txt"));
+    assert!(calls[3].1.contains("rollout notes"));
+
+    let complete: serde_json::Value = serde_json::from_str(&calls[4].1).unwrap();
+    assert_eq!(complete["channel_id"], "C123");
+    assert_eq!(complete["thread_ts"], "1700000000.000100");
+    assert_eq!(complete["files"][0]["id"], "F123");
+    assert_eq!(complete["files"][0]["title"], "Rollout notes");
+}
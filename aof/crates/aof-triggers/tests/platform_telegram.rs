@@ -1,8 +1,15 @@
 //! Telegram platform tests
 
+use aof_runtime::RuntimeOrchestrator;
 use aof_triggers::platforms::telegram::{TelegramPlatform, TelegramConfig};
 use aof_triggers::platforms::TriggerPlatform;
+use aof_triggers::{TriggerHandler, TriggerHandlerConfig};
+use axum::extract::{Path, State};
+use axum::routing::post;
+use axum::{Json, Router};
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 fn test_config() -> TelegramConfig {
     TelegramConfig {
@@ -12,6 +19,8 @@ fn test_config() -> TelegramConfig {
         bot_name: "aofbot".to_string(),
         allowed_users: None,
         allowed_groups: None,
+        api_base_url: "https://api.telegram.org".to_string(),
+        poll_timeout_secs: 30,
     }
 }
 
@@ -133,3 +142,199 @@ async fn test_telegram_signature_verification() {
     let result = platform.verify_signature(b"test payload", "wrong_secret").await;
     assert!(!result);
 }
+
+/// State of the mock Bot API: update ids returned by `getUpdates` and chat ids
+/// that `sendMessage` was called for
+#[derive(Default)]
+struct MockBotApi {
+    delivered: Vec<i64>,
+    sent_to: Vec<i64>,
+}
+
+async fn mock_bot_api(
+    State(api): State<Arc<Mutex<MockBotApi>>>,
+    Path((_bot, method)): Path<(String, String)>,
+    body: axum::body::Bytes,
+) -> Json<serde_json::Value> {
+    // deleteWebhook is sent without a body
+    let params: serde_json::Value = serde_json::from_slice(&body).unwrap_or_default();
+    match method.as_str() {
+        "getUpdates" => {
+            // Like Telegram, return only updates at or after the offset
+            let offset = params["offset"].as_i64().unwrap_or(0);
+            let updates: Vec<serde_json::Value> = [(1001, 1), (1002, 2)]
+                .into_iter()
+                .filter(|(update_id, _)| *update_id >= offset)
+                .map(|(update_id, chat_id)| {
+                    serde_json::json!({
+                        "update_id": update_id,
+                        "message": {
+                            "message_id": update_id,
+                            "from": {"id": chat_id, "is_bot": false, "first_name": "User"},
+                            "chat": {"id": chat_id, "type": "private"},
+                            "date": 1638360000,
+                            "text": "/help"
+                        }
+                    })
+                })
+                .collect();
+
+            if updates.is_empty() {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+            let mut api = api.lock().unwrap();
+            api.delivered
+                .extend(updates.iter().map(|u| u["update_id"].as_i64().unwrap()));
+            Json(serde_json::json!({"ok": true, "result": updates}))
+        }
+        "sendMessage" => {
+            api.lock()
+                .unwrap()
+                .sent_to
+                .push(params["chat_id"].as_i64().unwrap());
+            Json(serde_json::json!({
+                "ok": true,
+                "result": {"message_id": 1, "chat": {"id": params["chat_id"], "type": "private"}}
+            }))
+        }
+        _ => Json(serde_json::json!({"ok": true, "result": true})),
+    }
+}
+
+#[tokio::test]
+async fn test_telegram_long_poll_handles_each_update_once() {
+    let api = Arc::new(Mutex::new(MockBotApi::default()));
+    let app = Router::new()
+        .route("/:bot/:method", post(mock_bot_api))
+        .with_state(api.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let mut config = test_config();
+    config.api_base_url = format!("http://{}", addr);
+    config.poll_timeout_secs = 1;
+    let platform = Arc::new(TelegramPlatform::new(config).unwrap());
+
+    let handler_config = TriggerHandlerConfig {
+        auto_ack: false,
+        ..Default::default()
+    };
+    let mut handler =
+        TriggerHandler::with_config(Arc::new(RuntimeOrchestrator::new()), handler_config);
+    handler.register_platform(platform.clone());
+    let handler = Arc::new(handler);
+
+    let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+    let poller = {
+        let platform = platform.clone();
+        tokio::spawn(async move {
+            platform
+                .run_long_poll(handler, async {
+                    let _ = stop_rx.await;
+                })
+                .await
+        })
+    };
+
+    // Let the poller make several more getUpdates calls after both responses
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while api.lock().unwrap().sent_to.len() < 2 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("both updates should be handled");
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    stop_tx.send(()).unwrap();
+    poller.await.unwrap().unwrap();
+
+    let api = api.lock().unwrap();
+    assert_eq!(api.delivered, vec![1001, 1002]);
+    let mut sent_to = api.sent_to.clone();
+    sent_to.sort();
+    assert_eq!(sent_to, vec![1, 2]);
+}
+
+/// Bot API calls received by the mock: (method, raw request body)
+type RecordedCalls = Arc<Mutex<Vec<(String, String)>>>;
+
+async fn record_bot_api_call(
+    State(calls): State<RecordedCalls>,
+    Path((_bot, method)): Path<(String, String)>,
+    body: axum::body::Bytes,
+) -> Json<serde_json::Value> {
+    calls
+        .lock()
+        .unwrap()
+        .push((method, String::from_utf8_lossy(&body).into_owned()));
+    Json(serde_json::json!({
+        "ok": true,
+        "result": {"message_id": 7, "chat": {"id": 42, "type": "private"}}
+    }))
+}
+
+#[tokio::test]
+async fn test_telegram_uploads_attachments_with_matching_method() {
+    use aof_triggers::response::{Attachment, AttachmentType, TriggerResponseBuilder};
+    use axum::routing::get;
+
+    let calls = RecordedCalls::default();
+    let app = Router::new()
+        .route("/files/:name", get(|| async { "file contents" }))
+        .route("/:bot/:method", post(record_bot_api_call))
+        .with_state(calls.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let mut config = test_config();
+    config.api_base_url = format!("http://{}", addr);
+    let platform = TelegramPlatform::new(config).unwrap();
+
+    let mut response = TriggerResponseBuilder::new().text("Incident report");
+    for (attachment_type, name) in [
+        (AttachmentType::Image, "latency.png"),
+        (AttachmentType::File, "pods.log"),
+        (AttachmentType::Video, "replay.mp4"),
+        (AttachmentType::Audio, "page.ogg"),
+    ] {
+        response = response.attachment(Attachment {
+            attachment_type,
+            url: format!("http://{}/files/{}", addr, name),
+            filename: None,
+            title: Some(format!("About {}", name)),
+        });
+    }
+    platform
+        .send_response("42", response.build())
+        .await
+        .unwrap();
+
+    let calls = calls.lock().unwrap();
+    let methods: Vec<&str> = calls.iter().map(|(method, _)| method.as_str()).collect();
+    assert_eq!(
+        methods,
+        [
+            "sendMessage",
+            "sendPhoto",
+            "sendDocument",
+            "sendVideo",
+            "sendAudio"
+        ]
+    );
+
+    // Each file is uploaded under the field its method expects
+    let photo = &calls[1].1;
+    assert!(photo.contains(r#"name="photo"; filename="latency.png""#));
+    assert!(photo.contains("file contents"));
+    assert!(photo.contains("About latency.png"));
+    assert!(calls[2]
+        .1
+        .contains(r#"name="document"; filename="pods.log""#));
+    assert!(calls[3]
+        .1
+        .contains(r#"name="video"; filename="replay.mp4""#));
+    assert!(calls[4].1.contains(r#"name="audio"; filename="page.ogg""#));
+}
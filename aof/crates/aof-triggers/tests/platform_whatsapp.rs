@@ -1,8 +1,16 @@
 //! WhatsApp platform tests
 
 use aof_triggers::platforms::whatsapp::{WhatsAppPlatform, WhatsAppConfig};
-use aof_triggers::platforms::TriggerPlatform;
+use aof_triggers::platforms::{PlatformError, TriggerPlatform};
+use aof_triggers::response::{Action, ActionStyle, TriggerResponse};
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::routing::post;
+use axum::{Json, Router};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 
 fn test_config() -> WhatsAppConfig {
     WhatsAppConfig {
@@ -13,9 +21,57 @@ fn test_config() -> WhatsAppConfig {
         business_account_id: None,
         allowed_numbers: None,
         api_version: "v18.0".to_string(),
+        api_base_url: "https://graph.facebook.com".to_string(),
     }
 }
 
+/// Signature header for `payload` as Meta sends it
+fn signed_headers(payload: &str) -> HashMap<String, String> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(b"test_app_secret").unwrap();
+    mac.update(payload.as_bytes());
+
+    let mut headers = HashMap::new();
+    headers.insert(
+        "x-hub-signature-256".to_string(),
+        format!("sha256={}", hex::encode(mac.finalize().into_bytes())),
+    );
+    headers
+}
+
+/// Requests captured by the mock Graph API: (path, authorization, body)
+type Captured = Arc<Mutex<Vec<(String, String, serde_json::Value)>>>;
+
+/// Serve a mock `/{version}/{phone_number_id}/messages` endpoint and return
+/// a platform pointed at it
+async fn mock_graph_api() -> (WhatsAppPlatform, Captured) {
+    async fn messages(
+        State(captured): State<Captured>,
+        uri: axum::http::Uri,
+        headers: HeaderMap,
+        Json(body): Json<serde_json::Value>,
+    ) -> Json<serde_json::Value> {
+        let authorization = headers["authorization"].to_str().unwrap().to_string();
+        let mut captured = captured.lock().unwrap();
+        captured.push((uri.path().to_string(), authorization, body));
+        Json(serde_json::json!({
+            "messaging_product": "whatsapp",
+            "messages": [{ "id": format!("wamid.sent{}", captured.len()) }]
+        }))
+    }
+
+    let captured = Captured::default();
+    let app = Router::new()
+        .route("/:version/:phone_number_id/messages", post(messages))
+        .with_state(captured.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let mut config = test_config();
+    config.api_base_url = format!("http://{}", addr);
+    (WhatsAppPlatform::new(config).unwrap(), captured)
+}
+
 #[tokio::test]
 async fn test_whatsapp_platform_creation() {
     let platform = WhatsAppPlatform::new(test_config());
@@ -67,20 +123,79 @@ async fn test_whatsapp_parse_text_message() {
     }"#;
 
     let platform = WhatsAppPlatform::new(test_config()).unwrap();
-    let headers = HashMap::new();
+    let headers = signed_headers(payload);
     let message = platform.parse_message(payload.as_bytes(), &headers).await.unwrap();
 
     assert_eq!(message.platform, "whatsapp");
     assert_eq!(message.user.id, "15559876543");
     assert_eq!(message.channel_id, "15559876543");
     assert_eq!(message.text, "/run agent test-bot hello");
+    assert_eq!(message.timestamp.timestamp(), 1638360000);
+}
+
+#[tokio::test]
+async fn test_whatsapp_parse_button_reply() {
+    let payload = r#"{
+        "object": "whatsapp_business_account",
+        "entry": [{
+            "id": "123456789",
+            "changes": [{
+                "value": {
+                    "messaging_product": "whatsapp",
+                    "metadata": {
+                        "display_phone_number": "15551234567",
+                        "phone_number_id": "123456789012345"
+                    },
+                    "contacts": [
+                        {"profile": {"name": "Someone Else"}, "wa_id": "15550000000"},
+                        {"profile": {"name": "Jane Doe"}, "wa_id": "15559876543"}
+                    ],
+                    "messages": [{
+                        "context": {"from": "15551234567", "id": "wamid.original"},
+                        "id": "wamid.reply",
+                        "from": "15559876543",
+                        "timestamp": "1638360100",
+                        "type": "interactive",
+                        "interactive": {
+                            "type": "button_reply",
+                            "button_reply": {"id": "approve", "title": "Approve"}
+                        }
+                    }]
+                },
+                "field": "messages"
+            }]
+        }]
+    }"#;
+
+    let platform = WhatsAppPlatform::new(test_config()).unwrap();
+    let headers = signed_headers(payload);
+    let message = platform.parse_message(payload.as_bytes(), &headers).await.unwrap();
+
+    assert_eq!(message.id, "wamid.reply");
+    assert_eq!(message.text, "button:approve");
+    assert_eq!(message.user.display_name.as_deref(), Some("Jane Doe"));
+    assert_eq!(message.reply_to.as_deref(), Some("wamid.original"));
+}
+
+#[tokio::test]
+async fn test_whatsapp_rejects_unsigned_or_tampered_payload() {
+    let payload = r#"{"object": "whatsapp_business_account", "entry": []}"#;
+    let platform = WhatsAppPlatform::new(test_config()).unwrap();
+
+    let result = platform.parse_message(payload.as_bytes(), &HashMap::new()).await;
+    assert!(matches!(result, Err(PlatformError::InvalidSignature(_))));
+
+    let headers = signed_headers(payload);
+    let tampered = payload.replace("[]", "[{}]");
+    let result = platform.parse_message(tampered.as_bytes(), &headers).await;
+    assert!(matches!(result, Err(PlatformError::InvalidSignature(_))));
 }
 
 #[tokio::test]
 async fn test_whatsapp_parse_missing_entry() {
     let payload = r#"{"object": "whatsapp_business_account", "entry": []}"#;
     let platform = WhatsAppPlatform::new(test_config()).unwrap();
-    let headers = HashMap::new();
+    let headers = signed_headers(payload);
     let result = platform.parse_message(payload.as_bytes(), &headers).await;
 
     assert!(result.is_err());
@@ -90,8 +205,99 @@ async fn test_whatsapp_parse_missing_entry() {
 async fn test_whatsapp_parse_invalid_json() {
     let payload = "invalid json";
     let platform = WhatsAppPlatform::new(test_config()).unwrap();
-    let headers = HashMap::new();
+    let headers = signed_headers(payload);
     let result = platform.parse_message(payload.as_bytes(), &headers).await;
 
     assert!(result.is_err());
 }
+
+#[tokio::test]
+async fn test_whatsapp_send_text_response() {
+    let (platform, captured) = mock_graph_api().await;
+
+    let mut response = TriggerResponse::text("Agent finished");
+    response.reply_to = Some("wamid.original".to_string());
+    platform.send_response("15559876543", response).await.unwrap();
+
+    let captured = captured.lock().unwrap();
+    assert_eq!(captured.len(), 1);
+    let (path, authorization, body) = &captured[0];
+    assert_eq!(path, "/v18.0/123456789012345/messages");
+    assert_eq!(authorization, "Bearer EAABcDtest");
+    assert_eq!(
+        body,
+        &serde_json::json!({
+            "messaging_product": "whatsapp",
+            "recipient_type": "individual",
+            "to": "15559876543",
+            "type": "text",
+            "text": {"preview_url": false, "body": "ℹ️ Agent finished"},
+            "context": {"message_id": "wamid.original"}
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_whatsapp_send_interactive_response() {
+    let (platform, captured) = mock_graph_api().await;
+
+    let action = |id: &str| Action {
+        id: id.to_string(),
+        label: format!("Option {}", id),
+        value: id.to_string(),
+        style: ActionStyle::Primary,
+    };
+
+    let mut buttons = TriggerResponse::text("Approve deployment?");
+    buttons.actions = vec![action("yes"), action("no")];
+    platform.send_response("15559876543", buttons).await.unwrap();
+
+    let mut list = TriggerResponse::text("Pick an agent");
+    list.actions = (1..=5).map(|i| action(&i.to_string())).collect();
+    platform.send_response("15559876543", list).await.unwrap();
+
+    let captured = captured.lock().unwrap();
+    assert_eq!(captured.len(), 2);
+
+    let interactive = &captured[0].2["interactive"];
+    assert_eq!(interactive["type"], "button");
+    assert_eq!(interactive["body"]["text"], "ℹ️ Approve deployment?");
+    assert_eq!(interactive["action"]["buttons"][1]["reply"]["id"], "no");
+    assert_eq!(interactive["action"]["buttons"][1]["reply"]["title"], "Option no");
+
+    let interactive = &captured[1].2["interactive"];
+    assert_eq!(interactive["type"], "list");
+    let rows = interactive["action"]["sections"][0]["rows"].as_array().unwrap();
+    assert_eq!(rows.len(), 5);
+    assert_eq!(rows[4]["id"], "5");
+}
+
+#[tokio::test]
+async fn test_whatsapp_send_surfaces_api_error() {
+    let app = Router::new().route(
+        "/:version/:phone_number_id/messages",
+        post(|| async {
+            (
+                axum::http::StatusCode::UNAUTHORIZED,
+                Json(serde_json::json!({
+                    "error": {"message": "Invalid OAuth access token", "code": 190}
+                })),
+            )
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let mut config = test_config();
+    config.api_base_url = format!("http://{}", addr);
+    let platform = WhatsAppPlatform::new(config).unwrap();
+
+    let result = platform
+        .send_response("15559876543", TriggerResponse::text("hi"))
+        .await;
+    match result {
+        Err(PlatformError::ApiError(message)) => assert_eq!(message, "Invalid OAuth access token"),
+        other => panic!("expected API error, got {:?}", other),
+    }
+}
@@ -1,4 +1,4 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 
 use crate::commands;
 
@@ -14,21 +14,7 @@ pub struct Cli {
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     /// Run an agent with configuration (verb-first: run agent)
-    Run {
-        /// Resource type (agent, workflow, job)
-        resource_type: String,
-
-        /// Resource name or configuration file
-        name_or_config: String,
-
-        /// Input/query for the agent
-        #[arg(short, long)]
-        input: Option<String>,
-
-        /// Output format (json, yaml, text)
-        #[arg(short, long, default_value = "text")]
-        output: String,
-    },
+    Run(Box<RunArgs>),
 
     /// Get resources (verb-first: get agents, get agent <name>)
     Get {
@@ -117,41 +103,180 @@ pub enum Commands {
     /// List available API resources
     ApiResources,
 
-    /// List MCP tools (legacy command, use 'get mcptools' instead)
-    #[command(hide = true)]
+    /// List an agent's tools (tools list config.yaml)
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
     Tools {
-        /// MCP server command
-        #[arg(long)]
-        server: String,
+        #[command(subcommand)]
+        command: Option<ToolsCommands>,
+
+        /// MCP server command (legacy, use 'get mcptools' instead)
+        #[arg(long, required = true, hide = true)]
+        server: Option<String>,
 
         /// Server arguments
-        #[arg(long)]
+        #[arg(long, hide = true)]
         args: Vec<String>,
     },
 
-    /// Validate agent configuration (legacy command, use 'apply --dry-run' instead)
-    #[command(hide = true)]
+    /// Validate agent configuration and report every problem found
     Validate {
         /// Configuration file
+        #[arg(required_unless_present = "file", conflicts_with = "file")]
+        config: Option<String>,
+
+        /// Configuration file (alternative to the positional argument)
         #[arg(short, long)]
-        file: String,
+        file: Option<String>,
+    },
+
+    /// Print the JSON Schema for a resource's YAML (verb-first: schema agent)
+    Schema {
+        /// Resource type (agent)
+        resource_type: String,
     },
 
     /// Show version information
     Version,
 }
 
+/// Arguments of `aofctl run`
+#[derive(Args, Debug)]
+pub struct RunArgs {
+    /// Resource type (agent, workflow, job)
+    pub resource_type: String,
+
+    /// Resource name or configuration file
+    pub name_or_config: String,
+
+    /// Pass `-` to read the input from stdin
+    #[arg(value_parser = ["-"], conflicts_with_all = ["input", "input_file"])]
+    pub stdin: Option<String>,
+
+    /// Input/query for the agent
+    #[arg(short, long, conflicts_with = "input_file")]
+    pub input: Option<String>,
+
+    /// Read the input/query from this file
+    #[arg(long)]
+    pub input_file: Option<String>,
+
+    /// Output format (json, yaml, text)
+    #[arg(short, long, default_value = "text")]
+    pub output: String,
+
+    /// Write the full execution event transcript to this file
+    #[arg(long)]
+    pub transcript: Option<String>,
+
+    /// Transcript format (json, jsonl, text)
+    #[arg(long, default_value = "jsonl", value_parser = ["json", "jsonl", "text"])]
+    pub transcript_format: String,
+
+    /// Override the agent's model for this run (e.g. openai:gpt-4o)
+    #[arg(long)]
+    pub model: Option<String>,
+
+    /// Override the agent's sampling temperature for this run
+    #[arg(long)]
+    pub temperature: Option<f32>,
+
+    /// Override the agent's iteration limit for this run
+    #[arg(long)]
+    pub max_iterations: Option<usize>,
+
+    /// Override the agent's response token limit for this run
+    #[arg(long)]
+    pub max_tokens: Option<usize>,
+
+    /// Run the agent once per .txt/.json file in this directory
+    #[arg(
+        long,
+        requires = "out",
+        conflicts_with_all = ["stdin", "input", "input_file", "transcript"]
+    )]
+    pub batch_dir: Option<String>,
+
+    /// Directory for the batch results, one <name>.json per input file
+    #[arg(long, requires = "batch_dir")]
+    pub out: Option<String>,
+
+    /// Batch inputs run at the same time
+    #[arg(long, requires = "batch_dir")]
+    pub concurrency: Option<usize>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ToolsCommands {
+    /// List the tools an agent has access to, without running it
+    List {
+        /// Agent configuration file
+        config: String,
+
+        /// Output format (json, text)
+        #[arg(short, long, default_value = "text", value_parser = ["json", "text"])]
+        output: String,
+    },
+}
+
 impl Cli {
     pub async fn execute(self) -> anyhow::Result<()> {
         match self.command {
-            Commands::Run {
-                resource_type,
-                name_or_config,
-                input,
-                output,
-            } => {
-                commands::run::execute(&resource_type, &name_or_config, input.as_deref(), &output)
-                    .await
+            Commands::Run(args) => {
+                let RunArgs {
+                    resource_type,
+                    name_or_config,
+                    stdin,
+                    input,
+                    input_file,
+                    output,
+                    transcript,
+                    transcript_format,
+                    model,
+                    temperature,
+                    max_iterations,
+                    max_tokens,
+                    batch_dir,
+                    out,
+                    concurrency,
+                } = *args;
+                let overrides = commands::run::AgentOverrides {
+                    model,
+                    temperature,
+                    max_iterations,
+                    max_tokens,
+                };
+                if let (Some(dir), Some(out)) = (batch_dir, out) {
+                    let batch = commands::run::BatchOptions {
+                        dir,
+                        out,
+                        concurrency: concurrency
+                            .unwrap_or(commands::run::DEFAULT_BATCH_CONCURRENCY),
+                    };
+                    return commands::run::execute_batch(
+                        &resource_type,
+                        &name_or_config,
+                        &batch,
+                        &overrides,
+                    )
+                    .await;
+                }
+
+                let input = match (stdin, input, input_file) {
+                    (Some(_), _, _) => Some(commands::run::InputSource::Stdin),
+                    (_, Some(text), _) => Some(commands::run::InputSource::Text(text)),
+                    (_, _, Some(path)) => Some(commands::run::InputSource::File(path)),
+                    _ => None,
+                };
+                commands::run::execute(
+                    &resource_type,
+                    &name_or_config,
+                    input,
+                    &output,
+                    transcript.as_deref(),
+                    &transcript_format,
+                    &overrides,
+                )
+                .await
             }
             Commands::Get {
                 resource_type,
@@ -190,8 +315,21 @@ impl Cli {
                 command,
             } => commands::exec::execute(&resource_type, &name, command).await,
             Commands::ApiResources => commands::api_resources::execute().await,
-            Commands::Tools { server, args } => commands::tools::execute(&server, &args).await,
-            Commands::Validate { file } => commands::validate::execute(&file).await,
+            Commands::Tools {
+                command,
+                server,
+                args,
+            } => match command {
+                Some(ToolsCommands::List { config, output }) => {
+                    commands::tools::list(&config, &output).await
+                }
+                None => commands::tools::execute(&server.unwrap_or_default(), &args).await,
+            },
+            Commands::Validate { config, file } => {
+                let file = config.or(file).unwrap_or_default();
+                commands::validate::execute(&file).await
+            }
+            Commands::Schema { resource_type } => commands::schema::execute(&resource_type).await,
             Commands::Version => commands::version::execute().await,
         }
     }
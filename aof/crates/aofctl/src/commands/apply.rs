@@ -16,7 +16,7 @@ pub async fn execute(file: &str, namespace: Option<&str>) -> Result<()> {
         .with_context(|| format!("Failed to read config file: {}", file))?;
 
     // Try to parse as agent config (we'll support more resource types in the future)
-    match serde_yaml::from_str::<AgentConfig>(&config_content) {
+    match AgentConfig::from_yaml(&config_content) {
         Ok(agent_config) => {
             apply_agent_config(agent_config, namespace).await?;
         }
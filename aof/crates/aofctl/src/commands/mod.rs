@@ -8,4 +8,5 @@ pub mod exec;
 pub mod api_resources;
 pub mod tools;
 pub mod validate;
+pub mod schema;
 pub mod version;
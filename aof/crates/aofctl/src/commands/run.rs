@@ -1,8 +1,11 @@
 use anyhow::{Context, Result};
-use aof_core::AgentConfig;
-use aof_runtime::Runtime;
+use aof_core::{render_step_input, AgentContext, ExecutionMetadata, Memory, AgentConfig, OnError, Validate, WorkflowConfig, WorkflowStep};
+use aof_runtime::{
+    AgentExecutor, Runtime, RuntimeOrchestrator, StreamEvent, Task, TaskOutput, CONVERSATION_KEY,
+};
 use std::fs;
-use std::io::{self, IsTerminal, Write};
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::sync::mpsc::{channel, Receiver, Sender};
 use tokio::sync::mpsc as tokio_mpsc;
@@ -45,19 +48,142 @@ impl Write for LogWriter {
     }
 }
 
+/// Agent config values replaced for a single run from the command line
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AgentOverrides {
+    /// Model (`--model`)
+    pub model: Option<String>,
+
+    /// Sampling temperature (`--temperature`)
+    pub temperature: Option<f32>,
+
+    /// Iteration limit (`--max-iterations`)
+    pub max_iterations: Option<usize>,
+
+    /// Response token limit (`--max-tokens`)
+    pub max_tokens: Option<usize>,
+}
+
+impl AgentOverrides {
+    /// Whether no value is overridden
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Replace the overridden values in `config`, logging each change, and
+    /// validate the merged config
+    pub fn apply(&self, config: &mut AgentConfig) -> Result<()> {
+        if let Some(model) = &self.model {
+            info!("Overriding model: {} -> {}", config.model, model);
+            config.model = model.clone();
+        }
+        if let Some(temperature) = self.temperature {
+            info!(
+                "Overriding temperature: {} -> {}",
+                config.temperature, temperature
+            );
+            config.temperature = temperature;
+        }
+        if let Some(max_iterations) = self.max_iterations {
+            info!(
+                "Overriding max_iterations: {} -> {}",
+                config.max_iterations, max_iterations
+            );
+            config.max_iterations = max_iterations;
+        }
+        if let Some(max_tokens) = self.max_tokens {
+            info!(
+                "Overriding max_tokens: {:?} -> {}",
+                config.max_tokens, max_tokens
+            );
+            config.max_tokens = Some(max_tokens);
+        }
+
+        if !self.is_empty() {
+            config
+                .validate()
+                .map_err(aof_core::AofError::from)
+                .context("Invalid agent config after command-line overrides")?;
+        }
+        Ok(())
+    }
+}
+
+/// Where a run reads its input from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputSource {
+    /// Given on the command line (`--input`)
+    Text(String),
+
+    /// Contents of a file (`--input-file`)
+    File(String),
+
+    /// Everything piped to stdin (`-`)
+    Stdin,
+}
+
+impl InputSource {
+    /// Read the input
+    ///
+    /// Files and stdin are read straight into the prompt buffer; the model
+    /// needs the whole prompt at once, so it can't be streamed further.
+    /// Invalid UTF-8 (e.g. in piped logs) is replaced rather than rejected.
+    pub fn read(self) -> Result<String> {
+        let bytes = match self {
+            InputSource::Text(text) => return Ok(text),
+            InputSource::File(path) => {
+                fs::read(&path).with_context(|| format!("Failed to read input file: {}", path))?
+            }
+            InputSource::Stdin => {
+                let mut bytes = Vec::new();
+                io::stdin()
+                    .lock()
+                    .read_to_end(&mut bytes)
+                    .context("Failed to read input from stdin")?;
+                bytes
+            }
+        };
+
+        Ok(String::from_utf8(bytes)
+            .unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).into_owned()))
+    }
+}
+
 /// Execute a resource (agent, workflow, job) with configuration and input
 pub async fn execute(
     resource_type: &str,
     name_or_config: &str,
-    input: Option<&str>,
+    input: Option<InputSource>,
     output: &str,
+    transcript: Option<&str>,
+    transcript_format: &str,
+    overrides: &AgentOverrides,
 ) -> Result<()> {
     // Parse resource type
     let rt = ResourceType::from_str(resource_type)
         .ok_or_else(|| anyhow::anyhow!("Unknown resource type: {}", resource_type))?;
 
+    if rt != ResourceType::Agent && !overrides.is_empty() {
+        anyhow::bail!(
+            "--model, --temperature, --max-iterations and --max-tokens only apply to agents"
+        );
+    }
+
+    let input = input.map(InputSource::read).transpose()?;
+    let input = input.as_deref();
+
     match rt {
-        ResourceType::Agent => run_agent(name_or_config, input, output).await,
+        ResourceType::Agent => {
+            run_agent(
+                name_or_config,
+                input,
+                output,
+                transcript,
+                transcript_format,
+                overrides,
+            )
+            .await
+        }
         ResourceType::Workflow => run_workflow(name_or_config, input, output).await,
         ResourceType::Job => run_job(name_or_config, input, output).await,
         _ => {
@@ -66,18 +192,32 @@ pub async fn execute(
     }
 }
 
+/// Load an agent config file and apply the command-line overrides
+fn load_agent_config(path: &str, overrides: &AgentOverrides) -> Result<AgentConfig> {
+    let config_content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file: {}", path))?;
+
+    let mut agent_config = AgentConfig::from_yaml(&config_content)
+        .with_context(|| format!("Failed to parse agent config from: {}", path))?;
+    overrides.apply(&mut agent_config)?;
+    Ok(agent_config)
+}
+
 /// Run an agent with configuration
-async fn run_agent(config: &str, input: Option<&str>, output: &str) -> Result<()> {
+async fn run_agent(
+    config: &str,
+    input: Option<&str>,
+    output: &str,
+    transcript: Option<&str>,
+    transcript_format: &str,
+    overrides: &AgentOverrides,
+) -> Result<()> {
     // Check if interactive mode should be enabled (when no input provided and stdin is a TTY)
     let interactive = input.is_none() && io::stdin().is_terminal();
 
     if interactive {
         // Load agent configuration
-        let config_content = fs::read_to_string(config)
-            .with_context(|| format!("Failed to read config file: {}", config))?;
-
-        let agent_config: AgentConfig = serde_yaml::from_str(&config_content)
-            .with_context(|| format!("Failed to parse agent config from: {}", config))?;
+        let agent_config = load_agent_config(config, overrides)?;
 
         let agent_name = agent_config.name.clone();
 
@@ -96,11 +236,7 @@ async fn run_agent(config: &str, input: Option<&str>, output: &str) -> Result<()
     // Non-interactive mode: normal logging to console
     info!("Loading agent config from: {}", config);
 
-    let config_content = fs::read_to_string(config)
-        .with_context(|| format!("Failed to read config file: {}", config))?;
-
-    let agent_config: AgentConfig = serde_yaml::from_str(&config_content)
-        .with_context(|| format!("Failed to parse agent config from: {}", config))?;
+    let agent_config = load_agent_config(config, overrides)?;
 
     let agent_name = agent_config.name.clone();
     info!("Agent loaded: {}", agent_name);
@@ -114,10 +250,14 @@ async fn run_agent(config: &str, input: Option<&str>, output: &str) -> Result<()
 
     // Single execution mode
     let input_str = input.unwrap_or("default input");
-    let result = runtime
-        .execute(&agent_name, input_str)
-        .await
-        .context("Failed to execute agent")?;
+    let result = match transcript {
+        Some(path) => {
+            execute_with_transcript(&runtime, &agent_name, input_str, path, transcript_format)
+                .await
+        }
+        None => runtime.execute(&agent_name, input_str).await,
+    }
+    .context("Failed to execute agent")?;
 
     // Output result in requested format
     output_result(&agent_name, &result, output)?;
@@ -125,6 +265,294 @@ async fn run_agent(config: &str, input: Option<&str>, output: &str) -> Result<()
     Ok(())
 }
 
+/// Batch inputs run at the same time without `--concurrency`
+pub const DEFAULT_BATCH_CONCURRENCY: usize = 4;
+
+/// Agent run over a directory of input files (`--batch-dir`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchOptions {
+    /// Directory of `.txt`/`.json` input files
+    pub dir: String,
+
+    /// Directory receiving one `<name>.json` result per input (`--out`)
+    pub out: String,
+
+    /// Inputs run at the same time (`--concurrency`)
+    pub concurrency: usize,
+}
+
+/// Run an agent once per input file of a directory
+///
+/// Inputs run through the runtime orchestrator, at most `concurrency` at a
+/// time, each in a conversation session of its own. Every input gets a
+/// result file, failed ones with their error, and the batch only reports
+/// failure once all inputs have run.
+pub async fn execute_batch(
+    resource_type: &str,
+    config: &str,
+    batch: &BatchOptions,
+    overrides: &AgentOverrides,
+) -> Result<()> {
+    if ResourceType::from_str(resource_type) != Some(ResourceType::Agent) {
+        anyhow::bail!("--batch-dir only applies to agents");
+    }
+    if batch.concurrency == 0 {
+        anyhow::bail!("--concurrency must be at least 1");
+    }
+
+    let inputs = batch_inputs(Path::new(&batch.dir))?;
+    if inputs.is_empty() {
+        anyhow::bail!("No .txt or .json input files in: {}", batch.dir);
+    }
+    let out_dir = PathBuf::from(&batch.out);
+    fs::create_dir_all(&out_dir)
+        .with_context(|| format!("Failed to create output directory: {}", batch.out))?;
+
+    let agent_config = load_agent_config(config, overrides)?;
+    let agent_name = agent_config.name.clone();
+    let mut runtime = Runtime::new();
+    runtime
+        .load_agent_from_config(agent_config)
+        .await
+        .context("Failed to load agent")?;
+    let executor = runtime
+        .get_agent(&agent_name)
+        .ok_or_else(|| anyhow::anyhow!("Agent not found: {}", agent_name))?;
+
+    info!(
+        "Running {} batch inputs from {} ({} at a time)",
+        inputs.len(),
+        batch.dir,
+        batch.concurrency
+    );
+    let orchestrator = RuntimeOrchestrator::with_max_concurrent(batch.concurrency);
+    let mut handles = Vec::with_capacity(inputs.len());
+    for (name, path) in inputs {
+        let task = Task::new(
+            format!("batch-{}", name),
+            name.clone(),
+            agent_name.clone(),
+            path.display().to_string(),
+        );
+        let task_id = task.id.clone();
+        orchestrator.submit_task(task);
+
+        let executor = Arc::clone(&executor);
+        let result_path = out_dir.join(format!("{}.json", name));
+        let handle = orchestrator
+            .execute_task(&task_id, move |task, _progress| async move {
+                run_batch_item(&executor, &task.name, &path, &result_path).await
+            })
+            .await?;
+        handles.push((name, handle));
+    }
+
+    let total = handles.len();
+    let mut failed = 0;
+    for (name, handle) in handles {
+        let result = handle.wait().await?;
+        if !result.success {
+            failed += 1;
+            eprintln!(
+                "Batch input {} failed: {}",
+                name,
+                result.error.unwrap_or_default()
+            );
+        }
+    }
+
+    println!(
+        "Batch finished: {} succeeded, {} failed, results in {}",
+        total - failed,
+        failed,
+        batch.out
+    );
+    if failed > 0 {
+        anyhow::bail!("{} of {} batch inputs failed", failed, total);
+    }
+    Ok(())
+}
+
+/// `.txt` and `.json` files of a batch directory with their names, sorted
+fn batch_inputs(dir: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let entries = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read batch directory: {}", dir.display()))?;
+
+    let mut inputs = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        let is_input = path.is_file()
+            && matches!(
+                path.extension().and_then(|ext| ext.to_str()),
+                Some("txt" | "json")
+            );
+        if !is_input {
+            continue;
+        }
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        inputs.push((name, path));
+    }
+    inputs.sort();
+
+    if let Some(pair) = inputs.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+        anyhow::bail!(
+            "Batch inputs {} and {} would share the result file {}.json",
+            pair[0].1.display(),
+            pair[1].1.display(),
+            pair[0].0
+        );
+    }
+    Ok(inputs)
+}
+
+/// Run one batch input and write its result file
+///
+/// The result file records the response or error along with the run's
+/// metadata (tokens, tool calls, timing).
+async fn run_batch_item(
+    executor: &AgentExecutor,
+    name: &str,
+    input_path: &Path,
+    result_path: &Path,
+) -> aof_core::AofResult<TaskOutput> {
+    let mut context = AgentContext::new(String::new()).with_session_id(format!("batch:{}", name));
+    let result = match InputSource::File(input_path.display().to_string()).read() {
+        Ok(input) => {
+            context.input = input;
+            executor
+                .execute(&mut context)
+                .await
+                .map_err(|e| e.to_string())
+        }
+        Err(e) => Err(format!("{:#}", e)),
+    };
+
+    let report = match &result {
+        Ok(response) => serde_json::json!({
+            "input": input_path.display().to_string(),
+            "success": true,
+            "result": response,
+            "metadata": context.metadata,
+        }),
+        Err(error) => serde_json::json!({
+            "input": input_path.display().to_string(),
+            "success": false,
+            "error": error,
+            "metadata": context.metadata,
+        }),
+    };
+    let content = serde_json::to_string_pretty(&report)?;
+    fs::write(result_path, content + "\n").map_err(|e| {
+        aof_core::AofError::agent(format!(
+            "Failed to write batch result {}: {}",
+            result_path.display(),
+            e
+        ))
+    })?;
+
+    match result {
+        Ok(response) => Ok(TaskOutput::from_metadata(response, &context.metadata)),
+        Err(error) => Err(aof_core::AofError::agent(error)),
+    }
+}
+
+/// Execute an agent through the streaming executor, recording every event
+///
+/// The transcript is written even when execution fails so the events
+/// leading up to the failure can be inspected.
+async fn execute_with_transcript(
+    runtime: &Runtime,
+    agent_name: &str,
+    input: &str,
+    path: &str,
+    format: &str,
+) -> aof_core::AofResult<String> {
+    let (tx, mut rx) = tokio_mpsc::channel::<StreamEvent>(100);
+
+    let collector = tokio::spawn(async move {
+        let mut events = Vec::new();
+        while let Some(event) = rx.recv().await {
+            events.push(event);
+        }
+        events
+    });
+
+    let result = runtime.execute_streaming(agent_name, input, tx).await;
+    let events = collector.await.unwrap_or_default();
+
+    if let Err(e) = write_transcript(path, format, &events) {
+        eprintln!("Failed to write transcript to {}: {:#}", path, e);
+    } else {
+        info!("Transcript with {} events written to: {}", events.len(), path);
+    }
+
+    result
+}
+
+/// Write stream events to a transcript file
+fn write_transcript(path: &str, format: &str, events: &[StreamEvent]) -> Result<()> {
+    let content = match format {
+        "json" => serde_json::to_string_pretty(events)?,
+        "text" => events
+            .iter()
+            .map(format_transcript_event)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => events
+            .iter()
+            .map(serde_json::to_string)
+            .collect::<std::result::Result<Vec<_>, _>>()?
+            .join("\n"),
+    };
+
+    fs::write(path, content + "\n")
+        .with_context(|| format!("Failed to write transcript file: {}", path))
+}
+
+/// Render a single stream event as a human-readable transcript line
+fn format_transcript_event(event: &StreamEvent) -> String {
+    match event {
+        StreamEvent::TextDelta { delta, .. } => format!("[text] {}", delta),
+        StreamEvent::ToolCallStart { tool_name, tool_id, arguments } => {
+            format!("[tool_call_start] {} ({}) {}", tool_name, tool_id, arguments)
+        }
+        StreamEvent::ToolCallComplete { tool_name, tool_id, success, execution_time_ms, attempts, error } => {
+            let status = if *success { "ok" } else { "failed" };
+            let retries = if *attempts > 1 {
+                format!(" after {} attempts", attempts)
+            } else {
+                String::new()
+            };
+            match error {
+                Some(error) => format!(
+                    "[tool_call_complete] {} ({}) {} in {}ms{}: {}",
+                    tool_name, tool_id, status, execution_time_ms, retries, error
+                ),
+                None => format!(
+                    "[tool_call_complete] {} ({}) {} in {}ms{}",
+                    tool_name, tool_id, status, execution_time_ms, retries
+                ),
+            }
+        }
+        StreamEvent::Thinking { content } => format!("[thinking] {}", content),
+        StreamEvent::IterationStart { iteration, max_iterations } => {
+            format!("[iteration_start] {}/{}", iteration, max_iterations)
+        }
+        StreamEvent::IterationComplete { iteration, stop_reason } => {
+            format!("[iteration_complete] {} ({:?})", iteration, stop_reason)
+        }
+        StreamEvent::Done { content, total_iterations, execution_time_ms, input_tokens, output_tokens, trace_id } => format!(
+            "[done] {} iterations, {}ms, {} input / {} output tokens (trace {})\n{}",
+            total_iterations, execution_time_ms, input_tokens, output_tokens, trace_id, content
+        ),
+        StreamEvent::Warning { message } => format!("[warning] {}", message),
+        StreamEvent::Error { message } => format!("[error] {}", message),
+    }
+}
+
 /// Application state for TUI
 struct AppState {
     chat_history: Vec<(String, String)>, // (role, message)
@@ -147,19 +575,15 @@ struct AppState {
 }
 
 impl AppState {
-    fn new(log_receiver: Receiver<String>, model_name: String, tools: Vec<String>) -> Self {
+    fn new(
+        log_receiver: Receiver<String>,
+        model_name: String,
+        context_window: u32,
+        tools: Vec<String>,
+    ) -> Self {
         let (tx, rx) = tokio_mpsc::channel(1);
         let _ = tx; // Drop sender since we only use the receiver
 
-        // Set context window based on model
-        let context_window = match model_name.as_str() {
-            "google:gemini-2.5-flash" => 1000000, // 1M tokens
-            "google:gemini-2.0-flash" => 1000000,
-            "openai:gpt-4-turbo" => 128000,
-            "openai:gpt-4" => 8192,
-            _ => 128000, // default
-        };
-
         // Create greeting message with ASCII art
         let greeting = r#"
 ████╗  ████╗ ████████╗
@@ -227,10 +651,10 @@ aof.sh"#;
         }
     }
 
-    fn update_token_count(&mut self, text: &str) {
-        // Rough estimate: ~4 characters per token
-        let estimated_tokens = (text.len() / 4) as u32;
-        self.output_tokens = self.output_tokens.saturating_add(estimated_tokens);
+    /// Add the provider-reported token usage of one execution
+    fn record_usage(&mut self, metadata: &ExecutionMetadata) {
+        self.input_tokens = self.input_tokens.saturating_add(metadata.input_tokens as u32);
+        self.output_tokens = self.output_tokens.saturating_add(metadata.output_tokens as u32);
     }
 
     fn scroll_up(&mut self, amount: u16) {
@@ -244,6 +668,135 @@ aof.sh"#;
     fn auto_scroll_to_bottom(&mut self) {
         self.chat_scroll_offset = 0;
     }
+
+    /// Reset the chat panel and counters for a fresh session
+    fn clear_session(&mut self) {
+        self.chat_history.clear();
+        self.input_tokens = 0;
+        self.output_tokens = 0;
+        self.message_count = 0;
+        self.last_error = None;
+        self.chat_scroll_offset = 0;
+    }
+
+    /// Token totals for the session
+    fn token_summary(&self) -> String {
+        let total = self.input_tokens + self.output_tokens;
+        let percentage = if self.context_window > 0 {
+            (total as f64 / self.context_window as f64) * 100.0
+        } else {
+            0.0
+        };
+
+        format!(
+            "Tokens - input: {} | output: {} | total: {} / {} ({:.1}% of context window)",
+            self.input_tokens, self.output_tokens, total, self.context_window, percentage
+        )
+    }
+}
+
+/// In-session commands available in interactive mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SlashCommand {
+    /// List the agent's tools
+    Tools,
+    /// Show the stored conversation
+    History,
+    /// Reset the chat and the agent's conversation memory
+    Clear,
+    /// Show token totals
+    Tokens,
+}
+
+impl SlashCommand {
+    /// Parse a slash command from user input
+    fn parse(input: &str) -> Option<Self> {
+        match input.trim().to_lowercase().as_str() {
+            "/tools" => Some(Self::Tools),
+            "/history" => Some(Self::History),
+            "/clear" => Some(Self::Clear),
+            "/tokens" => Some(Self::Tokens),
+            _ => None,
+        }
+    }
+}
+
+/// Execute a slash command and return the text to show in the chat panel
+async fn run_slash_command(
+    command: SlashCommand,
+    runtime: &Runtime,
+    agent_name: &str,
+    app_state: &mut AppState,
+) -> String {
+    let agent = runtime.get_agent(agent_name);
+
+    match command {
+        SlashCommand::Tools => {
+            let tools = agent
+                .as_ref()
+                .and_then(|agent| agent.tool_executor().map(|executor| executor.list_tools()))
+                .unwrap_or_default();
+            format_tool_list(&tools, &app_state.tools)
+        }
+        SlashCommand::History => {
+            let messages = match agent.as_ref().and_then(|agent| agent.memory().cloned()) {
+                Some(memory) => memory
+                    .retrieve::<Vec<aof_core::Message>>(CONVERSATION_KEY)
+                    .await
+                    .ok()
+                    .flatten()
+                    .unwrap_or_default(),
+                None => Vec::new(),
+            };
+            format_history(&messages)
+        }
+        SlashCommand::Clear => {
+            if let Some(memory) = agent.as_ref().and_then(|agent| agent.memory()) {
+                let _ = memory.clear().await;
+            }
+            app_state.clear_session();
+            "Conversation cleared".to_string()
+        }
+        SlashCommand::Tokens => app_state.token_summary(),
+    }
+}
+
+/// Format tool definitions for display, falling back to configured names
+fn format_tool_list(tools: &[aof_core::ToolDefinition], configured: &[String]) -> String {
+    if tools.is_empty() && configured.is_empty() {
+        return "No tools available".to_string();
+    }
+
+    let mut text = format!("Available tools ({}):", tools.len().max(configured.len()));
+    if tools.is_empty() {
+        for name in configured {
+            text.push_str(&format!("\n• {}", name));
+        }
+    } else {
+        for tool in tools {
+            text.push_str(&format!("\n• {} - {}", tool.name, tool.description));
+        }
+    }
+    text
+}
+
+/// Format stored conversation messages for display
+fn format_history(messages: &[aof_core::Message]) -> String {
+    if messages.is_empty() {
+        return "No conversation history".to_string();
+    }
+
+    let mut text = format!("Conversation history ({} messages):", messages.len());
+    for message in messages {
+        let role = match message.role {
+            aof_core::MessageRole::User => "user",
+            aof_core::MessageRole::Assistant => "assistant",
+            aof_core::MessageRole::System => "system",
+            aof_core::MessageRole::Tool => "tool",
+        };
+        text.push_str(&format!("\n[{}] {}", role, message.content));
+    }
+    text
 }
 
 /// Run agent in interactive REPL mode with two-column TUI
@@ -254,6 +807,11 @@ async fn run_agent_interactive(runtime: &Runtime, agent_name: &str, _output: &st
         .map(|agent| agent.config().model.clone())
         .unwrap_or_else(|| "unknown".to_string());
 
+    let context_window = runtime
+        .get_agent(agent_name)
+        .map(|agent| agent.config().context_window())
+        .unwrap_or(aof_core::DEFAULT_CONTEXT_WINDOW) as u32;
+
     let tools = runtime
         .get_agent(agent_name)
         .map(|agent| agent.config().tools.clone())
@@ -298,7 +856,7 @@ async fn run_agent_interactive(runtime: &Runtime, agent_name: &str, _output: &st
     let mut terminal = Terminal::new(backend)?;
 
     // Initialize app state with log receiver
-    let mut app_state = AppState::new(log_rx, model_name, tools);
+    let mut app_state = AppState::new(log_rx, model_name, context_window, tools);
     let should_quit = Arc::new(Mutex::new(false));
 
     // Don't add welcome message yet - it will show after greeting is dismissed
@@ -345,13 +903,15 @@ async fn run_agent_interactive(runtime: &Runtime, agent_name: &str, _output: &st
                             break;
                         } else if trimmed.to_lowercase() == "help" {
                             app_state.chat_history.push(("system".to_string(),
-                                "Available: help, exit, quit. Type normally to chat with agent.".to_string()));
+                                "Available: help, exit, quit, /tools, /history, /clear, /tokens. Type normally to chat with agent.".to_string()));
+                        } else if let Some(command) = SlashCommand::parse(trimmed) {
+                            // Session commands are answered locally and never reach the agent
+                            let reply = run_slash_command(command, runtime, agent_name, &mut app_state).await;
+                            app_state.chat_history.push(("system".to_string(), reply));
+                            app_state.auto_scroll_to_bottom();
                         } else {
                             // Execute agent with timer updates during execution
                             app_state.chat_history.push(("user".to_string(), trimmed.to_string()));
-                            // Update input tokens based on user query length
-                            let input_tokens_estimate = (trimmed.len() / 4) as u32;
-                            app_state.input_tokens = app_state.input_tokens.saturating_add(input_tokens_estimate);
                             app_state.agent_busy = true;
                             app_state.last_error = None;
                             app_state.execution_start = Some(Instant::now());
@@ -361,8 +921,9 @@ async fn run_agent_interactive(runtime: &Runtime, agent_name: &str, _output: &st
                             terminal.draw(|f| ui(f, agent_name, &app_state))?;
 
                             // Execute with periodic UI updates using select! for timer
-                            let input_str = trimmed.to_string();
-                            let mut exec_future = Box::pin(runtime.execute(agent_name, &input_str));
+                            // Own the context so the provider-reported usage can be read afterwards
+                            let mut context = AgentContext::new(trimmed);
+                            let mut exec_future = Box::pin(runtime.execute_with_context(agent_name, &mut context));
                             let mut timer_handle = tokio::time::interval(std::time::Duration::from_millis(100));
 
                             loop {
@@ -370,17 +931,10 @@ async fn run_agent_interactive(runtime: &Runtime, agent_name: &str, _output: &st
                                     result = &mut exec_future => {
                                         match result {
                                             Ok(response) => {
-                                                if response.is_empty() {
-                                                    let error_msg = "Error: Empty response from agent".to_string();
-                                                    app_state.chat_history.push(("error".to_string(), error_msg.clone()));
-                                                    app_state.last_error = Some(error_msg);
-                                                } else {
-                                                    // Update output tokens based on response length
-                                                    app_state.update_token_count(&response);
-                                                    app_state.chat_history.push(("assistant".to_string(), response));
-                                                    // Auto-scroll to latest message
-                                                    app_state.auto_scroll_to_bottom();
-                                                }
+                                                // The executor already fails runs ending in an empty answer
+                                                app_state.chat_history.push(("assistant".to_string(), response));
+                                                // Auto-scroll to latest message
+                                                app_state.auto_scroll_to_bottom();
                                             }
                                             Err(e) => {
                                                 let error_msg = format!("Error: {}", e);
@@ -405,6 +959,9 @@ async fn run_agent_interactive(runtime: &Runtime, agent_name: &str, _output: &st
                                     }
                                 }
                             }
+
+                            drop(exec_future);
+                            app_state.record_usage(&context.metadata);
                         }
 
                         app_state.current_input.clear();
@@ -724,14 +1281,177 @@ fn output_result(agent_name: &str, result: &str, output: &str) -> Result<()> {
     Ok(())
 }
 
-/// Run a workflow (placeholder)
-async fn run_workflow(name_or_config: &str, input: Option<&str>, output: &str) -> Result<()> {
-    println!("Run workflow - Not yet implemented");
-    println!("Workflow: {}", name_or_config);
-    if let Some(inp) = input {
-        println!("Input: {}", inp);
+/// Outcome of a single workflow step
+#[derive(Debug, serde::Serialize)]
+struct StepOutcome {
+    name: String,
+    agent: String,
+    success: bool,
+    output: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Run a workflow: execute each agent step in order, piping outputs forward
+async fn run_workflow(config: &str, input: Option<&str>, output: &str) -> Result<()> {
+    info!("Loading workflow config from: {}", config);
+
+    let config_content = fs::read_to_string(config)
+        .with_context(|| format!("Failed to read workflow file: {}", config))?;
+
+    let workflow: WorkflowConfig = serde_yaml::from_str(&config_content)
+        .with_context(|| format!("Failed to parse workflow config from: {}", config))?;
+    workflow
+        .validate()
+        .with_context(|| format!("Invalid workflow config: {}", config))?;
+
+    // Agent paths are resolved relative to the workflow file
+    let base_dir = Path::new(config).parent().unwrap_or_else(|| Path::new("."));
+    let input = input.unwrap_or("default input");
+
+    let mut runtime = Runtime::new();
+    let mut outputs: Vec<String> = Vec::with_capacity(workflow.steps.len());
+    let mut outcomes: Vec<StepOutcome> = Vec::with_capacity(workflow.steps.len());
+    let mut aborted = None;
+
+    for (index, step) in workflow.steps.iter().enumerate() {
+        let step_name = step.display_name(index);
+        info!("Running workflow step {}: {}", index, step_name);
+
+        let (agent, result) = run_workflow_step(&mut runtime, base_dir, step, input, &outputs).await;
+
+        match result {
+            Ok(step_output) => {
+                outputs.push(step_output.clone());
+                outcomes.push(StepOutcome {
+                    name: step_name,
+                    agent,
+                    success: true,
+                    output: step_output,
+                    error: None,
+                });
+            }
+            Err(e) => {
+                let error = format!("{:#}", e);
+                outputs.push(String::new());
+                outcomes.push(StepOutcome {
+                    name: step_name.clone(),
+                    agent,
+                    success: false,
+                    output: String::new(),
+                    error: Some(error.clone()),
+                });
+
+                if step.on_error == OnError::Abort {
+                    aborted = Some((step_name, error));
+                    break;
+                }
+            }
+        }
+    }
+
+    let result = outcomes
+        .iter()
+        .rev()
+        .find(|outcome| outcome.success)
+        .map(|outcome| outcome.output.clone())
+        .unwrap_or_default();
+
+    output_workflow_result(&workflow.name, aborted.is_none(), &outcomes, &result, output)?;
+
+    if let Some((step_name, error)) = aborted {
+        anyhow::bail!(
+            "Workflow '{}' aborted at step '{}': {}",
+            workflow.name,
+            step_name,
+            error
+        );
+    }
+
+    Ok(())
+}
+
+/// Load (if needed) and execute the agent for one workflow step
+///
+/// Returns the agent name alongside the step result.
+async fn run_workflow_step(
+    runtime: &mut Runtime,
+    base_dir: &Path,
+    step: &WorkflowStep,
+    input: &str,
+    outputs: &[String],
+) -> (String, Result<String>) {
+    let agent_path = base_dir.join(&step.agent);
+
+    let agent_config: AgentConfig = match fs::read_to_string(&agent_path)
+        .with_context(|| format!("Failed to read agent config: {}", agent_path.display()))
+        .and_then(|content| {
+            AgentConfig::from_yaml(&content).with_context(|| {
+                format!("Failed to parse agent config from: {}", agent_path.display())
+            })
+        }) {
+        Ok(config) => config,
+        Err(e) => return (step.agent.clone(), Err(e)),
+    };
+
+    let agent_name = agent_config.name.clone();
+
+    let result = async {
+        if runtime.get_agent(&agent_name).is_none() {
+            runtime
+                .load_agent_from_config(agent_config)
+                .await
+                .context("Failed to load agent")?;
+        }
+
+        let step_input = render_step_input(&step.input, input, outputs)
+            .context("Failed to render step input")?;
+
+        runtime
+            .execute(&agent_name, &step_input)
+            .await
+            .context("Failed to execute agent")
+    }
+    .await;
+
+    (agent_name, result)
+}
+
+/// Format and output workflow result
+fn output_workflow_result(
+    workflow_name: &str,
+    success: bool,
+    steps: &[StepOutcome],
+    result: &str,
+    output: &str,
+) -> Result<()> {
+    let report = serde_json::json!({
+        "success": success,
+        "workflow": workflow_name,
+        "steps": steps,
+        "result": result
+    });
+
+    match output {
+        "json" => {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+        "yaml" => {
+            println!("{}", serde_yaml::to_string(&report)?);
+        }
+        _ => {
+            println!("Workflow: {}", workflow_name);
+            for (index, step) in steps.iter().enumerate() {
+                match &step.error {
+                    None => println!("  [{}] {} ({}): ok", index, step.name, step.agent),
+                    Some(error) => {
+                        println!("  [{}] {} ({}): failed - {}", index, step.name, step.agent, error)
+                    }
+                }
+            }
+            println!("Result: {}", result);
+        }
     }
-    println!("Output format: {}", output);
     Ok(())
 }
 
@@ -745,3 +1465,120 @@ async fn run_job(name_or_config: &str, input: Option<&str>, output: &str) -> Res
     println!("Output format: {}", output);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app_state() -> AppState {
+        let (_tx, rx) = channel::<String>();
+        AppState::new(
+            rx,
+            "openai:gpt-4".to_string(),
+            8_192,
+            vec!["shell".to_string()],
+        )
+    }
+
+    #[test]
+    fn test_record_usage_uses_reported_tokens() {
+        let mut state = app_state();
+        let metadata = ExecutionMetadata {
+            input_tokens: 1_234,
+            output_tokens: 56,
+            ..Default::default()
+        };
+
+        state.record_usage(&metadata);
+        state.record_usage(&metadata);
+
+        assert_eq!(state.input_tokens, 2_468);
+        assert_eq!(state.output_tokens, 112);
+    }
+
+    #[test]
+    fn test_overrides_replace_config_values() {
+        use clap::Parser;
+
+        let cli = crate::cli::Cli::try_parse_from([
+            "aofctl",
+            "run",
+            "agent",
+            "agent.yaml",
+            "--temperature",
+            "0.0",
+            "--max-iterations",
+            "3",
+        ])
+        .unwrap();
+        let crate::cli::Commands::Run(args) = cli.command else {
+            panic!("expected the run command");
+        };
+        let crate::cli::RunArgs {
+            model,
+            temperature,
+            max_iterations,
+            max_tokens,
+            ..
+        } = *args;
+        let overrides = AgentOverrides {
+            model,
+            temperature,
+            max_iterations,
+            max_tokens,
+        };
+
+        let mut config = AgentConfig::from_yaml(
+            "name: k8s-ops\nmodel: openai:gpt-4o\ntemperature: 0.7\nmax_tokens: 1024\n",
+        )
+        .unwrap();
+        overrides.apply(&mut config).unwrap();
+        assert_eq!(config.temperature, 0.0);
+        assert_eq!(config.max_iterations, 3);
+        assert_eq!(config.model, "openai:gpt-4o");
+        assert_eq!(config.max_tokens, Some(1024));
+
+        // The merged config must still be valid
+        let invalid = AgentOverrides {
+            temperature: Some(3.0),
+            ..Default::default()
+        };
+        let err = invalid.apply(&mut config).unwrap_err();
+        assert!(format!("{:#}", err).contains("temperature"));
+    }
+
+    #[test]
+    fn test_slash_command_parse() {
+        assert_eq!(SlashCommand::parse("/tools"), Some(SlashCommand::Tools));
+        assert_eq!(SlashCommand::parse(" /History "), Some(SlashCommand::History));
+        assert_eq!(SlashCommand::parse("/clear"), Some(SlashCommand::Clear));
+        assert_eq!(SlashCommand::parse("/tokens"), Some(SlashCommand::Tokens));
+        assert_eq!(SlashCommand::parse("/unknown"), None);
+        assert_eq!(SlashCommand::parse("tools"), None);
+    }
+
+    #[tokio::test]
+    async fn test_slash_command_dispatch() {
+        let runtime = Runtime::new();
+        let mut state = app_state();
+        state.input_tokens = 120;
+        state.output_tokens = 30;
+        state.message_count = 4;
+
+        let reply = run_slash_command(SlashCommand::Tokens, &runtime, "agent", &mut state).await;
+        assert!(reply.contains("input: 120 | output: 30 | total: 150 / 8192"));
+        assert_eq!(state.message_count, 4);
+
+        let reply = run_slash_command(SlashCommand::Tools, &runtime, "agent", &mut state).await;
+        assert_eq!(reply, "Available tools (1):\n• shell");
+
+        let reply = run_slash_command(SlashCommand::History, &runtime, "agent", &mut state).await;
+        assert_eq!(reply, "No conversation history");
+
+        let reply = run_slash_command(SlashCommand::Clear, &runtime, "agent", &mut state).await;
+        assert_eq!(reply, "Conversation cleared");
+        assert!(state.chat_history.is_empty());
+        assert_eq!(state.input_tokens + state.output_tokens, 0);
+        assert_eq!(state.message_count, 0);
+    }
+}
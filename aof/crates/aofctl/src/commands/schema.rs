@@ -0,0 +1,20 @@
+use crate::resources::ResourceType;
+use anyhow::Result;
+use aof_core::AgentConfig;
+
+/// Print the JSON Schema of a resource's YAML (verb-first: schema agent)
+///
+/// Point an editor's YAML language server at the output to validate and
+/// autocomplete config files.
+pub async fn execute(resource_type: &str) -> Result<()> {
+    let rt = ResourceType::from_str(resource_type)
+        .ok_or_else(|| anyhow::anyhow!("Unknown resource type: {}", resource_type))?;
+
+    let schema = match rt {
+        ResourceType::Agent => AgentConfig::json_schema(),
+        _ => anyhow::bail!("No schema available for resource type: {}", rt.name()),
+    };
+
+    println!("{}", serde_json::to_string_pretty(&schema)?);
+    Ok(())
+}
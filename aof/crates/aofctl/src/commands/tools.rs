@@ -1,5 +1,8 @@
 use anyhow::{Context, Result};
+use aof_core::{AgentConfig, ToolDefinition};
 use aof_mcp::McpClientBuilder;
+use aof_runtime::Runtime;
+use std::fs;
 use tracing::{info, warn};
 
 /// List available MCP tools from a server
@@ -64,3 +67,81 @@ pub async fn execute(server: &str, args: &[String]) -> Result<()> {
 
     Ok(())
 }
+
+/// List the tools an agent has access to, without running it
+///
+/// The agent is loaded the way `aofctl run` loads it, which starts its MCP
+/// servers. Tools of servers that can't be reached are reported as
+/// unavailable along with the error.
+pub async fn list(config: &str, output: &str) -> Result<()> {
+    let config_content = fs::read_to_string(config)
+        .with_context(|| format!("Failed to read config file: {}", config))?;
+    let agent_config = AgentConfig::from_yaml(&config_content)
+        .with_context(|| format!("Failed to parse agent config from: {}", config))?;
+
+    let mut runtime = Runtime::new();
+    let agent_name = runtime
+        .load_agent_from_config(agent_config)
+        .await
+        .context("Failed to load agent")?;
+    let executor = runtime
+        .get_agent(&agent_name)
+        .context("Agent missing after loading")?;
+    let unavailable = executor.unavailable_tools();
+
+    let tools = executor
+        .tool_executor()
+        .map(|tools| tools.list_tools())
+        .unwrap_or_default();
+
+    match output {
+        "json" => {
+            let tools: Vec<_> = tools
+                .iter()
+                .map(|tool| {
+                    serde_json::json!({
+                        "name": tool.name,
+                        "description": tool.description,
+                        "required": required_params(tool),
+                    })
+                })
+                .collect();
+            let report = serde_json::json!({
+                "agent": agent_name,
+                "tools": tools,
+                "unavailable": unavailable,
+            });
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+        _ => {
+            println!("Tools for agent {} ({}):", agent_name, tools.len());
+            for tool in &tools {
+                println!("\n  {}", tool.name);
+                println!("    {}", tool.description);
+                let required = required_params(tool);
+                if !required.is_empty() {
+                    println!("    Required: {}", required.join(", "));
+                }
+            }
+            for source in unavailable {
+                println!(
+                    "\nUnavailable ({}): {}\n  {}",
+                    source.source,
+                    source.tools.join(", "),
+                    source.error
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Parameters a tool's JSON schema marks as required
+fn required_params(tool: &ToolDefinition) -> Vec<&str> {
+    tool.parameters
+        .get("required")
+        .and_then(|required| required.as_array())
+        .map(|required| required.iter().filter_map(|p| p.as_str()).collect())
+        .unwrap_or_default()
+}
@@ -1,5 +1,5 @@
 use anyhow::{Context, Result};
-use aof_core::AgentConfig;
+use aof_core::{AgentConfig, Validate};
 use std::fs;
 use tracing::info;
 
@@ -12,28 +12,23 @@ pub async fn execute(file: &str) -> Result<()> {
         .with_context(|| format!("Failed to read config file: {}", file))?;
 
     // Parse as AgentConfig
-    let agent_config: AgentConfig = serde_yaml::from_str(&config_content)
+    let agent_config = AgentConfig::from_yaml(&config_content)
         .with_context(|| format!("Failed to parse agent config from: {}", file))?;
 
-    // Validate basic structure
-    if agent_config.name.is_empty() {
-        anyhow::bail!("Agent name cannot be empty");
-    }
-
-    if agent_config.model.is_empty() {
-        anyhow::bail!("Agent model cannot be empty");
-    }
-
-    if agent_config.max_iterations == 0 {
-        anyhow::bail!("max_iterations must be greater than 0");
-    }
-
-    if !(0.0..=2.0).contains(&agent_config.temperature) {
-        anyhow::bail!("temperature must be between 0.0 and 2.0");
+    // Run semantic checks and report every violation at once
+    if let Err(errors) = agent_config.validate() {
+        for error in &errors {
+            eprintln!("{}: {}", file, error);
+        }
+        anyhow::bail!(
+            "{} validation error(s) found in {}",
+            errors.len(),
+            file
+        );
     }
 
     // Print validation success
-    println!(" Configuration is valid");
+    println!(" Configuration is valid");
     println!("\nAgent Details:");
     println!("  Name: {}", agent_config.name);
     println!("  Model: {}", agent_config.model);
@@ -1,5 +1,4 @@
 use clap::Parser;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 mod cli;
 mod commands;
@@ -7,29 +6,52 @@ mod resources;
 
 use cli::Cli;
 
+/// Log filter used when `RUST_LOG` is unset
+const DEFAULT_LOG_FILTER: &str = "aofctl=info,aof_runtime=info";
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     use std::io::IsTerminal;
 
     // Parse CLI to detect interactive mode early
     let cli = Cli::parse();
-    let is_interactive = matches!(&cli.command, cli::Commands::Run { input, .. }
-        if input.is_none() && std::io::stdin().is_terminal());
+    let is_interactive = matches!(&cli.command, cli::Commands::Run(args)
+        if args.input.is_none() && std::io::stdin().is_terminal());
 
     // Initialize tracing only if NOT in interactive mode
     // Interactive mode will set up its own LogWriter-based layer in run_agent_interactive()
+    #[cfg(not(feature = "otel"))]
     if !is_interactive {
-        tracing_subscriber::registry()
-            .with(
-                tracing_subscriber::EnvFilter::try_from_default_env()
-                    .unwrap_or_else(|_| "aofctl=info,aof_runtime=info".into()),
-            )
-            .with(tracing_subscriber::fmt::layer())
-            .init();
+        aof_core::init_tracing(DEFAULT_LOG_FILTER);
     }
+    #[cfg(feature = "otel")]
+    let otel_provider = if is_interactive {
+        None
+    } else {
+        init_tracing_with_export()?
+    };
 
     // Execute command
-    cli.execute().await?;
+    let result = cli.execute().await;
+
+    // Flush spans still buffered for export
+    #[cfg(feature = "otel")]
+    if let Some(provider) = otel_provider {
+        let _ = provider.shutdown();
+    }
+
+    result
+}
+
+/// Initialize tracing, exporting spans over OTLP when
+/// `OTEL_EXPORTER_OTLP_ENDPOINT` is set
+#[cfg(feature = "otel")]
+fn init_tracing_with_export() -> anyhow::Result<Option<aof_runtime::telemetry::SdkTracerProvider>> {
+    if std::env::var_os("OTEL_EXPORTER_OTLP_ENDPOINT").is_none() {
+        aof_core::init_tracing(DEFAULT_LOG_FILTER);
+        return Ok(None);
+    }
 
-    Ok(())
+    let provider = aof_runtime::telemetry::init_tracing_with_otel(DEFAULT_LOG_FILTER, None)?;
+    Ok(Some(provider))
 }
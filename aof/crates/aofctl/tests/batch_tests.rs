@@ -0,0 +1,77 @@
+/// Batch run tests (one agent run per input file)
+mod mock_llm;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::path::Path;
+
+fn run_agent(endpoint: &str) -> Command {
+    let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/shell_agent.yaml");
+    let mut cmd = Command::cargo_bin("aofctl").unwrap();
+    cmd.env("OLLAMA_HOST", endpoint)
+        .env("RUST_LOG", "off")
+        .arg("run")
+        .arg("agent")
+        .arg(fixture);
+    cmd
+}
+
+#[test]
+fn test_batch_writes_one_result_per_input() {
+    let endpoint = mock_llm::start();
+    let inputs = tempfile::tempdir().unwrap();
+    let out = tempfile::tempdir().unwrap();
+    std::fs::write(inputs.path().join("disk.txt"), "disk full on node-1").unwrap();
+    std::fs::write(inputs.path().join("oom.txt"), "pod api-7 OOMKilled").unwrap();
+    std::fs::write(
+        inputs.path().join("alert.json"),
+        r#"{"alert": "HighLatency"}"#,
+    )
+    .unwrap();
+    std::fs::write(inputs.path().join("notes.md"), "not an input").unwrap();
+
+    run_agent(&endpoint)
+        .arg("--batch-dir")
+        .arg(inputs.path())
+        .arg("--out")
+        .arg(out.path())
+        .arg("--concurrency")
+        .arg("2")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("3 succeeded, 0 failed"));
+
+    let mut results: Vec<String> = std::fs::read_dir(out.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    results.sort();
+    assert_eq!(results, ["alert.json", "disk.json", "oom.json"]);
+
+    let report: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(out.path().join("oom.json")).unwrap()).unwrap();
+    assert_eq!(report["success"], true);
+    assert_eq!(report["result"], "echo: pod api-7 OOMKilled");
+    assert!(report["metadata"]["input_tokens"].as_u64().unwrap() > 0);
+}
+
+#[test]
+fn test_batch_conflicts_with_single_input() {
+    run_agent("http://127.0.0.1:9/v1")
+        .arg("--batch-dir")
+        .arg("inputs")
+        .arg("--out")
+        .arg("results")
+        .arg("--input")
+        .arg("hello")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+
+    run_agent("http://127.0.0.1:9/v1")
+        .arg("--batch-dir")
+        .arg("inputs")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--out"));
+}
@@ -159,3 +159,74 @@ fn test_delete_agent() {
         .success()
         .stdout(predicate::str::contains("Not yet implemented"));
 }
+
+#[test]
+fn test_validate_positional_config() {
+    let fixture = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/simple_agent.yaml");
+
+    let mut cmd = Command::cargo_bin("aofctl").unwrap();
+    cmd.arg("validate").arg(fixture);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Configuration is valid"));
+}
+
+#[test]
+fn test_validate_reports_all_violations() {
+    let fixture = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/invalid_agent.yaml");
+
+    let mut cmd = Command::cargo_bin("aofctl").unwrap();
+    cmd.arg("validate").arg(fixture);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid_agent.yaml: name: must not be empty"))
+        .stderr(predicate::str::contains("invalid_agent.yaml: model: must not be empty"))
+        .stderr(predicate::str::contains("temperature: must be between 0.0 and 2.0 (got 3)"))
+        .stderr(predicate::str::contains("max_iterations: must be at least 1"))
+        .stderr(predicate::str::contains("4 validation error(s)"));
+}
+
+#[test]
+fn test_validate_unknown_provider() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("agent.yaml");
+    std::fs::write(
+        &config,
+        "name: bad-provider\nmodel: acme:model-x\ntools:\n  - shell\n  - \"\"\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("aofctl").unwrap();
+    cmd.arg("validate").arg(&config);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("model: unknown provider 'acme'"))
+        .stderr(predicate::str::contains("tools[1]: tool name must not be empty"))
+        .stderr(predicate::str::contains("2 validation error(s)"));
+}
+
+#[test]
+fn test_schema_agent() {
+    let mut cmd = Command::cargo_bin("aofctl").unwrap();
+    cmd.arg("schema").arg("agent");
+
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let schema: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let temperature = &schema["$defs"]["FlatAgentConfig"]["properties"]["temperature"];
+    assert_eq!(temperature["maximum"], 2.0);
+}
+
+#[test]
+fn test_schema_unsupported_resource() {
+    let mut cmd = Command::cargo_bin("aofctl").unwrap();
+    cmd.arg("schema").arg("workflow");
+
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "No schema available for resource type: workflow",
+    ));
+}
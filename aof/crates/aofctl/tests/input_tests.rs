@@ -0,0 +1,90 @@
+/// Run input source tests (stdin and files)
+mod mock_llm;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::path::Path;
+
+/// Multi-line input, larger than a single pipe buffer
+fn long_input() -> String {
+    (0..2_000)
+        .map(|i| format!("2024-05-01T12:00:{:02}Z pod/api-{} OOMKilled\n", i % 60, i))
+        .collect()
+}
+
+fn run_agent(endpoint: &str) -> Command {
+    let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/shell_agent.yaml");
+    let mut cmd = Command::cargo_bin("aofctl").unwrap();
+    cmd.env("OLLAMA_HOST", endpoint)
+        .env("RUST_LOG", "off")
+        .arg("run")
+        .arg("agent")
+        .arg(fixture);
+    cmd
+}
+
+fn result(output: &[u8]) -> String {
+    let report: serde_json::Value = serde_json::from_slice(output).unwrap();
+    report["result"].as_str().unwrap().to_string()
+}
+
+#[test]
+fn test_run_reads_input_from_stdin() {
+    let endpoint = mock_llm::start();
+    let input = long_input();
+
+    let output = run_agent(&endpoint)
+        .arg("-")
+        .arg("--output")
+        .arg("json")
+        .write_stdin(input.clone())
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    assert_eq!(result(&output), format!("echo: {}", input));
+}
+
+#[test]
+fn test_run_reads_input_from_file() {
+    let endpoint = mock_llm::start();
+    let input = long_input();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("prompt.txt");
+    std::fs::write(&path, &input).unwrap();
+
+    let output = run_agent(&endpoint)
+        .arg("--input-file")
+        .arg(&path)
+        .arg("--output")
+        .arg("json")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    assert_eq!(result(&output), format!("echo: {}", input));
+}
+
+#[test]
+fn test_run_input_sources_are_exclusive() {
+    run_agent("http://127.0.0.1:9/v1")
+        .arg("-")
+        .arg("--input")
+        .arg("hello")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+
+    run_agent("http://127.0.0.1:9/v1")
+        .arg("--input")
+        .arg("hello")
+        .arg("--input-file")
+        .arg("prompt.txt")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
@@ -0,0 +1,205 @@
+/// Minimal LLM server for CLI tests
+///
+/// Serves both OpenAI-compatible chat completions and Ollama's native
+/// `/api/chat` API. Every request is answered with `echo: <last user message>`, which lets
+/// tests run real agents through the Ollama provider without a model.
+/// A user message of the form `run-tool <command>` is first answered with a
+/// `shell` tool call for `<command>`. Streaming requests get SSE (OpenAI) or
+/// newline-delimited JSON (Ollama) responses.
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+/// Start the server on a random local port and return its `/v1` base URL
+pub fn start() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind mock LLM server");
+    let addr = listener.local_addr().unwrap();
+
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            thread::spawn(move || handle(stream));
+        }
+    });
+
+    format!("http://{}/v1", addr)
+}
+
+fn handle(stream: TcpStream) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).unwrap_or(0) == 0 {
+        return;
+    }
+    let ollama = request_line.contains(" /api/chat ");
+
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).unwrap_or(0) == 0 {
+            return;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).unwrap();
+    let request: serde_json::Value = serde_json::from_slice(&body).unwrap_or_default();
+
+    let messages = request["messages"].as_array().cloned().unwrap_or_default();
+    let last_user_message = messages
+        .iter()
+        .rev()
+        .find(|m| m["role"] == "user")
+        .and_then(|m| m["content"].as_str())
+        .unwrap_or_default()
+        .to_string();
+    let has_tool_result = messages.iter().any(|m| m["role"] == "tool");
+
+    let tool_command = last_user_message
+        .strip_prefix("run-tool ")
+        .filter(|_| !has_tool_result);
+    let streaming = request["stream"] == true;
+
+    let (content_type, body) = if ollama {
+        ollama_response(tool_command, streaming, &last_user_message)
+    } else {
+        openai_response(tool_command, streaming, &last_user_message)
+    };
+
+    let mut stream = stream;
+    let _ = write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        content_type,
+        body.len(),
+        body
+    );
+}
+
+fn openai_response(
+    tool_command: Option<&str>,
+    streaming: bool,
+    last_user_message: &str,
+) -> (&'static str, String) {
+    match (tool_command, streaming) {
+        (Some(command), false) => (
+            "application/json",
+            completion(
+                serde_json::json!({"role": "assistant", "content": null, "tool_calls": [tool_call(command)]}),
+                "tool_calls",
+            ),
+        ),
+        (Some(command), true) => (
+            "text/event-stream",
+            sse(&[
+                serde_json::json!({"tool_calls": [tool_call(command)]}),
+                serde_json::json!({}),
+            ], "tool_calls"),
+        ),
+        (None, false) => (
+            "application/json",
+            completion(
+                serde_json::json!({"role": "assistant", "content": format!("echo: {}", last_user_message)}),
+                "stop",
+            ),
+        ),
+        (None, true) => (
+            "text/event-stream",
+            sse(&[
+                serde_json::json!({"content": "echo: "}),
+                serde_json::json!({"content": last_user_message}),
+                serde_json::json!({}),
+            ], "stop"),
+        ),
+    }
+}
+
+/// Ollama `/api/chat` response; streams are one JSON object per line
+fn ollama_response(
+    tool_command: Option<&str>,
+    streaming: bool,
+    last_user_message: &str,
+) -> (&'static str, String) {
+    let messages = match tool_command {
+        Some(command) => vec![serde_json::json!({
+            "role": "assistant",
+            "content": "",
+            "tool_calls": [{"id": "call_1", "function": {"name": "shell", "arguments": {"command": command}}}]
+        })],
+        None if streaming => vec![
+            serde_json::json!({"role": "assistant", "content": "echo: "}),
+            serde_json::json!({"role": "assistant", "content": last_user_message}),
+        ],
+        None => vec![serde_json::json!({
+            "role": "assistant",
+            "content": format!("echo: {}", last_user_message)
+        })],
+    };
+    let done = |message: serde_json::Value| {
+        serde_json::json!({
+            "message": message,
+            "done": true,
+            "done_reason": "stop",
+            "prompt_eval_count": 10,
+            "eval_count": 5
+        })
+    };
+
+    if !streaming {
+        return ("application/json", done(messages[0].clone()).to_string());
+    }
+
+    let mut body = String::new();
+    for message in messages {
+        body.push_str(&format!("{}\n", serde_json::json!({"message": message, "done": false})));
+    }
+    body.push_str(&format!(
+        "{}\n",
+        done(serde_json::json!({"role": "assistant", "content": ""}))
+    ));
+    ("application/x-ndjson", body)
+}
+
+fn tool_call(command: &str) -> serde_json::Value {
+    serde_json::json!({
+        "id": "call_1",
+        "type": "function",
+        "function": {
+            "name": "shell",
+            "arguments": serde_json::json!({"command": command}).to_string()
+        }
+    })
+}
+
+fn completion(message: serde_json::Value, finish_reason: &str) -> String {
+    serde_json::json!({
+        "id": "chatcmpl-test",
+        "object": "chat.completion",
+        "choices": [{"index": 0, "message": message, "finish_reason": finish_reason}],
+        "usage": {"prompt_tokens": 10, "completion_tokens": 5, "total_tokens": 15}
+    })
+    .to_string()
+}
+
+/// Server-sent events body; the last delta carries the finish reason
+fn sse(deltas: &[serde_json::Value], finish_reason: &str) -> String {
+    let mut body = String::new();
+    for (index, delta) in deltas.iter().enumerate() {
+        let finish = (index == deltas.len() - 1).then_some(finish_reason);
+        let chunk = serde_json::json!({
+            "choices": [{"index": 0, "delta": delta, "finish_reason": finish}]
+        });
+        body.push_str(&format!("data: {}\n\n", chunk));
+    }
+    body.push_str("data: [DONE]\n\n");
+    body
+}
@@ -0,0 +1,51 @@
+/// Agent tool listing tests (`aofctl tools list`)
+use assert_cmd::Command;
+use std::path::Path;
+
+/// Run `aofctl tools list` on the MCP agent fixture, starting `mcp_server`
+/// for its MCP tools
+fn list_tools(mcp_server: &str) -> serde_json::Value {
+    let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/mcp_agent.yaml");
+    let output = Command::cargo_bin("aofctl")
+        .unwrap()
+        .env("RUST_LOG", "off")
+        .env("AOF_MCP_SERVER", mcp_server)
+        .args(["tools", "list"])
+        .arg(fixture)
+        .args(["--output", "json"])
+        .timeout(std::time::Duration::from_secs(30))
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    serde_json::from_slice(&output).unwrap()
+}
+
+#[test]
+#[ignore = "needs SMOKE_TEST_MCP set to a built smoke-test-mcp binary"]
+fn test_tools_list_resolves_mcp_tools() {
+    let server = std::env::var("SMOKE_TEST_MCP")
+        .expect("SMOKE_TEST_MCP must name a smoke-test-mcp binary (cargo build -p smoke-test-mcp)");
+
+    let report = list_tools(&server);
+
+    assert_eq!(report["agent"], "mcp-agent");
+    let tools = report["tools"].as_array().unwrap();
+    let names: Vec<&str> = tools.iter().map(|t| t["name"].as_str().unwrap()).collect();
+    assert_eq!(names, ["echo", "add"]);
+    assert_eq!(tools[1]["description"], "Add two numbers together");
+    assert_eq!(tools[1]["required"], serde_json::json!(["a", "b"]));
+    assert_eq!(report["unavailable"], serde_json::json!([]));
+}
+
+#[test]
+fn test_tools_list_reports_unreachable_mcp_server() {
+    let report = list_tools("/nonexistent/smoke-test-mcp");
+
+    assert_eq!(report["tools"], serde_json::json!([]));
+    let unavailable = &report["unavailable"][0];
+    assert_eq!(unavailable["source"], "mcp");
+    assert_eq!(unavailable["tools"], serde_json::json!(["echo", "add"]));
+    assert!(unavailable["error"].as_str().unwrap().contains("MCP"));
+}
@@ -0,0 +1,78 @@
+/// Run transcript export tests
+mod mock_llm;
+
+use assert_cmd::Command;
+use std::path::Path;
+
+fn run_with_transcript(format: &str) -> String {
+    let endpoint = mock_llm::start();
+    let dir = tempfile::tempdir().unwrap();
+    let transcript = dir.path().join("transcript.out");
+    let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/shell_agent.yaml");
+
+    let mut cmd = Command::cargo_bin("aofctl").unwrap();
+    cmd.env("OLLAMA_HOST", &endpoint)
+        .env("RUST_LOG", "off")
+        .arg("run")
+        .arg("agent")
+        .arg(fixture)
+        .arg("--input")
+        .arg("run-tool echo transcript-test")
+        .arg("--transcript")
+        .arg(&transcript)
+        .arg("--transcript-format")
+        .arg(format);
+
+    cmd.assert().success();
+    std::fs::read_to_string(transcript).unwrap()
+}
+
+#[test]
+fn test_transcript_jsonl_records_event_sequence() {
+    let transcript = run_with_transcript("jsonl");
+
+    let events: Vec<serde_json::Value> = transcript
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let types: Vec<&str> = events.iter().map(|e| e["type"].as_str().unwrap()).collect();
+
+    assert_eq!(
+        types,
+        vec![
+            "iteration_start",
+            "tool_call_start",
+            "iteration_complete",
+            "tool_call_complete",
+            "iteration_start",
+            "text_delta",
+            "text_delta",
+            "iteration_complete",
+            "done",
+        ]
+    );
+
+    let tool_start = &events[1];
+    assert_eq!(tool_start["tool_name"], "shell");
+    assert_eq!(tool_start["arguments"]["command"], "echo transcript-test");
+
+    let tool_complete = &events[3];
+    assert_eq!(tool_complete["success"], true);
+    assert!(tool_complete["execution_time_ms"].is_u64());
+
+    assert_eq!(
+        events[8]["content"],
+        "echo: run-tool echo transcript-test"
+    );
+}
+
+#[test]
+fn test_transcript_json_and_text_formats() {
+    let json: Vec<serde_json::Value> = serde_json::from_str(&run_with_transcript("json")).unwrap();
+    assert_eq!(json.last().unwrap()["type"], "done");
+
+    let text = run_with_transcript("text");
+    assert!(text.contains("[tool_call_start] shell (call_1)"));
+    assert!(text.contains("[tool_call_complete] shell (call_1) ok in"));
+    assert!(text.contains("[done] 2 iterations"));
+}
@@ -0,0 +1,59 @@
+/// Workflow execution tests
+mod mock_llm;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::path::Path;
+
+fn workflow_fixture() -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/two_step_workflow.yaml")
+}
+
+#[test]
+fn test_run_two_step_workflow_pipes_outputs() {
+    let endpoint = mock_llm::start();
+
+    let mut cmd = Command::cargo_bin("aofctl").unwrap();
+    cmd.env("OLLAMA_HOST", &endpoint)
+        .env("RUST_LOG", "off")
+        .arg("run")
+        .arg("workflow")
+        .arg(workflow_fixture())
+        .arg("--input")
+        .arg("node-1 disk usage")
+        .arg("--output")
+        .arg("json");
+
+    let output = cmd.assert().success().get_output().stdout.clone();
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+    assert_eq!(report["success"], true);
+    assert_eq!(report["workflow"], "two-step-workflow");
+    assert_eq!(report["steps"][0]["agent"], "echo-agent");
+    assert_eq!(report["steps"][0]["output"], "echo: Collect: node-1 disk usage");
+    assert_eq!(report["steps"][1]["agent"], "summarizer-agent");
+    assert_eq!(
+        report["result"],
+        "echo: Summarize: echo: Collect: node-1 disk usage"
+    );
+}
+
+#[test]
+fn test_run_workflow_aborts_on_failed_step() {
+    // Nothing listens on the discard port, so every model call fails
+    let mut cmd = Command::cargo_bin("aofctl").unwrap();
+    cmd.env("OLLAMA_HOST", "http://127.0.0.1:9/v1")
+        .arg("run")
+        .arg("workflow")
+        .arg(workflow_fixture())
+        .arg("--input")
+        .arg("node-1")
+        .arg("--output")
+        .arg("text");
+
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("[0] collect (echo-agent): failed"))
+        .stdout(predicate::str::contains("summarize").not())
+        .stderr(predicate::str::contains("aborted at step 'collect'"));
+}
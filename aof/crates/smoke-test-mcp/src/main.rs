@@ -1,89 +1,245 @@
 //! Smoke Test MCP Server - Minimal MCP implementation for testing
 //!
-//! This server implements the MCP protocol and provides two simple tools:
+//! This server implements the MCP protocol and provides three simple tools:
 //! - echo: Returns the input string (for basic connectivity testing)
 //! - add: Adds two numbers together (for parameter passing testing)
+//! - get_system_info: Returns the OS and architecture
+//!
+//! Besides `initialize` and the tool methods it answers `ping`, accepts
+//! notifications without responding, and follows the `shutdown` request /
+//! `exit` notification handshake, so it can serve as a conformance target
+//! for client tests.
+//!
+//! Environment variables make it misbehave on demand, to exercise client
+//! timeouts, retries and reconnection (see `smoke-test-mcp --help`).
 //!
 //! Run with: cargo run --release --bin smoke-test-mcp
 //! Or from aof runtime: npx ./smoke-test-mcp (after building)
 
 use serde_json::{json, Value};
 use std::io::{self, BufRead, Write};
+use std::time::Duration;
 use tracing::{debug, info};
 
 const MCP_VERSION: &str = "2024-11-05";
 
+const HELP: &str = "\
+Smoke Test MCP Server - minimal MCP server over stdio for testing
+
+Usage: smoke-test-mcp
+
+Tools: echo, add, get_system_info
+
+Fault injection (environment variables):
+  SMOKE_DELAY_MS=<ms>       Delay every response by this many milliseconds
+  SMOKE_FAIL_RATE=<0.0-1.0> Answer this share of requests with a JSON-RPC
+                            internal error (-32603) instead of a result;
+                            initialize and shutdown always succeed
+  SMOKE_DROP_AFTER_N=<n>    Exit, closing stdout, after answering n requests
+";
+
+/// Misbehaviour requested through the environment
+#[derive(Debug, Default)]
+struct Faults {
+    /// Delay before every response (`SMOKE_DELAY_MS`)
+    delay: Option<Duration>,
+
+    /// Share of requests other than initialize and shutdown answered with
+    /// an error (`SMOKE_FAIL_RATE`)
+    fail_rate: f64,
+
+    /// Requests answered before the server exits (`SMOKE_DROP_AFTER_N`)
+    drop_after: Option<usize>,
+}
+
+impl Faults {
+    fn from_env() -> Result<Self, String> {
+        let delay = env_var::<u64>("SMOKE_DELAY_MS")?.map(Duration::from_millis);
+        let fail_rate = env_var::<f64>("SMOKE_FAIL_RATE")?.unwrap_or(0.0);
+        if !(0.0..=1.0).contains(&fail_rate) {
+            return Err(format!(
+                "SMOKE_FAIL_RATE must be between 0.0 and 1.0, got {}",
+                fail_rate
+            ));
+        }
+        let drop_after = env_var::<usize>("SMOKE_DROP_AFTER_N")?;
+
+        Ok(Self {
+            delay,
+            fail_rate,
+            drop_after,
+        })
+    }
+
+    /// Whether to answer a request for `method` with an injected error
+    ///
+    /// The lifecycle methods are exempt, so a client can still connect and
+    /// disconnect cleanly while its other requests fail.
+    fn inject_failure(&self, method: &str) -> bool {
+        !matches!(method, "initialize" | "shutdown")
+            && self.fail_rate > 0.0
+            && rand::random::<f64>() < self.fail_rate
+    }
+}
+
+/// Parse environment variable `name`, if set
+fn env_var<T: std::str::FromStr>(name: &str) -> Result<Option<T>, String> {
+    match std::env::var(name) {
+        Ok(value) => value
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("Invalid {}: {}", name, value)),
+        Err(_) => Ok(None),
+    }
+}
+
 #[tokio::main]
 async fn main() {
+    if std::env::args()
+        .skip(1)
+        .any(|arg| arg == "--help" || arg == "-h")
+    {
+        print!("{}", HELP);
+        return;
+    }
+
     // Initialize logging to stderr
     tracing_subscriber::fmt()
         .with_env_filter("info")
         .with_writer(io::stderr)
         .init();
 
+    let faults = match Faults::from_env() {
+        Ok(faults) => faults,
+        Err(e) => {
+            eprintln!("smoke-test-mcp: {}", e);
+            std::process::exit(2);
+        }
+    };
+
     info!("Starting Smoke Test MCP Server");
+    if faults.delay.is_some() || faults.fail_rate > 0.0 || faults.drop_after.is_some() {
+        info!("Injecting faults: {:?}", faults);
+    }
+    if faults.drop_after == Some(0) {
+        info!("Dropping the connection before any request");
+        return;
+    }
 
-    let mut id_counter = 1;
     let stdin = io::stdin();
     let reader = stdin.lock();
-    let mut lines = reader.lines();
+    let mut shutting_down = false;
+    let mut answered = 0;
+
+    // Main request loop, until `exit` or the client closes stdin
+    for line in reader.lines().map_while(Result::ok) {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        debug!("Received: {}", line);
 
-    // Main request loop
-    loop {
-        if let Some(Ok(line)) = lines.next() {
-            if line.is_empty() {
+        let request = match serde_json::from_str::<Value>(&line) {
+            Ok(request) => request,
+            Err(e) => {
+                respond(
+                    &faults,
+                    &error_response(&Value::Null, -32700, format!("Parse error: {}", e)),
+                );
                 continue;
             }
+        };
+        let id = request.get("id").cloned();
 
-            debug!("Received: {}", line);
-
-            match serde_json::from_str::<Value>(&line) {
-                Ok(request) => {
-                    if let Some(method) = request.get("method").and_then(|m| m.as_str()) {
-                        let req_id = request.get("id").and_then(|id| id.as_i64()).unwrap_or(id_counter as i64);
-                        id_counter += 1;
-
-                        let response = match method {
-                            "initialize" => handle_initialize(&request),
-                            "tools/list" => handle_list_tools(),
-                            "tools/call" => handle_tool_call(&request),
-                            _ => {
-                                json!({
-                                    "id": req_id,
-                                    "error": {
-                                        "code": -32601,
-                                        "message": format!("Method not found: {}", method)
-                                    }
-                                })
-                            }
-                        };
+        let Some(method) = request.get("method").and_then(|m| m.as_str()) else {
+            if let Some(id) = id {
+                respond(
+                    &faults,
+                    &error_response(&id, -32600, "Invalid request: missing method".to_string()),
+                );
+            }
+            continue;
+        };
 
-                        if let Ok(json_str) = serde_json::to_string(&response) {
-                            println!("{}", json_str);
-                            let _ = io::stdout().flush();
-                        }
-                    }
-                }
-                Err(e) => {
-                    let error_response = json!({
-                        "id": id_counter,
-                        "error": {
-                            "code": -32700,
-                            "message": format!("Parse error: {}", e)
-                        }
-                    });
-                    println!("{}", serde_json::to_string(&error_response).unwrap_or_default());
-                    let _ = io::stdout().flush();
-                    id_counter += 1;
-                }
+        if method == "exit" {
+            info!("Received exit, shutting down");
+            return;
+        }
+
+        // Notifications (e.g. notifications/initialized) carry no id and get no response
+        let Some(req_id) = id else {
+            debug!("Received notification: {}", method);
+            continue;
+        };
+
+        let response = match method {
+            _ if shutting_down => {
+                error_response(&req_id, -32600, "Server is shutting down".to_string())
+            }
+            _ if faults.inject_failure(method) => {
+                info!("Injecting failure for: {}", method);
+                error_response(&req_id, -32603, "Injected failure".to_string())
             }
+            "initialize" => handle_initialize(&request),
+            "ping" => result_response(&req_id, json!({})),
+            "tools/list" => handle_list_tools(&req_id),
+            "tools/call" => handle_tool_call(&request),
+            "shutdown" => {
+                info!("Received shutdown, waiting for exit");
+                shutting_down = true;
+                result_response(&req_id, Value::Null)
+            }
+            _ => error_response(&req_id, -32601, format!("Method not found: {}", method)),
+        };
+        respond(&faults, &response);
+
+        answered += 1;
+        if faults.drop_after == Some(answered) {
+            info!("Dropping the connection after {} requests", answered);
+            return;
         }
     }
+
+    info!("Client disconnected, shutting down");
+}
+
+/// Write a message to stdout, one per line, after any injected delay
+fn respond(faults: &Faults, message: &Value) {
+    if let Some(delay) = faults.delay {
+        std::thread::sleep(delay);
+    }
+    if let Ok(json_str) = serde_json::to_string(message) {
+        println!("{}", json_str);
+        let _ = io::stdout().flush();
+    }
+}
+
+/// Successful response to request `req_id`
+fn result_response(req_id: &Value, result: Value) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": req_id,
+        "result": result
+    })
+}
+
+/// Error response to request `req_id`
+fn error_response(req_id: &Value, code: i64, message: String) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": req_id,
+        "error": {
+            "code": code,
+            "message": message
+        }
+    })
 }
 
 /// Handle initialize request
 fn handle_initialize(request: &Value) -> Value {
-    let req_id = request.get("id").and_then(|id| id.as_i64()).unwrap_or(0);
+    // Echo the id as sent; clients may use strings as well as numbers
+    let req_id = request.get("id").cloned().unwrap_or(Value::Null);
 
     info!("Received initialize request");
 
@@ -104,11 +260,12 @@ fn handle_initialize(request: &Value) -> Value {
 }
 
 /// List available tools
-fn handle_list_tools() -> Value {
+fn handle_list_tools(req_id: &Value) -> Value {
     info!("Listing available tools");
 
     json!({
         "jsonrpc": "2.0",
+        "id": req_id,
         "result": {
             "tools": [
                 {
@@ -158,7 +315,8 @@ fn handle_list_tools() -> Value {
 
 /// Handle tool call
 fn handle_tool_call(request: &Value) -> Value {
-    let req_id = request.get("id").and_then(|id| id.as_i64()).unwrap_or(0);
+    // Echo the id as sent; clients may use strings as well as numbers
+    let req_id = request.get("id").cloned().unwrap_or(Value::Null);
 
     let tool_name = request
         .get("params")
@@ -212,20 +370,9 @@ fn handle_tool_call(request: &Value) -> Value {
             })
         }
         _ => {
-            return json!({
-                "jsonrpc": "2.0",
-                "id": req_id,
-                "error": {
-                    "code": -32601,
-                    "message": format!("Tool not found: {}", tool_name)
-                }
-            });
+            return error_response(&req_id, -32601, format!("Tool not found: {}", tool_name));
         }
     };
 
-    json!({
-        "jsonrpc": "2.0",
-        "id": req_id,
-        "result": result
-    })
+    result_response(&req_id, result)
 }
@@ -0,0 +1,157 @@
+//! MCP protocol tests driving the server over stdio
+
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Running server with its stdio
+struct Server {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl Server {
+    fn start() -> Self {
+        Self::start_with_env(&[])
+    }
+
+    fn start_with_env(env: &[(&str, &str)]) -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_smoke-test-mcp"))
+            .envs(env.iter().copied())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+        let stdin = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        Self {
+            child,
+            stdin,
+            stdout,
+        }
+    }
+
+    fn send(&mut self, message: Value) {
+        writeln!(self.stdin, "{}", message).unwrap();
+        self.stdin.flush().unwrap();
+    }
+
+    /// Send a request and read the next line the server writes
+    fn request(&mut self, message: Value) -> Value {
+        self.send(message);
+        let mut line = String::new();
+        self.stdout.read_line(&mut line).unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+
+    /// Exit status, once the server has exited on its own
+    fn wait(&mut self) -> std::process::ExitStatus {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            if let Some(status) = self.child.try_wait().unwrap() {
+                return status;
+            }
+            if Instant::now() > deadline {
+                self.child.kill().unwrap();
+                panic!("server did not exit");
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+    }
+}
+
+#[test]
+fn test_lifecycle() {
+    let mut server = Server::start();
+
+    let initialize = server.request(json!({
+        "jsonrpc": "2.0",
+        "id": "init-1",
+        "method": "initialize",
+        "params": {
+            "protocolVersion": "2024-11-05",
+            "capabilities": {},
+            "clientInfo": {"name": "protocol-test", "version": "0.0.0"}
+        }
+    }));
+    assert_eq!(initialize["jsonrpc"], "2.0");
+    assert_eq!(initialize["id"], "init-1");
+    assert_eq!(initialize["result"]["serverInfo"]["name"], "smoke-test-mcp");
+
+    // Notifications get no response, so the next line answers the ping
+    server.send(json!({"jsonrpc": "2.0", "method": "notifications/initialized"}));
+    let ping = server.request(json!({"jsonrpc": "2.0", "id": 2, "method": "ping"}));
+    assert_eq!(ping, json!({"jsonrpc": "2.0", "id": 2, "result": {}}));
+
+    let list = server.request(json!({"jsonrpc": "2.0", "id": 3, "method": "tools/list"}));
+    assert_eq!(list["jsonrpc"], "2.0");
+    assert_eq!(list["id"], 3);
+    let names: Vec<&str> = list["result"]["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|tool| tool["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["echo", "add", "get_system_info"]);
+
+    let unknown = server.request(json!({"jsonrpc": "2.0", "id": 4, "method": "resources/list"}));
+    assert_eq!(unknown["jsonrpc"], "2.0");
+    assert_eq!(unknown["id"], 4);
+    assert_eq!(unknown["error"]["code"], -32601);
+
+    let shutdown = server.request(json!({"jsonrpc": "2.0", "id": 5, "method": "shutdown"}));
+    assert_eq!(shutdown, json!({"jsonrpc": "2.0", "id": 5, "result": null}));
+
+    // Requests after shutdown are refused until the exit notification
+    let late = server.request(json!({"jsonrpc": "2.0", "id": 6, "method": "ping"}));
+    assert_eq!(late["id"], 6);
+    assert_eq!(late["error"]["code"], -32600);
+
+    server.send(json!({"jsonrpc": "2.0", "method": "exit"}));
+    assert!(server.wait().success());
+}
+
+#[test]
+fn test_injected_failures() {
+    let mut server = Server::start_with_env(&[("SMOKE_FAIL_RATE", "1.0")]);
+
+    // Lifecycle requests are never failed
+    let init = server.request(json!({
+        "jsonrpc": "2.0",
+        "id": 0,
+        "method": "initialize",
+        "params": {"protocolVersion": "2024-11-05", "capabilities": {}}
+    }));
+    assert!(init["result"].is_object());
+
+    for id in 1..=3 {
+        let response = server.request(json!({"jsonrpc": "2.0", "id": id, "method": "ping"}));
+        assert_eq!(response["jsonrpc"], "2.0");
+        assert_eq!(response["id"], id);
+        assert_eq!(response["error"]["code"], -32603);
+    }
+
+    let shutdown = server.request(json!({"jsonrpc": "2.0", "id": 4, "method": "shutdown"}));
+    assert_eq!(shutdown, json!({"jsonrpc": "2.0", "id": 4, "result": null}));
+
+    server.send(json!({"jsonrpc": "2.0", "method": "exit"}));
+    assert!(server.wait().success());
+}
+
+#[test]
+fn test_drop_after_n_requests() {
+    let mut server = Server::start_with_env(&[("SMOKE_DROP_AFTER_N", "2")]);
+
+    for id in 1..=2 {
+        let response = server.request(json!({"jsonrpc": "2.0", "id": id, "method": "ping"}));
+        assert_eq!(response["result"], json!({}));
+    }
+
+    // Gone without waiting for another request, stdout closed
+    assert!(server.wait().success());
+    let mut line = String::new();
+    assert_eq!(server.stdout.read_line(&mut line).unwrap(), 0);
+}
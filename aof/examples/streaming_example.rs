@@ -124,6 +124,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         tool_id: "call_123".to_string(),
         success: true,
         execution_time_ms: 487,
+        attempts: 1,
         error: None,
     }).await?;
 
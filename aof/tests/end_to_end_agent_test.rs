@@ -154,12 +154,8 @@ async fn test_end_to_end_simple_query() {
         name: "integration-test-agent".to_string(),
         system_prompt: Some("You are a helpful AI assistant for testing.".to_string()),
         model: "test-model".to_string(),
-        tools: vec![],
-        memory: None,
-        max_iterations: 10,
-        temperature: 0.7,
         max_tokens: Some(2000),
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     let model = Box::new(IntegrationMockModel::new());
@@ -181,11 +177,8 @@ async fn test_end_to_end_with_tool_usage() {
         system_prompt: Some("You are a calculator assistant.".to_string()),
         model: "test-model".to_string(),
         tools: vec!["calculator".to_string()],
-        memory: None,
-        max_iterations: 10,
-        temperature: 0.7,
         max_tokens: Some(2000),
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     let model = Box::new(IntegrationMockModel::new());
@@ -208,12 +201,8 @@ async fn test_end_to_end_multi_turn_conversation() {
         name: "conversation-agent".to_string(),
         system_prompt: Some("You are a helpful assistant.".to_string()),
         model: "test-model".to_string(),
-        tools: vec![],
-        memory: None,
-        max_iterations: 10,
-        temperature: 0.7,
         max_tokens: Some(2000),
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     let model = Box::new(IntegrationMockModel::new());
@@ -241,12 +230,8 @@ async fn test_end_to_end_token_tracking() {
         name: "token-tracking-agent".to_string(),
         system_prompt: Some("You are a test assistant.".to_string()),
         model: "test-model".to_string(),
-        tools: vec![],
-        memory: None,
-        max_iterations: 10,
-        temperature: 0.7,
         max_tokens: Some(2000),
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     let model = Box::new(IntegrationMockModel::new());
@@ -177,12 +177,8 @@ async fn test_gui_execute_command() {
         name: "gui-agent".to_string(),
         system_prompt: Some("You are a GUI assistant.".to_string()),
         model: "gui-test".to_string(),
-        tools: vec![],
-        memory: None,
-        max_iterations: 10,
-        temperature: 0.7,
         max_tokens: Some(2000),
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     let model = Box::new(GuiMockModel::new());
@@ -206,14 +202,8 @@ async fn test_gui_execute_command() {
 async fn test_gui_status_command() {
     let config = AgentConfig {
         name: "status-agent".to_string(),
-        system_prompt: None,
         model: "gui-test".to_string(),
-        tools: vec![],
-        memory: None,
-        max_iterations: 10,
-        temperature: 0.7,
-        max_tokens: None,
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     let model = Box::new(GuiMockModel::new());
@@ -235,14 +225,8 @@ async fn test_gui_status_command() {
 async fn test_gui_list_agents_command() {
     let config = AgentConfig {
         name: "list-agent".to_string(),
-        system_prompt: None,
         model: "gui-test".to_string(),
-        tools: vec![],
-        memory: None,
-        max_iterations: 10,
-        temperature: 0.7,
-        max_tokens: None,
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     let model = Box::new(GuiMockModel::new());
@@ -261,14 +245,8 @@ async fn test_gui_list_agents_command() {
 async fn test_gui_get_history_command() {
     let config = AgentConfig {
         name: "history-agent".to_string(),
-        system_prompt: None,
         model: "gui-test".to_string(),
-        tools: vec![],
-        memory: None,
-        max_iterations: 10,
-        temperature: 0.7,
-        max_tokens: None,
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     let model = Box::new(GuiMockModel::new());
@@ -206,11 +206,8 @@ async fn test_multi_tool_parallel_execution() {
         system_prompt: Some("You are a parallel execution test agent.".to_string()),
         model: "parallel-test".to_string(),
         tools: vec!["fast_tool".to_string(), "medium_tool".to_string(), "slow_tool".to_string()],
-        memory: None,
-        max_iterations: 10,
-        temperature: 0.7,
         max_tokens: Some(2000),
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     let model = Box::new(ParallelToolMockModel::new());
@@ -246,14 +243,9 @@ async fn test_multi_tool_parallel_execution() {
 async fn test_tool_execution_ordering() {
     let config = AgentConfig {
         name: "order-test-agent".to_string(),
-        system_prompt: None,
         model: "parallel-test".to_string(),
         tools: vec!["fast_tool".to_string(), "medium_tool".to_string(), "slow_tool".to_string()],
-        memory: None,
-        max_iterations: 10,
-        temperature: 0.7,
-        max_tokens: None,
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     let model = Box::new(ParallelToolMockModel::new());
@@ -284,14 +276,9 @@ async fn test_tool_execution_ordering() {
 async fn test_parallel_tool_result_preservation() {
     let config = AgentConfig {
         name: "result-test-agent".to_string(),
-        system_prompt: None,
         model: "parallel-test".to_string(),
         tools: vec!["fast_tool".to_string(), "slow_tool".to_string()],
-        memory: None,
-        max_iterations: 10,
-        temperature: 0.7,
-        max_tokens: None,
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     let model = Box::new(ParallelToolMockModel::new());
@@ -323,14 +310,9 @@ async fn test_parallel_execution_with_many_tools() {
     // Test with maximum parallel tools (10)
     let config = AgentConfig {
         name: "many-tools-agent".to_string(),
-        system_prompt: None,
         model: "parallel-test".to_string(),
         tools: vec!["fast_tool".to_string()],
-        memory: None,
-        max_iterations: 10,
-        temperature: 0.7,
-        max_tokens: None,
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     // Create a custom model that requests exactly 10 tools
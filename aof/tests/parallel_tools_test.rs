@@ -153,14 +153,9 @@ async fn test_parallel_tool_execution_performance() {
 
     let config = AgentConfig {
         name: "parallel-test".to_string(),
-        system_prompt: None,
         model: "test".to_string(),
-        tools: vec![],
-        memory: None,
         max_iterations: 2,
-        temperature: 0.7,
-        max_tokens: None,
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     let agent = AgentExecutor::new(config, model, Some(executor.clone()), None);
@@ -227,14 +222,9 @@ async fn test_single_tool_no_parallel_overhead() {
 
     let config = AgentConfig {
         name: "single-tool-test".to_string(),
-        system_prompt: None,
         model: "test".to_string(),
-        tools: vec![],
-        memory: None,
         max_iterations: 2,
-        temperature: 0.7,
-        max_tokens: None,
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     let agent = AgentExecutor::new(config, model, Some(executor), None);
@@ -292,14 +282,9 @@ async fn test_parallel_tool_with_failures() {
 
     let config = AgentConfig {
         name: "failing-tools-test".to_string(),
-        system_prompt: None,
         model: "test".to_string(),
-        tools: vec![],
-        memory: None,
         max_iterations: 2,
-        temperature: 0.7,
-        max_tokens: None,
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     let agent = AgentExecutor::new(config, model, Some(executor), None);
@@ -130,12 +130,8 @@ async fn test_platform_message_to_response_flow() {
         name: "platform-bot".to_string(),
         system_prompt: Some("You are a helpful platform bot.".to_string()),
         model: "platform-test".to_string(),
-        tools: vec![],
-        memory: None,
-        max_iterations: 10,
-        temperature: 0.7,
         max_tokens: Some(1000),
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     let model = Box::new(PlatformMockModel::new());
@@ -164,12 +160,8 @@ async fn test_platform_command_help() {
         name: "command-bot".to_string(),
         system_prompt: Some("You are a command bot.".to_string()),
         model: "platform-test".to_string(),
-        tools: vec![],
-        memory: None,
-        max_iterations: 10,
-        temperature: 0.7,
         max_tokens: Some(1000),
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     let model = Box::new(PlatformMockModel::new());
@@ -195,12 +187,8 @@ async fn test_platform_command_status() {
         name: "status-bot".to_string(),
         system_prompt: Some("You are a status bot.".to_string()),
         model: "platform-test".to_string(),
-        tools: vec![],
-        memory: None,
-        max_iterations: 10,
-        temperature: 0.7,
         max_tokens: Some(1000),
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     let model = Box::new(PlatformMockModel::new());
@@ -224,14 +212,8 @@ async fn test_platform_command_status() {
 async fn test_platform_metadata_preservation() {
     let config = AgentConfig {
         name: "metadata-bot".to_string(),
-        system_prompt: None,
         model: "platform-test".to_string(),
-        tools: vec![],
-        memory: None,
-        max_iterations: 10,
-        temperature: 0.7,
-        max_tokens: None,
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     let model = Box::new(PlatformMockModel::new());
@@ -271,12 +253,8 @@ async fn test_platform_multi_message_flow() {
         name: "multi-message-bot".to_string(),
         system_prompt: Some("You are a conversational bot.".to_string()),
         model: "platform-test".to_string(),
-        tools: vec![],
-        memory: None,
-        max_iterations: 10,
-        temperature: 0.7,
         max_tokens: Some(1000),
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     let model = Box::new(PlatformMockModel::new());
@@ -91,12 +91,8 @@ async fn test_streaming_basic_flow() {
         name: "streaming-agent".to_string(),
         system_prompt: Some("You are a test assistant".to_string()),
         model: "streaming-test".to_string(),
-        tools: vec![],
-        memory: None,
-        max_iterations: 10,
-        temperature: 0.7,
         max_tokens: Some(1000),
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     let model = Box::new(StreamingMockModel::new());
@@ -145,14 +141,8 @@ async fn test_streaming_basic_flow() {
 async fn test_streaming_text_deltas() {
     let config = AgentConfig {
         name: "delta-test-agent".to_string(),
-        system_prompt: None,
         model: "streaming-test".to_string(),
-        tools: vec![],
-        memory: None,
-        max_iterations: 10,
-        temperature: 0.7,
-        max_tokens: None,
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     let model = Box::new(StreamingMockModel::new());
@@ -185,14 +175,8 @@ async fn test_streaming_text_deltas() {
 async fn test_streaming_done_event() {
     let config = AgentConfig {
         name: "done-test-agent".to_string(),
-        system_prompt: None,
         model: "streaming-test".to_string(),
-        tools: vec![],
-        memory: None,
-        max_iterations: 10,
-        temperature: 0.7,
-        max_tokens: None,
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     let model = Box::new(StreamingMockModel::new());
@@ -242,14 +226,8 @@ async fn test_streaming_done_event() {
 async fn test_streaming_iteration_events() {
     let config = AgentConfig {
         name: "iteration-test-agent".to_string(),
-        system_prompt: None,
         model: "streaming-test".to_string(),
-        tools: vec![],
-        memory: None,
-        max_iterations: 10,
-        temperature: 0.7,
-        max_tokens: None,
-        extra: HashMap::new(),
+        ..Default::default()
     };
 
     let model = Box::new(StreamingMockModel::new());